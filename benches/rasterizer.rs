@@ -0,0 +1,102 @@
+//! Rasterizer benchmarks
+//!
+//! Renders a procedurally built stress level (see `create_stress_level`) from a
+//! fixed camera and reports triangles/second for each shading path at 320x240
+//! and 640x480, plus ray casts against a 144-room level with and without the
+//! spatial index. Run with `cargo bench`.
+
+use bonnie_engine::{rasterizer, world};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rasterizer::{
+    render_mesh, Camera, Color, Face, Framebuffer, RasterSettings, ShadingMode, Texture, Vertex,
+    HEIGHT, HEIGHT_HI, WIDTH, WIDTH_HI,
+};
+use world::{create_stress_level, raycast_max, Level, LevelBuilder, RoomBuilder, SpatialIndex};
+
+/// Sectors per side of the stress level
+const STRESS_SIZE: usize = 24;
+
+/// 64x64 checkerboard so the textured path does real sampling
+fn checker_texture() -> Texture {
+    let mut tex = Texture::new(64, 64);
+    for y in 0..64 {
+        for x in 0..64 {
+            let on = ((x / 8) + (y / 8)) % 2 == 0;
+            tex.pixels[y * 64 + x] = if on { Color::new(200, 180, 150) } else { Color::new(90, 70, 60) };
+        }
+    }
+    tex
+}
+
+/// Stress level geometry, with or without texture ids
+fn stress_mesh(textured: bool) -> (Vec<Vertex>, Vec<Face>) {
+    let level = create_stress_level(STRESS_SIZE);
+    level.rooms[0].to_render_data_with_textures(|_| if textured { Some(0) } else { None })
+}
+
+fn bench_camera() -> Camera {
+    let mut camera = Camera::new();
+    let half = STRESS_SIZE as f32 * 1024.0 * 0.5;
+    camera.position = rasterizer::Vec3::new(half * 0.4, 1536.0, half * 0.4);
+    camera.rotation_x = 0.25;
+    camera.rotation_y = 0.78;
+    camera.update_basis();
+    camera
+}
+
+fn bench_paths(c: &mut Criterion) {
+    let textures = vec![checker_texture()];
+    let camera = bench_camera();
+
+    let paths = [
+        ("textured", true, ShadingMode::None),
+        ("gouraud", false, ShadingMode::Gouraud),
+        ("flat", false, ShadingMode::Flat),
+    ];
+
+    for (name, textured, shading) in paths {
+        let (vertices, faces) = stress_mesh(textured);
+        let settings = RasterSettings { shading, ..RasterSettings::default() };
+
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Elements(faces.len() as u64));
+
+        for (w, h) in [(WIDTH, HEIGHT), (WIDTH_HI, HEIGHT_HI)] {
+            let mut fb = Framebuffer::new(w, h);
+            group.bench_with_input(BenchmarkId::from_parameter(format!("{}x{}", w, h)), &(w, h), |b, _| {
+                b.iter(|| {
+                    fb.clear(Color::new(0, 0, 0));
+                    render_mesh(&mut fb, &vertices, &faces, &textures, &camera, &settings);
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+/// 12x12 grid of 4x4 rooms with a gap between neighbours
+fn many_rooms_level() -> Level {
+    let mut builder = LevelBuilder::new();
+    for i in 0..144 {
+        let (x, z) = ((i % 12) as f32 * 5120.0, (i / 12) as f32 * 5120.0);
+        let room = RoomBuilder::new(4, 4).at(rasterizer::Vec3::new(x, 0.0, z)).fill(0.0, 2048.0);
+        builder = builder.room(room);
+    }
+    builder.build()
+}
+
+fn bench_raycast(c: &mut Criterion) {
+    let level = many_rooms_level();
+    let index = SpatialIndex::build(&level);
+    // A shallow ray across most of the level
+    let origin = rasterizer::Vec3::new(100.0, 1024.0, 100.0);
+    let dir = rasterizer::Vec3::new(1.0, -0.0025, 0.9);
+
+    let mut group = c.benchmark_group("raycast");
+    group.bench_function("linear", |b| b.iter(|| raycast_max(&level, origin, dir, f32::MAX)));
+    group.bench_function("indexed", |b| b.iter(|| index.raycast(&level, origin, dir, f32::MAX)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_paths, bench_raycast);
+criterion_main!(benches);
@@ -2,6 +2,8 @@
 //!
 //! Scans assets/textures/ and assets/levels/ and creates manifests
 //! listing all files, since WASM can't enumerate directories at runtime.
+//! The levels are also compiled into the web build (`embedded_levels.rs`
+//! in OUT_DIR), so its level browser has examples to open.
 
 use std::fs;
 use std::io::Write;
@@ -68,12 +70,14 @@ fn generate_texture_manifest() {
     file.write_all(manifest.as_bytes()).unwrap();
 }
 
-/// Generate manifest for levels (for WASM builds)
+/// Generate manifest for levels, and the list of levels to embed (for WASM builds)
 fn generate_levels_manifest() {
     let levels_dir = Path::new("assets/levels");
     let manifest_path = Path::new("assets/levels/manifest.txt");
 
     let mut manifest = String::new();
+    let mut embedded = String::from("/// Example levels compiled into the build: (name, RON source)\n");
+    embedded.push_str("const EMBEDDED_LEVELS: &[(&str, &str)] = &[\n");
 
     if levels_dir.exists() {
         let mut levels: Vec<_> = fs::read_dir(levels_dir)
@@ -94,10 +98,18 @@ fn generate_levels_manifest() {
         for level_entry in levels {
             let level_name = level_entry.file_name().to_string_lossy().to_string();
             manifest.push_str(&format!("{}\n", level_name));
+
+            let stem = level_name.strip_suffix(".ron").unwrap_or(&level_name).to_string();
+            let source = fs::canonicalize(level_entry.path()).unwrap();
+            embedded.push_str(&format!("    ({:?}, include_str!({:?})),\n", stem, source));
         }
     }
+    embedded.push_str("];\n");
 
     // Write manifest file
     let mut file = fs::File::create(manifest_path).unwrap();
     file.write_all(manifest.as_bytes()).unwrap();
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("embedded_levels.rs"), embedded).unwrap();
 }
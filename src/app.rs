@@ -1,23 +1,37 @@
 //! Application state and tool management
 //!
-//! Fixed set of tools, each with its own persistent state.
+//! Fixed set of tools, each with its own persistent state, plus any
+//! registered tool plugins (see `plugin`).
 //! Switch between tools via the tab bar - all tools stay alive in background.
+//! A tool that panics is shown as crashed until restarted (see `crash`).
+//! App-wide shortcuts (undo, redo, save, open, new) go to the active tool's
+//! handler (`route_shortcut`).
+//! Leaving unsaved changes behind (switching tabs, replacing the level,
+//! quitting) asks first: Save, Discard or Cancel.
 
-use crate::editor::{EditorState, EditorLayout, ExampleBrowser};
+use crate::console::ConsoleState;
+use crate::editor::{handle_editor_shortcut, CompareDialog, EditorAction, EditorState, EditorLayout, ExampleBrowser, ProjectPanel, WorldMapView};
+use crate::i18n::{tr, trf};
 use crate::landing::LandingState;
-use crate::modeler::{ModelerState, ModelerLayout};
-use crate::tracker::TrackerState;
-use crate::world::Level;
+use crate::modeler::{handle_modeler_shortcut, ModelerState, ModelerLayout};
+use crate::plugin::{register_plugins, AppServices, PluginRegistry};
+use crate::settings::Settings;
+use crate::tracker::{handle_tracker_shortcut, TrackerState};
+use crate::ui::{ButtonStyle, Dialog, DialogId, ErrorDialog, Shortcut, UiContext};
+use crate::world::{create_empty_level, load_level, Level};
 use macroquad::prelude::Font;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
-/// The available tools (fixed set, one tab each)
+/// The available tools (one tab each): the built-in set, then plugins
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tool {
-    Home = 0,
-    WorldEditor = 1,
-    Modeler = 2,
-    Tracker = 3,
+    Home,
+    WorldEditor,
+    Modeler,
+    Tracker,
+    /// Registered plugin, by index in `AppState::plugins`
+    Plugin(usize),
 }
 
 impl Tool {
@@ -34,6 +48,7 @@ impl Tool {
             Tool::WorldEditor => "World",
             Tool::Modeler => "Assets",
             Tool::Tracker => "Music",
+            Tool::Plugin(_) => "Plugin",
         }
     }
 
@@ -49,6 +64,40 @@ impl Tool {
     pub fn from_index(i: usize) -> Option<Tool> {
         Tool::ALL.get(i).copied()
     }
+
+    /// Translated tab name
+    pub fn tab_name(&self) -> String {
+        match self {
+            Tool::Home => tr("tabs.home"),
+            Tool::WorldEditor => tr("tabs.world"),
+            Tool::Modeler => tr("tabs.assets"),
+            Tool::Tracker => tr("tabs.music"),
+            Tool::Plugin(_) => self.label().to_string(),
+        }
+    }
+}
+
+/// Dialog asking what to do with unsaved changes
+pub const UNSAVED_DIALOG: DialogId = "app.unsaved";
+
+/// Something that would leave unsaved changes behind, held back while the
+/// unsaved changes dialog is up
+#[derive(Debug, Clone, PartialEq)]
+pub enum Guarded {
+    /// Switch tabs (Discard reverts the tool being left)
+    SwitchTool(Tool),
+    /// Replace the World Editor's level (New, Open, the level browser)
+    Editor(EditorAction),
+    /// Close the app (desktop)
+    Quit,
+}
+
+/// Answer to the unsaved changes dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsavedChoice {
+    Save,
+    Discard,
+    Cancel,
 }
 
 /// State for the World Editor tool
@@ -56,6 +105,15 @@ pub struct WorldEditorState {
     pub editor_state: EditorState,
     pub editor_layout: EditorLayout,
     pub example_browser: ExampleBrowser,
+    pub compare: CompareDialog,
+    pub project: ProjectPanel,
+    pub world_map: WorldMapView,
+    /// LAN co-op session, when started with `--host` or `--join`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub collab: Option<crate::editor::CollabSession>,
+    /// The 3D viewport's own window, while it is detached
+    #[cfg(not(target_arch = "wasm32"))]
+    pub viewport_window: Option<crate::editor::ViewportWindow>,
 }
 
 /// State for the Modeler tool
@@ -83,6 +141,24 @@ pub struct AppState {
 
     /// Icon font (Lucide)
     pub icon_font: Option<Font>,
+
+    /// Tools that panicked, with the panic message
+    pub crashed: HashMap<Tool, String>,
+
+    /// Errors waiting to be shown to the user
+    pub errors: ErrorDialog,
+
+    /// Log console (F12)
+    pub console: ConsoleState,
+
+    /// User preferences (language, ...)
+    pub settings: Settings,
+
+    /// Tool plugins, with tabs after the built-in tools
+    pub plugins: PluginRegistry,
+
+    /// Action waiting on the unsaved changes dialog
+    pub pending: Option<Guarded>,
 }
 
 impl AppState {
@@ -94,13 +170,20 @@ impl AppState {
             EditorState::new(level)
         };
 
-        Self {
+        let mut app = Self {
             active_tool: Tool::Home,
             landing: LandingState::new(),
             world_editor: WorldEditorState {
                 editor_state,
                 editor_layout: EditorLayout::new(),
                 example_browser: ExampleBrowser::default(),
+                compare: CompareDialog::default(),
+                project: ProjectPanel::default(),
+                world_map: WorldMapView::default(),
+                #[cfg(not(target_arch = "wasm32"))]
+                collab: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                viewport_window: None,
             },
             modeler: ModelerToolState {
                 modeler_state: ModelerState::new(),
@@ -108,7 +191,37 @@ impl AppState {
             },
             tracker: TrackerState::new(),
             icon_font,
-        }
+            crashed: HashMap::new(),
+            errors: ErrorDialog::new(),
+            console: ConsoleState::new(),
+            settings: Settings::load(),
+            plugins: PluginRegistry::new(),
+            pending: None,
+        };
+        register_plugins(&mut app.plugins);
+        app.apply_resolution();
+        app
+    }
+
+    /// Set both 3D tools to the saved viewport resolution
+    fn apply_resolution(&mut self) {
+        self.world_editor.editor_state.raster_settings.resolution = self.settings.resolution;
+        self.modeler.modeler_state.raster_settings.resolution = self.settings.resolution;
+    }
+
+    /// Share a resolution picked in either 3D tool with the other one and
+    /// save it; call once per frame
+    pub fn sync_resolution(&mut self) -> Result<(), String> {
+        let world = self.world_editor.editor_state.raster_settings.resolution;
+        let model = self.modeler.modeler_state.raster_settings.resolution;
+        let picked = match (world != self.settings.resolution, model != self.settings.resolution) {
+            (true, _) => world,
+            (_, true) => model,
+            _ => return Ok(()),
+        };
+        self.settings.resolution = picked;
+        self.apply_resolution();
+        self.settings.save()
     }
 
     /// Switch to a different tool
@@ -116,8 +229,190 @@ impl AppState {
         self.active_tool = tool;
     }
 
+    /// Replace a tool's state with a fresh one and clear its crash
+    pub fn restart_tool(&mut self, tool: Tool) {
+        match tool {
+            Tool::Home => self.landing = LandingState::new(),
+            Tool::WorldEditor => {
+                // Texture packs are expensive to reload (and async on WASM)
+                let texture_packs = std::mem::take(&mut self.world_editor.editor_state.texture_packs);
+                let mut editor_state = EditorState::new(create_empty_level());
+                editor_state.texture_packs = texture_packs;
+                self.world_editor = WorldEditorState {
+                    editor_state,
+                    editor_layout: EditorLayout::new(),
+                    example_browser: ExampleBrowser::default(),
+                    compare: CompareDialog::default(),
+                    project: ProjectPanel::default(),
+                    world_map: WorldMapView::default(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    collab: None,
+                    #[cfg(not(target_arch = "wasm32"))]
+                    viewport_window: None,
+                };
+            }
+            Tool::Modeler => {
+                self.modeler = ModelerToolState {
+                    modeler_state: ModelerState::new(),
+                    modeler_layout: ModelerLayout::new(),
+                };
+            }
+            Tool::Tracker => self.tracker = TrackerState::new(),
+            Tool::Plugin(i) => {
+                if let Some(plugin) = self.plugins.get_mut(i) {
+                    plugin.reset();
+                }
+            }
+        }
+        self.apply_resolution();
+        self.crashed.remove(&tool);
+    }
+
+    /// Whether `tool` has changes that aren't saved to disk
+    pub fn has_unsaved_changes(&self, tool: Tool) -> bool {
+        match tool {
+            Tool::WorldEditor => self.world_editor.editor_state.dirty,
+            Tool::Modeler => self.modeler.modeler_state.dirty,
+            Tool::Tracker => self.tracker.has_unsaved_songs(),
+            Tool::Home | Tool::Plugin(_) => false,
+        }
+    }
+
+    /// Whether `tool` can save its changes (models and songs are only saved
+    /// by the desktop app)
+    pub fn can_save(&self, tool: Tool) -> bool {
+        tool == Tool::WorldEditor || (matches!(tool, Tool::Modeler | Tool::Tracker) && cfg!(not(target_arch = "wasm32")))
+    }
+
+    /// Tools whose unsaved changes `action` would leave behind. Tabs stay
+    /// alive, so switching away from a tool that can't save keeps its changes
+    /// without asking (it would only offer to throw them away).
+    pub fn unsaved_tools(&self, action: &Guarded) -> Vec<Tool> {
+        let tools = match action {
+            Guarded::SwitchTool(tool) if *tool != self.active_tool && self.can_save(self.active_tool) => {
+                vec![self.active_tool]
+            }
+            Guarded::SwitchTool(_) => Vec::new(),
+            Guarded::Editor(_) => vec![Tool::WorldEditor],
+            Guarded::Quit => Tool::ALL.to_vec(),
+        };
+        tools.into_iter().filter(|&t| self.has_unsaved_changes(t)).collect()
+    }
+
+    /// Ask what to do with the unsaved changes `action` would leave behind.
+    /// Returns false when there are none and the caller can go ahead.
+    pub fn ask_unsaved(&mut self, ctx: &mut UiContext, action: Guarded) -> bool {
+        let tools = self.unsaved_tools(&action);
+        if tools.is_empty() {
+            return false;
+        }
+        let names: Vec<String> = tools.iter().map(|t| t.tab_name()).collect();
+        let message = match action {
+            Guarded::Quit => trf("unsaved.quit_message", &[&names.join(", ")]),
+            _ => trf("unsaved.message", &[&names.join(", ")]),
+        };
+        let mut dialog = Dialog::new(&tr("unsaved.title"), &message);
+        if tools.iter().all(|&t| self.can_save(t)) {
+            dialog = dialog.button(&tr("common.save"), ButtonStyle::Accent);
+        }
+        dialog = dialog
+            .button(&tr("unsaved.discard"), ButtonStyle::Danger)
+            .button(&tr("common.cancel"), ButtonStyle::Plain);
+        ctx.open_dialog(UNSAVED_DIALOG, dialog);
+        self.pending = Some(action);
+        true
+    }
+
+    /// Which choice button `button` of the dialog `ask_unsaved` opened for
+    /// `action` was (Save is left out when a tool can't save)
+    pub fn unsaved_choice(&self, action: &Guarded, button: usize) -> UnsavedChoice {
+        let can_save = self.unsaved_tools(action).iter().all(|&t| self.can_save(t));
+        let choices: &[UnsavedChoice] = if can_save {
+            &[UnsavedChoice::Save, UnsavedChoice::Discard, UnsavedChoice::Cancel]
+        } else {
+            &[UnsavedChoice::Discard, UnsavedChoice::Cancel]
+        };
+        choices.get(button).copied().unwrap_or(UnsavedChoice::Cancel)
+    }
+
+    /// Hold back an editor action that would replace a level with unsaved
+    /// changes (it comes back through the unsaved changes dialog)
+    pub fn guard_editor_action(&mut self, ctx: &mut UiContext, action: EditorAction) -> EditorAction {
+        let replaces_level = matches!(
+            action,
+            EditorAction::New | EditorAction::PromptLoad | EditorAction::Load(_) | EditorAction::BrowseExamples
+        );
+        if replaces_level && self.ask_unsaved(ctx, Guarded::Editor(action.clone())) {
+            EditorAction::None
+        } else {
+            action
+        }
+    }
+
+    /// Send an app-wide shortcut to the active tool's handler. The World
+    /// Editor's file shortcuts come back as the action to run.
+    pub fn route_shortcut(&mut self, shortcut: Shortcut) -> EditorAction {
+        match self.active_tool {
+            Tool::WorldEditor => return handle_editor_shortcut(&mut self.world_editor.editor_state, shortcut),
+            Tool::Modeler => handle_modeler_shortcut(&mut self.modeler.modeler_state, shortcut),
+            Tool::Tracker => handle_tracker_shortcut(&mut self.tracker, shortcut),
+            Tool::Plugin(i) => {
+                let mut services = AppServices {
+                    editor: &mut self.world_editor.editor_state,
+                    icon_font: self.icon_font.as_ref(),
+                };
+                if let Some(plugin) = self.plugins.get_mut(i) {
+                    plugin.shortcut(shortcut, &mut services);
+                }
+            }
+            Tool::Home => {}
+        }
+        EditorAction::None
+    }
+
+    /// Throw away a tool's unsaved changes: the level and songs are read
+    /// again from disk, the modeler starts over
+    pub fn discard_changes(&mut self, tool: Tool) {
+        match tool {
+            Tool::WorldEditor => {
+                let ws = &mut self.world_editor;
+                if let Some(path) = ws.editor_state.current_file.clone() {
+                    match load_level(&path) {
+                        Ok(level) => {
+                            ws.editor_layout.apply_config(&level.editor_layout);
+                            ws.editor_state.load_level(level, path);
+                            return;
+                        }
+                        Err(e) => log::warn!("Could not reload {}: {}", path.display(), e),
+                    }
+                }
+                let texture_packs = std::mem::take(&mut ws.editor_state.texture_packs);
+                ws.editor_state = EditorState::new(create_empty_level());
+                ws.editor_state.texture_packs = texture_packs;
+                ws.editor_layout.apply_config(&ws.editor_state.level.editor_layout);
+            }
+            Tool::Modeler => {
+                self.modeler.modeler_state = ModelerState::new();
+                self.apply_resolution();
+            }
+            Tool::Tracker => self.tracker.revert_songs(),
+            Tool::Home | Tool::Plugin(_) => {}
+        }
+    }
+
     /// Get the active tool index (for tab bar)
     pub fn active_tool_index(&self) -> usize {
-        self.active_tool as usize
+        match self.active_tool {
+            Tool::Plugin(i) => Tool::ALL.len() + i,
+            tool => Tool::ALL.iter().position(|&t| t == tool).unwrap_or(0),
+        }
+    }
+
+    /// Tool of a tab bar index (built-in tools, then plugins)
+    pub fn tool_at(&self, index: usize) -> Option<Tool> {
+        Tool::from_index(index).or_else(|| {
+            let plugin = index - Tool::ALL.len();
+            (plugin < self.plugins.len()).then_some(Tool::Plugin(plugin))
+        })
     }
 }
@@ -0,0 +1,135 @@
+//! Command-line tools
+//!
+//! Run instead of the editor when the first argument names a command:
+//! - `diff <old.ron> <new.ron>`: list structural changes between two levels
+//! - `merge <base.ron> <ours.ron> <theirs.ron> [-o <out.ron>]`: three-way
+//!   merge two edited copies of a level; writes to ours unless `-o` is given,
+//!   so it works as a git merge driver (`bonnie-engine merge %O %A %B`)
+//! - `bench [level.ron]`: time frames at 320x240 from the middle of every
+//!   room, turning a full circle; the standard benchmark level when no level
+//!   is given. Rooms over the frame budget are flagged.
+//!
+//! Exit codes: 0 success, 1 merge conflicts (ours kept), 2 error.
+
+use std::time::Instant;
+use crate::game::{level_budget, rooms_within, DEFAULT_STREAM_HOPS, FRAME_TRIANGLE_BUDGET, PLAYER_EYE_HEIGHT};
+use crate::rasterizer::{render_mesh, Camera, Color, Framebuffer, RasterSettings, Vec3};
+use crate::world::{create_benchmark_level, diff_levels, load_level, merge_levels, save_level};
+
+/// Frames timed from each room
+const BENCH_FRAMES: usize = 64;
+
+/// Frame time to stay under (30 fps)
+const BENCH_FRAME_MS: f64 = 1000.0 / 30.0;
+
+const USAGE: &str = "Usage:
+  bonnie-engine diff <old.ron> <new.ron>
+  bonnie-engine merge <base.ron> <ours.ron> <theirs.ron> [-o <out.ron>]
+  bonnie-engine bench [level.ron]     (frame times per room, benchmark level by default)
+  bonnie-engine --view <level.ron>    (walk through a level, no editor)
+  bonnie-engine --host [port]         (edit together over LAN, default port 7878)
+  bonnie-engine --join <host:port>    (join a co-op host)";
+
+/// Run the command named by `args` (program name excluded).
+/// Returns None if there is no command, so the editor should start.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let result = match command.as_str() {
+        "diff" => diff(rest),
+        "merge" => merge(rest),
+        "bench" => bench(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(0)
+        }
+        _ => return None,
+    };
+    Some(result.unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        2
+    }))
+}
+
+fn load(path: &str) -> Result<crate::world::Level, String> {
+    load_level(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn diff(args: &[String]) -> Result<i32, String> {
+    let [old, new] = args else {
+        return Err(format!("diff takes two files\n{}", USAGE));
+    };
+    let diff = diff_levels(&load(old)?, &load(new)?);
+    for change in &diff.changes {
+        println!("{}", change);
+    }
+    println!("{}", diff.summary());
+    Ok(0)
+}
+
+fn merge(args: &[String]) -> Result<i32, String> {
+    let (files, out) = match args {
+        [files @ .., flag, out] if flag == "-o" => (files, out),
+        [_, ours, _] => (args, ours),
+        _ => return Err(format!("merge takes three files\n{}", USAGE)),
+    };
+    let [base, ours, theirs] = files else {
+        return Err(format!("merge takes three files\n{}", USAGE));
+    };
+
+    let result = merge_levels(&load(base)?, &load(ours)?, &load(theirs)?);
+    save_level(&result.level, out).map_err(|e| format!("{}: {}", out, e))?;
+    for conflict in &result.conflicts {
+        println!("conflict: {} (kept ours)", conflict);
+    }
+    println!("Merged into {} with {} conflict(s)", out, result.conflicts.len());
+    Ok(if result.conflicts.is_empty() { 0 } else { 1 })
+}
+
+fn bench(args: &[String]) -> Result<i32, String> {
+    let (name, level) = match args {
+        [] => ("benchmark level".to_string(), create_benchmark_level()),
+        [path] => (path.clone(), load(path)?),
+        _ => return Err(format!("bench takes at most one file\n{}", USAGE)),
+    };
+    let settings = RasterSettings::default();
+    let (w, h) = settings.resolution();
+    let meshes: Vec<_> = level.rooms.iter().map(|r| r.to_game_render_data(|_| None)).collect();
+    let mut fb = Framebuffer::new(w, h);
+    let mut camera = Camera::new();
+    let mut slowest = 0.0f64;
+
+    println!("{} at {}x{}, {} frames per room", name, w, h, BENCH_FRAMES);
+    for estimate in level_budget(&level, DEFAULT_STREAM_HOPS) {
+        let room = &level.rooms[estimate.room];
+        let center = room.bounds.center();
+        camera.position = Vec3::new(center.x, room.bounds.min.y + PLAYER_EYE_HEIGHT, center.z);
+        camera.rotation_x = 0.0;
+        let drawn = rooms_within(&level, estimate.room, DEFAULT_STREAM_HOPS);
+
+        let start = Instant::now();
+        for frame in 0..BENCH_FRAMES {
+            camera.rotation_y = frame as f32 / BENCH_FRAMES as f32 * std::f32::consts::TAU;
+            camera.update_basis();
+            fb.clear(Color::new(0, 0, 0));
+            for &r in &drawn {
+                let (vertices, faces) = &meshes[r];
+                render_mesh(&mut fb, vertices, faces, &[], &camera, &settings);
+            }
+        }
+        let ms = start.elapsed().as_secs_f64() * 1000.0 / BENCH_FRAMES as f64;
+        slowest = slowest.max(ms);
+
+        let warning = if estimate.over() || ms > BENCH_FRAME_MS { "  OVER BUDGET" } else { "" };
+        println!(
+            "room {:3}: {:6.2} ms/frame, {:5} triangles worst case ({:3.0}% of {}){}",
+            estimate.room,
+            ms,
+            estimate.triangles,
+            estimate.load() * 100.0,
+            FRAME_TRIANGLE_BUDGET,
+            warning
+        );
+    }
+    println!("Slowest room: {:.2} ms/frame ({:.0} fps)", slowest, 1000.0 / slowest.max(0.001));
+    Ok(0)
+}
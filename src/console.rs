@@ -0,0 +1,259 @@
+//! Logging and the in-app console
+//!
+//! All diagnostics go through the `log` crate. `init` installs a logger that:
+//! - keeps the last `MAX_ENTRIES` records in memory for the console panel
+//! - mirrors records to stderr on native builds (WASM has no stderr)
+//!
+//! The console (F12) filters by level and module and copies the visible
+//! lines to the clipboard, so users can paste them into bug reports.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use macroquad::prelude::*;
+use crate::ui::{Rect, UiContext, draw_text_button, ACCENT_COLOR};
+
+/// Records kept for the console
+pub const MAX_ENTRIES: usize = 1000;
+
+/// A captured log record
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// Seconds since the app started
+    pub time: f64,
+    pub level: Level,
+    /// Module the record came from, without the crate prefix (e.g. "editor::state")
+    pub module: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// One line as shown in the console and copied to the clipboard
+    pub fn format(&self) -> String {
+        format!("[{:8.2}] {:5} {}: {}", self.time, self.level, self.module, self.message)
+    }
+
+    /// Top-level module (e.g. "editor"), used by the module filter
+    pub fn top_module(&self) -> &str {
+        self.module.split("::").next().unwrap_or(&self.module)
+    }
+}
+
+static ENTRIES: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+struct ConsoleLogger;
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let target = record.target();
+        let module = target.split_once("::").map_or(target, |(_, rest)| rest);
+        let entry = LogEntry {
+            time: macroquad::miniquad::date::now() - start_time(),
+            level: record.level(),
+            module: module.to_string(),
+            message: record.args().to_string(),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        eprintln!("{}", entry.format());
+        push_entry(entry);
+    }
+
+    fn flush(&self) {}
+}
+
+static START_TIME: Mutex<Option<f64>> = Mutex::new(None);
+
+fn start_time() -> f64 {
+    START_TIME.lock().ok().and_then(|t| *t).unwrap_or(0.0)
+}
+
+fn push_entry(entry: LogEntry) {
+    if let Ok(mut entries) = ENTRIES.lock() {
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+/// Install the console logger (call once at startup)
+pub fn init() {
+    if let Ok(mut start) = START_TIME.lock() {
+        *start = Some(macroquad::miniquad::date::now());
+    }
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(if cfg!(debug_assertions) { LevelFilter::Debug } else { LevelFilter::Info });
+    }
+}
+
+/// Copy of the captured records matching a filter
+pub fn filtered_entries(min_level: Level, module: Option<&str>) -> Vec<LogEntry> {
+    let Ok(entries) = ENTRIES.lock() else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter(|e| e.level <= min_level && module.is_none_or(|m| e.top_module() == m))
+        .cloned()
+        .collect()
+}
+
+/// Top-level modules that have logged something, sorted
+pub fn logged_modules() -> Vec<String> {
+    let Ok(entries) = ENTRIES.lock() else {
+        return Vec::new();
+    };
+    let mut modules: Vec<String> = entries.iter().map(|e| e.top_module().to_string()).collect();
+    modules.sort();
+    modules.dedup();
+    modules
+}
+
+/// Drop all captured records
+pub fn clear() {
+    if let Ok(mut entries) = ENTRIES.lock() {
+        entries.clear();
+    }
+}
+
+/// Console panel state
+pub struct ConsoleState {
+    pub open: bool,
+    /// Least severe level shown
+    pub min_level: Level,
+    /// Only show this top-level module
+    pub module: Option<String>,
+    /// Lines scrolled up from the newest record
+    pub scroll: usize,
+}
+
+impl ConsoleState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            min_level: Level::Info,
+            module: None,
+            scroll: 0,
+        }
+    }
+}
+
+/// Cycle Error -> Warn -> Info -> Debug -> Error
+fn next_level(level: Level) -> Level {
+    match level {
+        Level::Error => Level::Warn,
+        Level::Warn => Level::Info,
+        Level::Info => Level::Debug,
+        Level::Debug | Level::Trace => Level::Error,
+    }
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Error => Color::from_rgba(240, 100, 100, 255),
+        Level::Warn => Color::from_rgba(230, 190, 90, 255),
+        Level::Info => Color::from_rgba(210, 210, 210, 255),
+        Level::Debug | Level::Trace => Color::from_rgba(130, 130, 140, 255),
+    }
+}
+
+/// Draw the console over the bottom of `rect` (F12 toggles it)
+pub fn draw_console(ctx: &mut UiContext, rect: Rect, state: &mut ConsoleState) {
+    if is_key_pressed(KeyCode::F12) {
+        state.open = !state.open;
+    }
+    if !state.open {
+        return;
+    }
+
+    let h = (rect.h * 0.4).max(120.0).floor();
+    let panel = Rect::new(rect.x, rect.bottom() - h, rect.w, h);
+    draw_rectangle(panel.x, panel.y, panel.w, panel.h, Color::from_rgba(18, 18, 22, 240));
+    draw_line(panel.x, panel.y, panel.right(), panel.y, 1.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header: filters and actions
+    let header_h = 28.0;
+    let by = panel.y + 4.0;
+    draw_text("Console", panel.x + 8.0, by + 15.0, 16.0, WHITE);
+    let level_label = format!("Level: {}", state.min_level);
+    if draw_text_button(ctx, Rect::new(panel.x + 80.0, by, 100.0, 20.0), &level_label, Color::from_rgba(60, 60, 70, 255)) {
+        state.min_level = next_level(state.min_level);
+        state.scroll = 0;
+    }
+    let module_label = format!("Module: {}", state.module.as_deref().unwrap_or("all"));
+    if draw_text_button(ctx, Rect::new(panel.x + 186.0, by, 140.0, 20.0), &module_label, Color::from_rgba(60, 60, 70, 255)) {
+        // Cycle all -> each logged module -> all
+        let modules = logged_modules();
+        let next = match &state.module {
+            None => 0,
+            Some(m) => modules.iter().position(|x| x == m).map_or(modules.len(), |i| i + 1),
+        };
+        state.module = modules.get(next).cloned();
+        state.scroll = 0;
+    }
+
+    let entries = filtered_entries(state.min_level, state.module.as_deref());
+    if draw_text_button(ctx, Rect::new(panel.right() - 128.0, by, 60.0, 20.0), "Copy", ACCENT_COLOR) {
+        let text: Vec<String> = entries.iter().map(LogEntry::format).collect();
+        macroquad::miniquad::window::clipboard_set(&text.join("\n"));
+        log::info!("Copied {} console lines to the clipboard", text.len());
+    }
+    if draw_text_button(ctx, Rect::new(panel.right() - 64.0, by, 56.0, 20.0), "Clear", Color::from_rgba(60, 60, 70, 255)) {
+        clear();
+        state.scroll = 0;
+    }
+
+    // Records, newest at the bottom
+    let line_h = 16.0;
+    let body = Rect::new(panel.x + 8.0, panel.y + header_h, panel.w - 16.0, panel.h - header_h - 4.0);
+    let visible = (body.h / line_h).floor().max(1.0) as usize;
+    if ctx.mouse.inside(&panel) && ctx.mouse.scroll != 0.0 {
+        let step = ctx.mouse.scroll.signum() as isize * 3;
+        state.scroll = (state.scroll as isize + step).max(0) as usize;
+    }
+    state.scroll = state.scroll.min(entries.len().saturating_sub(visible));
+
+    let end = entries.len() - state.scroll;
+    let start = end.saturating_sub(visible);
+    for (i, entry) in entries[start..end].iter().enumerate() {
+        let y = body.y + (i as f32 + 1.0) * line_h - 3.0;
+        draw_text(&entry.format(), body.x, y.floor(), 14.0, level_color(entry.level));
+    }
+    if entries.is_empty() {
+        draw_text("No messages", body.x, body.y + 13.0, 14.0, Color::from_rgba(100, 100, 100, 255));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: Level, module: &str) -> LogEntry {
+        LogEntry { time: 1.5, level, module: module.to_string(), message: "hello".to_string() }
+    }
+
+    #[test]
+    fn test_entry_format_and_module() {
+        let e = entry(Level::Warn, "editor::state");
+        assert_eq!(e.top_module(), "editor");
+        assert_eq!(e.format(), "[    1.50] WARN  editor::state: hello");
+    }
+
+    #[test]
+    fn test_next_level_cycles() {
+        let mut level = Level::Error;
+        for _ in 0..4 {
+            level = next_level(level);
+        }
+        assert_eq!(level, Level::Error);
+    }
+}
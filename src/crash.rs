@@ -0,0 +1,166 @@
+//! Panic recovery for tools
+//!
+//! Each frame the active tool runs inside `catch_tool_panic` (native only;
+//! WASM builds abort on panic). A panicking tool is replaced by a crash
+//! screen instead of taking the whole application down:
+//! - "Save recovery copy and restart" writes the tool's document to
+//!   `recovery/` and gives the tool fresh state
+//! - "Restart" drops the document
+//!
+//! Other tools keep running, so unsaved work there is not lost.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use macroquad::prelude::*;
+use crate::app::{AppState, Tool};
+use crate::ui::{Rect, UiContext, draw_text_button, wrap_text, ACCENT_COLOR};
+use crate::world::save_level;
+
+/// Directory recovery copies are written to
+pub const RECOVERY_DIR: &str = "recovery";
+
+/// Message and location of the last panic, recorded by the panic hook
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Record panic messages for the crash screen (keeps the default stderr output)
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = match info.location() {
+            Some(loc) => format!("{} ({}:{})", payload_message(info.payload()), loc.file(), loc.line()),
+            None => payload_message(info.payload()),
+        };
+        log::error!("Panic: {}", message);
+        if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(message);
+        }
+        default_hook(info);
+    }));
+}
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Run one frame of a tool, returning the panic message if it panicked
+#[cfg(not(target_arch = "wasm32"))]
+pub fn catch_tool_panic<F: FnOnce()>(f: F) -> Result<(), String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+        LAST_PANIC
+            .lock()
+            .ok()
+            .and_then(|mut last| last.take())
+            .unwrap_or_else(|| payload_message(payload.as_ref()))
+    })
+}
+
+/// Run one frame of a tool (panics abort on WASM, so nothing to catch)
+#[cfg(target_arch = "wasm32")]
+pub fn catch_tool_panic<F: FnOnce()>(f: F) -> Result<(), String> {
+    f();
+    Ok(())
+}
+
+/// Write the crashed tool's document to `RECOVERY_DIR`
+pub fn save_recovery_copy(app: &AppState, tool: Tool) -> Result<PathBuf, String> {
+    let stamp = macroquad::miniquad::date::now() as u64;
+    let path = PathBuf::from(RECOVERY_DIR).join(format!("{}-{}.ron", tool.label().to_lowercase(), stamp));
+    std::fs::create_dir_all(RECOVERY_DIR).map_err(|e| format!("Failed to create {}: {}", RECOVERY_DIR, e))?;
+
+    let pretty = ron::ser::PrettyConfig::default();
+    let contents = match tool {
+        Tool::Home | Tool::Plugin(_) => return Err("Nothing to recover".to_string()),
+        Tool::WorldEditor => {
+            return save_level(&app.world_editor.editor_state.level, &path)
+                .map(|()| path.clone())
+                .map_err(|e| e.to_string());
+        }
+        Tool::Modeler => ron::ser::to_string_pretty(&app.modeler.modeler_state.model, pretty),
+        Tool::Tracker => ron::ser::to_string_pretty(&app.tracker.song, pretty),
+    }
+    .map_err(|e| format!("Failed to serialize: {}", e))?;
+
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Choice made on the crash screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashAction {
+    None,
+    SaveAndRestart,
+    Restart,
+}
+
+/// Draw the screen shown in place of a crashed tool
+pub fn draw_crash_screen(ctx: &mut UiContext, rect: Rect, tool: Tool, message: &str) -> CrashAction {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(25, 22, 24, 255));
+
+    let w = (rect.w - 64.0).min(640.0);
+    let x = (rect.x + (rect.w - w) / 2.0).floor();
+    let mut y = (rect.y + 64.0).floor();
+
+    draw_text(&format!("The {} tool crashed", tool.label()), x, y, 24.0, Color::from_rgba(230, 110, 110, 255));
+    y += 28.0;
+    draw_text(
+        "Other tabs are still running. Save a recovery copy of this tool's work, then restart it.",
+        x,
+        y,
+        14.0,
+        Color::from_rgba(180, 180, 180, 255),
+    );
+    y += 24.0;
+
+    // Panic message
+    let lines = wrap_text(message, w - 16.0, 14);
+    let box_h = lines.len() as f32 * 18.0 + 16.0;
+    draw_rectangle(x, y, w, box_h, Color::from_rgba(40, 32, 34, 255));
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, x + 8.0, y + 20.0 + i as f32 * 18.0, 14.0, Color::from_rgba(220, 200, 200, 255));
+    }
+    y += box_h + 16.0;
+
+    // Only the built-in editors have a document to recover
+    let has_document = !matches!(tool, Tool::Home | Tool::Plugin(_));
+    let mut action = CrashAction::None;
+    if has_document {
+        let save_rect = Rect::new(x, y, 240.0, 28.0);
+        if draw_text_button(ctx, save_rect, "Save recovery copy and restart", ACCENT_COLOR) {
+            action = CrashAction::SaveAndRestart;
+        }
+    }
+    let restart_x = if has_document { x + 252.0 } else { x };
+    let restart_rect = Rect::new(restart_x, y, 160.0, 28.0);
+    if draw_text_button(ctx, restart_rect, "Restart without saving", Color::from_rgba(60, 60, 70, 255)) {
+        action = CrashAction::Restart;
+    }
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_message() {
+        let payload: Box<dyn std::any::Any + Send> = Box::new("boom");
+        assert_eq!(payload_message(payload.as_ref()), "boom");
+        let payload: Box<dyn std::any::Any + Send> = Box::new(String::from("index out of bounds"));
+        assert_eq!(payload_message(payload.as_ref()), "index out of bounds");
+        let payload: Box<dyn std::any::Any + Send> = Box::new(3);
+        assert_eq!(payload_message(payload.as_ref()), "unknown panic");
+    }
+
+    #[test]
+    fn test_catch_tool_panic() {
+        assert!(catch_tool_panic(|| {}).is_ok());
+        let message = catch_tool_panic(|| panic!("tool exploded")).unwrap_err();
+        assert!(message.contains("tool exploded"));
+    }
+}
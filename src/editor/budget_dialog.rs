@@ -0,0 +1,134 @@
+//! Budget dialog - performance warnings per room
+//!
+//! Lists every room's worst-case triangle count (see `game::budget`) against
+//! the 320x240 frame budget, worst first, with the rooms over it in red.
+//! The estimate is taken when the dialog opens (toolbar gauge button);
+//! clicking a room jumps to it.
+
+use macroquad::prelude::*;
+use crate::game::{level_budget, RoomBudget, DEFAULT_STREAM_HOPS, FRAME_TRIANGLE_BUDGET};
+use crate::ui::{Rect, UiContext, draw_text_button, ui_screen_width, ui_screen_height};
+use crate::world::Level;
+use super::EditorState;
+
+/// Rooms at or above this share of the budget are shown as close to it
+const WARN_LOAD: f32 = 0.75;
+
+/// Estimate shown by the open dialog
+#[derive(Debug, Clone)]
+pub struct BudgetDialog {
+    /// Worst first
+    pub rooms: Vec<RoomBudget>,
+    scroll: f32,
+}
+
+impl BudgetDialog {
+    pub fn new(level: &Level) -> Self {
+        let mut rooms = level_budget(level, DEFAULT_STREAM_HOPS);
+        rooms.sort_by(|a, b| b.triangles.cmp(&a.triangles).then(a.room.cmp(&b.room)));
+        Self { rooms, scroll: 0.0 }
+    }
+
+    pub fn over_budget(&self) -> usize {
+        self.rooms.iter().filter(|r| r.over()).count()
+    }
+}
+
+fn load_color(budget: &RoomBudget) -> Color {
+    if budget.over() {
+        Color::from_rgba(220, 80, 70, 255)
+    } else if budget.load() >= WARN_LOAD {
+        Color::from_rgba(220, 180, 60, 255)
+    } else {
+        Color::from_rgba(90, 180, 100, 255)
+    }
+}
+
+/// Draw the budget dialog. Returns true when it should close.
+pub fn draw_budget_dialog(ctx: &mut UiContext, state: &mut EditorState) -> bool {
+    let Some(dialog) = state.budget.as_mut() else { return true };
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = 440.0;
+    let header_h = 36.0;
+    let footer_h = 44.0;
+    let row_h = 22.0;
+    let list_h = (row_h * dialog.rooms.len().clamp(1, 12) as f32).floor();
+    let dialog_h = header_h + 44.0 + list_h + footer_h;
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("Performance Budget", x + 16.0, y + 24.0, 18.0, WHITE);
+
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let summary = format!(
+        "Worst case at 320x240, {} triangles a frame - {} room(s) over",
+        FRAME_TRIANGLE_BUDGET,
+        dialog.over_budget()
+    );
+    draw_text(&summary, x + 16.0, y + header_h + 18.0, 14.0, dim);
+    let (col_rooms, col_tris, col_bar) = (x + 90.0, x + 170.0, x + 260.0);
+    let head_y = y + header_h + 38.0;
+    draw_text("Room", x + 16.0, head_y, 14.0, dim);
+    draw_text("Drawn", col_rooms, head_y, 14.0, dim);
+    draw_text("Triangles", col_tris, head_y, 14.0, dim);
+
+    // Rooms, worst first
+    let list = Rect::new(x + 8.0, head_y + 6.0, dialog_w - 16.0, list_h);
+    if ctx.mouse.inside(&list) {
+        let max_scroll = (dialog.rooms.len() as f32 * row_h - list.h).max(0.0);
+        dialog.scroll = (dialog.scroll - ctx.mouse.scroll * 30.0).clamp(0.0, max_scroll);
+    }
+    let first = (dialog.scroll / row_h) as usize;
+    let visible = (list.h / row_h) as usize;
+    let mut jump = None;
+    for (i, budget) in dialog.rooms.iter().enumerate().skip(first).take(visible) {
+        let row = Rect::new(list.x, list.y + (i - first) as f32 * row_h, list.w, row_h);
+        let hovered = ctx.mouse.inside(&row);
+        if hovered || budget.room == state.current_room {
+            draw_rectangle(row.x, row.y, row.w, row.h, Color::from_rgba(50, 50, 60, 255));
+        }
+        if ctx.mouse.clicked(&row) {
+            jump = Some(budget.room);
+        }
+
+        let text_y = row.y + 15.0;
+        let color = load_color(budget);
+        draw_text(&budget.room.to_string(), x + 16.0, text_y, 14.0, WHITE);
+        let drawn = if budget.mirrors > 0 {
+            format!("{} +{}m", budget.rooms, budget.mirrors)
+        } else {
+            budget.rooms.to_string()
+        };
+        draw_text(&drawn, col_rooms, text_y, 14.0, WHITE);
+        draw_text(&budget.triangles.to_string(), col_tris, text_y, 14.0, color);
+
+        // Share of the budget, the full bar being twice the budget
+        let bar_w = x + dialog_w - 16.0 - col_bar;
+        let bar = Rect::new(col_bar, row.y + 6.0, bar_w, row_h - 12.0);
+        draw_rectangle(bar.x, bar.y, bar.w, bar.h, Color::from_rgba(25, 25, 30, 255));
+        draw_rectangle(bar.x, bar.y, bar.w * (budget.load() / 2.0).min(1.0), bar.h, color);
+        draw_line(bar.x + bar.w / 2.0, bar.y - 2.0, bar.x + bar.w / 2.0, bar.bottom() + 2.0, 1.0, WHITE);
+    }
+    if dialog.rooms.is_empty() {
+        draw_text("The level has no rooms", x + 16.0, list.y + 15.0, 14.0, dim);
+    }
+
+    if let Some(room) = jump {
+        state.current_room = room;
+        state.set_status(&format!("Room {}", room), 2.0);
+    }
+
+    // Footer
+    let close_rect = Rect::new(x + dialog_w - 90.0, y + dialog_h - footer_h + 8.0, 80.0, 28.0);
+    draw_text_button(ctx, close_rect, "Close", Color::from_rgba(60, 60, 70, 255))
+        || is_key_pressed(KeyCode::Escape)
+        || is_key_pressed(KeyCode::Enter)
+}
@@ -0,0 +1,102 @@
+//! Editor camera collision
+//!
+//! Optional limits on the 3D viewport's fly camera, for reviewing interiors
+//! without ending up inside walls:
+//! - Collide: moves are swept with `raycast_max` and slide along the faces they hit
+//! - Walk: collide, then clamp to eye height above the floor under the camera
+
+use crate::game::PLAYER_EYE_HEIGHT;
+use crate::rasterizer::Vec3;
+use crate::world::{raycast, raycast_max, HitFace, Level};
+
+/// How close the camera may get to a face
+pub const CAMERA_RADIUS: f32 = 64.0;
+
+/// How the editor camera is held back by level geometry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CameraCollision {
+    /// Fly anywhere
+    #[default]
+    Off,
+    /// Stop at walls, floors and ceilings
+    Collide,
+    /// Collide and stay at walk height above the floor
+    Walk,
+}
+
+impl CameraCollision {
+    /// Next mode for the toolbar button
+    pub fn next(self) -> Self {
+        match self {
+            CameraCollision::Off => CameraCollision::Collide,
+            CameraCollision::Collide => CameraCollision::Walk,
+            CameraCollision::Walk => CameraCollision::Off,
+        }
+    }
+
+    /// Translation key for the mode name
+    pub fn label_key(self) -> &'static str {
+        match self {
+            CameraCollision::Off => "editor.camera_collision.off",
+            CameraCollision::Collide => "editor.camera_collision.collide",
+            CameraCollision::Walk => "editor.camera_collision.walk",
+        }
+    }
+}
+
+/// Where a camera moving from `from` to `to` ends up
+pub fn constrain_camera(level: &Level, from: Vec3, to: Vec3, mode: CameraCollision) -> Vec3 {
+    if mode == CameraCollision::Off {
+        return to;
+    }
+    let mut pos = to;
+    // A few passes so sliding off one face can't push through another (corners)
+    for _ in 0..3 {
+        let delta = pos - from;
+        let len = delta.len();
+        if len < 1e-3 {
+            break;
+        }
+        let Some(hit) = raycast_max(level, from, delta, len + CAMERA_RADIUS) else { break };
+        // The hit normal faces the camera: keep CAMERA_RADIUS in front of the face
+        let depth = (pos - hit.point).dot(hit.normal);
+        if depth >= CAMERA_RADIUS - 0.01 {
+            break;
+        }
+        pos = pos + hit.normal.scale(CAMERA_RADIUS - depth);
+    }
+
+    if mode == CameraCollision::Walk {
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        if let Some(hit) = raycast(level, pos, down).filter(|h| h.face == HitFace::Floor) {
+            pos.y = hit.point.y + PLAYER_EYE_HEIGHT;
+        }
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_test_level;
+
+    #[test]
+    fn test_camera_stops_at_walls_and_floor() {
+        let level = create_test_level();
+        let from = Vec3::new(512.0, 512.0, 512.0);
+
+        let off = constrain_camera(&level, from, Vec3::new(1500.0, 512.0, 512.0), CameraCollision::Off);
+        assert_eq!(off.x, 1500.0);
+
+        // Sliding: the X move is stopped, the Z move is kept
+        let wall = constrain_camera(&level, from, Vec3::new(1500.0, 512.0, 600.0), CameraCollision::Collide);
+        assert!((wall.x - (1024.0 - CAMERA_RADIUS)).abs() < 0.1);
+        assert!((wall.z - 600.0).abs() < 0.1);
+
+        let floor = constrain_camera(&level, from, Vec3::new(512.0, -300.0, 512.0), CameraCollision::Collide);
+        assert!((floor.y - CAMERA_RADIUS).abs() < 0.1);
+
+        let walk = constrain_camera(&level, from, Vec3::new(600.0, 900.0, 512.0), CameraCollision::Walk);
+        assert_eq!(walk.y, PLAYER_EYE_HEIGHT);
+    }
+}
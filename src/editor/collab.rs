@@ -0,0 +1,625 @@
+//! Co-op editing (experimental, native only)
+//!
+//! Several editors working on one level over TCP:
+//! - `--host [port]` serves the open level to this machine only; add `--lan`
+//!   and a `--key <key>` to serve the network. `--join <host:port>` (with the
+//!   host's `--key`) connects to a host and replaces the open level with the
+//!   host's
+//! - a joining peer first sends the session key; the host drops peers whose
+//!   key doesn't match before they see the level
+//! - messages are newline-delimited RON, read and written on their own
+//!   threads per connection so a slow peer never stalls the editor
+//! - edits travel as per-sector changes: every frame, sectors that differ
+//!   from the last synced copy (local edits, undo and redo alike) are sent.
+//!   Room settings, entities and portals go as one properties edit, and only
+//!   added or resized rooms are sent whole. Everything else in the level
+//!   (camera paths, events, selection sets, macros, ambience, metadata...)
+//!   goes as one settings edit when any of it changed; the editor layout and
+//!   editing stats stay each peer's own
+//! - conflicts: the host relays every edit to all peers, the sender included,
+//!   in the order it received them, so everyone applies edits in the host's
+//!   order and the last write to a sector to reach the host wins
+//! - each peer's selected sectors are shared and drawn in the grid view in
+//!   that peer's color
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::ColorGrade;
+use crate::world::{
+    load_level_from_str, Aabb, CameraPath, EditMacro, Level, LevelEvent, LevelMetadata, RespawnRules, Room, Sector,
+    SelectionSet, TextureVariants,
+};
+use super::EditorState;
+
+/// Port used by `--host` when none is given
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Selection colors, by peer id (the host is peer 0)
+pub const PEER_COLORS: [(u8, u8, u8); 6] = [
+    (255, 120, 80),
+    (90, 200, 255),
+    (150, 230, 90),
+    (230, 110, 230),
+    (255, 215, 70),
+    (120, 140, 255),
+];
+
+/// Sectors a peer has selected, as (room, x, z)
+pub type PeerSelection = Vec<(usize, usize, usize)>;
+
+/// Messages between peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum CollabMessage {
+    /// New peer to the host, before anything else: the session key
+    Hello { key: String },
+    /// Host to a new peer: the peer's id and the level as RON
+    Welcome { peer: u8, level: String },
+    /// Edits in the order they were made
+    Edits(Vec<LevelEdit>),
+    /// A peer's selected sectors
+    Selection { peer: u8, cells: PeerSelection },
+}
+
+/// One change to the level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum LevelEdit {
+    /// A sector was set or cleared
+    Sector { room: usize, x: usize, z: usize, sector: Option<Sector> },
+    /// Anything outside the sector grid changed (sent without its sectors)
+    Properties { room: usize, properties: Room },
+    /// A room was added or its grid resized
+    Room { room: usize, data: Room },
+    /// Rooms from `count` on were removed
+    Truncate { count: usize },
+    /// Something outside the rooms changed
+    Settings(Box<LevelSettings>),
+}
+
+/// Everything peers share outside the level's rooms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LevelSettings {
+    /// Without the editing stats, which are each peer's own
+    metadata: LevelMetadata,
+    camera_paths: Vec<CameraPath>,
+    sector_size: f32,
+    click_height: f32,
+    ambience: ColorGrade,
+    selection_sets: Vec<SelectionSet>,
+    texture_variants: TextureVariants,
+    macros: Vec<EditMacro>,
+    events: Vec<LevelEvent>,
+    respawn: RespawnRules,
+    music: Option<String>,
+}
+
+impl LevelSettings {
+    fn of(level: &Level) -> Self {
+        Self {
+            metadata: LevelMetadata { stats: Default::default(), ..level.metadata.clone() },
+            camera_paths: level.camera_paths.clone(),
+            sector_size: level.sector_size,
+            click_height: level.click_height,
+            ambience: level.ambience,
+            selection_sets: level.selection_sets.clone(),
+            texture_variants: level.texture_variants.clone(),
+            macros: level.macros.clone(),
+            events: level.events.clone(),
+            respawn: level.respawn,
+            music: level.music.clone(),
+        }
+    }
+
+    /// Put the settings into `level`, keeping its editing stats. A new sector
+    /// size rescales the rooms as it did for the sender.
+    fn apply(&self, level: &mut Level) {
+        if self.sector_size != level.sector_size {
+            level.set_sector_size(self.sector_size);
+        }
+        level.metadata = LevelMetadata { stats: level.metadata.stats, ..self.metadata.clone() };
+        level.camera_paths = self.camera_paths.clone();
+        level.click_height = self.click_height;
+        level.ambience = self.ambience;
+        level.selection_sets = self.selection_sets.clone();
+        level.texture_variants = self.texture_variants.clone();
+        level.macros = self.macros.clone();
+        level.events = self.events.clone();
+        level.respawn = self.respawn;
+        level.music = self.music.clone();
+    }
+}
+
+/// A room without its sectors, for comparing and sending everything else
+fn room_properties(room: &Room) -> Room {
+    Room { sectors: Vec::new(), bounds: Aabb::default(), ..room.clone() }
+}
+
+/// The edits that turn `old` into `new`
+fn diff_rooms(old: &[Room], new: &[Room]) -> Vec<LevelEdit> {
+    let mut edits = Vec::new();
+    for (i, room) in new.iter().enumerate() {
+        let Some(prev) = old.get(i) else {
+            edits.push(LevelEdit::Room { room: i, data: room.clone() });
+            continue;
+        };
+        if prev == room {
+            continue;
+        }
+        if (prev.width, prev.depth) != (room.width, room.depth) {
+            edits.push(LevelEdit::Room { room: i, data: room.clone() });
+            continue;
+        }
+        for (x, (column, prev_column)) in room.sectors.iter().zip(&prev.sectors).enumerate() {
+            for (z, (sector, prev_sector)) in column.iter().zip(prev_column).enumerate() {
+                if sector != prev_sector {
+                    edits.push(LevelEdit::Sector { room: i, x, z, sector: sector.clone() });
+                }
+            }
+        }
+        let properties = room_properties(room);
+        if properties != room_properties(prev) {
+            edits.push(LevelEdit::Properties { room: i, properties });
+        }
+    }
+    if new.len() < old.len() {
+        edits.push(LevelEdit::Truncate { count: new.len() });
+    }
+    edits
+}
+
+/// Apply one edit; edits for rooms or sectors that don't exist are dropped
+fn apply_edit(level: &mut Level, edit: &LevelEdit) {
+    let (rooms, sector_size) = (&mut level.rooms, level.sector_size);
+    match edit {
+        LevelEdit::Sector { room, x, z, sector } => {
+            let Some(room) = rooms.get_mut(*room) else { return };
+            let Some(slot) = room.sectors.get_mut(*x).and_then(|column| column.get_mut(*z)) else { return };
+            *slot = sector.clone();
+            room.recalculate_bounds();
+        }
+        LevelEdit::Properties { room, properties } => {
+            let Some(room) = rooms.get_mut(*room) else { return };
+            *room = Room {
+                width: room.width,
+                depth: room.depth,
+                sectors: std::mem::take(&mut room.sectors),
+                sector_size,
+                ..properties.clone()
+            };
+            room.recalculate_bounds();
+        }
+        LevelEdit::Room { room: i, data } => {
+            let mut room = data.clone();
+            room.sector_size = sector_size;
+            room.recalculate_bounds();
+            if *i < rooms.len() {
+                rooms[*i] = room;
+            } else if *i == rooms.len() {
+                rooms.push(room);
+            }
+        }
+        LevelEdit::Truncate { count } => rooms.truncate(*count),
+        LevelEdit::Settings(settings) => settings.apply(level),
+    }
+}
+
+/// A connected peer (for a client, the host)
+struct Connection {
+    peer: u8,
+    /// Lines for the connection's writer thread
+    outbox: Sender<String>,
+    /// Host only: the peer gave the session key and was sent the level
+    welcomed: bool,
+}
+
+impl Connection {
+    /// Start reading and writing `stream` on their own threads
+    fn open(peer: u8, stream: TcpStream, inbox: Sender<(u8, Option<CollabMessage>)>) -> Result<Self, String> {
+        spawn_reader(peer, &stream, inbox)?;
+        let (outbox, lines) = channel();
+        std::thread::spawn(move || write_lines(stream, lines));
+        Ok(Self { peer, outbox, welcomed: false })
+    }
+
+    /// Queue a message; fails once the writer thread has stopped
+    fn send(&self, message: &CollabMessage) -> Result<(), String> {
+        let mut line = ron::ser::to_string(message).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.outbox.send(line).map_err(|_| "connection closed".to_string())
+    }
+}
+
+/// Write queued lines until the connection is dropped or a write fails, then
+/// shut the stream down (which also ends its reader)
+fn write_lines(mut stream: TcpStream, lines: Receiver<String>) {
+    for line in lines {
+        if stream.write_all(line.as_bytes()).is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+/// Read messages from a connection until it closes; None marks the end
+fn spawn_reader(peer: u8, stream: &TcpStream, inbox: Sender<(u8, Option<CollabMessage>)>) -> Result<(), String> {
+    let reader = stream.try_clone().map_err(|e| e.to_string())?;
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            match ron::from_str(&line) {
+                Ok(message) => {
+                    if inbox.send((peer, Some(message))).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => log::warn!("Dropped co-op message from peer {}: {}", peer, e),
+            }
+        }
+        let _ = inbox.send((peer, None));
+    });
+    Ok(())
+}
+
+/// Value of `--name <value>` in the command line
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    let flag = args.iter().position(|a| a == name)?;
+    args.get(flag + 1).filter(|v| !v.starts_with("--"))
+}
+
+/// A hosted or joined co-op session
+pub struct CollabSession {
+    /// Accepts new peers (host only)
+    listener: Option<TcpListener>,
+    /// Key peers must give to join (host only; empty when hosting locally
+    /// without one)
+    key: String,
+    connections: Vec<Connection>,
+    inbox: Receiver<(u8, Option<CollabMessage>)>,
+    inbox_sender: Sender<(u8, Option<CollabMessage>)>,
+    /// Our peer id (0 = host)
+    peer: u8,
+    next_peer: u8,
+    /// Client only: the host's level hasn't arrived yet
+    waiting_for_level: bool,
+    /// Rooms as last sent or received
+    synced: Vec<Room>,
+    /// Everything else as last sent or received
+    synced_settings: LevelSettings,
+    selection: PeerSelection,
+    selections: HashMap<u8, PeerSelection>,
+}
+
+impl CollabSession {
+    fn new(listener: Option<TcpListener>, key: &str, level: &Level) -> Self {
+        let (inbox_sender, inbox) = channel();
+        Self {
+            waiting_for_level: listener.is_none(),
+            listener,
+            key: key.to_string(),
+            connections: Vec::new(),
+            inbox,
+            inbox_sender,
+            peer: 0,
+            next_peer: 1,
+            synced: level.rooms.clone(),
+            synced_settings: LevelSettings::of(level),
+            selection: Vec::new(),
+            selections: HashMap::new(),
+        }
+    }
+
+    /// Serve `level` to peers connecting on `port`: from this machine only,
+    /// or with `lan` from anywhere on the network. Peers must give `key`.
+    pub fn host(port: u16, lan: bool, key: &str, level: &Level) -> Result<Self, String> {
+        if lan && key.is_empty() {
+            return Err("Hosting on the network needs a session key (--key)".to_string());
+        }
+        let ip = if lan { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+        let listener = TcpListener::bind((ip, port)).map_err(|e| format!("Can't host on port {}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        log::info!("Hosting co-op editing on {}:{}", ip, port);
+        Ok(Self::new(Some(listener), key, level))
+    }
+
+    /// Connect to a host at `addr` (host:port), giving it `key`
+    pub fn join(addr: &str, key: &str) -> Result<Self, String> {
+        let stream = TcpStream::connect(addr).map_err(|e| format!("Can't join {}: {}", addr, e))?;
+        let mut session = Self::new(None, "", &Level::new());
+        let host = Connection::open(0, stream, session.inbox_sender.clone())?;
+        host.send(&CollabMessage::Hello { key: key.to_string() })?;
+        session.connections.push(host);
+        log::info!("Joined co-op editing at {}", addr);
+        Ok(session)
+    }
+
+    /// Start a session from `--host [port] [--lan] [--key <key>]` or
+    /// `--join <addr> [--key <key>]`, if given
+    pub fn from_args(args: &[String], level: &Level) -> Option<Result<Self, String>> {
+        let flag = args.iter().position(|a| a == "--host" || a == "--join")?;
+        let value = arg_value(args, &args[flag]);
+        let key = arg_value(args, "--key").map_or("", |k| k.as_str());
+        Some(if args[flag] == "--host" {
+            let lan = args.iter().any(|a| a == "--lan");
+            match value {
+                Some(v) => v.parse().map_err(|_| format!("Invalid port: {}", v)).and_then(|port| Self::host(port, lan, key, level)),
+                None => Self::host(DEFAULT_PORT, lan, key, level),
+            }
+        } else {
+            match value {
+                Some(addr) => Self::join(addr, key),
+                None => Err("--join needs a host:port address".to_string()),
+            }
+        })
+    }
+
+    /// Send to every welcomed connection except `skip`, dropping the ones
+    /// that closed
+    fn broadcast(&mut self, message: &CollabMessage, skip: Option<u8>) {
+        let host = self.listener.is_some();
+        self.connections.retain(|c| {
+            if Some(c.peer) == skip || (host && !c.welcomed) {
+                return true;
+            }
+            match c.send(message) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("Lost co-op peer {}: {}", c.peer, e);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Exchange edits with the peers; call once per frame
+    pub fn poll(&mut self, state: &mut EditorState) {
+        self.accept_peers();
+
+        // Local edits go out before incoming ones land, so a remote edit to
+        // the same sector can't overwrite one we haven't sent yet
+        if !self.waiting_for_level {
+            self.send_changes(state);
+        }
+
+        while let Ok((from, message)) = self.inbox.try_recv() {
+            // Until a peer gave the key, the host only listens for it
+            if self.listener.is_some() && message.is_some() {
+                let Some(connection) = self.connections.iter().find(|c| c.peer == from) else { continue };
+                if !connection.welcomed {
+                    self.welcome(from, message, state);
+                    continue;
+                }
+            }
+            match message {
+                None => {
+                    log::info!("Co-op peer {} disconnected", from);
+                    self.connections.retain(|c| c.peer != from);
+                    self.selections.remove(&from);
+                    if self.listener.is_none() {
+                        state.set_status("Disconnected from the co-op host", 5.0);
+                    }
+                }
+                Some(CollabMessage::Hello { .. }) => {}
+                Some(CollabMessage::Welcome { peer, level }) => match load_level_from_str(&level) {
+                    Ok(level) => {
+                        self.peer = peer;
+                        self.synced = level.rooms.clone();
+                        self.synced_settings = LevelSettings::of(&level);
+                        self.waiting_for_level = false;
+                        state.apply_merged_level(level, "Joined co-op session");
+                        state.set_status(&format!("Joined co-op session as peer {}", peer), 3.0);
+                    }
+                    Err(e) => log::error!("Bad level from co-op host: {}", e),
+                },
+                Some(CollabMessage::Edits(edits)) => {
+                    self.apply_edits(state, &edits);
+                    if self.listener.is_some() {
+                        // Echoed to the sender too, which puts every peer in the host's order
+                        self.broadcast(&CollabMessage::Edits(edits), None);
+                    }
+                }
+                Some(CollabMessage::Selection { peer, cells }) => {
+                    if self.listener.is_some() {
+                        self.broadcast(&CollabMessage::Selection { peer, cells: cells.clone() }, Some(from));
+                    }
+                    self.selections.insert(peer, cells);
+                }
+            }
+        }
+
+        state.peer_selections = self
+            .selections
+            .iter()
+            .filter(|(&peer, _)| peer != self.peer)
+            .map(|(&peer, cells)| (PEER_COLORS[peer as usize % PEER_COLORS.len()], cells.clone()))
+            .collect();
+    }
+
+    /// Take connections to a host; they're sent the level once they give the key
+    fn accept_peers(&mut self) {
+        let Some(listener) = &self.listener else { return };
+        while let Ok((stream, addr)) = listener.accept() {
+            let peer = self.next_peer;
+            self.next_peer = self.next_peer.wrapping_add(1).max(1);
+            // Accepted streams may inherit the listener's non-blocking mode
+            let result = stream.set_nonblocking(false).map_err(|e| e.to_string())
+                .and_then(|()| Connection::open(peer, stream, self.inbox_sender.clone()));
+            match result {
+                Ok(connection) => {
+                    log::info!("Co-op peer {} connecting from {}", peer, addr);
+                    self.connections.push(connection);
+                }
+                Err(e) => log::warn!("Co-op peer from {} failed to connect: {}", addr, e),
+            }
+        }
+    }
+
+    /// Host only: send the level to a peer that gave the session key, and
+    /// drop any other peer
+    fn welcome(&mut self, peer: u8, message: Option<CollabMessage>, state: &EditorState) {
+        let result = match message {
+            Some(CollabMessage::Hello { key }) if key == self.key => ron::ser::to_string(&state.level)
+                .map_err(|e| e.to_string())
+                .and_then(|level| self.connection(peer).send(&CollabMessage::Welcome { peer, level })),
+            _ => Err("wrong session key".to_string()),
+        };
+        match result {
+            Ok(()) => {
+                log::info!("Co-op peer {} joined", peer);
+                self.connections.iter_mut().filter(|c| c.peer == peer).for_each(|c| c.welcomed = true);
+            }
+            Err(e) => {
+                log::warn!("Turned away co-op peer {}: {}", peer, e);
+                self.connections.retain(|c| c.peer != peer);
+            }
+        }
+    }
+
+    fn connection(&self, peer: u8) -> &Connection {
+        self.connections.iter().find(|c| c.peer == peer).expect("peer is connected")
+    }
+
+    /// Put received edits into the level
+    fn apply_edits(&mut self, state: &mut EditorState, edits: &[LevelEdit]) {
+        for edit in edits {
+            match edit {
+                LevelEdit::Sector { room, .. } | LevelEdit::Properties { room, .. } | LevelEdit::Room { room, .. } => {
+                    state.mesh_cache.invalidate(*room);
+                }
+                LevelEdit::Settings(settings) if settings.sector_size != state.level.sector_size => {
+                    state.mesh_cache.invalidate_all();
+                    state.grid_size = settings.sector_size;
+                }
+                _ => {}
+            }
+            apply_edit(&mut state.level, edit);
+        }
+        self.synced = state.level.rooms.clone();
+        self.synced_settings = LevelSettings::of(&state.level);
+        if state.current_room >= state.level.rooms.len() {
+            state.current_room = 0;
+        }
+        state.dirty = true;
+    }
+
+    /// Send edits and the selection if they changed since the last sync
+    fn send_changes(&mut self, state: &EditorState) {
+        // Settings go first so a new sector size lands before the rooms it rescaled
+        let mut edits = Vec::new();
+        let settings = LevelSettings::of(&state.level);
+        if settings != self.synced_settings {
+            edits.push(LevelEdit::Settings(Box::new(settings.clone())));
+            self.synced_settings = settings;
+        }
+        let rooms = &state.level.rooms;
+        edits.extend(diff_rooms(&self.synced, rooms));
+        if !edits.is_empty() {
+            self.synced = rooms.clone();
+            self.broadcast(&CollabMessage::Edits(edits), None);
+        }
+
+        let selection: PeerSelection = std::iter::once(&state.selection)
+            .chain(&state.multi_selection)
+            .filter_map(|s| s.sector_coords())
+            .collect();
+        if selection != self.selection {
+            self.selection = selection.clone();
+            self.broadcast(&CollabMessage::Selection { peer: self.peer, cells: selection }, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{LevelEvent, TextureRef};
+
+    #[test]
+    fn test_session_flags() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let level = Level::new();
+        assert!(CollabSession::from_args(&args("level.ron"), &level).is_none());
+        assert!(CollabSession::from_args(&args("--join"), &level).unwrap().is_err());
+        assert!(CollabSession::from_args(&args("--host nope"), &level).unwrap().is_err());
+        // The network only gets a session behind a key
+        assert!(CollabSession::from_args(&args("--host 0 --lan"), &level).unwrap().is_err());
+        assert_eq!(arg_value(&args("--join a:1 --key s3cret"), "--key").map(String::as_str), Some("s3cret"));
+    }
+
+    #[test]
+    fn test_messages_round_trip_as_lines() {
+        let message = CollabMessage::Edits(vec![
+            LevelEdit::Room { room: 0, data: Room::new(0, Vec3::ZERO, 1, 1) },
+            LevelEdit::Truncate { count: 1 },
+        ]);
+        let line = ron::ser::to_string(&message).unwrap();
+        assert!(!line.contains('\n'));
+        let CollabMessage::Edits(edits) = ron::from_str(&line).unwrap() else { panic!() };
+        assert_eq!(edits.len(), 2);
+    }
+
+    #[test]
+    fn test_sector_edits_travel_alone() {
+        let old = vec![Room::new(0, Vec3::ZERO, 4, 4)];
+        let mut new = old.clone();
+        new[0].sectors[2][1] = Some(Sector::empty());
+        new[0].ambient = 0.9;
+        let edits = diff_rooms(&old, &new);
+        assert_eq!(edits.len(), 2);
+        assert!(matches!(edits[0], LevelEdit::Sector { room: 0, x: 2, z: 1, .. }));
+        assert!(matches!(&edits[1], LevelEdit::Properties { properties, .. } if properties.sectors.is_empty()));
+
+        let mut level = Level::new();
+        level.rooms = old.clone();
+        for edit in &edits {
+            apply_edit(&mut level, edit);
+        }
+        assert_eq!(level.rooms[0].sectors, new[0].sectors);
+        assert_eq!(level.rooms[0].ambient, 0.9);
+        assert!(diff_rooms(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_last_sector_write_in_host_order_wins() {
+        let base = vec![Room::new(0, Vec3::ZERO, 2, 2)];
+        let edit = |floor: f32| {
+            let mut rooms = base.clone();
+            rooms[0].sectors[0][0] = Some(Sector::with_floor(floor, TextureRef::none()));
+            diff_rooms(&base, &rooms)
+        };
+        let (a, b) = (edit(1.0), edit(2.0));
+
+        // Each peer has applied its own edit; the host relays a, then b, to both
+        let mut peer_a = Level::new();
+        peer_a.rooms = base.clone();
+        let mut peer_b = peer_a.clone();
+        for (peer, local) in [(&mut peer_a, &a), (&mut peer_b, &b)] {
+            for e in local.iter().chain(&a).chain(&b) {
+                apply_edit(peer, e);
+            }
+        }
+        assert_eq!(peer_a.rooms, peer_b.rooms);
+        assert_eq!(peer_a.rooms[0].sectors[0][0].as_ref().unwrap().floor.as_ref().unwrap().heights[0], 2.0);
+    }
+
+    #[test]
+    fn test_level_settings_travel_without_stats() {
+        let old = Level::new();
+        let mut new = old.clone();
+        new.events.push(LevelEvent::default());
+        new.music = Some("boss".to_string());
+        new.metadata.stats.operations = 12;
+        assert_eq!(LevelSettings::of(&old), LevelSettings::of(&Level { metadata: new.metadata.clone(), ..old.clone() }));
+
+        let edit = LevelEdit::Settings(Box::new(LevelSettings::of(&new)));
+        let line = ron::ser::to_string(&edit).unwrap();
+        let mut level = old.clone();
+        level.metadata.stats.operations = 3;
+        apply_edit(&mut level, &ron::from_str(&line).unwrap());
+        assert_eq!(level.events, new.events);
+        assert_eq!(level.music.as_deref(), Some("boss"));
+        assert_eq!(level.metadata.stats.operations, 3);
+    }
+}
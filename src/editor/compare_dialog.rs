@@ -0,0 +1,102 @@
+//! Compare/Merge dialog
+//!
+//! Modal listing the structural differences between the open level and
+//! another version of it. Given a common base version it also shows the
+//! three-way merge's conflicts and can apply the merge.
+
+use macroquad::prelude::*;
+use crate::ui::{Rect, UiContext, draw_scrollable_list, draw_text_button, draw_text_button_enabled, ui_screen_width, ui_screen_height, ACCENT_COLOR};
+use crate::world::{LevelDiff, MergeResult};
+
+/// State for the compare/merge dialog
+#[derive(Default)]
+pub struct CompareDialog {
+    pub open: bool,
+    /// File name of the version compared against
+    pub other_name: String,
+    /// Changes from the open level to the other version
+    pub diff: LevelDiff,
+    /// Merge of both versions, when a base was given
+    pub merge: Option<MergeResult>,
+    pub scroll_offset: f32,
+}
+
+/// What the user chose in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareAction {
+    None,
+    ApplyMerge,
+    Close,
+}
+
+impl CompareDialog {
+    /// Open the dialog with a comparison (and optionally a merge)
+    pub fn show(&mut self, other_name: String, diff: LevelDiff, merge: Option<MergeResult>) {
+        *self = Self { open: true, other_name, diff, merge, scroll_offset: 0.0 };
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Rows shown in the list: conflicts first, then the changes
+    fn rows(&self) -> Vec<String> {
+        let conflicts = self.merge.iter().flat_map(|m| &m.conflicts);
+        conflicts
+            .map(|c| format!("! conflict at {} (keeping ours)", c))
+            .chain(self.diff.changes.iter().map(|c| c.to_string()))
+            .collect()
+    }
+}
+
+/// Draw the compare/merge dialog
+pub fn draw_compare_dialog(ctx: &mut UiContext, dialog: &mut CompareDialog) -> CompareAction {
+    if !dialog.open {
+        return CompareAction::None;
+    }
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = (ui_screen_width() * 0.6).clamp(320.0, 560.0);
+    let dialog_h = (ui_screen_height() * 0.7).clamp(240.0, 480.0);
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+    let header_h = 36.0;
+    let footer_h = 44.0;
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    let title = if dialog.merge.is_some() { "Merge with" } else { "Compare with" };
+    draw_text(&format!("{} {}", title, dialog.other_name), x + 16.0, y + 24.0, 18.0, WHITE);
+
+    // Summary
+    let mut summary = dialog.diff.summary();
+    if let Some(merge) = &dialog.merge {
+        summary = format!("{} - {} conflict(s)", summary, merge.conflicts.len());
+    }
+    draw_text(&summary, x + 16.0, y + header_h + 20.0, 14.0, Color::from_rgba(180, 180, 180, 255));
+
+    // Change list
+    let list_rect = Rect::new(x + 8.0, y + header_h + 32.0, dialog_w - 16.0, dialog_h - header_h - 32.0 - footer_h);
+    draw_rectangle(list_rect.x, list_rect.y, list_rect.w, list_rect.h, Color::from_rgba(25, 25, 30, 255));
+    let rows = dialog.rows();
+    draw_scrollable_list(ctx, list_rect, &rows, None, &mut dialog.scroll_offset, 18.0, None);
+
+    // Footer
+    let button_y = y + dialog_h - footer_h + 8.0;
+    let close_rect = Rect::new(x + dialog_w - 90.0, button_y, 80.0, 28.0);
+    if draw_text_button(ctx, close_rect, "Close", Color::from_rgba(60, 60, 70, 255)) || is_key_pressed(KeyCode::Escape) {
+        return CompareAction::Close;
+    }
+    if dialog.merge.is_some() {
+        let merge_rect = Rect::new(close_rect.x - 130.0, button_y, 120.0, 28.0);
+        if draw_text_button_enabled(ctx, merge_rect, "Apply merge", ACCENT_COLOR, !dialog.diff.is_empty()) {
+            return CompareAction::ApplyMerge;
+        }
+    }
+    CompareAction::None
+}
@@ -3,12 +3,17 @@
 //! Modal dialog for browsing and previewing bundled example levels.
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, draw_icon_centered, draw_scrollable_list, ACCENT_COLOR};
-use crate::world::Level;
+use crate::ui::{Rect, UiContext, draw_icon_centered, draw_scrollable_list, draw_text_button, draw_text_button_enabled, ui_screen_width, ui_screen_height, ACCENT_COLOR};
+use crate::world::{Level, LevelMetadata};
 use crate::rasterizer::{Framebuffer, Texture as RasterTexture, Camera, render_mesh, Color as RasterColor, Vec3, RasterSettings};
-use super::example_levels::{ExampleLevelInfo, LevelStats, get_level_stats};
+use super::example_levels::{ExampleLevelInfo, LevelStats, get_level_stats, render_missing_thumbnails};
 use super::TexturePack;
 
+/// Auto-orbit speed of the preview camera, in radians per second
+const ORBIT_SPEED: f32 = 0.3;
+/// Seconds after the last drag or zoom before the auto-orbit resumes
+const ORBIT_RESUME_DELAY: f32 = 3.0;
+
 /// State for the example browser dialog
 pub struct ExampleBrowser {
     /// Whether the browser is open
@@ -29,8 +34,12 @@ pub struct ExampleBrowser {
     /// Mouse state for orbit control
     pub dragging: bool,
     pub last_mouse: (f32, f32),
+    /// Seconds since the user last moved the camera (auto-orbit runs past `ORBIT_RESUME_DELAY`)
+    pub orbit_idle: f32,
     /// Scroll offset for the list
     pub scroll_offset: f32,
+    /// Embedded thumbnails, one per example (None if the file has none)
+    pub thumbnails: Vec<Option<Texture2D>>,
 }
 
 impl Default for ExampleBrowser {
@@ -47,15 +56,23 @@ impl Default for ExampleBrowser {
             orbit_center: (0.0, 0.0, 0.0),
             dragging: false,
             last_mouse: (0.0, 0.0),
+            orbit_idle: ORBIT_RESUME_DELAY,
             scroll_offset: 0.0,
+            thumbnails: Vec::new(),
         }
     }
 }
 
 impl ExampleBrowser {
-    /// Open the browser with the given list of examples
-    pub fn open(&mut self, examples: Vec<ExampleLevelInfo>) {
+    /// Open the browser with the given list of examples (levels without a
+    /// thumbnail get one rendered with `texture_packs`)
+    pub fn open(&mut self, mut examples: Vec<ExampleLevelInfo>, texture_packs: &[TexturePack]) {
+        render_missing_thumbnails(&mut examples, texture_packs);
         self.open = true;
+        self.thumbnails = examples
+            .iter()
+            .map(|e| e.metadata.as_ref().and_then(thumbnail_texture))
+            .collect();
         self.examples = examples;
         self.selected_index = None;
         self.preview_level = None;
@@ -67,12 +84,11 @@ impl ExampleBrowser {
     pub fn close(&mut self) {
         self.open = false;
         self.preview_level = None;
+        self.thumbnails.clear();
     }
 
     /// Set the preview level (called after async load)
     pub fn set_preview(&mut self, level: Level) {
-        use crate::world::SECTOR_SIZE;
-
         // Calculate bounding box of all rooms to find center
         let mut min_x = f32::MAX;
         let mut max_x = f32::MIN;
@@ -83,9 +99,9 @@ impl ExampleBrowser {
 
         for room in &level.rooms {
             let room_min_x = room.position.x;
-            let room_max_x = room.position.x + (room.width as f32) * SECTOR_SIZE;
+            let room_max_x = room.position.x + (room.width as f32) * room.sector_size;
             let room_min_z = room.position.z;
-            let room_max_z = room.position.z + (room.depth as f32) * SECTOR_SIZE;
+            let room_max_z = room.position.z + (room.depth as f32) * room.sector_size;
 
             min_x = min_x.min(room_min_x);
             max_x = max_x.max(room_max_x);
@@ -132,12 +148,24 @@ impl ExampleBrowser {
         let diagonal = (size_x * size_x + size_y * size_y + size_z * size_z).sqrt();
         self.orbit_distance = diagonal.max(2000.0) * 1.2;
 
+        // Fill in metadata that couldn't be read up front
+        if let Some(i) = self.selected_index {
+            if let Some(example) = self.examples.get_mut(i) {
+                if example.metadata.is_none() {
+                    self.thumbnails[i] = thumbnail_texture(&level.metadata);
+                    example.metadata = Some(level.metadata.clone());
+                }
+            }
+        }
+
         self.preview_stats = Some(get_level_stats(&level));
         self.preview_level = Some(level);
 
         // Reset orbit angle - start looking at level from an angle
         self.orbit_yaw = 0.8;
         self.orbit_pitch = 0.4;
+        self.dragging = false;
+        self.orbit_idle = ORBIT_RESUME_DELAY;
     }
 
     /// Get the currently selected example info
@@ -175,14 +203,13 @@ pub fn draw_example_browser(
     let mut action = BrowserAction::None;
 
     // Darken background
-    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 180));
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
 
     // Dialog dimensions (centered, ~80% of screen)
-    let dialog_w = (screen_width() * 0.8).min(900.0);
-    let dialog_h = (screen_height() * 0.8).min(600.0);
-    let dialog_x = (screen_width() - dialog_w) / 2.0;
-    let dialog_y = (screen_height() - dialog_h) / 2.0;
-    let dialog_rect = Rect::new(dialog_x, dialog_y, dialog_w, dialog_h);
+    let dialog_w = (ui_screen_width() * 0.8).min(900.0);
+    let dialog_h = (ui_screen_height() * 0.8).min(600.0);
+    let dialog_x = (ui_screen_width() - dialog_w) / 2.0;
+    let dialog_y = (ui_screen_height() - dialog_h) / 2.0;
 
     // Draw dialog background
     draw_rectangle(dialog_x, dialog_y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
@@ -221,6 +248,21 @@ pub fn draw_example_browser(
         None, // Use default colors
     );
 
+    // Thumbnails on the right of each visible row
+    let thumb_h = item_h - 6.0;
+    let thumb_w = (thumb_h * 4.0 / 3.0).round();
+    for (i, thumb) in browser.thumbnails.iter().enumerate() {
+        let Some(texture) = thumb else { continue };
+        let y = list_rect.y + i as f32 * item_h - browser.scroll_offset + 3.0;
+        if y < list_rect.y || y + thumb_h > list_rect.bottom() {
+            continue;
+        }
+        draw_texture_ex(texture, list_rect.right() - thumb_w - 12.0, y, WHITE, DrawTextureParams {
+            dest_size: Some(vec2(thumb_w, thumb_h)),
+            ..Default::default()
+        });
+    }
+
     // Handle list click
     if let Some(clicked_idx) = list_result.clicked {
         if browser.selected_index != Some(clicked_idx) {
@@ -248,13 +290,26 @@ pub fn draw_example_browser(
         if let Some(stats) = &browser.preview_stats {
             let stats_y = preview_rect.bottom() - 24.0;
             draw_rectangle(preview_rect.x, stats_y, preview_rect.w, 24.0, Color::from_rgba(30, 30, 35, 200));
-            let stats_text = format!(
+            let mut stats_text = format!(
                 "Rooms: {}  Sectors: {}  Floors: {}  Walls: {}",
                 stats.room_count, stats.sector_count, stats.floor_count, stats.wall_count
             );
+            if let Some(saved) = browser.selected_example().and_then(|e| e.metadata.as_ref()).and_then(saved_info) {
+                stats_text = format!("{}  {}", stats_text, saved);
+            }
             draw_text(&stats_text, preview_rect.x + 8.0, stats_y + 17.0, 14.0, Color::from_rgba(180, 180, 180, 255));
         }
     } else if has_selection {
+        // Embedded thumbnail stands in until the level has loaded
+        let thumb = browser.selected_index.and_then(|i| browser.thumbnails.get(i)).and_then(|t| t.as_ref());
+        if let Some(texture) = thumb {
+            let w = preview_rect.w.min(preview_rect.h * 4.0 / 3.0);
+            let h = w * 3.0 / 4.0;
+            draw_texture_ex(texture, preview_rect.x + (preview_rect.w - w) / 2.0, preview_rect.y + (preview_rect.h - h) / 2.0, WHITE, DrawTextureParams {
+                dest_size: Some(vec2(w, h)),
+                ..Default::default()
+            });
+        }
         // Loading indicator
         draw_text("Loading preview...", preview_rect.x + 20.0, preview_rect.y + 40.0, 16.0, Color::from_rgba(150, 150, 150, 255));
     } else {
@@ -293,6 +348,24 @@ pub fn draw_example_browser(
     action
 }
 
+/// GPU texture for a level's embedded thumbnail
+fn thumbnail_texture(metadata: &LevelMetadata) -> Option<Texture2D> {
+    let (pixels, w, h) = metadata.thumbnail_pixels()?;
+    let texture = Texture2D::from_rgba8(w as u16, h as u16, &pixels);
+    texture.set_filter(FilterMode::Nearest);
+    Some(texture)
+}
+
+/// "Saved <date> (v<version>)" from a level's metadata, if it was ever saved
+fn saved_info(metadata: &LevelMetadata) -> Option<String> {
+    let date = metadata.modified_date()?;
+    if metadata.editor_version.is_empty() {
+        Some(format!("Saved {}", date))
+    } else {
+        Some(format!("Saved {} (v{})", date, metadata.editor_version))
+    }
+}
+
 /// Draw the orbit preview of a level
 fn draw_orbit_preview(
     ctx: &mut UiContext,
@@ -309,28 +382,35 @@ fn draw_orbit_preview(
         None => return,
     };
 
-    // Handle mouse drag for orbit
-    if ctx.mouse.inside(&rect) {
-        if ctx.mouse.left_down {
-            if browser.dragging {
-                let dx = ctx.mouse.x - browser.last_mouse.0;
-                let dy = ctx.mouse.y - browser.last_mouse.1;
-                browser.orbit_yaw += dx * 0.01;
-                browser.orbit_pitch = (browser.orbit_pitch + dy * 0.01).clamp(-1.4, 1.4);
-            }
-            browser.dragging = true;
-            browser.last_mouse = (ctx.mouse.x, ctx.mouse.y);
-        } else {
-            browser.dragging = false;
-        }
+    // Drag to orbit: starts on the preview, follows the mouse until release
+    if ctx.mouse.left_pressed && ctx.mouse.inside(&rect) {
+        browser.dragging = true;
+        browser.last_mouse = (ctx.mouse.x, ctx.mouse.y);
+    } else if !ctx.mouse.left_down {
+        browser.dragging = false;
+    }
+    if browser.dragging {
+        let dx = ctx.mouse.x - browser.last_mouse.0;
+        let dy = ctx.mouse.y - browser.last_mouse.1;
+        browser.orbit_yaw += dx * 0.01;
+        browser.orbit_pitch = (browser.orbit_pitch + dy * 0.01).clamp(-1.4, 1.4);
+        browser.last_mouse = (ctx.mouse.x, ctx.mouse.y);
+        browser.orbit_idle = 0.0;
+    }
+
+    // Scroll to zoom (proportional, so small and large levels zoom alike)
+    let scroll = ctx.mouse.scroll;
+    if scroll != 0.0 && ctx.mouse.inside(&rect) {
+        browser.orbit_distance = (browser.orbit_distance * 0.975f32.powf(scroll)).clamp(500.0, 20000.0);
+        browser.orbit_idle = 0.0;
+    }
 
-        // Scroll to zoom
-        let scroll = mouse_wheel().1;
-        if scroll != 0.0 {
-            browser.orbit_distance = (browser.orbit_distance - scroll * 100.0).clamp(500.0, 20000.0);
+    // Turn slowly on its own until the user takes over
+    if !browser.dragging {
+        browser.orbit_idle += get_frame_time();
+        if browser.orbit_idle >= ORBIT_RESUME_DELAY {
+            browser.orbit_yaw += ORBIT_SPEED * get_frame_time();
         }
-    } else {
-        browser.dragging = false;
     }
 
     // Calculate camera position from orbit using spherical coordinates
@@ -445,6 +525,7 @@ fn draw_orbit_preview(
             ..Default::default()
         },
     );
+    draw_text("Drag to rotate, scroll to zoom", rect.x + 8.0, rect.y + 18.0, 14.0, Color::from_rgba(150, 150, 150, 200));
 }
 
 /// Draw a close button (X)
@@ -462,32 +543,3 @@ fn draw_close_button(ctx: &mut UiContext, rect: Rect, icon_font: Option<&Font>)
 
     clicked
 }
-
-/// Draw a text button
-fn draw_text_button(ctx: &mut UiContext, rect: Rect, text: &str, bg_color: Color) -> bool {
-    draw_text_button_enabled(ctx, rect, text, bg_color, true)
-}
-
-/// Draw a text button with enabled state
-fn draw_text_button_enabled(ctx: &mut UiContext, rect: Rect, text: &str, bg_color: Color, enabled: bool) -> bool {
-    let hovered = enabled && ctx.mouse.inside(&rect);
-    let clicked = hovered && ctx.mouse.left_pressed;
-
-    let color = if !enabled {
-        Color::from_rgba(50, 50, 55, 255)
-    } else if hovered {
-        Color::new(bg_color.r * 1.2, bg_color.g * 1.2, bg_color.b * 1.2, bg_color.a)
-    } else {
-        bg_color
-    };
-
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
-
-    let text_color = if enabled { WHITE } else { Color::from_rgba(100, 100, 100, 255) };
-    let dims = measure_text(text, None, 14, 1.0);
-    let tx = rect.x + (rect.w - dims.width) / 2.0;
-    let ty = rect.y + (rect.h + dims.height) / 2.0 - 2.0;
-    draw_text(text, tx, ty, 14.0, text_color);
-
-    clicked
-}
@@ -1,12 +1,22 @@
 //! Level browser for the editor
 //!
-//! Handles loading bundled levels from disk (native) or via manifest (WASM).
+//! Lists bundled levels from disk (native). The web build has no directory
+//! to list, so the curated examples are compiled into it instead. Levels
+//! saved before thumbnails existed get one rendered when listed.
 
 use std::path::PathBuf;
-use crate::world::{Level, load_level};
+use crate::world::{Level, LevelMetadata};
+use super::{render_thumbnail, TexturePack};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::world::{load_level, read_level_metadata};
+
+#[cfg(target_arch = "wasm32")]
+use crate::world::{level_metadata_from_str, load_level_from_str};
+
+// Every level of assets/levels, listed by build.rs
 #[cfg(target_arch = "wasm32")]
-use crate::world::load_level_from_str;
+include!(concat!(env!("OUT_DIR"), "/embedded_levels.rs"));
 
 /// Metadata about a level (without loading the full level)
 #[derive(Debug, Clone)]
@@ -15,6 +25,8 @@ pub struct ExampleLevelInfo {
     pub name: String,
     /// Full path to the level file
     pub path: PathBuf,
+    /// Thumbnail and save info embedded in the file (None if not read)
+    pub metadata: Option<LevelMetadata>,
 }
 
 /// Discover all levels in the levels directory (native)
@@ -32,7 +44,8 @@ pub fn discover_examples() -> Vec<ExampleLevelInfo> {
                     .file_stem()
                     .map(|s| s.to_string_lossy().to_string())
                     .unwrap_or_else(|| "unnamed".to_string());
-                levels.push(ExampleLevelInfo { name, path });
+                let metadata = read_level_metadata(&path).ok();
+                levels.push(ExampleLevelInfo { name, path, metadata });
             }
         }
     }
@@ -41,63 +54,53 @@ pub fn discover_examples() -> Vec<ExampleLevelInfo> {
     levels
 }
 
-/// Discover all levels from manifest (WASM)
+/// The levels compiled into the build (WASM)
 #[cfg(target_arch = "wasm32")]
 pub fn discover_examples() -> Vec<ExampleLevelInfo> {
-    // On WASM, we return empty here and load async later
-    Vec::new()
+    EMBEDDED_LEVELS
+        .iter()
+        .map(|(name, source)| ExampleLevelInfo {
+            name: name.to_string(),
+            path: PathBuf::from(format!("assets/levels/{}.ron", name)),
+            metadata: level_metadata_from_str(source).ok(),
+        })
+        .collect()
 }
 
-/// Load level list from manifest asynchronously (for WASM)
-pub async fn load_example_list() -> Vec<ExampleLevelInfo> {
-    use macroquad::prelude::*;
-
-    // Load and parse manifest
-    let manifest = match load_string("assets/levels/manifest.txt").await {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Failed to load levels manifest: {}", e);
-            return Vec::new();
-        }
-    };
-
-    let mut levels = Vec::new();
-
-    for line in manifest.lines() {
-        let line = line.trim();
-        if line.is_empty() || !line.ends_with(".ron") {
-            continue;
-        }
-
-        let name = line
-            .strip_suffix(".ron")
-            .unwrap_or(line)
-            .to_string();
-        let path = PathBuf::from(format!("assets/levels/{}", line));
-
-        levels.push(ExampleLevelInfo { name, path });
-    }
-
-    levels
-}
-
-/// Load a specific example level by path
-pub async fn load_example_level(path: &PathBuf) -> Option<Level> {
+/// Load a listed level: from its file, or from the build itself on the web
+pub fn load_example(example: &ExampleLevelInfo) -> Result<Level, String> {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        load_level(path).ok()
+        load_level(&example.path).map_err(|e| e.to_string())
     }
 
     #[cfg(target_arch = "wasm32")]
     {
-        use macroquad::prelude::*;
-        let path_str = path.to_string_lossy();
-        match load_string(&path_str).await {
-            Ok(contents) => load_level_from_str(&contents).ok(),
+        let (_, source) = EMBEDDED_LEVELS
+            .iter()
+            .find(|(name, _)| *name == example.name)
+            .ok_or_else(|| format!("{} is not part of this build", example.name))?;
+        load_level_from_str(source).map_err(|e| e.to_string())
+    }
+}
+
+/// Render a thumbnail for every listed level saved without one
+pub fn render_missing_thumbnails(examples: &mut [ExampleLevelInfo], texture_packs: &[TexturePack]) {
+    for example in examples {
+        if example.metadata.as_ref().is_some_and(|m| m.thumbnail.is_some()) {
+            continue;
+        }
+        let level = match load_example(example) {
+            Ok(level) => level,
             Err(e) => {
-                eprintln!("Failed to load example level: {}", e);
-                None
+                log::warn!("No thumbnail for {}: {}", example.name, e);
+                continue;
             }
+        };
+        let fb = render_thumbnail(&level, texture_packs);
+        let metadata = example.metadata.get_or_insert(level.metadata);
+        if let Err(e) = metadata.set_thumbnail(&fb.pixels, fb.width, fb.height) {
+            log::warn!("{}", e);
         }
     }
 }
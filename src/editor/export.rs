@@ -0,0 +1,300 @@
+//! Game export - bundles a project into a stand-alone, player-only build
+//!
+//! Output layout:
+//! - `game.ron` manifest (its presence boots the executable into the player)
+//! - the current executable, renamed after the game
+//! - `assets/` (levels, textures, soundfonts, songs, fonts, project config)
+//! - `web/` with the WASM player, if one has been built
+//!
+//! Exported levels are compiled: the textures each one uses are packed into
+//! atlases, saved as texture pack `atlas-<level>`, and its faces point at
+//! them with UVs rewritten to match. They are also split: each room's sectors
+//! go to `<level>.rooms/<index>.ron`, loaded by the game as the player nears
+//! the room.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::game::{FrontEndConfig, GameManifest, DEFAULT_STREAM_HOPS, FRONT_END_CONFIG_PATH, GAME_MANIFEST_PATH};
+use crate::rasterizer::{pack_atlases, Texture, Vec2, ATLAS_SIZE};
+use crate::world::{load_level, save_split_level, Level, Sector, TextureRef};
+use super::TexturePack;
+
+/// Asset directories copied into every export
+const EXPORT_ASSET_DIRS: [&str; 6] = [
+    "assets/levels",
+    "assets/textures",
+    "assets/soundfonts",
+    "assets/songs",
+    "assets/fonts",
+    "assets/project",
+];
+
+/// WASM player built with `cargo build --release --target wasm32-unknown-unknown --features player`
+const WASM_PLAYER_PATH: &str = "target/wasm32-unknown-unknown/release/bonnie-engine.wasm";
+
+/// Web shell files copied next to the WASM player
+const WEB_SHELL_FILES: [&str; 4] = [
+    "docs/index.html",
+    "docs/audio-processor.js",
+    "docs/mq_js_bundle.js",
+    "docs/favicon-32.png",
+];
+
+/// Summary of a finished export
+#[derive(Debug, Default)]
+pub struct ExportReport {
+    pub files_copied: usize,
+    pub levels: usize,
+    /// Texture atlases written for the levels
+    pub atlases: usize,
+    pub warnings: Vec<String>,
+}
+
+/// Copy a directory tree, returning the number of files copied
+fn copy_dir(src: &Path, dst: &Path) -> Result<usize, String> {
+    fs::create_dir_all(dst).map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+    let entries = fs::read_dir(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+
+    let mut count = 0;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let target = dst.join(entry.file_name());
+        if path.is_dir() {
+            count += copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Level files bundled with the game, relative to the export root
+fn bundled_levels(levels_dir: &Path) -> Vec<PathBuf> {
+    let mut levels: Vec<PathBuf> = fs::read_dir(levels_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ron")))
+                .filter_map(|p| p.file_name().map(|n| Path::new("assets/levels").join(n)))
+                .collect()
+        })
+        .unwrap_or_default();
+    levels.sort();
+    levels
+}
+
+/// Call `f` with the texture, stored UVs and UVs as drawn of every floor,
+/// ceiling and wall of a level
+fn for_each_face(level: &mut Level, mut f: impl FnMut(&mut TextureRef, &mut Option<[Vec2; 4]>, [Vec2; 4])) {
+    for room in &mut level.rooms {
+        for sector in room.sectors.iter_mut().flatten().flatten() {
+            let Sector { floor, ceiling, walls_north, walls_east, walls_south, walls_west } = sector;
+            for face in floor.iter_mut().chain(ceiling.iter_mut()) {
+                let uvs = face.uvs();
+                f(&mut face.texture, &mut face.uv, uvs);
+            }
+            for wall in walls_north.iter_mut().chain(walls_east).chain(walls_south).chain(walls_west) {
+                let uvs = wall.uvs();
+                f(&mut wall.texture, &mut wall.uv, uvs);
+            }
+        }
+    }
+}
+
+/// Pack the pack textures a level's faces use into atlases (`atlas_0`,
+/// `atlas_1`.. of texture pack `pack`) and point those faces at them, with
+/// their UVs moved into the texture's region. Faces whose UVs tile (leave
+/// 0..1) keep their texture. Returns the atlases.
+pub fn compile_atlases(level: &mut Level, packs: &[TexturePack], pack: &str) -> Vec<Texture> {
+    let by_name: HashMap<(&str, &str), &Texture> = packs
+        .iter()
+        .flat_map(|p| p.textures.iter().map(move |t| ((p.name.as_str(), t.name.as_str()), t)))
+        .collect();
+    let in_range = |uvs: &[Vec2; 4]| uvs.iter().all(|c| (0.0..=1.0).contains(&c.x) && (0.0..=1.0).contains(&c.y));
+
+    // One slot per texture, in order of first use
+    let mut slots: HashMap<(String, String), usize> = HashMap::new();
+    let mut found: Vec<&Texture> = Vec::new();
+    for_each_face(level, |tex, _, uvs| {
+        let Some(texture) = by_name.get(&(tex.pack.as_str(), tex.name.as_str())).filter(|_| in_range(&uvs)) else { return };
+        slots.entry((tex.pack.clone(), tex.name.clone())).or_insert_with(|| {
+            found.push(texture);
+            found.len() - 1
+        });
+    });
+
+    let (atlases, regions) = pack_atlases(&found, ATLAS_SIZE);
+    for_each_face(level, |tex, uv, uvs| {
+        let Some(&slot) = slots.get(&(tex.pack.clone(), tex.name.clone())).filter(|_| in_range(&uvs)) else { return };
+        if let Some(region) = regions[slot] {
+            *uv = Some(uvs.map(|c| region.map_uv(c)));
+            *tex = TextureRef::new(pack, atlases[region.atlas].name.clone());
+        }
+    });
+    atlases
+}
+
+/// Compile an exported level in place: write its atlases as texture pack
+/// `atlas-<level>`, add the pack to the exported texture manifest, and split
+/// the level so the game streams its rooms' sectors (see `world::split_rooms`).
+/// Returns the number of atlases written.
+fn compile_level(out_dir: &Path, level_path: &Path, packs: &[TexturePack]) -> Result<usize, String> {
+    let path = out_dir.join(level_path);
+    let mut level = load_level(&path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+    let stem = level_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let pack_name = format!("atlas-{}", stem);
+    let atlases = compile_atlases(&mut level, packs, &pack_name);
+    let count = atlases.len();
+    if !atlases.is_empty() {
+        write_atlas_pack(out_dir, &pack_name, atlases)?;
+    }
+
+    save_split_level(&level, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(count)
+}
+
+/// Save a level's atlases as texture pack `pack_name` and list it in the
+/// exported texture manifest
+fn write_atlas_pack(out_dir: &Path, pack_name: &str, atlases: Vec<Texture>) -> Result<(), String> {
+    let textures_dir = out_dir.join("assets/textures");
+    let mut pack = TexturePack { name: pack_name.to_string(), path: textures_dir.join(pack_name), textures: Vec::new() };
+    fs::create_dir_all(&pack.path).map_err(|e| format!("Failed to create {}: {}", pack.path.display(), e))?;
+    let mut manifest = format!("\n[{}]\n", pack_name);
+    for atlas in atlases {
+        manifest.push_str(&format!("{}.png\n", atlas.name));
+        pack.save_texture(atlas)?;
+    }
+    let manifest_path = textures_dir.join("manifest.txt");
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&manifest_path)
+        .and_then(|mut f| f.write_all(manifest.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))
+}
+
+/// Export the project in the current directory to `out_dir`
+pub fn export_game(name: &str, out_dir: &Path) -> Result<ExportReport, String> {
+    let mut report = ExportReport::default();
+    fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+
+    // Assets
+    for dir in EXPORT_ASSET_DIRS {
+        let src = Path::new(dir);
+        if src.is_dir() {
+            report.files_copied += copy_dir(src, &out_dir.join(dir))?;
+        }
+    }
+
+    // Levels, compiled against the project's texture packs
+    let levels = bundled_levels(&out_dir.join("assets/levels"));
+    report.levels = levels.len();
+    let packs = TexturePack::discover_all();
+    for level in &levels {
+        match compile_level(out_dir, level, &packs) {
+            Ok(atlases) => report.atlases += atlases,
+            Err(e) => report.warnings.push(e),
+        }
+    }
+
+    // Manifest
+    let front_end = FrontEndConfig::load(Path::new(FRONT_END_CONFIG_PATH)).unwrap_or_else(|e| {
+        report.warnings.push(format!("{} - using default front end", e));
+        FrontEndConfig::default()
+    });
+    if !levels.contains(&front_end.first_level) {
+        report.warnings.push(format!("First level {} is not bundled", front_end.first_level.display()));
+    }
+    let manifest = GameManifest { name: name.to_string(), front_end, levels, stream_hops: DEFAULT_STREAM_HOPS, music: None };
+    let manifest_str = ron::ser::to_string_pretty(&manifest, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+    fs::write(out_dir.join(GAME_MANIFEST_PATH), manifest_str)
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+    // Native runtime: the engine binary boots into the player when game.ron is present
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+    let exe_name = match exe.extension() {
+        Some(ext) => format!("{}.{}", name, ext.to_string_lossy()),
+        None => name.to_string(),
+    };
+    fs::copy(&exe, out_dir.join(exe_name)).map_err(|e| format!("Failed to copy executable: {}", e))?;
+    report.files_copied += 1;
+
+    // Web runtime
+    let wasm = Path::new(WASM_PLAYER_PATH);
+    if wasm.exists() {
+        let web_dir = out_dir.join("web");
+        fs::create_dir_all(&web_dir).map_err(|e| format!("Failed to create {}: {}", web_dir.display(), e))?;
+        fs::copy(wasm, web_dir.join("bonnie-engine.wasm")).map_err(|e| format!("Failed to copy WASM player: {}", e))?;
+        report.files_copied += 1;
+        for file in WEB_SHELL_FILES {
+            let src = Path::new(file);
+            match src.file_name() {
+                Some(file_name) if src.exists() => {
+                    fs::copy(src, web_dir.join(file_name)).map_err(|e| format!("Failed to copy {}: {}", file, e))?;
+                    report.files_copied += 1;
+                }
+                _ => report.warnings.push(format!("Missing web file {}", file)),
+            }
+        }
+        fs::copy(out_dir.join(GAME_MANIFEST_PATH), web_dir.join(GAME_MANIFEST_PATH))
+            .map_err(|e| format!("Failed to copy manifest: {}", e))?;
+        report.files_copied += copy_dir(&out_dir.join("assets"), &web_dir.join("assets"))?;
+    } else {
+        report.warnings.push(
+            "No WASM player found - build with `cargo build --release --target wasm32-unknown-unknown --features player`".to_string(),
+        );
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_test_level;
+
+    #[test]
+    fn test_bundled_levels_only_ron() {
+        let dir = std::env::temp_dir().join(format!("bonnie_export_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b.ron"), "").unwrap();
+        fs::write(dir.join("a.RON"), "").unwrap();
+        fs::write(dir.join("manifest.txt"), "").unwrap();
+
+        let levels = bundled_levels(&dir);
+        assert_eq!(levels, vec![PathBuf::from("assets/levels/a.RON"), PathBuf::from("assets/levels/b.ron")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compile_atlases_rewrites_faces() {
+        let mut stone = Texture::new(16, 16);
+        stone.name = "stone".to_string();
+        let pack = TexturePack { name: "pack".to_string(), path: PathBuf::new(), textures: vec![stone] };
+
+        let mut level = create_test_level();
+        let stone_ref = TextureRef::new("pack", "stone");
+        let sector = level.rooms[0].get_sector_mut(0, 0).unwrap();
+        sector.floor.as_mut().unwrap().texture = stone_ref.clone();
+        // Tiles twice across: stays on its own texture
+        let ceiling = sector.ceiling.as_mut().unwrap();
+        ceiling.texture = stone_ref.clone();
+        ceiling.uv = Some([Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(2.0, 2.0), Vec2::new(0.0, 2.0)]);
+
+        let atlases = compile_atlases(&mut level, &[pack], "atlas-test");
+        assert_eq!(atlases.len(), 1);
+        let sector = level.rooms[0].get_sector(0, 0).unwrap();
+        let floor = sector.floor.as_ref().unwrap();
+        assert_eq!(floor.texture, TextureRef::new("atlas-test", "atlas_0"));
+        // The whole 16x16 texture now covers a corner of the atlas
+        let uv = floor.uv.unwrap();
+        assert!(uv.iter().all(|c| c.x < 16.0 / ATLAS_SIZE as f32 && c.y > 1.0 - 16.0 / ATLAS_SIZE as f32));
+        assert_eq!(sector.ceiling.as_ref().unwrap().texture, stone_ref);
+    }
+}
@@ -1,19 +1,28 @@
 //! 2D Grid View - Top-down room editing
 //!
 //! Sector-based geometry system - selection and editing works on sectors.
+//! Selected sector blocks can be dragged to a new position, rotated (R,
+//! Shift+R) and mirrored (H, V). The box room tool drags out a rectangle
+//! that becomes a new enclosed room.
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext};
-use crate::world::SECTOR_SIZE;
-use super::{EditorState, Selection, CEILING_HEIGHT};
+use crate::ui::{Rect, UiContext, ui_pixel_scale};
+use crate::world::{EntityRef, PortalKind, Room, SectorTransform, WaypointRef};
+use crate::rasterizer::Vec3;
+use super::{box_room_rect, EditorState, Selection, CEILING_HEIGHT, EDITOR_FOCUS};
 
 /// Draw the 2D grid view (top-down view of current room)
 pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let sector_size = state.level.sector_size;
+
     // Background
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(20, 20, 25, 255));
 
     let mouse_pos = (ctx.mouse.x, ctx.mouse.y);
-    let inside = ctx.mouse.inside(&rect);
+    // Editing is paused while the 3D viewport simulates play mode
+    let inside = ctx.mouse.inside(&rect) && state.simulation.is_none();
+    // Shortcuts also need the keyboard free (no text field typing)
+    let keys_inside = inside && ctx.accepts_keys(EDITOR_FOCUS);
 
     // Handle pan and zoom
     if inside {
@@ -69,7 +78,7 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
     };
 
     // Enable scissor rectangle to clip drawing to viewport bounds
-    let dpi = screen_dpi_scale();
+    let dpi = ui_pixel_scale();
     gl_use_default_material();
     unsafe {
         get_internal_gl().quad_gl.scissor(
@@ -137,8 +146,8 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         let local_x = wx - room.position.x;
         let local_z = wz - room.position.z;
         if local_x >= 0.0 && local_z >= 0.0 {
-            let gx = (local_x / SECTOR_SIZE) as usize;
-            let gz = (local_z / SECTOR_SIZE) as usize;
+            let gx = (local_x / sector_size) as usize;
+            let gz = (local_z / sector_size) as usize;
             if gx < room.width && gz < room.depth {
                 if room.get_sector(gx, gz).is_some() {
                     hovered_sector = Some((gx, gz));
@@ -149,13 +158,13 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
 
     // Draw sectors
     for (gx, gz, sector) in room.iter_sectors() {
-        let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
-        let base_z = room.position.z + (gz as f32) * SECTOR_SIZE;
+        let base_x = room.position.x + (gx as f32) * sector_size;
+        let base_z = room.position.z + (gz as f32) * sector_size;
 
         let (sx0, sy0) = world_to_screen(base_x, base_z);
-        let (sx1, sy1) = world_to_screen(base_x + SECTOR_SIZE, base_z);
-        let (sx2, sy2) = world_to_screen(base_x + SECTOR_SIZE, base_z + SECTOR_SIZE);
-        let (sx3, sy3) = world_to_screen(base_x, base_z + SECTOR_SIZE);
+        let (sx1, sy1) = world_to_screen(base_x + sector_size, base_z);
+        let (sx2, sy2) = world_to_screen(base_x + sector_size, base_z + sector_size);
+        let (sx3, sy3) = world_to_screen(base_x, base_z + sector_size);
 
         let is_hovered = hovered_sector == Some((gx, gz));
         let is_selected = matches!(state.selection, Selection::Sector { x, z, .. } if x == gx && z == gz);
@@ -166,8 +175,6 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         // Determine fill color based on sector contents
         let has_floor = sector.floor.is_some();
         let has_ceiling = sector.ceiling.is_some();
-        let has_walls = !sector.walls_north.is_empty() || !sector.walls_east.is_empty()
-            || !sector.walls_south.is_empty() || !sector.walls_west.is_empty();
 
         let fill_color = if is_selected || is_multi_selected {
             Color::from_rgba(255, 200, 100, 150)
@@ -224,6 +231,21 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         }
     }
 
+    // Draw sectors selected by co-op peers, outlined in each peer's color
+    for ((r, g, b), cells) in &state.peer_selections {
+        let color = Color::from_rgba(*r, *g, *b, 255);
+        for &(room_idx, gx, gz) in cells {
+            if room_idx != current_room_idx {
+                continue;
+            }
+            let base_x = room.position.x + (gx as f32) * sector_size;
+            let base_z = room.position.z + (gz as f32) * sector_size;
+            let (sx0, sy0) = world_to_screen(base_x, base_z);
+            let (sx2, sy2) = world_to_screen(base_x + sector_size, base_z + sector_size);
+            draw_rectangle_lines(sx0.min(sx2), sy0.min(sy2), (sx2 - sx0).abs(), (sy2 - sy0).abs(), 2.0, color);
+        }
+    }
+
     // Draw portals
     for portal in &room.portals {
         let v0 = portal.vertices[0];
@@ -236,25 +258,93 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         let (sx2, sy2) = world_to_screen(v2.x, v2.z);
         let (sx3, sy3) = world_to_screen(v3.x, v3.z);
 
-        // Portal fill (magenta)
+        // Portal fill (magenta openings, cyan mirrors, yellow warps)
+        let (fill, outline) = match portal.kind {
+            PortalKind::Opening => (Color::from_rgba(200, 50, 200, 80), Color::from_rgba(255, 100, 255, 255)),
+            PortalKind::Mirror => (Color::from_rgba(50, 180, 220, 80), Color::from_rgba(120, 220, 255, 255)),
+            PortalKind::Warp { .. } => (Color::from_rgba(220, 180, 40, 80), Color::from_rgba(255, 220, 80, 255)),
+        };
         draw_triangle(
             Vec2::new(sx0, sy0),
             Vec2::new(sx1, sy1),
             Vec2::new(sx2, sy2),
-            Color::from_rgba(200, 50, 200, 80),
+            fill,
         );
         draw_triangle(
             Vec2::new(sx0, sy0),
             Vec2::new(sx2, sy2),
             Vec2::new(sx3, sy3),
-            Color::from_rgba(200, 50, 200, 80),
+            fill,
         );
 
         // Portal outline
-        draw_line(sx0, sy0, sx1, sy1, 2.0, Color::from_rgba(255, 100, 255, 255));
-        draw_line(sx1, sy1, sx2, sy2, 2.0, Color::from_rgba(255, 100, 255, 255));
-        draw_line(sx2, sy2, sx3, sy3, 2.0, Color::from_rgba(255, 100, 255, 255));
-        draw_line(sx3, sy3, sx0, sy0, 2.0, Color::from_rgba(255, 100, 255, 255));
+        draw_line(sx0, sy0, sx1, sy1, 2.0, outline);
+        draw_line(sx1, sy1, sx2, sy2, 2.0, outline);
+        draw_line(sx2, sy2, sx3, sy3, 2.0, outline);
+        draw_line(sx3, sy3, sx0, sy0, 2.0, outline);
+    }
+
+    // Draw waypoints and their links
+    let mut hovered_waypoint: Option<WaypointRef> = None;
+    for (idx, wp) in room.waypoints.iter().enumerate() {
+        let this = WaypointRef::new(current_room_idx, idx);
+        let world = room.position + wp.position;
+        let (sx, sy) = world_to_screen(world.x, world.z);
+
+        for link in &wp.links {
+            // Draw each same-room link once
+            if link.room == current_room_idx && link.index < idx {
+                continue;
+            }
+            if let Some(target) = state.level.waypoint_world_pos(*link) {
+                let (tx, ty) = world_to_screen(target.x, target.z);
+                let both_patrol = wp.patrol && state.level.waypoint(*link).is_some_and(|l| l.patrol);
+                let link_color = if both_patrol {
+                    Color::from_rgba(255, 170, 60, 255)
+                } else {
+                    Color::from_rgba(80, 200, 220, 200)
+                };
+                draw_line(sx, sy, tx, ty, if both_patrol { 2.0 } else { 1.0 }, link_color);
+            }
+        }
+
+        if inside && (mouse_pos.0 - sx).powi(2) + (mouse_pos.1 - sy).powi(2) < 64.0 {
+            hovered_waypoint = Some(this);
+        }
+
+        let fill = if wp.patrol {
+            Color::from_rgba(255, 170, 60, 255)
+        } else {
+            Color::from_rgba(80, 200, 220, 255)
+        };
+        draw_circle(sx, sy, 5.0, fill);
+        if state.selected_waypoint == Some(this) || hovered_waypoint == Some(this) {
+            draw_circle_lines(sx, sy, 8.0, 1.5, WHITE);
+        }
+    }
+
+    // Draw entities (squares in their archetype's color, notes as labelled pins)
+    let mut hovered_entity: Option<EntityRef> = None;
+    for (idx, entity) in room.entities.iter().enumerate() {
+        let this = EntityRef::new(current_room_idx, idx);
+        let world = room.position + entity.position;
+        let (sx, sy) = world_to_screen(world.x, world.z);
+        if inside && (mouse_pos.0 - sx).abs() < 6.0 && (mouse_pos.1 - sy).abs() < 6.0 {
+            hovered_entity = Some(this);
+        }
+        if entity.is_note() {
+            let (r, g, b) = entity.note_color();
+            let color = Color::from_rgba(r, g, b, 255);
+            draw_line(sx, sy, sx, sy - 10.0, 1.5, color);
+            draw_circle(sx, sy - 12.0, 4.0, color);
+            draw_text(entity.note_text(), (sx + 7.0).floor(), (sy - 8.0).floor(), 14.0, color);
+        } else {
+            let (r, g, b) = state.entity_schema.archetype(&entity.archetype).map(|a| a.color).unwrap_or((255, 80, 80));
+            draw_rectangle(sx - 4.0, sy - 4.0, 8.0, 8.0, Color::from_rgba(r, g, b, 255));
+        }
+        if state.selected_entity == Some(this) || hovered_entity == Some(this) {
+            draw_rectangle_lines(sx - 7.0, sy - 7.0, 14.0, 14.0, 1.5, WHITE);
+        }
     }
 
     // Draw room origin marker
@@ -274,13 +364,20 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
             match state.tool {
                 EditorTool::Select => {
                     if let Some((gx, gz)) = hovered_sector {
-                        let new_selection = Selection::Sector { room: current_room_idx, x: gx, z: gz };
-                        if shift_down {
-                            state.toggle_multi_selection(new_selection.clone());
-                            state.selection = new_selection;
+                        let selected = state.selected_sectors();
+                        if !shift_down && selected.len() > 1 && selected.contains(&(gx, gz)) {
+                            // Grab the whole block; releasing in place selects just this sector
+                            state.grid_block_drag = Some(((gx, gz), true));
                         } else {
-                            state.clear_multi_selection();
-                            state.selection = new_selection;
+                            let new_selection = Selection::Sector { room: current_room_idx, x: gx, z: gz };
+                            if shift_down {
+                                state.toggle_multi_selection(new_selection.clone());
+                                state.selection = new_selection;
+                            } else {
+                                state.clear_multi_selection();
+                                state.selection = new_selection;
+                                state.grid_block_drag = Some(((gx, gz), false));
+                            }
                         }
                     } else {
                         // Clicked on nothing - clear selection (unless Shift is held)
@@ -291,83 +388,104 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
                     }
                 }
 
-                EditorTool::DrawFloor => {
+                EditorTool::DrawFloor | EditorTool::DrawCeiling => {
+                    let is_floor = state.tool == EditorTool::DrawFloor;
                     let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
-                    let snapped_x = (wx / SECTOR_SIZE).floor() * SECTOR_SIZE;
-                    let snapped_z = (wz / SECTOR_SIZE).floor() * SECTOR_SIZE;
-
-                    // Check if sector already has a floor
-                    let gx = ((snapped_x - room.position.x) / SECTOR_SIZE) as usize;
-                    let gz = ((snapped_z - room.position.z) / SECTOR_SIZE) as usize;
+                    let snapped_x = (wx / sector_size).floor() * sector_size;
+                    let snapped_z = (wz / sector_size).floor() * sector_size;
 
-                    let has_floor = room.get_sector(gx, gz)
-                        .map(|s| s.floor.is_some())
-                        .unwrap_or(false);
+                    // Check if the sector already has this face (cells left of / above the grid are empty)
+                    let gx = ((snapped_x - room.position.x) / sector_size).floor() as i32;
+                    let gz = ((snapped_z - room.position.z) / sector_size).floor() as i32;
+                    let occupied = gx >= 0 && gz >= 0 && room.get_sector(gx as usize, gz as usize)
+                        .is_some_and(|s| if is_floor { s.floor.is_some() } else { s.ceiling.is_some() });
 
-                    if has_floor {
-                        state.set_status("Sector already has a floor", 2.0);
+                    if occupied {
+                        state.set_status(if is_floor { "Sector already has a floor" } else { "Sector already has a ceiling" }, 2.0);
                     } else {
-                        state.save_undo();
-
-                        if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
-                            // Expand room grid if needed
-                            while gx >= room.width {
-                                room.width += 1;
-                                room.sectors.push((0..room.depth).map(|_| None).collect());
-                            }
-                            while gz >= room.depth {
-                                room.depth += 1;
-                                for col in &mut room.sectors {
-                                    col.push(None);
-                                }
+                        let texture = state.selected_texture.clone();
+                        let label = if is_floor { "Created floor sector" } else { "Created ceiling sector" };
+                        state.edit_sector_at(snapped_x + sector_size * 0.5, snapped_z + sector_size * 0.5, label, |room, gx, gz| {
+                            if is_floor {
+                                room.set_floor(gx, gz, 0.0, texture);
+                            } else {
+                                room.set_ceiling(gx, gz, CEILING_HEIGHT, texture);
                             }
-
-                            room.set_floor(gx, gz, 0.0, state.selected_texture.clone());
-                            room.recalculate_bounds();
-                            state.set_status("Created floor sector", 2.0);
-                        }
+                        });
                     }
                 }
 
-                EditorTool::DrawCeiling => {
-                    let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
-                    let snapped_x = (wx / SECTOR_SIZE).floor() * SECTOR_SIZE;
-                    let snapped_z = (wz / SECTOR_SIZE).floor() * SECTOR_SIZE;
-
-                    let gx = ((snapped_x - room.position.x) / SECTOR_SIZE) as usize;
-                    let gz = ((snapped_z - room.position.z) / SECTOR_SIZE) as usize;
-
-                    let has_ceiling = room.get_sector(gx, gz)
-                        .map(|s| s.ceiling.is_some())
-                        .unwrap_or(false);
+                EditorTool::DrawWall => {
+                    state.set_status("Wall tool: not yet implemented", 3.0);
+                }
 
-                    if has_ceiling {
-                        state.set_status("Sector already has a ceiling", 2.0);
-                    } else {
-                        state.save_undo();
+                EditorTool::BoxRoom => {
+                    let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+                    let cell = ((wx / sector_size).floor() as i32, (wz / sector_size).floor() as i32);
+                    state.box_room_drag = Some((cell, cell));
+                }
 
-                        if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
-                            // Expand room grid if needed
-                            while gx >= room.width {
-                                room.width += 1;
-                                room.sectors.push((0..room.depth).map(|_| None).collect());
+                EditorTool::PlaceWaypoint => {
+                    if let Some(clicked) = hovered_waypoint {
+                        match state.selected_waypoint {
+                            Some(selected) if selected == clicked => {
+                                state.selected_waypoint = None;
+                            }
+                            Some(selected) if state.level.waypoint(selected).is_some() => {
+                                state.save_undo("Toggled waypoint link");
+                                let linked = state.level.toggle_waypoint_link(selected, clicked);
+                                state.selected_waypoint = Some(clicked);
+                                state.set_status(if linked { "Waypoints linked" } else { "Waypoints unlinked" }, 2.0);
                             }
-                            while gz >= room.depth {
-                                room.depth += 1;
-                                for col in &mut room.sectors {
-                                    col.push(None);
+                            _ => {
+                                state.selected_waypoint = Some(clicked);
+                            }
+                        }
+                    } else if let Some((gx, gz)) = hovered_sector {
+                        let floor_y = room.get_sector(gx, gz)
+                            .and_then(|s| s.floor.as_ref())
+                            .map(|f| f.avg_height());
+
+                        if let Some(floor_y) = floor_y {
+                            let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+                            let local = Vec3::new(wx - room.position.x, floor_y, wz - room.position.z);
+                            let previous = state.selected_waypoint;
+
+                            state.save_undo("Placed waypoint");
+                            if let Some(new_wp) = state.level.add_waypoint(current_room_idx, local) {
+                                // Shift+click chains the new waypoint to the selected one
+                                if let (true, Some(prev)) = (shift_down, previous) {
+                                    state.level.toggle_waypoint_link(prev, new_wp);
                                 }
+                                state.selected_waypoint = Some(new_wp);
+                                state.set_status("Waypoint placed", 1.5);
                             }
-
-                            room.set_ceiling(gx, gz, CEILING_HEIGHT, state.selected_texture.clone());
-                            room.recalculate_bounds();
-                            state.set_status("Created ceiling sector", 2.0);
+                        } else {
+                            state.set_status("Waypoints need a floor", 2.0);
                         }
+                    } else {
+                        state.selected_waypoint = None;
                     }
                 }
 
-                EditorTool::DrawWall => {
-                    state.set_status("Wall tool: not yet implemented", 3.0);
+                EditorTool::PlaceObject => {
+                    if hovered_entity.is_some() {
+                        state.selected_entity = hovered_entity;
+                    } else if let Some((gx, gz)) = hovered_sector {
+                        let floor_y = room.get_sector(gx, gz)
+                            .and_then(|s| s.floor.as_ref())
+                            .map(|f| f.avg_height());
+
+                        if let Some(floor_y) = floor_y {
+                            let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+                            let local = Vec3::new(wx - room.position.x, floor_y, wz - room.position.z);
+                            state.place_entity(current_room_idx, local);
+                        } else {
+                            state.set_status("Entities need a floor", 2.0);
+                        }
+                    } else {
+                        state.selected_entity = None;
+                    }
                 }
 
                 _ => {}
@@ -375,8 +493,134 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         }
     }
 
+    // Sector block drag: move the selection by whole sectors
+    if let Some((anchor, collapse)) = state.grid_block_drag {
+        let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+        let cell_x = ((wx - room.position.x) / sector_size).floor() as i32;
+        let cell_z = ((wz - room.position.z) / sector_size).floor() as i32;
+        let (dx, dz) = (cell_x - anchor.0 as i32, cell_z - anchor.1 as i32);
+        let cells = state.selected_sectors();
+
+        if ctx.mouse.left_down {
+            // Preview the block at its new position
+            let preview_color = Color::from_rgba(0, 190, 230, 255);
+            for &(x, z) in &cells {
+                let base_x = room.position.x + (x as i32 + dx) as f32 * sector_size;
+                let base_z = room.position.z + (z as i32 + dz) as f32 * sector_size;
+                let (sx0, sy0) = world_to_screen(base_x, base_z);
+                let (sx1, sy1) = world_to_screen(base_x + sector_size, base_z);
+                let (sx2, sy2) = world_to_screen(base_x + sector_size, base_z + sector_size);
+                let (sx3, sy3) = world_to_screen(base_x, base_z + sector_size);
+                draw_line(sx0, sy0, sx1, sy1, 2.0, preview_color);
+                draw_line(sx1, sy1, sx2, sy2, 2.0, preview_color);
+                draw_line(sx2, sy2, sx3, sy3, 2.0, preview_color);
+                draw_line(sx3, sy3, sx0, sy0, 2.0, preview_color);
+            }
+        } else {
+            state.grid_block_drag = None;
+            if (dx, dz) != (0, 0) {
+                let label = format!("Moved {} sector(s)", cells.len());
+                apply_block_edit(state, current_room_idx, |room| room.move_sectors(&cells, dx, dz), &label);
+            } else if collapse {
+                state.select_sectors(&[anchor]);
+            }
+        }
+    }
+
+    // Box room rectangle (also shows drags started in the 3D viewport)
+    if let Some((anchor, current)) = state.box_room_drag {
+        let current = if inside && ctx.mouse.left_down {
+            let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+            let cell = ((wx / sector_size).floor() as i32, (wz / sector_size).floor() as i32);
+            state.box_room_drag = Some((anchor, cell));
+            cell
+        } else {
+            current
+        };
+        let ((x0, z0), (width, depth)) = box_room_rect(anchor, current);
+        let (sx0, sy0) = world_to_screen(x0 as f32 * sector_size, z0 as f32 * sector_size);
+        let (sx1, sy1) = world_to_screen((x0 + width as i32) as f32 * sector_size, (z0 + depth as i32) as f32 * sector_size);
+        let (left, top, w, h) = (sx0.min(sx1), sy0.min(sy1), (sx1 - sx0).abs(), (sy1 - sy0).abs());
+        draw_rectangle(left, top, w, h, Color::from_rgba(0, 190, 230, 40));
+        draw_rectangle_lines(left, top, w, h, 2.0, Color::from_rgba(0, 190, 230, 255));
+        draw_text(&format!("{}x{}", width, depth), left + 4.0, top + 14.0, 14.0, WHITE);
+
+        if !ctx.mouse.left_down {
+            state.box_room_drag = None;
+            state.create_box_room(anchor, current);
+        }
+    }
+
+    // Block transforms: R rotates clockwise (Shift+R counter-clockwise), H/V mirror, X extrudes
+    if keys_inside && state.tool == super::EditorTool::Select && state.grid_block_drag.is_none() {
+        let shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let transform = if is_key_pressed(KeyCode::R) {
+            Some(if shift_down { SectorTransform::RotateCcw } else { SectorTransform::RotateCw })
+        } else if is_key_pressed(KeyCode::H) {
+            Some(SectorTransform::MirrorX)
+        } else if is_key_pressed(KeyCode::V) {
+            Some(SectorTransform::MirrorZ)
+        } else {
+            None
+        };
+        let cells = state.selected_sectors();
+        if let (Some(t), false) = (transform, cells.is_empty()) {
+            apply_block_edit(state, current_room_idx, |room| room.transform_sectors(&cells, t), t.label());
+        }
+        if is_key_pressed(KeyCode::X) {
+            state.extrude_selection(shift_down);
+        }
+    }
+
+    // Waypoint shortcuts: P toggles patrol, Delete removes
+    if keys_inside && state.tool == super::EditorTool::PlaceWaypoint {
+        if let Some(selected) = state.selected_waypoint {
+            if is_key_pressed(KeyCode::P) {
+                state.save_undo("Toggled waypoint patrol");
+                if let Some(wp) = state.level.waypoint_mut(selected) {
+                    wp.patrol = !wp.patrol;
+                    let msg = if wp.patrol { "Waypoint added to patrol" } else { "Waypoint removed from patrol" };
+                    state.set_status(msg, 1.5);
+                }
+            }
+            if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
+                state.save_undo("Removed waypoint");
+                state.level.remove_waypoint(selected);
+                state.selected_waypoint = None;
+                state.set_status("Waypoint removed", 1.5);
+            }
+        }
+    }
+
+    // Entity shortcut: Delete removes
+    if keys_inside && state.tool == super::EditorTool::PlaceObject && state.selected_entity.is_some()
+        && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace))
+    {
+        state.remove_selected_entity();
+    }
+
     // Disable scissor rectangle
     unsafe {
         get_internal_gl().quad_gl.scissor(None);
     }
 }
+
+/// Apply an edit to a block of sectors on a copy of the room, committing it
+/// (with undo) only if it succeeds
+fn apply_block_edit<F>(state: &mut EditorState, room_idx: usize, edit: F, label: &str)
+where
+    F: FnOnce(&mut Room) -> Result<Vec<(usize, usize)>, String>,
+{
+    let Some(mut room) = state.level.rooms.get(room_idx).cloned() else {
+        return;
+    };
+    match edit(&mut room) {
+        Ok(cells) => {
+            state.save_undo(label);
+            state.replace_room(room_idx, room);
+            state.select_sectors(&cells);
+            state.set_status(label, 2.0);
+        }
+        Err(e) => state.set_status(&e, 3.0),
+    }
+}
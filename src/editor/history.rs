@@ -0,0 +1,280 @@
+//! Undo history with structural sharing
+//!
+//! Each undo step stores a `LevelSnapshot` instead of a full level clone:
+//! - rooms are reference counted, and a room that is unchanged from the
+//!   neighbouring snapshot shares its allocation
+//! - so are the level-wide lists (camera paths, metadata, macros, events,
+//!   selection sets)
+//! - an edit to one room therefore only costs that room's memory
+//! - `HistoryMemory` estimates what the stacks actually hold
+
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::rc::Rc;
+use crate::rasterizer::ColorGrade;
+use crate::world::{CameraKey, CameraPath, EditMacro, EditorLayoutConfig, Entity, Level, LevelEvent, LevelMetadata, MacroCell, Portal, PropertyValue, RespawnRules, Room, Sector, SelectionSet, SetMember, TextureVariants, VerticalFace, Waypoint, WaypointRef, WeightedTexture};
+
+/// A level frozen for the undo/redo stacks
+#[derive(Debug, Clone)]
+pub struct LevelSnapshot {
+    rooms: Vec<Rc<Room>>,
+    camera_paths: Rc<Vec<CameraPath>>,
+    metadata: Rc<LevelMetadata>,
+    editor_layout: EditorLayoutConfig,
+    sector_size: f32,
+    click_height: f32,
+    ambience: ColorGrade,
+    selection_sets: Rc<Vec<SelectionSet>>,
+    texture_variants: TextureVariants,
+    macros: Rc<Vec<EditMacro>>,
+    events: Rc<Vec<LevelEvent>>,
+    respawn: RespawnRules,
+    music: Option<String>,
+}
+
+/// `value`, sharing `base`'s allocation when it's unchanged
+fn share<T: Clone + PartialEq>(base: Option<&Rc<T>>, value: &T) -> Rc<T> {
+    match base {
+        Some(shared) if **shared == *value => Rc::clone(shared),
+        _ => Rc::new(value.clone()),
+    }
+}
+
+/// The value of a snapshot field, cloned only if another snapshot still shares it
+fn unshare<T: Clone>(shared: Rc<T>) -> T {
+    Rc::try_unwrap(shared).unwrap_or_else(|v| (*v).clone())
+}
+
+impl LevelSnapshot {
+    /// Snapshot `level`, reusing rooms and lists that are unchanged in `base`
+    pub fn capture(level: &Level, base: Option<&LevelSnapshot>) -> Self {
+        let rooms = level
+            .rooms
+            .iter()
+            .enumerate()
+            .map(|(i, room)| share(base.and_then(|b| b.rooms.get(i)), room))
+            .collect();
+        Self {
+            rooms,
+            camera_paths: share(base.map(|b| &b.camera_paths), &level.camera_paths),
+            metadata: share(base.map(|b| &b.metadata), &level.metadata),
+            editor_layout: level.editor_layout.clone(),
+            sector_size: level.sector_size,
+            click_height: level.click_height,
+            ambience: level.ambience,
+            selection_sets: share(base.map(|b| &b.selection_sets), &level.selection_sets),
+            texture_variants: level.texture_variants.clone(),
+            macros: share(base.map(|b| &b.macros), &level.macros),
+            events: share(base.map(|b| &b.events), &level.events),
+            respawn: level.respawn,
+            music: level.music.clone(),
+        }
+    }
+
+    /// Rooms that differ from `other`, a snapshot captured against this one
+    /// or the other way round (unchanged rooms share their allocation).
+    /// None when the room count differs, as rooms may have been renumbered.
+    pub fn changed_rooms(&self, other: &LevelSnapshot) -> Option<Vec<usize>> {
+        if self.rooms.len() != other.rooms.len() {
+            return None;
+        }
+        Some((0..self.rooms.len()).filter(|&i| !Rc::ptr_eq(&self.rooms[i], &other.rooms[i])).collect())
+    }
+
+    /// Turn the snapshot back into a level, cloning only what's still shared
+    pub fn into_level(self) -> Level {
+        Level {
+            metadata: unshare(self.metadata),
+            rooms: self.rooms.into_iter().map(unshare).collect(),
+            editor_layout: self.editor_layout,
+            camera_paths: unshare(self.camera_paths),
+            sector_size: self.sector_size,
+            click_height: self.click_height,
+            ambience: self.ambience,
+            selection_sets: unshare(self.selection_sets),
+            texture_variants: self.texture_variants,
+            macros: unshare(self.macros),
+            events: unshare(self.events),
+            respawn: self.respawn,
+            music: self.music,
+            // The editor loads split levels whole
+            streamed: None,
+        }
+    }
+}
+
+/// A labeled snapshot on the undo/redo stacks
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// Level before (undo stack) or after (redo stack) the step
+    pub snapshot: LevelSnapshot,
+    /// What the step did, e.g. "Created floor sector"
+    pub label: String,
+}
+
+/// Estimated memory held by the undo/redo stacks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryMemory {
+    /// Bytes actually allocated (shared parts counted once)
+    pub bytes: usize,
+    /// Bytes full level copies would have taken
+    pub unshared_bytes: usize,
+}
+
+impl HistoryMemory {
+    /// Measure a set of history entries
+    pub fn measure<'a>(entries: impl IntoIterator<Item = &'a HistoryEntry>) -> Self {
+        // Size each shared allocation once, keyed by address
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        let mut memory = Self::default();
+        let mut count = |memory: &mut Self, address: usize, size: &dyn Fn() -> usize| {
+            memory.unshared_bytes += *sizes.entry(address).or_insert_with(|| {
+                let bytes = size();
+                memory.bytes += bytes;
+                bytes
+            });
+        };
+        for entry in entries {
+            let snapshot = &entry.snapshot;
+            for room in &snapshot.rooms {
+                count(&mut memory, Rc::as_ptr(room) as usize, &|| room_bytes(room));
+            }
+            let paths = &snapshot.camera_paths;
+            count(&mut memory, Rc::as_ptr(paths) as usize, &|| camera_paths_bytes(paths));
+            let metadata = &snapshot.metadata;
+            count(&mut memory, Rc::as_ptr(metadata) as usize, &|| metadata_bytes(metadata));
+            let macros = &snapshot.macros;
+            count(&mut memory, Rc::as_ptr(macros) as usize, &|| macros_bytes(macros));
+            let events = &snapshot.events;
+            count(&mut memory, Rc::as_ptr(events) as usize, &|| events.capacity() * size_of::<LevelEvent>());
+            let sets = &snapshot.selection_sets;
+            count(&mut memory, Rc::as_ptr(sets) as usize, &|| selection_sets_bytes(sets));
+
+            // Everything else is copied into each snapshot
+            let own = size_of::<LevelSnapshot>()
+                + snapshot.rooms.capacity() * size_of::<Rc<Room>>()
+                + snapshot.texture_variants.textures.capacity() * size_of::<WeightedTexture>()
+                + snapshot.music.as_ref().map_or(0, |m| m.capacity());
+            memory.bytes += own;
+            memory.unshared_bytes += own;
+        }
+        memory
+    }
+}
+
+/// Human-readable byte count, e.g. "12.5 KB"
+pub fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Approximate heap + inline size of a room
+fn room_bytes(room: &Room) -> usize {
+    let grid = room.sectors.iter().map(|col| col.capacity() * size_of::<Option<Sector>>()).sum::<usize>();
+    let walls = room
+        .iter_sectors()
+        .map(|(_, _, s)| {
+            let count = s.walls_north.capacity() + s.walls_east.capacity() + s.walls_south.capacity() + s.walls_west.capacity();
+            count * size_of::<VerticalFace>()
+        })
+        .sum::<usize>();
+    let waypoints = room.waypoints.iter().map(|w| size_of::<Waypoint>() + w.links.capacity() * size_of::<WaypointRef>()).sum::<usize>();
+    let entities = room.entities.iter().map(|e| size_of::<Entity>() + e.properties.len() * size_of::<(String, PropertyValue)>()).sum::<usize>();
+    size_of::<Room>() + grid + walls + waypoints + entities + room.portals.capacity() * size_of::<Portal>()
+}
+
+fn camera_paths_bytes(paths: &[CameraPath]) -> usize {
+    paths
+        .iter()
+        .map(|p| size_of::<CameraPath>() + p.name.capacity() + p.keys.capacity() * size_of::<CameraKey>())
+        .sum()
+}
+
+fn metadata_bytes(metadata: &LevelMetadata) -> usize {
+    size_of::<LevelMetadata>() + metadata.thumbnail.as_ref().map_or(0, |t| t.capacity()) + metadata.editor_version.capacity()
+}
+
+fn macros_bytes(macros: &[EditMacro]) -> usize {
+    macros
+        .iter()
+        .map(|m| size_of::<EditMacro>() + m.name.capacity() + m.cells.capacity() * size_of::<MacroCell>())
+        .sum()
+}
+
+fn selection_sets_bytes(sets: &[SelectionSet]) -> usize {
+    sets.iter()
+        .map(|s| size_of::<SelectionSet>() + s.name.capacity() + s.members.capacity() * size_of::<SetMember>())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{LevelBuilder, RoomBuilder};
+    use crate::rasterizer::Vec3;
+
+    fn two_room_level() -> Level {
+        LevelBuilder::new()
+            .room(RoomBuilder::new(2, 2).fill(0.0, 1024.0))
+            .room(RoomBuilder::new(2, 2).at(Vec3::new(2048.0, 0.0, 0.0)).fill(0.0, 1024.0))
+            .build()
+    }
+
+    #[test]
+    fn test_unchanged_rooms_are_shared() {
+        let mut level = two_room_level();
+        let first = LevelSnapshot::capture(&level, None);
+        level.rooms[1].ambient = 0.9;
+        let second = LevelSnapshot::capture(&level, Some(&first));
+
+        assert!(Rc::ptr_eq(&first.rooms[0], &second.rooms[0]));
+        assert!(!Rc::ptr_eq(&first.rooms[1], &second.rooms[1]));
+        assert!(Rc::ptr_eq(&first.camera_paths, &second.camera_paths));
+        assert!(Rc::ptr_eq(&first.metadata, &second.metadata));
+        assert!(Rc::ptr_eq(&first.events, &second.events));
+
+        let entries = [
+            HistoryEntry { snapshot: first, label: "a".to_string() },
+            HistoryEntry { snapshot: second, label: "b".to_string() },
+        ];
+        let memory = HistoryMemory::measure(&entries);
+        assert!(memory.bytes < memory.unshared_bytes);
+    }
+
+    #[test]
+    fn test_changed_rooms() {
+        let mut level = two_room_level();
+        let first = LevelSnapshot::capture(&level, None);
+        level.rooms[1].position.y += 256.0;
+        let second = LevelSnapshot::capture(&level, Some(&first));
+        assert_eq!(second.changed_rooms(&first), Some(vec![1]));
+
+        level.rooms.pop();
+        let third = LevelSnapshot::capture(&level, Some(&second));
+        assert_eq!(third.changed_rooms(&second), None);
+    }
+
+    #[test]
+    fn test_memory_counts_level_wide_data() {
+        let mut level = two_room_level();
+        let plain = LevelSnapshot::capture(&level, None);
+        level.metadata.thumbnail = Some("A".repeat(4096));
+        let with_thumbnail = LevelSnapshot::capture(&level, None);
+
+        let measure = |snapshot: LevelSnapshot| HistoryMemory::measure(&[HistoryEntry { snapshot, label: String::new() }]).bytes;
+        assert!(measure(with_thumbnail) >= measure(plain) + 4096);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let level = two_room_level();
+        let restored = LevelSnapshot::capture(&level, None).into_level();
+        assert_eq!(restored.rooms, level.rooms);
+        assert_eq!(restored.camera_paths, level.camera_paths);
+    }
+}
@@ -1,13 +1,22 @@
 //! Editor layout - TRLE-inspired panel arrangement
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, SplitPanel, draw_panel, panel_content_rect, Toolbar, icon};
+use crate::i18n::{tr, trf};
+use crate::ui::{edit_text, FocusId, Rect, Shortcut, TextEdit, UiContext, SplitPanel, draw_panel, panel_content_rect, draw_text_button, Toolbar, icon, ui_pixel_scale};
 use crate::rasterizer::{Framebuffer, Texture as RasterTexture};
-use super::{EditorState, EditorTool};
+use crate::world::{Direction, PortalKind, PropertyDef, PropertyKind, PropertyValue, RespawnRules, ReverbPreset, RoomAudio, SaveFormat, SectorTransform, Weather, WeatherKind};
+use super::{format_bytes, format_clicks, CameraCollision, EditorState, EditorTool, HeightSnap, SnapSettings, ANGLE_STEPS, GRID_DIVISIONS, MAX_SECTOR_SIZE, MIN_CLICK_HEIGHT, MIN_SECTOR_SIZE};
 use super::grid_view::draw_grid_view;
 use super::viewport_3d::draw_viewport_3d;
 use super::texture_palette::draw_texture_palette;
 
+/// Keyboard focus of the editor's views: their shortcuts work while no text
+/// field (or dialog) has the keyboard
+pub(super) const EDITOR_FOCUS: FocusId = "editor";
+const SET_NAME_FOCUS: FocusId = "editor.set_name";
+const TRIGGER_FOCUS: FocusId = "editor.camera_trigger";
+const ENTITY_TEXT_FOCUS: FocusId = "editor.entity_text";
+
 /// Actions that can be triggered by the editor UI
 #[derive(Debug, Clone, PartialEq)]
 pub enum EditorAction {
@@ -21,6 +30,10 @@ pub enum EditorAction {
     Export,         // Browser: download as file
     Import,         // Browser: upload file
     BrowseExamples, // Open example browser
+    ExportGame,     // Native: bundle a stand-alone player build
+    CompareMerge,   // Native: compare or merge with another version of the level
+    ProjectPanel,   // Open the project panel (progression, equipment, loot)
+    WorldMap,       // Open the world map view (level exit links)
     Exit,           // Close/quit
 }
 
@@ -34,6 +47,10 @@ pub struct EditorLayout {
     pub left_split: SplitPanel,
     /// Right vertical split (texture palette | properties)
     pub right_panel_split: SplitPanel,
+    /// Room panel split (room properties | history)
+    pub room_history_split: SplitPanel,
+    /// The 3D viewport is in its own window (native), so the 2D grid takes its place
+    pub viewport_detached: bool,
 }
 
 impl EditorLayout {
@@ -43,6 +60,8 @@ impl EditorLayout {
             right_split: SplitPanel::horizontal(2).with_ratio(0.75).with_min_size(150.0),
             left_split: SplitPanel::vertical(3).with_ratio(0.6).with_min_size(100.0),
             right_panel_split: SplitPanel::vertical(4).with_ratio(0.6).with_min_size(100.0),
+            room_history_split: SplitPanel::vertical(5).with_ratio(0.5).with_min_size(60.0),
+            viewport_detached: false,
         }
     }
 
@@ -52,6 +71,7 @@ impl EditorLayout {
         self.right_split.ratio = config.right_split;
         self.left_split.ratio = config.left_split;
         self.right_panel_split.ratio = config.right_panel_split;
+        self.room_history_split.ratio = config.room_history_split;
     }
 
     /// Extract current layout as a config (for saving with level)
@@ -61,8 +81,16 @@ impl EditorLayout {
             right_split: self.right_split.ratio,
             left_split: self.left_split.ratio,
             right_panel_split: self.right_panel_split.ratio,
+            room_history_split: self.room_history_split.ratio,
         }
     }
+
+    /// A divider is being dragged
+    pub fn is_resizing(&self, ctx: &UiContext) -> bool {
+        [&self.main_split, &self.right_split, &self.left_split, &self.right_panel_split, &self.room_history_split]
+            .iter()
+            .any(|split| split.is_dragging(ctx))
+    }
 }
 
 /// Draw the complete editor UI, returns action if triggered
@@ -76,6 +104,7 @@ pub fn draw_editor(
     icon_font: Option<&Font>,
 ) -> EditorAction {
     let screen = bounds;
+    state.session.tick(get_frame_time());
 
     // Single unified toolbar at top
     let toolbar_height = 36.0;
@@ -88,42 +117,95 @@ pub fn draw_editor(
     let panels_rect = main_rect.remaining_after_bottom(status_height);
 
     // Draw unified toolbar
+    let snap_menu_was_open = state.snap_menu_x.is_some();
     let action = draw_unified_toolbar(ctx, toolbar_rect, state, icon_font);
 
+    // Snap menu hangs over the panels, so keep clicks on it from reaching them
+    let snap_menu = state.snap_menu_x.map(|x| snap_menu_rect(x, toolbar_rect));
+    let real_mouse = ctx.mouse;
+    let block_panels = !ctx.is_modal_active() && snap_menu.is_some_and(|r| real_mouse.inside(&r));
+    if block_panels {
+        ctx.begin_modal();
+    }
+
     // Main split: left panels | rest
     let (left_rect, rest_rect) = layout.main_split.update(ctx, panels_rect);
 
     // Right split: center viewport | right panels
     let (center_rect, right_rect) = layout.right_split.update(ctx, rest_rect);
 
-    // Left split: 2D grid view | room controls
-    let (grid_rect, room_props_rect) = layout.left_split.update(ctx, left_rect);
+    // Left split: 2D grid view | room controls (the grid takes the center
+    // while the 3D viewport is detached)
+    let (grid_rect, room_panel_rect) = if layout.viewport_detached {
+        (center_rect, left_rect)
+    } else {
+        layout.left_split.update(ctx, left_rect)
+    };
+
+    // Room controls split: room properties | history
+    let (room_props_rect, history_rect) = layout.room_history_split.update(ctx, room_panel_rect);
 
     // Right split: texture palette | face properties
     let (texture_rect, props_rect) = layout.right_panel_split.update(ctx, right_rect);
 
     // Draw panels
-    draw_panel(grid_rect, Some("2D Grid"), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(grid_rect, Some(&tr("editor.panel.grid")), Color::from_rgba(35, 35, 40, 255));
     draw_grid_view(ctx, panel_content_rect(grid_rect, true), state);
+    if layout.viewport_detached {
+        draw_detach_button(ctx, grid_rect, layout, icon_font);
+    }
 
-    draw_panel(room_props_rect, Some("Room"), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(room_props_rect, Some(&tr("editor.panel.room")), Color::from_rgba(35, 35, 40, 255));
     draw_room_properties(ctx, panel_content_rect(room_props_rect, true), state);
 
-    draw_panel(center_rect, Some("3D Viewport"), Color::from_rgba(25, 25, 30, 255));
-    draw_viewport_3d(ctx, panel_content_rect(center_rect, true), state, textures, fb);
+    draw_panel(history_rect, Some(&tr("editor.panel.history")), Color::from_rgba(35, 35, 40, 255));
+    draw_history(ctx, panel_content_rect(history_rect, true), state);
+
+    if !layout.viewport_detached {
+        draw_panel(center_rect, Some(&tr("common.viewport_3d")), Color::from_rgba(25, 25, 30, 255));
+        draw_detach_button(ctx, center_rect, layout, icon_font);
+        let resizing = layout.is_resizing(ctx);
+        draw_viewport_3d(ctx, panel_content_rect(center_rect, true), state, textures, fb, resizing);
+    }
 
-    draw_panel(texture_rect, Some("Textures"), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(texture_rect, Some(&tr("editor.panel.textures")), Color::from_rgba(35, 35, 40, 255));
     draw_texture_palette(ctx, panel_content_rect(texture_rect, true), state, icon_font);
 
-    draw_panel(props_rect, Some("Properties"), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(props_rect, Some(&tr("common.properties")), Color::from_rgba(35, 35, 40, 255));
     draw_properties(ctx, panel_content_rect(props_rect, true), state, icon_font);
 
     // Draw status bar
     draw_status_bar(status_rect, state);
 
+    if block_panels {
+        ctx.end_modal(real_mouse);
+    }
+    if let Some(menu_rect) = snap_menu {
+        draw_snap_menu(ctx, menu_rect, state);
+        // Click anywhere else closes it (the toolbar button toggles it itself)
+        let mouse = ctx.mouse;
+        if snap_menu_was_open && mouse.left_pressed && !mouse.inside(&menu_rect) && !mouse.inside(&toolbar_rect) {
+            state.snap_menu_x = None;
+        }
+    }
+
     action
 }
 
+/// Title bar button of the center panel that opens the 3D viewport in its
+/// own window or docks it back (native only: the web build has one canvas)
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+fn draw_detach_button(ctx: &mut UiContext, panel: Rect, layout: &mut EditorLayout, icon_font: Option<&Font>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let button = Rect::new(panel.right() - 20.0, panel.y + 1.0, 18.0, 18.0);
+        let tooltip = if layout.viewport_detached { tr("editor.viewport.dock") } else { tr("editor.viewport.detach") };
+        if crate::ui::icon_button_active(ctx, button, icon::LAYERS, icon_font, &tooltip, layout.viewport_detached) {
+            layout.viewport_detached = !layout.viewport_detached;
+        }
+    }
+}
+
 fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, icon_font: Option<&Font>) -> EditorAction {
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(40, 40, 45, 255));
 
@@ -131,69 +213,87 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     let mut toolbar = Toolbar::new(rect);
 
     // File operations
-    if toolbar.icon_button(ctx, icon::FILE_PLUS, icon_font, "New") {
+    if toolbar.icon_button(ctx, icon::FILE_PLUS, icon_font, &tr("common.new")) {
         action = EditorAction::New;
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     {
-        if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Open") {
+        if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, &tr("common.open")) {
             action = EditorAction::PromptLoad;
         }
-        if toolbar.icon_button(ctx, icon::SAVE, icon_font, "Save") {
+        if toolbar.icon_button(ctx, icon::SAVE, icon_font, &tr("common.save")) {
             action = EditorAction::Save;
         }
-        if toolbar.icon_button(ctx, icon::SAVE_AS, icon_font, "Save As") {
+        if toolbar.icon_button(ctx, icon::SAVE_AS, icon_font, &tr("common.save_as")) {
             action = EditorAction::SaveAs;
         }
+        if toolbar.icon_button(ctx, icon::PACKAGE, icon_font, &tr("editor.export_game")) {
+            action = EditorAction::ExportGame;
+        }
+        if toolbar.icon_button(ctx, icon::GIT_COMPARE, icon_font, &tr("editor.compare_merge")) {
+            action = EditorAction::CompareMerge;
+        }
     }
 
     #[cfg(target_arch = "wasm32")]
     {
-        if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Upload") {
+        if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, &tr("editor.upload")) {
             action = EditorAction::Import;
         }
-        if toolbar.icon_button(ctx, icon::SAVE, icon_font, "Download") {
+        if toolbar.icon_button(ctx, icon::SAVE, icon_font, &tr("editor.download")) {
             action = EditorAction::Export;
         }
     }
 
     // Level browser (works on both native and WASM)
-    if toolbar.icon_button(ctx, icon::BOOK_OPEN, icon_font, "Browse") {
+    if toolbar.icon_button(ctx, icon::BOOK_OPEN, icon_font, &tr("editor.browse")) {
         action = EditorAction::BrowseExamples;
     }
+    if toolbar.icon_button(ctx, icon::PERSON_STANDING, icon_font, &tr("editor.project")) {
+        action = EditorAction::ProjectPanel;
+    }
+    if toolbar.icon_button(ctx, icon::GLOBE, icon_font, &tr("editor.world_map")) {
+        action = EditorAction::WorldMap;
+    }
 
     toolbar.separator();
 
     // Edit operations
-    if toolbar.icon_button(ctx, icon::UNDO, icon_font, "Undo") {
+    if toolbar.icon_button(ctx, icon::UNDO, icon_font, &tr("common.undo")) {
         state.undo();
     }
-    if toolbar.icon_button(ctx, icon::REDO, icon_font, "Redo") {
+    if toolbar.icon_button(ctx, icon::REDO, icon_font, &tr("common.redo")) {
         state.redo();
     }
 
     toolbar.separator();
 
     // Play button
-    if toolbar.icon_button(ctx, icon::PLAY, icon_font, "Play") {
+    if toolbar.icon_button(ctx, icon::PLAY, icon_font, &tr("common.play")) {
         action = EditorAction::Play;
     }
+    if toolbar.icon_button_active(ctx, icon::GAMEPAD, icon_font, &tr("editor.simulate"), state.simulation.is_some()) {
+        state.toggle_simulation();
+    }
 
     toolbar.separator();
 
     // Tool buttons
     let tools = [
-        (icon::MOVE, "Select", EditorTool::Select),
-        (icon::SQUARE, "Floor", EditorTool::DrawFloor),
-        (icon::BOX, "Wall", EditorTool::DrawWall),
-        (icon::LAYERS, "Ceiling", EditorTool::DrawCeiling),
-        (icon::DOOR_CLOSED, "Portal", EditorTool::PlacePortal),
+        (icon::MOVE, "common.select", EditorTool::Select),
+        (icon::SQUARE, "editor.tool.floor", EditorTool::DrawFloor),
+        (icon::BOX, "editor.tool.wall", EditorTool::DrawWall),
+        (icon::LAYERS, "editor.tool.ceiling", EditorTool::DrawCeiling),
+        (icon::DOOR_CLOSED, "editor.tool.portal", EditorTool::PlacePortal),
+        (icon::FOOTPRINTS, "editor.tool.waypoint", EditorTool::PlaceWaypoint),
+        (icon::SHAPES, "editor.tool.entity", EditorTool::PlaceObject),
+        (icon::BOXES, "editor.tool.box_room", EditorTool::BoxRoom),
     ];
 
-    for (icon_char, tooltip, tool) in tools {
+    for (icon_char, tooltip_key, tool) in tools {
         let is_active = state.tool == tool;
-        if toolbar.icon_button_active(ctx, icon_char, icon_font, tooltip, is_active) {
+        if toolbar.icon_button_active(ctx, icon_char, icon_font, &tr(tooltip_key), is_active) {
             state.tool = tool;
         }
     }
@@ -202,47 +302,96 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
 
     // Vertex mode toggle
     let link_icon = if state.link_coincident_vertices { icon::LINK } else { icon::UNLINK };
-    let link_tooltip = if state.link_coincident_vertices { "Vertices Linked" } else { "Vertices Independent" };
-    if toolbar.icon_button_active(ctx, link_icon, icon_font, link_tooltip, state.link_coincident_vertices) {
+    let link_tooltip = tr(if state.link_coincident_vertices { "editor.vertices_linked" } else { "editor.vertices_independent" });
+    if toolbar.icon_button_active(ctx, link_icon, icon_font, &link_tooltip, state.link_coincident_vertices) {
         state.link_coincident_vertices = !state.link_coincident_vertices;
         let mode = if state.link_coincident_vertices { "Linked" } else { "Independent" };
         state.set_status(&format!("Vertex mode: {}", mode), 2.0);
     }
 
+    // Snapping options
+    let snap_x = toolbar.cursor_x();
+    if toolbar.icon_button_active(ctx, icon::GRID, icon_font, &tr("editor.snap"), state.snap_menu_x.is_some()) {
+        state.snap_menu_x = match state.snap_menu_x {
+            Some(_) => None,
+            None => Some(snap_x),
+        };
+    }
+
+    // Camera collision: off -> collide -> walk height
+    let collision = state.camera_collision;
+    let collision_tip = trf("editor.camera_collision", &[&tr(collision.label_key())]);
+    if toolbar.icon_button_active(ctx, icon::PERSON_STANDING, icon_font, &collision_tip, collision != CameraCollision::Off) {
+        state.camera_collision = collision.next();
+        let mode = tr(state.camera_collision.label_key());
+        state.set_status(&trf("editor.camera_collision", &[&mode]), 2.0);
+    }
+
+    // Debug overlays
+    let overlays = &mut state.debug_overlays;
+    if toolbar.icon_button_active(ctx, icon::ARROW_UP_FROM_DOT, icon_font, &tr("editor.debug.normals"), overlays.normals) {
+        overlays.normals = !overlays.normals;
+    }
+    if toolbar.icon_button_active(ctx, icon::FLIP_VERTICAL, icon_font, &tr("editor.debug.backfaces"), overlays.backfaces) {
+        overlays.backfaces = !overlays.backfaces;
+    }
+    if toolbar.icon_button_active(ctx, icon::COMPASS, icon_font, &tr("editor.debug.wall_labels"), overlays.wall_labels) {
+        overlays.wall_labels = !overlays.wall_labels;
+    }
+    if toolbar.icon_button_active(ctx, icon::BOX, icon_font, &tr("editor.debug.hitboxes"), overlays.hitboxes) {
+        overlays.hitboxes = !overlays.hitboxes;
+    }
+
+    if toolbar.icon_button_active(ctx, icon::TIMER, icon_font, &tr("editor.level_stats"), state.show_stats) {
+        state.show_stats = true;
+    }
+    if toolbar.icon_button_active(ctx, icon::GAUGE, icon_font, &tr("editor.budget"), state.budget.is_some()) {
+        state.budget = Some(super::BudgetDialog::new(&state.level));
+    }
+    if toolbar.icon_button_active(ctx, icon::MEMORY_STICK, icon_font, &tr("editor.vram"), state.vram.is_some()) {
+        state.vram = Some(super::VramView::new(state));
+    }
+
     toolbar.separator();
 
     // Room navigation
-    toolbar.label(&format!("Room: {}", state.current_room));
+    toolbar.label(&trf("editor.room_label", &[&state.current_room]));
 
-    if toolbar.icon_button(ctx, icon::CIRCLE_CHEVRON_LEFT, icon_font, "Previous Room") {
+    if toolbar.icon_button(ctx, icon::CIRCLE_CHEVRON_LEFT, icon_font, &tr("editor.prev_room")) {
         if state.current_room > 0 {
             state.current_room -= 1;
         }
     }
-    if toolbar.icon_button(ctx, icon::CIRCLE_CHEVRON_RIGHT, icon_font, "Next Room") {
+    if toolbar.icon_button(ctx, icon::CIRCLE_CHEVRON_RIGHT, icon_font, &tr("editor.next_room")) {
         if state.current_room + 1 < state.level.rooms.len() {
             state.current_room += 1;
         }
     }
-    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Add Room") {
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, &tr("editor.add_room")) {
         // TODO: Add new room
-        println!("Add room clicked");
+        log::debug!("Add room clicked");
+    }
+    if toolbar.icon_button(ctx, icon::MERGE, icon_font, &tr("editor.merge_rooms")) {
+        state.merge_selected_rooms();
+    }
+    if toolbar.icon_button(ctx, icon::SPLIT, icon_font, &tr("editor.split_room")) {
+        state.split_selected_sectors();
     }
 
     toolbar.separator();
 
     // PS1 effect toggles
-    if toolbar.icon_button_active(ctx, icon::WAVES, icon_font, "Affine Textures (PS1 warp)", state.raster_settings.affine_textures) {
+    if toolbar.icon_button_active(ctx, icon::WAVES, icon_font, &tr("editor.fx.affine"), state.raster_settings.affine_textures) {
         state.raster_settings.affine_textures = !state.raster_settings.affine_textures;
         let mode = if state.raster_settings.affine_textures { "ON" } else { "OFF" };
         state.set_status(&format!("Affine textures: {}", mode), 2.0);
     }
-    if toolbar.icon_button_active(ctx, icon::MAGNET, icon_font, "Vertex Snap (PS1 jitter)", state.raster_settings.vertex_snap) {
+    if toolbar.icon_button_active(ctx, icon::MAGNET, icon_font, &tr("editor.fx.vertex_snap"), state.raster_settings.vertex_snap) {
         state.raster_settings.vertex_snap = !state.raster_settings.vertex_snap;
         let mode = if state.raster_settings.vertex_snap { "ON" } else { "OFF" };
         state.set_status(&format!("Vertex snap: {}", mode), 2.0);
     }
-    if toolbar.icon_button_active(ctx, icon::SUN, icon_font, "Gouraud Shading", state.raster_settings.shading != crate::rasterizer::ShadingMode::None) {
+    if toolbar.icon_button_active(ctx, icon::SUN, icon_font, &tr("editor.fx.gouraud"), state.raster_settings.shading != crate::rasterizer::ShadingMode::None) {
         use crate::rasterizer::ShadingMode;
         state.raster_settings.shading = if state.raster_settings.shading == ShadingMode::None {
             ShadingMode::Gouraud
@@ -252,16 +401,38 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         let mode = if state.raster_settings.shading != ShadingMode::None { "ON" } else { "OFF" };
         state.set_status(&format!("Shading: {}", mode), 2.0);
     }
-    if toolbar.icon_button_active(ctx, icon::MONITOR, icon_font, "Low Resolution (PS1 320x240)", state.raster_settings.low_resolution) {
-        state.raster_settings.low_resolution = !state.raster_settings.low_resolution;
-        let mode = if state.raster_settings.low_resolution { "320x240" } else { "High-res" };
-        state.set_status(&format!("Resolution: {}", mode), 2.0);
+    let resolution = state.raster_settings.resolution;
+    if toolbar.icon_button_active(ctx, icon::MONITOR, icon_font, &trf("editor.fx.resolution", &[&resolution.label()]), resolution.is_low()) {
+        state.raster_settings.resolution = resolution.next();
+        state.set_status(&format!("Resolution: {}", state.raster_settings.resolution.label()), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::ROWS_2, icon_font, &tr("editor.fx.interlaced"), state.raster_settings.interlaced) {
+        state.raster_settings.interlaced = !state.raster_settings.interlaced;
+        let mode = if state.raster_settings.interlaced { "ON" } else { "OFF" };
+        state.set_status(&format!("Interlaced: {}", mode), 2.0);
+    }
+    if state.raster_settings.interlaced
+        && toolbar.icon_button_active(ctx, icon::ROWS_4, icon_font, &tr("editor.fx.combing"), state.raster_settings.interlace_combing)
+    {
+        state.raster_settings.interlace_combing = !state.raster_settings.interlace_combing;
+        let mode = if state.raster_settings.interlace_combing { "Weave (combing)" } else { "Line double" };
+        state.set_status(&format!("Interlace: {}", mode), 2.0);
     }
-    if toolbar.icon_button_active(ctx, icon::BLEND, icon_font, "Dithering (PS1 color banding)", state.raster_settings.dithering) {
+    if toolbar.icon_button_active(ctx, icon::BLEND, icon_font, &tr("editor.fx.dithering"), state.raster_settings.dithering) {
         state.raster_settings.dithering = !state.raster_settings.dithering;
         let mode = if state.raster_settings.dithering { "ON" } else { "OFF" };
         state.set_status(&format!("Dithering: {}", mode), 2.0);
     }
+    if toolbar.icon_button_active(ctx, icon::BINARY, icon_font, &tr("editor.fx.fixed_point"), state.raster_settings.fixed_point) {
+        state.raster_settings.fixed_point = !state.raster_settings.fixed_point;
+        let mode = if state.raster_settings.fixed_point { "ON" } else { "OFF" };
+        state.set_status(&format!("Fixed-point: {}", mode), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::TV, icon_font, &tr("editor.fx.crt"), state.raster_settings.crt.enabled) {
+        state.raster_settings.crt.enabled = !state.raster_settings.crt.enabled;
+        let mode = if state.raster_settings.crt.enabled { "ON" } else { "OFF" };
+        state.set_status(&format!("CRT filter: {}", mode), 2.0);
+    }
 
     toolbar.separator();
 
@@ -287,43 +458,87 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     };
     toolbar.label(&file_label);
 
-    // Keyboard shortcuts
-    let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
-             || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper);
-    let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+    action
+}
 
-    if ctrl && is_key_pressed(KeyCode::N) {
-        action = EditorAction::New;
+/// The World Editor's part of the app-wide shortcuts: undo and redo act
+/// right away, the file shortcuts come back as the toolbar's actions
+pub fn handle_editor_shortcut(state: &mut EditorState, shortcut: Shortcut) -> EditorAction {
+    match shortcut {
+        Shortcut::Undo => state.undo(),
+        Shortcut::Redo => state.redo(),
+        Shortcut::New => return EditorAction::New,
+        #[cfg(not(target_arch = "wasm32"))]
+        Shortcut::Open => return EditorAction::PromptLoad,
+        #[cfg(not(target_arch = "wasm32"))]
+        Shortcut::Save => return EditorAction::Save,
+        #[cfg(not(target_arch = "wasm32"))]
+        Shortcut::SaveAs => return EditorAction::SaveAs,
+        #[cfg(target_arch = "wasm32")]
+        Shortcut::Open => return EditorAction::Import,
+        #[cfg(target_arch = "wasm32")]
+        Shortcut::Save | Shortcut::SaveAs => return EditorAction::Export,
     }
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        if ctrl && is_key_pressed(KeyCode::O) {
-            action = EditorAction::PromptLoad;
-        }
-        if ctrl && shift && is_key_pressed(KeyCode::S) {
-            action = EditorAction::SaveAs;
-        } else if ctrl && is_key_pressed(KeyCode::S) {
-            action = EditorAction::Save;
+    EditorAction::None
+}
+
+/// Snap menu popover, hanging below its toolbar button
+fn snap_menu_rect(anchor_x: f32, toolbar: Rect) -> Rect {
+    Rect::new(anchor_x.round(), toolbar.bottom(), 300.0, 94.0)
+}
+
+fn draw_snap_menu(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(45, 45, 52, 255));
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, Color::from_rgba(80, 80, 90, 255));
+
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let row_height = 26.0;
+    let label_w = 70.0;
+    let x = rect.x + 8.0;
+    let mut y = rect.y + 6.0;
+
+    // One row of mutually exclusive buttons, returns the clicked index
+    let option_row = |ctx: &mut UiContext, y: f32, label: &str, options: &[String], selected: Option<usize>| {
+        draw_text(label, x.floor(), (y + 16.0).floor(), 14.0, dim);
+        let button_w = ((rect.right() - 8.0 - (x + label_w)) / options.len() as f32).floor();
+        let mut clicked = None;
+        for (i, text) in options.iter().enumerate() {
+            let button = Rect::new(x + label_w + i as f32 * button_w, y, button_w - 2.0, row_height - 4.0);
+            let color = if selected == Some(i) { Color::from_rgba(0, 140, 170, 255) } else { Color::from_rgba(60, 60, 70, 255) };
+            if draw_text_button(ctx, button, text, color) {
+                clicked = Some(i);
+            }
         }
+        clicked
+    };
+
+    // Height snap
+    let heights: Vec<String> = HeightSnap::ALL.iter().map(|h| tr(h.label_key())).collect();
+    let current = HeightSnap::ALL.iter().position(|&h| h == state.snap.height);
+    if let Some(i) = option_row(ctx, y, &tr("editor.snap.height"), &heights, current) {
+        state.snap.height = HeightSnap::ALL[i];
+        state.set_status(&format!("Height snap: {}", tr(state.snap.height.label_key())), 2.0);
     }
-    #[cfg(target_arch = "wasm32")]
-    {
-        if ctrl && is_key_pressed(KeyCode::O) {
-            action = EditorAction::Import;
-        }
-        if ctrl && is_key_pressed(KeyCode::S) {
-            action = EditorAction::Export;
-        }
+    y += row_height;
+
+    // Reference grid, relative to the level's sector size
+    let sector_size = state.level.sector_size;
+    let grids: Vec<String> = GRID_DIVISIONS.iter().map(|d| format!("{}", sector_size * d)).collect();
+    let current = GRID_DIVISIONS.iter().position(|d| sector_size * d == state.grid_size);
+    if let Some(i) = option_row(ctx, y, &tr("editor.snap.grid"), &grids, current) {
+        state.grid_size = sector_size * GRID_DIVISIONS[i];
+        state.set_status(&format!("Grid size: {}", state.grid_size), 2.0);
     }
-    if ctrl && is_key_pressed(KeyCode::Z) {
-        if shift {
-            state.redo();
-        } else {
-            state.undo();
-        }
+    y += row_height;
+
+    // Angle snap
+    let angles: Vec<String> = ANGLE_STEPS.iter()
+        .map(|&a| if a > 0.0 { format!("{}", a) } else { tr("editor.snap.off") })
+        .collect();
+    let current = ANGLE_STEPS.iter().position(|&a| a == state.snap.angle_step);
+    if let Some(i) = option_row(ctx, y, &tr("editor.snap.angle"), &angles, current) {
+        state.snap.angle_step = ANGLE_STEPS[i];
     }
-
-    action
 }
 
 fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
@@ -331,6 +546,265 @@ fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     let x = rect.x.floor();
     let line_height = 20.0;
 
+    // Level grid metrics: halve / double within sane limits
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let sector_size = state.level.sector_size;
+    draw_text(&format!("Sector size: {}", sector_size), x, (y + 14.0).floor(), 16.0, dim);
+    match step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+        Some(false) if sector_size > MIN_SECTOR_SIZE => state.set_sector_size(sector_size * 0.5),
+        Some(true) if sector_size < MAX_SECTOR_SIZE => state.set_sector_size(sector_size * 2.0),
+        _ => {}
+    }
+    y += line_height;
+    let click_height = state.level.click_height;
+    draw_text(&format!("Click height: {}", click_height), x, (y + 14.0).floor(), 16.0, dim);
+    match step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+        Some(false) if click_height > MIN_CLICK_HEIGHT => state.set_click_height(click_height * 0.5),
+        Some(true) if click_height < sector_size => state.set_click_height(click_height * 2.0),
+        _ => {}
+    }
+    y += line_height;
+    // File layout: diff-friendly puts every field on its own line (for git)
+    let diff_friendly = state.level.metadata.save_format == SaveFormat::DiffFriendly;
+    draw_text("Diff-friendly saves", x, (y + 14.0).floor(), 16.0, dim);
+    let toggle = Rect::new(rect.right() - 44.0, y + 1.0, 40.0, line_height - 2.0);
+    let label = if diff_friendly { "On" } else { "Off" };
+    if draw_text_button(ctx, toggle, label, Color::from_rgba(60, 60, 70, 255)) {
+        state.save_undo("Save format");
+        state.level.metadata.save_format = if diff_friendly { SaveFormat::Compact } else { SaveFormat::DiffFriendly };
+    }
+    y += line_height;
+    // Song played in the level (by id), cycling through the project's songs
+    let music = state.level.music.as_deref().unwrap_or("game default");
+    draw_text(&format!("Music: {}", music), x, (y + 14.0).floor(), 16.0, dim);
+    if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+        state.step_level_music(up);
+    }
+    y += line_height;
+
+    // Level ambience (color grade), collapsed by default
+    let header = Rect::new(x, y, rect.w - 4.0, line_height);
+    let arrow = if state.show_ambience { "v" } else { ">" };
+    draw_text(&format!("{} {}", arrow, tr("editor.ambience")), x, (y + 14.0).floor(), 16.0, dim);
+    if ctx.mouse.clicked(&header) {
+        state.show_ambience = !state.show_ambience;
+    }
+    y += line_height;
+    if state.show_ambience {
+        // Presets: -/+ cycles through them, applying each one
+        let count = state.ambience_presets.len();
+        if let Some(preset) = state.ambience_presets.get(state.ambience_preset) {
+            draw_text(&format!("Preset: {}", preset.name), x + 8.0, (y + 14.0).floor(), 16.0, WHITE);
+            if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+                let index = if up { (state.ambience_preset + 1) % count } else { (state.ambience_preset + count - 1) % count };
+                state.apply_ambience_preset(index);
+            }
+            y += line_height;
+        }
+
+        let grade = state.level.ambience;
+        let rows = [
+            ("Gamma", grade.gamma, 0.1, (0.2, 3.0)),
+            ("Contrast", grade.contrast, 0.1, (0.0, 3.0)),
+            ("Saturation", grade.saturation, 0.1, (0.0, 3.0)),
+            ("Tint R", grade.tint[0], 0.05, (0.0, 2.0)),
+            ("Tint G", grade.tint[1], 0.05, (0.0, 2.0)),
+            ("Tint B", grade.tint[2], 0.05, (0.0, 2.0)),
+        ];
+        for (i, (label, value, step, (min, max))) in rows.into_iter().enumerate() {
+            draw_text(&format!("{}: {:.2}", label, value), x + 8.0, (y + 14.0).floor(), 16.0, WHITE);
+            if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+                let v = ((value + if up { step } else { -step }) * 100.0).round() / 100.0;
+                let mut new = grade;
+                match i {
+                    0 => new.gamma = v.clamp(min, max),
+                    1 => new.contrast = v.clamp(min, max),
+                    2 => new.saturation = v.clamp(min, max),
+                    _ => new.tint[i - 3] = v.clamp(min, max),
+                }
+                state.set_ambience(new);
+            }
+            y += line_height;
+        }
+        if !grade.is_identity() {
+            let reset = Rect::new(x + 8.0, y + 2.0, rect.w - 14.0, line_height - 2.0);
+            if draw_text_button(ctx, reset, "Reset ambience", Color::from_rgba(60, 60, 70, 255)) {
+                state.set_ambience(Default::default());
+            }
+            y += line_height + 2.0;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let save = Rect::new(x + 8.0, y + 2.0, rect.w - 14.0, line_height - 2.0);
+            if draw_text_button(ctx, save, "Save as preset", Color::from_rgba(60, 60, 70, 255)) {
+                if let Err(e) = state.save_ambience_preset() {
+                    state.set_status(&e, 4.0);
+                }
+            }
+            y += line_height + 2.0;
+        }
+    }
+
+    // Selection sets, collapsed by default
+    let header = Rect::new(x, y, rect.w - 4.0, line_height);
+    let arrow = if state.show_selection_sets { "v" } else { ">" };
+    let title = format!("{} {} ({})", arrow, tr("editor.selection_sets"), state.level.selection_sets.len());
+    draw_text(&title, x, (y + 14.0).floor(), 16.0, dim);
+    if ctx.mouse.clicked(&header) {
+        state.show_selection_sets = !state.show_selection_sets;
+    }
+    y += line_height;
+    if state.show_selection_sets {
+        y = draw_selection_sets(ctx, Rect::new(x, y, rect.w, rect.bottom() - y), state);
+    }
+
+    // Designer notes in every room, collapsed by default
+    let header = Rect::new(x, y, rect.w - 4.0, line_height);
+    let arrow = if state.show_notes { "v" } else { ">" };
+    let title = format!("{} {} ({})", arrow, tr("editor.notes"), state.level.notes().count());
+    draw_text(&title, x, (y + 14.0).floor(), 16.0, dim);
+    if ctx.mouse.clicked(&header) {
+        state.show_notes = !state.show_notes;
+    }
+    y += line_height;
+    if state.show_notes {
+        y = draw_notes(ctx, Rect::new(x, y, rect.w, rect.bottom() - y), state);
+    }
+
+    // Macros, collapsed by default (but shown while recording)
+    let header = Rect::new(x, y, rect.w - 4.0, line_height);
+    let expanded = state.show_macros || state.recording_macro.is_some();
+    let arrow = if expanded { "v" } else { ">" };
+    let title = format!("{} {} ({})", arrow, tr("editor.macros"), state.level.macros.len());
+    draw_text(&title, x, (y + 14.0).floor(), 16.0, dim);
+    if ctx.mouse.clicked(&header) {
+        state.show_macros = !state.show_macros;
+    }
+    y += line_height;
+    if expanded {
+        y = draw_macros(ctx, Rect::new(x, y, rect.w, rect.bottom() - y), state);
+    }
+
+    // Level event timeline, collapsed by default
+    let header = Rect::new(x, y, rect.w - 4.0, line_height);
+    let arrow = if state.show_events { "v" } else { ">" };
+    let title = format!("{} {} ({})", arrow, tr("editor.events"), state.level.events.len());
+    draw_text(&title, x, (y + 14.0).floor(), 16.0, dim);
+    if ctx.mouse.clicked(&header) {
+        state.show_events = !state.show_events;
+    }
+    y += line_height;
+    if state.show_events {
+        y = draw_events(ctx, Rect::new(x, y, rect.w, rect.bottom() - y), state);
+    }
+
+    // Camera paths (keys are added with K in the 3D view), collapsed by default
+    let header = Rect::new(x, y, rect.w - 4.0, line_height);
+    let arrow = if state.show_camera_paths { "v" } else { ">" };
+    let title = format!("{} {} ({})", arrow, tr("editor.camera_paths"), state.level.camera_paths.len());
+    draw_text(&title, x, (y + 14.0).floor(), 16.0, dim);
+    if ctx.mouse.clicked(&header) {
+        state.show_camera_paths = !state.show_camera_paths;
+    }
+    y += line_height;
+    if state.show_camera_paths {
+        y = draw_camera_paths(ctx, Rect::new(x, y, rect.w, rect.bottom() - y), state);
+    }
+    y += 6.0;
+
+    // Box room tool heights, in clicks
+    if state.tool == EditorTool::BoxRoom {
+        let accent = Color::from_rgba(0, 190, 230, 255);
+        draw_text(&format!("Box floor: {}", state.box_floor_y), x, (y + 14.0).floor(), 16.0, accent);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            state.box_floor_y += if up { click_height } else { -click_height };
+        }
+        y += line_height;
+        draw_text(&format!("Box ceiling: {}", state.box_ceiling_y), x, (y + 14.0).floor(), 16.0, accent);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            state.box_ceiling_y += if up { click_height } else { -click_height };
+        }
+        y += line_height + 6.0;
+    }
+
+    // Floor noise for the selected sectors
+    if !state.selected_sectors().is_empty() {
+        draw_text(&format!("Noise: +/-{} clicks", format_clicks(state.noise_amplitude)), x, (y + 14.0).floor(), 16.0, WHITE);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            state.noise_amplitude = (state.noise_amplitude + if up { 0.25 } else { -0.25 }).clamp(0.25, 8.0);
+        }
+        y += line_height;
+        draw_text(&format!("Noise seed: {}", state.noise_seed), x, (y + 14.0).floor(), 16.0, WHITE);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            state.noise_seed = if up { state.noise_seed.wrapping_add(1) } else { state.noise_seed.wrapping_sub(1) };
+        }
+        y += line_height;
+        let apply = Rect::new(x, y + 2.0, rect.w - 6.0, line_height - 2.0);
+        if draw_text_button(ctx, apply, "Apply noise to floors", Color::from_rgba(60, 60, 70, 255)) {
+            state.apply_floor_noise();
+        }
+        y += line_height + 8.0;
+    }
+
+    // Current room's weather: -/+ cycles the kind, then the density
+    if let Some(weather) = state.current_room().map(|r| r.weather) {
+        draw_text(&format!("Weather: {}", weather.kind.label()), x, (y + 14.0).floor(), 16.0, WHITE);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            let kinds = WeatherKind::ALL;
+            let i = kinds.iter().position(|k| *k == weather.kind).unwrap_or(0);
+            let next = if up { (i + 1) % kinds.len() } else { (i + kinds.len() - 1) % kinds.len() };
+            state.set_room_weather(Weather { kind: kinds[next], ..weather });
+        }
+        y += line_height;
+        if weather.kind != WeatherKind::None {
+            draw_text(&format!("Density: {:.1}", weather.density), x + 8.0, (y + 14.0).floor(), 16.0, WHITE);
+            if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+                let density = ((weather.density + if up { 0.1 } else { -0.1 }) * 10.0).round() / 10.0;
+                state.set_room_weather(Weather { density: density.clamp(0.1, 1.0), ..weather });
+            }
+            y += line_height;
+        }
+    }
+
+    // Current room's audio: ambient loop, its volume and the reverb preset
+    if let Some(audio) = state.current_room().map(|r| r.audio.clone()) {
+        let name = audio.ambient_loop.as_deref()
+            .map(|p| std::path::Path::new(p).file_name().map_or(p.to_string(), |n| n.to_string_lossy().into_owned()));
+        draw_text(&format!("Loop: {}", name.as_deref().unwrap_or("None")), x, (y + 14.0).floor(), 16.0, WHITE);
+        y += line_height;
+        let half = (rect.w - 10.0) / 2.0;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let pick = Rect::new(x, y + 2.0, half, line_height - 2.0);
+            if draw_text_button(ctx, pick, "Pick WAV...", Color::from_rgba(60, 60, 70, 255)) {
+                if let Some(path) = rfd::FileDialog::new().add_filter("WAV", &["wav"]).pick_file() {
+                    state.set_room_audio(RoomAudio { ambient_loop: Some(path.to_string_lossy().into_owned()), ..audio.clone() });
+                }
+            }
+        }
+        let clear = Rect::new(x + half + 4.0, y + 2.0, half, line_height - 2.0);
+        if audio.ambient_loop.is_some() && draw_text_button(ctx, clear, "Clear loop", Color::from_rgba(60, 60, 70, 255)) {
+            state.set_room_audio(RoomAudio { ambient_loop: None, ..audio.clone() });
+        }
+        y += line_height;
+        if audio.ambient_loop.is_some() {
+            draw_text(&format!("Volume: {:.1}", audio.volume), x + 8.0, (y + 14.0).floor(), 16.0, WHITE);
+            if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+                let volume = ((audio.volume + if up { 0.1 } else { -0.1 }) * 10.0).round() / 10.0;
+                state.set_room_audio(RoomAudio { volume: volume.clamp(0.0, 1.0), ..audio.clone() });
+            }
+            y += line_height;
+        }
+        draw_text(&format!("Reverb: {}", audio.reverb.label()), x, (y + 14.0).floor(), 16.0, WHITE);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            let presets = ReverbPreset::ALL;
+            let i = presets.iter().position(|p| *p == audio.reverb).unwrap_or(0);
+            let next = if up { (i + 1) % presets.len() } else { (i + presets.len() - 1) % presets.len() };
+            state.set_room_audio(RoomAudio { reverb: presets[next], ..audio });
+        }
+        y += line_height;
+    }
+
     if let Some(room) = state.current_room() {
         draw_text(&format!("ID: {}", room.id), x, (y + 14.0).floor(), 16.0, WHITE);
         y += line_height;
@@ -352,9 +826,50 @@ fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         draw_text(&format!("Portals: {}", room.portals.len()), x, (y + 14.0).floor(), 16.0, WHITE);
         y += line_height;
 
+        // Portal list - click one to set it up in the Properties panel
+        let portal_rows: Vec<String> = room.portals
+            .iter()
+            .map(|p| format!("  -> Room {} ({})", p.target_room, p.kind.label()))
+            .collect();
+        let room_idx = state.current_room;
+        for (i, label) in portal_rows.iter().enumerate() {
+            let row = Rect::new(x, y, rect.w - 4.0, line_height);
+            let sel = super::Selection::Portal { room: room_idx, portal: i };
+            if state.selection == sel {
+                draw_rectangle(row.x.floor(), row.y.floor(), row.w, row.h, Color::from_rgba(80, 50, 80, 255));
+            }
+            if ctx.mouse.clicked(&row) {
+                state.selection = sel;
+            }
+            draw_text(label, x, (y + 14.0).floor(), 16.0, Color::from_rgba(255, 100, 255, 255));
+            y += line_height;
+        }
+
+        // Whole-room transforms
+        let transforms = [
+            ("Rot CW", SectorTransform::RotateCw),
+            ("Rot CCW", SectorTransform::RotateCcw),
+            ("Flip X", SectorTransform::MirrorX),
+            ("Flip Z", SectorTransform::MirrorZ),
+        ];
+        let button_w = ((rect.w - 4.0) / transforms.len() as f32).floor();
+        for (i, (label, t)) in transforms.into_iter().enumerate() {
+            let btn = Rect::new(x + i as f32 * button_w, y + 2.0, button_w - 2.0, line_height - 2.0);
+            if draw_text_button(ctx, btn, label, Color::from_rgba(60, 60, 70, 255)) {
+                state.transform_current_room(t);
+            }
+        }
+        y += line_height + 2.0;
+
+        let trim_btn = Rect::new(x, y + 2.0, rect.w - 6.0, line_height - 2.0);
+        if draw_text_button(ctx, trim_btn, "Trim empty borders", Color::from_rgba(60, 60, 70, 255)) {
+            state.trim_current_room();
+        }
+        y += line_height + 2.0;
+
         // Room list
         y += 10.0;
-        draw_text("Rooms:", x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+        draw_text(&tr("editor.rooms"), x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
         y += line_height;
 
         for (i, room) in state.level.rooms.iter().enumerate() {
@@ -366,12 +881,25 @@ fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
             };
 
             let room_btn_rect = Rect::new(x, y, rect.w - 4.0, line_height);
+            let in_merge_set = state.multi_selection.contains(&super::Selection::Room(i));
             if ctx.mouse.clicked(&room_btn_rect) {
-                state.current_room = i;
+                // Shift+click marks rooms to merge into the current one
+                if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                    let sel = super::Selection::Room(i);
+                    if let Some(pos) = state.multi_selection.iter().position(|s| *s == sel) {
+                        state.multi_selection.remove(pos);
+                    } else if !is_selected {
+                        state.multi_selection.push(sel);
+                    }
+                } else {
+                    state.current_room = i;
+                }
             }
 
             if is_selected {
                 draw_rectangle(room_btn_rect.x.floor(), room_btn_rect.y.floor(), room_btn_rect.w, room_btn_rect.h, Color::from_rgba(60, 80, 60, 255));
+            } else if in_merge_set {
+                draw_rectangle(room_btn_rect.x.floor(), room_btn_rect.y.floor(), room_btn_rect.w, room_btn_rect.h, Color::from_rgba(80, 70, 40, 255));
             }
 
             let sector_count = room.iter_sectors().count();
@@ -383,7 +911,399 @@ fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
             }
         }
     } else {
-        draw_text("No room selected", x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+        draw_text(&tr("editor.no_room"), x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+    }
+}
+
+/// Selection set rows (click recalls, "Aa" renames, "x" deletes) and a save
+/// button. Returns the y below the section.
+fn draw_selection_sets(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let line_height = 20.0;
+    let x = rect.x + 8.0;
+    let mut y = rect.y;
+
+    // Typing into the set being renamed: Enter keeps the name, Escape cancels
+    if state.renaming_set.as_ref().is_some_and(|(i, _)| *i >= state.level.selection_sets.len()) {
+        state.renaming_set = None;
+    }
+    if let Some((idx, mut name)) = state.renaming_set.take() {
+        match edit_text(ctx, SET_NAME_FOCUS, &mut name, 32) {
+            TextEdit::Typing => state.renaming_set = Some((idx, name)),
+            TextEdit::Commit => state.rename_selection_set(idx, &name),
+            TextEdit::Cancel => {}
+        }
+    }
+
+    let mut recall = None;
+    let mut delete = None;
+    for (i, set) in state.level.selection_sets.iter().enumerate() {
+        let row = Rect::new(x, y, rect.w - 60.0, line_height);
+        let rename_btn = Rect::new(rect.right() - 48.0, y + 1.0, 24.0, line_height - 2.0);
+        let delete_btn = Rect::new(rect.right() - 22.0, y + 1.0, 20.0, line_height - 2.0);
+        let label = match &state.renaming_set {
+            Some((idx, name)) if *idx == i => {
+                draw_rectangle(row.x.floor(), row.y.floor(), row.w, row.h, Color::from_rgba(50, 50, 70, 255));
+                format!("{}_", name)
+            }
+            _ => format!("{} ({})", set.name, set.members.len()),
+        };
+        if ctx.mouse.clicked(&row) {
+            recall = Some(i);
+        }
+        draw_text(&label, x, (y + 14.0).floor(), 16.0, WHITE);
+        if draw_text_button(ctx, rename_btn, "Aa", Color::from_rgba(60, 60, 70, 255)) {
+            state.renaming_set = Some((i, set.name.clone()));
+            ctx.set_focus(SET_NAME_FOCUS);
+        }
+        if draw_text_button(ctx, delete_btn, "x", Color::from_rgba(90, 50, 50, 255)) {
+            delete = Some(i);
+        }
+        y += line_height;
+    }
+    if let Some(i) = recall {
+        state.recall_selection_set(i);
+    }
+    if let Some(i) = delete {
+        state.delete_selection_set(i);
+    }
+
+    let save = Rect::new(x, y + 2.0, rect.w - 14.0, line_height - 2.0);
+    if draw_text_button(ctx, save, &tr("editor.save_selection_set"), Color::from_rgba(60, 60, 70, 255)) {
+        state.save_selection_set();
+    }
+    y + line_height + 2.0
+}
+
+/// Record button, one row per macro (play / delete), and the replay parameters
+fn draw_macros(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let line_height = 20.0;
+    let x = rect.x + 8.0;
+    let mut y = rect.y;
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+
+    let record = Rect::new(x, y + 2.0, rect.w - 14.0, line_height - 2.0);
+    if state.recording_macro.is_some() {
+        if draw_text_button(ctx, record, &tr("editor.macro_stop"), Color::from_rgba(140, 50, 50, 255)) {
+            state.stop_macro_recording();
+        }
+    } else if draw_text_button(ctx, record, &tr("editor.macro_record"), button_color) {
+        state.start_macro_recording();
+    }
+    y += line_height + 2.0;
+
+    let mut play = None;
+    let mut delete = None;
+    for (i, edit) in state.level.macros.iter().enumerate() {
+        let play_btn = Rect::new(rect.right() - 70.0, y + 1.0, 46.0, line_height - 2.0);
+        let delete_btn = Rect::new(rect.right() - 22.0, y + 1.0, 20.0, line_height - 2.0);
+        draw_text(&format!("{} ({})", edit.name, edit.cells.len()), x, (y + 14.0).floor(), 16.0, WHITE);
+        if draw_text_button(ctx, play_btn, &tr("editor.macro_play"), button_color) {
+            play = Some(i);
+        }
+        if draw_text_button(ctx, delete_btn, "x", Color::from_rgba(90, 50, 50, 255)) {
+            delete = Some(i);
+        }
+        y += line_height;
+    }
+    if let Some(i) = play {
+        state.play_macro(i);
+    }
+    if let Some(i) = delete {
+        state.delete_macro(i);
+    }
+
+    // Replay parameters
+    let click = state.level.click_height;
+    let replay = &mut state.macro_replay;
+    let rows = [
+        format!("Repeats: {}", replay.repeats),
+        format!("Step X: {}", replay.step.0),
+        format!("Step Z: {}", replay.step.1),
+        format!("Rise: {} clicks", format_clicks(replay.rise / click)),
+    ];
+    for (i, label) in rows.iter().enumerate() {
+        draw_text(label, x, (y + 14.0).floor(), 16.0, WHITE);
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            let d = if up { 1 } else { -1 };
+            match i {
+                0 => replay.repeats = (replay.repeats as i32 + d).clamp(1, 64) as usize,
+                1 => replay.step.0 = (replay.step.0 + d).clamp(-64, 64),
+                2 => replay.step.1 = (replay.step.1 + d).clamp(-64, 64),
+                _ => replay.rise += d as f32 * click,
+            }
+        }
+        y += line_height;
+    }
+    y
+}
+
+/// The active camera path: a row that steps between paths, its trigger name
+/// ("Aa" renames, empty clears) and one row per key with its time in half
+/// seconds and an "x" to delete it. Returns the y below the section.
+fn draw_camera_paths(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let line_height = 20.0;
+    let x = rect.x + 8.0;
+    let mut y = rect.y;
+    let count = state.level.camera_paths.len();
+    if count == 0 {
+        draw_text(&tr("editor.camera_paths_hint"), x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+        return y + line_height;
+    }
+    if state.active_camera_path >= count {
+        state.active_camera_path = count - 1;
+        state.renaming_trigger = None;
+    }
+    let idx = state.active_camera_path;
+
+    // Path: -/+ step between the level's paths
+    let path = &state.level.camera_paths[idx];
+    draw_text(&format!("{} ({} keys)", path.name, path.keys.len()), x, (y + 14.0).floor(), 16.0, WHITE);
+    if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+        state.active_camera_path = if up { (idx + 1) % count } else { (idx + count - 1) % count };
+        state.renaming_trigger = None;
+        return y + line_height;
+    }
+    y += line_height;
+
+    // Trigger: Enter keeps the typed name, Escape cancels
+    if let Some(mut name) = state.renaming_trigger.take() {
+        match edit_text(ctx, TRIGGER_FOCUS, &mut name, 32) {
+            TextEdit::Typing => state.renaming_trigger = Some(name),
+            TextEdit::Commit => state.set_camera_trigger(idx, &name),
+            TextEdit::Cancel => {}
+        }
+    }
+    let path = &state.level.camera_paths[idx];
+    let row = Rect::new(x, y, rect.w - 36.0, line_height);
+    let label = match &state.renaming_trigger {
+        Some(name) => {
+            draw_rectangle(row.x.floor(), row.y.floor(), row.w, row.h, Color::from_rgba(50, 50, 70, 255));
+            format!("{}: {}_", tr("editor.camera_trigger"), name)
+        }
+        None => format!("{}: {}", tr("editor.camera_trigger"), path.trigger.clone().unwrap_or_else(|| tr("editor.camera_trigger_none"))),
+    };
+    draw_text(&label, x, (y + 14.0).floor(), 16.0, WHITE);
+    let rename_btn = Rect::new(rect.right() - 26.0, y + 1.0, 24.0, line_height - 2.0);
+    if draw_text_button(ctx, rename_btn, "Aa", Color::from_rgba(60, 60, 70, 255)) {
+        state.renaming_trigger = Some(path.trigger.clone().unwrap_or_default());
+        ctx.set_focus(TRIGGER_FOCUS);
+    }
+    y += line_height;
+
+    // Keys: time on the -/+ buttons, clamped between the neighbouring keys
+    let mut retime = None;
+    let mut delete = None;
+    for (i, key) in path.keys.iter().enumerate() {
+        draw_text(&format!("Key {}: {:.1}s", i + 1, key.time), x + 8.0, (y + 14.0).floor(), 16.0, WHITE);
+        let delete_btn = Rect::new(rect.right() - 70.0, y + 1.0, 20.0, line_height - 2.0);
+        if draw_text_button(ctx, delete_btn, "x", Color::from_rgba(90, 50, 50, 255)) {
+            delete = Some(i);
+        }
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            retime = Some((i, key.time + if up { 0.5 } else { -0.5 }));
+        }
+        y += line_height;
+    }
+    if let Some((i, time)) = retime {
+        state.set_camera_key_time(idx, i, time);
+    }
+    if let Some(i) = delete {
+        state.delete_camera_key(idx, i);
+    }
+    y
+}
+
+/// Two rows per event: when it runs (start or channel, then the delay) and
+/// its action (kind, then number), an Add button, then the respawn rules
+/// (which decide whether the timeline starts over when the player respawns)
+fn draw_events(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let line_height = 20.0;
+    let x = rect.x + 8.0;
+    let mut y = rect.y;
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+
+    let mut changed = None;
+    let mut delete = None;
+    for (i, event) in state.level.events.iter().enumerate() {
+        let mut event = *event;
+        // When: the channel's -/+ (left) steps down past channel 0 to "start",
+        // the delay's (right) in half seconds
+        let delete_btn = Rect::new(rect.right() - 118.0, y + 1.0, 20.0, line_height - 2.0);
+        draw_text(&format!("{}. {}", i + 1, event.when_label()), x, (y + 14.0).floor(), 16.0, WHITE);
+        if draw_text_button(ctx, delete_btn, "x", Color::from_rgba(90, 50, 50, 255)) {
+            delete = Some(i);
+        }
+        if let Some(up) = step_buttons(ctx, rect.right() - 48.0, y, line_height - 2.0) {
+            event.channel = match (event.channel, up) {
+                (None, true) => Some(0),
+                (None, false) | (Some(0), false) => None,
+                (Some(c), _) => Some((c + if up { 1 } else { -1 }).min(99)),
+            };
+        }
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            event.delay = (event.delay + if up { 0.5 } else { -0.5 }).max(0.0);
+        }
+        y += line_height;
+
+        // Action: the kind cycles on the left buttons, the number on the right
+        draw_text(&event.action.label(), x + 8.0, (y + 14.0).floor(), 16.0, WHITE);
+        if let Some(up) = step_buttons(ctx, rect.right() - 48.0, y, line_height - 2.0) {
+            event.action = event.action.cycled(up);
+        }
+        if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+            event.action = event.action.with_value((event.action.value() + if up { 1 } else { -1 }).clamp(0, 99));
+        }
+        y += line_height;
+        if event != state.level.events[i] {
+            changed = Some((i, event));
+        }
+    }
+    if let Some((i, event)) = changed {
+        state.set_level_event(i, event);
+    }
+    if let Some(i) = delete {
+        state.delete_level_event(i);
+    }
+
+    let add = Rect::new(x, y + 2.0, rect.w - 14.0, line_height - 2.0);
+    if draw_text_button(ctx, add, &tr("editor.event_add"), button_color) {
+        state.add_level_event();
+    }
+    y += line_height + 4.0;
+
+    let rules = state.level.respawn;
+    let toggles = [("Respawn resets enemies", rules.reset_enemies), ("Respawn resets doors", rules.reset_doors)];
+    for (i, (label, on)) in toggles.into_iter().enumerate() {
+        draw_text(label, x, (y + 14.0).floor(), 16.0, WHITE);
+        let toggle = Rect::new(rect.right() - 44.0, y + 1.0, 40.0, line_height - 2.0);
+        if draw_text_button(ctx, toggle, if on { "On" } else { "Off" }, button_color) {
+            let mut rules = rules;
+            if i == 0 {
+                rules.reset_enemies = !on;
+            } else {
+                rules.reset_doors = !on;
+            }
+            state.set_respawn_rules(rules);
+        }
+        y += line_height;
+    }
+    draw_text(&format!("Respawn fade: {:.1}s", rules.fade), x, (y + 14.0).floor(), 16.0, WHITE);
+    if let Some(up) = step_buttons(ctx, rect.right(), y, line_height - 2.0) {
+        let fade = (rules.fade + if up { 0.5 } else { -0.5 }).clamp(0.0, 5.0);
+        state.set_respawn_rules(RespawnRules { fade, ..rules });
+    }
+    y + line_height
+}
+
+/// One row per note (swatch, room and text); clicking a row jumps to the note
+fn draw_notes(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let line_height = 20.0;
+    let x = rect.x + 8.0;
+    let mut y = rect.y;
+    let mut jump = None;
+    for (r, note) in state.level.notes() {
+        let row = Rect::new(x, y, rect.w - 14.0, line_height);
+        if state.selected_entity == Some(r) {
+            draw_rectangle(row.x.floor(), row.y.floor(), row.w, row.h, Color::from_rgba(50, 50, 70, 255));
+        }
+        let (cr, cg, cb) = note.note_color();
+        draw_rectangle(x.floor(), (y + 5.0).floor(), 10.0, 10.0, Color::from_rgba(cr, cg, cb, 255));
+        let text: String = note.note_text().chars().take(28).collect();
+        draw_text(&format!("{}: {}", r.room, text), x + 16.0, (y + 14.0).floor(), 16.0, WHITE);
+        if ctx.mouse.clicked(&row) {
+            jump = Some(r);
+        }
+        y += line_height;
+    }
+    if y == rect.y {
+        draw_text(&tr("editor.notes_hint"), x, (y + 14.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+        y += line_height;
+    }
+    if let Some(r) = jump {
+        state.jump_to_entity(r);
+    }
+    y
+}
+
+/// Draw a "-" / "+" button pair right-aligned at `right`.
+/// Returns Some(false) for "-", Some(true) for "+" when clicked.
+pub(super) fn step_buttons(ctx: &mut UiContext, right: f32, y: f32, height: f32) -> Option<bool> {
+    let minus_rect = Rect::new(right - 44.0, y, 20.0, height);
+    let plus_rect = Rect::new(right - 22.0, y, 20.0, height);
+    for (btn, label) in [(minus_rect, "-"), (plus_rect, "+")] {
+        let hovered = ctx.mouse.inside(&btn);
+        let bg = if hovered { Color::from_rgba(70, 70, 80, 255) } else { Color::from_rgba(50, 50, 58, 255) };
+        draw_rectangle(btn.x.floor(), btn.y.floor(), btn.w, btn.h, bg);
+        draw_text(label, (btn.x + 6.0).floor(), (btn.y + 14.0).floor(), 16.0, WHITE);
+    }
+    if ctx.mouse.clicked(&minus_rect) {
+        Some(false)
+    } else if ctx.mouse.clicked(&plus_rect) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Undo history: every step, click to jump to it, plus the history depth
+fn draw_history(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let x = rect.x.floor();
+    let line_height = 18.0;
+    let mut y = rect.y.floor();
+
+    // Depth controls
+    draw_text(&format!("Depth: {}", state.history_depth), x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+    match step_buttons(ctx, rect.right(), y, line_height) {
+        Some(false) => state.set_history_depth(state.history_depth.saturating_sub(10)),
+        Some(true) => state.set_history_depth(state.history_depth + 10),
+        None => {}
+    }
+    y += line_height;
+
+    // Memory held by the stacks (unchanged rooms are shared between steps)
+    let memory = state.history_memory();
+    draw_text(
+        &format!("Memory: {} (full copies: {})", format_bytes(memory.bytes), format_bytes(memory.unshared_bytes)),
+        x,
+        (y + 13.0).floor(),
+        14.0,
+        Color::from_rgba(150, 150, 150, 255),
+    );
+    y += line_height + 6.0;
+
+    // Steps: oldest first, then undone steps (greyed) after the current state
+    let applied = state.undo_stack.len();
+    let mut rows: Vec<(String, usize)> = vec![(tr("editor.history.opened"), 0)];
+    rows.extend(state.undo_stack.iter().enumerate().map(|(i, e)| (e.label.clone(), i + 1)));
+    rows.extend(state.redo_stack.iter().rev().enumerate().map(|(i, e)| (e.label.clone(), applied + i + 1)));
+
+    // Keep the current step visible
+    let visible = ((rect.bottom() - y) / line_height).floor().max(1.0) as usize;
+    let first = (applied + 1).saturating_sub(visible);
+
+    let mut jump = None;
+    for (label, steps) in rows.iter().skip(first).take(visible) {
+        let row = Rect::new(x, y, rect.w - 4.0, line_height);
+        let color = if *steps == applied {
+            draw_rectangle(row.x, row.y, row.w, row.h, Color::from_rgba(60, 80, 60, 255));
+            Color::from_rgba(100, 200, 100, 255)
+        } else if *steps > applied {
+            Color::from_rgba(110, 110, 110, 255)
+        } else {
+            WHITE
+        };
+        if ctx.mouse.clicked(&row) {
+            jump = Some(*steps);
+        }
+        draw_text(label, x + 4.0, (y + 13.0).floor(), 14.0, color);
+        y += line_height;
+    }
+
+    if let Some(steps) = jump {
+        if steps != applied {
+            state.jump_to_history(steps);
+            state.set_status(&format!("History: {} step(s) applied", steps), 1.5);
+        }
     }
 }
 
@@ -432,7 +1352,7 @@ fn draw_container_start(
 fn horizontal_face_container_height(face: &crate::world::HorizontalFace) -> f32 {
     let line_height = 18.0;
     let header_height = 22.0;
-    let mut lines = 3; // texture, height, walkable
+    let mut lines = 4; // texture, height, walkable, flags
     if !face.is_flat() {
         lines += 1; // extra line for individual heights
     }
@@ -443,26 +1363,43 @@ fn horizontal_face_container_height(face: &crate::world::HorizontalFace) -> f32
 fn wall_face_container_height(_wall: &crate::world::VerticalFace) -> f32 {
     let line_height = 18.0;
     let header_height = 22.0;
-    let lines = 3; // texture, y range, blend
+    let lines = 4; // texture, y range, blend, flags
     header_height + CONTAINER_PADDING * 2.0 + (lines as f32) * line_height
 }
 
-/// Draw properties for a horizontal face inside a container
-fn draw_horizontal_face_container(
-    ctx: &mut UiContext,
+/// Position and header of a face container
+#[derive(Clone, Copy)]
+struct FaceContainer<'a> {
     x: f32,
     y: f32,
     width: f32,
-    face: &crate::world::HorizontalFace,
-    label: &str,
+    label: &'a str,
     label_color: Color,
-    room_idx: usize,
-    gx: usize,
-    gz: usize,
+}
+
+/// Step a property from the panel; headings (`facing`) step by the angle snap when it's on
+fn step_property(def: &PropertyDef, value: &PropertyValue, steps: i32, snap: &SnapSettings) -> PropertyValue {
+    if def.key != "facing" || snap.angle_step <= 0.0 {
+        return def.step_value(value, steps);
+    }
+    let heading = PropertyDef { step: snap.angle_step, ..def.clone() };
+    match heading.step_value(value, steps) {
+        PropertyValue::Float(degrees) => PropertyValue::Float(snap.snap_angle(degrees)),
+        other => other,
+    }
+}
+
+/// Draw properties for a horizontal face inside a container
+fn draw_horizontal_face_container(
+    ctx: &mut UiContext,
+    container: FaceContainer,
+    face: &crate::world::HorizontalFace,
+    (room_idx, gx, gz): (usize, usize, usize),
     is_floor: bool,
     state: &mut EditorState,
     icon_font: Option<&Font>,
 ) -> f32 {
+    let FaceContainer { x, y, width, label, label_color } = container;
     let line_height = 18.0;
     let header_height = 22.0;
     let container_height = horizontal_face_container_height(face);
@@ -497,10 +1434,10 @@ fn draw_horizontal_face_container(
     let walkable = face.walkable;
     let icon_size = 18.0;
     let btn_rect = Rect::new(content_x, content_y - 2.0, icon_size, icon_size);
-    let clicked = crate::ui::icon_button_active(ctx, btn_rect, icon::FOOTPRINTS, icon_font, "Walkable", walkable);
+    let clicked = crate::ui::icon_button_active(ctx, btn_rect, icon::FOOTPRINTS, icon_font, &tr("editor.walkable"), walkable);
 
     if clicked {
-        if let Some(r) = state.level.rooms.get_mut(room_idx) {
+        if let Some(r) = state.room_mut(room_idx) {
             if let Some(s) = r.get_sector_mut(gx, gz) {
                 if is_floor {
                     if let Some(f) = &mut s.floor {
@@ -512,19 +1449,46 @@ fn draw_horizontal_face_container(
             }
         }
     }
+    content_y += line_height;
+
+    let face = if is_floor { super::SectorFace::Floor } else { super::SectorFace::Ceiling };
+    draw_face_flag_buttons(ctx, content_x, content_y, width - CONTAINER_PADDING * 2.0, (room_idx, gx, gz), face, state);
 
     container_height
 }
 
-/// Draw properties for a wall face inside a container
-fn draw_wall_face_container(
+/// Row of toggles for a face's render/collision flags
+fn draw_face_flag_buttons(
+    ctx: &mut UiContext,
     x: f32,
     y: f32,
     width: f32,
+    (room_idx, gx, gz): (usize, usize, usize),
+    face: super::SectorFace,
+    state: &mut EditorState,
+) {
+    let off_color = Color::from_rgba(60, 60, 70, 255);
+    let on_color = Color::from_rgba(60, 100, 140, 255);
+    let button_w = ((width - 8.0) / 3.0).floor();
+    for (i, flag) in super::FaceFlag::ALL.into_iter().enumerate() {
+        let on = state.face_flag(room_idx, gx, gz, face, flag).unwrap_or(false);
+        let rect = Rect::new(x + i as f32 * (button_w + 4.0), y - 2.0, button_w, 16.0);
+        if draw_text_button(ctx, rect, &tr(flag.label_key()), if on { on_color } else { off_color }) {
+            state.toggle_face_flag(room_idx, gx, gz, face, flag);
+        }
+    }
+}
+
+/// Draw properties for a wall face inside a container
+fn draw_wall_face_container(
+    ctx: &mut UiContext,
+    container: FaceContainer,
     wall: &crate::world::VerticalFace,
-    label: &str,
-    label_color: Color,
+    (room_idx, gx, gz): (usize, usize, usize),
+    face: super::SectorFace,
+    state: &mut EditorState,
 ) -> f32 {
+    let FaceContainer { x, y, width, label, label_color } = container;
     let line_height = 18.0;
     let header_height = 22.0;
     let container_height = wall_face_container_height(wall);
@@ -551,6 +1515,9 @@ fn draw_wall_face_container(
 
     // Blend mode
     draw_text(&format!("Blend: {:?}", wall.blend_mode), content_x.floor(), (content_y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+    content_y += line_height;
+
+    draw_face_flag_buttons(ctx, content_x, content_y, width - CONTAINER_PADDING * 2.0, (room_idx, gx, gz), face, state);
 
     container_height
 }
@@ -576,7 +1543,7 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
     state.properties_scroll = state.properties_scroll.clamp(0.0, max_scroll);
 
     // Enable scissor for clipping
-    let dpi = screen_dpi_scale();
+    let dpi = ui_pixel_scale();
     gl_use_default_material();
     unsafe {
         get_internal_gl().quad_gl.scissor(
@@ -593,8 +1560,11 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
     let mut y = rect.y.floor() - state.properties_scroll;
 
     match &selection {
+        _ if state.tool == EditorTool::PlaceObject => {
+            draw_entity_properties(ctx, x, y, container_width, state);
+        }
         super::Selection::None => {
-            draw_text("Nothing selected", x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+            draw_text(&tr("common.nothing_selected"), x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
         }
         super::Selection::Room(idx) => {
             draw_text(&format!("Room {}", idx), x, (y + 14.0).floor(), 16.0, WHITE);
@@ -614,9 +1584,9 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                     super::SectorFace::Floor => {
                         if let Some(floor) = &sector.floor {
                             let h = draw_horizontal_face_container(
-                                ctx, x, y, container_width, floor, "Floor",
-                                Color::from_rgba(150, 200, 255, 255),
-                                *room, *gx, *gz, true, state, icon_font
+                                ctx,
+                                FaceContainer { x, y, width: container_width, label: "Floor", label_color: Color::from_rgba(150, 200, 255, 255) },
+                                floor, (*room, *gx, *gz), true, state, icon_font
                             );
                             y += h + CONTAINER_MARGIN;
                         } else {
@@ -626,9 +1596,9 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                     super::SectorFace::Ceiling => {
                         if let Some(ceiling) = &sector.ceiling {
                             let h = draw_horizontal_face_container(
-                                ctx, x, y, container_width, ceiling, "Ceiling",
-                                Color::from_rgba(200, 150, 255, 255),
-                                *room, *gx, *gz, false, state, icon_font
+                                ctx,
+                                FaceContainer { x, y, width: container_width, label: "Ceiling", label_color: Color::from_rgba(200, 150, 255, 255) },
+                                ceiling, (*room, *gx, *gz), false, state, icon_font
                             );
                             y += h + CONTAINER_MARGIN;
                         } else {
@@ -637,29 +1607,65 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                     }
                     super::SectorFace::WallNorth(i) => {
                         if let Some(wall) = sector.walls_north.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (North)", Color::from_rgba(255, 180, 120, 255));
+                            let h = draw_wall_face_container(
+                                ctx,
+                                FaceContainer { x, y, width: container_width, label: "Wall (North)", label_color: Color::from_rgba(255, 180, 120, 255) },
+                                wall, (*room, *gx, *gz), *face, state
+                            );
                             y += h + CONTAINER_MARGIN;
                         }
                     }
                     super::SectorFace::WallEast(i) => {
                         if let Some(wall) = sector.walls_east.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (East)", Color::from_rgba(255, 180, 120, 255));
+                            let h = draw_wall_face_container(
+                                ctx,
+                                FaceContainer { x, y, width: container_width, label: "Wall (East)", label_color: Color::from_rgba(255, 180, 120, 255) },
+                                wall, (*room, *gx, *gz), *face, state
+                            );
                             y += h + CONTAINER_MARGIN;
                         }
                     }
                     super::SectorFace::WallSouth(i) => {
                         if let Some(wall) = sector.walls_south.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (South)", Color::from_rgba(255, 180, 120, 255));
+                            let h = draw_wall_face_container(
+                                ctx,
+                                FaceContainer { x, y, width: container_width, label: "Wall (South)", label_color: Color::from_rgba(255, 180, 120, 255) },
+                                wall, (*room, *gx, *gz), *face, state
+                            );
                             y += h + CONTAINER_MARGIN;
                         }
                     }
                     super::SectorFace::WallWest(i) => {
                         if let Some(wall) = sector.walls_west.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (West)", Color::from_rgba(255, 180, 120, 255));
+                            let h = draw_wall_face_container(
+                                ctx,
+                                FaceContainer { x, y, width: container_width, label: "Wall (West)", label_color: Color::from_rgba(255, 180, 120, 255) },
+                                wall, (*room, *gx, *gz), *face, state
+                            );
                             y += h + CONTAINER_MARGIN;
                         }
                     }
                 }
+
+                // Extrude (X / Shift+X in the viewports)
+                let button_color = Color::from_rgba(60, 60, 70, 255);
+                if let super::SectorFace::Floor | super::SectorFace::Ceiling = face {
+                    let half = ((container_width - 4.0) * 0.5).floor();
+                    if draw_text_button(ctx, Rect::new(x, y, half, 20.0), "Extrude up", button_color) {
+                        state.extrude_selection(false);
+                    }
+                    if draw_text_button(ctx, Rect::new(x + half + 4.0, y, half, 20.0), "Extrude down", button_color) {
+                        state.extrude_selection(true);
+                    }
+                } else if draw_text_button(ctx, Rect::new(x, y, container_width, 20.0), "Extrude out", button_color) {
+                    state.extrude_selection(false);
+                }
+                y += 24.0;
+
+                // Continuous UVs across this face and the rest of the selection
+                if draw_text_button(ctx, Rect::new(x, y, container_width, 20.0), "Align textures", button_color) {
+                    state.align_selected_textures();
+                }
             } else {
                 draw_text("Sector not found", x, (y + 14.0).floor(), 14.0, Color::from_rgba(255, 100, 100, 255));
             }
@@ -678,9 +1684,9 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                 // === FLOOR ===
                 if let Some(floor) = &sector.floor {
                     let h = draw_horizontal_face_container(
-                        ctx, x, y, container_width, floor, "Floor",
-                        Color::from_rgba(150, 200, 255, 255),
-                        *room, *gx, *gz, true, state, icon_font
+                        ctx,
+                        FaceContainer { x, y, width: container_width, label: "Floor", label_color: Color::from_rgba(150, 200, 255, 255) },
+                        floor, (*room, *gx, *gz), true, state, icon_font
                     );
                     y += h + CONTAINER_MARGIN;
                 }
@@ -688,29 +1694,33 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                 // === CEILING ===
                 if let Some(ceiling) = &sector.ceiling {
                     let h = draw_horizontal_face_container(
-                        ctx, x, y, container_width, ceiling, "Ceiling",
-                        Color::from_rgba(200, 150, 255, 255),
-                        *room, *gx, *gz, false, state, icon_font
+                        ctx,
+                        FaceContainer { x, y, width: container_width, label: "Ceiling", label_color: Color::from_rgba(200, 150, 255, 255) },
+                        ceiling, (*room, *gx, *gz), false, state, icon_font
                     );
                     y += h + CONTAINER_MARGIN;
                 }
 
                 // === WALLS ===
-                let wall_dirs: [(&str, &Vec<crate::world::VerticalFace>); 4] = [
-                    ("North", &sector.walls_north),
-                    ("East", &sector.walls_east),
-                    ("South", &sector.walls_south),
-                    ("West", &sector.walls_west),
+                let wall_dirs: [(&str, Direction, &Vec<crate::world::VerticalFace>); 4] = [
+                    ("North", Direction::North, &sector.walls_north),
+                    ("East", Direction::East, &sector.walls_east),
+                    ("South", Direction::South, &sector.walls_south),
+                    ("West", Direction::West, &sector.walls_west),
                 ];
 
-                for (dir_name, walls) in wall_dirs {
+                for (dir_name, dir, walls) in wall_dirs {
                     for (i, wall) in walls.iter().enumerate() {
                         let label = if walls.len() == 1 {
                             format!("Wall ({})", dir_name)
                         } else {
                             format!("Wall ({}) [{}]", dir_name, i)
                         };
-                        let h = draw_wall_face_container(x, y, container_width, wall, &label, Color::from_rgba(255, 180, 120, 255));
+                        let h = draw_wall_face_container(
+                            ctx,
+                            FaceContainer { x, y, width: container_width, label: &label, label_color: Color::from_rgba(255, 180, 120, 255) },
+                            wall, (*room, *gx, *gz), super::SectorFace::wall(dir, i), state
+                        );
                         y += h + CONTAINER_MARGIN;
                     }
                 }
@@ -720,6 +1730,42 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
         }
         super::Selection::Portal { room, portal } => {
             draw_text(&format!("Portal {} in Room {}", portal, room), x, (y + 14.0).floor(), 16.0, WHITE);
+            y += 24.0;
+            if let Some(p) = state.level.rooms.get(*room).and_then(|r| r.portals.get(*portal)).cloned() {
+                draw_text(&format!("Target: Room {}", p.target_room), x, (y + 14.0).floor(), 14.0, Color::from_rgba(150, 150, 150, 255));
+                y += 20.0;
+
+                // Warps start out landing in the middle of the target room
+                let target_pos = state.level.rooms.get(p.target_room).map(|t| t.position);
+                let warp_default = match p.kind {
+                    PortalKind::Warp { destination } => destination,
+                    _ => state.level.rooms.get(p.target_room).map_or(crate::rasterizer::Vec3::ZERO, |t| t.world_bounds().center() - t.position),
+                };
+                let kinds = [PortalKind::Opening, PortalKind::Mirror, PortalKind::Warp { destination: warp_default }];
+                let button_w = ((container_width - 8.0) / 3.0).floor();
+                for (i, kind) in kinds.into_iter().enumerate() {
+                    let active = kind.label() == p.kind.label();
+                    let color = if active { Color::from_rgba(60, 100, 140, 255) } else { Color::from_rgba(60, 60, 70, 255) };
+                    if draw_text_button(ctx, Rect::new(x + i as f32 * (button_w + 4.0), y, button_w, 20.0), kind.label(), color) {
+                        state.set_portal_kind(*room, *portal, kind);
+                    }
+                }
+                y += 26.0;
+
+                if let (PortalKind::Warp { destination }, Some(target_pos)) = (p.kind, target_pos) {
+                    draw_text(
+                        &format!("Lands at ({:.0}, {:.0}, {:.0})", destination.x, destination.y, destination.z),
+                        x, (y + 14.0).floor(), 14.0, WHITE,
+                    );
+                    y += 20.0;
+                    if draw_text_button(ctx, Rect::new(x, y, container_width, 20.0), "Land at 3D camera", Color::from_rgba(60, 60, 70, 255)) {
+                        let destination = state.camera_3d.position - target_pos;
+                        state.set_portal_kind(*room, *portal, PortalKind::Warp { destination });
+                    }
+                }
+            } else {
+                draw_text("Portal not found", x, (y + 14.0).floor(), 14.0, Color::from_rgba(255, 100, 100, 255));
+            }
         }
         super::Selection::Edge { room, x: gx, z: gz, face_idx, edge_idx, wall_face } => {
             // Determine face name based on type
@@ -763,8 +1809,8 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
             // Get vertex coordinates
             if let Some(room_data) = state.level.rooms.get(*room) {
                 if let Some(sector) = room_data.get_sector(*gx, *gz) {
-                    let base_x = room_data.position.x + (*gx as f32) * crate::world::SECTOR_SIZE;
-                    let base_z = room_data.position.z + (*gz as f32) * crate::world::SECTOR_SIZE;
+                    let base_x = room_data.position.x + (*gx as f32) * room_data.sector_size;
+                    let base_z = room_data.position.z + (*gz as f32) * room_data.sector_size;
 
                     // Get heights based on face type
                     let heights = if *face_idx == 0 {
@@ -805,9 +1851,9 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                             // Floor/ceiling corners
                             let corners = [
                                 (base_x, base_z),                                           // NW - 0
-                                (base_x + crate::world::SECTOR_SIZE, base_z),               // NE - 1
-                                (base_x + crate::world::SECTOR_SIZE, base_z + crate::world::SECTOR_SIZE), // SE - 2
-                                (base_x, base_z + crate::world::SECTOR_SIZE),               // SW - 3
+                                (base_x + room_data.sector_size, base_z),               // NE - 1
+                                (base_x + room_data.sector_size, base_z + room_data.sector_size), // SE - 2
+                                (base_x, base_z + room_data.sector_size),               // SW - 3
                             ];
 
                             draw_text("Vertex 1:", x, (y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
@@ -845,12 +1891,144 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
     }
 }
 
+/// Entity tool: archetype to place, then the selected entity's properties as
+/// declared by the project's schema
+fn draw_entity_properties(ctx: &mut UiContext, x: f32, mut y: f32, width: f32, state: &mut EditorState) {
+    let row_height = 22.0;
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+    let label_color = Color::from_rgba(150, 150, 150, 255);
+
+    // Archetype placed by the next click
+    let count = state.entity_schema.archetypes.len();
+    let Some(current) = state.entity_schema.archetypes.get(state.entity_archetype).map(|a| a.name.clone()) else {
+        draw_text(&tr("editor.entity_no_schema"), x, (y + 14.0).floor(), 13.0, label_color);
+        return;
+    };
+    draw_text(&trf("editor.entity_place", &[&current]), x, (y + 14.0).floor(), 14.0, WHITE);
+    let next = Rect::new(x + width - 20.0, y, 20.0, 18.0);
+    let prev = Rect::new(next.x - 22.0, y, 20.0, 18.0);
+    if draw_text_button(ctx, prev, "<", button_color) {
+        state.entity_archetype = (state.entity_archetype + count - 1) % count;
+    }
+    if draw_text_button(ctx, next, ">", button_color) {
+        state.entity_archetype = (state.entity_archetype + 1) % count;
+    }
+    y += row_height + CONTAINER_MARGIN;
+
+    let Some(selected) = state.selected_entity else {
+        draw_text(&tr("editor.entity_hint"), x, (y + 14.0).floor(), 13.0, label_color);
+        return;
+    };
+    let Some(entity) = state.level.entity(selected).cloned() else {
+        return;
+    };
+    let Some(archetype) = state.entity_schema.archetype(&entity.archetype).cloned() else {
+        draw_text(&trf("editor.entity_unknown", &[&entity.archetype]), x, (y + 14.0).floor(), 14.0, Color::from_rgba(255, 100, 100, 255));
+        return;
+    };
+
+    let (r, g, b) = archetype.color;
+    draw_text(&archetype.name, x, (y + 14.0).floor(), 16.0, Color::from_rgba(r, g, b, 255));
+    y += row_height;
+
+    // Typing into a text property: Enter keeps the text, Escape cancels
+    if let Some((r, key, mut text)) = state.editing_text.take().filter(|(r, _, _)| *r == selected) {
+        match edit_text(ctx, ENTITY_TEXT_FOCUS, &mut text, 64) {
+            TextEdit::Typing => state.editing_text = Some((r, key, text)),
+            TextEdit::Commit => {
+                let new_value = PropertyValue::Text(text);
+                if entity.get(&key) != Some(&new_value) {
+                    state.save_undo(&format!("Set {} {}", archetype.name, key));
+                    if let Some(e) = state.level.entity_mut(r) {
+                        e.set(&key, new_value);
+                    }
+                }
+            }
+            TextEdit::Cancel => {}
+        }
+    }
+
+    // One row per declared property: key, value and its editor
+    for def in &archetype.properties {
+        let value = entity.value(def);
+        let label = match &state.editing_text {
+            Some((_, key, text)) if *key == def.key => format!("{}_", text),
+            _ => value.label(),
+        };
+        draw_text(&def.key, x, (y + 14.0).floor(), 13.0, label_color);
+        draw_text(&label, x + width * 0.45, (y + 14.0).floor(), 13.0, WHITE);
+
+        let plus = Rect::new(x + width - 20.0, y, 20.0, 18.0);
+        let minus = Rect::new(plus.x - 22.0, y, 20.0, 18.0);
+        let new_value = match (&def.kind, &value) {
+            (PropertyKind::Text, _) => {
+                if draw_text_button(ctx, Rect::new(minus.x, y, 42.0, 18.0), "Aa", button_color) {
+                    state.editing_text = Some((selected, def.key.clone(), value.label()));
+                    ctx.set_focus(ENTITY_TEXT_FOCUS);
+                }
+                None
+            }
+            (_, PropertyValue::Bool(_)) => draw_text_button(ctx, Rect::new(minus.x, y, 42.0, 18.0), &tr("editor.entity_toggle"), button_color)
+                .then(|| def.step_value(&value, 1)),
+            _ => {
+                let down = draw_text_button(ctx, minus, "-", button_color);
+                let up = draw_text_button(ctx, plus, "+", button_color);
+                match (down, up) {
+                    (true, _) => Some(step_property(def, &value, -1, &state.snap)),
+                    (_, true) => Some(step_property(def, &value, 1, &state.snap)),
+                    _ => None,
+                }
+            }
+        };
+        if let Some(new_value) = new_value.filter(|v| *v != value) {
+            state.save_undo(&format!("Set {} {}", archetype.name, def.key));
+            if let Some(e) = state.level.entity_mut(selected) {
+                e.set(&def.key, new_value);
+            }
+        }
+        y += row_height;
+    }
+
+    // Logic preview: fire triggers, doors and platforms by hand
+    y += CONTAINER_MARGIN;
+    let half = (width - 4.0) / 2.0;
+    let preview_color = if state.logic_preview.is_some() { Color::from_rgba(60, 100, 140, 255) } else { button_color };
+    if draw_text_button(ctx, Rect::new(x, y, half, 20.0), &tr("editor.entity_logic_preview"), preview_color) {
+        state.toggle_logic_preview();
+    }
+    if crate::game::is_logic_archetype(&entity.archetype)
+        && draw_text_button(ctx, Rect::new(x + half + 4.0, y, half, 20.0), &tr("editor.entity_activate"), button_color)
+    {
+        state.activate_selected_entity();
+    }
+    y += row_height;
+
+    if draw_text_button(ctx, Rect::new(x, y, width, 20.0), &tr("editor.entity_remove"), button_color) {
+        state.remove_selected_entity();
+    }
+}
+
+/// Height of `draw_entity_properties` content
+fn entity_properties_height(state: &EditorState) -> f32 {
+    let properties = state
+        .selected_entity
+        .and_then(|r| state.level.entity(r))
+        .and_then(|e| state.entity_schema.archetype(&e.archetype))
+        .map(|a| a.properties.len())
+        .unwrap_or(0);
+    22.0 * (properties as f32 + 3.0) + CONTAINER_MARGIN * 2.0 + 20.0
+}
+
 /// Calculate total content height for properties panel (for scroll bounds)
 fn calculate_properties_content_height(selection: &super::Selection, state: &EditorState) -> f32 {
     let header_height = 24.0;
 
     match selection {
-        super::Selection::None | super::Selection::Room(_) | super::Selection::Portal { .. } => 30.0,
+        _ if state.tool == EditorTool::PlaceObject => entity_properties_height(state),
+
+        super::Selection::None | super::Selection::Room(_) => 30.0,
+
+        super::Selection::Portal { .. } => 140.0, // Header, target, kind buttons, warp destination
 
         super::Selection::Edge { .. } => 120.0, // Edge header + 2 vertex coords
 
@@ -893,6 +2071,7 @@ fn calculate_properties_content_height(selection: &super::Selection, state: &Edi
                         }
                     }
                 }
+                height += 48.0; // Extrude and align buttons
             }
             height
         }
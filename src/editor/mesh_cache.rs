@@ -0,0 +1,119 @@
+//! Per-room mesh cache for the 3D viewport
+//!
+//! Building a room's render data walks every sector and looks up every
+//! texture by name. The viewport used to do that for every room on every
+//! frame; the cache keeps each room's vertices and faces and rebuilds a room
+//! only when it is dirty:
+//! - edits mark the rooms they touch dirty (`EditorState::room_mut`), undo and
+//!   redo the rooms that differ between the two snapshots
+//! - room operations that renumber rooms, loading and swapping the texture
+//!   packs (web builds load them late) drop everything
+
+use std::collections::HashMap;
+use crate::rasterizer::{Face, Vertex};
+use crate::world::Room;
+use super::TexturePack;
+
+/// Render data of one room
+struct CachedRoom {
+    vertices: Vec<Vertex>,
+    faces: Vec<Face>,
+}
+
+/// Cached render data of every room, by room index
+#[derive(Default)]
+pub struct RoomMeshCache {
+    /// None for dirty rooms
+    rooms: Vec<Option<CachedRoom>>,
+    /// Texture index by (pack, name), in renderer order
+    texture_index: HashMap<(String, String), usize>,
+    /// Pack names and sizes the index was built from
+    texture_key: Vec<(String, usize)>,
+}
+
+impl RoomMeshCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild the texture index (and drop every mesh) if the packs changed
+    pub fn sync_textures(&mut self, packs: &[TexturePack]) {
+        let key: Vec<(String, usize)> = packs.iter().map(|p| (p.name.clone(), p.textures.len())).collect();
+        if key == self.texture_key {
+            return;
+        }
+        self.texture_index = packs
+            .iter()
+            .flat_map(|p| p.textures.iter().map(move |t| (p.name.clone(), t.name.clone())))
+            .enumerate()
+            .map(|(i, k)| (k, i))
+            .collect();
+        self.texture_key = key;
+        self.rooms.clear();
+    }
+
+    /// Forget rooms past the end of the level
+    pub fn truncate(&mut self, room_count: usize) {
+        self.rooms.truncate(room_count);
+    }
+
+    /// Mark a room dirty so its next `get` rebuilds it
+    pub fn invalidate(&mut self, room_idx: usize) {
+        if let Some(cached) = self.rooms.get_mut(room_idx) {
+            *cached = None;
+        }
+    }
+
+    /// Mark every room dirty
+    pub fn invalidate_all(&mut self) {
+        self.rooms.clear();
+    }
+
+    /// Render data of a room, rebuilt first if it is dirty
+    pub fn get(&mut self, room_idx: usize, room: &Room) -> (&[Vertex], &[Face]) {
+        if self.rooms.len() <= room_idx {
+            self.rooms.resize_with(room_idx + 1, || None);
+        }
+        if self.rooms[room_idx].is_none() {
+            let index = &self.texture_index;
+            let (vertices, faces) = room.to_render_data_with_textures(|tex| {
+                if !tex.is_valid() {
+                    return Some(0); // Fallback to first texture
+                }
+                index.get(&(tex.pack.clone(), tex.name.clone())).copied()
+            });
+            self.rooms[room_idx] = Some(CachedRoom { vertices, faces });
+        }
+        let cached = self.rooms[room_idx].as_ref().expect("room mesh was just cached");
+        (&cached.vertices, &cached.faces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_test_level;
+
+    #[test]
+    fn test_rebuilds_only_dirty_rooms() {
+        let mut level = create_test_level();
+        level.rooms.push(level.rooms[0].clone());
+        let mut cache = RoomMeshCache::new();
+        let cached = |cache: &RoomMeshCache| -> Vec<bool> { cache.rooms.iter().map(Option::is_some).collect() };
+        for (i, room) in level.rooms.iter().enumerate() {
+            cache.get(i, room);
+        }
+        assert_eq!(cached(&cache), [true, true]);
+
+        // Editing room 1 marks room 1 dirty only
+        level.rooms[1].position.y += 256.0;
+        cache.invalidate(1);
+        assert_eq!(cached(&cache), [true, false]);
+        let (vertices, _) = cache.get(1, &level.rooms[1]);
+        assert!(!vertices.is_empty());
+        assert_eq!(cached(&cache), [true, true]);
+
+        cache.invalidate_all();
+        assert!(cached(&cache).is_empty());
+    }
+}
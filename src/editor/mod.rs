@@ -5,8 +5,19 @@
 //! - 3D viewport (software rendered preview)
 //! - Texture palette
 //! - Properties panel
+//! - Snapping settings (height, grid, angle)
+//! - Compare/merge dialog for other versions of a level
+//! - Project panel (project-wide game data: progression, equipment, loot)
+//! - World map view (which level each level exit leads to)
+//! - LAN co-op editing (native only)
+//! - 3D viewport detached into its own window (native only)
 
 mod state;
+mod history;
+mod picking;
+mod mesh_cache;
+mod snapping;
+mod camera_collision;
 mod layout;
 mod grid_view;
 mod viewport_3d;
@@ -14,9 +25,39 @@ mod texture_palette;
 mod texture_pack;
 mod example_levels;
 mod example_browser;
+mod compare_dialog;
+mod session_stats;
+mod budget_dialog;
+mod vram_view;
+mod texture_editor;
+mod project_panel;
+mod world_map_view;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
+#[cfg(not(target_arch = "wasm32"))]
+mod collab;
+#[cfg(not(target_arch = "wasm32"))]
+mod viewport_window;
 
 pub use state::*;
+pub use history::*;
+pub use picking::*;
+pub use snapping::*;
+pub use camera_collision::*;
 pub use layout::*;
 pub use texture_pack::TexturePack;
 pub use example_levels::*;
 pub use example_browser::*;
+pub use compare_dialog::*;
+pub use session_stats::*;
+pub use budget_dialog::*;
+pub use vram_view::*;
+pub use texture_editor::*;
+pub use project_panel::*;
+pub use world_map_view::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use export::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use collab::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use viewport_window::*;
@@ -0,0 +1,191 @@
+//! Viewport picking through the rasterizer's ID buffer
+//!
+//! The 3D viewport renders with `Framebuffer::enable_picking`, so every
+//! visible pixel knows which triangle it came from:
+//! - `PickTable` maps those triangle IDs back to sector faces
+//! - hover tests only look at the faces around the cursor, not every face
+//!   in the room, and hidden faces never show up
+//! - `faces_at_point` lists every face under a point, front to back, for
+//!   selecting through occluders (Alt in the viewport)
+
+use crate::rasterizer::{barycentric, Camera, Vec3, world_to_screen};
+use crate::world::{Room, Sector};
+use super::SectorFace;
+
+/// A sector face that can be picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickTarget {
+    pub room: usize,
+    pub gx: usize,
+    pub gz: usize,
+    pub face: SectorFace,
+}
+
+/// Triangle ID -> sector face, rebuilt every frame alongside the ID buffer
+#[derive(Debug, Clone, Default)]
+pub struct PickTable {
+    /// One entry per sector face (each face is rendered as two triangles)
+    targets: Vec<PickTarget>,
+}
+
+impl PickTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.targets.clear();
+    }
+
+    /// Register a room's faces and return the ID of its first triangle
+    /// (set it as `Framebuffer::pick_base` before rendering the room).
+    ///
+    /// The order matches `Room::to_render_data_with_textures`.
+    pub fn push_room(&mut self, room_idx: usize, room: &Room) -> u32 {
+        let base = (self.targets.len() * 2) as u32;
+        for (gx, gz, sector) in room.iter_sectors() {
+            for face in sector_faces(sector) {
+                self.targets.push(PickTarget { room: room_idx, gx, gz, face });
+            }
+        }
+        base
+    }
+
+    /// Face a triangle ID belongs to
+    pub fn get(&self, id: u32) -> Option<PickTarget> {
+        self.targets.get(id as usize / 2).copied()
+    }
+}
+
+/// Faces of a sector in render order: floor, ceiling, then walls N/E/S/W
+pub fn sector_faces(sector: &Sector) -> Vec<SectorFace> {
+    let mut faces = Vec::new();
+    if sector.floor.is_some() {
+        faces.push(SectorFace::Floor);
+    }
+    if sector.ceiling.is_some() {
+        faces.push(SectorFace::Ceiling);
+    }
+    faces.extend((0..sector.walls_north.len()).map(SectorFace::WallNorth));
+    faces.extend((0..sector.walls_east.len()).map(SectorFace::WallEast));
+    faces.extend((0..sector.walls_south.len()).map(SectorFace::WallSouth));
+    faces.extend((0..sector.walls_west.len()).map(SectorFace::WallWest));
+    faces
+}
+
+/// World-space corners of a sector face, in the same order as its heights
+/// (floors/ceilings: NW, NE, SE, SW; walls: bottom-left, bottom-right, top-right, top-left)
+pub fn face_corners(room: &Room, gx: usize, gz: usize, face: SectorFace) -> Option<[Vec3; 4]> {
+    let sector = room.get_sector(gx, gz)?;
+    let x = room.position.x + gx as f32 * room.sector_size;
+    let z = room.position.z + gz as f32 * room.sector_size;
+    let (x1, z1) = (x + room.sector_size, z + room.sector_size);
+
+    let horizontal = |h: [f32; 4]| [
+        Vec3::new(x, h[0], z),
+        Vec3::new(x1, h[1], z),
+        Vec3::new(x1, h[2], z1),
+        Vec3::new(x, h[3], z1),
+    ];
+    let vertical = |h: [f32; 4], (ax, az): (f32, f32), (bx, bz): (f32, f32)| [
+        Vec3::new(ax, h[0], az),
+        Vec3::new(bx, h[1], bz),
+        Vec3::new(bx, h[2], bz),
+        Vec3::new(ax, h[3], az),
+    ];
+
+    match face {
+        SectorFace::Floor => sector.floor.as_ref().map(|f| horizontal(f.heights)),
+        SectorFace::Ceiling => sector.ceiling.as_ref().map(|c| horizontal(c.heights)),
+        SectorFace::WallNorth(i) => sector.walls_north.get(i).map(|w| vertical(w.heights, (x, z), (x1, z))),
+        SectorFace::WallEast(i) => sector.walls_east.get(i).map(|w| vertical(w.heights, (x1, z), (x1, z1))),
+        SectorFace::WallSouth(i) => sector.walls_south.get(i).map(|w| vertical(w.heights, (x1, z1), (x, z1))),
+        SectorFace::WallWest(i) => sector.walls_west.get(i).map(|w| vertical(w.heights, (x, z1), (x, z))),
+    }
+}
+
+/// Faces of a room covering framebuffer point (x, y), nearest first.
+/// Unlike the ID buffer this includes hidden faces.
+pub fn faces_at_point(room: &Room, room_idx: usize, camera: &Camera, fb_width: usize, fb_height: usize, x: f32, y: f32) -> Vec<PickTarget> {
+    let point = Vec3::new(x, y, 0.0);
+    let mut hits: Vec<(f32, PickTarget)> = Vec::new();
+
+    for (gx, gz, sector) in room.iter_sectors() {
+        for face in sector_faces(sector) {
+            let Some(corners) = face_corners(room, gx, gz, face) else { continue };
+            let depths = corners.map(|c| (c - camera.position).dot(camera.basis_z));
+            let screen = corners.map(|c| world_to_screen(c, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb_width, fb_height));
+            let [Some(s0), Some(s1), Some(s2), Some(s3)] = screen else { continue };
+            let projected = [s0, s1, s2, s3].map(|(sx, sy)| Vec3::new(sx, sy, 0.0));
+
+            // Test both triangles of the quad, interpolating 1/z for a perspective-correct depth
+            for [a, b, c] in [[0, 1, 2], [0, 2, 3]] {
+                let bc = barycentric(point, projected[a], projected[b], projected[c]);
+                if bc.x >= 0.0 && bc.y >= 0.0 && bc.z >= 0.0 {
+                    let inv_z = bc.x / depths[a] + bc.y / depths[b] + bc.z / depths[c];
+                    hits.push((1.0 / inv_z, PickTarget { room: room_idx, gx, gz, face }));
+                    break;
+                }
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+    hits.into_iter().map(|(_, target)| target).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, SECTOR_SIZE};
+
+    #[test]
+    fn test_pick_table_matches_render_order() {
+        let level = create_test_level();
+        let room = &level.rooms[0];
+        let mut table = PickTable::new();
+        assert_eq!(table.push_room(0, room), 0);
+
+        // Two triangles per face, in the same order the room is rendered
+        let (_, faces) = room.to_render_data_with_textures(|_| None);
+        let face_count: usize = room.iter_sectors().map(|(_, _, s)| sector_faces(s).len()).sum();
+        assert_eq!(faces.len(), face_count * 2);
+
+        let (gx, gz, sector) = room.iter_sectors().next().unwrap();
+        let first = sector_faces(sector)[0];
+        assert_eq!(table.get(0), Some(PickTarget { room: 0, gx, gz, face: first }));
+        assert_eq!(table.get(1), table.get(0));
+        assert_eq!(table.get((face_count * 2) as u32), None);
+
+        // A second room starts after the first one's triangles
+        assert_eq!(table.push_room(1, room), (face_count * 2) as u32);
+    }
+
+    #[test]
+    fn test_face_corners_match_heights() {
+        let level = create_test_level();
+        let room = &level.rooms[0];
+        let (gx, gz, sector) = room.iter_sectors().find(|(_, _, s)| s.floor.is_some()).unwrap();
+        let corners = face_corners(room, gx, gz, SectorFace::Floor).unwrap();
+        let heights = sector.floor.as_ref().unwrap().heights;
+        for (corner, height) in corners.iter().zip(heights) {
+            assert_eq!(corner.y, height);
+        }
+        assert_eq!(corners[2].x - corners[0].x, SECTOR_SIZE);
+        assert!(face_corners(room, gx, gz, SectorFace::WallNorth(99)).is_none());
+    }
+
+    #[test]
+    fn test_faces_at_point_front_to_back() {
+        let level = create_test_level();
+        let room = &level.rooms[0];
+
+        // Outside the room looking at the north wall: the south wall is behind it
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(512.0, 512.0, -2000.0);
+        camera.look_at(Vec3::new(512.0, 512.0, 512.0));
+        let faces = faces_at_point(room, 0, &camera, 320, 240, 160.0, 120.0);
+        let walls: Vec<SectorFace> = faces.iter().map(|t| t.face).collect();
+        assert_eq!(walls, vec![SectorFace::WallNorth(0), SectorFace::WallSouth(0)]);
+    }
+}
@@ -0,0 +1,768 @@
+//! Project panel
+//!
+//! Project-wide game data that isn't part of any one level, edited in a
+//! dialog opened from the toolbar:
+//! - Progression: the stat curves and level-up costs of
+//!   `assets/project/progression.ron` (see `game::Progression`)
+//! - Equipment: the weapons and armor of `assets/project/equipment.ron`
+//!   (see `game::Equipment`)
+//! - Loot: the weighted tables of `assets/project/loot.ron` (see
+//!   `game::LootTables`), with a preview that simulates rolls
+//! - Text: the localized string table of `assets/project/text.ron` (see
+//!   `game::GameText`), one column per language
+//!
+//! Edits stay in the dialog until saved (desktop only).
+
+use macroquad::prelude::*;
+use crate::game::{ArmorDef, Equipment, GameText, LootEntry, LootTable, LootTables, Progression, Requirements, Stat, WeaponDef, MAX_DEFENSE};
+use crate::ui::{edit_text, FocusId, Rect, TextEdit, UiContext, draw_scrollable_list, draw_text_button, draw_text_button_enabled, ui_screen_width, ui_screen_height, ACCENT_COLOR};
+use super::step_buttons;
+
+/// Focus of the text field being typed into
+const TEXT_FOCUS: FocusId = "editor.project_text";
+
+/// Page of the project panel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProjectPage {
+    #[default]
+    Progression,
+    Equipment,
+    Loot,
+    Text,
+}
+
+/// Text field being typed into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    Name,
+    Model,
+    /// Comma-separated attack clips
+    Moveset,
+    /// Item of a loot entry
+    Item(usize),
+    /// Key of the selected text row
+    Key,
+    /// Code of the selected language
+    Language,
+    /// Text of a row in a language column
+    Value(usize, usize),
+}
+
+/// Rolls made by the loot preview
+pub const LOOT_PREVIEW_ROLLS: u32 = 1000;
+
+/// Language columns shown at once on the Text page
+const TEXT_COLUMNS: usize = 3;
+
+/// State for the project panel
+#[derive(Default)]
+pub struct ProjectPanel {
+    pub open: bool,
+    pub page: ProjectPage,
+    pub progression: Progression,
+    pub equipment: Equipment,
+    /// Selected gear: weapons first, then armor
+    pub selected: usize,
+    pub loot: LootTables,
+    /// Selected loot table, and entry within it
+    pub table: usize,
+    pub entry: usize,
+    pub table_scroll: f32,
+    /// Result lines of the last loot preview
+    pub preview: Vec<String>,
+    pub text: GameText,
+    /// Selected text row and language column, and the first row shown
+    pub text_row: usize,
+    pub text_column: usize,
+    pub text_scroll: usize,
+    /// Text field being edited, with the text typed so far
+    pub editing: Option<(TextField, String)>,
+    /// Edited since opened or saved
+    pub dirty: bool,
+}
+
+/// What the user chose in the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectAction {
+    None,
+    Save,
+    Close,
+}
+
+impl ProjectPanel {
+    /// Open the panel on the project's current data
+    pub fn show(&mut self, progression: Progression, equipment: Equipment, loot: LootTables, text: GameText) {
+        *self = Self { open: true, page: self.page, progression, equipment, loot, text, ..Default::default() };
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+/// Steps of a stat's base value and per-level gain
+fn stat_steps(stat: Stat) -> (f32, f32) {
+    match stat {
+        Stat::Vigor | Stat::Endurance => (5.0, 1.0),
+        Stat::Strength => (0.05, 0.01),
+    }
+}
+
+/// One "label  value  [-][+]" row; returns the step direction when clicked
+fn value_row(ctx: &mut UiContext, x: f32, right: f32, y: f32, label: &str, value: &str) -> Option<bool> {
+    draw_text(label, x, y + 14.0, 14.0, Color::from_rgba(150, 150, 150, 255));
+    draw_text(value, right - 110.0, y + 14.0, 14.0, WHITE);
+    step_buttons(ctx, right, y, 18.0)
+}
+
+/// Draw the project panel
+pub fn draw_project_panel(ctx: &mut UiContext, panel: &mut ProjectPanel) -> ProjectAction {
+    if !panel.open {
+        return ProjectAction::None;
+    }
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = 520.0;
+    let header_h = 36.0;
+    let footer_h = 44.0;
+    let row_h = 22.0;
+    let dialog_h = header_h + 24.0 + row_h * 11.0 + footer_h;
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header with the page tabs
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    let title = if panel.dirty { "Project *" } else { "Project" };
+    draw_text(title, x + 16.0, y + 24.0, 18.0, WHITE);
+    let mut tab_x = x + dialog_w - 16.0;
+    let pages = [(ProjectPage::Text, "Text"), (ProjectPage::Loot, "Loot"), (ProjectPage::Equipment, "Equipment"), (ProjectPage::Progression, "Progression")];
+    for (page, label) in pages {
+        tab_x -= 96.0;
+        let color = if panel.page == page { ACCENT_COLOR } else { Color::from_rgba(60, 60, 70, 255) };
+        if draw_text_button(ctx, Rect::new(tab_x, y + 6.0, 90.0, 24.0), label, color) {
+            panel.page = page;
+            panel.editing = None;
+        }
+    }
+
+    let area = Rect::new(x + 16.0, y + header_h + 12.0, dialog_w - 32.0, row_h * 11.0);
+    let changed = match panel.page {
+        ProjectPage::Progression => draw_progression_page(ctx, area, row_h, &mut panel.progression),
+        ProjectPage::Equipment => draw_equipment_page(ctx, area, row_h, panel),
+        ProjectPage::Loot => draw_loot_page(ctx, area, row_h, panel),
+        ProjectPage::Text => draw_text_page(ctx, area, row_h, panel),
+    };
+    panel.dirty |= changed;
+
+    // Footer (Escape cancels typing before it closes the panel)
+    let button_y = y + dialog_h - footer_h + 8.0;
+    let close_rect = Rect::new(x + dialog_w - 90.0, button_y, 80.0, 28.0);
+    let escape = is_key_pressed(KeyCode::Escape) && panel.editing.is_none() && !ctx.has_focus(TEXT_FOCUS);
+    if draw_text_button(ctx, close_rect, "Close", Color::from_rgba(60, 60, 70, 255)) || escape {
+        return ProjectAction::Close;
+    }
+    let save_rect = Rect::new(close_rect.x - 90.0, button_y, 80.0, 28.0);
+    if draw_text_button_enabled(ctx, save_rect, "Save", ACCENT_COLOR, panel.dirty) {
+        return ProjectAction::Save;
+    }
+    ProjectAction::None
+}
+
+/// Stat curves, level-up costs and a preview. Returns true if anything changed.
+fn draw_progression_page(ctx: &mut UiContext, area: Rect, row_h: f32, progression: &mut Progression) -> bool {
+    let (left, right) = (area.x, area.right());
+    let mut row_y = area.y;
+    let mut changed = false;
+
+    // Stat curves
+    for stat in Stat::ALL {
+        let (base_step, level_step) = stat_steps(stat);
+        let curve = *progression.curve(stat);
+        let base = format!("{:.2}", curve.base);
+        if let Some(up) = value_row(ctx, left, right, row_y, &format!("{} base", stat.label()), &base) {
+            let step = if up { base_step } else { -base_step };
+            progression.curve_mut(stat).base = (curve.base + step).max(0.0);
+            changed = true;
+        }
+        row_y += row_h;
+        let per_level = format!("+{:.2}", curve.per_level);
+        if let Some(up) = value_row(ctx, left, right, row_y, "  per level", &per_level) {
+            let step = if up { level_step } else { -level_step };
+            progression.curve_mut(stat).per_level = (curve.per_level + step).max(0.0);
+            changed = true;
+        }
+        row_y += row_h;
+    }
+
+    // Level-up costs
+    if let Some(up) = value_row(ctx, left, right, row_y, "First level-up cost", &progression.base_cost.to_string()) {
+        progression.base_cost = if up { progression.base_cost + 10 } else { progression.base_cost.saturating_sub(10) };
+        changed = true;
+    }
+    row_y += row_h;
+    let growth = format!("x{:.2}", progression.cost_growth);
+    if let Some(up) = value_row(ctx, left, right, row_y, "Cost growth per level", &growth) {
+        let step = if up { 0.01 } else { -0.01 };
+        progression.cost_growth = (progression.cost_growth + step).clamp(1.0, 3.0);
+        changed = true;
+    }
+    row_y += row_h * 1.5;
+
+    // Preview
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let costs: Vec<String> = (0..6).map(|level| progression.level_up_cost(level).to_string()).collect();
+    draw_text(&format!("Costs: {} ...", costs.join(", ")), left, row_y + 14.0, 14.0, dim);
+    row_y += row_h;
+    let at_ten: Vec<String> = Stat::ALL
+        .iter()
+        .map(|s| format!("{} {:.1}", s.label(), progression.curve(*s).at(10)))
+        .collect();
+    draw_text(&format!("At level 10: {}", at_ten.join(", ")), left, row_y + 14.0, 14.0, dim);
+    changed
+}
+
+/// Gear list on the left, the selected piece's fields on the right. Returns
+/// true if anything changed.
+fn draw_equipment_page(ctx: &mut UiContext, area: Rect, row_h: f32, panel: &mut ProjectPanel) -> bool {
+    let list_w = 170.0;
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let weapon_count = panel.equipment.weapons.len();
+    let total = weapon_count + panel.equipment.armor.len();
+    let mut changed = false;
+
+    // Gear list: weapons, then armor
+    let mut row_y = area.y;
+    let visible_rows = 9;
+    for i in 0..total.min(visible_rows) {
+        let row = Rect::new(area.x, row_y, list_w, row_h - 2.0);
+        let (kind, name) = if i < weapon_count {
+            ("W", &panel.equipment.weapons[i].name)
+        } else {
+            ("A", &panel.equipment.armor[i - weapon_count].name)
+        };
+        if i == panel.selected {
+            draw_rectangle(row.x, row.y, row.w, row.h, Color::from_rgba(50, 50, 70, 255));
+        }
+        draw_text(&format!("{}  {}", kind, name), row.x + 4.0, row_y + 14.0, 14.0, WHITE);
+        if ctx.mouse.clicked(&row) {
+            panel.selected = i;
+            panel.editing = None;
+        }
+        row_y += row_h;
+    }
+    if total > visible_rows {
+        draw_text(&format!("... {} more", total - visible_rows), area.x + 4.0, row_y + 14.0, 13.0, dim);
+    }
+
+    let button_y = area.bottom() - row_h;
+    let third = (list_w - 8.0) / 3.0;
+    if draw_text_button(ctx, Rect::new(area.x, button_y, third, row_h - 2.0), "+ Wpn", button_color) {
+        let name = format!("Weapon {}", weapon_count + 1);
+        panel.equipment.weapons.push(WeaponDef::new(&name));
+        panel.selected = weapon_count;
+        changed = true;
+    }
+    if draw_text_button(ctx, Rect::new(area.x + third + 4.0, button_y, third, row_h - 2.0), "+ Arm", button_color) {
+        let name = format!("Armor {}", panel.equipment.armor.len() + 1);
+        panel.equipment.armor.push(ArmorDef::new(&name));
+        panel.selected = total;
+        changed = true;
+    }
+    let delete_rect = Rect::new(area.x + (third + 4.0) * 2.0, button_y, third, row_h - 2.0);
+    if draw_text_button_enabled(ctx, delete_rect, "Delete", Color::from_rgba(90, 50, 50, 255), panel.selected < total) {
+        if panel.selected < weapon_count {
+            panel.equipment.weapons.remove(panel.selected);
+        } else {
+            panel.equipment.armor.remove(panel.selected - weapon_count);
+        }
+        panel.selected = panel.selected.saturating_sub(1);
+        panel.editing = None;
+        return true;
+    }
+
+    // Fields of the selected piece
+    let fields = Rect::new(area.x + list_w + 16.0, area.y, area.w - list_w - 16.0, area.h);
+    if panel.selected >= total {
+        draw_text("Add a weapon or armor to edit it", fields.x, fields.y + 14.0, 14.0, dim);
+        return changed;
+    }
+    if panel.selected < weapon_count {
+        let weapon = &mut panel.equipment.weapons[panel.selected];
+        changed |= draw_weapon_fields(ctx, fields, row_h, weapon, &mut panel.editing);
+    } else {
+        let armor = &mut panel.equipment.armor[panel.selected - weapon_count];
+        changed |= draw_armor_fields(ctx, fields, row_h, armor, &mut panel.editing);
+    }
+    changed
+}
+
+fn draw_weapon_fields(ctx: &mut UiContext, rect: Rect, row_h: f32, weapon: &mut WeaponDef, editing: &mut Option<(TextField, String)>) -> bool {
+    let (left, right) = (rect.x, rect.right());
+    let mut changed = false;
+    let moveset = weapon.moveset.join(", ");
+    let rows = [(TextField::Name, weapon.name.as_str()), (TextField::Model, weapon.model.as_str()), (TextField::Moveset, moveset.as_str())];
+    let typed = text_rows(ctx, rect, row_h, &rows, editing);
+    changed |= typed.is_some();
+    match typed {
+        Some((TextField::Name, text)) if !text.trim().is_empty() => weapon.name = text.trim().to_string(),
+        Some((TextField::Model, text)) => weapon.model = text.trim().to_string(),
+        Some((TextField::Moveset, text)) => {
+            weapon.moveset = text.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+        }
+        _ => {}
+    }
+    let mut row_y = rect.y + row_h * 3.0;
+
+    if let Some(up) = value_row(ctx, left, right, row_y, "Damage", &format!("{:.0}", weapon.damage)) {
+        weapon.damage = (weapon.damage + if up { 1.0 } else { -1.0 }).max(0.0);
+        changed = true;
+    }
+    row_y += row_h;
+    if let Some(up) = value_row(ctx, left, right, row_y, "Poise damage", &format!("{:.0}", weapon.poise_damage)) {
+        weapon.poise_damage = (weapon.poise_damage + if up { 1.0 } else { -1.0 }).max(0.0);
+        changed = true;
+    }
+    row_y += row_h;
+    let speed = format!("x{:.2} ({:.2}s)", weapon.speed, weapon.swing_time());
+    if let Some(up) = value_row(ctx, left, right, row_y, "Speed", &speed) {
+        weapon.speed = (weapon.speed + if up { 0.05 } else { -0.05 }).clamp(0.1, 4.0);
+        changed = true;
+    }
+    row_y += row_h;
+    changed | requirement_rows(ctx, left, right, row_y, row_h, &mut weapon.requirements)
+}
+
+fn draw_armor_fields(ctx: &mut UiContext, rect: Rect, row_h: f32, armor: &mut ArmorDef, editing: &mut Option<(TextField, String)>) -> bool {
+    let (left, right) = (rect.x, rect.right());
+    let mut changed = false;
+    let rows = [(TextField::Name, armor.name.as_str()), (TextField::Model, armor.model.as_str())];
+    let typed = text_rows(ctx, rect, row_h, &rows, editing);
+    changed |= typed.is_some();
+    match typed {
+        Some((TextField::Name, text)) if !text.trim().is_empty() => armor.name = text.trim().to_string(),
+        Some((TextField::Model, text)) => armor.model = text.trim().to_string(),
+        _ => {}
+    }
+    let mut row_y = rect.y + row_h * 2.0;
+
+    if let Some(up) = value_row(ctx, left, right, row_y, "Defense", &format!("{:.0}%", armor.defense * 100.0)) {
+        armor.defense = (armor.defense + if up { 0.01 } else { -0.01 }).clamp(0.0, MAX_DEFENSE);
+        changed = true;
+    }
+    row_y += row_h;
+    if let Some(up) = value_row(ctx, left, right, row_y, "Poise", &format!("+{:.0}", armor.poise)) {
+        armor.poise = (armor.poise + if up { 1.0 } else { -1.0 }).max(0.0);
+        changed = true;
+    }
+    row_y += row_h;
+    changed | requirement_rows(ctx, left, right, row_y, row_h, &mut armor.requirements)
+}
+
+/// One row per stat requirement; returns true if one changed
+fn requirement_rows(ctx: &mut UiContext, left: f32, right: f32, mut row_y: f32, row_h: f32, requirements: &mut Requirements) -> bool {
+    let mut changed = false;
+    for stat in Stat::ALL {
+        let level = requirements.get_mut(stat);
+        if let Some(up) = value_row(ctx, left, right, row_y, &format!("Needs {}", stat.label()), &level.to_string()) {
+            *level = if up { *level + 1 } else { level.saturating_sub(1) };
+            changed = true;
+        }
+        row_y += row_h;
+    }
+    changed
+}
+
+/// Text rows ("label  value  [Aa]"): Aa starts typing, Enter keeps the text,
+/// Escape cancels. Returns the field and text when one is committed.
+fn text_rows(
+    ctx: &mut UiContext,
+    rect: Rect,
+    row_h: f32,
+    rows: &[(TextField, &str)],
+    editing: &mut Option<(TextField, String)>,
+) -> Option<(TextField, String)> {
+    let mut committed = None;
+    if let Some((field, mut text)) = editing.take() {
+        match edit_text(ctx, TEXT_FOCUS, &mut text, 200) {
+            TextEdit::Typing => *editing = Some((field, text)),
+            TextEdit::Commit => committed = Some((field, text)),
+            TextEdit::Cancel => {}
+        }
+    }
+
+    let mut row_y = rect.y;
+    for (field, value) in rows {
+        let label = match field {
+            TextField::Name => "Name",
+            TextField::Model => "Model",
+            TextField::Moveset => "Moveset",
+            TextField::Item(_) => "Item",
+            TextField::Key => "Key",
+            TextField::Language => "Language",
+            TextField::Value(..) => "Text",
+        };
+        draw_text(label, rect.x, row_y + 14.0, 14.0, Color::from_rgba(150, 150, 150, 255));
+        let shown = match editing.as_ref() {
+            Some((f, text)) if f == field => {
+                draw_rectangle(rect.x + 70.0, row_y, rect.w - 100.0, row_h - 2.0, Color::from_rgba(50, 50, 70, 255));
+                format!("{}_", text)
+            }
+            _ if value.is_empty() => "-".to_string(),
+            _ => value.to_string(),
+        };
+        draw_text(&fit_text(&shown, rect.w - 104.0, 14.0), rect.x + 74.0, row_y + 14.0, 14.0, WHITE);
+        let edit_btn = Rect::new(rect.right() - 24.0, row_y + 1.0, 24.0, row_h - 4.0);
+        if draw_text_button(ctx, edit_btn, "Aa", Color::from_rgba(60, 60, 70, 255)) {
+            *editing = Some((*field, value.to_string()));
+            ctx.set_focus(TEXT_FOCUS);
+        }
+        row_y += row_h;
+    }
+    committed
+}
+
+/// Table list and the preview on the left, the selected table's entries on
+/// the right. Returns true if anything changed.
+fn draw_loot_page(ctx: &mut UiContext, area: Rect, row_h: f32, panel: &mut ProjectPanel) -> bool {
+    let list_w = 170.0;
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let mut changed = false;
+
+    // Tables
+    let names: Vec<String> = panel.loot.tables.iter().map(|t| t.name.clone()).collect();
+    let list_rect = Rect::new(area.x, area.y, list_w, row_h * 4.0 - 2.0);
+    let selected = Some(panel.table).filter(|t| *t < names.len());
+    let list = draw_scrollable_list(ctx, list_rect, &names, selected, &mut panel.table_scroll, row_h, None);
+    if let Some(i) = list.clicked.filter(|i| *i != panel.table) {
+        panel.table = i;
+        panel.entry = 0;
+        panel.editing = None;
+        panel.preview.clear();
+    }
+    let mut row_y = area.y + row_h * 4.0;
+    let half = (list_w - 4.0) / 2.0;
+    if draw_text_button(ctx, Rect::new(area.x, row_y, half, row_h - 2.0), "+ Table", button_color) {
+        let name = format!("table_{}", panel.loot.tables.len() + 1);
+        panel.loot.tables.push(LootTable::new(&name));
+        panel.table = panel.loot.tables.len() - 1;
+        panel.entry = 0;
+        panel.preview.clear();
+        changed = true;
+    }
+    let has_table = panel.table < panel.loot.tables.len();
+    let delete_rect = Rect::new(area.x + half + 4.0, row_y, half, row_h - 2.0);
+    if draw_text_button_enabled(ctx, delete_rect, "Delete", Color::from_rgba(90, 50, 50, 255), has_table) {
+        panel.loot.tables.remove(panel.table);
+        panel.table = panel.table.saturating_sub(1);
+        panel.editing = None;
+        panel.preview.clear();
+        return true;
+    }
+    row_y += row_h;
+
+    // Preview: drop rates over many simulated rolls
+    let simulate_rect = Rect::new(area.x, row_y, list_w, row_h - 2.0);
+    let label = format!("Simulate {} rolls", LOOT_PREVIEW_ROLLS);
+    if draw_text_button_enabled(ctx, simulate_rect, &label, button_color, has_table) {
+        panel.preview = loot_preview(&panel.loot.tables[panel.table]);
+    }
+    row_y += row_h;
+    for line in &panel.preview {
+        draw_text(line, area.x + 4.0, row_y + 14.0, 13.0, dim);
+        row_y += row_h * 0.8;
+    }
+
+    // The selected table
+    let fields = Rect::new(area.x + list_w + 16.0, area.y, area.w - list_w - 16.0, area.h);
+    let (left, right) = (fields.x, fields.right());
+    let Some(table) = panel.loot.tables.get_mut(panel.table) else {
+        draw_text("Add a table to edit it", fields.x, fields.y + 14.0, 14.0, dim);
+        return changed;
+    };
+    let rows = [(TextField::Name, table.name.as_str())];
+    let typed = text_rows(ctx, fields, row_h, &rows, &mut panel.editing);
+    changed |= typed.is_some();
+    match typed {
+        Some((TextField::Name, text)) if !text.trim().is_empty() => table.name = text.trim().to_string(),
+        Some((TextField::Item(i), text)) if !text.trim().is_empty() => {
+            if let Some(entry) = table.entries.get_mut(i) {
+                entry.item = text.trim().to_string();
+            }
+        }
+        _ => {}
+    }
+    let mut row_y = fields.y + row_h;
+    if let Some(up) = value_row(ctx, left, right, row_y, "Rolls", &table.rolls.to_string()) {
+        table.rolls = if up { (table.rolls + 1).min(10) } else { table.rolls.saturating_sub(1) };
+        changed = true;
+    }
+    row_y += row_h;
+    if let Some(up) = value_row(ctx, left, right, row_y, "Nothing weight", &table.nothing_weight.to_string()) {
+        table.nothing_weight = if up { table.nothing_weight + 1 } else { table.nothing_weight.saturating_sub(1) };
+        changed = true;
+    }
+    row_y += row_h;
+
+    // Entries: click selects, Aa renames the item, x deletes, -/+ weight
+    let total: u32 = table.nothing_weight + table.entries.iter().map(|e| e.weight).sum::<u32>();
+    let visible_entries = 5;
+    let mut delete = None;
+    for (i, entry) in table.entries.iter_mut().enumerate().take(visible_entries) {
+        let row = Rect::new(left, row_y, right - left - 176.0, row_h - 2.0);
+        if i == panel.entry {
+            draw_rectangle(row.x, row.y, row.w, row.h, Color::from_rgba(50, 50, 70, 255));
+        }
+        let item = match &panel.editing {
+            Some((TextField::Item(e), text)) if *e == i => format!("{}_", text),
+            _ => entry.item.clone(),
+        };
+        draw_text(&item, left + 4.0, row_y + 14.0, 14.0, WHITE);
+        if ctx.mouse.clicked(&row) {
+            panel.entry = i;
+        }
+        if draw_text_button(ctx, Rect::new(right - 170.0, row_y + 1.0, 24.0, row_h - 4.0), "Aa", button_color) {
+            panel.editing = Some((TextField::Item(i), entry.item.clone()));
+            ctx.set_focus(TEXT_FOCUS);
+        }
+        if draw_text_button(ctx, Rect::new(right - 142.0, row_y + 1.0, 20.0, row_h - 4.0), "x", Color::from_rgba(90, 50, 50, 255)) {
+            delete = Some(i);
+        }
+        let percent = if total > 0 { entry.weight as f32 / total as f32 * 100.0 } else { 0.0 };
+        draw_text(&format!("w{} {:.0}%", entry.weight, percent), right - 110.0, row_y + 14.0, 14.0, WHITE);
+        if let Some(up) = step_buttons(ctx, right, row_y, 18.0) {
+            entry.weight = if up { entry.weight + 1 } else { entry.weight.saturating_sub(1) };
+            changed = true;
+        }
+        row_y += row_h;
+    }
+    if let Some(i) = delete {
+        table.entries.remove(i);
+        panel.entry = panel.entry.min(table.entries.len().saturating_sub(1));
+        panel.editing = None;
+        changed = true;
+    }
+    if table.entries.len() > visible_entries {
+        draw_text(&format!("... {} more", table.entries.len() - visible_entries), left + 4.0, row_y + 14.0, 13.0, dim);
+    }
+    row_y = fields.y + row_h * (3 + visible_entries) as f32;
+
+    // Amount range of the selected entry
+    if let Some(entry) = table.entries.get_mut(panel.entry) {
+        if let Some(up) = value_row(ctx, left, right, row_y, "Amount min", &entry.min.to_string()) {
+            entry.min = if up { entry.min + 1 } else { entry.min.saturating_sub(1) };
+            entry.max = entry.max.max(entry.min);
+            changed = true;
+        }
+        if let Some(up) = value_row(ctx, left, right, row_y + row_h, "Amount max", &entry.max.to_string()) {
+            entry.max = if up { entry.max + 1 } else { entry.max.saturating_sub(1) };
+            entry.min = entry.min.min(entry.max);
+            changed = true;
+        }
+    }
+    let add_rect = Rect::new(left, fields.bottom() - row_h, 100.0, row_h - 2.0);
+    if draw_text_button(ctx, add_rect, "+ Entry", button_color) {
+        table.entries.push(LootEntry::new("item"));
+        panel.entry = table.entries.len() - 1;
+        changed = true;
+    }
+    if changed {
+        panel.preview.clear();
+    }
+    changed
+}
+
+/// "item  rate%  avg amount" lines for `LOOT_PREVIEW_ROLLS` rolls of a table
+fn loot_preview(table: &LootTable) -> Vec<String> {
+    let counts = table.simulate(LOOT_PREVIEW_ROLLS, 1);
+    if counts.is_empty() {
+        return vec!["Drops nothing".to_string()];
+    }
+    counts
+        .iter()
+        .map(|(item, (hits, amount))| {
+            let rate = *hits as f32 / LOOT_PREVIEW_ROLLS as f32 * 100.0;
+            format!("{} {:.1}% (avg {:.1})", item, rate, *amount as f32 / (*hits).max(1) as f32)
+        })
+        .collect()
+}
+
+/// Cut text to fit a width, keeping the end of text being typed visible
+fn fit_text(text: &str, width: f32, size: f32) -> String {
+    let fits = |t: &str| measure_text(t, None, size as u16, 1.0).width <= width;
+    if fits(text) {
+        return text.to_string();
+    }
+    let typing = text.ends_with('_');
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        if typing {
+            chars.remove(0);
+        } else {
+            chars.pop();
+        }
+        let cut: String = chars.iter().collect();
+        let shown = if typing { format!("..{}", cut) } else { format!("{}..", cut) };
+        if fits(&shown) {
+            return shown;
+        }
+    }
+    String::new()
+}
+
+/// The selected row's key and language's code above a table of every key's
+/// text per language. Click a cell to select it, click it again to type.
+/// Returns true if anything changed.
+fn draw_text_page(ctx: &mut UiContext, area: Rect, row_h: f32, panel: &mut ProjectPanel) -> bool {
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let mut changed = false;
+    let languages = panel.text.languages.len();
+    panel.text_column = panel.text_column.min(languages.saturating_sub(1));
+
+    // Key and language
+    let key = panel.text.rows.get(panel.text_row).map_or("", |r| r.key.as_str());
+    let code = panel.text.language_code(panel.text_column);
+    let rows = [(TextField::Key, key), (TextField::Language, code)];
+    let typed = text_rows(ctx, Rect::new(area.x, area.y, area.w, row_h * 2.0), row_h, &rows, &mut panel.editing);
+    match typed {
+        Some((TextField::Key, text)) => {
+            let text = text.trim();
+            // Keys stay unique
+            if !text.is_empty() && panel.text.row(text).is_none() {
+                if let Some(row) = panel.text.rows.get_mut(panel.text_row) {
+                    row.key = text.to_string();
+                    changed = true;
+                }
+            }
+        }
+        Some((TextField::Language, text)) if !text.trim().is_empty() => {
+            if let Some(code) = panel.text.languages.get_mut(panel.text_column) {
+                *code = text.trim().to_string();
+                changed = true;
+            }
+        }
+        Some((TextField::Value(row, column), text)) => {
+            panel.text.set(row, column, &text);
+            changed = true;
+        }
+        _ => {}
+    }
+
+    // Header: the key column, then up to `TEXT_COLUMNS` languages ending at
+    // the selected one
+    let key_w = 130.0;
+    let col_w = (area.w - key_w) / TEXT_COLUMNS as f32;
+    let first = panel.text_column.saturating_sub(TEXT_COLUMNS - 1);
+    let columns: Vec<usize> = (first..languages).take(TEXT_COLUMNS).collect();
+    let mut row_y = area.y + row_h * 2.0 + 4.0;
+    draw_text("Key", area.x + 4.0, row_y + 14.0, 14.0, dim);
+    for (c, &language) in columns.iter().enumerate() {
+        let cell = Rect::new(area.x + key_w + c as f32 * col_w, row_y, col_w - 2.0, row_h - 2.0);
+        let selected = language == panel.text_column;
+        if selected {
+            draw_rectangle(cell.x, cell.y, cell.w, cell.h, Color::from_rgba(50, 50, 70, 255));
+        }
+        let missing = panel.text.missing(language);
+        let mut label = panel.text.language_code(language).to_string();
+        if language > 0 && missing > 0 {
+            label = format!("{} ({} missing)", label, missing);
+        }
+        draw_text(&fit_text(&label, cell.w - 8.0, 14.0), cell.x + 4.0, row_y + 14.0, 14.0, if selected { ACCENT_COLOR } else { WHITE });
+        if ctx.mouse.clicked(&cell) {
+            panel.text_column = language;
+            panel.editing = None;
+        }
+    }
+    row_y += row_h;
+
+    // Rows (the mouse wheel scrolls)
+    let visible_rows = 6;
+    let table = Rect::new(area.x, row_y, area.w, row_h * visible_rows as f32);
+    let max_scroll = panel.text.rows.len().saturating_sub(visible_rows);
+    if ctx.mouse.inside(&table) && ctx.mouse.scroll != 0.0 {
+        panel.text_scroll = if ctx.mouse.scroll > 0.0 { panel.text_scroll.saturating_sub(1) } else { panel.text_scroll + 1 };
+    }
+    panel.text_scroll = panel.text_scroll.min(max_scroll);
+    let end = (panel.text_scroll + visible_rows).min(panel.text.rows.len());
+    for r in panel.text_scroll..end {
+        let row = &panel.text.rows[r];
+        let key_cell = Rect::new(area.x, row_y, key_w - 2.0, row_h - 2.0);
+        if r == panel.text_row {
+            draw_rectangle(area.x, row_y, area.w, row_h - 2.0, Color::from_rgba(45, 45, 60, 255));
+        }
+        draw_text(&fit_text(&row.key, key_cell.w - 8.0, 14.0), key_cell.x + 4.0, row_y + 14.0, 14.0, WHITE);
+        if ctx.mouse.clicked(&key_cell) {
+            panel.text_row = r;
+            panel.editing = None;
+        }
+        for (c, &language) in columns.iter().enumerate() {
+            let cell = Rect::new(area.x + key_w + c as f32 * col_w, row_y, col_w - 2.0, row_h - 2.0);
+            let selected = r == panel.text_row && language == panel.text_column;
+            if selected {
+                draw_rectangle(cell.x, cell.y, cell.w, cell.h, Color::from_rgba(50, 50, 70, 255));
+            }
+            let (shown, color) = match &panel.editing {
+                Some((TextField::Value(er, ec), text)) if *er == r && *ec == language => (format!("{}_", text), WHITE),
+                _ if row.value(language).is_empty() => ("-".to_string(), Color::from_rgba(200, 90, 90, 255)),
+                _ => (row.value(language).to_string(), WHITE),
+            };
+            draw_text(&fit_text(&shown, cell.w - 8.0, 14.0), cell.x + 4.0, row_y + 14.0, 14.0, color);
+            if ctx.mouse.clicked(&cell) {
+                if selected {
+                    panel.editing = Some((TextField::Value(r, language), row.value(language).to_string()));
+                    ctx.set_focus(TEXT_FOCUS);
+                } else {
+                    panel.text_row = r;
+                    panel.text_column = language;
+                    panel.editing = None;
+                }
+            }
+        }
+        row_y += row_h;
+    }
+    if panel.text.rows.is_empty() {
+        draw_text("Add a row for each line of dialogue, item or menu text", area.x + 4.0, row_y + 14.0, 14.0, dim);
+    } else if panel.text.rows.len() > visible_rows {
+        let shown = format!("rows {}-{} of {}", panel.text_scroll + 1, end, panel.text.rows.len());
+        draw_text(&shown, area.x + 4.0, table.bottom() + 14.0, 13.0, dim);
+    }
+
+    // Row and language buttons
+    let button_y = area.bottom() - row_h;
+    let button_w = (area.w - 12.0) / 4.0;
+    let button = |i: usize| Rect::new(area.x + i as f32 * (button_w + 4.0), button_y, button_w, row_h - 2.0);
+    let delete_color = Color::from_rgba(90, 50, 50, 255);
+    if draw_text_button(ctx, button(0), "+ Row", button_color) {
+        panel.text_row = panel.text.add_row("text");
+        panel.text_scroll = panel.text_row.saturating_sub(visible_rows - 1);
+        panel.editing = None;
+        changed = true;
+    }
+    if draw_text_button_enabled(ctx, button(1), "Delete row", delete_color, panel.text_row < panel.text.rows.len()) {
+        panel.text.rows.remove(panel.text_row);
+        panel.text_row = panel.text_row.saturating_sub(1);
+        panel.editing = None;
+        changed = true;
+    }
+    if draw_text_button(ctx, button(2), "+ Language", button_color) {
+        panel.text.add_language(&format!("lang{}", languages + 1));
+        panel.text_column = languages;
+        panel.editing = None;
+        changed = true;
+    }
+    // The source language can't be removed
+    if draw_text_button_enabled(ctx, button(3), "Delete lang", delete_color, panel.text_column > 0) {
+        panel.text.remove_language(panel.text_column);
+        panel.text_column -= 1;
+        panel.editing = None;
+        changed = true;
+    }
+    changed
+}
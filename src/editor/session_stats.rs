@@ -0,0 +1,144 @@
+//! Session statistics
+//!
+//! Time spent, operations and sectors created while editing a level:
+//! - counted in `SessionStats` on the editor state, outside the undo history,
+//!   so undoing never rewinds them
+//! - folded into the level's metadata when it's saved (`EditStats`); a
+//!   session saved several times still counts once
+//! - shown with the saved totals in the stats dialog (toolbar timer button)
+
+use macroquad::prelude::*;
+use crate::ui::{Rect, UiContext, draw_text_button, ui_screen_width, ui_screen_height};
+use crate::world::{EditStats, Level};
+use super::EditorState;
+
+/// Longest frame counted as editing time (longer gaps mean the app was suspended)
+const MAX_FRAME_SECONDS: f32 = 1.0;
+
+/// Counters since the level was opened or last saved
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    pub stats: EditStats,
+    /// Sector count last time sectors were tallied
+    sector_baseline: usize,
+    /// This session is already counted in the level's saved totals
+    saved: bool,
+}
+
+/// Total sectors in a level
+fn sector_count(level: &Level) -> usize {
+    level.rooms.iter().map(|r| r.iter_sectors().count()).sum()
+}
+
+impl SessionStats {
+    /// Start counting from a level's current state
+    pub fn new(level: &Level) -> Self {
+        Self { stats: EditStats::default(), sector_baseline: sector_count(level), saved: false }
+    }
+
+    /// Count a frame of editing time
+    pub fn tick(&mut self, dt: f32) {
+        self.stats.seconds += dt.clamp(0.0, MAX_FRAME_SECONDS) as f64;
+    }
+
+    /// Count sectors added since the last tally
+    pub fn tally_sectors(&mut self, level: &Level) {
+        let count = sector_count(level);
+        self.stats.sectors_created += count.saturating_sub(self.sector_baseline) as u64;
+        self.sector_baseline = count;
+    }
+
+    /// Take the level's sector count as the baseline without counting it
+    /// (after undo/redo, which aren't new work)
+    pub fn rebase_sectors(&mut self, level: &Level) {
+        self.sector_baseline = sector_count(level);
+    }
+
+    /// Add what was counted since the last save to the level's saved totals
+    pub fn fold_into(&mut self, level: &mut Level) {
+        self.tally_sectors(level);
+        let counted = EditStats { sessions: u32::from(!self.saved), ..self.stats };
+        level.metadata.stats = level.metadata.stats.plus(&counted);
+        self.stats = EditStats::default();
+        self.saved = true;
+    }
+
+    /// Saved totals plus what was counted since the last save
+    pub fn totals(&self, level: &Level) -> EditStats {
+        let counted = EditStats { sessions: u32::from(!self.saved), ..self.stats };
+        level.metadata.stats.plus(&counted)
+    }
+}
+
+/// Draw the stats dialog. Returns true when it should close.
+pub fn draw_stats_dialog(ctx: &mut UiContext, state: &EditorState) -> bool {
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = 360.0;
+    let header_h = 36.0;
+    let footer_h = 44.0;
+    let row_h = 20.0;
+    let dialog_h = header_h + 16.0 + row_h * 5.0 + footer_h;
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("Level Statistics", x + 16.0, y + 24.0, 18.0, WHITE);
+
+    // Columns: since the last save, all sessions (saved totals plus unsaved work)
+    let session = &state.session.stats;
+    let total = state.session.totals(&state.level);
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let (col_session, col_total) = (x + 170.0, x + 260.0);
+    let mut row_y = y + header_h + 20.0;
+    draw_text("Since save", col_session, row_y, 14.0, dim);
+    draw_text("All time", col_total, row_y, 14.0, dim);
+    let rows = [
+        ("Time spent", session.time_label(), total.time_label()),
+        ("Operations", session.operations.to_string(), total.operations.to_string()),
+        ("Sectors created", session.sectors_created.to_string(), total.sectors_created.to_string()),
+        ("Sessions", String::new(), total.sessions.to_string()),
+    ];
+    for (label, now, all) in rows {
+        row_y += row_h;
+        draw_text(label, x + 16.0, row_y, 14.0, dim);
+        draw_text(&now, col_session, row_y, 14.0, WHITE);
+        draw_text(&all, col_total, row_y, 14.0, WHITE);
+    }
+
+    // Footer
+    let close_rect = Rect::new(x + dialog_w - 90.0, y + dialog_h - footer_h + 8.0, 80.0, 28.0);
+    draw_text_button(ctx, close_rect, "Close", Color::from_rgba(60, 60, 70, 255))
+        || is_key_pressed(KeyCode::Escape)
+        || is_key_pressed(KeyCode::Enter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{create_test_level, Room, TextureRef};
+
+    #[test]
+    fn test_session_folds_into_level_once() {
+        let mut level = create_test_level();
+        let mut session = SessionStats::new(&level);
+        // A long gap (app suspended) counts as at most a second
+        session.tick(30.0);
+        session.stats.operations += 1;
+        level.add_room(Room::new(1, Vec3::ZERO, 2, 1));
+        level.rooms[1].set_floor(0, 0, 0.0, TextureRef::none());
+        level.rooms[1].set_floor(1, 0, 0.0, TextureRef::none());
+
+        session.fold_into(&mut level);
+        session.fold_into(&mut level);
+        let saved = level.metadata.stats;
+        assert_eq!((saved.seconds, saved.operations, saved.sectors_created, saved.sessions), (1.0, 1, 2, 1));
+        assert_eq!(session.totals(&level), saved);
+    }
+}
@@ -0,0 +1,110 @@
+//! Snapping settings for the editor viewports
+//!
+//! - Height snap: full click, half click or free (no snapping)
+//! - Reference grid size as a fraction or multiple of the level's sector size
+//! - Angle snap, the step for entity headings (the `facing` property)
+
+/// How heights snap while dragging vertices or adjusting placement height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeightSnap {
+    /// Whole clicks (TRLE behavior)
+    #[default]
+    Click,
+    /// Half a click, for finer slopes
+    HalfClick,
+    /// No snapping
+    Free,
+}
+
+impl HeightSnap {
+    pub const ALL: [HeightSnap; 3] = [HeightSnap::Click, HeightSnap::HalfClick, HeightSnap::Free];
+
+    /// Snapping step for a click height, None when free
+    pub fn step(self, click_height: f32) -> Option<f32> {
+        match self {
+            HeightSnap::Click => Some(click_height),
+            HeightSnap::HalfClick => Some(click_height * 0.5),
+            HeightSnap::Free => None,
+        }
+    }
+
+    /// Snap a height (or height delta)
+    pub fn snap(self, height: f32, click_height: f32) -> f32 {
+        match self.step(click_height) {
+            Some(step) if step > 0.0 => (height / step).round() * step,
+            _ => height.round(),
+        }
+    }
+
+    /// Translation key for the snap menu
+    pub fn label_key(self) -> &'static str {
+        match self {
+            HeightSnap::Click => "editor.snap.click",
+            HeightSnap::HalfClick => "editor.snap.half_click",
+            HeightSnap::Free => "editor.snap.free",
+        }
+    }
+}
+
+/// Reference grid sizes offered in the snap menu, as fractions of the sector size
+pub const GRID_DIVISIONS: [f32; 4] = [0.25, 0.5, 1.0, 2.0];
+
+/// Angle steps offered in the snap menu (degrees, 0 = off)
+pub const ANGLE_STEPS: [f32; 4] = [0.0, 15.0, 45.0, 90.0];
+
+/// Snapping options shown in the toolbar's snap menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapSettings {
+    pub height: HeightSnap,
+    /// Angle step in degrees (0 = off)
+    pub angle_step: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self { height: HeightSnap::Click, angle_step: 15.0 }
+    }
+}
+
+impl SnapSettings {
+    /// Snap an angle in degrees to the current step
+    pub fn snap_angle(&self, degrees: f32) -> f32 {
+        if self.angle_step > 0.0 {
+            (degrees / self.angle_step).round() * self.angle_step
+        } else {
+            degrees
+        }
+    }
+}
+
+/// Format a height in clicks, with decimals only when it's off the click grid
+pub fn format_clicks(clicks: f32) -> String {
+    if (clicks - clicks.round()).abs() < 0.01 {
+        format!("{}", clicks.round() as i32)
+    } else {
+        format!("{:.2}", clicks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_snap_modes() {
+        assert_eq!(HeightSnap::Click.snap(300.0, 256.0), 256.0);
+        assert_eq!(HeightSnap::HalfClick.snap(300.0, 256.0), 256.0);
+        assert_eq!(HeightSnap::HalfClick.snap(400.0, 256.0), 384.0);
+        assert_eq!(HeightSnap::Free.snap(400.4, 256.0), 400.0);
+        assert_eq!(format_clicks(384.0 / 256.0), "1.50");
+        assert_eq!(format_clicks(-2.0), "-2");
+    }
+
+    #[test]
+    fn test_angle_snap() {
+        let mut snap = SnapSettings::default();
+        assert_eq!(snap.snap_angle(50.0), 45.0);
+        snap.angle_step = 0.0;
+        assert_eq!(snap.snap_angle(50.0), 50.0);
+    }
+}
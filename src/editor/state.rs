@@ -1,16 +1,23 @@
 //! Editor state and data
 
+use std::collections::HashMap;
 use std::path::PathBuf;
-use crate::world::Level;
-use crate::rasterizer::{Camera, Vec3, Texture, RasterSettings};
+use crate::modeler::{model_path, Model};
+use crate::world::{overview_camera, render_level, Direction, Level, Room, RoomBuilder, SectorTransform, SelectionSet, SetFace, SetMember, SpatialIndex, TextureRef, VariantSlot, THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+use crate::rasterizer::{Camera, Color as RasterColor, Framebuffer, Vec3, Texture, RasterSettings};
 use super::texture_pack::TexturePack;
+use super::history::{HistoryEntry, HistoryMemory, LevelSnapshot};
+use super::picking::PickTable;
+use super::mesh_cache::RoomMeshCache;
+use super::camera_collision::CameraCollision;
+use super::snapping::SnapSettings;
 
-/// TRLE grid constraints
-/// Sector size in world units (X-Z plane)
-pub const SECTOR_SIZE: f32 = 1024.0;
-/// Height subdivision ("click") in world units (Y axis)
-pub const CLICK_HEIGHT: f32 = 256.0;
-/// Default ceiling height (2x sector size)
+/// Sector size range offered by the level grid controls
+pub const MIN_SECTOR_SIZE: f32 = 128.0;
+pub const MAX_SECTOR_SIZE: f32 = 4096.0;
+/// Smallest click height offered by the level grid controls
+pub const MIN_CLICK_HEIGHT: f32 = 16.0;
+/// Default ceiling height (2x the default sector size)
 pub const CEILING_HEIGHT: f32 = 2048.0;
 
 /// Current editor tool
@@ -22,6 +29,9 @@ pub enum EditorTool {
     DrawCeiling,
     PlacePortal,
     PlaceObject,
+    PlaceWaypoint,
+    /// Drag a rectangle to create an enclosed room
+    BoxRoom,
 }
 
 /// Which face within a sector is selected
@@ -35,6 +45,79 @@ pub enum SectorFace {
     WallWest(usize),
 }
 
+impl SectorFace {
+    /// Wall face on an edge
+    pub fn wall(direction: Direction, index: usize) -> Self {
+        match direction {
+            Direction::North => SectorFace::WallNorth(index),
+            Direction::East => SectorFace::WallEast(index),
+            Direction::South => SectorFace::WallSouth(index),
+            Direction::West => SectorFace::WallWest(index),
+        }
+    }
+
+    /// Edge and stack index, if this is a wall
+    pub fn wall_direction(self) -> Option<(Direction, usize)> {
+        match self {
+            SectorFace::WallNorth(i) => Some((Direction::North, i)),
+            SectorFace::WallEast(i) => Some((Direction::East, i)),
+            SectorFace::WallSouth(i) => Some((Direction::South, i)),
+            SectorFace::WallWest(i) => Some((Direction::West, i)),
+            SectorFace::Floor | SectorFace::Ceiling => None,
+        }
+    }
+}
+
+/// Render/collision flag on a sector face (toggled in the Properties panel)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceFlag {
+    DoubleSided,
+    Invisible,
+    Solid,
+}
+
+impl FaceFlag {
+    pub const ALL: [FaceFlag; 3] = [FaceFlag::DoubleSided, FaceFlag::Invisible, FaceFlag::Solid];
+
+    /// i18n key of the toggle button
+    pub fn label_key(self) -> &'static str {
+        match self {
+            FaceFlag::DoubleSided => "editor.face_double_sided",
+            FaceFlag::Invisible => "editor.face_invisible",
+            FaceFlag::Solid => "editor.face_solid",
+        }
+    }
+}
+
+/// Viewport overlays for debugging face orientation and combat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DebugOverlays {
+    /// Short line along each front face's normal
+    pub normals: bool,
+    /// Outline faces seen from behind in red
+    pub backfaces: bool,
+    /// N/E/S/W on the current room's walls
+    pub wall_labels: bool,
+    /// Hit and hurt volumes while simulating
+    pub hitboxes: bool,
+}
+
+impl DebugOverlays {
+    pub fn any(&self) -> bool {
+        self.normals || self.backfaces || self.wall_labels || self.hitboxes
+    }
+}
+
+/// Corner cell and size (width, depth) of the cell rectangle between `a` and `b`
+pub fn box_room_rect(a: (i32, i32), b: (i32, i32)) -> ((i32, i32), (usize, usize)) {
+    let min = (a.0.min(b.0), a.1.min(b.1));
+    let size = ((a.0 - b.0).unsigned_abs() as usize + 1, (a.1 - b.1).unsigned_abs() as usize + 1);
+    (min, size)
+}
+
+/// Default number of undo steps kept
+pub const DEFAULT_HISTORY_DEPTH: usize = 50;
+
 /// What is currently selected in the editor
 #[derive(Debug, Clone, PartialEq)]
 pub enum Selection {
@@ -84,6 +167,33 @@ impl Selection {
             _ => false,
         }
     }
+
+    /// Selection set member for a sector or sector-face selection
+    pub fn to_set_member(&self) -> Option<SetMember> {
+        match *self {
+            Selection::Sector { room, x, z } => Some(SetMember { room, x, z, face: None }),
+            Selection::SectorFace { room, x, z, face } => {
+                let face = match face {
+                    SectorFace::Floor => SetFace::Floor,
+                    SectorFace::Ceiling => SetFace::Ceiling,
+                    _ => face.wall_direction().map(|(dir, i)| SetFace::Wall(dir, i))?,
+                };
+                Some(SetMember { room, x, z, face: Some(face) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Selection for a selection set member
+    pub fn from_set_member(member: &SetMember) -> Selection {
+        let SetMember { room, x, z, face } = *member;
+        match face {
+            None => Selection::Sector { room, x, z },
+            Some(SetFace::Floor) => Selection::SectorFace { room, x, z, face: SectorFace::Floor },
+            Some(SetFace::Ceiling) => Selection::SectorFace { room, x, z, face: SectorFace::Ceiling },
+            Some(SetFace::Wall(dir, i)) => Selection::SectorFace { room, x, z, face: SectorFace::wall(dir, i) },
+        }
+    }
 }
 
 /// Editor state
@@ -110,12 +220,85 @@ pub struct EditorState {
     /// Currently selected room index (for editing)
     pub current_room: usize,
 
+    /// Selected AI waypoint (waypoint tool)
+    pub selected_waypoint: Option<crate::world::WaypointRef>,
+
+    /// Selected entity (entity tool)
+    pub selected_entity: Option<crate::world::EntityRef>,
+    /// Project entity archetypes (see `world::entity`)
+    pub entity_schema: crate::world::EntitySchema,
+    /// Ambience presets: built-in, then the project's (see `world::ambience`)
+    pub ambience_presets: Vec<crate::world::AmbiencePreset>,
+    /// Ambience preset last picked in the Room panel
+    pub ambience_preset: usize,
+    /// Archetype placed by the entity tool
+    pub entity_archetype: usize,
+
+    /// Camera path being authored in the 3D viewport
+    pub active_camera_path: usize,
+    /// Camera path preview playback (player + camera pose to restore afterwards)
+    pub camera_preview: Option<(crate::game::CutscenePlayer, (Vec3, f32, f32))>,
+    /// Play mode running in the 3D viewport (simulation + camera pose to restore afterwards)
+    pub simulation: Option<(crate::game::Simulation, (Vec3, f32, f32))>,
+    /// Level Ambience section of the Room panel is expanded
+    pub show_ambience: bool,
+    /// Selection Sets section of the Room panel is expanded
+    pub show_selection_sets: bool,
+    /// Selection set being renamed, with the name typed so far
+    pub renaming_set: Option<(usize, String)>,
+    /// Notes section of the Room panel is expanded
+    pub show_notes: bool,
+    /// Text property being typed into (entity, key, text so far)
+    pub editing_text: Option<(crate::world::EntityRef, String, String)>,
+    /// Editing time, operations and sectors created since the last save
+    pub session: super::SessionStats,
+    /// Level statistics dialog is open
+    pub show_stats: bool,
+    /// Budget dialog is open, with the estimate taken when it opened
+    pub budget: Option<super::BudgetDialog>,
+    /// VRAM view is open, with the layout taken when it opened
+    pub vram: Option<super::VramView>,
+    /// Texture being edited in the texture editor, if open
+    pub texture_editor: Option<super::TextureEditor>,
+    /// Macros section of the Room panel is expanded
+    pub show_macros: bool,
+    /// Macro being recorded: room index, the room as it was, and the anchor cell
+    pub recording_macro: Option<(usize, crate::world::Room, (usize, usize))>,
+    /// Repeats, step and rise used when playing a macro
+    pub macro_replay: crate::world::MacroReplay,
+    /// Events section of the Room panel is expanded
+    pub show_events: bool,
+    /// Camera Paths section of the Room panel is expanded
+    pub show_camera_paths: bool,
+    /// Trigger name being typed for the active camera path
+    pub renaming_trigger: Option<String>,
+    /// Sectors other co-op peers have selected, with each peer's color
+    pub peer_selections: Vec<((u8, u8, u8), super::PeerSelection)>,
+    /// Floor noise amplitude in clicks, and its seed
+    pub noise_amplitude: f32,
+    pub noise_seed: u32,
+    /// Door/trigger logic running on the edited level (fired from Properties)
+    pub logic_preview: Option<crate::game::LogicState>,
+
     /// Selected texture reference (pack + name)
     pub selected_texture: crate::world::TextureRef,
 
     /// 3D viewport camera
     pub camera_3d: Camera,
 
+    /// Triangle IDs of the last rendered viewport frame (see `picking`)
+    pub pick_table: PickTable,
+
+    /// Room render data reused across frames until a room changes
+    pub mesh_cache: RoomMeshCache,
+
+    /// Room buckets for viewport ray casts, refreshed before use
+    pub spatial: SpatialIndex,
+
+    /// Select-through (Alt): how many faces deep to pick, and where it started
+    pub select_through_layer: usize,
+    pub select_through_anchor: Option<(usize, usize)>,
+
     /// 2D grid view camera (pan and zoom)
     pub grid_offset_x: f32,
     pub grid_offset_y: f32,
@@ -124,13 +307,23 @@ pub struct EditorState {
     /// Grid settings
     pub grid_size: f32, // World units per grid cell
     pub show_grid: bool,
+    /// Height and angle snapping
+    pub snap: SnapSettings,
+    /// Toolbar x of the open snap menu (None = closed)
+    pub snap_menu_x: Option<f32>,
+    /// Whether level geometry stops the 3D camera
+    pub camera_collision: CameraCollision,
+    /// Face orientation overlays in the 3D viewport
+    pub debug_overlays: DebugOverlays,
 
     /// Vertex editing mode
     pub link_coincident_vertices: bool, // When true, moving a vertex moves all vertices at same position
 
     /// Undo/redo (simple version - just level snapshots)
-    pub undo_stack: Vec<Level>,
-    pub redo_stack: Vec<Level>,
+    pub undo_stack: Vec<HistoryEntry>,
+    pub redo_stack: Vec<HistoryEntry>,
+    /// Maximum number of undo steps kept
+    pub history_depth: usize,
 
     /// Dirty flag (unsaved changes)
     pub dirty: bool,
@@ -141,6 +334,8 @@ pub struct EditorState {
     /// 3D viewport mouse state (for camera control)
     pub viewport_last_mouse: (f32, f32),
     pub viewport_mouse_captured: bool,
+    /// The camera was moved last frame (the viewport renders at reduced resolution meanwhile)
+    pub camera_moving: bool,
 
     /// 2D grid view mouse state
     pub grid_last_mouse: (f32, f32),
@@ -148,6 +343,8 @@ pub struct EditorState {
     pub grid_dragging_vertex: Option<usize>, // Primary dragged vertex (for backward compat)
     pub grid_dragging_vertices: Vec<usize>,   // All vertices being dragged (for linking)
     pub grid_drag_started: bool, // True if we've started dragging (for undo)
+    /// Sector block drag in the grid: (anchor cell, collapse selection if released in place)
+    pub grid_block_drag: Option<((usize, usize), bool)>,
 
     /// 3D viewport vertex dragging state (legacy - kept for compatibility)
     pub viewport_dragging_vertices: Vec<(usize, usize)>, // List of (room_idx, vertex_idx)
@@ -177,6 +374,12 @@ pub struct EditorState {
     pub height_adjust_start_y: f32,        // placement_target_y when height adjust started
     pub height_adjust_locked_pos: Option<(f32, f32)>, // Locked (x, z) position when adjusting
 
+    /// Box room tool: floor and ceiling heights, and the rectangle being
+    /// dragged as (anchor, current) world sector cells
+    pub box_floor_y: f32,
+    pub box_ceiling_y: f32,
+    pub box_room_drag: Option<((i32, i32), (i32, i32))>,
+
     /// Rasterizer settings (PS1 effects)
     pub raster_settings: RasterSettings,
 }
@@ -191,12 +394,14 @@ impl EditorState {
         camera_3d.rotation_x = 0.46;
         camera_3d.rotation_y = 4.02;
         camera_3d.update_basis();
+        let grid_size = level.sector_size;
+        let session = super::SessionStats::new(&level);
 
         // Discover all texture packs
         let texture_packs = TexturePack::discover_all();
-        println!("Discovered {} texture packs", texture_packs.len());
+        log::info!("Discovered {} texture packs", texture_packs.len());
         for pack in &texture_packs {
-            println!("  - {} ({} textures)", pack.name, pack.textures.len());
+            log::debug!("  - {} ({} textures)", pack.name, pack.textures.len());
         }
 
         // Auto-select first texture from first pack (if available)
@@ -215,25 +420,66 @@ impl EditorState {
             selection_rect_start: None,
             selection_rect_end: None,
             current_room: 0,
+            selected_waypoint: None,
+            selected_entity: None,
+            entity_schema: crate::world::EntitySchema::load_project(),
+            ambience_presets: crate::world::project_ambience_presets(),
+            ambience_preset: 0,
+            entity_archetype: 0,
+            active_camera_path: 0,
+            camera_preview: None,
+            simulation: None,
+            show_ambience: false,
+            show_selection_sets: false,
+            renaming_set: None,
+            show_notes: false,
+            editing_text: None,
+            session,
+            show_stats: false,
+            budget: None,
+            vram: None,
+            texture_editor: None,
+            show_macros: false,
+            recording_macro: None,
+            show_events: false,
+            show_camera_paths: false,
+            renaming_trigger: None,
+            macro_replay: Default::default(),
+            peer_selections: Vec::new(),
+            noise_amplitude: 1.0,
+            noise_seed: 1,
+            logic_preview: None,
             selected_texture,
             camera_3d,
+            pick_table: PickTable::new(),
+            mesh_cache: RoomMeshCache::new(),
+            spatial: SpatialIndex::new(),
+            select_through_layer: 1,
+            select_through_anchor: None,
             grid_offset_x: 0.0,
             grid_offset_y: 0.0,
             grid_zoom: 0.1, // Pixels per world unit (very zoomed out for TRLE 1024-unit sectors)
-            grid_size: SECTOR_SIZE, // TRLE sector size
+            grid_size, // Reference grid follows the level's sectors
             show_grid: true,
+            snap: SnapSettings::default(),
+            snap_menu_x: None,
+            camera_collision: CameraCollision::Off,
+            debug_overlays: DebugOverlays::default(),
             link_coincident_vertices: true, // Default to linked mode
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            history_depth: DEFAULT_HISTORY_DEPTH,
             dirty: false,
             status_message: None,
             viewport_last_mouse: (0.0, 0.0),
             viewport_mouse_captured: false,
+            camera_moving: false,
             grid_last_mouse: (0.0, 0.0),
             grid_panning: false,
             grid_dragging_vertex: None,
             grid_dragging_vertices: Vec::new(),
             grid_drag_started: false,
+            grid_block_drag: None,
             viewport_dragging_vertices: Vec::new(),
             viewport_drag_started: false,
             viewport_drag_plane_y: 0.0,
@@ -249,6 +495,9 @@ impl EditorState {
             height_adjust_start_mouse_y: 0.0,
             height_adjust_start_y: 0.0,
             height_adjust_locked_pos: None,
+            box_floor_y: 0.0,
+            box_ceiling_y: CEILING_HEIGHT,
+            box_room_drag: None,
             raster_settings: RasterSettings::default(), // backface_cull=true shows backfaces as wireframe
         }
     }
@@ -262,18 +511,148 @@ impl EditorState {
 
     /// Load a new level, preserving view state (camera, zoom, etc.)
     pub fn load_level(&mut self, level: Level, path: PathBuf) {
-        self.level = level;
+        self.grid_size = level.sector_size;
+        self.replace_level(level);
         self.current_file = Some(path);
         self.dirty = false;
         self.undo_stack.clear();
         self.redo_stack.clear();
+        self.session = super::SessionStats::new(&self.level);
+        self.recording_macro = None;
         self.selection = Selection::None;
+        self.grid_block_drag = None;
+        self.selected_waypoint = None;
+        self.selected_entity = None;
+        self.active_camera_path = 0;
+        self.renaming_trigger = None;
+        self.camera_preview = None;
+        self.simulation = None;
+        self.logic_preview = None;
         // Clamp current_room to valid range
         if self.current_room >= self.level.rooms.len() {
             self.current_room = 0;
         }
     }
 
+    /// Refresh the level's thumbnail, save time and editor version (call before saving)
+    pub fn stamp_metadata(&mut self) {
+        let fb = render_thumbnail(&self.level, &self.texture_packs);
+        let metadata = &mut self.level.metadata;
+        if let Err(e) = metadata.set_thumbnail(&fb.pixels, fb.width, fb.height) {
+            log::warn!("{}", e);
+        }
+        metadata.modified = macroquad::miniquad::date::now() as u64;
+        metadata.editor_version = crate::VERSION.to_string();
+        self.session.fold_into(&mut self.level);
+    }
+
+    /// Replace the level with a merged version (undoable, keeps the file and layout)
+    pub fn apply_merged_level(&mut self, mut level: Level, label: &str) {
+        self.save_undo(label);
+        level.editor_layout = self.level.editor_layout.clone();
+        level.metadata.stats = self.level.metadata.stats;
+        self.grid_size = level.sector_size;
+        self.replace_level(level);
+        // Sectors merged in from the other version aren't this session's work
+        self.session.rebase_sectors(&self.level);
+        self.selection = Selection::None;
+        self.clear_multi_selection();
+        self.selected_waypoint = None;
+        self.selected_entity = None;
+        if self.current_room >= self.level.rooms.len() {
+            self.current_room = 0;
+        }
+        self.set_status(label, 3.0);
+    }
+
+    /// Start or stop simulating play mode in the 3D viewport. The simulation
+    /// runs on a copy of the level; the editor camera comes back when it stops.
+    pub fn toggle_simulation(&mut self) {
+        if let Some((_, (pos, rx, ry))) = self.simulation.take() {
+            self.camera_3d.position = pos;
+            self.camera_3d.rotation_x = rx;
+            self.camera_3d.rotation_y = ry;
+            self.camera_3d.update_basis();
+            self.set_status("Simulation stopped", 1.5);
+            return;
+        }
+        let cam = &self.camera_3d;
+        let restore = match self.camera_preview.take() {
+            Some((_, pose)) => pose,
+            None => (cam.position, cam.rotation_x, cam.rotation_y),
+        };
+        // Fixed seed and no intro cutscene: every run starts the same way
+        let mut sim = crate::game::Simulation::new(self.level.clone(), 0);
+        sim.cutscene = None;
+        sim.set_models(load_enemy_models(&self.level));
+        self.simulation = Some((sim, restore));
+        self.set_status("Simulating: WASD/arrows to move, Esc to stop", 3.0);
+    }
+
+    /// Turn the logic preview on or off. Turning it off puts every door and
+    /// platform back where it was placed.
+    pub fn toggle_logic_preview(&mut self) {
+        if self.logic_preview.take().is_some() {
+            self.set_status("Logic preview off", 1.5);
+        } else {
+            self.logic_preview = Some(crate::game::LogicState::new(&self.level));
+            self.set_status("Logic preview on: select a trigger, door or platform and Activate it", 3.0);
+        }
+    }
+
+    /// Fire the selected logic entity in the preview
+    pub fn activate_selected_entity(&mut self) {
+        let Some(selected) = self.selected_entity else {
+            return;
+        };
+        let logic = self.logic_preview.get_or_insert_with(|| crate::game::LogicState::new(&self.level));
+        let reacted = logic.activate(&self.level, selected);
+        // Level events only run while simulating
+        logic.take_fired();
+        self.set_status(&format!("Activated: {} reacted", reacted), 1.5);
+    }
+
+    /// Place an entity of the current archetype at a room-relative position
+    pub fn place_entity(&mut self, room: usize, position: Vec3) {
+        let Some(archetype) = self.entity_schema.archetypes.get(self.entity_archetype).map(|a| a.name.clone()) else {
+            self.set_status("No entity archetypes (see assets/project/entities.ron)", 3.0);
+            return;
+        };
+        let mut entity = self.entity_schema.instantiate(&archetype, position);
+        // Checkpoint ids are what save games refer to, so keep them unique
+        if archetype == crate::game::CHECKPOINT_ARCHETYPE {
+            entity.set("id", crate::world::PropertyValue::Int(crate::game::next_checkpoint_id(&self.level)));
+        }
+        self.save_undo(&format!("Placed {}", archetype));
+        if let Some(new_entity) = self.level.add_entity(room, entity) {
+            self.selected_entity = Some(new_entity);
+            self.set_status(&format!("Placed {}", archetype), 1.5);
+        }
+    }
+
+    /// Remove the selected entity
+    pub fn remove_selected_entity(&mut self) {
+        if let Some(selected) = self.selected_entity.take() {
+            if self.level.entity(selected).is_some() {
+                self.save_undo("Removed entity");
+                self.level.remove_entity(selected);
+                self.set_status("Entity removed", 1.5);
+            }
+        }
+    }
+
+    /// Select an entity and bring both views to it
+    pub fn jump_to_entity(&mut self, r: crate::world::EntityRef) {
+        let Some(pos) = self.level.entity_world_pos(r) else { return };
+        self.selected_entity = Some(r);
+        self.current_room = r.room;
+        self.grid_offset_x = -pos.x * self.grid_zoom;
+        self.grid_offset_y = pos.z * self.grid_zoom;
+        let size = self.level.sector_size;
+        self.camera_3d.position = pos + Vec3::new(0.0, size * 1.5, -size * 2.0);
+        self.camera_3d.look_at(pos);
+    }
+
     /// Set a status message that will be displayed for a duration
     pub fn set_status(&mut self, message: &str, duration_secs: f64) {
         let expiry = macroquad::time::get_time() + duration_secs;
@@ -290,31 +669,414 @@ impl EditorState {
         None
     }
 
-    /// Save current state for undo
-    pub fn save_undo(&mut self) {
-        self.undo_stack.push(self.level.clone());
+    /// Save current state for undo, labeling the step about to be made
+    pub fn save_undo(&mut self, label: &str) {
+        self.session.tally_sectors(&self.level);
+        self.session.stats.operations += 1;
+        let snapshot = LevelSnapshot::capture(&self.level, self.undo_stack.last().map(|e| &e.snapshot));
+        self.undo_stack.push(HistoryEntry { snapshot, label: label.to_string() });
         self.redo_stack.clear();
         self.dirty = true;
+        self.trim_history();
+    }
 
-        // Limit undo stack size
-        if self.undo_stack.len() > 50 {
-            self.undo_stack.remove(0);
-        }
+    /// Drop the oldest undo steps beyond `history_depth`
+    fn trim_history(&mut self) {
+        let excess = self.undo_stack.len().saturating_sub(self.history_depth);
+        self.undo_stack.drain(..excess);
+    }
+
+    /// Change how many undo steps are kept
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history_depth = depth.max(1);
+        self.trim_history();
     }
 
     /// Undo last action
     pub fn undo(&mut self) {
         if let Some(prev) = self.undo_stack.pop() {
-            self.redo_stack.push(self.level.clone());
-            self.level = prev;
+            let snapshot = LevelSnapshot::capture(&self.level, Some(&prev.snapshot));
+            let changed = snapshot.changed_rooms(&prev.snapshot);
+            self.session.tally_sectors(&self.level);
+            // Saved stats aren't part of the history
+            let stats = self.level.metadata.stats;
+            self.level = prev.snapshot.into_level();
+            self.invalidate_rooms(changed);
+            self.level.metadata.stats = stats;
+            self.session.rebase_sectors(&self.level);
+            self.redo_stack.push(HistoryEntry { snapshot, label: prev.label });
         }
     }
 
     /// Redo last undone action
     pub fn redo(&mut self) {
         if let Some(next) = self.redo_stack.pop() {
-            self.undo_stack.push(self.level.clone());
-            self.level = next;
+            let snapshot = LevelSnapshot::capture(&self.level, Some(&next.snapshot));
+            let changed = snapshot.changed_rooms(&next.snapshot);
+            self.session.tally_sectors(&self.level);
+            let stats = self.level.metadata.stats;
+            self.level = next.snapshot.into_level();
+            self.invalidate_rooms(changed);
+            self.level.metadata.stats = stats;
+            self.session.rebase_sectors(&self.level);
+            self.undo_stack.push(HistoryEntry { snapshot, label: next.label });
+        }
+    }
+
+    /// Mark the rooms an undo or redo step changed for a mesh rebuild (all
+    /// of them when rooms were added or removed)
+    fn invalidate_rooms(&mut self, changed: Option<Vec<usize>>) {
+        match changed {
+            Some(rooms) => rooms.into_iter().for_each(|r| self.mesh_cache.invalidate(r)),
+            None => self.mesh_cache.invalidate_all(),
+        }
+    }
+
+    /// Undo or redo until exactly `applied` steps of the history are applied
+    pub fn jump_to_history(&mut self, applied: usize) {
+        while self.undo_stack.len() > applied {
+            self.undo();
+        }
+        while self.undo_stack.len() < applied && !self.redo_stack.is_empty() {
+            self.redo();
+        }
+    }
+
+    /// Estimated memory held by the undo and redo stacks
+    pub fn history_memory(&self) -> HistoryMemory {
+        HistoryMemory::measure(self.undo_stack.iter().chain(&self.redo_stack))
+    }
+
+    /// Change the level's sector size (rooms keep their grid cells)
+    pub fn set_sector_size(&mut self, sector_size: f32) {
+        if sector_size == self.level.sector_size {
+            return;
+        }
+        self.save_undo("Changed sector size");
+        self.level.set_sector_size(sector_size);
+        self.mesh_cache.invalidate_all();
+        self.grid_size = self.level.sector_size;
+        self.set_status(&format!("Sector size: {}", self.level.sector_size), 2.0);
+    }
+
+    /// Change the level's height snapping step
+    pub fn set_click_height(&mut self, click_height: f32) {
+        if click_height <= 0.0 || click_height == self.level.click_height {
+            return;
+        }
+        self.save_undo("Changed click height");
+        self.level.click_height = click_height;
+        self.set_status(&format!("Click height: {}", click_height), 2.0);
+    }
+
+    /// Current value of a flag on a sector face (None if the face doesn't exist)
+    pub fn face_flag(&self, room: usize, x: usize, z: usize, face: SectorFace, flag: FaceFlag) -> Option<bool> {
+        let sector = self.level.rooms.get(room)?.get_sector(x, z)?;
+        let (double_sided, invisible, solid) = match face.wall_direction() {
+            Some((dir, i)) => sector.walls(dir).get(i).map(|w| (w.double_sided, w.invisible, w.solid))?,
+            None => {
+                let f = if face == SectorFace::Floor { &sector.floor } else { &sector.ceiling };
+                f.as_ref().map(|f| (f.double_sided, f.invisible, f.solid))?
+            }
+        };
+        Some(match flag {
+            FaceFlag::DoubleSided => double_sided,
+            FaceFlag::Invisible => invisible,
+            FaceFlag::Solid => solid,
+        })
+    }
+
+    /// Flip a flag on a sector face (undoable)
+    pub fn toggle_face_flag(&mut self, room: usize, x: usize, z: usize, face: SectorFace, flag: FaceFlag) {
+        if self.face_flag(room, x, z, face, flag).is_none() {
+            return;
+        }
+        self.save_undo("Toggle face flag");
+        let Some(sector) = self.room_mut(room).and_then(|r| r.get_sector_mut(x, z)) else {
+            return;
+        };
+        let (double_sided, invisible, solid) = match face.wall_direction() {
+            Some((dir, i)) => match sector.walls_mut(dir).get_mut(i) {
+                Some(w) => (&mut w.double_sided, &mut w.invisible, &mut w.solid),
+                None => return,
+            },
+            None => {
+                let f = if face == SectorFace::Floor { &mut sector.floor } else { &mut sector.ceiling };
+                match f {
+                    Some(f) => (&mut f.double_sided, &mut f.invisible, &mut f.solid),
+                    None => return,
+                }
+            }
+        };
+        let value = match flag {
+            FaceFlag::DoubleSided => double_sided,
+            FaceFlag::Invisible => invisible,
+            FaceFlag::Solid => solid,
+        };
+        *value = !*value;
+    }
+
+    /// Change what a portal does (undoable)
+    pub fn set_portal_kind(&mut self, room: usize, portal: usize, kind: crate::world::PortalKind) {
+        let Some(current) = self.level.rooms.get(room).and_then(|r| r.portals.get(portal)).map(|p| p.kind) else {
+            return;
+        };
+        if current == kind {
+            return;
+        }
+        self.save_undo(&format!("Portal: {}", kind.label()));
+        self.level.rooms[room].portals[portal].kind = kind;
+    }
+
+    /// Change the level's color grade
+    pub fn set_ambience(&mut self, grade: crate::rasterizer::ColorGrade) {
+        if grade == self.level.ambience {
+            return;
+        }
+        self.save_undo("Changed ambience");
+        self.level.ambience = grade;
+    }
+
+    /// Pick the next (or previous) song of the project as the level's music,
+    /// going through "the game's default" between the last and the first
+    pub fn step_level_music(&mut self, up: bool) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let ids = crate::tracker::list_song_ids();
+        #[cfg(target_arch = "wasm32")]
+        let ids: Vec<String> = Vec::new();
+        let options: Vec<Option<String>> = std::iter::once(None).chain(ids.into_iter().map(Some)).collect();
+        let current = options.iter().position(|o| *o == self.level.music).unwrap_or(0);
+        let next = if up { (current + 1) % options.len() } else { (current + options.len() - 1) % options.len() };
+        if options[next] != self.level.music {
+            self.save_undo("Changed level music");
+            self.level.music = options[next].clone();
+        }
+    }
+
+    /// Change the current room's weather
+    pub fn set_room_weather(&mut self, weather: crate::world::Weather) {
+        let Some(room) = self.level.rooms.get(self.current_room) else { return };
+        if room.weather == weather {
+            return;
+        }
+        self.save_undo("Changed weather");
+        self.level.rooms[self.current_room].weather = weather;
+    }
+
+    /// Set the current room's ambient loop, volume and reverb
+    pub fn set_room_audio(&mut self, audio: crate::world::RoomAudio) {
+        let Some(room) = self.level.rooms.get(self.current_room) else { return };
+        if room.audio == audio {
+            return;
+        }
+        self.save_undo("Changed room audio");
+        self.level.rooms[self.current_room].audio = audio;
+    }
+
+    /// Apply an ambience preset to the whole level
+    pub fn apply_ambience_preset(&mut self, index: usize) {
+        let Some(preset) = self.ambience_presets.get(index).cloned() else { return };
+        self.ambience_preset = index;
+        self.save_undo(&format!("Ambience: {}", preset.name));
+        self.level.apply_ambience_preset(&preset);
+        self.set_status(&format!("Applied {} ambience", preset.name), 2.0);
+    }
+
+    /// Add the level's current ambience to the project's presets
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_ambience_preset(&mut self) -> Result<(), String> {
+        use crate::world::{builtin_ambience_presets, save_ambience_presets, AMBIENCE_PRESETS_PATH};
+        let builtin = builtin_ambience_presets().len();
+        let name = format!("Custom {}", self.ambience_presets.len() - builtin + 1);
+        let mut custom = self.ambience_presets[builtin..].to_vec();
+        custom.push(self.level.ambience_preset(&name));
+        save_ambience_presets(std::path::Path::new(AMBIENCE_PRESETS_PATH), &custom)?;
+        self.ambience_presets.extend(custom.pop());
+        self.set_status(&format!("Saved ambience as {} ({})", name, AMBIENCE_PRESETS_PATH), 3.0);
+        Ok(())
+    }
+
+    /// Save the current selection (sectors and faces) as a new named set,
+    /// and start renaming it
+    pub fn save_selection_set(&mut self) {
+        let mut members: Vec<SetMember> = Vec::new();
+        for sel in std::iter::once(&self.selection).chain(&self.multi_selection) {
+            if let Some(m) = sel.to_set_member() {
+                if !members.contains(&m) {
+                    members.push(m);
+                }
+            }
+        }
+        if members.is_empty() {
+            self.set_status("Select sectors or faces to save a selection set", 3.0);
+            return;
+        }
+        self.save_undo("Saved selection set");
+        let name = format!("Set {}", self.level.selection_sets.len() + 1);
+        self.set_status(&format!("Saved {} ({} items)", name, members.len()), 2.0);
+        self.level.selection_sets.push(SelectionSet { name: name.clone(), members });
+        self.renaming_set = Some((self.level.selection_sets.len() - 1, name));
+    }
+
+    /// Replace the selection with a saved set's members that still exist
+    pub fn recall_selection_set(&mut self, idx: usize) {
+        let Some(set) = self.level.selection_sets.get(idx) else { return };
+        let members: Vec<SetMember> = set.members.iter().filter(|m| self.level.set_member_exists(m)).copied().collect();
+        let missing = set.members.len() - members.len();
+        let name = set.name.clone();
+        if let Some(first) = members.first() {
+            self.current_room = first.room;
+        }
+        self.multi_selection = members.iter().map(Selection::from_set_member).collect();
+        self.selection = self.multi_selection.first().cloned().unwrap_or(Selection::None);
+        if self.multi_selection.len() < 2 {
+            self.multi_selection.clear();
+        }
+        let mut status = format!("Selected {} items from {}", members.len(), name);
+        if missing > 0 {
+            status.push_str(&format!(" ({} no longer exist)", missing));
+        }
+        self.set_status(&status, 2.0);
+    }
+
+    /// Rename a selection set
+    pub fn rename_selection_set(&mut self, idx: usize, name: &str) {
+        let name = name.trim();
+        if name.is_empty() || self.level.selection_sets.get(idx).is_none_or(|s| s.name == name) {
+            return;
+        }
+        self.save_undo("Renamed selection set");
+        self.level.selection_sets[idx].name = name.to_string();
+    }
+
+    /// Delete a selection set
+    pub fn delete_selection_set(&mut self, idx: usize) {
+        if idx < self.level.selection_sets.len() {
+            self.save_undo("Deleted selection set");
+            self.level.selection_sets.remove(idx);
+            self.renaming_set = None;
+        }
+    }
+
+    /// Start recording a macro, anchored at the first selected sector
+    pub fn start_macro_recording(&mut self) {
+        let Some(&anchor) = self.selected_sectors().first() else {
+            self.set_status("Select the sector to record the macro from", 3.0);
+            return;
+        };
+        let Some(room) = self.level.rooms.get(self.current_room).cloned() else { return };
+        self.recording_macro = Some((self.current_room, room, anchor));
+        self.set_status("Recording macro: edit the room, then press Stop", 3.0);
+    }
+
+    /// Stop recording and keep the sectors changed since it started as a macro
+    pub fn stop_macro_recording(&mut self) {
+        let Some((room_idx, before, anchor)) = self.recording_macro.take() else { return };
+        let Some(after) = self.level.rooms.get(room_idx).filter(|r| r.id == before.id) else {
+            self.set_status("The recorded room no longer exists", 3.0);
+            return;
+        };
+        let name = format!("Macro {}", self.level.macros.len() + 1);
+        let edit = crate::world::EditMacro::record(&name, &before, after, anchor);
+        if edit.cells.is_empty() {
+            self.set_status("Nothing changed while recording", 3.0);
+            return;
+        }
+        let cells = edit.cells.len();
+        self.save_undo("Recorded macro");
+        self.level.macros.push(edit);
+        self.set_status(&format!("Recorded {} ({} sectors)", name, cells), 2.0);
+    }
+
+    /// Play a macro at the first selected sector of the current room
+    pub fn play_macro(&mut self, idx: usize) {
+        let Some(edit) = self.level.macros.get(idx).cloned() else { return };
+        let Some(&anchor) = self.selected_sectors().first() else {
+            self.set_status("Select the sector to play the macro at", 3.0);
+            return;
+        };
+        let Some(mut room) = self.level.rooms.get(self.current_room).cloned() else { return };
+        match room.play_macro(&edit, anchor, &self.macro_replay) {
+            Ok(written) => {
+                self.save_undo(&format!("Played {}", edit.name));
+                self.replace_room(self.current_room, room);
+                self.selection = Selection::None;
+                self.multi_selection.clear();
+                self.set_status(&format!("Played {} ({} sectors)", edit.name, written), 2.0);
+            }
+            Err(e) => self.set_status(&e, 3.0),
+        }
+    }
+
+    /// Delete a macro
+    pub fn delete_macro(&mut self, idx: usize) {
+        if idx < self.level.macros.len() {
+            self.save_undo("Deleted macro");
+            self.level.macros.remove(idx);
+        }
+    }
+
+    /// Set what resets when the player respawns
+    pub fn set_respawn_rules(&mut self, rules: crate::world::RespawnRules) {
+        if self.level.respawn == rules {
+            return;
+        }
+        self.save_undo("Changed respawn rules");
+        self.level.respawn = rules;
+    }
+
+    /// Append an event to the level's timeline (spawns wave 1 at the start)
+    pub fn add_level_event(&mut self) {
+        self.save_undo("Added event");
+        self.level.events.push(crate::world::LevelEvent::new());
+    }
+
+    /// Replace an event in the timeline
+    pub fn set_level_event(&mut self, idx: usize, event: crate::world::LevelEvent) {
+        if self.level.events.get(idx).is_none_or(|e| *e == event) {
+            return;
+        }
+        self.save_undo("Changed event");
+        self.level.events[idx] = event;
+    }
+
+    /// Delete an event from the timeline
+    pub fn delete_level_event(&mut self, idx: usize) {
+        if idx < self.level.events.len() {
+            self.save_undo("Deleted event");
+            self.level.events.remove(idx);
+        }
+    }
+
+    /// Set the trigger name that starts a camera path (empty clears it)
+    pub fn set_camera_trigger(&mut self, path: usize, trigger: &str) {
+        let trigger = Some(trigger.trim()).filter(|t| !t.is_empty()).map(str::to_string);
+        if self.level.camera_paths.get(path).is_none_or(|p| p.trigger == trigger) {
+            return;
+        }
+        self.save_undo("Changed camera trigger");
+        self.level.camera_paths[path].trigger = trigger;
+    }
+
+    /// Retime a camera key (kept between its neighbours)
+    pub fn set_camera_key_time(&mut self, path: usize, key: usize, time: f32) {
+        let Some(old) = self.level.camera_paths.get(path).and_then(|p| p.keys.get(key)).map(|k| k.time) else {
+            return;
+        };
+        let mut retimed = self.level.camera_paths[path].clone();
+        retimed.set_key_time(key, time);
+        if retimed.keys[key].time == old {
+            return;
+        }
+        self.save_undo("Changed camera key time");
+        self.level.camera_paths[path] = retimed;
+    }
+
+    /// Delete a key from a camera path
+    pub fn delete_camera_key(&mut self, path: usize, key: usize) {
+        if self.level.camera_paths.get(path).is_some_and(|p| key < p.keys.len()) {
+            self.save_undo("Removed camera key");
+            self.level.camera_paths[path].keys.remove(key);
         }
     }
 
@@ -325,7 +1087,26 @@ impl EditorState {
 
     /// Get current room mutably
     pub fn current_room_mut(&mut self) -> Option<&mut crate::world::Room> {
-        self.level.rooms.get_mut(self.current_room)
+        self.room_mut(self.current_room)
+    }
+
+    /// Get a room to edit; its mesh is rebuilt before the next draw
+    pub fn room_mut(&mut self, room: usize) -> Option<&mut crate::world::Room> {
+        self.mesh_cache.invalidate(room);
+        self.level.rooms.get_mut(room)
+    }
+
+    /// Put back an edited copy of a room
+    pub fn replace_room(&mut self, idx: usize, room: Room) {
+        self.level.rooms[idx] = room;
+        self.mesh_cache.invalidate(idx);
+    }
+
+    /// Put back an edited copy of the whole level (room operations that may
+    /// renumber rooms)
+    fn replace_level(&mut self, level: Level) {
+        self.level = level;
+        self.mesh_cache.invalidate_all();
     }
 
     /// Get textures from the currently selected pack
@@ -361,6 +1142,350 @@ impl EditorState {
         self.multi_selection.clear();
     }
 
+    /// Sectors of the current room in the primary and multi-selection
+    pub fn selected_sectors(&self) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for sel in std::iter::once(&self.selection).chain(&self.multi_selection) {
+            if let Some((room, x, z)) = sel.sector_coords() {
+                if room == self.current_room && !cells.contains(&(x, z)) {
+                    cells.push((x, z));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Replace the selection with whole sectors of the current room
+    pub fn select_sectors(&mut self, cells: &[(usize, usize)]) {
+        let room = self.current_room;
+        self.multi_selection = cells.iter().map(|&(x, z)| Selection::Sector { room, x, z }).collect();
+        self.selection = self.multi_selection.first().cloned().unwrap_or(Selection::None);
+        if self.multi_selection.len() < 2 {
+            self.multi_selection.clear();
+        }
+    }
+
+    /// Merge rooms Shift+clicked in the room list into the current room
+    pub fn merge_selected_rooms(&mut self) {
+        let mut others: Vec<usize> = self
+            .multi_selection
+            .iter()
+            .filter_map(|sel| match sel {
+                Selection::Room(idx) if *idx != self.current_room => Some(*idx),
+                _ => None,
+            })
+            .collect();
+        if others.is_empty() {
+            self.set_status("Shift+click rooms in the room list to merge them into this room", 3.0);
+            return;
+        }
+
+        // Highest index first so removals don't shift the rooms still to merge
+        others.sort_unstable_by(|a, b| b.cmp(a));
+        let mut level = self.level.clone();
+        let mut keep = self.current_room;
+        for other in &others {
+            match level.merge_rooms(keep, *other) {
+                Ok(idx) => keep = idx,
+                Err(e) => {
+                    self.set_status(&format!("Merge failed: {}", e), 3.0);
+                    return;
+                }
+            }
+        }
+
+        self.save_undo(&format!("Merged {} room(s)", others.len()));
+        self.replace_level(level);
+        self.current_room = keep;
+        self.selection = Selection::None;
+        self.clear_multi_selection();
+        self.set_status(&format!("Merged {} room(s) into room {}", others.len(), keep), 2.0);
+    }
+
+    /// Split the bounding rectangle of the selected sectors off into a new room
+    pub fn split_selected_sectors(&mut self) {
+        let cells = self.selected_sectors();
+        if cells.is_empty() {
+            self.set_status("Select sectors in the 2D grid to split them into a new room", 3.0);
+            return;
+        }
+        let min = (cells.iter().map(|c| c.0).min().unwrap(), cells.iter().map(|c| c.1).min().unwrap());
+        let max = (cells.iter().map(|c| c.0).max().unwrap(), cells.iter().map(|c| c.1).max().unwrap());
+
+        let mut level = self.level.clone();
+        match level.split_room(self.current_room, min, max) {
+            Ok(new_room) => {
+                self.save_undo("Split room");
+                self.replace_level(level);
+                self.current_room = new_room;
+                self.selection = Selection::None;
+                self.clear_multi_selection();
+                self.set_status(&format!("Split sectors into room {}", new_room), 2.0);
+            }
+            Err(e) => self.set_status(&format!("Split failed: {}", e), 3.0),
+        }
+    }
+
+    /// Extrude the selected face. Floors and ceilings (with any other selected
+    /// sectors) move one click up, or down with `reverse`; walls are pushed
+    /// out by one sector.
+    pub fn extrude_selection(&mut self, reverse: bool) {
+        let (room_idx, x, z, face) = match self.selection {
+            Selection::SectorFace { room, x, z, face } => (room, x, z, face),
+            Selection::Sector { room, x, z } => (room, x, z, SectorFace::Floor),
+            _ => {
+                self.set_status("Select a floor, ceiling or wall to extrude", 3.0);
+                return;
+            }
+        };
+        let Some(mut room) = self.level.rooms.get(room_idx).cloned() else {
+            return;
+        };
+
+        if let Some((dir, index)) = face.wall_direction() {
+            match room.extrude_wall((x, z), dir, index) {
+                Ok((nx, nz)) => {
+                    let new_index = room.get_sector(nx, nz).map_or(0, |s| s.walls(dir).len() - 1);
+                    self.save_undo("Extruded wall");
+                    self.replace_room(room_idx, room);
+                    self.selection = Selection::SectorFace { room: room_idx, x: nx, z: nz, face: SectorFace::wall(dir, new_index) };
+                    self.clear_multi_selection();
+                    self.set_status("Extruded wall", 2.0);
+                }
+                Err(e) => self.set_status(&format!("Extrude failed: {}", e), 3.0),
+            }
+            return;
+        }
+
+        let mut cells = if room_idx == self.current_room { self.selected_sectors() } else { Vec::new() };
+        if !cells.contains(&(x, z)) {
+            cells.insert(0, (x, z));
+        }
+        let ceiling = face == SectorFace::Ceiling;
+        let dy = if reverse { -self.level.click_height } else { self.level.click_height };
+        let name = if ceiling { "ceiling" } else { "floor" };
+        match room.extrude_horizontal(&cells, ceiling, dy, self.selected_texture.clone()) {
+            Ok(moved) => {
+                self.save_undo(&format!("Extruded {}", name));
+                self.replace_room(room_idx, room);
+                // The grid may have grown, so every selected cell moves by the same offset
+                let (dx, dz) = (moved[0].0 - cells[0].0, moved[0].1 - cells[0].1);
+                let selection = std::iter::once(&self.selection).chain(&self.multi_selection).map(|sel| match *sel {
+                    Selection::SectorFace { room, x, z, face } if room == room_idx => Selection::SectorFace { room, x: x + dx, z: z + dz, face },
+                    Selection::Sector { room, x, z } if room == room_idx => Selection::Sector { room, x: x + dx, z: z + dz },
+                    ref other => other.clone(),
+                });
+                let mut selection: Vec<Selection> = selection.collect();
+                self.selection = selection.remove(0);
+                self.multi_selection = selection;
+                self.set_status(&format!("Extruded {} ({} sector(s)) by {}", name, cells.len(), dy), 2.0);
+            }
+            Err(e) => self.set_status(&format!("Extrude failed: {}", e), 3.0),
+        }
+    }
+
+    /// Roughen the selected floors with seeded corner noise, in quarter-click steps
+    pub fn apply_floor_noise(&mut self) {
+        let cells = self.selected_sectors();
+        let click = self.level.click_height;
+        let Some(mut room) = self.level.rooms.get(self.current_room).cloned() else { return };
+        let changed = room.add_floor_noise(&cells, self.noise_amplitude * click, click * 0.25, self.noise_seed);
+        if changed == 0 {
+            self.set_status("Select floor sectors to add noise to", 3.0);
+            return;
+        }
+        self.save_undo("Added floor noise");
+        self.replace_room(self.current_room, room);
+        self.set_status(&format!("Added noise to {} floor(s) (seed {})", changed, self.noise_seed), 2.0);
+    }
+
+    /// Paint the selection with the level's texture variants. `reshuffle`
+    /// picks a new seed first.
+    pub fn fill_texture_variants(&mut self, reshuffle: bool) {
+        if self.level.texture_variants.textures.is_empty() {
+            self.set_status("Ctrl+click textures in the palette to build a variant set", 3.0);
+            return;
+        }
+        let mut faces = Vec::new();
+        for sel in std::iter::once(&self.selection).chain(&self.multi_selection) {
+            let (room, x, z, face) = match *sel {
+                Selection::SectorFace { room, x, z, face } => (room, x, z, Some(face)),
+                Selection::Sector { room, x, z } => (room, x, z, None),
+                _ => continue,
+            };
+            let Some(sector) = self.level.rooms.get(room).and_then(|r| r.get_sector(x, z)) else { continue };
+            // A whole sector fills each of its faces
+            let sector_faces = match face {
+                Some(f) => vec![f],
+                None => {
+                    let mut all = Vec::new();
+                    all.extend(sector.floor.as_ref().map(|_| SectorFace::Floor));
+                    all.extend(sector.ceiling.as_ref().map(|_| SectorFace::Ceiling));
+                    for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                        all.extend((0..sector.walls(dir).len()).map(|i| SectorFace::wall(dir, i)));
+                    }
+                    all
+                }
+            };
+            for f in sector_faces {
+                let slot = match (f, f.wall_direction()) {
+                    (SectorFace::Floor, _) => VariantSlot::Floor,
+                    (SectorFace::Ceiling, _) => VariantSlot::Ceiling,
+                    (_, Some((dir, i))) => VariantSlot::Wall(dir, i),
+                    _ => continue,
+                };
+                faces.push((room, x, z, f, slot));
+            }
+        }
+        if faces.is_empty() {
+            self.set_status("Select faces or sectors to fill with texture variants", 3.0);
+            return;
+        }
+
+        self.save_undo(if reshuffle { "Reshuffled texture variants" } else { "Filled texture variants" });
+        if reshuffle {
+            self.level.texture_variants.seed = self.level.texture_variants.seed.wrapping_add(1);
+        }
+        let variants = self.level.texture_variants.clone();
+        for &(room, x, z, face, slot) in &faces {
+            let Some(texture) = variants.pick(room, x, z, slot) else { continue };
+            let Some(sector) = self.room_mut(room).and_then(|r| r.get_sector_mut(x, z)) else { continue };
+            match face {
+                SectorFace::Floor => sector.floor.iter_mut().for_each(|f| f.texture = texture.clone()),
+                SectorFace::Ceiling => sector.ceiling.iter_mut().for_each(|c| c.texture = texture.clone()),
+                _ => {
+                    if let Some(wall) = face.wall_direction().and_then(|(dir, i)| sector.walls_mut(dir).get_mut(i)) {
+                        wall.texture = texture.clone();
+                    }
+                }
+            }
+        }
+        self.set_status(&format!("Filled {} face(s) with {} variants (seed {})", faces.len(), variants.textures.len(), variants.seed), 2.0);
+    }
+
+    /// Give the selected faces of the current room continuous texture
+    /// coordinates, so tiled textures flow across them
+    pub fn align_selected_textures(&mut self) {
+        let room_idx = self.current_room;
+        let (mut walls, mut floors, mut ceilings) = (Vec::new(), Vec::new(), Vec::new());
+        for sel in std::iter::once(&self.selection).chain(&self.multi_selection) {
+            match *sel {
+                Selection::SectorFace { room, x, z, face } if room == room_idx => match face {
+                    SectorFace::Floor => floors.push((x, z)),
+                    SectorFace::Ceiling => ceilings.push((x, z)),
+                    _ => walls.extend(face.wall_direction().map(|(dir, i)| (x, z, dir, i))),
+                },
+                Selection::Sector { room, x, z } if room == room_idx => floors.push((x, z)),
+                _ => {}
+            }
+        }
+        let Some(mut room) = self.level.rooms.get(room_idx).cloned() else { return };
+        let count = room.align_wall_textures(&walls)
+            + room.align_horizontal_textures(&floors, false)
+            + room.align_horizontal_textures(&ceilings, true);
+        if count == 0 {
+            self.set_status("Select walls, floors or ceilings to align their textures", 3.0);
+            return;
+        }
+        self.save_undo("Aligned textures");
+        self.replace_room(room_idx, room);
+        self.set_status(&format!("Aligned textures on {} face(s)", count), 2.0);
+    }
+
+    /// Rotate or mirror the whole current room in place
+    pub fn transform_current_room(&mut self, t: SectorTransform) {
+        let mut level = self.level.clone();
+        match level.transform_room(self.current_room, t) {
+            Ok(dropped) => {
+                self.save_undo(t.label());
+                self.replace_level(level);
+                self.selection = Selection::Room(self.current_room);
+                self.clear_multi_selection();
+                let msg = if dropped > 0 {
+                    format!("{} (removed {} portal(s) that no longer line up)", t.label(), dropped)
+                } else {
+                    t.label().to_string()
+                };
+                self.set_status(&msg, 3.0);
+            }
+            Err(e) => self.set_status(&format!("Transform failed: {}", e), 3.0),
+        }
+    }
+
+    /// Edit the current room's sector at a world X/Z position, growing the
+    /// grid to reach it (up to `MAX_ROOM_SIZE`). Saves undo under `label`.
+    /// Returns false if the room couldn't grow that far.
+    pub fn edit_sector_at(&mut self, world_x: f32, world_z: f32, label: &str, edit: impl FnOnce(&mut Room, usize, usize)) -> bool {
+        let Some(mut room) = self.level.rooms.get(self.current_room).cloned() else {
+            return false;
+        };
+        let gx = ((world_x - room.position.x) / room.sector_size).floor() as i32;
+        let gz = ((world_z - room.position.z) / room.sector_size).floor() as i32;
+        let (sx, sz) = match room.grow_to_include(gx, gz) {
+            Ok(shift) => shift,
+            Err(e) => {
+                self.set_status(&e, 3.0);
+                return false;
+            }
+        };
+        edit(&mut room, (gx + sx as i32) as usize, (gz + sz as i32) as usize);
+        room.recalculate_bounds();
+        self.save_undo(label);
+        self.replace_room(self.current_room, room);
+        if (sx, sz) != (0, 0) {
+            // Grid coordinates moved, so old selections point at the wrong sectors
+            self.selection = Selection::None;
+            self.clear_multi_selection();
+        }
+        self.set_status(label, 2.0);
+        true
+    }
+
+    /// Shrink the current room's grid to the sectors that have geometry
+    pub fn trim_current_room(&mut self) {
+        let Some(mut room) = self.level.rooms.get(self.current_room).cloned() else {
+            return;
+        };
+        if !room.trim_empty_borders() {
+            self.set_status("Nothing to trim", 2.0);
+            return;
+        }
+        let (width, depth) = (room.width, room.depth);
+        self.save_undo("Trimmed room");
+        self.replace_room(self.current_room, room);
+        self.selection = Selection::Room(self.current_room);
+        self.clear_multi_selection();
+        self.set_status(&format!("Trimmed room to {}x{}", width, depth), 2.0);
+    }
+
+    /// Create a new room enclosing the world sector cells between `a` and `b`
+    /// (inclusive): floor, ceiling and perimeter walls in the selected texture
+    pub fn create_box_room(&mut self, a: (i32, i32), b: (i32, i32)) {
+        let (floor, ceiling) = (self.box_floor_y, self.box_ceiling_y);
+        if ceiling <= floor {
+            self.set_status("Box room ceiling must be above its floor", 3.0);
+            return;
+        }
+        let (min, (width, depth)) = box_room_rect(a, b);
+        let size = self.level.sector_size;
+        let texture = self.selected_texture.clone();
+        let room = RoomBuilder::new(width, depth)
+            .at(Vec3::new(min.0 as f32 * size, 0.0, min.1 as f32 * size))
+            .texture(texture.clone())
+            .wall_texture(texture)
+            .fill(floor, ceiling)
+            .enclose(floor, ceiling)
+            .build();
+
+        self.save_undo("Created box room");
+        let idx = self.level.add_room(room);
+        self.mesh_cache.invalidate(idx);
+        self.current_room = idx;
+        self.selection = Selection::Room(idx);
+        self.clear_multi_selection();
+        self.set_status(&format!("Created {}x{} box room {}", width, depth, idx), 2.0);
+    }
+
     /// Toggle a selection in the multi-selection list
     /// Also ensures the current primary selection is in multi_selection
     /// (so Shift+click after a regular click keeps the first item selected)
@@ -381,3 +1506,41 @@ impl EditorState {
         }
     }
 }
+
+/// Render a level's thumbnail: the whole level from above one corner
+pub fn render_thumbnail(level: &Level, texture_packs: &[TexturePack]) -> Framebuffer {
+    let textures: Vec<Texture> = texture_packs.iter().flat_map(|pack| &pack.textures).cloned().collect();
+    let texture_map: std::collections::HashMap<(&str, &str), usize> = texture_packs
+        .iter()
+        .flat_map(|pack| pack.textures.iter().map(move |tex| (pack.name.as_str(), tex.name.as_str())))
+        .enumerate()
+        .map(|(i, key)| (key, i))
+        .collect();
+    let resolve_texture = |tex_ref: &TextureRef| -> Option<usize> {
+        if !tex_ref.is_valid() {
+            return Some(0);
+        }
+        texture_map.get(&(tex_ref.pack.as_str(), tex_ref.name.as_str())).copied()
+    };
+
+    let mut fb = Framebuffer::new(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+    fb.clear(RasterColor::new(15, 15, 20));
+    let camera = overview_camera(level);
+    render_level(&mut fb, level, &camera, &textures, &RasterSettings::default(), resolve_texture);
+    fb
+}
+
+/// Read the models the level's enemies are drawn with while simulating
+/// (models that fail to load are logged and left out)
+fn load_enemy_models(level: &Level) -> HashMap<String, Model> {
+    crate::game::enemy_models(level)
+        .into_iter()
+        .filter_map(|name| match Model::load(&model_path(&name)) {
+            Ok(model) => Some((name, model)),
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        })
+        .collect()
+}
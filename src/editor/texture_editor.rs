@@ -0,0 +1,369 @@
+//! Texture editor - a texture of the active pack as indexed color
+//!
+//! Opened on the selected texture from the texture panel's palette button.
+//! The texture is reduced to a 4-bit or 8-bit CLUT (median cut, see
+//! `rasterizer::palette`) and previewed that way next to its palette:
+//! - paint texels with the selected palette entry (pencil, flood fill), or
+//!   pick the entry under a texel (picker, or right click)
+//! - click a swatch to select an entry and edit its color at 15-bit precision
+//! - pick a variant (another texture of the pack the same size) and take its
+//!   palette through this texture's indices, swapping the colors of textures
+//!   that share their pixels
+//! - bake directional shading into a lit variant (see `rasterizer::light_bake`)
+//! - save over the texture, or as a new variant of it, in the pack; faces
+//!   using it update right away
+
+use macroquad::prelude::*;
+use crate::rasterizer::{
+    bake_light, to_15bit, Color as RasterColor, HeightSource, IndexedTexture, LightBake, TexelDepth, Texture as RasterTexture,
+    LIGHT_DIRECTIONS,
+};
+use crate::ui::{Rect, UiContext, draw_text_button, draw_text_button_enabled, ui_screen_width, ui_screen_height, ACCENT_COLOR};
+use super::{step_buttons, EditorState};
+
+/// Palette swatches per row
+const SWATCH_COLUMNS: usize = 16;
+
+/// What clicking the preview does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PixelTool {
+    Pencil,
+    Fill,
+    Picker,
+}
+
+/// The texture being edited
+pub struct TextureEditor {
+    /// Index into the editor's texture packs
+    pub pack: usize,
+    /// The texture as it was opened (or last saved)
+    original: RasterTexture,
+    depth: TexelDepth,
+    indexed: IndexedTexture,
+    /// Palette entry being edited
+    selected: usize,
+    /// Same-size texture of the pack to take a palette from
+    variant: Option<usize>,
+    show_original: bool,
+    tool: PixelTool,
+    bake: LightBake,
+    /// Preview of what's shown, rebuilt after a change
+    preview: Option<Texture2D>,
+}
+
+impl TextureEditor {
+    /// Edit texture `name` of pack `pack`
+    pub fn open(state: &EditorState, pack: usize, name: &str) -> Option<Self> {
+        let original = state.texture_packs.get(pack)?.textures.iter().find(|t| t.name == name)?.clone();
+        let depth = match TexelDepth::of(&original) {
+            TexelDepth::Bit4 => TexelDepth::Bit4,
+            _ => TexelDepth::Bit8,
+        };
+        let indexed = IndexedTexture::quantize(&original, depth.clut_entries());
+        Some(Self { pack, original, depth, indexed, selected: 0, variant: None, show_original: false, tool: PixelTool::Pencil, bake: LightBake::default(), preview: None })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.original.name
+    }
+
+    fn set_depth(&mut self, depth: TexelDepth) {
+        self.depth = depth;
+        self.indexed = IndexedTexture::quantize(&self.original, depth.clut_entries());
+        self.selected = self.selected.min(self.indexed.palette.len().saturating_sub(1));
+        self.preview = None;
+    }
+
+    /// Paint the texel at (x, y) with the current tool
+    fn apply_tool(&mut self, x: usize, y: usize) {
+        let index = self.selected as u8;
+        let changed = match self.tool {
+            PixelTool::Pencil => self.indexed.paint(x, y, index),
+            PixelTool::Fill => self.indexed.fill(x, y, index),
+            PixelTool::Picker => {
+                self.pick(x, y);
+                false
+            }
+        };
+        if changed {
+            self.show_original = false;
+            self.preview = None;
+        }
+    }
+
+    fn pick(&mut self, x: usize, y: usize) {
+        if let Some(i) = self.indexed.index_at(x, y) {
+            self.selected = i as usize;
+        }
+    }
+
+    fn set_color(&mut self, color: RasterColor) {
+        if let Some(entry) = self.indexed.palette.get_mut(self.selected) {
+            *entry = to_15bit(color);
+            self.preview = None;
+        }
+    }
+
+    /// Next or previous texture of the pack the same size as this one
+    fn step_variant(&mut self, textures: &[RasterTexture], forward: bool) {
+        let size = (self.original.width, self.original.height);
+        let candidates: Vec<usize> = (0..textures.len())
+            .filter(|&i| textures[i].name != self.original.name && (textures[i].width, textures[i].height) == size)
+            .collect();
+        let at = self.variant.and_then(|v| candidates.iter().position(|&c| c == v));
+        self.variant = match (at, forward) {
+            (None, _) => candidates.first().copied(),
+            (Some(i), true) => candidates.get(i + 1).or(candidates.first()).copied(),
+            (Some(i), false) => candidates.get(i.checked_sub(1).unwrap_or(candidates.len() - 1)).copied(),
+        };
+    }
+
+    fn preview(&mut self) -> &Texture2D {
+        self.preview.get_or_insert_with(|| {
+            let texture = if self.show_original { self.original.clone() } else { self.indexed.to_texture("") };
+            let rgba: Vec<u8> = texture.pixels.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect();
+            let image = Texture2D::from_rgba8(texture.width as u16, texture.height as u16, &rgba);
+            image.set_filter(FilterMode::Nearest);
+            image
+        })
+    }
+}
+
+fn swatch_color(c: RasterColor) -> Color {
+    Color::from_rgba(c.r, c.g, c.b, 255)
+}
+
+/// Draw the texture editor. Returns true when it should close.
+pub fn draw_texture_editor(ctx: &mut UiContext, state: &mut EditorState) -> bool {
+    let Some(editor) = state.texture_editor.as_mut() else { return true };
+    let Some(pack) = state.texture_packs.get_mut(editor.pack) else { return true };
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = 660.0;
+    let dialog_h = 584.0;
+    let header_h = 36.0;
+    let footer_h = 44.0;
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+    let dim = Color::from_rgba(150, 150, 150, 255);
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    let title = format!("Texture Editor - {}/{}", pack.name, editor.name());
+    draw_text(&title, x + 16.0, y + 24.0, 18.0, WHITE);
+
+    // Preview, fitted into a square
+    let area = Rect::new(x + 16.0, y + header_h + 16.0, 288.0, 288.0);
+    draw_rectangle(area.x, area.y, area.w, area.h, Color::from_rgba(20, 20, 24, 255));
+    let (tw, th) = (editor.original.width.max(1) as f32, editor.original.height.max(1) as f32);
+    let scale = (area.w / tw).min(area.h / th);
+    let image = Rect::new(area.x + (area.w - tw * scale) / 2.0, area.y + (area.h - th * scale) / 2.0, tw * scale, th * scale);
+    draw_texture_ex(editor.preview(), image.x, image.y, WHITE, DrawTextureParams {
+        dest_size: Some(Vec2::new(image.w, image.h)),
+        ..Default::default()
+    });
+    if ctx.mouse.inside(&image) {
+        let tx = (((ctx.mouse.x - image.x) / scale) as usize).min(editor.indexed.width.saturating_sub(1));
+        let ty = (((ctx.mouse.y - image.y) / scale) as usize).min(editor.indexed.height.saturating_sub(1));
+        draw_rectangle_lines(image.x + tx as f32 * scale, image.y + ty as f32 * scale, scale, scale, 1.0, WHITE);
+        // The pencil paints while held, fill and picker act once per click
+        let paint = match editor.tool {
+            PixelTool::Pencil => ctx.mouse.left_down,
+            _ => ctx.mouse.left_pressed,
+        };
+        if paint {
+            editor.apply_tool(tx, ty);
+        } else if ctx.mouse.right_down {
+            editor.pick(tx, ty);
+        }
+    }
+
+    // Original / 4-bit / 8-bit
+    let mut bx = area.x;
+    let by = area.bottom() + 10.0;
+    let modes = [("Original", None), ("4-bit", Some(TexelDepth::Bit4)), ("8-bit", Some(TexelDepth::Bit8))];
+    for (label, depth) in modes {
+        let active = match depth {
+            None => editor.show_original,
+            Some(d) => !editor.show_original && editor.depth == d,
+        };
+        let rect = Rect::new(bx, by, 92.0, 24.0);
+        if draw_text_button(ctx, rect, label, if active { ACCENT_COLOR } else { button_color }) {
+            match depth {
+                None => editor.show_original = true,
+                Some(d) => {
+                    editor.show_original = false;
+                    if d != editor.depth {
+                        editor.set_depth(d);
+                    }
+                }
+            }
+            editor.preview = None;
+        }
+        bx += 98.0;
+    }
+
+    // Pixel tools
+    let mut bx = area.x;
+    let by = by + 32.0;
+    let tools = [("Pencil", PixelTool::Pencil), ("Fill", PixelTool::Fill), ("Picker", PixelTool::Picker)];
+    for (label, tool) in tools {
+        let rect = Rect::new(bx, by, 92.0, 24.0);
+        if draw_text_button(ctx, rect, label, if editor.tool == tool { ACCENT_COLOR } else { button_color }) {
+            editor.tool = tool;
+        }
+        bx += 98.0;
+    }
+    let sizes = format!(
+        "{}x{}, {} colors in the CLUT",
+        editor.original.width,
+        editor.original.height,
+        editor.indexed.palette.len()
+    );
+    draw_text(&sizes, area.x, by + 44.0, 14.0, dim);
+
+    // Baked lighting, from the texture as last saved
+    let mut bake_y = by + 58.0;
+    let settings = [
+        format!("Light from {}", LIGHT_DIRECTIONS[editor.bake.direction]),
+        format!("Strength {:.2}", editor.bake.strength),
+        format!("Height: {}", editor.bake.source.label()),
+    ];
+    for (i, label) in settings.iter().enumerate() {
+        draw_text(label, area.x, bake_y + 14.0, 14.0, WHITE);
+        if let Some(up) = step_buttons(ctx, area.right(), bake_y, 18.0) {
+            let bake = &mut editor.bake;
+            match i {
+                0 => bake.direction = (bake.direction + if up { 1 } else { LIGHT_DIRECTIONS.len() - 1 }) % LIGHT_DIRECTIONS.len(),
+                1 => bake.strength = (bake.strength + if up { 0.25 } else { -0.25 }).clamp(0.25, 2.0),
+                _ => {
+                    bake.source = match bake.source {
+                        HeightSource::Emboss => HeightSource::Bevel,
+                        HeightSource::Bevel => HeightSource::Emboss,
+                    }
+                }
+            }
+        }
+        bake_y += 22.0;
+    }
+    let bake_rect = Rect::new(area.x, bake_y + 4.0, 140.0, 24.0);
+    let mut status = None;
+    if draw_text_button(ctx, bake_rect, "Bake lit variant", button_color) {
+        let name = pack.unique_name(&format!("{}_lit", editor.name()));
+        let mut baked = bake_light(&editor.original, &editor.bake);
+        baked.name = name.clone();
+        status = Some(match pack.save_texture(baked) {
+            Ok(_) => format!("Saved lit variant {}", name),
+            Err(e) => e,
+        });
+    }
+
+    // Palette swatches
+    let grid_x = area.right() + 24.0;
+    let grid_w = x + dialog_w - 16.0 - grid_x;
+    let rows = editor.indexed.palette.len().div_ceil(SWATCH_COLUMNS).max(1);
+    let cell = (grid_w / SWATCH_COLUMNS as f32).min(192.0 / rows as f32).floor();
+    let mut row_y = area.y;
+    draw_text("CLUT", grid_x, row_y + 12.0, 14.0, dim);
+    row_y += 20.0;
+    for (i, &c) in editor.indexed.palette.iter().enumerate() {
+        let r = Rect::new(
+            grid_x + (i % SWATCH_COLUMNS) as f32 * cell,
+            row_y + (i / SWATCH_COLUMNS) as f32 * cell,
+            cell - 1.0,
+            cell - 1.0,
+        );
+        draw_rectangle(r.x, r.y, r.w, r.h, swatch_color(c));
+        if c.a == 0 {
+            draw_line(r.x, r.bottom(), r.right(), r.y, 1.0, RED);
+        }
+        if i == editor.selected {
+            draw_rectangle_lines(r.x - 1.0, r.y - 1.0, r.w + 2.0, r.h + 2.0, 2.0, WHITE);
+        }
+        if ctx.mouse.clicked(&r) {
+            editor.selected = i;
+        }
+    }
+    row_y += rows as f32 * cell + 16.0;
+
+    // Selected entry, 0-31 per channel
+    let usage = editor.indexed.usage();
+    if let Some(&color) = editor.indexed.palette.get(editor.selected) {
+        let label = format!("Entry {} - {} texels", editor.selected, usage.get(editor.selected).copied().unwrap_or(0));
+        draw_text(&label, grid_x, row_y + 12.0, 14.0, WHITE);
+        row_y += 20.0;
+        let channels = [("R", color.r), ("G", color.g), ("B", color.b)];
+        for (ch, (name, value)) in channels.into_iter().enumerate() {
+            draw_text(&format!("{} {:2}", name, value >> 3), grid_x, row_y + 14.0, 14.0, dim);
+            if let Some(up) = step_buttons(ctx, grid_x + 120.0, row_y, 18.0) {
+                let v5 = if up { (value >> 3).saturating_add(1).min(31) } else { (value >> 3).saturating_sub(1) };
+                let mut c = color;
+                match ch {
+                    0 => c.r = v5 << 3,
+                    1 => c.g = v5 << 3,
+                    _ => c.b = v5 << 3,
+                }
+                editor.set_color(c);
+            }
+            row_y += 22.0;
+        }
+        let swatch = Rect::new(grid_x + 132.0, row_y - 66.0, 60.0, 62.0);
+        draw_rectangle(swatch.x, swatch.y, swatch.w, swatch.h, swatch_color(color));
+    }
+
+    // Palette from a variant sharing the pixels
+    row_y += 8.0;
+    let variant_name = editor.variant.and_then(|v| pack.textures.get(v)).map_or("(none)", |t| t.name.as_str());
+    draw_text(&format!("Variant: {}", variant_name), grid_x, row_y + 14.0, 14.0, dim);
+    if let Some(forward) = step_buttons(ctx, x + dialog_w - 16.0, row_y, 18.0) {
+        editor.step_variant(&pack.textures, forward);
+    }
+    row_y += 24.0;
+    let variant = editor.variant.and_then(|v| pack.textures.get(v));
+    let palette = variant.and_then(|v| editor.indexed.palette_from(v));
+    let take_rect = Rect::new(grid_x, row_y, 120.0, 24.0);
+    if draw_text_button_enabled(ctx, take_rect, "Take palette", button_color, palette.is_some()) {
+        if let Some(palette) = palette {
+            editor.indexed.palette = palette;
+            editor.show_original = false;
+            editor.preview = None;
+        }
+    }
+
+    // Footer
+    let footer_y = y + dialog_h - footer_h + 8.0;
+    let save_rect = Rect::new(x + 16.0, footer_y, 80.0, 28.0);
+    let variant_rect = Rect::new(x + 104.0, footer_y, 130.0, 28.0);
+    if draw_text_button(ctx, save_rect, "Save", ACCENT_COLOR) {
+        let texture = editor.indexed.to_texture(editor.name());
+        editor.original = texture.clone();
+        status = Some(match pack.save_texture(texture) {
+            Ok(_) => format!("Saved {}", editor.name()),
+            Err(e) => e,
+        });
+    }
+    if draw_text_button(ctx, variant_rect, "Save as variant", button_color) {
+        let name = pack.unique_name(&format!("{}_alt", editor.name()));
+        status = Some(match pack.save_texture(editor.indexed.to_texture(&name)) {
+            Ok(_) => format!("Saved variant {}", name),
+            Err(e) => e,
+        });
+    }
+    let revert_rect = Rect::new(x + 242.0, footer_y, 80.0, 28.0);
+    if draw_text_button(ctx, revert_rect, "Revert", button_color) {
+        editor.set_depth(editor.depth);
+    }
+    let close_rect = Rect::new(x + dialog_w - 90.0, footer_y, 80.0, 28.0);
+    let close = draw_text_button(ctx, close_rect, "Close", button_color) || is_key_pressed(KeyCode::Escape);
+
+    if let Some(message) = status {
+        state.set_status(&message, 3.0);
+    }
+    close
+}
@@ -60,6 +60,42 @@ impl TexturePack {
         packs
     }
 
+    /// Put a texture into the pack, replacing the one with the same name, and
+    /// write it to `<name>.png` in the pack folder (web builds only keep it in
+    /// memory). Faces using it pick up the new pixels on the next frame.
+    /// Returns the texture's index in the pack.
+    pub fn save_texture(&mut self, texture: Texture) -> Result<usize, String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = self.path.join(format!("{}.png", texture.name));
+            let rgba: Vec<u8> = texture.pixels.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect();
+            let img = image::RgbaImage::from_raw(texture.width as u32, texture.height as u32, rgba)
+                .ok_or_else(|| format!("{} does not match its size", texture.name))?;
+            img.save(&path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        match self.textures.iter().position(|t| t.name == texture.name) {
+            Some(i) => {
+                self.textures[i] = texture;
+                Ok(i)
+            }
+            None => {
+                self.textures.push(texture);
+                Ok(self.textures.len() - 1)
+            }
+        }
+    }
+
+    /// A texture name not used in the pack yet, `base` or `base_2`, `base_3`...
+    pub fn unique_name(&self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut n = 2;
+        while self.textures.iter().any(|t| t.name == name) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        name
+    }
+
     /// Discover all texture packs from manifest (WASM stub - returns empty, loaded async later)
     #[cfg(target_arch = "wasm32")]
     pub fn discover_all() -> Vec<Self> {
@@ -76,7 +112,7 @@ impl TexturePack {
         let manifest = match load_string("assets/textures/manifest.txt").await {
             Ok(s) => s,
             Err(e) => {
-                eprintln!("Failed to load texture manifest: {}", e);
+                log::error!("Failed to load texture manifest: {}", e);
                 wasm::hide_loading();
                 return Vec::new();
             }
@@ -104,7 +140,7 @@ impl TexturePack {
             }
         }
 
-        println!("Loaded {} texture packs from manifest", packs.len());
+        log::info!("Loaded {} texture packs from manifest", packs.len());
         wasm::hide_loading();
         packs
     }
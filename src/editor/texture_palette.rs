@@ -1,7 +1,10 @@
 //! Texture Palette - Grid of available textures with folder selection
+//!
+//! Ctrl+click adds a texture to the level's variant set (again to weight it
+//! higher); the variant bar then fills or reshuffles the selection with them.
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, icon, draw_icon_centered};
+use crate::ui::{Rect, UiContext, icon, draw_icon_centered, draw_text_button};
 use crate::rasterizer::Texture as RasterTexture;
 use super::EditorState;
 
@@ -9,6 +12,7 @@ use super::EditorState;
 const THUMB_SIZE: f32 = 48.0;
 const THUMB_PADDING: f32 = 4.0;
 const HEADER_HEIGHT: f32 = 28.0;
+const VARIANT_BAR_HEIGHT: f32 = 24.0;
 
 /// Draw the texture palette
 pub fn draw_texture_palette(
@@ -24,8 +28,15 @@ pub fn draw_texture_palette(
     let header_rect = Rect::new(rect.x, rect.y, rect.w, HEADER_HEIGHT);
     draw_folder_selector(ctx, header_rect, state, icon_font);
 
+    // Variant bar, while a variant set exists
+    let mut top = HEADER_HEIGHT;
+    if !state.level.texture_variants.textures.is_empty() {
+        draw_variant_bar(ctx, Rect::new(rect.x, rect.y + top, rect.w, VARIANT_BAR_HEIGHT), state);
+        top += VARIANT_BAR_HEIGHT;
+    }
+
     // Content area (below header)
-    let content_rect = Rect::new(rect.x, rect.y + HEADER_HEIGHT, rect.w, rect.h - HEADER_HEIGHT);
+    let content_rect = Rect::new(rect.x, rect.y + top, rect.w, rect.h - top);
 
     // Get texture count without borrowing state
     let texture_count = state.texture_packs
@@ -179,6 +190,16 @@ pub fn draw_texture_palette(
         );
     }
 
+    // Ctrl+click builds the variant set instead of applying
+    let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+    if let (true, Some(tex_ref)) = (ctrl, &clicked_texture) {
+        state.save_undo("Added texture variant");
+        state.level.texture_variants.add(tex_ref.clone());
+        let count = state.level.texture_variants.textures.len();
+        state.set_status(&format!("Variant set: {} texture(s)", count), 2.0);
+        return;
+    }
+
     // Apply clicked texture after loop
     if let Some(tex_ref) = clicked_texture {
         state.selected_texture = tex_ref.clone();
@@ -191,16 +212,43 @@ pub fn draw_texture_palette(
         let has_valid_selection = all_selections.iter().any(|sel| !matches!(sel, super::Selection::None));
 
         if has_valid_selection {
-            state.save_undo();
+            state.save_undo("Applied texture");
 
             // Apply texture to all selections
             for sel in all_selections {
+                if let Some((room, _, _)) = sel.sector_coords() {
+                    state.mesh_cache.invalidate(room);
+                }
                 apply_texture_to_selection(&mut state.level, sel, tex_ref.clone());
             }
         }
     }
 }
 
+/// Variant set summary with fill / reshuffle / clear buttons
+fn draw_variant_bar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, Color::from_rgba(35, 35, 42, 255));
+    let variants = &state.level.texture_variants;
+    let weights: Vec<String> = variants.textures.iter().map(|t| t.weight.to_string()).collect();
+    let label = format!("Variants {}", weights.join(":"));
+    draw_text(&label, (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, Color::from_rgba(180, 180, 180, 255));
+
+    let button_color = Color::from_rgba(60, 60, 70, 255);
+    let fill = Rect::new(rect.right() - 128.0, rect.y + 3.0, 40.0, rect.h - 6.0);
+    let shuffle = Rect::new(rect.right() - 86.0, rect.y + 3.0, 56.0, rect.h - 6.0);
+    let clear = Rect::new(rect.right() - 28.0, rect.y + 3.0, 24.0, rect.h - 6.0);
+    if draw_text_button(ctx, fill, "Fill", button_color) {
+        state.fill_texture_variants(false);
+    }
+    if draw_text_button(ctx, shuffle, "Shuffle", button_color) {
+        state.fill_texture_variants(true);
+    }
+    if draw_text_button(ctx, clear, "x", Color::from_rgba(90, 50, 50, 255)) {
+        state.save_undo("Cleared texture variants");
+        state.level.texture_variants.textures.clear();
+    }
+}
+
 /// Apply a texture to a single selection
 fn apply_texture_to_selection(level: &mut crate::world::Level, selection: super::Selection, tex_ref: crate::world::TextureRef) {
     match selection {
@@ -315,6 +363,24 @@ fn draw_folder_selector(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         state.texture_scroll = 0.0;
     }
 
+    // Edit the selected texture (palette, pixels)
+    let edit_rect = Rect::new(next_rect.x - btn_size - 2.0, next_rect.y, btn_size, btn_size);
+    let can_edit = state.selected_texture.is_valid() && state.selected_texture.pack == state.current_pack_name();
+    let edit_hovered = can_edit && ctx.mouse.inside(&edit_rect);
+    if edit_hovered {
+        draw_rectangle(edit_rect.x, edit_rect.y, edit_rect.w, edit_rect.h, Color::from_rgba(60, 60, 70, 255));
+    }
+    let edit_color = match (can_edit, edit_hovered) {
+        (false, _) => Color::from_rgba(80, 80, 80, 255),
+        (true, true) => WHITE,
+        (true, false) => Color::from_rgba(180, 180, 180, 255),
+    };
+    draw_icon_centered(icon_font, icon::PALETTE, &edit_rect, 14.0, edit_color);
+    if can_edit && ctx.mouse.clicked(&edit_rect) {
+        let name = state.selected_texture.name.clone();
+        state.texture_editor = super::TextureEditor::open(state, state.selected_pack, &name);
+    }
+
     // Pack name in center - properly centered vertically
     let name = state.current_pack_name();
     let pack_count = state.texture_packs.len();
@@ -3,48 +3,14 @@
 //! Sector-based geometry system - selection works on faces within sectors.
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext};
+use crate::ui::{shortcut_modifier_down, Rect, UiContext};
 use crate::rasterizer::{
     Framebuffer, Texture as RasterTexture, render_mesh, Color as RasterColor, Vec3,
-    WIDTH, HEIGHT, WIDTH_HI, HEIGHT_HI,
-    perspective_transform,
+    world_to_screen, apply_crt, apply_color_grade, screen_to_ray, text_size, TextPalette, GLYPH_HEIGHT,
 };
-use crate::world::SECTOR_SIZE;
-use super::{EditorState, EditorTool, Selection, SectorFace};
-
-/// Project a world-space point to framebuffer coordinates
-fn world_to_screen(
-    world_pos: Vec3,
-    camera_pos: Vec3,
-    basis_x: Vec3,
-    basis_y: Vec3,
-    basis_z: Vec3,
-    fb_width: usize,
-    fb_height: usize,
-) -> Option<(f32, f32)> {
-    let rel = world_pos - camera_pos;
-    let cam_z = rel.dot(basis_z);
-
-    // Behind camera
-    if cam_z <= 0.1 {
-        return None;
-    }
-
-    let cam_x = rel.dot(basis_x);
-    let cam_y = rel.dot(basis_y);
-
-    // Same projection as the rasterizer
-    const SCALE: f32 = 0.75;
-    let vs = (fb_width.min(fb_height) as f32 / 2.0) * SCALE;
-    let ud = 5.0;
-    let us = ud - 1.0;
-
-    let denom = cam_z + ud;
-    let sx = (cam_x * us / denom) * vs + (fb_width as f32 / 2.0);
-    let sy = (cam_y * us / denom) * vs + (fb_height as f32 / 2.0);
-
-    Some((sx, sy))
-}
+use crate::world::{blob_shadow, draw_blob_shadow, draw_weather, CameraKey, CameraPath, Direction, EntityRef, Frustum, HitFace};
+use crate::game::{CutscenePlayer, InputFrame, BOSS_ARCHETYPE, DOOR_ARCHETYPE, PLATFORM_ARCHETYPE};
+use super::{constrain_camera, face_corners, faces_at_point, format_clicks, CameraCollision, EditorState, EDITOR_FOCUS, EditorTool, PickTarget, Selection, SectorFace};
 
 /// Calculate distance from point to line segment in 2D screen space
 fn point_to_segment_distance(
@@ -77,63 +43,61 @@ fn point_to_segment_distance(
     (dist_x * dist_x + dist_y * dist_y).sqrt()
 }
 
-/// Test if point is inside 2D triangle using sign-based edge test
-/// This works regardless of triangle winding order
-fn point_in_triangle_2d(
-    px: f32, py: f32,      // Point
-    x1: f32, y1: f32,      // Triangle v1
-    x2: f32, y2: f32,      // Triangle v2
-    x3: f32, y3: f32,      // Triangle v3
-) -> bool {
-    // Calculate signed areas using cross product
-    fn sign(px: f32, py: f32, ax: f32, ay: f32, bx: f32, by: f32) -> f32 {
-        (px - bx) * (ay - by) - (ax - bx) * (py - by)
-    }
-
-    let d1 = sign(px, py, x1, y1, x2, y2);
-    let d2 = sign(px, py, x2, y2, x3, y3);
-    let d3 = sign(px, py, x3, y3, x1, y1);
-
-    let has_neg = (d1 < 0.0) || (d2 < 0.0) || (d3 < 0.0);
-    let has_pos = (d1 > 0.0) || (d2 > 0.0) || (d3 > 0.0);
-
-    // Point is inside if all signs are same (all positive or all negative)
-    !(has_neg && has_pos)
-}
-
-/// Draw the 3D viewport using the software rasterizer
+/// Draw the 3D viewport using the software rasterizer (at reduced resolution
+/// while `resizing` its panel or moving the camera)
 pub fn draw_viewport_3d(
     ctx: &mut UiContext,
     rect: Rect,
     state: &mut EditorState,
     textures: &[RasterTexture],
     fb: &mut Framebuffer,
+    resizing: bool,
 ) {
-    // Resize framebuffer based on resolution setting
-    let (target_w, target_h) = if state.raster_settings.low_resolution {
-        (WIDTH, HEIGHT)
+    // Grid metrics of the level being edited
+    let sector_size = state.level.sector_size;
+    let click_height = state.level.click_height;
+
+    // Simulate: play mode drives the camera, editing input is paused
+    let simulating = state.simulation.is_some();
+
+    // Resolution from the setting, halved while the view moves so big levels
+    // keep up, back to full as soon as it stops. The framebuffer is only
+    // resized once input is handled: hover picking reads last frame's IDs,
+    // which a resize would wipe.
+    let reduced = !simulating && (resizing || state.camera_moving);
+    let (target_w, target_h) = if reduced {
+        state.raster_settings.reduced_resolution()
     } else {
-        (WIDTH_HI, HEIGHT_HI)
+        state.raster_settings.resolution()
     };
-    fb.resize(target_w, target_h);
+
+    if simulating && is_key_pressed(KeyCode::Escape) {
+        state.toggle_simulation();
+    }
+    if let Some((sim, _)) = state.simulation.as_mut() {
+        sim.update(get_frame_time(), InputFrame::capture());
+        // No loot notices, rumble or sounds in the viewport
+        sim.looted.clear();
+        sim.rumble.clear();
+        sim.sounds.clear();
+        state.camera_3d.position = sim.camera.position;
+        state.camera_3d.rotation_x = sim.camera.rotation_x;
+        state.camera_3d.rotation_y = sim.camera.rotation_y;
+        state.camera_3d.update_basis();
+    }
+    if let Some(logic) = state.logic_preview.as_mut() {
+        logic.update(&state.level, get_frame_time());
+    }
 
     let mouse_pos = (ctx.mouse.x, ctx.mouse.y);
-    let inside_viewport = ctx.mouse.inside(&rect);
+    let inside_viewport = ctx.mouse.inside(&rect) && !simulating;
+    // Shortcuts need the mouse over the view and the keyboard free (no text field typing)
+    let keys_viewport = inside_viewport && ctx.accepts_keys(EDITOR_FOCUS);
 
     // Pre-calculate viewport scaling (used multiple times)
     let fb_width = fb.width;
     let fb_height = fb.height;
-    let fb_aspect = fb_width as f32 / fb_height as f32;
-    let rect_aspect = rect.w / rect.h;
-    let (draw_w, draw_h, draw_x, draw_y) = if fb_aspect > rect_aspect {
-        let w = rect.w;
-        let h = rect.w / fb_aspect;
-        (w, h, rect.x, rect.y + (rect.h - h) * 0.5)
-    } else {
-        let h = rect.h;
-        let w = rect.h * fb_aspect;
-        (w, h, rect.x + (rect.w - w) * 0.5, rect.y)
-    };
+    let (draw_w, draw_h, draw_x, draw_y) = fit_in_rect(rect, fb_width, fb_height);
 
     // Helper to convert screen mouse to framebuffer coordinates
     let screen_to_fb = |mx: f32, my: f32| -> Option<(f32, f32)> {
@@ -146,7 +110,7 @@ pub fn draw_viewport_3d(
         }
     };
 
-    // Camera rotation with right mouse button (same as game mode)
+    // Camera rotation with right mouse button or two-finger drag (same as game mode)
     // Only rotate camera when not dragging a vertex
     if ctx.mouse.right_down && inside_viewport && state.dragging_sector_vertices.is_empty() {
         if state.viewport_mouse_captured {
@@ -161,8 +125,14 @@ pub fn draw_viewport_3d(
     }
 
     // Keyboard camera movement (WASD + Q/E) - only when viewport focused and not dragging
+    // (Ctrl+S is a save, not a step back)
     let move_speed = 100.0; // Scaled for TRLE units (1024 per sector)
-    if (inside_viewport || state.viewport_mouse_captured) && state.dragging_sector_vertices.is_empty() {
+    let camera_before = state.camera_3d.position;
+    if (inside_viewport || state.viewport_mouse_captured)
+        && ctx.accepts_keys(EDITOR_FOCUS)
+        && !shortcut_modifier_down()
+        && state.dragging_sector_vertices.is_empty()
+    {
         if is_key_down(KeyCode::W) {
             state.camera_3d.position = state.camera_3d.position + state.camera_3d.basis_z * move_speed;
         }
@@ -183,15 +153,81 @@ pub fn draw_viewport_3d(
         }
     }
 
+    // Mouse wheel / pinch moves the camera forward and back
+    if inside_viewport && ctx.mouse.scroll != 0.0 && state.dragging_sector_vertices.is_empty() {
+        let zoom_dir = if ctx.mouse.scroll > 0.0 { 1.0 } else { -1.0 };
+        state.camera_3d.position = state.camera_3d.position + state.camera_3d.basis_z * move_speed * 2.0 * zoom_dir;
+    }
+    if state.camera_collision != CameraCollision::Off && state.camera_3d.position != camera_before {
+        state.camera_3d.position = constrain_camera(&state.level, camera_before, state.camera_3d.position, state.camera_collision);
+    }
+    state.camera_moving = state.viewport_mouse_captured || state.camera_3d.position != camera_before;
+
+    // Camera path authoring: K adds a key from the current view, Shift+K removes the
+    // last one; the trigger name and key times are edited in the Room panel
+    let shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+    if keys_viewport && state.camera_preview.is_none() && is_key_pressed(KeyCode::K) {
+        if shift_down {
+            let idx = state.active_camera_path;
+            if state.level.camera_paths.get(idx).is_some_and(|p| !p.keys.is_empty()) {
+                state.save_undo("Removed camera key");
+                state.level.camera_paths[idx].keys.pop();
+                let count = state.level.camera_paths[idx].keys.len();
+                state.set_status(&format!("Camera path: {} keys", count), 2.0);
+            }
+        } else {
+            state.save_undo("Added camera key");
+            if state.level.camera_paths.is_empty() {
+                state.level.camera_paths.push(CameraPath::new("path_0"));
+                state.active_camera_path = 0;
+            }
+            let idx = state.active_camera_path.min(state.level.camera_paths.len() - 1);
+            state.active_camera_path = idx;
+            let cam = &state.camera_3d;
+            let path = &mut state.level.camera_paths[idx];
+            let time = path.next_key_time();
+            path.add_key(CameraKey {
+                time,
+                position: cam.position,
+                target: cam.position + cam.basis_z * 1024.0,
+            });
+            let msg = format!("Camera key {} at {:.1}s", path.keys.len(), time);
+            state.set_status(&msg, 2.0);
+        }
+    }
+
+    // P previews the active camera path (press again to stop)
+    if keys_viewport && is_key_pressed(KeyCode::P) {
+        if state.camera_preview.is_some() {
+            stop_camera_preview(state);
+        } else if state.level.camera_paths.get(state.active_camera_path).is_some_and(|p| p.keys.len() >= 2) {
+            let cam = &state.camera_3d;
+            let restore = (cam.position, cam.rotation_x, cam.rotation_y);
+            state.camera_preview = Some((CutscenePlayer::start(state.active_camera_path), restore));
+        } else {
+            state.set_status("Camera path needs at least 2 keys (K)", 2.0);
+        }
+    }
+    if let Some((player, _)) = state.camera_preview.as_mut() {
+        if !player.update(get_frame_time(), &state.level, &mut state.camera_3d) {
+            stop_camera_preview(state);
+        }
+    }
+
     // Toggle link coincident vertices mode with L key
-    if inside_viewport && is_key_pressed(KeyCode::L) {
+    if keys_viewport && is_key_pressed(KeyCode::L) {
         state.link_coincident_vertices = !state.link_coincident_vertices;
         let mode = if state.link_coincident_vertices { "Linked" } else { "Independent" };
         state.set_status(&format!("Vertex mode: {}", mode), 2.0);
     }
 
+    // X extrudes the selected face (Shift+X pushes floors and ceilings down)
+    if keys_viewport && is_key_pressed(KeyCode::X) {
+        state.extrude_selection(shift_down);
+    }
+
     // Delete selected face with Delete or Backspace key
-    if inside_viewport && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace)) {
+    if keys_viewport && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace)) {
         if let Selection::SectorFace { room, x, z, face } = &state.selection {
             let (room_idx, gx, gz, face) = (*room, *x, *z, *face);
 
@@ -224,11 +260,11 @@ pub fn draw_viewport_3d(
             };
 
             if has_face {
-                state.save_undo();
+                state.save_undo("Deleted face");
 
                 let deleted = match face {
                     SectorFace::Floor => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                        if let Some(room) = state.room_mut(room_idx) {
                             if let Some(sector) = room.get_sector_mut(gx, gz) {
                                 sector.floor = None;
                             }
@@ -237,7 +273,7 @@ pub fn draw_viewport_3d(
                         } else { None }
                     }
                     SectorFace::Ceiling => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                        if let Some(room) = state.room_mut(room_idx) {
                             if let Some(sector) = room.get_sector_mut(gx, gz) {
                                 sector.ceiling = None;
                             }
@@ -246,7 +282,7 @@ pub fn draw_viewport_3d(
                         } else { None }
                     }
                     SectorFace::WallNorth(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                        if let Some(room) = state.room_mut(room_idx) {
                             if let Some(sector) = room.get_sector_mut(gx, gz) {
                                 if i < sector.walls_north.len() {
                                     sector.walls_north.remove(i);
@@ -257,7 +293,7 @@ pub fn draw_viewport_3d(
                         } else { None }
                     }
                     SectorFace::WallEast(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                        if let Some(room) = state.room_mut(room_idx) {
                             if let Some(sector) = room.get_sector_mut(gx, gz) {
                                 if i < sector.walls_east.len() {
                                     sector.walls_east.remove(i);
@@ -268,7 +304,7 @@ pub fn draw_viewport_3d(
                         } else { None }
                     }
                     SectorFace::WallSouth(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                        if let Some(room) = state.room_mut(room_idx) {
                             if let Some(sector) = room.get_sector_mut(gx, gz) {
                                 if i < sector.walls_south.len() {
                                     sector.walls_south.remove(i);
@@ -279,7 +315,7 @@ pub fn draw_viewport_3d(
                         } else { None }
                     }
                     SectorFace::WallWest(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                        if let Some(room) = state.room_mut(room_idx) {
                             if let Some(sector) = room.get_sector_mut(gx, gz) {
                                 if i < sector.walls_west.len() {
                                     sector.walls_west.remove(i);
@@ -292,6 +328,10 @@ pub fn draw_viewport_3d(
                 };
 
                 if let Some(type_name) = deleted {
+                    // Don't leave empty rows and columns behind on the room's edges
+                    if let Some(room) = state.room_mut(room_idx) {
+                        room.trim_empty_borders();
+                    }
                     state.selection = Selection::None;
                     state.set_status(&format!("Deleted {}", type_name), 2.0);
                 }
@@ -314,31 +354,31 @@ pub fn draw_viewport_3d(
 
     if let Some(room) = state.level.rooms.get(state.current_room) {
         for (gx, gz, sector) in room.iter_sectors() {
-            let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
-            let base_z = room.position.z + (gz as f32) * SECTOR_SIZE;
+            let base_x = room.position.x + (gx as f32) * sector_size;
+            let base_z = room.position.z + (gz as f32) * sector_size;
 
             // Floor vertices
             if let Some(floor) = &sector.floor {
                 all_vertices.push((Vec3::new(base_x, floor.heights[0], base_z), state.current_room, gx, gz, 0, SectorFace::Floor));
-                all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z), state.current_room, gx, gz, 1, SectorFace::Floor));
-                all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE), state.current_room, gx, gz, 2, SectorFace::Floor));
-                all_vertices.push((Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE), state.current_room, gx, gz, 3, SectorFace::Floor));
+                all_vertices.push((Vec3::new(base_x + sector_size, floor.heights[1], base_z), state.current_room, gx, gz, 1, SectorFace::Floor));
+                all_vertices.push((Vec3::new(base_x + sector_size, floor.heights[2], base_z + sector_size), state.current_room, gx, gz, 2, SectorFace::Floor));
+                all_vertices.push((Vec3::new(base_x, floor.heights[3], base_z + sector_size), state.current_room, gx, gz, 3, SectorFace::Floor));
             }
 
             // Ceiling vertices
             if let Some(ceiling) = &sector.ceiling {
                 all_vertices.push((Vec3::new(base_x, ceiling.heights[0], base_z), state.current_room, gx, gz, 0, SectorFace::Ceiling));
-                all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z), state.current_room, gx, gz, 1, SectorFace::Ceiling));
-                all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE), state.current_room, gx, gz, 2, SectorFace::Ceiling));
-                all_vertices.push((Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE), state.current_room, gx, gz, 3, SectorFace::Ceiling));
+                all_vertices.push((Vec3::new(base_x + sector_size, ceiling.heights[1], base_z), state.current_room, gx, gz, 1, SectorFace::Ceiling));
+                all_vertices.push((Vec3::new(base_x + sector_size, ceiling.heights[2], base_z + sector_size), state.current_room, gx, gz, 2, SectorFace::Ceiling));
+                all_vertices.push((Vec3::new(base_x, ceiling.heights[3], base_z + sector_size), state.current_room, gx, gz, 3, SectorFace::Ceiling));
             }
 
             // Wall vertices
             let wall_configs: [(&Vec<crate::world::VerticalFace>, f32, f32, f32, f32, fn(usize) -> SectorFace); 4] = [
-                (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z, |i| SectorFace::WallNorth(i)),
-                (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, |i| SectorFace::WallEast(i)),
-                (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE, |i| SectorFace::WallSouth(i)),
-                (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z, |i| SectorFace::WallWest(i)),
+                (&sector.walls_north, base_x, base_z, base_x + sector_size, base_z, |i| SectorFace::WallNorth(i)),
+                (&sector.walls_east, base_x + sector_size, base_z, base_x + sector_size, base_z + sector_size, |i| SectorFace::WallEast(i)),
+                (&sector.walls_south, base_x + sector_size, base_z + sector_size, base_x, base_z + sector_size, |i| SectorFace::WallSouth(i)),
+                (&sector.walls_west, base_x, base_z + sector_size, base_x, base_z, |i| SectorFace::WallWest(i)),
             ];
 
             for (walls, x0, z0, x1, z1, make_face) in wall_configs {
@@ -354,240 +394,132 @@ pub fn draw_viewport_3d(
         }
     }
 
-    // In Select mode, find hovered vertex/edge/face using 2D screen projection
-    if inside_viewport && !ctx.mouse.right_down && state.tool == EditorTool::Select {
+    // In Select mode, find hovered vertex/edge/face from last frame's ID buffer:
+    // only faces visible around the cursor are tested, so hidden geometry is never hovered.
+    // Holding Alt selects through: faces only, starting with the one behind the visible face.
+    let select_through = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+    let mut select_through_depth = 0;
+    if inside_viewport && !ctx.mouse.right_down && state.tool == EditorTool::Select && select_through {
+        if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
+            let stack = state.level.rooms.get(state.current_room).map_or_else(Vec::new, |room| {
+                faces_at_point(room, state.current_room, &state.camera_3d, fb.width, fb.height, mouse_fb_x, mouse_fb_y)
+            });
+
+            // Moving the cursor starts again from the face right behind the visible one
+            let anchor = (mouse_fb_x as usize, mouse_fb_y as usize);
+            let same_spot = state.select_through_anchor
+                .is_some_and(|(ax, ay)| ax.abs_diff(anchor.0) <= 2 && ay.abs_diff(anchor.1) <= 2);
+            if !same_spot {
+                state.select_through_anchor = Some(anchor);
+                state.select_through_layer = 1;
+            }
+
+            if !stack.is_empty() {
+                let layer = state.select_through_layer % stack.len();
+                let t = stack[layer];
+                hovered_face = Some((t.room, t.gx, t.gz, t.face));
+                select_through_depth = stack.len();
+            }
+        }
+    } else if inside_viewport && !ctx.mouse.right_down && state.tool == EditorTool::Select {
         if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
             const VERTEX_THRESHOLD: f32 = 10.0;
             const EDGE_THRESHOLD: f32 = 8.0;
 
-            // Check vertices first (highest priority)
-            for (world_pos, room_idx, gx, gz, corner_idx, face) in &all_vertices {
-                if let Some((sx, sy)) = world_to_screen(
-                    *world_pos,
-                    state.camera_3d.position,
-                    state.camera_3d.basis_x,
-                    state.camera_3d.basis_y,
-                    state.camera_3d.basis_z,
-                    fb.width,
-                    fb.height,
-                ) {
-                    let dist = ((mouse_fb_x - sx).powi(2) + (mouse_fb_y - sy).powi(2)).sqrt();
-                    if dist < VERTEX_THRESHOLD {
-                        if hovered_vertex.map_or(true, |(_, _, _, _, _, best_dist)| dist < best_dist) {
-                            hovered_vertex = Some((*room_idx, *gx, *gz, *corner_idx, *face, dist));
-                        }
+            let (pick_x, pick_y) = (mouse_fb_x as usize, mouse_fb_y as usize);
+            let to_screen = |p: Vec3| world_to_screen(
+                p,
+                state.camera_3d.position,
+                state.camera_3d.basis_x,
+                state.camera_3d.basis_y,
+                state.camera_3d.basis_z,
+                fb.width,
+                fb.height,
+            );
+
+            // Faces of the current room visible near the cursor, nearest first
+            let mut nearby: Vec<PickTarget> = Vec::new();
+            for id in fb.pick_ids_near(pick_x, pick_y, VERTEX_THRESHOLD as usize) {
+                if let Some(target) = state.pick_table.get(id) {
+                    if target.room == state.current_room && !nearby.contains(&target) {
+                        nearby.push(target);
                     }
                 }
             }
 
-            // Check edges if no vertex hovered
-            if hovered_vertex.is_none() {
-                if let Some(room) = state.level.rooms.get(state.current_room) {
-                    for (gx, gz, sector) in room.iter_sectors() {
-                        let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
-                        let base_z = room.position.z + (gz as f32) * SECTOR_SIZE;
-
-                        // Check floor edges
-                        if let Some(floor) = &sector.floor {
-                            let corners = [
-                                Vec3::new(base_x, floor.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE),
-                            ];
-                            for edge_idx in 0..4 {
-                                let v0 = corners[edge_idx];
-                                let v1 = corners[(edge_idx + 1) % 4];
-                                if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                                    world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
-                                ) {
-                                    let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
-                                    if dist < EDGE_THRESHOLD {
-                                        if hovered_edge.map_or(true, |(_, _, _, _, _, _, best_dist)| dist < best_dist) {
-                                            hovered_edge = Some((state.current_room, gx, gz, 0, edge_idx, None, dist)); // face_idx=0 for floor
-                                        }
-                                    }
-                                }
-                            }
-                        }
+            if let Some(room) = state.level.rooms.get(state.current_room) {
+                for target in &nearby {
+                    let Some(corners) = face_corners(room, target.gx, target.gz, target.face) else {
+                        continue;
+                    };
+                    let screen = corners.map(to_screen);
 
-                        // Check ceiling edges
-                        if let Some(ceiling) = &sector.ceiling {
-                            let corners = [
-                                Vec3::new(base_x, ceiling.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE),
-                            ];
-                            for edge_idx in 0..4 {
-                                let v0 = corners[edge_idx];
-                                let v1 = corners[(edge_idx + 1) % 4];
-                                if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                                    world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
-                                ) {
-                                    let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
-                                    if dist < EDGE_THRESHOLD {
-                                        if hovered_edge.map_or(true, |(_, _, _, _, _, _, best_dist)| dist < best_dist) {
-                                            hovered_edge = Some((state.current_room, gx, gz, 1, edge_idx, None, dist)); // face_idx=1 for ceiling
-                                        }
-                                    }
-                                }
-                            }
+                    // Vertices (highest priority)
+                    for (corner_idx, (sx, sy)) in screen.iter().enumerate().filter_map(|(i, s)| s.map(|s| (i, s))) {
+                        let dist = ((mouse_fb_x - sx).powi(2) + (mouse_fb_y - sy).powi(2)).sqrt();
+                        if dist < VERTEX_THRESHOLD && hovered_vertex.is_none_or(|(_, _, _, _, _, best_dist)| dist < best_dist) {
+                            hovered_vertex = Some((target.room, target.gx, target.gz, corner_idx, target.face, dist));
                         }
+                    }
 
-                        // Check wall edges
-                        let wall_configs: [(&Vec<crate::world::VerticalFace>, f32, f32, f32, f32, fn(usize) -> SectorFace); 4] = [
-                            (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z, |i| SectorFace::WallNorth(i)),
-                            (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, |i| SectorFace::WallEast(i)),
-                            (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE, |i| SectorFace::WallSouth(i)),
-                            (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z, |i| SectorFace::WallWest(i)),
-                        ];
-
-                        for (walls, x0, z0, x1, z1, make_face) in wall_configs {
-                            for (i, wall) in walls.iter().enumerate() {
-                                let wall_corners = [
-                                    Vec3::new(x0, wall.heights[0], z0),
-                                    Vec3::new(x1, wall.heights[1], z1),
-                                    Vec3::new(x1, wall.heights[2], z1),
-                                    Vec3::new(x0, wall.heights[3], z0),
-                                ];
-                                for edge_idx in 0..4 {
-                                    let v0 = wall_corners[edge_idx];
-                                    let v1 = wall_corners[(edge_idx + 1) % 4];
-                                    if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                                        world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                                            state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                        world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                                            state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
-                                    ) {
-                                        let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
-                                        if dist < EDGE_THRESHOLD {
-                                            if hovered_edge.map_or(true, |(_, _, _, _, _, _, best_dist)| dist < best_dist) {
-                                                let wall_face = make_face(i);
-                                                hovered_edge = Some((state.current_room, gx, gz, 2, edge_idx, Some(wall_face), dist)); // face_idx=2 for walls
-                                            }
-                                        }
-                                    }
-                                }
+                    // Edges (face_idx: 0 = floor, 1 = ceiling, 2 = wall)
+                    let (face_idx, wall_face) = match target.face {
+                        SectorFace::Floor => (0, None),
+                        SectorFace::Ceiling => (1, None),
+                        wall => (2, Some(wall)),
+                    };
+                    for edge_idx in 0..4 {
+                        if let (Some((sx0, sy0)), Some((sx1, sy1))) = (screen[edge_idx], screen[(edge_idx + 1) % 4]) {
+                            let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
+                            if dist < EDGE_THRESHOLD && hovered_edge.is_none_or(|(_, _, _, _, _, _, best_dist)| dist < best_dist) {
+                                hovered_edge = Some((target.room, target.gx, target.gz, face_idx, edge_idx, wall_face, dist));
                             }
                         }
                     }
                 }
             }
 
-            // Check faces if no vertex or edge hovered
-            if hovered_vertex.is_none() && hovered_edge.is_none() {
-                if let Some(room) = state.level.rooms.get(state.current_room) {
-                    'face_loop: for (gx, gz, sector) in room.iter_sectors() {
-                        let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
-                        let base_z = room.position.z + (gz as f32) * SECTOR_SIZE;
-
-                        // Check floor (no backface culling - always selectable)
-                        if let Some(floor) = &sector.floor {
-                            let corners = [
-                                Vec3::new(base_x, floor.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE),
-                            ];
-
-                            if let (Some((sx0, sy0)), Some((sx1, sy1)), Some((sx2, sy2)), Some((sx3, sy3))) = (
-                                world_to_screen(corners[0], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[1], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[2], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[3], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                            ) {
-                                // Test both triangles that make up the quad (0-1-2 and 0-2-3)
-                                if point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1, sx2, sy2) ||
-                                   point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx2, sy2, sx3, sy3) {
-                                    hovered_face = Some((state.current_room, gx, gz, SectorFace::Floor));
-                                    break 'face_loop;
-                                }
-                            }
-                        }
-
-                        // Check ceiling
-                        if let Some(ceiling) = &sector.ceiling {
-                            let corners = [
-                                Vec3::new(base_x, ceiling.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE),
-                            ];
-
-                            if let (Some((sx0, sy0)), Some((sx1, sy1)), Some((sx2, sy2)), Some((sx3, sy3))) = (
-                                world_to_screen(corners[0], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[1], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[2], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[3], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                            ) {
-                                if point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1, sx2, sy2) ||
-                                   point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx2, sy2, sx3, sy3) {
-                                    hovered_face = Some((state.current_room, gx, gz, SectorFace::Ceiling));
-                                    break 'face_loop;
-                                }
-                            }
-                        }
-
-                        // Check walls
-                        let wall_configs: [(&Vec<crate::world::VerticalFace>, f32, f32, f32, f32, fn(usize) -> SectorFace); 4] = [
-                            (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z, |i| SectorFace::WallNorth(i)),
-                            (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, |i| SectorFace::WallEast(i)),
-                            (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE, |i| SectorFace::WallSouth(i)),
-                            (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z, |i| SectorFace::WallWest(i)),
-                        ];
-
-                        for (walls, x0, z0, x1, z1, make_face) in wall_configs {
-                            for (i, wall) in walls.iter().enumerate() {
-                                let wall_corners = [
-                                    Vec3::new(x0, wall.heights[0], z0),
-                                    Vec3::new(x1, wall.heights[1], z1),
-                                    Vec3::new(x1, wall.heights[2], z1),
-                                    Vec3::new(x0, wall.heights[3], z0),
-                                ];
+            // Priority: vertex > edge > face (the face is whatever is drawn under the cursor)
+            if hovered_vertex.is_some() {
+                hovered_edge = None;
+            } else if hovered_edge.is_none() {
+                hovered_face = fb
+                    .pick_id(pick_x, pick_y)
+                    .and_then(|id| state.pick_table.get(id))
+                    .filter(|t| t.room == state.current_room)
+                    .map(|t| (t.room, t.gx, t.gz, t.face));
+            }
+        }
+    }
 
-                                if let (Some((sx0, sy0)), Some((sx1, sy1)), Some((sx2, sy2)), Some((sx3, sy3))) = (
-                                    world_to_screen(wall_corners[0], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(wall_corners[1], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(wall_corners[2], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(wall_corners[3], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                ) {
-                                    if point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1, sx2, sy2) ||
-                                       point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx2, sy2, sx3, sy3) {
-                                        hovered_face = Some((state.current_room, gx, gz, make_face(i)));
-                                        break 'face_loop;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    // Box room tool: world sector cell under the mouse on the box floor plane
+    let box_cell = if inside_viewport && state.tool == EditorTool::BoxRoom {
+        screen_to_fb(mouse_pos.0, mouse_pos.1).and_then(|(fb_x, fb_y)| {
+            let cam = &state.camera_3d;
+            let (origin, dir) = screen_to_ray(fb_x, fb_y, fb_width, fb_height, cam.position, cam.basis_x, cam.basis_y, cam.basis_z);
+            let t = (state.box_floor_y - origin.y) / dir.y;
+            if dir.y.abs() < 1e-6 || t <= 0.0 {
+                return None;
             }
+            let p = origin + dir.scale(t);
+            Some(((p.x / sector_size).floor() as i32, (p.z / sector_size).floor() as i32))
+        })
+    } else {
+        None
+    };
+    if let (Some((anchor, _)), Some(cell), true) = (state.box_room_drag, box_cell, ctx.mouse.left_down) {
+        state.box_room_drag = Some((anchor, cell));
+    }
+    if !ctx.mouse.left_down {
+        if let Some((a, b)) = state.box_room_drag.take() {
+            state.create_box_room(a, b);
         }
     }
 
     // In drawing modes, find preview sector position
     if inside_viewport && (state.tool == EditorTool::DrawFloor || state.tool == EditorTool::DrawCeiling) {
         if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
-            use super::{CEILING_HEIGHT, CLICK_HEIGHT};
+            use super::CEILING_HEIGHT;
 
             let is_floor = state.tool == EditorTool::DrawFloor;
 
@@ -608,15 +540,15 @@ pub fn draw_viewport_3d(
                 let search_radius = 20;
                 let cam_x = state.camera_3d.position.x;
                 let cam_z = state.camera_3d.position.z;
-                let start_x = ((cam_x / SECTOR_SIZE).floor() as i32 - search_radius) as f32 * SECTOR_SIZE;
-                let start_z = ((cam_z / SECTOR_SIZE).floor() as i32 - search_radius) as f32 * SECTOR_SIZE;
+                let start_x = ((cam_x / sector_size).floor() as i32 - search_radius) as f32 * sector_size;
+                let start_z = ((cam_z / sector_size).floor() as i32 - search_radius) as f32 * sector_size;
 
                 let mut closest: Option<(f32, f32, f32)> = None;
                 for ix in 0..(search_radius * 2) {
                     for iz in 0..(search_radius * 2) {
-                        let grid_x = start_x + (ix as f32 * SECTOR_SIZE);
-                        let grid_z = start_z + (iz as f32 * SECTOR_SIZE);
-                        let test_pos = Vec3::new(grid_x + SECTOR_SIZE / 2.0, target_y, grid_z + SECTOR_SIZE / 2.0);
+                        let grid_x = start_x + (ix as f32 * sector_size);
+                        let grid_z = start_z + (iz as f32 * sector_size);
+                        let test_pos = Vec3::new(grid_x + sector_size / 2.0, target_y, grid_z + sector_size / 2.0);
 
                         if let Some((sx, sy)) = world_to_screen(test_pos, state.camera_3d.position,
                             state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
@@ -662,12 +594,11 @@ pub fn draw_viewport_3d(
                 let mouse_delta = state.height_adjust_start_mouse_y - mouse_pos.1;
                 let y_sensitivity = 5.0;
                 let raw_delta = mouse_delta * y_sensitivity;
-                // Snap to CLICK_HEIGHT increments
-                let snapped_delta = (raw_delta / CLICK_HEIGHT).round() * CLICK_HEIGHT;
+                let snapped_delta = state.snap.height.snap(raw_delta, click_height);
                 state.placement_target_y = state.height_adjust_start_y + snapped_delta;
                 // Show height in status bar
-                let clicks = (state.placement_target_y / CLICK_HEIGHT) as i32;
-                state.set_status(&format!("Height: {:.0} ({} clicks)", state.placement_target_y, clicks), 0.5);
+                let clicks = state.placement_target_y / click_height;
+                state.set_status(&format!("Height: {:.0} ({} clicks)", state.placement_target_y, format_clicks(clicks)), 0.5);
             }
 
             // Set preview sector if we have a valid position
@@ -675,7 +606,7 @@ pub fn draw_viewport_3d(
                 // Check if sector is occupied using new sector API
                 let occupied = if let Some(room) = state.level.rooms.get(state.current_room) {
                     // Convert world coords to grid coords
-                    if let Some((gx, gz)) = room.world_to_grid(snapped_x + SECTOR_SIZE * 0.5, snapped_z + SECTOR_SIZE * 0.5) {
+                    if let Some((gx, gz)) = room.world_to_grid(snapped_x + sector_size * 0.5, snapped_z + sector_size * 0.5) {
                         if let Some(sector) = room.get_sector(gx, gz) {
                             if is_floor { sector.floor.is_some() } else { sector.ceiling.is_some() }
                         } else {
@@ -704,15 +635,15 @@ pub fn draw_viewport_3d(
     // In DrawWall mode, find preview wall edge
     if inside_viewport && state.tool == EditorTool::DrawWall {
         if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
-            use super::{CEILING_HEIGHT, CLICK_HEIGHT};
+            use super::CEILING_HEIGHT;
             use crate::world::Direction;
 
             // Find the closest sector edge to the mouse cursor
             let search_radius = 20;
             let cam_x = state.camera_3d.position.x;
             let cam_z = state.camera_3d.position.z;
-            let start_x = ((cam_x / SECTOR_SIZE).floor() as i32 - search_radius) as f32 * SECTOR_SIZE;
-            let start_z = ((cam_z / SECTOR_SIZE).floor() as i32 - search_radius) as f32 * SECTOR_SIZE;
+            let start_x = ((cam_x / sector_size).floor() as i32 - search_radius) as f32 * sector_size;
+            let start_z = ((cam_z / sector_size).floor() as i32 - search_radius) as f32 * sector_size;
 
             // Default wall height (floor to ceiling or 0 to CEILING_HEIGHT)
             let (default_y_bottom, default_y_top) = (0.0, CEILING_HEIGHT);
@@ -722,8 +653,8 @@ pub fn draw_viewport_3d(
 
             for ix in 0..(search_radius * 2) {
                 for iz in 0..(search_radius * 2) {
-                    let grid_x = start_x + (ix as f32 * SECTOR_SIZE);
-                    let grid_z = start_z + (iz as f32 * SECTOR_SIZE);
+                    let grid_x = start_x + (ix as f32 * sector_size);
+                    let grid_z = start_z + (iz as f32 * sector_size);
 
                     // Mid-height for edge center detection
                     let mid_y = (default_y_bottom + default_y_top) / 2.0;
@@ -731,13 +662,13 @@ pub fn draw_viewport_3d(
                     // Check all 4 edges of this sector
                     let edges = [
                         // North edge (-Z): from NW to NE corner
-                        (Direction::North, Vec3::new(grid_x + SECTOR_SIZE / 2.0, mid_y, grid_z)),
+                        (Direction::North, Vec3::new(grid_x + sector_size / 2.0, mid_y, grid_z)),
                         // East edge (+X): from NE to SE corner
-                        (Direction::East, Vec3::new(grid_x + SECTOR_SIZE, mid_y, grid_z + SECTOR_SIZE / 2.0)),
+                        (Direction::East, Vec3::new(grid_x + sector_size, mid_y, grid_z + sector_size / 2.0)),
                         // South edge (+Z): from SE to SW corner
-                        (Direction::South, Vec3::new(grid_x + SECTOR_SIZE / 2.0, mid_y, grid_z + SECTOR_SIZE)),
+                        (Direction::South, Vec3::new(grid_x + sector_size / 2.0, mid_y, grid_z + sector_size)),
                         // West edge (-X): from SW to NW corner
-                        (Direction::West, Vec3::new(grid_x, mid_y, grid_z + SECTOR_SIZE / 2.0)),
+                        (Direction::West, Vec3::new(grid_x, mid_y, grid_z + sector_size / 2.0)),
                     ];
 
                     for (edge_dir, center) in edges {
@@ -749,8 +680,8 @@ pub fn draw_viewport_3d(
                             if closest_edge.map_or(true, |(_, _, _, best_dist)| dist < best_dist) {
                                 // Walls face inward based on direction:
                                 // - North wall (at z=grid_z) faces +Z
-                                // - South wall (at z=grid_z+SECTOR_SIZE) faces -Z
-                                // - East wall (at x=grid_x+SECTOR_SIZE) faces -X
+                                // - South wall (at z=grid_z+sector_size) faces -Z
+                                // - East wall (at x=grid_x+sector_size) faces -X
                                 // - West wall (at x=grid_x) faces +X
                                 //
                                 // To make wall face camera, we may need to place on adjacent sector
@@ -759,29 +690,29 @@ pub fn draw_viewport_3d(
                                     Direction::North => {
                                         // Edge at z=grid_z, wall faces +Z (south)
                                         // If camera is north of edge (cam.z < center.z), place as South wall
-                                        // on the sector to the north (grid_z - SECTOR_SIZE)
+                                        // on the sector to the north (grid_z - sector_size)
                                         if cam.z < center.z {
-                                            (grid_x, grid_z - SECTOR_SIZE, Direction::South)
+                                            (grid_x, grid_z - sector_size, Direction::South)
                                         } else {
                                             (grid_x, grid_z, Direction::North)
                                         }
                                     }
                                     Direction::South => {
-                                        // Edge at z=grid_z+SECTOR_SIZE, wall faces -Z (north)
+                                        // Edge at z=grid_z+sector_size, wall faces -Z (north)
                                         // If camera is south of edge (cam.z > center.z), place as North wall
-                                        // on the sector to the south (grid_z + SECTOR_SIZE)
+                                        // on the sector to the south (grid_z + sector_size)
                                         if cam.z > center.z {
-                                            (grid_x, grid_z + SECTOR_SIZE, Direction::North)
+                                            (grid_x, grid_z + sector_size, Direction::North)
                                         } else {
                                             (grid_x, grid_z, Direction::South)
                                         }
                                     }
                                     Direction::East => {
-                                        // Edge at x=grid_x+SECTOR_SIZE, wall faces -X (west)
+                                        // Edge at x=grid_x+sector_size, wall faces -X (west)
                                         // If camera is east of edge (cam.x > center.x), place as West wall
-                                        // on the sector to the east (grid_x + SECTOR_SIZE)
+                                        // on the sector to the east (grid_x + sector_size)
                                         if cam.x > center.x {
-                                            (grid_x + SECTOR_SIZE, grid_z, Direction::West)
+                                            (grid_x + sector_size, grid_z, Direction::West)
                                         } else {
                                             (grid_x, grid_z, Direction::East)
                                         }
@@ -789,9 +720,9 @@ pub fn draw_viewport_3d(
                                     Direction::West => {
                                         // Edge at x=grid_x, wall faces +X (east)
                                         // If camera is west of edge (cam.x < center.x), place as East wall
-                                        // on the sector to the west (grid_x - SECTOR_SIZE)
+                                        // on the sector to the west (grid_x - sector_size)
                                         if cam.x < center.x {
-                                            (grid_x - SECTOR_SIZE, grid_z, Direction::East)
+                                            (grid_x - sector_size, grid_z, Direction::East)
                                         } else {
                                             (grid_x, grid_z, Direction::West)
                                         }
@@ -808,7 +739,7 @@ pub fn draw_viewport_3d(
                 if dist < 80.0 {
                     // Check if this edge already has a wall
                     let occupied = if let Some(room) = state.level.rooms.get(state.current_room) {
-                        if let Some((gx, gz)) = room.world_to_grid(grid_x + SECTOR_SIZE * 0.5, grid_z + SECTOR_SIZE * 0.5) {
+                        if let Some((gx, gz)) = room.world_to_grid(grid_x + sector_size * 0.5, grid_z + sector_size * 0.5) {
                             if let Some(sector) = room.get_sector(gx, gz) {
                                 !sector.walls(dir).is_empty()
                             } else {
@@ -971,22 +902,22 @@ pub fn draw_viewport_3d(
 
                                         // If linking, find coincident vertices for the edge
                                         if state.link_coincident_vertices {
-                                            let base_x = room.position.x + (*gx as f32) * SECTOR_SIZE;
-                                            let base_z = room.position.z + (*gz as f32) * SECTOR_SIZE;
+                                            let base_x = room.position.x + (*gx as f32) * sector_size;
+                                            let base_z = room.position.z + (*gz as f32) * sector_size;
 
                                             let edge_positions = [
                                                 match corner0 {
                                                     0 => Vec3::new(base_x, h[0], base_z),
-                                                    1 => Vec3::new(base_x + SECTOR_SIZE, h[1], base_z),
-                                                    2 => Vec3::new(base_x + SECTOR_SIZE, h[2], base_z + SECTOR_SIZE),
-                                                    3 => Vec3::new(base_x, h[3], base_z + SECTOR_SIZE),
+                                                    1 => Vec3::new(base_x + sector_size, h[1], base_z),
+                                                    2 => Vec3::new(base_x + sector_size, h[2], base_z + sector_size),
+                                                    3 => Vec3::new(base_x, h[3], base_z + sector_size),
                                                     _ => unreachable!(),
                                                 },
                                                 match corner1 {
                                                     0 => Vec3::new(base_x, h[0], base_z),
-                                                    1 => Vec3::new(base_x + SECTOR_SIZE, h[1], base_z),
-                                                    2 => Vec3::new(base_x + SECTOR_SIZE, h[2], base_z + SECTOR_SIZE),
-                                                    3 => Vec3::new(base_x, h[3], base_z + SECTOR_SIZE),
+                                                    1 => Vec3::new(base_x + sector_size, h[1], base_z),
+                                                    2 => Vec3::new(base_x + sector_size, h[2], base_z + sector_size),
+                                                    3 => Vec3::new(base_x, h[3], base_z + sector_size),
                                                     _ => unreachable!(),
                                                 },
                                             ];
@@ -1056,6 +987,13 @@ pub fn draw_viewport_3d(
                     state.drag_initial_heights.clear();
                     state.viewport_drag_started = false;
 
+                    // Alt+click again at the same spot goes one face deeper
+                    if select_through_depth > 0 {
+                        let layer = state.select_through_layer % select_through_depth;
+                        state.set_status(&format!("Selected through: face {} of {}", layer + 1, select_through_depth), 1.5);
+                        state.select_through_layer = layer + 1;
+                    }
+
                     // Handle selection first (Shift = toggle multi-select)
                     let new_selection = Selection::SectorFace { room: room_idx, x: gx, z: gz, face };
                     if shift_down {
@@ -1116,13 +1054,13 @@ pub fn draw_viewport_3d(
 
                                     // If linking, find coincident vertices
                                     if state.link_coincident_vertices {
-                                        let base_x = room.position.x + (*gx as f32) * SECTOR_SIZE;
-                                        let base_z = room.position.z + (*gz as f32) * SECTOR_SIZE;
+                                        let base_x = room.position.x + (*gx as f32) * sector_size;
+                                        let base_z = room.position.z + (*gz as f32) * sector_size;
                                         let face_positions = [
                                             Vec3::new(base_x, h[0], base_z),
-                                            Vec3::new(base_x + SECTOR_SIZE, h[1], base_z),
-                                            Vec3::new(base_x + SECTOR_SIZE, h[2], base_z + SECTOR_SIZE),
-                                            Vec3::new(base_x, h[3], base_z + SECTOR_SIZE),
+                                            Vec3::new(base_x + sector_size, h[1], base_z),
+                                            Vec3::new(base_x + sector_size, h[2], base_z + sector_size),
+                                            Vec3::new(base_x, h[3], base_z + sector_size),
                                         ];
 
                                         const EPSILON: f32 = 0.1;
@@ -1192,71 +1130,15 @@ pub fn draw_viewport_3d(
                         let type_name = if is_floor { "floor" } else { "ceiling" };
                         state.set_status(&format!("Sector already has a {}", type_name), 2.0);
                     } else {
-                        state.save_undo();
-
-                        // Get texture and room position before borrowing mutably
                         let texture = state.selected_texture.clone();
-                        let room_pos = state.level.rooms.get(state.current_room)
-                            .map(|r| r.position)
-                            .unwrap_or_default();
-
-                        if let Some(room) = state.level.rooms.get_mut(state.current_room) {
-                            // Convert world coords to local coords (can be negative)
-                            let local_x = snapped_x - room_pos.x;
-                            let local_z = snapped_z - room_pos.z;
-
-                            // Calculate grid coords, handling negative values
-                            let mut gx = (local_x / SECTOR_SIZE).floor() as i32;
-                            let mut gz = (local_z / SECTOR_SIZE).floor() as i32;
-
-                            // Expand grid in negative X direction if needed
-                            while gx < 0 {
-                                // Shift room position by one sector in -X
-                                room.position.x -= SECTOR_SIZE;
-                                // Insert new column at front
-                                room.sectors.insert(0, (0..room.depth).map(|_| None).collect());
-                                room.width += 1;
-                                gx += 1; // Grid index shifts up
-                            }
-
-                            // Expand grid in negative Z direction if needed
-                            while gz < 0 {
-                                // Shift room position by one sector in -Z
-                                room.position.z -= SECTOR_SIZE;
-                                // Insert new row at front of each column
-                                for col in &mut room.sectors {
-                                    col.insert(0, None);
-                                }
-                                room.depth += 1;
-                                gz += 1; // Grid index shifts up
-                            }
-
-                            // Now gx and gz are guaranteed >= 0, convert to usize
-                            let gx = gx as usize;
-                            let gz = gz as usize;
-
-                            // Expand room grid in positive direction if needed
-                            while gx >= room.width {
-                                room.width += 1;
-                                room.sectors.push((0..room.depth).map(|_| None).collect());
-                            }
-                            while gz >= room.depth {
-                                room.depth += 1;
-                                for col in &mut room.sectors {
-                                    col.push(None);
-                                }
-                            }
-
+                        let label = if is_floor { "Created floor sector" } else { "Created ceiling sector" };
+                        state.edit_sector_at(snapped_x + sector_size * 0.5, snapped_z + sector_size * 0.5, label, |room, gx, gz| {
                             if is_floor {
                                 room.set_floor(gx, gz, target_y, texture);
                             } else {
                                 room.set_ceiling(gx, gz, target_y, texture);
                             }
-                            room.recalculate_bounds();
-                        }
-
-                        let status = if is_floor { "Created floor sector" } else { "Created ceiling sector" };
-                        state.set_status(status, 2.0);
+                        });
                     }
                 }
             }
@@ -1268,75 +1150,64 @@ pub fn draw_viewport_3d(
                     if occupied {
                         state.set_status("Edge already has a wall", 2.0);
                     } else {
-                        state.save_undo();
-
                         let texture = state.selected_texture.clone();
-                        let room_pos = state.level.rooms.get(state.current_room)
-                            .map(|r| r.position)
-                            .unwrap_or_default();
-
-                        if let Some(room) = state.level.rooms.get_mut(state.current_room) {
-                            // Convert world coords to local coords (can be negative)
-                            let local_x = grid_x - room_pos.x;
-                            let local_z = grid_z - room_pos.z;
-
-                            // Calculate grid coords, handling negative values
-                            let mut gx = (local_x / SECTOR_SIZE).floor() as i32;
-                            let mut gz = (local_z / SECTOR_SIZE).floor() as i32;
-
-                            // Expand grid in negative X direction if needed
-                            while gx < 0 {
-                                room.position.x -= SECTOR_SIZE;
-                                room.sectors.insert(0, (0..room.depth).map(|_| None).collect());
-                                room.width += 1;
-                                gx += 1;
-                            }
-
-                            // Expand grid in negative Z direction if needed
-                            while gz < 0 {
-                                room.position.z -= SECTOR_SIZE;
-                                for col in &mut room.sectors {
-                                    col.insert(0, None);
-                                }
-                                room.depth += 1;
-                                gz += 1;
-                            }
-
-                            // Expand in positive direction if needed
-                            let gx = gx as usize;
-                            let gz = gz as usize;
-                            while gx >= room.width {
-                                room.width += 1;
-                                room.sectors.push((0..room.depth).map(|_| None).collect());
-                            }
-                            while gz >= room.depth {
-                                room.depth += 1;
-                                for col in &mut room.sectors {
-                                    col.push(None);
-                                }
-                            }
-
-                            // Create the wall
-                            let wall = VerticalFace::new(y_bottom, y_top, texture);
-
-                            // Ensure sector exists and add wall
-                            let sector = room.ensure_sector(gx, gz);
-                            match dir {
-                                Direction::North => sector.walls_north.push(wall),
-                                Direction::East => sector.walls_east.push(wall),
-                                Direction::South => sector.walls_south.push(wall),
-                                Direction::West => sector.walls_west.push(wall),
-                            }
-                            room.recalculate_bounds();
-                        }
-
                         let dir_name = match dir {
                             Direction::North => "north",
                             Direction::East => "east",
                             Direction::South => "south",
                             Direction::West => "west",
                         };
-                        state.set_status(&format!("Created {} wall", dir_name), 2.0);
+                        state.edit_sector_at(grid_x + sector_size * 0.5, grid_z + sector_size * 0.5, &format!("Created {} wall", dir_name), |room, gx, gz| {
+                            room.ensure_sector(gx, gz).walls_mut(dir).push(VerticalFace::new(y_bottom, y_top, texture));
+                        });
+                    }
+                }
+            }
+            // BoxRoom mode - start dragging out the room's rectangle
+            else if state.tool == EditorTool::BoxRoom {
+                if let Some(cell) = box_cell {
+                    state.box_room_drag = Some((cell, cell));
+                }
+            }
+            // PlaceWaypoint mode - drop a waypoint on the floor under the cursor
+            else if state.tool == EditorTool::PlaceWaypoint {
+                if let Some((fb_x, fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
+                    let cam = &state.camera_3d;
+                    let (origin, dir) = screen_to_ray(
+                        fb_x, fb_y, fb_width, fb_height,
+                        cam.position, cam.basis_x, cam.basis_y, cam.basis_z,
+                    );
+                    state.spatial.refresh(&state.level);
+                    match state.spatial.raycast(&state.level, origin, dir, f32::MAX) {
+                        Some(hit) if hit.face == HitFace::Floor => {
+                            let local = hit.point - state.level.rooms[hit.room].position;
+                            state.save_undo("Placed waypoint");
+                            if let Some(new_wp) = state.level.add_waypoint(hit.room, local) {
+                                state.selected_waypoint = Some(new_wp);
+                                state.set_status("Waypoint placed", 1.5);
+                            }
+                        }
+                        Some(_) => state.set_status("Waypoints need a floor", 2.0),
+                        None => {}
+                    }
+                }
+            }
+            // PlaceObject mode - drop an entity on the floor under the cursor
+            else if state.tool == EditorTool::PlaceObject {
+                if let Some((fb_x, fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
+                    let cam = &state.camera_3d;
+                    let (origin, dir) = screen_to_ray(
+                        fb_x, fb_y, fb_width, fb_height,
+                        cam.position, cam.basis_x, cam.basis_y, cam.basis_z,
+                    );
+                    state.spatial.refresh(&state.level);
+                    match state.spatial.raycast(&state.level, origin, dir, f32::MAX) {
+                        Some(hit) if hit.face == HitFace::Floor => {
+                            let local = hit.point - state.level.rooms[hit.room].position;
+                            state.place_entity(hit.room, local);
+                        }
+                        Some(_) => state.set_status("Entities need a floor", 2.0),
+                        None => {}
                     }
                 }
             }
@@ -1344,10 +1215,8 @@ pub fn draw_viewport_3d(
 
         // Continue dragging (Y-axis only - TRLE constraint)
         if ctx.mouse.left_down && !state.dragging_sector_vertices.is_empty() {
-            use super::CLICK_HEIGHT;
-
             if !state.viewport_drag_started {
-                state.save_undo();
+                state.save_undo(&format!("Dragged {} vertices", state.dragging_sector_vertices.len()));
                 state.viewport_drag_started = true;
             }
 
@@ -1368,9 +1237,9 @@ pub fn draw_viewport_3d(
             for (i, &(room_idx, gx, gz, face, corner_idx)) in state.dragging_sector_vertices.clone().iter().enumerate() {
                 if let Some(initial_h) = state.drag_initial_heights.get(i) {
                     let new_h = initial_h + delta_from_initial;
-                    let snapped_h = (new_h / CLICK_HEIGHT).round() * CLICK_HEIGHT;
+                    let snapped_h = state.snap.height.snap(new_h, click_height);
 
-                    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                    if let Some(room) = state.room_mut(room_idx) {
                         if let Some(sector) = room.get_sector_mut(gx, gz) {
                             match face {
                                 SectorFace::Floor => {
@@ -1404,7 +1273,7 @@ pub fn draw_viewport_3d(
             }
 
             // Recalculate bounds while dragging so wireframe updates in real-time
-            if let Some(room) = state.level.rooms.get_mut(state.current_room) {
+            if let Some(room) = state.room_mut(state.current_room) {
                 room.recalculate_bounds();
             }
         }
@@ -1413,7 +1282,7 @@ pub fn draw_viewport_3d(
         if ctx.mouse.left_released {
             // If we actually dragged geometry, recalculate room bounds
             if state.viewport_drag_started {
-                if let Some(room) = state.level.rooms.get_mut(state.current_room) {
+                if let Some(room) = state.room_mut(state.current_room) {
                     room.recalculate_bounds();
                 }
             }
@@ -1426,7 +1295,9 @@ pub fn draw_viewport_3d(
     // Update mouse position for next frame
     state.viewport_last_mouse = mouse_pos;
 
-    // Clear framebuffer
+    // Clear framebuffer (only the current field when interlaced)
+    fb.resize(target_w, target_h);
+    fb.begin_field(state.raster_settings.is_interlaced() && !reduced);
     fb.clear(RasterColor::new(30, 30, 40));
 
     // Draw main floor grid (large, fixed extent)
@@ -1439,7 +1310,7 @@ pub fn draw_viewport_3d(
         let grid_y = 0.0;
 
         // Draw grid lines - use shorter segments for better clipping behavior
-        let segment_length: f32 = SECTOR_SIZE;
+        let segment_length: f32 = sector_size;
 
         // X-parallel lines (varying X, fixed Z)
         let mut z: f32 = -grid_extent;
@@ -1501,15 +1372,15 @@ pub fn draw_viewport_3d(
             let grid_y = 0.0;
 
             // Center of the hovered sector (snap to grid)
-            let center_x = (snapped_x / SECTOR_SIZE).floor() * SECTOR_SIZE + SECTOR_SIZE * 0.5;
-            let center_z = (snapped_z / SECTOR_SIZE).floor() * SECTOR_SIZE + SECTOR_SIZE * 0.5;
+            let center_x = (snapped_x / sector_size).floor() * sector_size + sector_size * 0.5;
+            let center_z = (snapped_z / sector_size).floor() * sector_size + sector_size * 0.5;
 
-            let inner_half = SECTOR_SIZE * 1.5; // Inner 3x3
-            let outer_half = SECTOR_SIZE * 2.5; // Outer 5x5
+            let inner_half = sector_size * 1.5; // Inner 3x3
+            let outer_half = sector_size * 2.5; // Outer 5x5
 
             // Draw grid lines - 6 lines in each direction for 5x5 grid
             for i in 0..=5 {
-                let offset = -outer_half + (i as f32 * SECTOR_SIZE);
+                let offset = -outer_half + (i as f32 * sector_size);
                 let dist_from_center = offset.abs();
 
                 let color = if dist_from_center <= inner_half {
@@ -1547,14 +1418,14 @@ pub fn draw_viewport_3d(
             let inner_color = RasterColor::new(140, 100, 180); // Purple (bright)
             let outer_color = RasterColor::new(70, 50, 90);    // Purple (dim)
 
-            let center_x = (snapped_x / SECTOR_SIZE).floor() * SECTOR_SIZE + SECTOR_SIZE * 0.5;
-            let center_z = (snapped_z / SECTOR_SIZE).floor() * SECTOR_SIZE + SECTOR_SIZE * 0.5;
+            let center_x = (snapped_x / sector_size).floor() * sector_size + sector_size * 0.5;
+            let center_z = (snapped_z / sector_size).floor() * sector_size + sector_size * 0.5;
 
-            let inner_half = SECTOR_SIZE * 1.5;
-            let outer_half = SECTOR_SIZE * 2.5;
+            let inner_half = sector_size * 1.5;
+            let outer_half = sector_size * 2.5;
 
             for i in 0..=5 {
-                let offset = -outer_half + (i as f32 * SECTOR_SIZE);
+                let offset = -outer_half + (i as f32 * sector_size);
                 let dist_from_center = offset.abs();
 
                 let color = if dist_from_center <= inner_half {
@@ -1584,6 +1455,18 @@ pub fn draw_viewport_3d(
         }
     }
 
+    // Draw the box room being dragged out (or the hovered cell)
+    if let Some((a, b)) = state.box_room_drag.or(box_cell.map(|c| (c, c))) {
+        let ((x0, z0), (width, depth)) = super::box_room_rect(a, b);
+        let bounds = crate::world::Aabb::new(
+            Vec3::new(x0 as f32 * sector_size, state.box_floor_y, z0 as f32 * sector_size),
+            Vec3::new((x0 + width as i32) as f32 * sector_size, state.box_ceiling_y, (z0 + depth as i32) as f32 * sector_size),
+        );
+        for (p0, p1) in bounds.edges() {
+            draw_3d_line(fb, p0, p1, &state.camera_3d, RasterColor::new(0, 190, 230));
+        }
+    }
+
     // Draw wall preview when in DrawWall mode
     if let Some((grid_x, grid_z, dir, y_bottom, y_top, occupied)) = preview_wall {
         use crate::world::Direction;
@@ -1592,18 +1475,18 @@ pub fn draw_viewport_3d(
         let (p0, p1) = match dir {
             Direction::North => (
                 Vec3::new(grid_x, y_bottom, grid_z),
-                Vec3::new(grid_x + SECTOR_SIZE, y_bottom, grid_z),
+                Vec3::new(grid_x + sector_size, y_bottom, grid_z),
             ),
             Direction::East => (
-                Vec3::new(grid_x + SECTOR_SIZE, y_bottom, grid_z),
-                Vec3::new(grid_x + SECTOR_SIZE, y_bottom, grid_z + SECTOR_SIZE),
+                Vec3::new(grid_x + sector_size, y_bottom, grid_z),
+                Vec3::new(grid_x + sector_size, y_bottom, grid_z + sector_size),
             ),
             Direction::South => (
-                Vec3::new(grid_x + SECTOR_SIZE, y_bottom, grid_z + SECTOR_SIZE),
-                Vec3::new(grid_x, y_bottom, grid_z + SECTOR_SIZE),
+                Vec3::new(grid_x + sector_size, y_bottom, grid_z + sector_size),
+                Vec3::new(grid_x, y_bottom, grid_z + sector_size),
             ),
             Direction::West => (
-                Vec3::new(grid_x, y_bottom, grid_z + SECTOR_SIZE),
+                Vec3::new(grid_x, y_bottom, grid_z + sector_size),
                 Vec3::new(grid_x, y_bottom, grid_z),
             ),
         };
@@ -1631,29 +1514,32 @@ pub fn draw_viewport_3d(
         }
     }
 
-    // Build texture map from texture packs
-    let mut texture_map: std::collections::HashMap<(String, String), usize> = std::collections::HashMap::new();
-    let mut texture_idx = 0;
-    for pack in &state.texture_packs {
-        for tex in &pack.textures {
-            texture_map.insert((pack.name.clone(), tex.name.clone()), texture_idx);
-            texture_idx += 1;
+    // Render rooms in view from cached meshes (rebuilt only when a room
+    // changed), recording triangle IDs for next frame's hover picking
+    state.mesh_cache.sync_textures(&state.texture_packs);
+    state.mesh_cache.truncate(state.level.rooms.len());
+    let settings = &state.raster_settings;
+    fb.enable_picking(true);
+    state.pick_table.clear();
+    let frustum = Frustum::new(&state.camera_3d, fb.width, fb.height);
+    for (room_idx, room) in state.level.rooms.iter().enumerate() {
+        if !frustum.contains_room(room) {
+            continue;
         }
+        fb.pick_base = state.pick_table.push_room(room_idx, room);
+        let (vertices, faces) = state.mesh_cache.get(room_idx, room);
+        render_mesh(fb, vertices, faces, textures, &state.camera_3d, settings);
     }
-
-    // Texture resolver closure
-    let resolve_texture = |tex_ref: &crate::world::TextureRef| -> Option<usize> {
-        if !tex_ref.is_valid() {
-            return Some(0); // Fallback to first texture
-        }
-        texture_map.get(&(tex_ref.pack.clone(), tex_ref.name.clone())).copied()
-    };
-
-    // Render all rooms
-    let settings = &state.raster_settings;
-    for room in &state.level.rooms {
-        let (vertices, faces) = room.to_render_data_with_textures(&resolve_texture);
-        render_mesh(fb, &vertices, &faces, textures, &state.camera_3d, settings);
+    if let Some((sim, _)) = &state.simulation {
+        sim.draw_models(fb, &state.camera_3d, settings);
+    }
+    let time = get_time() as f32;
+    for room in state.level.rooms.iter().filter(|r| r.weather.is_active() && frustum.contains_room(r)) {
+        draw_weather(fb, room, &state.camera_3d, time);
+    }
+    fb.finish_field(settings.interlace_combing);
+    if state.debug_overlays.any() {
+        draw_debug_overlays(fb, state, &frustum);
     }
 
     // Draw room boundary wireframe for the current room
@@ -1663,8 +1549,8 @@ pub fn draw_viewport_3d(
         // Room grid extents in world space
         let min_x = room.position.x;
         let min_z = room.position.z;
-        let max_x = room.position.x + (room.width as f32) * SECTOR_SIZE;
-        let max_z = room.position.z + (room.depth as f32) * SECTOR_SIZE;
+        let max_x = room.position.x + (room.width as f32) * sector_size;
+        let max_z = room.position.z + (room.depth as f32) * sector_size;
 
         // Use Y range from room's actual geometry bounds
         // bounds are room-relative, so add room.position.y
@@ -1744,8 +1630,8 @@ pub fn draw_viewport_3d(
     if let Some((room_idx, gx, gz, face_idx, edge_idx, wall_face_opt, _)) = hovered_edge {
         if let Some(room) = state.level.rooms.get(room_idx) {
             if let Some(sector) = room.get_sector(gx, gz) {
-                let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
-                let base_z = room.position.z + (gz as f32) * SECTOR_SIZE;
+                let base_x = room.position.x + (gx as f32) * sector_size;
+                let base_z = room.position.z + (gz as f32) * sector_size;
 
                 let edge_color = RasterColor::new(255, 200, 100); // Orange for edge hover
 
@@ -1753,24 +1639,24 @@ pub fn draw_viewport_3d(
                 let corners: Option<[Vec3; 4]> = match face_idx {
                     0 => sector.floor.as_ref().map(|f| [
                         Vec3::new(base_x, f.heights[0], base_z),
-                        Vec3::new(base_x + SECTOR_SIZE, f.heights[1], base_z),
-                        Vec3::new(base_x + SECTOR_SIZE, f.heights[2], base_z + SECTOR_SIZE),
-                        Vec3::new(base_x, f.heights[3], base_z + SECTOR_SIZE),
+                        Vec3::new(base_x + sector_size, f.heights[1], base_z),
+                        Vec3::new(base_x + sector_size, f.heights[2], base_z + sector_size),
+                        Vec3::new(base_x, f.heights[3], base_z + sector_size),
                     ]),
                     1 => sector.ceiling.as_ref().map(|c| [
                         Vec3::new(base_x, c.heights[0], base_z),
-                        Vec3::new(base_x + SECTOR_SIZE, c.heights[1], base_z),
-                        Vec3::new(base_x + SECTOR_SIZE, c.heights[2], base_z + SECTOR_SIZE),
-                        Vec3::new(base_x, c.heights[3], base_z + SECTOR_SIZE),
+                        Vec3::new(base_x + sector_size, c.heights[1], base_z),
+                        Vec3::new(base_x + sector_size, c.heights[2], base_z + sector_size),
+                        Vec3::new(base_x, c.heights[3], base_z + sector_size),
                     ]),
                     2 => {
                         // Wall edge - get corners from the specific wall
                         if let Some(wf) = &wall_face_opt {
                             let (x0, z0, x1, z1) = match wf {
-                                SectorFace::WallNorth(_) => (base_x, base_z, base_x + SECTOR_SIZE, base_z),
-                                SectorFace::WallEast(_) => (base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                                SectorFace::WallSouth(_) => (base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE),
-                                SectorFace::WallWest(_) => (base_x, base_z + SECTOR_SIZE, base_x, base_z),
+                                SectorFace::WallNorth(_) => (base_x, base_z, base_x + sector_size, base_z),
+                                SectorFace::WallEast(_) => (base_x + sector_size, base_z, base_x + sector_size, base_z + sector_size),
+                                SectorFace::WallSouth(_) => (base_x + sector_size, base_z + sector_size, base_x, base_z + sector_size),
+                                SectorFace::WallWest(_) => (base_x, base_z + sector_size, base_x, base_z),
                                 _ => (0.0, 0.0, 0.0, 0.0),
                             };
                             let wall_heights = match wf {
@@ -1817,8 +1703,8 @@ pub fn draw_viewport_3d(
         if !is_selected {
             if let Some(room) = state.level.rooms.get(room_idx) {
                 if let Some(sector) = room.get_sector(gx, gz) {
-                    let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
-                    let base_z = room.position.z + (gz as f32) * SECTOR_SIZE;
+                    let base_x = room.position.x + (gx as f32) * sector_size;
+                    let base_z = room.position.z + (gz as f32) * sector_size;
 
                     let hover_color = RasterColor::new(150, 200, 255); // Light blue for hover
 
@@ -1827,9 +1713,9 @@ pub fn draw_viewport_3d(
                             if let Some(floor) = &sector.floor {
                                 let corners = [
                                     Vec3::new(base_x, floor.heights[0], base_z),
-                                    Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z),
-                                    Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE),
-                                    Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE),
+                                    Vec3::new(base_x + sector_size, floor.heights[1], base_z),
+                                    Vec3::new(base_x + sector_size, floor.heights[2], base_z + sector_size),
+                                    Vec3::new(base_x, floor.heights[3], base_z + sector_size),
                                 ];
                                 for i in 0..4 {
                                     draw_3d_line(fb, corners[i], corners[(i + 1) % 4], &state.camera_3d, hover_color);
@@ -1842,9 +1728,9 @@ pub fn draw_viewport_3d(
                             if let Some(ceiling) = &sector.ceiling {
                                 let corners = [
                                     Vec3::new(base_x, ceiling.heights[0], base_z),
-                                    Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z),
-                                    Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE),
-                                    Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE),
+                                    Vec3::new(base_x + sector_size, ceiling.heights[1], base_z),
+                                    Vec3::new(base_x + sector_size, ceiling.heights[2], base_z + sector_size),
+                                    Vec3::new(base_x, ceiling.heights[3], base_z + sector_size),
                                 ];
                                 for i in 0..4 {
                                     draw_3d_line(fb, corners[i], corners[(i + 1) % 4], &state.camera_3d, hover_color);
@@ -1855,8 +1741,8 @@ pub fn draw_viewport_3d(
                         SectorFace::WallNorth(i) => {
                             if let Some(wall) = sector.walls_north.get(i) {
                                 let p0 = Vec3::new(base_x, wall.heights[0], base_z);
-                                let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z);
-                                let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z);
+                                let p1 = Vec3::new(base_x + sector_size, wall.heights[1], base_z);
+                                let p2 = Vec3::new(base_x + sector_size, wall.heights[2], base_z);
                                 let p3 = Vec3::new(base_x, wall.heights[3], base_z);
                                 draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
@@ -1867,10 +1753,10 @@ pub fn draw_viewport_3d(
                         }
                         SectorFace::WallEast(i) => {
                             if let Some(wall) = sector.walls_east.get(i) {
-                                let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z);
-                                let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z + SECTOR_SIZE);
-                                let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z + SECTOR_SIZE);
-                                let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z);
+                                let p0 = Vec3::new(base_x + sector_size, wall.heights[0], base_z);
+                                let p1 = Vec3::new(base_x + sector_size, wall.heights[1], base_z + sector_size);
+                                let p2 = Vec3::new(base_x + sector_size, wall.heights[2], base_z + sector_size);
+                                let p3 = Vec3::new(base_x + sector_size, wall.heights[3], base_z);
                                 draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
@@ -1880,10 +1766,10 @@ pub fn draw_viewport_3d(
                         }
                         SectorFace::WallSouth(i) => {
                             if let Some(wall) = sector.walls_south.get(i) {
-                                let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z + SECTOR_SIZE);
-                                let p1 = Vec3::new(base_x, wall.heights[1], base_z + SECTOR_SIZE);
-                                let p2 = Vec3::new(base_x, wall.heights[2], base_z + SECTOR_SIZE);
-                                let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z + SECTOR_SIZE);
+                                let p0 = Vec3::new(base_x + sector_size, wall.heights[0], base_z + sector_size);
+                                let p1 = Vec3::new(base_x, wall.heights[1], base_z + sector_size);
+                                let p2 = Vec3::new(base_x, wall.heights[2], base_z + sector_size);
+                                let p3 = Vec3::new(base_x + sector_size, wall.heights[3], base_z + sector_size);
                                 draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
@@ -1893,10 +1779,10 @@ pub fn draw_viewport_3d(
                         }
                         SectorFace::WallWest(i) => {
                             if let Some(wall) = sector.walls_west.get(i) {
-                                let p0 = Vec3::new(base_x, wall.heights[0], base_z + SECTOR_SIZE);
+                                let p0 = Vec3::new(base_x, wall.heights[0], base_z + sector_size);
                                 let p1 = Vec3::new(base_x, wall.heights[1], base_z);
                                 let p2 = Vec3::new(base_x, wall.heights[2], base_z);
-                                let p3 = Vec3::new(base_x, wall.heights[3], base_z + SECTOR_SIZE);
+                                let p3 = Vec3::new(base_x, wall.heights[3], base_z + sector_size);
                                 draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
@@ -1919,17 +1805,17 @@ pub fn draw_viewport_3d(
             Selection::SectorFace { room, x, z, face } => {
                 if let Some(room_data) = state.level.rooms.get(*room) {
                     if let Some(sector) = room_data.get_sector(*x, *z) {
-                        let base_x = room_data.position.x + (*x as f32) * SECTOR_SIZE;
-                        let base_z = room_data.position.z + (*z as f32) * SECTOR_SIZE;
+                        let base_x = room_data.position.x + (*x as f32) * sector_size;
+                        let base_z = room_data.position.z + (*z as f32) * sector_size;
 
                         match face {
                             SectorFace::Floor => {
                                 if let Some(floor) = &sector.floor {
                                     let corners = [
                                         Vec3::new(base_x, floor.heights[0], base_z),
-                                        Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z),
-                                        Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE),
-                                        Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE),
+                                        Vec3::new(base_x + sector_size, floor.heights[1], base_z),
+                                        Vec3::new(base_x + sector_size, floor.heights[2], base_z + sector_size),
+                                        Vec3::new(base_x, floor.heights[3], base_z + sector_size),
                                     ];
                                     for i in 0..4 {
                                         draw_3d_line(fb, corners[i], corners[(i + 1) % 4], &state.camera_3d, select_color);
@@ -1941,9 +1827,9 @@ pub fn draw_viewport_3d(
                                 if let Some(ceiling) = &sector.ceiling {
                                     let corners = [
                                         Vec3::new(base_x, ceiling.heights[0], base_z),
-                                        Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z),
-                                        Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE),
-                                        Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE),
+                                        Vec3::new(base_x + sector_size, ceiling.heights[1], base_z),
+                                        Vec3::new(base_x + sector_size, ceiling.heights[2], base_z + sector_size),
+                                        Vec3::new(base_x, ceiling.heights[3], base_z + sector_size),
                                     ];
                                     for i in 0..4 {
                                         draw_3d_line(fb, corners[i], corners[(i + 1) % 4], &state.camera_3d, select_color);
@@ -1954,8 +1840,8 @@ pub fn draw_viewport_3d(
                             SectorFace::WallNorth(i) => {
                                 if let Some(wall) = sector.walls_north.get(*i) {
                                     let p0 = Vec3::new(base_x, wall.heights[0], base_z);
-                                    let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z);
-                                    let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z);
+                                    let p1 = Vec3::new(base_x + sector_size, wall.heights[1], base_z);
+                                    let p2 = Vec3::new(base_x + sector_size, wall.heights[2], base_z);
                                     let p3 = Vec3::new(base_x, wall.heights[3], base_z);
                                     draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
@@ -1966,10 +1852,10 @@ pub fn draw_viewport_3d(
                             }
                             SectorFace::WallEast(i) => {
                                 if let Some(wall) = sector.walls_east.get(*i) {
-                                    let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z);
-                                    let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z + SECTOR_SIZE);
-                                    let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z + SECTOR_SIZE);
-                                    let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z);
+                                    let p0 = Vec3::new(base_x + sector_size, wall.heights[0], base_z);
+                                    let p1 = Vec3::new(base_x + sector_size, wall.heights[1], base_z + sector_size);
+                                    let p2 = Vec3::new(base_x + sector_size, wall.heights[2], base_z + sector_size);
+                                    let p3 = Vec3::new(base_x + sector_size, wall.heights[3], base_z);
                                     draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
@@ -1979,10 +1865,10 @@ pub fn draw_viewport_3d(
                             }
                             SectorFace::WallSouth(i) => {
                                 if let Some(wall) = sector.walls_south.get(*i) {
-                                    let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z + SECTOR_SIZE);
-                                    let p1 = Vec3::new(base_x, wall.heights[1], base_z + SECTOR_SIZE);
-                                    let p2 = Vec3::new(base_x, wall.heights[2], base_z + SECTOR_SIZE);
-                                    let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z + SECTOR_SIZE);
+                                    let p0 = Vec3::new(base_x + sector_size, wall.heights[0], base_z + sector_size);
+                                    let p1 = Vec3::new(base_x, wall.heights[1], base_z + sector_size);
+                                    let p2 = Vec3::new(base_x, wall.heights[2], base_z + sector_size);
+                                    let p3 = Vec3::new(base_x + sector_size, wall.heights[3], base_z + sector_size);
                                     draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
@@ -1992,10 +1878,10 @@ pub fn draw_viewport_3d(
                             }
                             SectorFace::WallWest(i) => {
                                 if let Some(wall) = sector.walls_west.get(*i) {
-                                    let p0 = Vec3::new(base_x, wall.heights[0], base_z + SECTOR_SIZE);
+                                    let p0 = Vec3::new(base_x, wall.heights[0], base_z + sector_size);
                                     let p1 = Vec3::new(base_x, wall.heights[1], base_z);
                                     let p2 = Vec3::new(base_x, wall.heights[2], base_z);
-                                    let p3 = Vec3::new(base_x, wall.heights[3], base_z + SECTOR_SIZE);
+                                    let p3 = Vec3::new(base_x, wall.heights[3], base_z + sector_size);
                                     draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
@@ -2011,16 +1897,16 @@ pub fn draw_viewport_3d(
                 // Sector-level selection (from 2D grid view) - highlight all faces
                 if let Some(room_data) = state.level.rooms.get(*room) {
                     if let Some(sector) = room_data.get_sector(*x, *z) {
-                        let base_x = room_data.position.x + (*x as f32) * SECTOR_SIZE;
-                        let base_z = room_data.position.z + (*z as f32) * SECTOR_SIZE;
+                        let base_x = room_data.position.x + (*x as f32) * sector_size;
+                        let base_z = room_data.position.z + (*z as f32) * sector_size;
 
                         // Draw floor outline if floor exists
                         if let Some(floor) = &sector.floor {
                             let corners = [
                                 Vec3::new(base_x, floor.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE),
+                                Vec3::new(base_x + sector_size, floor.heights[1], base_z),
+                                Vec3::new(base_x + sector_size, floor.heights[2], base_z + sector_size),
+                                Vec3::new(base_x, floor.heights[3], base_z + sector_size),
                             ];
                             for i in 0..4 {
                                 draw_3d_line(fb, corners[i], corners[(i + 1) % 4], &state.camera_3d, select_color);
@@ -2031,9 +1917,9 @@ pub fn draw_viewport_3d(
                         if let Some(ceiling) = &sector.ceiling {
                             let corners = [
                                 Vec3::new(base_x, ceiling.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE),
+                                Vec3::new(base_x + sector_size, ceiling.heights[1], base_z),
+                                Vec3::new(base_x + sector_size, ceiling.heights[2], base_z + sector_size),
+                                Vec3::new(base_x, ceiling.heights[3], base_z + sector_size),
                             ];
                             for i in 0..4 {
                                 draw_3d_line(fb, corners[i], corners[(i + 1) % 4], &state.camera_3d, select_color);
@@ -2047,9 +1933,9 @@ pub fn draw_viewport_3d(
 
                             let corner_positions = [
                                 (base_x, base_z),
-                                (base_x + SECTOR_SIZE, base_z),
-                                (base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                                (base_x, base_z + SECTOR_SIZE),
+                                (base_x + sector_size, base_z),
+                                (base_x + sector_size, base_z + sector_size),
+                                (base_x, base_z + sector_size),
                             ];
 
                             for (i, &(cx, cz)) in corner_positions.iter().enumerate() {
@@ -2067,10 +1953,10 @@ pub fn draw_viewport_3d(
 
                         // Draw wall outlines
                         let wall_sets = [
-                            (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z),
-                            (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                            (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE),
-                            (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z),
+                            (&sector.walls_north, base_x, base_z, base_x + sector_size, base_z),
+                            (&sector.walls_east, base_x + sector_size, base_z, base_x + sector_size, base_z + sector_size),
+                            (&sector.walls_south, base_x + sector_size, base_z + sector_size, base_x, base_z + sector_size),
+                            (&sector.walls_west, base_x, base_z + sector_size, base_x, base_z),
                         ];
 
                         for (walls, x0, z0, x1, z1) in wall_sets {
@@ -2091,31 +1977,31 @@ pub fn draw_viewport_3d(
             Selection::Edge { room, x, z, face_idx, edge_idx, wall_face } => {
                 if let Some(room_data) = state.level.rooms.get(*room) {
                     if let Some(sector) = room_data.get_sector(*x, *z) {
-                        let base_x = room_data.position.x + (*x as f32) * SECTOR_SIZE;
-                        let base_z = room_data.position.z + (*z as f32) * SECTOR_SIZE;
+                        let base_x = room_data.position.x + (*x as f32) * sector_size;
+                        let base_z = room_data.position.z + (*z as f32) * sector_size;
 
                         let corners: Option<[Vec3; 4]> = if *face_idx == 0 {
                             sector.floor.as_ref().map(|f| [
                                 Vec3::new(base_x, f.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, f.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, f.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, f.heights[3], base_z + SECTOR_SIZE),
+                                Vec3::new(base_x + sector_size, f.heights[1], base_z),
+                                Vec3::new(base_x + sector_size, f.heights[2], base_z + sector_size),
+                                Vec3::new(base_x, f.heights[3], base_z + sector_size),
                             ])
                         } else if *face_idx == 1 {
                             sector.ceiling.as_ref().map(|c| [
                                 Vec3::new(base_x, c.heights[0], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, c.heights[1], base_z),
-                                Vec3::new(base_x + SECTOR_SIZE, c.heights[2], base_z + SECTOR_SIZE),
-                                Vec3::new(base_x, c.heights[3], base_z + SECTOR_SIZE),
+                                Vec3::new(base_x + sector_size, c.heights[1], base_z),
+                                Vec3::new(base_x + sector_size, c.heights[2], base_z + sector_size),
+                                Vec3::new(base_x, c.heights[3], base_z + sector_size),
                             ])
                         } else if *face_idx == 2 {
                             // Wall edge
                             if let Some(wf) = wall_face {
                                 let (x0, z0, x1, z1) = match wf {
-                                    SectorFace::WallNorth(_) => (base_x, base_z, base_x + SECTOR_SIZE, base_z),
-                                    SectorFace::WallEast(_) => (base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                                    SectorFace::WallSouth(_) => (base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE),
-                                    SectorFace::WallWest(_) => (base_x, base_z + SECTOR_SIZE, base_x, base_z),
+                                    SectorFace::WallNorth(_) => (base_x, base_z, base_x + sector_size, base_z),
+                                    SectorFace::WallEast(_) => (base_x + sector_size, base_z, base_x + sector_size, base_z + sector_size),
+                                    SectorFace::WallSouth(_) => (base_x + sector_size, base_z + sector_size, base_x, base_z + sector_size),
+                                    SectorFace::WallWest(_) => (base_x, base_z + sector_size, base_x, base_z),
                                     _ => (0.0, 0.0, 0.0, 0.0),
                                 };
                                 let wall_heights = match wf {
@@ -2167,23 +2053,23 @@ pub fn draw_viewport_3d(
 
         let corners = [
             Vec3::new(snapped_x, target_y, snapped_z),
-            Vec3::new(snapped_x, target_y, snapped_z + SECTOR_SIZE),
-            Vec3::new(snapped_x + SECTOR_SIZE, target_y, snapped_z + SECTOR_SIZE),
-            Vec3::new(snapped_x + SECTOR_SIZE, target_y, snapped_z),
+            Vec3::new(snapped_x, target_y, snapped_z + sector_size),
+            Vec3::new(snapped_x + sector_size, target_y, snapped_z + sector_size),
+            Vec3::new(snapped_x + sector_size, target_y, snapped_z),
         ];
 
         let floor_corners = [
             Vec3::new(snapped_x, floor_y, snapped_z),
-            Vec3::new(snapped_x, floor_y, snapped_z + SECTOR_SIZE),
-            Vec3::new(snapped_x + SECTOR_SIZE, floor_y, snapped_z + SECTOR_SIZE),
-            Vec3::new(snapped_x + SECTOR_SIZE, floor_y, snapped_z),
+            Vec3::new(snapped_x, floor_y, snapped_z + sector_size),
+            Vec3::new(snapped_x + sector_size, floor_y, snapped_z + sector_size),
+            Vec3::new(snapped_x + sector_size, floor_y, snapped_z),
         ];
 
         let ceiling_corners = [
             Vec3::new(snapped_x, ceiling_y, snapped_z),
-            Vec3::new(snapped_x, ceiling_y, snapped_z + SECTOR_SIZE),
-            Vec3::new(snapped_x + SECTOR_SIZE, ceiling_y, snapped_z + SECTOR_SIZE),
-            Vec3::new(snapped_x + SECTOR_SIZE, ceiling_y, snapped_z),
+            Vec3::new(snapped_x, ceiling_y, snapped_z + sector_size),
+            Vec3::new(snapped_x + sector_size, ceiling_y, snapped_z + sector_size),
+            Vec3::new(snapped_x + sector_size, ceiling_y, snapped_z),
         ];
 
         let mut screen_corners = Vec::new();
@@ -2264,10 +2150,131 @@ pub fn draw_viewport_3d(
         }
     }
 
-    // Convert framebuffer to texture and draw to viewport
-    let texture = Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &fb.pixels);
+    // Draw camera path splines (hidden while previewing or simulating)
+    if state.camera_preview.is_none() && !simulating {
+        for (i, path) in state.level.camera_paths.iter().enumerate() {
+            let color = if i == state.active_camera_path {
+                RasterColor::new(240, 200, 80)
+            } else {
+                RasterColor::new(140, 120, 70)
+            };
+            let steps = (path.duration() * 8.0).ceil().max(1.0) as usize;
+            let mut prev = path.sample(0.0).map(|(p, _)| p);
+            for step in 1..=steps {
+                let t = step as f32 / steps as f32 * path.duration();
+                let next = path.sample(t).map(|(p, _)| p);
+                if let (Some(a), Some(b)) = (prev, next) {
+                    fb.draw_line_3d(a, b, &state.camera_3d, color);
+                }
+                prev = next;
+            }
+            // Key markers with a short look direction
+            for key in &path.keys {
+                let dir = (key.target - key.position).normalize();
+                fb.draw_line_3d(key.position, key.position + dir * 256.0, &state.camera_3d, RasterColor::new(80, 200, 240));
+                if let Some((sx, sy)) = world_to_screen(key.position, state.camera_3d.position, state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height) {
+                    fb.draw_circle(sx as i32, sy as i32, 3, color);
+                }
+            }
+        }
+    }
+
+    // Entity markers, moved by running door/platform logic (and hidden while
+    // a simulated wave hasn't spawned)
+    let logic = match &state.simulation {
+        Some((sim, _)) => Some(&sim.logic),
+        None => state.logic_preview.as_ref(),
+    };
+    let entity_refs: Vec<EntityRef> = state.level.rooms.iter().enumerate()
+        .flat_map(|(room, r)| (0..r.entities.len()).map(move |i| EntityRef::new(room, i)))
+        .collect();
+    for r in entity_refs {
+        let (Some(entity), Some(base)) = (state.level.entity(r), state.level.entity_world_pos(r)) else {
+            continue;
+        };
+        if state.simulation.as_ref().is_some_and(|(sim, _)| !sim.events.is_spawned(entity)) {
+            continue;
+        }
+        if entity.is_note() {
+            draw_note_billboard(fb, state, entity, base, state.selected_entity == Some(r));
+            continue;
+        }
+        let (cr, cg, cb) = state.entity_schema.archetype(&entity.archetype).map_or((200, 200, 200), |a| a.color);
+        let color = if state.selected_entity == Some(r) { RasterColor::new(255, 255, 255) } else { RasterColor::new(cr, cg, cb) };
+        let (half, height) = match entity.archetype.as_str() {
+            DOOR_ARCHETYPE => (512.0, 1024.0),
+            PLATFORM_ARCHETYPE => (512.0, 128.0),
+            _ => (128.0, 512.0),
+        };
+        let walked = state.simulation.as_ref().and_then(|(sim, _)| sim.enemy_position(r));
+        let pos = walked.unwrap_or_else(|| base + logic.map_or(Vec3::ZERO, |l| l.offset(r)));
+        // Blob shadow under characters and objects (doors and platforms are level geometry)
+        if !matches!(entity.archetype.as_str(), DOOR_ARCHETYPE | PLATFORM_ARCHETYPE) {
+            if let Some(shadow) = blob_shadow(&state.level, pos, half * 1.5) {
+                draw_blob_shadow(fb, &state.camera_3d, &shadow);
+            }
+        }
+        let corner = |i: usize| pos + Vec3::new(
+            if i & 1 == 0 { -half } else { half },
+            if i & 4 == 0 { 0.0 } else { height },
+            if i & 2 == 0 { -half } else { half },
+        );
+        // Corners differing in exactly one bit share an edge
+        for a in 0..8 {
+            for bit in [1, 2, 4] {
+                if a & bit == 0 {
+                    fb.draw_line_3d(corner(a), corner(a | bit), &state.camera_3d, color);
+                }
+            }
+        }
+        // Boss arena boundary on the boss's floor
+        if entity.archetype == BOSS_ARCHETYPE {
+            let radius = entity.get("arena").and_then(|v| v.as_float()).unwrap_or(4096.0);
+            let point = |i: usize| {
+                let angle = i as f32 / 32.0 * std::f32::consts::TAU;
+                base + Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius)
+            };
+            for i in 0..32 {
+                fb.draw_line_3d(point(i), point(i + 1), &state.camera_3d, color);
+            }
+        }
+    }
+
+    // Camera info (position and rotation), drawn into the framebuffer at native resolution
+    // (left out at reduced resolution, where the glyphs would double in size)
+    if !reduced {
+        let cam_info = format!(
+            "Cam: ({:.0}, {:.0}, {:.0}) | Rot: ({:.2}, {:.2})",
+            state.camera_3d.position.x,
+            state.camera_3d.position.y,
+            state.camera_3d.position.z,
+            state.camera_3d.rotation_x,
+            state.camera_3d.rotation_y
+        );
+        fb.draw_text(&cam_info, 3, fb.height as i32 - GLYPH_HEIGHT as i32 - 2, &TextPalette::SYSTEM);
+    }
+    if simulating {
+        fb.draw_text("SIMULATING  [Esc] stop", 3, 3, &TextPalette::SYSTEM);
+    }
+
+    // Convert framebuffer to texture (through the CRT pass if enabled, then
+    // the level's color grade) and draw to viewport
+    let grade = &state.level.ambience;
+    let texture = if state.raster_settings.crt.enabled {
+        let (mut pixels, w, h) = apply_crt(fb, &state.raster_settings.crt);
+        apply_color_grade(&mut pixels, grade);
+        Texture2D::from_rgba8(w as u16, h as u16, &pixels)
+    } else if !grade.is_identity() {
+        let mut pixels = fb.pixels.clone();
+        apply_color_grade(&mut pixels, grade);
+        Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &pixels)
+    } else {
+        Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &fb.pixels)
+    };
     texture.set_filter(FilterMode::Nearest);
 
+    // The framebuffer may have been resized since input was mapped onto it
+    let (draw_w, draw_h, draw_x, draw_y) = fit_in_rect(rect, fb.width, fb.height);
     draw_texture_ex(
         &texture,
         draw_x,
@@ -2281,22 +2288,32 @@ pub fn draw_viewport_3d(
 
     // Draw viewport border
     draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, Color::from_rgba(60, 60, 60, 255));
+}
 
-    // Draw camera info (position and rotation)
-    draw_text(
-        &format!(
-            "Cam: ({:.0}, {:.0}, {:.0}) | Rot: ({:.2}, {:.2})",
-            state.camera_3d.position.x,
-            state.camera_3d.position.y,
-            state.camera_3d.position.z,
-            state.camera_3d.rotation_x,
-            state.camera_3d.rotation_y
-        ),
-        rect.x + 5.0,
-        rect.bottom() - 5.0,
-        14.0,
-        Color::from_rgba(200, 200, 200, 255),
-    );
+/// Size and position (w, h, x, y) of a `width` x `height` framebuffer drawn
+/// as large as fits in `rect`, keeping its aspect ratio, centered
+pub(super) fn fit_in_rect(rect: Rect, width: usize, height: usize) -> (f32, f32, f32, f32) {
+    let fb_aspect = width as f32 / height as f32;
+    let rect_aspect = rect.w / rect.h;
+    if fb_aspect > rect_aspect {
+        let w = rect.w;
+        let h = rect.w / fb_aspect;
+        (w, h, rect.x, rect.y + (rect.h - h) * 0.5)
+    } else {
+        let h = rect.h;
+        let w = rect.h * fb_aspect;
+        (w, h, rect.x + (rect.w - w) * 0.5, rect.y)
+    }
+}
+
+/// End camera path preview and restore the editor camera
+fn stop_camera_preview(state: &mut EditorState) {
+    if let Some((_, (pos, rx, ry))) = state.camera_preview.take() {
+        state.camera_3d.position = pos;
+        state.camera_3d.rotation_x = rx;
+        state.camera_3d.rotation_y = ry;
+        state.camera_3d.update_basis();
+    }
 }
 
 /// Draw a 3D line into the framebuffer using Bresenham's algorithm
@@ -2307,73 +2324,73 @@ fn draw_3d_line(
     camera: &crate::rasterizer::Camera,
     color: RasterColor,
 ) {
-    const NEAR_PLANE: f32 = 0.1;
-
-    // Transform to camera space
-    let rel0 = p0 - camera.position;
-    let rel1 = p1 - camera.position;
+    fb.draw_line_3d(p0, p1, camera, color);
+}
 
-    let z0 = rel0.dot(camera.basis_z);
-    let z1 = rel1.dot(camera.basis_z);
+/// Designer note: a pin with its text on a dark tag, facing the camera
+fn draw_note_billboard(fb: &mut Framebuffer, state: &EditorState, note: &crate::world::Entity, base: Vec3, selected: bool) {
+    let (r, g, b) = note.note_color();
+    let color = if selected { RasterColor::new(255, 255, 255) } else { RasterColor::new(r, g, b) };
+    let top = base + Vec3::new(0.0, 384.0, 0.0);
+    fb.draw_line_3d(base, top, &state.camera_3d, color);
+    let Some((sx, sy)) = world_to_screen(top, state.camera_3d.position, state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height) else { return };
+    let text: String = note.note_text().chars().take(32).collect();
+    let (w, h) = text_size(&text);
+    let (x0, y0) = (sx as i32 - w as i32 / 2 - 2, sy as i32 - h as i32 - 3);
+    fb.draw_filled_rect(x0, y0, x0 + w as i32 + 3, y0 + h as i32 + 1, RasterColor::new(r / 4, g / 4, b / 4));
+    fb.draw_rect(x0, y0, x0 + w as i32 + 3, y0 + h as i32 + 1, color);
+    fb.draw_text_centered(&text, sx as i32, y0 + 1, &TextPalette::solid(color));
+}
 
-    // Both behind camera - skip entirely
-    if z0 <= NEAR_PLANE && z1 <= NEAR_PLANE {
-        return;
+/// Debug overlays: face normals, backfacing faces outlined in red, N/E/S/W
+/// labels on the current room's walls and the simulation's hitboxes
+fn draw_debug_overlays(fb: &mut Framebuffer, state: &mut EditorState, frustum: &Frustum) {
+    let overlays = state.debug_overlays;
+    if let Some((sim, _)) = state.simulation.as_mut() {
+        sim.combat.show_hitboxes = overlays.hitboxes;
+        sim.combat.draw_debug(fb, &state.camera_3d);
     }
-
-    // Clip line to near plane if needed
-    let (clipped_p0, clipped_p1) = if z0 <= NEAR_PLANE {
-        let t = (NEAR_PLANE - z0) / (z1 - z0);
-        let new_p0 = p0 + (p1 - p0) * t;
-        (new_p0, p1)
-    } else if z1 <= NEAR_PLANE {
-        let t = (NEAR_PLANE - z0) / (z1 - z0);
-        let new_p1 = p0 + (p1 - p0) * t;
-        (p0, new_p1)
-    } else {
-        (p0, p1)
-    };
-
-    // Project clipped endpoints
-    let s0 = world_to_screen(clipped_p0, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
-    let s1 = world_to_screen(clipped_p1, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
-
-    let (Some((x0f, y0f)), Some((x1f, y1f))) = (s0, s1) else {
-        return;
-    };
-
-    // Convert to integers for Bresenham
-    let mut x0 = x0f as i32;
-    let mut y0 = y0f as i32;
-    let x1 = x1f as i32;
-    let y1 = y1f as i32;
-
-    let dx = (x1 - x0).abs();
-    let dy = -(y1 - y0).abs();
-    let sx = if x0 < x1 { 1 } else { -1 };
-    let sy = if y0 < y1 { 1 } else { -1 };
-    let mut err = dx + dy;
-
-    let w = fb.width as i32;
-    let h = fb.height as i32;
-
-    loop {
-        if x0 >= 0 && x0 < w && y0 >= 0 && y0 < h {
-            fb.set_pixel(x0 as usize, y0 as usize, color);
-        }
-
-        if x0 == x1 && y0 == y1 {
-            break;
+    let camera = &state.camera_3d;
+    let level = &state.level;
+    let normal_length = level.sector_size * 0.25;
+
+    if overlays.normals || overlays.backfaces {
+        for (room_idx, room) in level.rooms.iter().enumerate() {
+            if !frustum.contains_room(room) {
+                continue;
+            }
+            let (vertices, faces) = state.mesh_cache.get(room_idx, room);
+            for face in faces {
+                let [a, b, c] = [face.v0, face.v1, face.v2].map(|i| vertices[i]);
+                let center = (a.pos + b.pos + c.pos).scale(1.0 / 3.0);
+                // Same orientation test as the rasterizer: averaged vertex normals
+                let normal = (a.normal + b.normal + c.normal).normalize();
+                let backfacing = normal.dot(center - camera.position) > 0.0;
+                if backfacing && overlays.backfaces {
+                    let red = RasterColor::new(230, 40, 40);
+                    fb.draw_line_3d(a.pos, b.pos, camera, red);
+                    fb.draw_line_3d(b.pos, c.pos, camera, red);
+                    fb.draw_line_3d(c.pos, a.pos, camera, red);
+                }
+                if !backfacing && overlays.normals {
+                    fb.draw_line_3d(center, center + normal.scale(normal_length), camera, RasterColor::new(80, 220, 120));
+                }
+            }
         }
+    }
 
-        let e2 = 2 * err;
-        if e2 >= dy {
-            err += dy;
-            x0 += sx;
-        }
-        if e2 <= dx {
-            err += dx;
-            y0 += sy;
+    if overlays.wall_labels {
+        let Some(room) = level.rooms.get(state.current_room) else { return };
+        for (gx, gz, sector) in room.iter_sectors() {
+            for (dir, letter) in [(Direction::North, "N"), (Direction::East, "E"), (Direction::South, "S"), (Direction::West, "W")] {
+                for i in 0..sector.walls(dir).len() {
+                    let Some(corners) = face_corners(room, gx, gz, SectorFace::wall(dir, i)) else { continue };
+                    let center = (corners[0] + corners[1] + corners[2] + corners[3]).scale(0.25);
+                    if let Some((sx, sy)) = world_to_screen(center, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height) {
+                        fb.draw_text_centered(letter, sx as i32, sy as i32 - GLYPH_HEIGHT as i32 / 2, &TextPalette::GOLD);
+                    }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,248 @@
+//! Detached 3D viewport (native only)
+//!
+//! macroquad opens a single window per process, so detaching the 3D viewport
+//! starts the engine again as a child process (`--viewport`) that opens a
+//! second OS window and draws only the 3D view. Put it on another monitor to
+//! keep both it and the 2D grid maximized.
+//! - the editor sends the level down the child's stdin as newline-delimited
+//!   RON, from a writer thread, whenever it changed (checked a few times a
+//!   second), so a slow window never stalls the editor
+//! - the window flies its own camera (right-drag look, WASD/QE, wheel) and
+//!   doesn't edit: editing stays in the editor window
+//! - closing the window docks the viewport back; closing the editor closes
+//!   the window (its stdin ends)
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError};
+use macroquad::prelude::*;
+use crate::rasterizer::{render_mesh, Camera, Color as RasterColor, Framebuffer, RasterSettings, Texture as RasterTexture, HEIGHT, WIDTH};
+use crate::ui::Rect;
+use crate::world::{load_level_from_str, overview_camera, Frustum, Level};
+use super::mesh_cache::RoomMeshCache;
+use super::viewport_3d::fit_in_rect;
+use super::TexturePack;
+
+/// Command-line flag that opens the detached viewport window
+pub const VIEWPORT_FLAG: &str = "--viewport";
+
+/// Seconds between checks for level changes to send
+const SYNC_INTERVAL: f32 = 0.2;
+
+/// Camera speed in the window, in world units per frame (as in the editor)
+const MOVE_SPEED: f32 = 100.0;
+
+/// The editor's end of a detached viewport window
+pub struct ViewportWindow {
+    child: Child,
+    /// Levels for the writer thread; holds one, so while the window is busy
+    /// newer levels are skipped rather than queued
+    levels: SyncSender<Level>,
+    since_sync: f32,
+}
+
+impl ViewportWindow {
+    /// Open the window on `level`
+    pub fn open(level: &Level) -> Result<Self, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut child = Command::new(exe)
+            .arg(VIEWPORT_FLAG)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Can't open the viewport window: {}", e))?;
+        let stdin = child.stdin.take().ok_or("The viewport window has no input")?;
+        let (levels, queued) = sync_channel(1);
+        std::thread::spawn(move || write_levels(stdin, queued));
+        let _ = levels.try_send(level.clone());
+        Ok(Self { child, levels, since_sync: 0.0 })
+    }
+
+    /// Whether the window is still open (the user may have closed it)
+    pub fn is_open(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Send `level` if it may have changed; call once per frame
+    pub fn sync(&mut self, level: &Level, dt: f32) {
+        self.since_sync += dt;
+        if self.since_sync < SYNC_INTERVAL {
+            return;
+        }
+        self.since_sync = 0.0;
+        // The writer thread drops levels identical to the last one it sent
+        let _ = self.levels.try_send(level.clone());
+    }
+}
+
+impl Drop for ViewportWindow {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Write each level that differs from the last one written, until the
+/// editor closes the window or the window goes away
+fn write_levels(mut out: ChildStdin, levels: Receiver<Level>) {
+    let mut last = String::new();
+    for level in levels {
+        let line = match ron::ser::to_string(&level) {
+            Ok(line) => line,
+            Err(e) => {
+                log::warn!("Can't send the level to the viewport window: {}", e);
+                continue;
+            }
+        };
+        if line == last {
+            continue;
+        }
+        if writeln!(out, "{}", line).and_then(|()| out.flush()).is_err() {
+            break;
+        }
+        last = line;
+    }
+}
+
+/// Read levels from the editor until stdin ends; None marks the end
+fn read_levels(input: impl BufRead + Send + 'static) -> Receiver<Option<Level>> {
+    let (sender, levels) = sync_channel(4);
+    std::thread::spawn(move || {
+        for line in input.lines() {
+            let Ok(line) = line else { break };
+            match load_level_from_str(&line) {
+                Ok(level) => {
+                    if sender.send(Some(level)).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => log::warn!("Dropped a level from the editor: {}", e),
+            }
+        }
+        let _ = sender.send(None);
+    });
+    levels
+}
+
+/// Window settings for the viewport window
+pub fn viewport_window_conf() -> Conf {
+    Conf {
+        window_title: format!("Bonnie Engine v{} - 3D Viewport", crate::VERSION),
+        window_width: WIDTH as i32 * 3,
+        window_height: HEIGHT as i32 * 3,
+        window_resizable: true,
+        high_dpi: true,
+        ..Default::default()
+    }
+}
+
+/// The child process side: draw the level the editor sends until it closes
+pub async fn run_viewport_window() {
+    // The same packs the editor found, so texture lookups match
+    let packs = TexturePack::discover_all();
+    let textures: Vec<RasterTexture> = packs.iter().flat_map(|p| &p.textures).cloned().collect();
+    let levels = read_levels(BufReader::new(std::io::stdin()));
+    let settings = RasterSettings::default();
+    let mut fb = Framebuffer::new(WIDTH, HEIGHT);
+    let mut cache = RoomMeshCache::new();
+    let mut level: Option<Level> = None;
+    let mut camera = Camera::new();
+    let mut last_mouse = mouse_position();
+
+    loop {
+        // Only the newest level matters
+        let mut ended = false;
+        loop {
+            match levels.try_recv() {
+                Ok(Some(next)) => {
+                    if level.is_none() {
+                        camera = overview_camera(&next);
+                    }
+                    level = Some(next);
+                    cache.invalidate_all();
+                }
+                Ok(None) | Err(TryRecvError::Disconnected) => {
+                    ended = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+        if ended {
+            break;
+        }
+
+        let mouse = mouse_position();
+        if is_mouse_button_down(MouseButton::Right) {
+            // Same mapping as the editor viewport (Y-down)
+            camera.rotate((mouse.1 - last_mouse.1) * 0.005, -(mouse.0 - last_mouse.0) * 0.005);
+        }
+        last_mouse = mouse;
+        move_camera(&mut camera);
+
+        clear_background(Color::from_rgba(25, 25, 30, 255));
+        fb.clear(RasterColor::new(30, 30, 40));
+        if let Some(level) = &level {
+            cache.sync_textures(&packs);
+            cache.truncate(level.rooms.len());
+            let frustum = Frustum::new(&camera, fb.width, fb.height);
+            for (room_idx, room) in level.rooms.iter().enumerate() {
+                if frustum.contains_room(room) {
+                    let (vertices, faces) = cache.get(room_idx, room);
+                    render_mesh(&mut fb, vertices, faces, &textures, &camera, &settings);
+                }
+            }
+        }
+
+        let texture = Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &fb.pixels);
+        texture.set_filter(FilterMode::Nearest);
+        let (w, h, x, y) = fit_in_rect(Rect::new(0.0, 0.0, screen_width(), screen_height()), fb.width, fb.height);
+        draw_texture_ex(&texture, x, y, WHITE, DrawTextureParams { dest_size: Some(Vec2::new(w, h)), ..Default::default() });
+
+        next_frame().await;
+    }
+}
+
+/// WASD + Q/E fly the camera, the wheel moves it forward and back
+fn move_camera(camera: &mut Camera) {
+    if is_key_down(KeyCode::W) {
+        camera.position = camera.position + camera.basis_z * MOVE_SPEED;
+    }
+    if is_key_down(KeyCode::S) {
+        camera.position = camera.position - camera.basis_z * MOVE_SPEED;
+    }
+    if is_key_down(KeyCode::A) {
+        camera.position = camera.position - camera.basis_x * MOVE_SPEED;
+    }
+    if is_key_down(KeyCode::D) {
+        camera.position = camera.position + camera.basis_x * MOVE_SPEED;
+    }
+    if is_key_down(KeyCode::Q) {
+        camera.position = camera.position - camera.basis_y * MOVE_SPEED;
+    }
+    if is_key_down(KeyCode::E) {
+        camera.position = camera.position + camera.basis_y * MOVE_SPEED;
+    }
+    let scroll = mouse_wheel().1;
+    if scroll != 0.0 {
+        camera.position = camera.position + camera.basis_z * (MOVE_SPEED * 2.0 * scroll.signum());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_test_level;
+
+    #[test]
+    fn test_levels_round_trip_as_lines() {
+        let level = create_test_level();
+        let mut input = ron::ser::to_string(&level).unwrap();
+        input.push('\n');
+        input.push_str("not a level\n");
+        let levels = read_levels(std::io::Cursor::new(input.into_bytes()));
+        let received = levels.recv().unwrap().expect("level arrives");
+        assert_eq!(received.rooms.len(), level.rooms.len());
+        // The bad line is skipped, then the end of input is reported
+        assert!(levels.recv().unwrap().is_none());
+    }
+}
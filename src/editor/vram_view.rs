@@ -0,0 +1,234 @@
+//! VRAM view - the level's textures packed into a simulated PS1 VRAM
+//!
+//! Shows the 1024x512 VRAM grid with the framebuffers (double buffered when
+//! two fit), every texture the level uses at the smallest depth its colors
+//! allow, and their CLUTs (see `rasterizer::vram`). Hovering a block names
+//! it; textures that don't fit are listed as over budget. Opened from the
+//! toolbar memory button, laid out once when it opens.
+
+use std::collections::HashSet;
+use macroquad::prelude::*;
+use crate::rasterizer::{
+    pack_vram, Texture as RasterTexture, TexelDepth, VramBlock, VramContent, VramLayout, PAGE_HEIGHT, PAGE_WIDTH,
+    VRAM_HEIGHT, VRAM_WIDTH,
+};
+use crate::ui::{Rect, UiContext, draw_text_button, ui_screen_width, ui_screen_height};
+use crate::world::{Direction, Level};
+use super::{format_bytes, EditorState, TexturePack};
+
+const FREE_COLOR: Color = Color::new(0.09, 0.09, 0.11, 1.0);
+const FRAMEBUFFER_COLOR: Color = Color::new(0.2, 0.3, 0.55, 1.0);
+const CLUT_COLOR: Color = Color::new(0.75, 0.3, 0.7, 1.0);
+
+fn depth_color(depth: TexelDepth) -> Color {
+    match depth {
+        TexelDepth::Bit4 => Color::from_rgba(90, 180, 100, 255),
+        TexelDepth::Bit8 => Color::from_rgba(220, 180, 60, 255),
+        TexelDepth::Bit15 => Color::from_rgba(230, 120, 60, 255),
+    }
+}
+
+/// The level's textures in VRAM, as laid out when the view opened
+pub struct VramView {
+    /// "pack/name" of each packed texture
+    names: Vec<String>,
+    /// Texture sizes in texels
+    sizes: Vec<(usize, usize)>,
+    layout: VramLayout,
+    /// Bytes the textures that didn't fit would need
+    overflow_bytes: usize,
+    image: Texture2D,
+}
+
+/// Textures the level's faces use, in the order first used
+fn level_textures<'a>(level: &Level, packs: &'a [TexturePack]) -> Vec<(String, &'a RasterTexture)> {
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for room in &level.rooms {
+        for (_, _, sector) in room.iter_sectors() {
+            let walls = [Direction::North, Direction::East, Direction::South, Direction::West]
+                .into_iter()
+                .flat_map(|d| sector.walls(d).iter().map(|w| &w.texture));
+            for tex in sector.floor.iter().chain(&sector.ceiling).map(|f| &f.texture).chain(walls) {
+                if !tex.is_valid() || !seen.insert((tex.pack.as_str(), tex.name.as_str())) {
+                    continue;
+                }
+                let texture = packs
+                    .iter()
+                    .filter(|p| p.name == tex.pack)
+                    .find_map(|p| p.textures.iter().find(|t| t.name == tex.name));
+                if let Some(texture) = texture {
+                    found.push((format!("{}/{}", tex.pack, tex.name), texture));
+                }
+            }
+        }
+    }
+    found
+}
+
+/// VRAM as an RGBA image: textures squeezed to their width in words, as a
+/// VRAM viewer on hardware shows them
+fn vram_image(layout: &VramLayout, textures: &[&RasterTexture]) -> Texture2D {
+    let mut pixels = vec![0u8; VRAM_WIDTH * VRAM_HEIGHT * 4];
+    let mut put = |x: usize, y: usize, c: [u8; 4]| {
+        let i = (y * VRAM_WIDTH + x) * 4;
+        pixels[i..i + 4].copy_from_slice(&c);
+    };
+    for y in 0..VRAM_HEIGHT {
+        for x in 0..VRAM_WIDTH {
+            put(x, y, FREE_COLOR.into());
+        }
+    }
+    for block in &layout.blocks {
+        for y in 0..block.height {
+            for x in 0..block.width {
+                let color = match block.content {
+                    VramContent::Framebuffer(_) => FRAMEBUFFER_COLOR.into(),
+                    VramContent::Clut(_) => CLUT_COLOR.into(),
+                    VramContent::Texture(i) => {
+                        let tex = textures[i];
+                        let tx = (x * tex.width / block.width).min(tex.width - 1);
+                        let c = tex.pixels[y * tex.width + tx];
+                        [c.r, c.g, c.b, 255]
+                    }
+                };
+                put(block.x + x, block.y + y, color);
+            }
+        }
+    }
+    let image = Texture2D::from_rgba8(VRAM_WIDTH as u16, VRAM_HEIGHT as u16, &pixels);
+    image.set_filter(FilterMode::Nearest);
+    image
+}
+
+impl VramView {
+    pub fn new(state: &EditorState) -> Self {
+        let used = level_textures(&state.level, &state.texture_packs);
+        let textures: Vec<&RasterTexture> = used.iter().map(|(_, t)| *t).collect();
+        let (fb_w, fb_h) = state.raster_settings.resolution();
+        let buffers = if fb_h * 2 <= VRAM_HEIGHT { 2 } else { 1 };
+        let layout = pack_vram(&textures, fb_w, fb_h, buffers);
+        Self {
+            sizes: textures.iter().map(|t| (t.width, t.height)).collect(),
+            overflow_bytes: layout.overflow_bytes(&textures),
+            image: vram_image(&layout, &textures),
+            names: used.into_iter().map(|(name, _)| name).collect(),
+            layout,
+        }
+    }
+
+    fn describe(&self, block: &VramBlock) -> String {
+        let place = format!("at {},{} - {}", block.x, block.y, format_bytes(block.bytes()));
+        match block.content {
+            VramContent::Framebuffer(n) => format!("Framebuffer {} ({}x{}) {}", n + 1, block.width, block.height, place),
+            VramContent::Clut(i) => format!("CLUT of {} ({} colors) {}", self.names[i], block.width, place),
+            VramContent::Texture(i) => {
+                let (w, h) = self.sizes[i];
+                let page = block.x / PAGE_WIDTH + block.y / PAGE_HEIGHT * (VRAM_WIDTH / PAGE_WIDTH);
+                format!("{} {}x{} {}, page {} {}", self.names[i], w, h, self.layout.depths[i].label(), page, place)
+            }
+        }
+    }
+}
+
+/// Draw the VRAM view. Returns true when it should close.
+pub fn draw_vram_view(ctx: &mut UiContext, view: &VramView) -> bool {
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let scale = ((ui_screen_width() - 80.0) / VRAM_WIDTH as f32).clamp(0.25, 0.75);
+    let (map_w, map_h) = (VRAM_WIDTH as f32 * scale, VRAM_HEIGHT as f32 * scale);
+    let dialog_w = map_w + 32.0;
+    let header_h = 36.0;
+    let footer_h = 44.0;
+    let info_h = 66.0;
+    let dialog_h = header_h + 16.0 + map_h + info_h + footer_h;
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("VRAM (1 MB)", x + 16.0, y + 24.0, 18.0, WHITE);
+
+    // The grid, with texture page borders and each block outlined
+    let map = Rect::new(x + 16.0, y + header_h + 16.0, map_w, map_h);
+    draw_texture_ex(&view.image, map.x, map.y, WHITE, DrawTextureParams {
+        dest_size: Some(Vec2::new(map.w, map.h)),
+        ..Default::default()
+    });
+    let page_line = Color::from_rgba(255, 255, 255, 40);
+    for px in 1..VRAM_WIDTH / PAGE_WIDTH {
+        let lx = map.x + (px * PAGE_WIDTH) as f32 * scale;
+        draw_line(lx, map.y, lx, map.bottom(), 1.0, page_line);
+    }
+    let mid = map.y + PAGE_HEIGHT as f32 * scale;
+    draw_line(map.x, mid, map.right(), mid, 1.0, page_line);
+    let block_rect = |b: &VramBlock| {
+        Rect::new(map.x + b.x as f32 * scale, map.y + b.y as f32 * scale, (b.width as f32 * scale).max(1.0), (b.height as f32 * scale).max(1.0))
+    };
+    for block in &view.layout.blocks {
+        let r = block_rect(block);
+        let color = match block.content {
+            VramContent::Framebuffer(_) => FRAMEBUFFER_COLOR,
+            VramContent::Clut(_) => CLUT_COLOR,
+            VramContent::Texture(i) => depth_color(view.layout.depths[i]),
+        };
+        draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, color);
+    }
+
+    // Hovered block
+    let dim = Color::from_rgba(150, 150, 150, 255);
+    let mut info_y = map.bottom() + 20.0;
+    let hovered = ctx.mouse.inside(&map).then(|| {
+        let vx = ((ctx.mouse.x - map.x) / scale) as usize;
+        let vy = ((ctx.mouse.y - map.y) / scale) as usize;
+        view.layout.block_at(vx, vy)
+    });
+    match hovered.flatten() {
+        Some(block) => {
+            let r = block_rect(block);
+            draw_rectangle_lines(r.x - 1.0, r.y - 1.0, r.w + 2.0, r.h + 2.0, 2.0, WHITE);
+            draw_text(&view.describe(block), x + 16.0, info_y, 14.0, WHITE);
+        }
+        None => {
+            draw_text("Hover a block to see what it holds", x + 16.0, info_y, 14.0, dim);
+        }
+    }
+
+    // Totals, and the textures that didn't fit
+    info_y += 20.0;
+    let count = |depth| view.layout.depths.iter().filter(|&&d| d == depth).count();
+    let totals = format!(
+        "{} of {} used - {} of 32 texture pages - {} textures ({} 4-bit, {} 8-bit, {} 15-bit)",
+        format_bytes(view.layout.used_bytes()),
+        format_bytes(VRAM_WIDTH * VRAM_HEIGHT * 2),
+        view.layout.pages_used(),
+        view.names.len(),
+        count(TexelDepth::Bit4),
+        count(TexelDepth::Bit8),
+        count(TexelDepth::Bit15),
+    );
+    draw_text(&totals, x + 16.0, info_y, 14.0, dim);
+    info_y += 20.0;
+    if view.layout.overflow.is_empty() {
+        draw_text("Everything fits", x + 16.0, info_y, 14.0, depth_color(TexelDepth::Bit4));
+    } else {
+        let names: Vec<&str> = view.layout.overflow.iter().map(|&i| view.names[i].as_str()).collect();
+        let warning = format!(
+            "Over budget by {}: {} don't fit ({})",
+            format_bytes(view.overflow_bytes),
+            names.len(),
+            names.join(", ")
+        );
+        draw_text(&warning, x + 16.0, info_y, 14.0, Color::from_rgba(220, 80, 70, 255));
+    }
+
+    // Footer
+    let close_rect = Rect::new(x + dialog_w - 90.0, y + dialog_h - footer_h + 8.0, 80.0, 28.0);
+    draw_text_button(ctx, close_rect, "Close", Color::from_rgba(60, 60, 70, 255))
+        || is_key_pressed(KeyCode::Escape)
+        || is_key_pressed(KeyCode::Enter)
+}
@@ -0,0 +1,253 @@
+//! World map view
+//!
+//! Project-level graph of the levels in `assets/levels`: one box per level
+//! listing its exits, with a line from every linked exit to the level it
+//! leads to. Drag a box by its title to move it; click an exit, then another
+//! level's box to link it there (arriving at that level's first spawn), and
+//! pick the arrival spawn - an entrance or checkpoint id - or unlink in the
+//! bar below. Right-drag pans. Saved to `assets/project/world.ron` (desktop only).
+
+use std::path::PathBuf;
+use macroquad::prelude::*;
+use crate::game::{level_exits, level_spawns, WorldMap};
+use crate::ui::{Rect, UiContext, draw_text_button, draw_text_button_enabled, ui_screen_width, ui_screen_height, ACCENT_COLOR};
+use super::{load_example, step_buttons, ExampleLevelInfo};
+
+/// Width of a level box
+const NODE_W: f32 = 160.0;
+/// Height of a level box's title and of each exit row
+const ROW_H: f32 = 20.0;
+
+/// A level's exits and the spawns other levels can link to
+#[derive(Debug, Clone)]
+pub struct LevelPorts {
+    pub path: PathBuf,
+    pub name: String,
+    pub exits: Vec<String>,
+    pub spawns: Vec<i32>,
+}
+
+/// Read the exits and spawns of the listed levels
+pub fn scan_levels(levels: &[ExampleLevelInfo]) -> Vec<LevelPorts> {
+    levels
+        .iter()
+        .filter_map(|info| {
+            let level = load_example(info).ok()?;
+            Some(LevelPorts { path: info.path.clone(), name: info.name.clone(), exits: level_exits(&level), spawns: level_spawns(&level) })
+        })
+        .collect()
+}
+
+/// State for the world map view
+#[derive(Default)]
+pub struct WorldMapView {
+    pub open: bool,
+    pub map: WorldMap,
+    pub levels: Vec<LevelPorts>,
+    /// Exit picked for linking: its level and name
+    pub selected: Option<(usize, String)>,
+    /// Level box being dragged, with the grab offset
+    dragging: Option<(usize, f32, f32)>,
+    /// Graph scroll offset
+    pan: (f32, f32),
+    /// Mouse position last frame, for panning
+    last_mouse: (f32, f32),
+    /// Edited since opened or saved
+    pub dirty: bool,
+}
+
+/// What the user chose in the view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldMapAction {
+    None,
+    Save,
+    Close,
+}
+
+impl WorldMapView {
+    /// Open the view on the project's map and levels
+    pub fn show(&mut self, mut map: WorldMap, levels: Vec<LevelPorts>) {
+        let paths: Vec<PathBuf> = levels.iter().map(|l| l.path.clone()).collect();
+        map.place_new(&paths);
+        *self = Self { open: true, map, levels, ..Default::default() };
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.selected = None;
+        self.dragging = None;
+    }
+
+    /// Screen rectangle of a level's box
+    fn node_rect(&self, origin: (f32, f32), level: usize) -> Option<Rect> {
+        let ports = self.levels.get(level)?;
+        let node = self.map.node(&ports.path)?;
+        let h = ROW_H * (1 + ports.exits.len().max(1)) as f32;
+        Some(Rect::new(origin.0 + self.pan.0 + node.x, origin.1 + self.pan.1 + node.y, NODE_W, h))
+    }
+}
+
+/// Draw the world map view
+pub fn draw_world_map_view(ctx: &mut UiContext, view: &mut WorldMapView) -> WorldMapAction {
+    if !view.open {
+        return WorldMapAction::None;
+    }
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = (ui_screen_width() * 0.85).floor();
+    let dialog_h = (ui_screen_height() * 0.85).floor();
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+    let header_h = 36.0;
+    let footer_h = 44.0;
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    let title = if view.dirty { "World Map *" } else { "World Map" };
+    draw_text(title, x + 16.0, y + 24.0, 18.0, WHITE);
+    let hint = "Click an exit, then a level to link it. Drag titles to move, right-drag to pan.";
+    draw_text(hint, x + 140.0, y + 23.0, 13.0, Color::from_rgba(150, 150, 150, 255));
+
+    // Graph
+    let graph = Rect::new(x + 8.0, y + header_h + 8.0, dialog_w - 16.0, dialog_h - header_h - footer_h - 16.0);
+    draw_rectangle(graph.x, graph.y, graph.w, graph.h, Color::from_rgba(25, 25, 30, 255));
+    if view.levels.is_empty() {
+        draw_text("No levels found in assets/levels", graph.x + 16.0, graph.y + 24.0, 14.0, Color::from_rgba(150, 150, 150, 255));
+    }
+    draw_graph(ctx, graph, view);
+
+    // Footer: the selected exit's link
+    let button_y = y + dialog_h - footer_h + 8.0;
+    if let Some((level, exit)) = view.selected.clone() {
+        draw_link_bar(ctx, Rect::new(x + 16.0, button_y, dialog_w - 220.0, 28.0), view, level, &exit);
+    }
+    let close_rect = Rect::new(x + dialog_w - 90.0, button_y, 80.0, 28.0);
+    if draw_text_button(ctx, close_rect, "Close", Color::from_rgba(60, 60, 70, 255)) || is_key_pressed(KeyCode::Escape) {
+        return WorldMapAction::Close;
+    }
+    let save_rect = Rect::new(close_rect.x - 90.0, button_y, 80.0, 28.0);
+    if draw_text_button_enabled(ctx, save_rect, "Save", ACCENT_COLOR, view.dirty) {
+        return WorldMapAction::Save;
+    }
+    WorldMapAction::None
+}
+
+/// Level boxes and link lines, with dragging, panning and linking
+fn draw_graph(ctx: &mut UiContext, graph: Rect, view: &mut WorldMapView) {
+    let origin = (graph.x, graph.y);
+    let (mx, my) = (ctx.mouse.x, ctx.mouse.y);
+
+    // Pan with the right button, drag boxes by their title
+    if ctx.mouse.right_down && ctx.mouse.inside(&graph) {
+        view.pan.0 += mx - view.last_mouse.0;
+        view.pan.1 += my - view.last_mouse.1;
+    }
+    view.last_mouse = (mx, my);
+    if let Some((level, gx, gy)) = view.dragging {
+        if ctx.mouse.left_down {
+            let (pan_x, pan_y) = view.pan;
+            if let Some(node) = view.levels.get(level).and_then(|p| view.map.node_mut(&p.path)) {
+                node.x = (mx - origin.0 - pan_x - gx).round();
+                node.y = (my - origin.1 - pan_y - gy).round();
+                view.dirty = true;
+            }
+        } else {
+            view.dragging = None;
+        }
+    }
+
+    // Links: exit row to the target box
+    for (i, ports) in view.levels.iter().enumerate() {
+        let Some(from) = view.node_rect(origin, i) else { continue };
+        for (e, exit) in ports.exits.iter().enumerate() {
+            let Some(link) = view.map.link(&ports.path, exit) else { continue };
+            let Some(to) = view.levels.iter().position(|l| l.path == link.to).and_then(|t| view.node_rect(origin, t)) else {
+                continue;
+            };
+            let (sx, sy) = (from.right(), from.y + ROW_H * (e + 1) as f32 + ROW_H * 0.5);
+            let (tx, ty) = (to.x, to.y + ROW_H * 0.5);
+            let color = Color::from_rgba(90, 230, 180, 255);
+            draw_line(sx, sy, tx, ty, 2.0, color);
+            draw_circle(tx, ty, 3.0, color);
+            draw_text(&format!("@{}", link.spawn), (sx + tx) * 0.5, (sy + ty) * 0.5 - 4.0, 13.0, color);
+        }
+    }
+
+    // Boxes
+    let mut clicked_level = None;
+    for i in 0..view.levels.len() {
+        let Some(rect) = view.node_rect(origin, i) else { continue };
+        let ports = &view.levels[i];
+        let title = Rect::new(rect.x, rect.y, rect.w, ROW_H);
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(45, 45, 55, 255));
+        draw_rectangle(title.x, title.y, title.w, title.h, Color::from_rgba(60, 60, 75, 255));
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, Color::from_rgba(90, 90, 110, 255));
+        draw_text(&ports.name, title.x + 6.0, title.y + 14.0, 14.0, WHITE);
+        if ports.exits.is_empty() {
+            draw_text("no exits", rect.x + 6.0, rect.y + ROW_H + 14.0, 13.0, Color::from_rgba(130, 130, 130, 255));
+        }
+        for (e, exit) in ports.exits.iter().enumerate() {
+            let row = Rect::new(rect.x, rect.y + ROW_H * (e + 1) as f32, rect.w, ROW_H);
+            let selected = view.selected.as_ref().is_some_and(|(l, n)| *l == i && n == exit);
+            if selected {
+                draw_rectangle(row.x, row.y, row.w, row.h, ACCENT_COLOR);
+            }
+            draw_text(exit, row.x + 6.0, row.y + 14.0, 13.0, WHITE);
+            draw_circle(row.right(), row.y + ROW_H * 0.5, 4.0, Color::from_rgba(90, 230, 180, 255));
+            if ctx.mouse.inside(&graph) && ctx.mouse.clicked(&row) {
+                view.selected = if selected { None } else { Some((i, exit.clone())) };
+            }
+        }
+        if ctx.mouse.inside(&graph) && ctx.mouse.clicked(&title) {
+            view.dragging = Some((i, mx - rect.x, my - rect.y));
+            clicked_level = Some(i);
+        }
+    }
+
+    // Clicking another level's title with an exit picked links it there
+    if let (Some(target), Some((from, exit))) = (clicked_level, view.selected.clone()) {
+        if target != from {
+            let spawn = view.levels[target].spawns.first().copied().unwrap_or(1);
+            let (from_path, to_path) = (view.levels[from].path.clone(), view.levels[target].path.clone());
+            view.map.set_link(&from_path, &exit, &to_path, spawn);
+            view.dragging = None;
+            view.dirty = true;
+        }
+    }
+}
+
+/// "exit -> level @ spawn [-][+] [Unlink]" for the selected exit
+fn draw_link_bar(ctx: &mut UiContext, rect: Rect, view: &mut WorldMapView, level: usize, exit: &str) {
+    let Some(from) = view.levels.get(level).map(|l| l.path.clone()) else {
+        return;
+    };
+    let Some(link) = view.map.link(&from, exit).cloned() else {
+        draw_text(&format!("{}: not linked", exit), rect.x, rect.y + 19.0, 14.0, WHITE);
+        return;
+    };
+    let target = view.levels.iter().find(|l| l.path == link.to);
+    let name = target.map_or_else(|| link.to.display().to_string(), |l| l.name.clone());
+    let spawns = target.map(|l| l.spawns.clone()).unwrap_or_default();
+    let known = spawns.contains(&link.spawn);
+    let label = format!("{} -> {} @ spawn {}{}", exit, name, link.spawn, if known { "" } else { " (missing)" });
+    draw_text(&label, rect.x, rect.y + 19.0, 14.0, if known { WHITE } else { Color::from_rgba(255, 120, 120, 255) });
+
+    let unlink_rect = Rect::new(rect.right() - 70.0, rect.y, 70.0, rect.h);
+    if draw_text_button(ctx, unlink_rect, "Unlink", Color::from_rgba(90, 50, 50, 255)) {
+        view.map.unlink(&from, exit);
+        view.dirty = true;
+        return;
+    }
+    // Step through the target's spawns
+    if let Some(up) = step_buttons(ctx, unlink_rect.x - 8.0, rect.y + 5.0, 18.0).filter(|_| !spawns.is_empty()) {
+        let current = spawns.iter().position(|s| *s == link.spawn).unwrap_or(0);
+        let next = if up { (current + 1) % spawns.len() } else { (current + spawns.len() - 1) % spawns.len() };
+        view.map.set_link(&from, exit, &link.to, spawns[next]);
+        view.dirty = true;
+    }
+}
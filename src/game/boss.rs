@@ -0,0 +1,230 @@
+//! Boss encounters
+//!
+//! A `boss` entity defines the whole fight:
+//! - its arena is the circle of radius `arena` around where it's placed;
+//!   walking in engages it, closing the doors on `seal_channel` behind the
+//!   player (give them `open` so they start open)
+//! - it's a combatant in the simulation's `Combat` with `hp` health: the
+//!   player's swings hurt it through their hit volumes like any enemy's, and
+//!   it swings back for `damage` when the player is in reach
+//! - dropping to `phase2` and `phase3` percent of its health fires
+//!   `phase_channel` and the channel after it, starting the level events and
+//!   moving the doors on them
+//! - defeating it opens the sealed doors again and pays `reward` currency;
+//!   dying in the fight resets it
+//!
+//! The HUD's boss bar and name show the engaged boss.
+
+use crate::rasterizer::Vec3;
+use crate::world::{Entity, EntityRef, Level};
+use super::{AttackDef, Combat, Combatant, Health, LogicState, ENEMY_ATTACK_RANGE};
+
+pub const BOSS_ARCHETYPE: &str = "boss";
+
+/// Currency paid by a boss without a `reward` property
+pub const DEFAULT_BOSS_REWARD: i32 = 1000;
+
+fn int(entity: &Entity, key: &str, default: i32) -> i32 {
+    entity.get(key).and_then(|v| v.as_int()).unwrap_or(default)
+}
+
+/// A boss's health at the start of its fight
+fn full_health(entity: &Entity) -> Health {
+    let hp = int(entity, "hp", 500).max(1) as f32;
+    Health::new(hp, hp)
+}
+
+/// Play-mode state of one boss
+#[derive(Debug, Clone)]
+pub struct BossFight {
+    pub boss: EntityRef,
+    pub name: String,
+    /// Its combatant (and attack) in the simulation's `Combat`
+    pub combatant: usize,
+    /// The player is in the arena and the doors are sealed
+    pub engaged: bool,
+    pub defeated: bool,
+    /// Phase thresholds passed so far
+    phase: usize,
+}
+
+impl BossFight {
+    /// A fight for every boss in the level, each joining `combat`
+    pub fn all(level: &Level, combat: &mut Combat) -> Vec<Self> {
+        level.entities_of(BOSS_ARCHETYPE).map(|(r, e)| Self::new(level, r, e, combat)).collect()
+    }
+
+    fn new(level: &Level, boss: EntityRef, entity: &Entity, combat: &mut Combat) -> Self {
+        let name = entity.get("name").and_then(|v| v.as_str()).unwrap_or("Boss").to_string();
+        let mut fight = Self { boss, name, combatant: 0, engaged: false, defeated: false, phase: 0 };
+        fight.join(level, combat);
+        fight
+    }
+
+    /// Add the boss to `combat` at full health, inactive until its arena is
+    /// entered (call again when combat is rebuilt)
+    pub fn join(&mut self, level: &Level, combat: &mut Combat) {
+        let Some(entity) = level.entity(self.boss) else { return };
+        let position = level.entity_world_pos(self.boss).unwrap_or(entity.position);
+        let mut attack = AttackDef::new(BOSS_ARCHETYPE);
+        attack.damage = int(entity, "damage", 30).max(0) as f32;
+        let mut combatant = Combatant::new(position, 1, full_health(entity));
+        combatant.active = false;
+        self.combatant = combat.combatants.len();
+        combat.attacks.push(attack);
+        combat.combatants.push(combatant);
+    }
+
+    /// The boss's health in `combat`
+    pub fn health<'a>(&self, combat: &'a Combat) -> &'a Health {
+        &combat.combatants[self.combatant].health
+    }
+
+    /// Health fractions at which the next phases start, highest first
+    fn thresholds(entity: &Entity) -> Vec<f32> {
+        ["phase2", "phase3"]
+            .iter()
+            .map(|key| int(entity, key, 0) as f32 / 100.0)
+            .filter(|t| *t > 0.0)
+            .collect()
+    }
+
+    /// One simulation step before combat resolves its hits, with the
+    /// player's feet at `feet`: engage when they walk into the arena, then
+    /// turn to them and swing whenever they're in reach
+    pub fn step(&mut self, level: &Level, logic: &mut LogicState, combat: &mut Combat, feet: Vec3) {
+        let Some(entity) = level.entity(self.boss).filter(|_| !self.defeated) else {
+            return;
+        };
+        let c = &mut combat.combatants[self.combatant];
+        let to_player = feet - c.position;
+        if !self.engaged {
+            let arena = entity.get("arena").and_then(|v| v.as_float()).unwrap_or(4096.0);
+            if to_player.x * to_player.x + to_player.z * to_player.z <= arena * arena {
+                self.engaged = true;
+                c.active = true;
+                logic.close_channel(level, int(entity, "seal_channel", 50));
+            }
+            return;
+        }
+        if c.can_act() && to_player.len() <= ENEMY_ATTACK_RANGE {
+            c.yaw = to_player.x.atan2(to_player.z);
+            combat.start_attack(self.combatant, self.combatant);
+        }
+    }
+
+    /// Follow the boss's health after combat resolved its hits: start the
+    /// phases it dropped to, and end the fight when it's dead. Returns the
+    /// currency paid when the boss is defeated.
+    pub fn resolve(&mut self, level: &Level, logic: &mut LogicState, combat: &Combat) -> u32 {
+        let Some(entity) = level.entity(self.boss).filter(|_| self.engaged) else {
+            return 0;
+        };
+        let health = self.health(combat);
+        let fraction = health.hp / health.max_hp;
+        let thresholds = Self::thresholds(entity);
+        while self.phase < thresholds.len() && fraction <= thresholds[self.phase] {
+            logic.fire_channel(level, int(entity, "phase_channel", 51) + self.phase as i32);
+            self.phase += 1;
+        }
+        if health.is_dead() {
+            self.defeated = true;
+            self.engaged = false;
+            logic.open_channel(level, int(entity, "seal_channel", 50));
+            return int(entity, "reward", DEFAULT_BOSS_REWARD).max(0) as u32;
+        }
+        0
+    }
+
+    /// Put an unfinished fight back to the start (the player died), opening
+    /// its sealed doors and healing the boss
+    pub fn reset(&mut self, level: &Level, logic: &mut LogicState, combat: &mut Combat) {
+        let Some(entity) = level.entity(self.boss).filter(|_| !self.defeated) else {
+            return;
+        };
+        if self.engaged {
+            logic.open_channel(level, int(entity, "seal_channel", 50));
+        }
+        self.engaged = false;
+        self.phase = 0;
+        if let Some(c) = combat.combatants.get_mut(self.combatant) {
+            c.health = full_health(entity);
+            c.attack = None;
+            c.active = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{DOOR_ARCHETYPE, FIXED_DT, PLAYER_ATTACK_DAMAGE};
+    use crate::world::{create_test_level, PropertyValue};
+
+    #[test]
+    fn test_fight_seals_phases_and_unseals() {
+        let mut level = create_test_level();
+        let mut gate = Entity { archetype: DOOR_ARCHETYPE.to_string(), position: Vec3::ZERO, properties: Default::default() };
+        gate.set("channel", PropertyValue::Int(7));
+        gate.set("open", PropertyValue::Bool(true));
+        let gate = level.add_entity(0, gate).unwrap();
+        let mut boss = Entity { archetype: BOSS_ARCHETYPE.to_string(), position: Vec3::new(512.0, 0.0, 700.0), properties: Default::default() };
+        boss.set("hp", PropertyValue::Int(100));
+        boss.set("arena", PropertyValue::Float(1024.0));
+        boss.set("seal_channel", PropertyValue::Int(7));
+        boss.set("phase2", PropertyValue::Int(50));
+        boss.set("phase_channel", PropertyValue::Int(20));
+        level.add_entity(0, boss);
+
+        // The player swings from in front of the boss, facing it
+        let mut combat = Combat::new();
+        let mut swing = AttackDef::new("attack");
+        swing.damage = PLAYER_ATTACK_DAMAGE;
+        combat.attacks.push(swing);
+        combat.combatants.push(Combatant::new(Vec3::new(512.0, 0.0, 4096.0), 0, Health::new(1000.0, 1000.0)));
+        let mut logic = LogicState::new(&level);
+        assert_eq!(logic.offset(gate).y, 1024.0);
+        let mut fights = BossFight::all(&level, &mut combat);
+        let fight = &mut fights[0];
+
+        // Outside the arena it can't be hit
+        let feet = combat.combatants[0].position;
+        fight.step(&level, &mut logic, &mut combat, feet);
+        assert!(!fight.engaged && !combat.combatants[fight.combatant].is_target());
+        let feet = Vec3::new(512.0, 0.0, 200.0);
+        combat.combatants[0].position = feet;
+        fight.step(&level, &mut logic, &mut combat, feet);
+        assert!(fight.engaged);
+        logic.update(&level, 10.0);
+        assert_eq!(logic.offset(gate).y, 0.0);
+
+        // Each swing lands once during its active frames
+        let hit = |fight: &mut BossFight, logic: &mut LogicState, combat: &mut Combat| -> u32 {
+            assert!(combat.start_attack(0, 0));
+            let mut paid = 0;
+            for _ in 0..(1.2 / FIXED_DT) as usize {
+                fight.step(&level, logic, combat, feet);
+                combat.update(&level, FIXED_DT);
+                paid += fight.resolve(&level, logic, combat);
+            }
+            paid
+        };
+
+        // Two hits reach phase 2, two more finish it
+        logic.take_fired();
+        for _ in 0..2 {
+            hit(fight, &mut logic, &mut combat);
+        }
+        assert_eq!(fight.health(&combat).hp, 50.0);
+        assert_eq!(logic.take_fired(), [20]);
+        let paid: u32 = (0..2).map(|_| hit(fight, &mut logic, &mut combat)).sum();
+        assert!(fight.defeated && !fight.engaged);
+        assert_eq!(paid, DEFAULT_BOSS_REWARD as u32);
+        logic.update(&level, 10.0);
+        assert_eq!(logic.offset(gate).y, 1024.0);
+
+        // It swung back at the player in reach
+        let player = &combat.combatants[0].health;
+        assert!(player.hp < player.max_hp);
+    }
+}
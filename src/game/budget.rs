@@ -0,0 +1,89 @@
+//! Performance budget - worst-case triangles per room
+//!
+//! The game draws every room within the stream distance of the player's room
+//! (see `streaming`), culled only by the view frustum, and draws them all a
+//! second time for each mirror portal among them. From somewhere in a room,
+//! the worst case is all of those in view at once. `level_budget` estimates
+//! that for each room, so the editor's Budget dialog can flag rooms that
+//! would go over `FRAME_TRIANGLE_BUDGET` while the level is still being built.
+//!
+//! The budget is for 320x240. `bonnie-engine bench` measures real frame times
+//! on the benchmark level (`create_benchmark_level`) to check it against.
+
+use crate::world::{Level, PortalKind, Room};
+use super::rooms_within;
+
+/// Triangles a frame can draw at 320x240 and still hold 30 fps on the
+/// benchmark level
+pub const FRAME_TRIANGLE_BUDGET: usize = 4000;
+
+/// Worst-case view from inside one room
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoomBudget {
+    pub room: usize,
+    /// Triangles of the room's own mesh
+    pub own: usize,
+    /// Rooms drawn from inside it (itself included)
+    pub rooms: usize,
+    /// Mirror portals among them
+    pub mirrors: usize,
+    /// Triangles drawn with all of them in view
+    pub triangles: usize,
+}
+
+impl RoomBudget {
+    /// Share of the frame budget (1.0 = all of it)
+    pub fn load(&self) -> f32 {
+        self.triangles as f32 / FRAME_TRIANGLE_BUDGET as f32
+    }
+
+    pub fn over(&self) -> bool {
+        self.triangles > FRAME_TRIANGLE_BUDGET
+    }
+}
+
+/// Triangles of a room as the game draws it
+pub fn room_triangles(room: &Room) -> usize {
+    room.to_game_render_data(|_| None).1.len()
+}
+
+/// Worst case of every room, with rooms up to `hops` portals away drawn
+pub fn level_budget(level: &Level, hops: usize) -> Vec<RoomBudget> {
+    let own: Vec<usize> = level.rooms.iter().map(room_triangles).collect();
+    (0..level.rooms.len())
+        .map(|room| {
+            let drawn = rooms_within(level, room, hops);
+            let resident: usize = drawn.iter().map(|&r| own[r]).sum();
+            let mirrors = drawn
+                .iter()
+                .map(|&r| level.rooms[r].portals.iter().filter(|p| p.kind == PortalKind::Mirror).count())
+                .sum::<usize>();
+            RoomBudget { room, own: own[room], rooms: drawn.len(), mirrors, triangles: resident * (1 + mirrors) }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_benchmark_level, BENCHMARK_ROOMS};
+
+    #[test]
+    fn test_budget_counts_rooms_in_stream_distance() {
+        let mut level = create_benchmark_level();
+        assert_eq!(level.rooms.len(), BENCHMARK_ROOMS);
+        let budget = level_budget(&level, 2);
+        assert_eq!(budget.iter().map(|b| b.rooms).collect::<Vec<_>>(), [3, 4, 5, 4, 3]);
+        // Pillars get denser down the row
+        assert!(budget[BENCHMARK_ROOMS - 1].own > budget[0].own);
+        let first: usize = (0..3).map(|r| budget[r].own).sum();
+        assert_eq!(budget[0].triangles, first);
+        assert!(!budget[0].over());
+
+        // A mirror doubles everything drawn around it
+        level.rooms[0].portals[0].kind = PortalKind::Mirror;
+        let mirrored = level_budget(&level, 2);
+        assert_eq!((mirrored[0].mirrors, mirrored[0].triangles), (1, first * 2));
+        assert_eq!(mirrored[4].mirrors, 0);
+    }
+}
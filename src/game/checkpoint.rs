@@ -0,0 +1,111 @@
+//! Checkpoints and respawning
+//!
+//! `checkpoint` entities carry an `id` (unique per level, assigned when one
+//! is placed in the editor):
+//! - walking within `CHECKPOINT_RANGE` of one makes it the player's
+//!   checkpoint, and exported games save its id (see `SaveGame`)
+//! - when the player dies (out of health, or falling `KILL_DEPTH` below the
+//!   level's lowest floor) the screen fades out, the player comes back at the
+//!   checkpoint (or the level's start) and the level resets per its
+//!   `RespawnRules`, then the screen fades back in
+
+use crate::rasterizer::Vec3;
+use crate::world::{Entity, Level};
+use super::PLAYER_EYE_HEIGHT;
+
+pub const CHECKPOINT_ARCHETYPE: &str = "checkpoint";
+
+/// How close the player must come to reach a checkpoint (one sector)
+pub const CHECKPOINT_RANGE: f32 = 1024.0;
+
+/// How far below the lowest floor falling kills the player
+pub const KILL_DEPTH: f32 = 4096.0;
+
+/// Player health (before any vigor levels) and poise
+pub const PLAYER_MAX_HP: f32 = 100.0;
+pub const PLAYER_MAX_POISE: f32 = 50.0;
+
+fn checkpoint_id(entity: &Entity) -> i32 {
+    entity.get("id").and_then(|v| v.as_int()).unwrap_or(0)
+}
+
+/// Eye position and heading (radians) of a checkpoint
+pub fn checkpoint_pose(level: &Level, id: i32) -> Option<(Vec3, f32)> {
+    let (r, entity) = level.entities_of(CHECKPOINT_ARCHETYPE).find(|(_, e)| checkpoint_id(e) == id)?;
+    let position = level.entity_world_pos(r)? + Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let facing = entity.get("facing").and_then(|v| v.as_float()).unwrap_or(0.0);
+    Some((position, facing.to_radians()))
+}
+
+/// Id of the nearest checkpoint an eye position is in range of
+pub fn checkpoint_in_range(level: &Level, eye: Vec3) -> Option<i32> {
+    level
+        .entities_of(CHECKPOINT_ARCHETYPE)
+        .filter_map(|(r, e)| {
+            let distance = (level.entity_world_pos(r)? + Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0) - eye).len();
+            (distance <= CHECKPOINT_RANGE).then_some((distance, checkpoint_id(e)))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, id)| id)
+}
+
+/// Ids of a level's checkpoints, in entity order
+pub fn checkpoint_ids(level: &Level) -> Vec<i32> {
+    level.entities_of(CHECKPOINT_ARCHETYPE).map(|(_, e)| checkpoint_id(e)).collect()
+}
+
+/// Id for a newly placed checkpoint (one past the highest in the level)
+pub fn next_checkpoint_id(level: &Level) -> i32 {
+    level.entities_of(CHECKPOINT_ARCHETYPE).map(|(_, e)| checkpoint_id(e) + 1).max().unwrap_or(1)
+}
+
+/// Whether a position has fallen out of the level
+pub fn below_kill_plane(level: &Level, position: Vec3) -> bool {
+    let lowest = level.rooms.iter().map(|r| r.world_bounds().min.y).fold(f32::INFINITY, f32::min);
+    lowest.is_finite() && position.y < lowest - KILL_DEPTH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Simulation, FIXED_DT};
+    use crate::world::{create_test_level, PropertyValue};
+
+    fn checkpoint(id: i32, position: Vec3) -> Entity {
+        let mut e = Entity { archetype: CHECKPOINT_ARCHETYPE.to_string(), position, properties: Default::default() };
+        e.set("id", PropertyValue::Int(id));
+        e
+    }
+
+    #[test]
+    fn test_dying_respawns_at_the_last_checkpoint() {
+        let mut level = create_test_level();
+        level.add_entity(0, checkpoint(1, Vec3::new(256.0, 0.0, 256.0)));
+        level.add_entity(0, checkpoint(2, Vec3::new(768.0, 0.0, 768.0)));
+        assert_eq!(next_checkpoint_id(&level), 3);
+
+        let mut sim = Simulation::new(level, 1);
+        sim.cutscene = None;
+        sim.camera.position = Vec3::new(768.0, PLAYER_EYE_HEIGHT, 700.0);
+        sim.step(Default::default());
+        assert_eq!(sim.checkpoint, Some(2));
+
+        // Fall out of the level: fade out, respawn, fade back in
+        sim.camera.position = Vec3::new(0.0, -KILL_DEPTH * 2.0, 0.0);
+        sim.step(Default::default());
+        assert!(sim.player.is_dead());
+        // The death is noticed on the next step, which starts the fade
+        let fade_steps = (sim.level.respawn.fade / FIXED_DT).ceil() as usize;
+        for _ in 0..fade_steps + 1 {
+            sim.step(Default::default());
+        }
+        assert!(sim.fade() > 0.9);
+        assert!(!sim.player.is_dead());
+        let pos = sim.camera.position;
+        assert_eq!((pos.x, pos.y, pos.z), (768.0, PLAYER_EYE_HEIGHT, 768.0));
+        for _ in 0..fade_steps {
+            sim.step(Default::default());
+        }
+        assert_eq!(sim.fade(), 0.0);
+    }
+}
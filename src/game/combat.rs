@@ -0,0 +1,584 @@
+//! Combat - hitboxes, damage and stagger
+//!
+//! Souls-style melee combat:
+//! - Attacks have an active frame window and one or more hit volumes,
+//!   timed by "hit_start"/"hit_end" events on modeler animation clips
+//! - Combatants carry health and poise; breaking poise staggers them
+//! - A short invulnerability window follows every hit
+//! - Hits and hitscan attacks need a clear line through the level between
+//!   attacker and target
+//! - Optional wireframe hitbox visualization for debugging
+
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::{Camera, Color, Framebuffer, Vec3};
+use crate::world::{line_of_sight, raycast_solid, Aabb, Level};
+use crate::modeler::{Animation, EVENT_HIT_START, EVENT_HIT_END};
+
+/// Invulnerability granted after taking a hit (seconds)
+pub const HIT_INVULN_TIME: f32 = 0.4;
+
+/// How long a poise break staggers (seconds)
+pub const STAGGER_TIME: f32 = 0.8;
+
+/// Delay before poise starts regenerating after a hit (seconds)
+pub const POISE_REGEN_DELAY: f32 = 2.0;
+
+/// Damage of the player's bare-handed attack (at strength level 0)
+pub const PLAYER_ATTACK_DAMAGE: f32 = 25.0;
+
+/// Box-shaped hit or hurt volume, relative to its owner
+///
+/// Offset is in local space: +Z is the direction the owner is facing.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HitVolume {
+    pub offset: Vec3,
+    pub half_extents: Vec3,
+}
+
+impl HitVolume {
+    pub fn new(offset: Vec3, half_extents: Vec3) -> Self {
+        Self { offset, half_extents }
+    }
+
+    /// World-space bounds for an owner at `position` facing `yaw` (radians around Y)
+    pub fn world_aabb(&self, position: Vec3, yaw: f32) -> Aabb {
+        let (sin, cos) = yaw.sin_cos();
+        let center = position + Vec3::new(
+            self.offset.x * cos + self.offset.z * sin,
+            self.offset.y,
+            -self.offset.x * sin + self.offset.z * cos,
+        );
+        // Bounds of the rotated box
+        let ex = self.half_extents.x * cos.abs() + self.half_extents.z * sin.abs();
+        let ez = self.half_extents.x * sin.abs() + self.half_extents.z * cos.abs();
+        let extents = Vec3::new(ex, self.half_extents.y, ez);
+        Aabb::new(center - extents, center + extents)
+    }
+}
+
+/// Data-driven attack definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackDef {
+    pub name: String,
+    /// Modeler animation clip played for this attack
+    pub animation: String,
+    /// Frame rate of the attack timeline (matches the animation clip)
+    pub fps: u8,
+    /// Total length in frames (attacker can't act again until it ends)
+    pub total_frames: u32,
+    /// First frame the hit volumes are live (inclusive)
+    pub active_start: u32,
+    /// Last frame the hit volumes are live (inclusive)
+    pub active_end: u32,
+    pub damage: f32,
+    pub poise_damage: f32,
+    pub hit_volumes: Vec<HitVolume>,
+}
+
+impl AttackDef {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            animation: name.to_string(),
+            fps: 15,
+            total_frames: 15,
+            active_start: 5,
+            active_end: 8,
+            damage: 10.0,
+            poise_damage: 10.0,
+            hit_volumes: vec![HitVolume::new(
+                Vec3::new(0.0, 512.0, 384.0),
+                Vec3::new(192.0, 192.0, 256.0),
+            )],
+        }
+    }
+
+    /// Build an attack whose timing comes from a modeler animation clip
+    ///
+    /// The active window spans the clip's "hit_start" to "hit_end" events;
+    /// without them the default window is kept.
+    pub fn from_animation(name: &str, anim: &Animation) -> Self {
+        let mut def = Self::new(name);
+        def.animation = anim.name.clone();
+        def.fps = anim.fps;
+        def.total_frames = anim.last_frame().max(1);
+        if let Some(start) = anim.find_event(EVENT_HIT_START) {
+            def.active_start = start;
+            def.active_end = anim.find_event(EVENT_HIT_END).unwrap_or(def.total_frames);
+        }
+        def
+    }
+
+    /// Check if a frame falls inside the active window
+    pub fn is_active_frame(&self, frame: u32) -> bool {
+        frame >= self.active_start && frame <= self.active_end
+    }
+}
+
+/// Health and poise of a combatant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    pub hp: f32,
+    pub max_hp: f32,
+    pub poise: f32,
+    pub max_poise: f32,
+    /// Poise regained per second once the regen delay has passed
+    pub poise_regen: f32,
+    /// Fraction of damage absorbed (armor)
+    #[serde(default)]
+    pub defense: f32,
+    /// Remaining invulnerability (seconds)
+    #[serde(skip)]
+    pub invuln: f32,
+    /// Remaining stagger (seconds)
+    #[serde(skip)]
+    pub stagger: f32,
+    /// Time since last hit (seconds)
+    #[serde(skip)]
+    pub since_hit: f32,
+}
+
+/// Outcome of applying a hit to a combatant
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageResult {
+    /// Invulnerable or already dead
+    Ignored,
+    Damaged,
+    /// Poise broke
+    Staggered,
+    Killed,
+}
+
+impl Health {
+    pub fn new(max_hp: f32, max_poise: f32) -> Self {
+        Self {
+            hp: max_hp,
+            max_hp,
+            poise: max_poise,
+            max_poise,
+            poise_regen: max_poise * 0.5,
+            defense: 0.0,
+            invuln: 0.0,
+            stagger: 0.0,
+            since_hit: 0.0,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.hp <= 0.0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invuln > 0.0
+    }
+
+    pub fn is_staggered(&self) -> bool {
+        self.stagger > 0.0
+    }
+
+    /// Apply damage (less what armor absorbs), respecting invulnerability windows
+    pub fn apply_damage(&mut self, damage: f32, poise_damage: f32) -> DamageResult {
+        if self.is_dead() || self.is_invulnerable() {
+            return DamageResult::Ignored;
+        }
+
+        self.hp = (self.hp - damage * (1.0 - self.defense)).max(0.0);
+        self.invuln = HIT_INVULN_TIME;
+        self.since_hit = 0.0;
+
+        if self.is_dead() {
+            return DamageResult::Killed;
+        }
+
+        self.poise -= poise_damage;
+        if self.poise <= 0.0 {
+            // Poise break: stagger and reset to full
+            self.poise = self.max_poise;
+            self.stagger = STAGGER_TIME;
+            return DamageResult::Staggered;
+        }
+
+        DamageResult::Damaged
+    }
+
+    /// Tick timers and regenerate poise
+    pub fn update(&mut self, delta: f32) {
+        self.invuln = (self.invuln - delta).max(0.0);
+        self.stagger = (self.stagger - delta).max(0.0);
+        self.since_hit += delta;
+
+        if self.since_hit >= POISE_REGEN_DELAY {
+            self.poise = (self.poise + self.poise_regen * delta).min(self.max_poise);
+        }
+    }
+}
+
+/// An attack in progress
+#[derive(Debug, Clone)]
+pub struct ActiveAttack {
+    /// Index into `Combat::attacks`
+    pub def: usize,
+    /// Time since the attack started (seconds)
+    pub time: f32,
+    /// Combatants already hit by this swing (each target is hit at most once)
+    pub already_hit: Vec<usize>,
+    /// Hit window opened or closed by the attacker's animation events,
+    /// overriding the frame window once one has fired
+    pub live: Option<bool>,
+}
+
+impl ActiveAttack {
+    /// Current frame on the attack timeline
+    pub fn frame(&self, def: &AttackDef) -> u32 {
+        (self.time * def.fps as f32) as u32
+    }
+}
+
+/// Anything that can deal or take damage in play mode
+#[derive(Debug, Clone)]
+pub struct Combatant {
+    pub position: Vec3,
+    /// Facing direction (radians around Y, 0 = +Z)
+    pub yaw: f32,
+    /// Combatants on the same team can't hurt each other
+    pub team: u8,
+    pub hurtbox: HitVolume,
+    pub health: Health,
+    pub attack: Option<ActiveAttack>,
+    /// Inactive combatants (not spawned yet) neither hit nor get hit
+    pub active: bool,
+}
+
+impl Combatant {
+    pub fn new(position: Vec3, team: u8, health: Health) -> Self {
+        Self {
+            position,
+            yaw: 0.0,
+            team,
+            hurtbox: HitVolume::new(Vec3::new(0.0, 512.0, 0.0), Vec3::new(192.0, 512.0, 192.0)),
+            health,
+            attack: None,
+            active: true,
+        }
+    }
+
+    /// Can this combatant start a new action?
+    pub fn can_act(&self) -> bool {
+        self.active && self.attack.is_none() && !self.health.is_staggered() && !self.health.is_dead()
+    }
+
+    /// Can this combatant be hit?
+    pub fn is_target(&self) -> bool {
+        self.active && !self.health.is_dead()
+    }
+}
+
+/// A hit that landed this frame
+#[derive(Debug, Clone, Copy)]
+pub struct HitEvent {
+    pub attacker: usize,
+    pub target: usize,
+    pub result: DamageResult,
+}
+
+/// Combat world: attack library, combatants and debug settings
+pub struct Combat {
+    pub attacks: Vec<AttackDef>,
+    pub combatants: Vec<Combatant>,
+    /// Draw hit/hurt volumes as wireframes
+    pub show_hitboxes: bool,
+}
+
+impl Combat {
+    pub fn new() -> Self {
+        Self {
+            attacks: Vec::new(),
+            combatants: Vec::new(),
+            show_hitboxes: false,
+        }
+    }
+
+    /// Start an attack; returns false if the combatant is busy, staggered or dead
+    pub fn start_attack(&mut self, combatant: usize, attack: usize) -> bool {
+        if attack >= self.attacks.len() {
+            return false;
+        }
+        match self.combatants.get_mut(combatant) {
+            Some(c) if c.can_act() => {
+                c.attack = Some(ActiveAttack { def: attack, time: 0.0, already_hit: Vec::new(), live: None });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Open or close the hit window of a combatant's attack in progress
+    /// (its animation reached "hit_start" or "hit_end")
+    pub fn set_hit_window(&mut self, combatant: usize, live: bool) {
+        if let Some(attack) = self.combatants.get_mut(combatant).and_then(|c| c.attack.as_mut()) {
+            attack.live = Some(live);
+        }
+    }
+
+    /// World-space hit volumes of a combatant's attack, if its active window is open
+    pub fn active_hitboxes(&self, combatant: usize) -> Vec<Aabb> {
+        let Some(c) = self.combatants.get(combatant) else {
+            return Vec::new();
+        };
+        let Some(attack) = c.attack.as_ref().filter(|_| c.active) else {
+            return Vec::new();
+        };
+        let def = &self.attacks[attack.def];
+        if !attack.live.unwrap_or_else(|| def.is_active_frame(attack.frame(def))) {
+            return Vec::new();
+        }
+        def.hit_volumes.iter().map(|v| v.world_aabb(c.position, c.yaw)).collect()
+    }
+
+    /// Advance timers and attacks, resolve hits (not through level geometry)
+    pub fn update(&mut self, level: &Level, delta: f32) -> Vec<HitEvent> {
+        // Tick health, advance or cancel attacks
+        for c in &mut self.combatants {
+            c.health.update(delta);
+            if c.health.is_staggered() || c.health.is_dead() {
+                c.attack = None;
+                continue;
+            }
+            if let Some(attack) = &mut c.attack {
+                attack.time += delta;
+                let def = &self.attacks[attack.def];
+                if attack.frame(def) >= def.total_frames {
+                    c.attack = None;
+                }
+            }
+        }
+
+        // Resolve hits
+        let mut hits = Vec::new();
+        for attacker in 0..self.combatants.len() {
+            let hitboxes = self.active_hitboxes(attacker);
+            if hitboxes.is_empty() {
+                continue;
+            }
+            let Some(attack) = &self.combatants[attacker].attack else { continue };
+            let def = &self.attacks[attack.def];
+            let (damage, poise_damage) = (def.damage, def.poise_damage);
+            let team = self.combatants[attacker].team;
+            let a = &self.combatants[attacker];
+            let origin = a.hurtbox.world_aabb(a.position, a.yaw).center();
+
+            for target in 0..self.combatants.len() {
+                let t = &self.combatants[target];
+                if target == attacker || t.team == team || !t.is_target() {
+                    continue;
+                }
+                if self.combatants[attacker].attack.as_ref().is_some_and(|a| a.already_hit.contains(&target)) {
+                    continue;
+                }
+                let hurtbox = t.hurtbox.world_aabb(t.position, t.yaw);
+                if !hitboxes.iter().any(|h| h.intersects(&hurtbox)) || !line_of_sight(level, origin, hurtbox.center()) {
+                    continue;
+                }
+
+                let result = self.combatants[target].health.apply_damage(damage, poise_damage);
+                if let Some(attack) = &mut self.combatants[attacker].attack {
+                    attack.already_hit.push(target);
+                }
+                if result != DamageResult::Ignored {
+                    hits.push(HitEvent { attacker, target, result });
+                }
+            }
+        }
+
+        hits
+    }
+
+    /// Instant ray attack from the attacker's chest along `dir`, using the
+    /// damage of attack `attack`. Hits the nearest enemy within `range`
+    /// unless level geometry is in the way.
+    pub fn hitscan(&mut self, level: &Level, attacker: usize, attack: usize, dir: Vec3, range: f32) -> Option<HitEvent> {
+        let def = self.attacks.get(attack)?;
+        let (damage, poise_damage) = (def.damage, def.poise_damage);
+        // Callers pace shots; only a staggered or dead shooter misses its shot
+        let a = self.combatants.get(attacker)?;
+        if !a.is_target() || a.health.is_staggered() || dir.len() < 1e-6 {
+            return None;
+        }
+        let (origin, team) = (a.hurtbox.world_aabb(a.position, a.yaw).center(), a.team);
+        let dir = dir.normalize();
+
+        // Nearest enemy along the ray
+        let (target, distance) = self.combatants.iter().enumerate()
+            .filter(|(i, t)| *i != attacker && t.team != team && t.is_target())
+            .filter_map(|(i, t)| {
+                let t_hit = t.hurtbox.world_aabb(t.position, t.yaw).ray_intersect(origin, dir)?;
+                (t_hit <= range).then_some((i, t_hit))
+            })
+            .min_by(|x, y| x.1.total_cmp(&y.1))?;
+
+        if raycast_solid(level, origin, dir, distance).is_some() {
+            return None;
+        }
+        let result = self.combatants[target].health.apply_damage(damage, poise_damage);
+        (result != DamageResult::Ignored).then_some(HitEvent { attacker, target, result })
+    }
+
+    /// Draw hurtboxes (green, red while invulnerable) and live hitboxes (orange)
+    pub fn draw_debug(&self, fb: &mut Framebuffer, camera: &Camera) {
+        if !self.show_hitboxes {
+            return;
+        }
+
+        for (i, c) in self.combatants.iter().enumerate() {
+            if !c.is_target() {
+                continue;
+            }
+            let hurt_color = if c.health.is_invulnerable() {
+                Color::new(255, 60, 60)
+            } else {
+                Color::new(60, 255, 60)
+            };
+            draw_aabb(fb, camera, &c.hurtbox.world_aabb(c.position, c.yaw), hurt_color);
+
+            for hitbox in self.active_hitboxes(i) {
+                draw_aabb(fb, camera, &hitbox, Color::new(255, 160, 40));
+            }
+        }
+    }
+}
+
+impl Default for Combat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draw a wireframe box
+fn draw_aabb(fb: &mut Framebuffer, camera: &Camera, aabb: &Aabb, color: Color) {
+    for (p0, p1) in aabb.edges() {
+        fb.draw_line_3d(p0, p1, camera, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duel() -> Combat {
+        let mut combat = Combat::new();
+        combat.attacks.push(AttackDef::new("slash"));
+        combat.combatants.push(Combatant::new(Vec3::new(512.0, 0.0, 200.0), 0, Health::new(100.0, 30.0)));
+        combat.combatants.push(Combatant::new(Vec3::new(512.0, 0.0, 712.0), 1, Health::new(100.0, 30.0)));
+        combat
+    }
+
+    #[test]
+    fn test_invuln_window_blocks_damage() {
+        let mut health = Health::new(100.0, 50.0);
+        assert_eq!(health.apply_damage(10.0, 0.0), DamageResult::Damaged);
+        assert_eq!(health.apply_damage(10.0, 0.0), DamageResult::Ignored);
+        health.update(HIT_INVULN_TIME);
+        assert_eq!(health.apply_damage(10.0, 0.0), DamageResult::Damaged);
+        assert_eq!(health.hp, 80.0);
+    }
+
+    #[test]
+    fn test_defense_absorbs_damage() {
+        let mut health = Health::new(100.0, 50.0);
+        health.defense = 0.25;
+        assert_eq!(health.apply_damage(40.0, 0.0), DamageResult::Damaged);
+        assert_eq!(health.hp, 70.0);
+    }
+
+    #[test]
+    fn test_poise_break_staggers() {
+        let mut health = Health::new(100.0, 15.0);
+        assert_eq!(health.apply_damage(1.0, 20.0), DamageResult::Staggered);
+        assert!(health.is_staggered());
+        assert_eq!(health.poise, 15.0);
+    }
+
+    #[test]
+    fn test_attack_hits_once_in_active_window() {
+        let level = crate::world::create_test_level();
+        let mut combat = duel();
+        assert!(combat.start_attack(0, 0));
+        assert!(!combat.start_attack(0, 0));
+
+        let mut hits = Vec::new();
+        for _ in 0..30 {
+            hits.extend(combat.update(&level, 1.0 / 30.0));
+        }
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].target, 1);
+        assert_eq!(combat.combatants[1].health.hp, 90.0);
+        assert!(combat.combatants[0].attack.is_none());
+    }
+
+    #[test]
+    fn test_hit_window_follows_animation_events() {
+        let level = crate::world::create_test_level();
+        let mut combat = duel();
+        combat.start_attack(0, 0);
+        combat.set_hit_window(0, false);
+        // Deep into the default active window, but closed by the event
+        for _ in 0..6 {
+            assert!(combat.update(&level, 1.0 / 15.0).is_empty());
+        }
+        combat.set_hit_window(0, true);
+        assert_eq!(combat.update(&level, 1.0 / 15.0).len(), 1);
+    }
+
+    #[test]
+    fn test_walls_stop_melee_hits() {
+        // Target just outside the test room, in reach but behind its wall
+        let level = crate::world::create_test_level();
+        let mut combat = duel();
+        combat.combatants[0].position = Vec3::new(512.0, 0.0, 900.0);
+        combat.combatants[1].position = Vec3::new(512.0, 0.0, 1100.0);
+        combat.start_attack(0, 0);
+        for _ in 0..30 {
+            assert!(combat.update(&level, 1.0 / 30.0).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_attack_window_from_animation_events() {
+        let mut anim = Animation::new("swing");
+        anim.fps = 30;
+        anim.set_keyframe(crate::modeler::Keyframe::new(20, 0));
+        anim.add_event(9, EVENT_HIT_END);
+        anim.add_event(6, EVENT_HIT_START);
+
+        let def = AttackDef::from_animation("swing", &anim);
+        assert_eq!((def.active_start, def.active_end, def.total_frames), (6, 9, 20));
+        assert!(def.is_active_frame(7));
+        assert!(!def.is_active_frame(10));
+    }
+
+    #[test]
+    fn test_hitscan_blocked_by_walls() {
+        // Both inside the test room, facing each other along Z
+        let level = crate::world::create_test_level();
+        let mut combat = duel();
+        combat.combatants[0].position = Vec3::new(512.0, 0.0, 200.0);
+        combat.combatants[1].position = Vec3::new(512.0, 0.0, 800.0);
+        let hit = combat.hitscan(&level, 0, 0, Vec3::new(0.0, 0.0, 1.0), 2000.0).unwrap();
+        assert_eq!(hit.target, 1);
+        assert_eq!(combat.combatants[1].health.hp, 90.0);
+
+        // Target outside the room: the wall takes the shot
+        combat.combatants[1].position = Vec3::new(512.0, 0.0, 1600.0);
+        combat.combatants[1].health.update(HIT_INVULN_TIME);
+        assert!(combat.hitscan(&level, 0, 0, Vec3::new(0.0, 0.0, 1.0), 2000.0).is_none());
+        assert!(combat.hitscan(&level, 0, 0, Vec3::new(0.0, 0.0, -1.0), 2000.0).is_none());
+    }
+
+    #[test]
+    fn test_rotated_volume_bounds() {
+        let volume = HitVolume::new(Vec3::new(0.0, 0.0, 100.0), Vec3::new(10.0, 10.0, 10.0));
+        let aabb = volume.world_aabb(Vec3::ZERO, std::f32::consts::FRAC_PI_2);
+        assert!((aabb.center().x - 100.0).abs() < 0.01);
+        assert!(aabb.center().z.abs() < 0.01);
+    }
+}
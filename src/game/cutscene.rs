@@ -0,0 +1,37 @@
+//! Cutscene playback - drives the camera along a level camera path
+
+use crate::rasterizer::Camera;
+use crate::world::Level;
+
+/// Plays one camera path on a camera
+#[derive(Debug, Clone)]
+pub struct CutscenePlayer {
+    /// Index into `Level::camera_paths`
+    pub path: usize,
+    /// Seconds since start
+    pub time: f32,
+}
+
+impl CutscenePlayer {
+    pub fn start(path: usize) -> Self {
+        Self { path, time: 0.0 }
+    }
+
+    /// Start the path bound to a trigger name, if any
+    pub fn from_trigger(level: &Level, trigger: &str) -> Option<Self> {
+        level.find_camera_path_by_trigger(trigger).map(Self::start)
+    }
+
+    /// Advance playback and pose the camera. Returns false once finished.
+    pub fn update(&mut self, dt: f32, level: &Level, camera: &mut Camera) -> bool {
+        let Some(path) = level.camera_paths.get(self.path) else { return false };
+        let Some((position, target)) = path.sample(self.time) else { return false };
+
+        camera.position = position;
+        camera.look_at(target);
+
+        let playing = self.time < path.duration();
+        self.time += dt;
+        playing
+    }
+}
@@ -0,0 +1,276 @@
+//! Enemy AI - regular enemies walking the navigation graph
+//!
+//! An `enemy` entity's `behavior` property picks how it moves:
+//! - idle: stands where it was placed
+//! - patrol: walks the patrol loop (see `Level::patrol_route`) starting at
+//!   the patrol waypoint nearest to it
+//! - chase: heads for the player while it can see them (within
+//!   `ENEMY_SIGHT_RANGE`, nothing in the way), and back to where it was
+//!   placed otherwise
+//!
+//! Enemies with a `ranged` attack shoot the player on sight instead of
+//! closing in (see `Combat::hitscan`).
+//!
+//! An enemy whose `model` property names a model (see `MODELS_DIR`) is drawn
+//! with it, at the model's detail level for its distance. The model's state
+//! machine runs in an `AnimationPlayer`, sent "idle", "walk" and "attack" as
+//! the enemy stands, moves and strikes; a clip in its "attack" state times
+//! the enemy's hits.
+//!
+//! Paths come from the level's `NavGraph`; enemies walk them at their
+//! `speed` times `ENEMY_WALK_SPEED`. Simulation holds them still while they
+//! attack or are staggered.
+
+use crate::modeler::AnimationPlayer;
+use crate::rasterizer::Vec3;
+use crate::world::{line_of_sight, Entity, EntityRef, Level, NavGraph, WaypointRef};
+use super::{ENEMY_ARCHETYPE, PLAYER_EYE_HEIGHT};
+
+/// Walking speed of an enemy with `speed` 1.0 (units per second)
+pub const ENEMY_WALK_SPEED: f32 = 1024.0;
+
+/// How far a chasing enemy notices the player (four sectors)
+pub const ENEMY_SIGHT_RANGE: f32 = 4096.0;
+
+/// Signals sent to an enemy model's state machine
+pub const SIGNAL_IDLE: &str = "idle";
+pub const SIGNAL_WALK: &str = "walk";
+pub const SIGNAL_ATTACK: &str = "attack";
+
+/// Steps between path searches
+const REPATH_STEPS: u32 = 30;
+
+/// How close to a path point counts as reaching it
+const ARRIVE_DISTANCE: f32 = 32.0;
+
+/// How an enemy moves when it isn't attacking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyBehavior {
+    Idle,
+    Patrol,
+    Chase,
+}
+
+impl EnemyBehavior {
+    /// From the entity's `behavior` property (patrol when unset)
+    pub fn of(entity: &Entity) -> Self {
+        match entity.get("behavior").and_then(|v| v.as_str()) {
+            Some("idle") => Self::Idle,
+            Some("chase") => Self::Chase,
+            _ => Self::Patrol,
+        }
+    }
+}
+
+/// Movement state of one regular enemy
+#[derive(Debug, Clone)]
+pub struct Enemy {
+    pub entity: EntityRef,
+    pub behavior: EnemyBehavior,
+    /// Where it was placed (world space, at its feet)
+    pub home: Vec3,
+    /// Units per second
+    pub speed: f32,
+    /// Shoots from afar instead of swinging up close
+    pub ranged: bool,
+    /// Name of the model it's drawn with
+    pub model: Option<String>,
+    /// Runs the model's state machine (once the model is loaded)
+    pub anim: Option<AnimationPlayer>,
+    /// Patrol loop, world space (empty without patrol waypoints)
+    route: Vec<Vec3>,
+    /// Patrol point being walked to
+    next: usize,
+    /// Path being followed, next point first
+    path: Vec<Vec3>,
+    /// Steps until the path is searched again
+    repath: u32,
+}
+
+impl Enemy {
+    pub fn new(level: &Level, entity: EntityRef, home: Vec3) -> Self {
+        let e = level.entity(entity);
+        let behavior = e.map_or(EnemyBehavior::Idle, EnemyBehavior::of);
+        let speed = e.and_then(|e| e.get("speed")).and_then(|v| v.as_float()).unwrap_or(1.0).max(0.0);
+        let ranged = e.and_then(|e| e.get("attack")).and_then(|v| v.as_str()) == Some("ranged");
+        let model = e.and_then(|e| e.get("model")).and_then(|v| v.as_str()).filter(|m| !m.is_empty()).map(str::to_string);
+        let route = match behavior {
+            EnemyBehavior::Patrol => nearest_patrol_waypoint(level, home)
+                .map(|start| level.patrol_route(start).into_iter().filter_map(|r| level.waypoint_world_pos(r)).collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        Self {
+            entity,
+            behavior,
+            home,
+            speed: speed * ENEMY_WALK_SPEED,
+            ranged,
+            model,
+            anim: None,
+            route,
+            next: 0,
+            path: Vec::new(),
+            repath: 0,
+        }
+    }
+
+    /// Whether an enemy with its feet at `position` can see the player's
+    /// feet at `player`
+    pub fn sees(level: &Level, position: Vec3, player: Vec3) -> bool {
+        let eye = Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+        (player - position).len() <= ENEMY_SIGHT_RANGE && line_of_sight(level, position + eye, player + eye)
+    }
+
+    /// Where an enemy standing at `position` walks to in `dt` seconds, with
+    /// the player's feet at `player`
+    pub fn step(&mut self, level: &Level, nav: &NavGraph, position: Vec3, player: Vec3, dt: f32) -> Vec3 {
+        let goal = match self.behavior {
+            EnemyBehavior::Idle => None,
+            EnemyBehavior::Patrol => self.route.get(self.next).copied(),
+            EnemyBehavior::Chase if Self::sees(level, position, player) => Some(player),
+            EnemyBehavior::Chase => Some(self.home),
+        };
+        let Some(goal) = goal else { return position };
+
+        self.repath = self.repath.saturating_sub(1);
+        if self.repath == 0 {
+            self.path = nav.find_path(position, goal).unwrap_or_default();
+            self.repath = REPATH_STEPS;
+            // The search starts at the nearest node, which may already be behind
+            while self.path.len() >= 2 && (self.path[0] - position).dot(self.path[1] - self.path[0]) <= 0.0 {
+                self.path.remove(0);
+            }
+        }
+
+        let mut position = position;
+        let mut budget = self.speed * dt;
+        while let Some(&target) = self.path.first() {
+            let to = target - position;
+            let distance = Vec3::new(to.x, 0.0, to.z).len();
+            if distance <= ARRIVE_DISTANCE {
+                self.path.remove(0);
+                if self.path.is_empty() && !self.route.is_empty() {
+                    // On to the next patrol point
+                    self.next = (self.next + 1) % self.route.len();
+                    self.repath = 0;
+                }
+                continue;
+            }
+            if budget <= 0.0 {
+                break;
+            }
+            let t = (budget / distance).min(1.0);
+            position = position + to * t;
+            budget -= distance * t;
+        }
+        position
+    }
+}
+
+/// Names of the models a level's enemies are drawn with, each once
+pub fn enemy_models(level: &Level) -> Vec<String> {
+    let mut names: Vec<String> = level.entities_of(ENEMY_ARCHETYPE)
+        .filter_map(|(_, e)| e.get("model").and_then(|v| v.as_str()))
+        .filter(|m| !m.is_empty())
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// The patrol waypoint closest to a world position
+fn nearest_patrol_waypoint(level: &Level, position: Vec3) -> Option<WaypointRef> {
+    level.rooms.iter().enumerate()
+        .flat_map(|(room, r)| (0..r.waypoints.len()).map(move |i| WaypointRef::new(room, i)))
+        .filter(|r| level.waypoint(*r).is_some_and(|wp| wp.patrol))
+        .filter_map(|r| Some((r, (level.waypoint_world_pos(r)? - position).len())))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(r, _)| r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{Direction, PropertyValue, Room, TextureRef};
+
+    /// A corridor of `len` sectors along X with an enemy at its start
+    fn corridor(len: usize, behavior: &str) -> Level {
+        let mut level = Level::new();
+        let mut room = Room::new(0, Vec3::ZERO, len, 1);
+        for x in 0..len {
+            room.set_floor(x, 0, 0.0, TextureRef::none());
+        }
+        level.add_room(room);
+        let mut enemy = Entity {
+            archetype: "enemy".to_string(),
+            position: Vec3::new(512.0, 0.0, 512.0),
+            properties: Default::default(),
+        };
+        enemy.set("behavior", PropertyValue::Text(behavior.to_string()));
+        level.add_entity(0, enemy);
+        level
+    }
+
+    #[test]
+    fn test_chasing_enemy_walks_toward_the_player() {
+        let level = corridor(4, "chase");
+        let nav = NavGraph::build(&level);
+        let mut enemy = Enemy::new(&level, EntityRef::new(0, 0), Vec3::new(512.0, 0.0, 512.0));
+        let mut position = enemy.home;
+        for _ in 0..60 {
+            position = enemy.step(&level, &nav, position, Vec3::new(3584.0, 0.0, 512.0), 1.0 / 60.0);
+        }
+        assert!((position.x - 1536.0).abs() < 1.0, "x = {}", position.x);
+
+        // Out of sight: back home
+        for _ in 0..120 {
+            position = enemy.step(&level, &nav, position, Vec3::new(1e5, 0.0, 512.0), 1.0 / 60.0);
+        }
+        assert!((position - enemy.home).len() < ARRIVE_DISTANCE);
+    }
+
+    #[test]
+    fn test_walls_block_sight() {
+        let mut level = corridor(4, "chase");
+        let (from, to) = (Vec3::new(512.0, 0.0, 512.0), Vec3::new(3584.0, 0.0, 512.0));
+        assert!(Enemy::sees(&level, from, to));
+        level.rooms[0].add_wall(1, 0, Direction::East, 0.0, 1024.0, TextureRef::none());
+        level.rooms[0].recalculate_bounds();
+        assert!(!Enemy::sees(&level, from, to));
+    }
+
+    #[test]
+    fn test_patrolling_enemy_loops_its_route() {
+        let mut level = corridor(4, "patrol");
+        let a = level.add_waypoint(0, Vec3::new(512.0, 0.0, 512.0)).unwrap();
+        let b = level.add_waypoint(0, Vec3::new(3584.0, 0.0, 512.0)).unwrap();
+        level.toggle_waypoint_link(a, b);
+        for r in [a, b] {
+            level.waypoint_mut(r).unwrap().patrol = true;
+        }
+        let nav = NavGraph::build(&level);
+        let mut enemy = Enemy::new(&level, EntityRef::new(0, 0), Vec3::new(512.0, 0.0, 512.0));
+        let mut position = enemy.home;
+        let mut furthest: f32 = 0.0;
+        for _ in 0..300 {
+            position = enemy.step(&level, &nav, position, Vec3::ZERO, 1.0 / 60.0);
+            furthest = furthest.max(position.x);
+        }
+        assert!(furthest > 3500.0);
+        assert!(position.x < 3000.0, "x = {}", position.x);
+    }
+
+    #[test]
+    fn test_enemies_name_their_models_once() {
+        let mut level = corridor(2, "idle");
+        level.rooms[0].entities[0].set("model", PropertyValue::Text("grunt".to_string()));
+        let mut second = level.rooms[0].entities[0].clone();
+        second.position = Vec3::new(1536.0, 0.0, 512.0);
+        level.add_entity(0, second);
+        assert_eq!(enemy_models(&level), vec!["grunt".to_string()]);
+        let enemy = Enemy::new(&level, EntityRef::new(0, 1), Vec3::ZERO);
+        assert_eq!(enemy.model.as_deref(), Some("grunt"));
+    }
+}
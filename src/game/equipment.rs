@@ -0,0 +1,280 @@
+//! Equipment - weapon and armor definitions
+//!
+//! The project's gear lives in `assets/project/equipment.ron`, edited in the
+//! editor's Project panel (Equipment page):
+//! - a weapon has base damage, a speed (1.0 = one swing every
+//!   `BASE_SWING_TIME`), a moveset of attack clips on its model and the stat
+//!   levels needed to wield it
+//! - armor absorbs a fraction of incoming damage and adds poise
+//! - gear used without its requirements works at `UNDERLEVELLED_SCALE`
+//!
+//! In play, Q cycles weapons and 4 cycles armor while resting at a
+//! checkpoint. What's equipped is saved with the player's stats.
+//!
+//! ```ron
+//! (
+//!     weapons: [
+//!         (name: "Broadsword", model: "models/broadsword.ron", moveset: ["slash", "thrust"],
+//!          damage: 30.0, poise_damage: 15.0, speed: 1.0, requirements: (strength: 2)),
+//!     ],
+//!     armor: [
+//!         (name: "Chainmail", model: "models/chainmail.ron", defense: 0.25, poise: 20.0,
+//!          requirements: (vigor: 3)),
+//!     ],
+//! )
+//! ```
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use super::{AttackDef, PlayerStats, Progression, Stat};
+
+/// Location of the project's equipment definitions
+pub const EQUIPMENT_PATH: &str = "assets/project/equipment.ron";
+
+/// Seconds per swing of a weapon with speed 1.0
+pub const BASE_SWING_TIME: f32 = 0.6;
+
+/// Damage and defense of gear used without its stat requirements
+pub const UNDERLEVELLED_SCALE: f32 = 0.5;
+
+/// Most damage armor can absorb
+pub const MAX_DEFENSE: f32 = 0.9;
+
+/// Stat levels needed to use a piece of gear at full strength
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Requirements {
+    pub vigor: u32,
+    pub endurance: u32,
+    pub strength: u32,
+}
+
+impl Requirements {
+    pub fn get(&self, stat: Stat) -> u32 {
+        match stat {
+            Stat::Vigor => self.vigor,
+            Stat::Endurance => self.endurance,
+            Stat::Strength => self.strength,
+        }
+    }
+
+    pub fn get_mut(&mut self, stat: Stat) -> &mut u32 {
+        match stat {
+            Stat::Vigor => &mut self.vigor,
+            Stat::Endurance => &mut self.endurance,
+            Stat::Strength => &mut self.strength,
+        }
+    }
+
+    pub fn met_by(&self, stats: &PlayerStats) -> bool {
+        Stat::ALL.iter().all(|s| stats.get(*s) >= self.get(*s))
+    }
+
+    /// Multiplier on the gear's numbers for a player with `stats`
+    pub fn scale(&self, stats: &PlayerStats) -> f32 {
+        if self.met_by(stats) { 1.0 } else { UNDERLEVELLED_SCALE }
+    }
+}
+
+/// A weapon the player can wield
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeaponDef {
+    pub name: String,
+    /// Modeler model drawn in the player's hand
+    #[serde(default)]
+    pub model: String,
+    /// Attack animation clips on the model, chained by repeated swings
+    #[serde(default)]
+    pub moveset: Vec<String>,
+    pub damage: f32,
+    #[serde(default)]
+    pub poise_damage: f32,
+    /// Swing rate relative to `BASE_SWING_TIME`
+    pub speed: f32,
+    #[serde(default)]
+    pub requirements: Requirements,
+}
+
+impl WeaponDef {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            model: String::new(),
+            moveset: vec!["slash".to_string()],
+            damage: 25.0,
+            poise_damage: 10.0,
+            speed: 1.0,
+            requirements: Requirements::default(),
+        }
+    }
+
+    /// Seconds before the wielder can swing again
+    pub fn swing_time(&self) -> f32 {
+        BASE_SWING_TIME / self.speed.max(0.1)
+    }
+
+    /// Damage of one hit, scaled by the wielder's strength
+    pub fn attack_damage(&self, stats: &PlayerStats, progression: &Progression) -> f32 {
+        self.damage * stats.value(Stat::Strength, progression) * self.requirements.scale(stats)
+    }
+
+    /// The moveset as combat attacks: damage from the weapon, timelines
+    /// sped up or slowed down to its speed
+    pub fn attacks(&self, stats: &PlayerStats, progression: &Progression) -> Vec<AttackDef> {
+        let damage = self.attack_damage(stats, progression);
+        let poise_damage = self.poise_damage * self.requirements.scale(stats);
+        self.moveset
+            .iter()
+            .map(|clip| {
+                let mut def = AttackDef::new(clip);
+                def.fps = (def.fps as f32 * self.speed).round().clamp(1.0, 60.0) as u8;
+                def.damage = damage;
+                def.poise_damage = poise_damage;
+                def
+            })
+            .collect()
+    }
+}
+
+/// A set of armor the player can wear
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmorDef {
+    pub name: String,
+    /// Modeler model worn by the player
+    #[serde(default)]
+    pub model: String,
+    /// Fraction of incoming damage absorbed (0.0 - `MAX_DEFENSE`)
+    pub defense: f32,
+    /// Poise added to the player's own
+    #[serde(default)]
+    pub poise: f32,
+    #[serde(default)]
+    pub requirements: Requirements,
+}
+
+impl ArmorDef {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            model: String::new(),
+            defense: 0.1,
+            poise: 10.0,
+            requirements: Requirements::default(),
+        }
+    }
+
+    /// Fraction of damage absorbed for a wearer with `stats`
+    pub fn defense_for(&self, stats: &PlayerStats) -> f32 {
+        self.defense.clamp(0.0, MAX_DEFENSE) * self.requirements.scale(stats)
+    }
+}
+
+/// What the player has equipped (by name)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loadout {
+    pub weapon: Option<String>,
+    pub armor: Option<String>,
+}
+
+/// The project's weapons and armor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Equipment {
+    pub weapons: Vec<WeaponDef>,
+    pub armor: Vec<ArmorDef>,
+}
+
+impl Equipment {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read equipment: {}", e))?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse equipment: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize equipment: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn weapon(&self, name: &str) -> Option<&WeaponDef> {
+        self.weapons.iter().find(|w| w.name == name)
+    }
+
+    pub fn armor(&self, name: &str) -> Option<&ArmorDef> {
+        self.armor.iter().find(|a| a.name == name)
+    }
+
+    /// A new game starts with the first weapon and armor
+    pub fn starting_loadout(&self) -> Loadout {
+        Loadout {
+            weapon: self.weapons.first().map(|w| w.name.clone()),
+            armor: self.armor.first().map(|a| a.name.clone()),
+        }
+    }
+
+    /// The weapon after `current` (wrapping, None when there are none)
+    pub fn next_weapon(&self, current: Option<&str>) -> Option<String> {
+        let names: Vec<&str> = self.weapons.iter().map(|w| w.name.as_str()).collect();
+        next_name(&names, current)
+    }
+
+    /// The armor after `current` (wrapping, None when there is none)
+    pub fn next_armor(&self, current: Option<&str>) -> Option<String> {
+        let names: Vec<&str> = self.armor.iter().map(|a| a.name.as_str()).collect();
+        next_name(&names, current)
+    }
+}
+
+fn next_name(names: &[&str], current: Option<&str>) -> Option<String> {
+    let next = match current.and_then(|c| names.iter().position(|n| *n == c)) {
+        Some(i) => (i + 1) % names.len(),
+        None => 0,
+    };
+    names.get(next).map(|n| n.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requirements_scale_weapon_damage() {
+        let progression = Progression::default();
+        let mut sword = WeaponDef::new("Sword");
+        sword.damage = 40.0;
+        sword.requirements.strength = 2;
+        let mut stats = PlayerStats::default();
+        assert_eq!(sword.attack_damage(&stats, &progression), 20.0);
+
+        stats.strength = 2;
+        let damage = sword.attack_damage(&stats, &progression);
+        assert!((damage - 48.0).abs() < 1e-4);
+
+        sword.speed = 2.0;
+        let attacks = sword.attacks(&stats, &progression);
+        assert_eq!(attacks.len(), 1);
+        assert_eq!(attacks[0].fps, 30);
+        assert!((sword.swing_time() - BASE_SWING_TIME / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cycling_and_round_trip() {
+        let mut equipment = Equipment::default();
+        assert_eq!(equipment.next_weapon(None), None);
+        equipment.weapons.push(WeaponDef::new("Dagger"));
+        equipment.weapons.push(WeaponDef::new("Club"));
+        equipment.armor.push(ArmorDef::new("Leather"));
+        assert_eq!(equipment.next_weapon(Some("Dagger")).as_deref(), Some("Club"));
+        assert_eq!(equipment.next_weapon(Some("Club")).as_deref(), Some("Dagger"));
+        assert_eq!(equipment.next_armor(Some("Gone")).as_deref(), Some("Leather"));
+        assert_eq!(equipment.starting_loadout().weapon.as_deref(), Some("Dagger"));
+
+        let ron = ron::to_string(&equipment).unwrap();
+        assert_eq!(Equipment::from_ron(&ron).unwrap(), equipment);
+    }
+}
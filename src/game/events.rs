@@ -0,0 +1,114 @@
+//! Event scheduler - runs a level's event timeline in play mode
+//!
+//! Every event (`world::LevelEvent`) has its own clock:
+//! - events started by the level begin counting at the first step
+//! - events on a channel wait until a trigger fires it (the scheduler is
+//!   handed the channels `LogicState` saw fire)
+//! - an event runs once, when its clock passes its delay
+//!
+//! The scheduler only changes state in fixed simulation steps, so replays
+//! run the same timeline.
+
+use std::collections::HashSet;
+use crate::world::{entity_wave, Entity, EventAction, Level};
+use super::LogicState;
+
+/// Play-mode state of a level's events
+#[derive(Debug, Clone, Default)]
+pub struct EventScheduler {
+    /// Seconds since each event started (None = waiting for its channel)
+    clocks: Vec<Option<f32>>,
+    /// Events that already ran
+    done: Vec<bool>,
+    /// Waves spawned so far
+    waves: HashSet<i32>,
+    /// Music cues started since the runtime last drained them (see `MusicPlayer`)
+    pub music_cues: Vec<i32>,
+}
+
+impl EventScheduler {
+    pub fn new(level: &Level) -> Self {
+        Self {
+            clocks: level.events.iter().map(|e| e.channel.is_none().then_some(0.0)).collect(),
+            done: vec![false; level.events.len()],
+            waves: HashSet::new(),
+            music_cues: Vec::new(),
+        }
+    }
+
+    /// Start the events waiting on `fired` channels, advance every clock by
+    /// `dt` and run the events that are due. Returns how many ran.
+    pub fn update(&mut self, level: &Level, logic: &mut LogicState, fired: &[i32], dt: f32) -> usize {
+        let mut ran = 0;
+        for (i, event) in level.events.iter().enumerate() {
+            if self.done.get(i).copied().unwrap_or(true) {
+                continue;
+            }
+            let clock = &mut self.clocks[i];
+            if clock.is_none() && event.channel.is_some_and(|c| fired.contains(&c)) {
+                *clock = Some(0.0);
+            }
+            let Some(elapsed) = clock.as_mut() else {
+                continue;
+            };
+            *elapsed += dt;
+            if *elapsed < event.delay {
+                continue;
+            }
+            self.done[i] = true;
+            ran += 1;
+            match event.action {
+                EventAction::SpawnWave(wave) => {
+                    self.waves.insert(wave);
+                }
+                EventAction::OpenGate(channel) => {
+                    logic.open_channel(level, channel);
+                }
+                EventAction::MusicCue(cue) => self.music_cues.push(cue),
+            }
+        }
+        ran
+    }
+
+    /// Whether an entity is in play (not waiting for its wave)
+    pub fn is_spawned(&self, entity: &Entity) -> bool {
+        entity_wave(entity).is_none_or(|wave| self.waves.contains(&wave))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::game::DOOR_ARCHETYPE;
+    use crate::world::{create_test_level, LevelEvent, PropertyValue, WAVE_PROPERTY};
+
+    #[test]
+    fn test_timed_and_triggered_events_run_once() {
+        let mut level = create_test_level();
+        let mut door = Entity { archetype: DOOR_ARCHETYPE.to_string(), position: Vec3::ZERO, properties: Default::default() };
+        door.set("channel", PropertyValue::Int(3));
+        let door = level.add_entity(0, door).unwrap();
+        let mut enemy = Entity { archetype: "enemy".to_string(), position: Vec3::ZERO, properties: Default::default() };
+        enemy.set(WAVE_PROPERTY, PropertyValue::Int(1));
+        level.events = vec![
+            LevelEvent { channel: None, delay: 2.0, action: EventAction::SpawnWave(1) },
+            LevelEvent { channel: Some(5), delay: 0.5, action: EventAction::OpenGate(3) },
+        ];
+
+        let mut logic = LogicState::default();
+        let mut events = EventScheduler::new(&level);
+        assert_eq!(events.update(&level, &mut logic, &[], 1.0), 0);
+        assert!(!events.is_spawned(&enemy));
+        assert_eq!(events.update(&level, &mut logic, &[], 1.0), 1);
+        assert!(events.is_spawned(&enemy));
+
+        // The gate waits for its channel, then its delay
+        assert_eq!(events.update(&level, &mut logic, &[], 10.0), 0);
+        assert_eq!(events.update(&level, &mut logic, &[5], 0.25), 0);
+        assert_eq!(events.update(&level, &mut logic, &[], 0.25), 1);
+        logic.update(&level, 10.0);
+        assert!(logic.offset(door).y > 0.0);
+        assert_eq!(events.update(&level, &mut logic, &[5], 10.0), 0);
+    }
+}
@@ -0,0 +1,698 @@
+//! Front end - title screen and main menu a packaged game boots into
+//!
+//! Flow: Title ("Press Start") -> Main menu (Continue / New Game / Options / Quit)
+//! -> first level. With `memory_card` on, Continue opens a PS1-style Memory
+//! Card screen instead: two cards of save blocks with icons, where Enter
+//! loads a block, C copies it to the other card and X deletes it. Configured
+//! per project through a RON file:
+//!
+//! ```ron
+//! (
+//!     title: "Bonnie",
+//!     subtitle: "A PS1-style adventure",
+//!     first_level: "assets/levels/level_001.ron",
+//! )
+//! ```
+//!
+//! Drawing reuses the landing page palette so menus match the rest of the app.
+//! Menu text comes from the project's game text (`menu.*` and `options.*`
+//! keys) in the language picked on the Options screen.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::landing::{ACCENT_COLOR, BG_COLOR, MUTED_COLOR, SECTION_BG, TEXT_COLOR};
+use crate::rasterizer::{RasterSettings, Resolution};
+use crate::ui::Rect;
+use super::{GameRng, GameText, MemoryCard, SaveGame, MEMORY_CARD_BLOCKS};
+
+/// Default location of the project's front-end config
+pub const FRONT_END_CONFIG_PATH: &str = "assets/project/front_end.ron";
+
+/// Per-project front-end configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontEndConfig {
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: String,
+    /// Level loaded by "New Game"
+    pub first_level: PathBuf,
+    /// Show the "Continue" entry (greyed out when there is no save)
+    #[serde(default = "default_true")]
+    pub show_continue: bool,
+    /// Save to memory card blocks, picked on the Memory Card screen
+    #[serde(default)]
+    pub memory_card: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for FrontEndConfig {
+    fn default() -> Self {
+        Self {
+            title: "Bonnie Engine".to_string(),
+            subtitle: String::new(),
+            first_level: PathBuf::from("assets/levels/level_001.ron"),
+            show_continue: true,
+            memory_card: false,
+        }
+    }
+}
+
+impl FrontEndConfig {
+    /// Load a config from a RON file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read front-end config: {}", e))?;
+        ron::from_str(&contents).map_err(|e| format!("Failed to parse front-end config: {}", e))
+    }
+}
+
+/// Player-facing options edited from the Options screen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameOptions {
+    pub low_resolution: bool,
+    /// 16:9 instead of 4:3
+    #[serde(default)]
+    pub widescreen: bool,
+    /// Music volume (0.0 - 1.0)
+    pub music_volume: f32,
+    /// CRT post-processing filter
+    pub crt: bool,
+    /// Sharp-bilinear upscale shader for the final blit (nearest when off)
+    #[serde(default)]
+    pub sharp_bilinear: bool,
+    /// PGXP-style smooth geometry: no vertex snapping, perspective-correct textures
+    #[serde(default)]
+    pub smooth_geometry: bool,
+    /// Language column of the project's game text
+    #[serde(default)]
+    pub language: usize,
+    /// Controller rumble
+    #[serde(default = "default_true")]
+    pub vibration: bool,
+}
+
+impl GameOptions {
+    /// Framebuffer resolution picked by the options
+    pub fn resolution(&self) -> Resolution {
+        Resolution::standard(self.low_resolution, self.widescreen)
+    }
+
+    /// Turn the PS1 wobble (vertex snap, affine textures) off in smooth mode
+    pub fn apply_geometry(&self, settings: &mut RasterSettings) {
+        settings.vertex_snap = !self.smooth_geometry;
+        settings.affine_textures = !self.smooth_geometry;
+    }
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self {
+            low_resolution: true,
+            widescreen: false,
+            music_volume: 0.8,
+            crt: false,
+            sharp_bilinear: false,
+            smooth_geometry: false,
+            language: 0,
+            vibration: true,
+        }
+    }
+}
+
+/// Which front-end screen is showing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontEndScreen {
+    Title,
+    Main,
+    Options,
+    MemoryCard,
+}
+
+/// Main menu entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Continue,
+    NewGame,
+    Options,
+    Quit,
+}
+
+impl MenuItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Continue => "Continue",
+            MenuItem::NewGame => "New Game",
+            MenuItem::Options => "Options",
+            MenuItem::Quit => "Quit",
+        }
+    }
+
+    /// Game text key of the label
+    pub fn key(&self) -> &'static str {
+        match self {
+            MenuItem::Continue => "menu.continue",
+            MenuItem::NewGame => "menu.new_game",
+            MenuItem::Options => "menu.options",
+            MenuItem::Quit => "menu.quit",
+        }
+    }
+}
+
+/// Options screen rows
+const OPTION_ROWS: usize = 9;
+
+/// Blocks per row on the Memory Card screen
+const CARD_COLUMNS: usize = 3;
+
+/// What the game should do after a front-end frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontEndAction {
+    None,
+    NewGame(PathBuf),
+    Continue,
+    /// Memory card (slot) and block to load, copy to the other card or delete
+    LoadBlock(usize, usize),
+    CopyBlock(usize, usize),
+    DeleteBlock(usize, usize),
+    Quit,
+}
+
+/// Front-end state machine
+pub struct FrontEnd {
+    pub config: FrontEndConfig,
+    pub screen: FrontEndScreen,
+    pub selected: usize,
+    /// A save exists to continue from
+    pub can_continue: bool,
+    pub options: GameOptions,
+    /// The project's game text, in `options.language`
+    pub text: GameText,
+    /// Memory cards shown on the Memory Card screen, and the one browsed
+    pub cards: Vec<MemoryCard>,
+    pub card: usize,
+    /// X was pressed once on the selected block
+    confirm_delete: bool,
+    /// Time on the current screen (drives the "Press Start" blink)
+    time: f32,
+}
+
+impl FrontEnd {
+    pub fn new(config: FrontEndConfig, can_continue: bool) -> Self {
+        let mut front_end = Self {
+            config,
+            screen: FrontEndScreen::Title,
+            selected: 0,
+            can_continue,
+            options: GameOptions::default(),
+            text: GameText::default(),
+            cards: Vec::new(),
+            card: 0,
+            confirm_delete: false,
+            time: 0.0,
+        };
+        front_end.selected = front_end.default_selection();
+        front_end
+    }
+
+    /// Entries shown on the main menu
+    pub fn menu_items(&self) -> Vec<MenuItem> {
+        let mut items = Vec::new();
+        if self.config.show_continue {
+            items.push(MenuItem::Continue);
+        }
+        items.push(MenuItem::NewGame);
+        items.push(MenuItem::Options);
+        // Browsers can't quit the page
+        #[cfg(not(target_arch = "wasm32"))]
+        items.push(MenuItem::Quit);
+        items
+    }
+
+    fn is_enabled(&self, item: MenuItem) -> bool {
+        item != MenuItem::Continue || self.can_continue
+    }
+
+    /// Continue if there's a save, otherwise New Game
+    fn default_selection(&self) -> usize {
+        let items = self.menu_items();
+        items.iter().position(|i| self.is_enabled(*i)).unwrap_or(0)
+    }
+
+    fn row_count(&self) -> usize {
+        match self.screen {
+            FrontEndScreen::Title => 0,
+            FrontEndScreen::Main => self.menu_items().len(),
+            FrontEndScreen::Options => OPTION_ROWS,
+            FrontEndScreen::MemoryCard => MEMORY_CARD_BLOCKS,
+        }
+    }
+
+    /// Save in the selected memory card block, if any
+    pub fn selected_block(&self) -> Option<&SaveGame> {
+        self.cards.get(self.card)?.blocks.get(self.selected)?.as_ref()
+    }
+
+    /// Move the cursor up/down, wrapping and skipping disabled entries
+    pub fn navigate(&mut self, delta: i32) {
+        let count = self.row_count();
+        if count == 0 {
+            return;
+        }
+        // Blocks are a grid: up/down moves a row
+        if self.screen == FrontEndScreen::MemoryCard {
+            self.selected = (self.selected as i32 + delta * CARD_COLUMNS as i32).rem_euclid(count as i32) as usize;
+            self.confirm_delete = false;
+            return;
+        }
+        let items = self.menu_items();
+        let mut idx = self.selected;
+        for _ in 0..count {
+            idx = (idx as i32 + delta).rem_euclid(count as i32) as usize;
+            let enabled = self.screen != FrontEndScreen::Main || self.is_enabled(items[idx]);
+            if enabled {
+                self.selected = idx;
+                return;
+            }
+        }
+    }
+
+    /// Adjust the highlighted option (left/right on the Options screen), or
+    /// move between blocks and cards on the Memory Card screen
+    pub fn adjust(&mut self, delta: i32) {
+        if self.screen == FrontEndScreen::MemoryCard {
+            let column = self.selected % CARD_COLUMNS;
+            let cards = self.cards.len().max(1);
+            if delta < 0 && column == 0 {
+                self.card = (self.card + cards - 1) % cards;
+                self.selected += CARD_COLUMNS - 1;
+            } else if delta > 0 && column == CARD_COLUMNS - 1 {
+                self.card = (self.card + 1) % cards;
+                self.selected -= CARD_COLUMNS - 1;
+            } else {
+                self.selected = (self.selected as i32 + delta.signum()) as usize;
+            }
+            self.confirm_delete = false;
+            return;
+        }
+        if self.screen != FrontEndScreen::Options {
+            return;
+        }
+        match self.selected {
+            0 => self.options.low_resolution = !self.options.low_resolution,
+            1 => self.options.widescreen = !self.options.widescreen,
+            2 => {
+                self.options.music_volume = (self.options.music_volume + delta as f32 * 0.1).clamp(0.0, 1.0);
+            }
+            3 => self.options.crt = !self.options.crt,
+            4 => self.options.sharp_bilinear = !self.options.sharp_bilinear,
+            5 => self.options.smooth_geometry = !self.options.smooth_geometry,
+            6 => {
+                let count = self.text.languages.len().max(1) as i32;
+                self.options.language = (self.options.language as i32 + delta).rem_euclid(count) as usize;
+            }
+            7 => self.options.vibration = !self.options.vibration,
+            _ => {}
+        }
+    }
+
+    /// Activate the highlighted entry
+    pub fn confirm(&mut self) -> FrontEndAction {
+        match self.screen {
+            FrontEndScreen::Title => {
+                self.set_screen(FrontEndScreen::Main);
+                FrontEndAction::None
+            }
+            FrontEndScreen::Main => {
+                let Some(item) = self.menu_items().get(self.selected).copied() else {
+                    return FrontEndAction::None;
+                };
+                if !self.is_enabled(item) {
+                    return FrontEndAction::None;
+                }
+                match item {
+                    MenuItem::Continue if self.config.memory_card => {
+                        self.set_screen(FrontEndScreen::MemoryCard);
+                        FrontEndAction::None
+                    }
+                    MenuItem::Continue => FrontEndAction::Continue,
+                    MenuItem::NewGame => FrontEndAction::NewGame(self.config.first_level.clone()),
+                    MenuItem::Options => {
+                        self.set_screen(FrontEndScreen::Options);
+                        FrontEndAction::None
+                    }
+                    MenuItem::Quit => FrontEndAction::Quit,
+                }
+            }
+            FrontEndScreen::Options => {
+                if self.selected == OPTION_ROWS - 1 {
+                    self.back();
+                } else {
+                    self.adjust(1);
+                }
+                FrontEndAction::None
+            }
+            FrontEndScreen::MemoryCard => match self.selected_block() {
+                Some(_) => FrontEndAction::LoadBlock(self.card, self.selected),
+                None => FrontEndAction::None,
+            },
+        }
+    }
+
+    /// Copy the selected block to the next card
+    pub fn copy_block(&mut self) -> FrontEndAction {
+        match self.selected_block() {
+            Some(_) => FrontEndAction::CopyBlock(self.card, self.selected),
+            None => FrontEndAction::None,
+        }
+    }
+
+    /// Delete the selected block, on the second press
+    pub fn delete_block(&mut self) -> FrontEndAction {
+        if self.selected_block().is_none() {
+            return FrontEndAction::None;
+        }
+        if !std::mem::replace(&mut self.confirm_delete, true) {
+            return FrontEndAction::None;
+        }
+        self.confirm_delete = false;
+        FrontEndAction::DeleteBlock(self.card, self.selected)
+    }
+
+    /// Go back one screen
+    pub fn back(&mut self) {
+        match self.screen {
+            FrontEndScreen::Title => {}
+            FrontEndScreen::Main => self.set_screen(FrontEndScreen::Title),
+            FrontEndScreen::Options => {
+                self.set_screen(FrontEndScreen::Main);
+                // Land back on the Options entry
+                if let Some(i) = self.menu_items().iter().position(|i| *i == MenuItem::Options) {
+                    self.selected = i;
+                }
+            }
+            FrontEndScreen::MemoryCard => self.set_screen(FrontEndScreen::Main),
+        }
+    }
+
+    /// Switch screens, resetting the cursor
+    pub fn set_screen(&mut self, screen: FrontEndScreen) {
+        self.screen = screen;
+        self.time = 0.0;
+        self.confirm_delete = false;
+        self.selected = if screen == FrontEndScreen::Main { self.default_selection() } else { 0 };
+    }
+
+    /// Game text of a key in the player's language, or `fallback`
+    pub fn tr<'a>(&'a self, key: &str, fallback: &'a str) -> &'a str {
+        self.text.text(key, self.options.language, fallback)
+    }
+
+    /// Handle keyboard input for this frame
+    pub fn update(&mut self, dt: f32) -> FrontEndAction {
+        self.time += dt;
+
+        if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W) {
+            self.navigate(-1);
+        }
+        if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S) {
+            self.navigate(1);
+        }
+        if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A) {
+            self.adjust(-1);
+        }
+        if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D) {
+            self.adjust(1);
+        }
+        if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Backspace) {
+            self.back();
+        }
+        if self.screen == FrontEndScreen::MemoryCard {
+            if is_key_pressed(KeyCode::C) {
+                return self.copy_block();
+            }
+            if is_key_pressed(KeyCode::X) || is_key_pressed(KeyCode::Delete) {
+                return self.delete_block();
+            }
+        }
+        let start = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space);
+        if start || (self.screen == FrontEndScreen::Title && is_mouse_button_pressed(MouseButton::Left)) {
+            return self.confirm();
+        }
+        FrontEndAction::None
+    }
+
+    /// Draw the current screen
+    pub fn draw(&self, rect: Rect) {
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
+
+        let center_x = rect.x + rect.w * 0.5;
+        let title_y = (rect.y + rect.h * 0.3).round();
+        draw_centered(&self.config.title, center_x, title_y, 48.0, ACCENT_COLOR);
+        if !self.config.subtitle.is_empty() {
+            draw_centered(&self.config.subtitle, center_x, title_y + 32.0, 18.0, MUTED_COLOR);
+        }
+
+        let rows: Vec<(String, bool)> = match self.screen {
+            FrontEndScreen::Title => {
+                // Blink at ~1Hz
+                if (self.time * 2.0) as i32 % 2 == 0 {
+                    draw_centered(self.tr("menu.press_start", "Press Start"), center_x, (rect.y + rect.h * 0.65).round(), 20.0, TEXT_COLOR);
+                }
+                return;
+            }
+            FrontEndScreen::Main => self
+                .menu_items()
+                .iter()
+                .map(|i| (self.tr(i.key(), i.label()).to_string(), self.is_enabled(*i)))
+                .collect(),
+            FrontEndScreen::Options => {
+                let on_off = |on: bool| if on { self.tr("options.on", "On") } else { self.tr("options.off", "Off") };
+                vec![
+                    (format!("{}: {}", self.tr("options.resolution", "Resolution"), if self.options.low_resolution { "240p" } else { "480i" }), true),
+                    (format!("{}: {}", self.tr("options.aspect", "Aspect"), self.options.resolution().aspect_label()), true),
+                    (format!("{}: {:.0}%", self.tr("options.music_volume", "Music Volume"), self.options.music_volume * 100.0), true),
+                    (format!("{}: {}", self.tr("options.crt", "CRT Filter"), on_off(self.options.crt)), true),
+                    (format!("{}: {}", self.tr("options.upscale", "Upscale"), if self.options.sharp_bilinear { "Sharp Bilinear" } else { "Nearest" }), true),
+                    (format!("{}: {}", self.tr("options.geometry", "Geometry"), if self.options.smooth_geometry { "Smooth" } else { "PS1" }), true),
+                    (format!("{}: {}", self.tr("options.language", "Language"), self.text.language_code(self.options.language)), true),
+                    (format!("{}: {}", self.tr("options.vibration", "Vibration"), on_off(self.options.vibration)), true),
+                    (self.tr("options.back", "Back").to_string(), true),
+                ]
+            }
+            FrontEndScreen::MemoryCard => {
+                self.draw_memory_cards(rect);
+                return;
+            }
+        };
+
+        let row_h = 30.0;
+        let panel_w = 260.0;
+        let panel_h = rows.len() as f32 * row_h + 16.0;
+        let panel_x = (center_x - panel_w * 0.5).round();
+        let panel_y = (rect.y + rect.h * 0.5).round();
+        draw_rectangle(panel_x, panel_y, panel_w, panel_h, SECTION_BG);
+
+        for (i, (label, enabled)) in rows.iter().enumerate() {
+            let y = panel_y + 8.0 + i as f32 * row_h;
+            let selected = i == self.selected;
+            if selected {
+                draw_rectangle(panel_x, y, 3.0, row_h, ACCENT_COLOR);
+            }
+            let color = if !enabled {
+                Color::new(0.35, 0.35, 0.38, 1.0)
+            } else if selected {
+                ACCENT_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            draw_centered(label, center_x, y + row_h * 0.5 + 6.0, 18.0, color);
+        }
+    }
+}
+
+impl FrontEnd {
+    /// The cards side by side as grids of block icons, with the selected
+    /// block's details and the controls below
+    fn draw_memory_cards(&self, rect: Rect) {
+        let center_x = rect.x + rect.w * 0.5;
+        let cell = 44.0;
+        let card_w = cell * CARD_COLUMNS as f32 + 16.0;
+        let rows = MEMORY_CARD_BLOCKS.div_ceil(CARD_COLUMNS);
+        let card_h = cell * rows as f32 + 40.0;
+        let gap = 32.0;
+        let total_w = card_w * self.cards.len() as f32 + gap * self.cards.len().saturating_sub(1) as f32;
+        let top = (rect.y + rect.h * 0.4).round();
+
+        for (c, card) in self.cards.iter().enumerate() {
+            let x = (center_x - total_w * 0.5 + c as f32 * (card_w + gap)).round();
+            draw_rectangle(x, top, card_w, card_h, SECTION_BG);
+            let label = format!("{} {}  ({}/{})", self.tr("menu.memory_card", "Memory Card"), c + 1, card.used(), MEMORY_CARD_BLOCKS);
+            draw_centered(&label, x + card_w * 0.5, top + 22.0, 16.0, if c == self.card { ACCENT_COLOR } else { MUTED_COLOR });
+            for (b, block) in card.blocks.iter().enumerate() {
+                let bx = x + 8.0 + (b % CARD_COLUMNS) as f32 * cell;
+                let by = top + 32.0 + (b / CARD_COLUMNS) as f32 * cell;
+                draw_rectangle(bx + 2.0, by + 2.0, cell - 4.0, cell - 4.0, BG_COLOR);
+                if let Some(save) = block {
+                    draw_block_icon(save, bx + 6.0, by + 6.0, 2.0);
+                }
+                // The cursor blinks like the BIOS's
+                if c == self.card && b == self.selected && (self.time * 3.0) as i32 % 3 != 0 {
+                    draw_rectangle_lines(bx + 1.0, by + 1.0, cell - 2.0, cell - 2.0, 2.0, ACCENT_COLOR);
+                }
+            }
+        }
+
+        let info_y = top + card_h + 28.0;
+        let info = match self.selected_block() {
+            Some(save) => {
+                let level = save.level.file_stem().map_or(String::new(), |s| s.to_string_lossy().to_string());
+                format!("{}  -  {}  Lv {}", self.config.title, level, save.stats.level())
+            }
+            None => self.tr("memory_card.free", "Free block").to_string(),
+        };
+        draw_centered(&info, center_x, info_y, 18.0, TEXT_COLOR);
+        let hint = if self.confirm_delete {
+            self.tr("memory_card.confirm_delete", "Press X again to delete this block")
+        } else {
+            self.tr("memory_card.controls", "Enter Load   C Copy   X Delete   Esc Back")
+        };
+        draw_centered(hint, center_x, info_y + 26.0, 16.0, MUTED_COLOR);
+    }
+}
+
+/// A save block's 16x16 icon: a mirrored pattern in two colors picked from
+/// its level, so saves in different places tell apart at a glance
+fn draw_block_icon(save: &SaveGame, x: f32, y: f32, pixel: f32) {
+    let seed = save.level.to_string_lossy().bytes().fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+    let mut rng = GameRng::new(seed);
+    let mut color = || Color::from_rgba(80 + (rng.next_u32() % 176) as u8, 80 + (rng.next_u32() % 176) as u8, 80 + (rng.next_u32() % 176) as u8, 255);
+    let (a, b) = (color(), color());
+    for row in 0..16 {
+        for col in 0..8 {
+            let bits = rng.next_u32() % 3;
+            if bits == 0 {
+                continue;
+            }
+            let c = if bits == 1 { a } else { b };
+            for px in [col, 15 - col] {
+                draw_rectangle(x + px as f32 * pixel, y + row as f32 * pixel, pixel, pixel, c);
+            }
+        }
+    }
+}
+
+fn draw_centered(text: &str, center_x: f32, y: f32, size: f32, color: Color) {
+    let dims = measure_text(text, None, size as u16, 1.0);
+    draw_text(text, (center_x - dims.width * 0.5).round(), y, size, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continue_skipped_without_save() {
+        let mut fe = FrontEnd::new(FrontEndConfig::default(), false);
+        assert_eq!(fe.confirm(), FrontEndAction::None);
+        assert_eq!(fe.screen, FrontEndScreen::Main);
+        assert_eq!(fe.menu_items()[fe.selected], MenuItem::NewGame);
+
+        // Wrapping upwards never lands on the disabled Continue entry
+        let count = fe.menu_items().len();
+        for _ in 0..count * 2 {
+            fe.navigate(-1);
+            assert_ne!(fe.menu_items()[fe.selected], MenuItem::Continue);
+        }
+    }
+
+    #[test]
+    fn test_new_game_returns_first_level() {
+        let config = FrontEndConfig { first_level: PathBuf::from("intro.ron"), ..Default::default() };
+        let mut fe = FrontEnd::new(config, true);
+        fe.confirm();
+        assert_eq!(fe.menu_items()[fe.selected], MenuItem::Continue);
+        fe.navigate(1);
+        assert_eq!(fe.confirm(), FrontEndAction::NewGame(PathBuf::from("intro.ron")));
+    }
+
+    #[test]
+    fn test_options_back_returns_to_menu() {
+        let mut fe = FrontEnd::new(FrontEndConfig::default(), true);
+        fe.confirm();
+        fe.navigate(1);
+        fe.navigate(1);
+        assert_eq!(fe.confirm(), FrontEndAction::None);
+        assert_eq!(fe.screen, FrontEndScreen::Options);
+        fe.confirm();
+        assert!(!fe.options.low_resolution);
+        fe.back();
+        assert_eq!(fe.menu_items()[fe.selected], MenuItem::Options);
+    }
+
+    #[test]
+    fn test_widescreen_option_picks_16_9() {
+        let mut fe = FrontEnd::new(FrontEndConfig::default(), true);
+        fe.set_screen(FrontEndScreen::Options);
+        fe.selected = 1;
+        fe.confirm();
+        assert_eq!(fe.options.resolution().size(), (426, 240));
+        assert_eq!(fe.options.resolution().aspect_label(), "16:9");
+    }
+
+    #[test]
+    fn test_smooth_geometry_disables_wobble() {
+        let mut fe = FrontEnd::new(FrontEndConfig::default(), true);
+        fe.set_screen(FrontEndScreen::Options);
+        fe.selected = 5;
+        fe.confirm();
+        let mut settings = RasterSettings::default();
+        fe.options.apply_geometry(&mut settings);
+        assert!(!settings.vertex_snap && !settings.affine_textures);
+    }
+
+    #[test]
+    fn test_language_option_switches_menu_text() {
+        let mut fe = FrontEnd::new(FrontEndConfig::default(), true);
+        fe.text.add_language("it");
+        let row = fe.text.add_row("menu.quit");
+        fe.text.set(row, 1, "Esci");
+        assert_eq!(fe.tr(MenuItem::Quit.key(), MenuItem::Quit.label()), "Quit");
+
+        fe.set_screen(FrontEndScreen::Options);
+        fe.selected = 6;
+        fe.confirm();
+        assert_eq!(fe.options.language, 1);
+        assert_eq!(fe.tr(MenuItem::Quit.key(), MenuItem::Quit.label()), "Esci");
+        fe.adjust(1);
+        assert_eq!(fe.options.language, 0);
+    }
+
+    #[test]
+    fn test_memory_card_navigation_and_delete_confirmation() {
+        let config = FrontEndConfig { memory_card: true, ..Default::default() };
+        let mut fe = FrontEnd::new(config, true);
+        let dir = std::env::temp_dir().join("bonnie_front_end_no_cards");
+        fe.cards = vec![MemoryCard::open(&dir.join("a")), MemoryCard::open(&dir.join("b"))];
+        let save = SaveGame { level: PathBuf::from("a.ron"), checkpoint: 1, stats: Default::default() };
+        fe.cards[1].blocks[2] = Some(save);
+
+        fe.confirm();
+        assert_eq!(fe.confirm(), FrontEndAction::None);
+        assert_eq!(fe.screen, FrontEndScreen::MemoryCard);
+        fe.adjust(-1);
+        assert_eq!((fe.card, fe.selected), (1, 2));
+        fe.navigate(1);
+        fe.navigate(-1);
+        assert_eq!(fe.confirm(), FrontEndAction::LoadBlock(1, 2));
+        assert_eq!(fe.copy_block(), FrontEndAction::CopyBlock(1, 2));
+        assert_eq!(fe.delete_block(), FrontEndAction::None);
+        assert_eq!(fe.delete_block(), FrontEndAction::DeleteBlock(1, 2));
+        fe.adjust(1);
+        assert_eq!((fe.card, fe.selected), (0, 0));
+        assert_eq!(fe.confirm(), FrontEndAction::None);
+    }
+}
@@ -0,0 +1,254 @@
+//! HUD - heads-up display for play mode
+//!
+//! Drawn on top of the blitted framebuffer. Layouts are data-driven (RON)
+//! so they can be reskinned without code changes:
+//!
+//! ```ron
+//! (
+//!     elements: [
+//!         Bar(source: Health, x: 8.0, y: 8.0, w: 96.0, h: 5.0, fill: (200, 40, 40, 255), back: (20, 20, 20, 200)),
+//!         Prompt(x: 160.0, y: 200.0),
+//!     ],
+//! )
+//! ```
+//!
+//! Coordinates are in PS1 pixels (320x240 reference) and snapped to whole
+//! virtual pixels, so the HUD stays as chunky as the scene at any window size.
+//! The project's layout lives in `assets/project/hud.ron`.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::ui::Rect;
+use super::Health;
+
+/// Project HUD layout
+pub const HUD_LAYOUT_PATH: &str = "assets/project/hud.ron";
+
+/// Reference resolution HUD coordinates are authored in
+pub const HUD_REF_WIDTH: f32 = 320.0;
+pub const HUD_REF_HEIGHT: f32 = 240.0;
+
+/// Value a bar displays
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BarSource {
+    Health,
+    Stamina,
+    Boss,
+}
+
+/// One element of a HUD layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HudElement {
+    /// Fill bar (boss bars are hidden when no boss is engaged)
+    Bar {
+        source: BarSource,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        fill: (u8, u8, u8, u8),
+        back: (u8, u8, u8, u8),
+    },
+    /// Row of item slots
+    ItemSlots {
+        x: f32,
+        y: f32,
+        count: usize,
+        size: f32,
+        spacing: f32,
+    },
+    /// Boss name label (drawn above the boss bar)
+    BossName { x: f32, y: f32 },
+    /// Interaction prompt, centered on x ("Open door", "Pick up")
+    Prompt { x: f32, y: f32 },
+}
+
+/// A complete HUD layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HudLayout {
+    pub elements: Vec<HudElement>,
+}
+
+impl HudLayout {
+    /// Parse a layout from a RON string
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse HUD layout: {}", e))
+    }
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self {
+            elements: vec![
+                HudElement::Bar { source: BarSource::Health, x: 8.0, y: 8.0, w: 96.0, h: 5.0, fill: (200, 40, 40, 255), back: (20, 20, 20, 200) },
+                HudElement::Bar { source: BarSource::Stamina, x: 8.0, y: 15.0, w: 72.0, h: 4.0, fill: (60, 170, 60, 255), back: (20, 20, 20, 200) },
+                HudElement::ItemSlots { x: 8.0, y: 204.0, count: 4, size: 28.0, spacing: 3.0 },
+                HudElement::BossName { x: 40.0, y: 212.0 },
+                HudElement::Bar { source: BarSource::Boss, x: 40.0, y: 216.0, w: 240.0, h: 4.0, fill: (170, 30, 30, 255), back: (20, 20, 20, 200) },
+                HudElement::Prompt { x: 160.0, y: 180.0 },
+            ],
+        }
+    }
+}
+
+/// Engaged boss info
+#[derive(Debug, Clone)]
+pub struct BossInfo {
+    pub name: String,
+    pub hp: f32,
+    pub max_hp: f32,
+}
+
+/// Values the HUD displays (filled in by the game each frame)
+#[derive(Debug, Clone, Default)]
+pub struct HudState {
+    pub hp: f32,
+    pub max_hp: f32,
+    pub stamina: f32,
+    pub max_stamina: f32,
+    pub boss: Option<BossInfo>,
+    /// Item names per slot (None = empty slot)
+    pub items: Vec<Option<String>>,
+    pub selected_item: usize,
+    pub prompt: Option<String>,
+}
+
+impl HudState {
+    /// Copy health values from a combatant
+    pub fn set_health(&mut self, health: &Health) {
+        self.hp = health.hp;
+        self.max_hp = health.max_hp;
+    }
+
+    fn bar_fraction(&self, source: BarSource) -> Option<f32> {
+        let (value, max) = match source {
+            BarSource::Health => (self.hp, self.max_hp),
+            BarSource::Stamina => (self.stamina, self.max_stamina),
+            BarSource::Boss => {
+                let boss = self.boss.as_ref()?;
+                (boss.hp, boss.max_hp)
+            }
+        };
+        if max <= 0.0 {
+            return Some(0.0);
+        }
+        Some((value / max).clamp(0.0, 1.0))
+    }
+}
+
+/// Maps HUD reference pixels to screen pixels, snapped to the virtual pixel grid
+struct HudTransform {
+    origin_x: f32,
+    origin_y: f32,
+    pixel: f32,
+}
+
+impl HudTransform {
+    fn new(rect: Rect) -> Self {
+        Self {
+            origin_x: rect.x,
+            origin_y: rect.y,
+            pixel: (rect.w / HUD_REF_WIDTH).min(rect.h / HUD_REF_HEIGHT),
+        }
+    }
+
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (self.origin_x + x.round() * self.pixel).round(),
+            (self.origin_y + y.round() * self.pixel).round(),
+        )
+    }
+
+    fn size(&self, v: f32) -> f32 {
+        (v.round().max(1.0) * self.pixel).round()
+    }
+}
+
+fn to_color(c: (u8, u8, u8, u8)) -> Color {
+    Color::from_rgba(c.0, c.1, c.2, c.3)
+}
+
+/// Draw the HUD over the framebuffer's screen rectangle
+pub fn draw_hud(rect: Rect, layout: &HudLayout, state: &HudState) {
+    let t = HudTransform::new(rect);
+    let font_size = t.size(8.0);
+
+    for element in &layout.elements {
+        match element {
+            HudElement::Bar { source, x, y, w, h, fill, back } => {
+                let Some(fraction) = state.bar_fraction(*source) else { continue };
+                let (sx, sy) = t.point(*x, *y);
+                let (sw, sh) = (t.size(*w), t.size(*h));
+                draw_rectangle(sx, sy, sw, sh, to_color(*back));
+                // Snap fill width to whole virtual pixels
+                let fill_w = ((*w * fraction).floor() * t.pixel).round();
+                draw_rectangle(sx, sy, fill_w, sh, to_color(*fill));
+                draw_rectangle_lines(sx, sy, sw, sh, t.pixel.max(1.0), Color::from_rgba(0, 0, 0, 255));
+            }
+            HudElement::ItemSlots { x, y, count, size, spacing } => {
+                for i in 0..*count {
+                    let (sx, sy) = t.point(*x + i as f32 * (*size + *spacing), *y);
+                    let s = t.size(*size);
+                    draw_rectangle(sx, sy, s, s, Color::from_rgba(10, 10, 15, 180));
+                    let border = if i == state.selected_item {
+                        Color::from_rgba(230, 200, 120, 255)
+                    } else {
+                        Color::from_rgba(90, 90, 100, 255)
+                    };
+                    draw_rectangle_lines(sx, sy, s, s, t.pixel.max(1.0), border);
+
+                    if let Some(Some(item)) = state.items.get(i) {
+                        // Abbreviate to fit the slot until items have icons
+                        let label: String = item.chars().take(3).collect();
+                        draw_text(&label, sx + t.size(3.0), sy + s * 0.5 + font_size * 0.25, font_size, WHITE);
+                    }
+                }
+            }
+            HudElement::BossName { x, y } => {
+                if let Some(boss) = &state.boss {
+                    let (sx, sy) = t.point(*x, *y);
+                    draw_text(&boss.name, sx, sy, font_size, Color::from_rgba(220, 210, 190, 255));
+                }
+            }
+            HudElement::Prompt { x, y } => {
+                if let Some(prompt) = &state.prompt {
+                    let dims = measure_text(prompt, None, font_size as u16, 1.0);
+                    let (cx, sy) = t.point(*x, *y);
+                    let pad = t.size(3.0);
+                    let sx = (cx - dims.width * 0.5).round();
+                    draw_rectangle(sx - pad, sy - dims.height - pad, dims.width + pad * 2.0, dims.height + pad * 2.0, Color::from_rgba(0, 0, 0, 170));
+                    draw_text(prompt, sx, sy, font_size, WHITE);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_layout_round_trips() {
+        let layout = HudLayout::default();
+        let text = ron::to_string(&layout).unwrap();
+        let parsed = HudLayout::from_ron(&text).unwrap();
+        assert_eq!(parsed.elements.len(), layout.elements.len());
+    }
+
+    #[test]
+    fn test_project_layout_is_the_default() {
+        let text = std::fs::read_to_string(HUD_LAYOUT_PATH).unwrap();
+        let layout = HudLayout::from_ron(&text).unwrap();
+        assert_eq!(format!("{:?}", layout), format!("{:?}", HudLayout::default()));
+    }
+
+    #[test]
+    fn test_boss_bar_hidden_without_boss() {
+        let mut state = HudState { hp: 50.0, max_hp: 100.0, ..Default::default() };
+        assert_eq!(state.bar_fraction(BarSource::Health), Some(0.5));
+        assert_eq!(state.bar_fraction(BarSource::Boss), None);
+        state.boss = Some(BossInfo { name: "Warden".to_string(), hp: 300.0, max_hp: 300.0 });
+        assert_eq!(state.bar_fraction(BarSource::Boss), Some(1.0));
+    }
+}
@@ -0,0 +1,240 @@
+//! Level logic - triggers, doors and moving platforms
+//!
+//! Logic entities are plain entities whose archetype names a behavior:
+//! - `trigger`: fires every door and platform on its `channel`
+//!   (`once` triggers only fire the first time)
+//! - `door`: slides up by `lift` when opened, back down when closed again
+//!   (`open` doors start opened, e.g. boss arena gates)
+//! - `platform`: travels up `travel` and back while running
+//!
+//! Doors and platforms move at `speed` units per second. The editor's logic
+//! preview and play mode run the same `LogicState`.
+
+use std::collections::{HashMap, HashSet};
+use crate::rasterizer::Vec3;
+use crate::world::{Entity, EntityRef, Level};
+
+pub const TRIGGER_ARCHETYPE: &str = "trigger";
+pub const DOOR_ARCHETYPE: &str = "door";
+pub const PLATFORM_ARCHETYPE: &str = "platform";
+
+/// Movement speed when an entity has no `speed` property (units per second)
+const DEFAULT_SPEED: f32 = 1024.0;
+
+/// A door or platform in motion (or at rest away from its start)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mover {
+    /// Current height above the placed position
+    offset: f32,
+    /// Height it is heading to
+    target: f32,
+    /// Platforms keep bouncing between 0 and their travel
+    running: bool,
+}
+
+/// Runtime state of a level's logic entities
+#[derive(Debug, Clone, Default)]
+pub struct LogicState {
+    movers: HashMap<EntityRef, Mover>,
+    /// `once` triggers that already fired
+    spent: HashSet<EntityRef>,
+    /// Channels fired since the last `take_fired` (starts level events)
+    fired: Vec<i32>,
+}
+
+impl LogicState {
+    /// Logic state at the start of play: `open` doors are already open
+    pub fn new(level: &Level) -> Self {
+        let mut logic = Self::default();
+        for (r, entity) in level.entities_of(DOOR_ARCHETYPE).filter(|(_, e)| flag(e, "open")) {
+            let lift = number(entity, "lift", 1024.0);
+            logic.movers.insert(r, Mover { offset: lift, target: lift, running: false });
+        }
+        logic
+    }
+
+    /// Fire a logic entity: a trigger fires its channel, a door toggles,
+    /// a platform starts or stops. Returns how many entities reacted.
+    pub fn activate(&mut self, level: &Level, r: EntityRef) -> usize {
+        let Some(entity) = level.entity(r) else {
+            return 0;
+        };
+        match entity.archetype.as_str() {
+            TRIGGER_ARCHETYPE => {
+                if self.spent.contains(&r) {
+                    return 0;
+                }
+                if flag(entity, "once") {
+                    self.spent.insert(r);
+                }
+                self.fire_channel(level, channel(entity))
+            }
+            DOOR_ARCHETYPE => {
+                let lift = number(entity, "lift", 1024.0);
+                let mover = self.movers.entry(r).or_insert(Mover { offset: 0.0, target: 0.0, running: false });
+                mover.target = if mover.target > 0.0 { 0.0 } else { lift };
+                1
+            }
+            PLATFORM_ARCHETYPE => {
+                let travel = number(entity, "travel", 1024.0);
+                let mover = self.movers.entry(r).or_insert(Mover { offset: 0.0, target: 0.0, running: false });
+                mover.running = !mover.running;
+                mover.target = match mover.running {
+                    true if mover.offset >= travel => 0.0,
+                    true => travel,
+                    // Stopping freezes it where it is
+                    false => mover.offset,
+                };
+                1
+            }
+            _ => 0,
+        }
+    }
+
+    /// Fire a channel as a trigger would: every door and platform on it
+    /// reacts, and level events waiting on it start. Returns how many
+    /// entities reacted.
+    pub fn fire_channel(&mut self, level: &Level, fired: i32) -> usize {
+        self.fired.push(fired);
+        let targets: Vec<EntityRef> = [DOOR_ARCHETYPE, PLATFORM_ARCHETYPE]
+            .iter()
+            .flat_map(|a| level.entities_of(a))
+            .filter(|(_, e)| channel(e) == fired)
+            .map(|(target, _)| target)
+            .collect();
+        targets.iter().map(|&t| self.activate(level, t)).sum()
+    }
+
+    /// Open every door on a channel, leaving open ones open. Returns how
+    /// many started opening.
+    pub fn open_channel(&mut self, level: &Level, channel_id: i32) -> usize {
+        self.move_doors(level, channel_id, true)
+    }
+
+    /// Close every door on a channel, leaving closed ones closed. Returns
+    /// how many started closing.
+    pub fn close_channel(&mut self, level: &Level, channel_id: i32) -> usize {
+        self.move_doors(level, channel_id, false)
+    }
+
+    fn move_doors(&mut self, level: &Level, channel_id: i32, open: bool) -> usize {
+        let doors: Vec<(EntityRef, f32)> = level
+            .entities_of(DOOR_ARCHETYPE)
+            .filter(|(_, e)| channel(e) == channel_id)
+            .map(|(r, e)| (r, if open { number(e, "lift", 1024.0) } else { 0.0 }))
+            .collect();
+        let mut moved = 0;
+        for (r, target) in doors {
+            let mover = self.movers.entry(r).or_insert(Mover { offset: 0.0, target: 0.0, running: false });
+            if mover.target != target {
+                mover.target = target;
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    /// Channels triggers fired since the last call
+    pub fn take_fired(&mut self) -> Vec<i32> {
+        std::mem::take(&mut self.fired)
+    }
+
+    /// Move doors and platforms by `dt` seconds
+    pub fn update(&mut self, level: &Level, dt: f32) {
+        for (r, mover) in &mut self.movers {
+            let Some(entity) = level.entity(*r) else {
+                continue;
+            };
+            let step = number(entity, "speed", DEFAULT_SPEED) * dt;
+            let delta = mover.target - mover.offset;
+            if delta.abs() <= step {
+                mover.offset = mover.target;
+                if mover.running {
+                    let travel = number(entity, "travel", 1024.0);
+                    mover.target = if mover.target > 0.0 { 0.0 } else { travel };
+                }
+            } else {
+                mover.offset += step * delta.signum();
+            }
+        }
+    }
+
+    /// How far an entity has moved from where it was placed
+    pub fn offset(&self, r: EntityRef) -> Vec3 {
+        let y = self.movers.get(&r).map_or(0.0, |m| m.offset);
+        Vec3::new(0.0, y, 0.0)
+    }
+}
+
+/// Whether an archetype has logic behavior
+pub fn is_logic_archetype(archetype: &str) -> bool {
+    matches!(archetype, TRIGGER_ARCHETYPE | DOOR_ARCHETYPE | PLATFORM_ARCHETYPE)
+}
+
+fn channel(entity: &Entity) -> i32 {
+    entity.get("channel").and_then(|v| v.as_int()).unwrap_or(0)
+}
+
+fn flag(entity: &Entity, key: &str) -> bool {
+    entity.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+fn number(entity: &Entity, key: &str, default: f32) -> f32 {
+    entity.get(key).and_then(|v| v.as_float()).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, PropertyValue};
+
+    fn entity(archetype: &str, channel: i32) -> Entity {
+        let mut e = Entity { archetype: archetype.to_string(), position: Vec3::ZERO, properties: Default::default() };
+        e.set("channel", PropertyValue::Int(channel));
+        e.set("speed", PropertyValue::Float(512.0));
+        e
+    }
+
+    #[test]
+    fn test_trigger_opens_doors_on_its_channel() {
+        let mut level = create_test_level();
+        let mut trigger = entity(TRIGGER_ARCHETYPE, 1);
+        trigger.set("once", PropertyValue::Bool(true));
+        let trigger = level.add_entity(0, trigger).unwrap();
+        let door = level.add_entity(0, entity(DOOR_ARCHETYPE, 1)).unwrap();
+        let other = level.add_entity(0, entity(DOOR_ARCHETYPE, 2)).unwrap();
+
+        let mut logic = LogicState::default();
+        assert_eq!(logic.activate(&level, trigger), 1);
+        logic.update(&level, 1.0);
+        assert_eq!(logic.offset(door).y, 512.0);
+        assert_eq!(logic.offset(other).y, 0.0);
+        logic.update(&level, 1.0);
+        assert_eq!(logic.offset(door).y, 1024.0);
+        assert!(logic.movers.values().all(|m| !m.running && m.offset == m.target));
+
+        // A spent one-shot trigger does nothing
+        assert_eq!(logic.activate(&level, trigger), 0);
+    }
+
+    #[test]
+    fn test_platform_bounces_while_running() {
+        let mut level = create_test_level();
+        let mut platform = entity(PLATFORM_ARCHETYPE, 0);
+        platform.set("travel", PropertyValue::Float(512.0));
+        let platform = level.add_entity(0, platform).unwrap();
+
+        let mut logic = LogicState::default();
+        logic.activate(&level, platform);
+        logic.update(&level, 1.0);
+        assert_eq!(logic.offset(platform).y, 512.0);
+        logic.update(&level, 0.5);
+        assert_eq!(logic.offset(platform).y, 256.0);
+
+        // Stopping freezes it in place
+        logic.activate(&level, platform);
+        logic.update(&level, 1.0);
+        assert_eq!(logic.offset(platform).y, 256.0);
+        assert!(logic.movers.values().all(|m| !m.running && m.offset == m.target));
+    }
+}
@@ -0,0 +1,200 @@
+//! Loot tables
+//!
+//! Weighted item lists in the project's `assets/project/loot.ron`, edited on
+//! the editor's Project panel (Loot page, with a preview that simulates
+//! rolls). Enemies, bosses and chests name a table in their `loot`
+//! property; the simulation rolls it with the play session's seeded RNG when
+//! the enemy is defeated or the chest opened, so replays drop the same loot.
+//!
+//! Each roll picks one entry by weight (or nothing, by `nothing_weight`).
+//! Items are free-form names: `currency` pays into the player's currency,
+//! anything else (weapon and armor names, pickups) goes to the inventory.
+//!
+//! ```ron
+//! (
+//!     tables: [
+//!         (name: "skeleton", rolls: 1, nothing_weight: 2, entries: [
+//!             (item: "currency", weight: 3, min: 20, max: 60),
+//!             (item: "medipack", weight: 1),
+//!         ]),
+//!     ],
+//! )
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use super::GameRng;
+
+/// Location of the project's loot tables
+pub const LOOT_PATH: &str = "assets/project/loot.ron";
+
+/// Item name that pays into the player's currency
+pub const CURRENCY_ITEM: &str = "currency";
+
+/// Entity archetype opened with Interact to roll its loot once
+pub const CHEST_ARCHETYPE: &str = "chest";
+
+fn one() -> u32 {
+    1
+}
+
+/// One weighted item in a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub item: String,
+    pub weight: u32,
+    /// Amount dropped, picked uniformly in `min..=max`
+    #[serde(default = "one")]
+    pub min: u32,
+    #[serde(default = "one")]
+    pub max: u32,
+}
+
+impl LootEntry {
+    pub fn new(item: &str) -> Self {
+        Self { item: item.to_string(), weight: 1, min: 1, max: 1 }
+    }
+}
+
+/// Items dropped by one roll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDrop {
+    pub item: String,
+    pub amount: u32,
+}
+
+/// A named weighted item list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LootTable {
+    pub name: String,
+    /// Picks made each time the table is rolled
+    #[serde(default = "one")]
+    pub rolls: u32,
+    /// Weight of a pick dropping nothing
+    #[serde(default)]
+    pub nothing_weight: u32,
+    #[serde(default)]
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), rolls: 1, nothing_weight: 0, entries: Vec::new() }
+    }
+
+    fn total_weight(&self) -> u32 {
+        self.nothing_weight + self.entries.iter().map(|e| e.weight).sum::<u32>()
+    }
+
+    /// Make the table's picks
+    pub fn roll(&self, rng: &mut GameRng) -> Vec<ItemDrop> {
+        let total = self.total_weight();
+        if total == 0 {
+            return Vec::new();
+        }
+        let mut drops = Vec::new();
+        for _ in 0..self.rolls {
+            let mut pick = rng.next_u32() % total;
+            let Some(entry) = self.entries.iter().find(|e| {
+                let hit = pick < e.weight;
+                pick = pick.saturating_sub(e.weight);
+                hit
+            }) else {
+                continue;
+            };
+            let (min, max) = (entry.min.min(entry.max), entry.min.max(entry.max));
+            let amount = min + rng.next_u32() % (max - min + 1);
+            if amount > 0 {
+                drops.push(ItemDrop { item: entry.item.clone(), amount });
+            }
+        }
+        drops
+    }
+
+    /// Roll the table `times` times; per item, how many rolls dropped it and
+    /// the total amount
+    pub fn simulate(&self, times: u32, seed: u32) -> BTreeMap<String, (u32, u32)> {
+        let mut rng = GameRng::new(seed);
+        let mut counts: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+        for _ in 0..times {
+            let mut seen = Vec::new();
+            for drop in self.roll(&mut rng) {
+                let count = counts.entry(drop.item.clone()).or_default();
+                if !seen.contains(&drop.item) {
+                    count.0 += 1;
+                    seen.push(drop.item);
+                }
+                count.1 += drop.amount;
+            }
+        }
+        counts
+    }
+}
+
+/// The project's loot tables
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LootTables {
+    pub tables: Vec<LootTable>,
+}
+
+impl LootTables {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read loot tables: {}", e))?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse loot tables: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize loot tables: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn find(&self, name: &str) -> Option<&LootTable> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> LootTable {
+        let mut table = LootTable::new("skeleton");
+        table.nothing_weight = 1;
+        table.entries.push(LootEntry { item: CURRENCY_ITEM.to_string(), weight: 2, min: 10, max: 20 });
+        table.entries.push(LootEntry::new("medipack"));
+        table
+    }
+
+    #[test]
+    fn test_rolls_are_seeded_and_in_range() {
+        let table = table();
+        let (mut a, mut b) = (GameRng::new(5), GameRng::new(5));
+        for _ in 0..50 {
+            let drops = table.roll(&mut a);
+            assert_eq!(drops, table.roll(&mut b));
+            for drop in drops {
+                match drop.item.as_str() {
+                    CURRENCY_ITEM => assert!((10..=20).contains(&drop.amount)),
+                    _ => assert_eq!(drop.amount, 1),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulated_rates_follow_weights() {
+        let counts = table().simulate(4000, 1);
+        let rate = |item: &str| counts.get(item).map_or(0, |c| c.0) as f32 / 4000.0;
+        assert!((rate(CURRENCY_ITEM) - 0.5).abs() < 0.05);
+        assert!((rate("medipack") - 0.25).abs() < 0.05);
+        assert!(LootTable::new("empty").roll(&mut GameRng::new(1)).is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+//! Game runtime
+//!
+//! Play-mode systems that run on top of the level and model data:
+//! - Simulation (fixed-step play-mode world, also run inside the editor viewport)
+//! - Player runtime (stand-alone game loop for exported games, replays, viewer mode)
+//! - Gameplay (combat, enemy AI, bosses, checkpoints, progression, equipment, loot)
+//! - Presentation (HUD, front end, cutscenes, game text, music, room audio, rumble)
+//! - Levels (logic, events, world map, save games, room mesh streaming, budgets)
+
+mod combat;
+mod enemy;
+mod hud;
+mod cutscene;
+mod front_end;
+mod logic;
+mod events;
+mod checkpoint;
+mod boss;
+mod progression;
+mod equipment;
+mod loot;
+mod world_map;
+mod text;
+mod save;
+mod rumble;
+mod simulation;
+mod streaming;
+mod budget;
+mod runtime;
+mod replay;
+mod viewer;
+mod room_audio;
+mod music;
+
+pub use combat::*;
+pub use enemy::*;
+pub use hud::*;
+pub use cutscene::*;
+pub use front_end::*;
+pub use logic::*;
+pub use events::*;
+pub use checkpoint::*;
+pub use boss::*;
+pub use progression::*;
+pub use equipment::*;
+pub use loot::*;
+pub use world_map::*;
+pub use text::*;
+pub use save::*;
+pub use rumble::*;
+pub use simulation::*;
+pub use streaming::*;
+pub use budget::*;
+pub use runtime::*;
+pub use replay::*;
+pub use viewer::*;
+pub use room_audio::*;
+pub use music::*;
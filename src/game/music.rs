@@ -0,0 +1,68 @@
+//! Music playback in play mode
+//!
+//! Plays a tracker song through the tracker's own playback, once the
+//! soundfont has loaded. Each level names its song by id (`Level::music`),
+//! falling back to the manifest's; switching songs keeps the soundfont.
+//! Level events move a song around: `EventAction::MusicCue(n)` jumps to its
+//! n-th cue point, fading out and back in so the switch doesn't cut notes
+//! mid-phrase.
+
+use crate::tracker::{CueSwitch, Song, TrackerState};
+
+/// Plays one song at a time and follows the level's music cues
+pub struct MusicPlayer {
+    tracker: TrackerState,
+    /// A song is waiting for the soundfont to start
+    pending: bool,
+}
+
+impl MusicPlayer {
+    pub fn new() -> Self {
+        Self { tracker: TrackerState::new(), pending: false }
+    }
+
+    /// Parse a song saved as RON
+    pub fn parse_song(s: &str) -> Result<Song, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse song: {}", e))
+    }
+
+    /// Play `song` from the start (or silence with None)
+    pub fn play(&mut self, song: Option<Song>) {
+        match song {
+            Some(song) => {
+                self.tracker.set_song(song);
+                self.pending = true;
+            }
+            None => {
+                self.tracker.stop_playback();
+                self.pending = false;
+            }
+        }
+    }
+
+    /// Start the cues level events fired and advance playback; call once per frame
+    pub fn update(&mut self, cues: impl IntoIterator<Item = i32>, dt: f32) {
+        if !self.pending && !self.tracker.playing {
+            return;
+        }
+        self.tracker.update_soundfont();
+        // Sample-only songs play even without the soundfont
+        if self.pending && !self.tracker.soundfont.is_loading() {
+            self.tracker.play_from_start();
+            self.pending = false;
+        }
+        for cue in cues {
+            let found = usize::try_from(cue).is_ok_and(|i| self.tracker.jump_to_cue(i, CueSwitch::Fade));
+            if !found {
+                log::warn!("Song \"{}\" has no cue {}", self.tracker.song.name, cue);
+            }
+        }
+        self.tracker.update_playback(dt as f64);
+    }
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,220 @@
+//! Stats and progression
+//!
+//! A light RPG layer on top of play mode:
+//! - the player levels three stats: vigor (health), endurance (stamina) and
+//!   strength (attack damage)
+//! - defeating a boss pays its `reward` in currency; dying drops all of it
+//!   where the player last stood in a room, and walking back there recovers
+//!   it (dying again first loses it for good)
+//! - levelling up costs currency and happens at a checkpoint (keys 1-3)
+//!
+//! All numbers come from the project's `assets/project/progression.ron`,
+//! edited in the editor's Project panel:
+//!
+//! ```ron
+//! (
+//!     vigor: (base: 100.0, per_level: 10.0),
+//!     endurance: (base: 100.0, per_level: 8.0),
+//!     strength: (base: 1.0, per_level: 0.1),
+//!     base_cost: 100,
+//!     cost_growth: 1.15,
+//! )
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::Vec3;
+use super::{ItemDrop, Loadout, CURRENCY_ITEM, PLAYER_MAX_HP};
+
+/// Location of the project's progression balance
+pub const PROGRESSION_PATH: &str = "assets/project/progression.ron";
+
+/// How close the player must come to dropped currency to pick it up
+pub const RECOVER_RANGE: f32 = 768.0;
+
+/// A stat the player can level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stat {
+    Vigor,
+    Endurance,
+    Strength,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 3] = [Stat::Vigor, Stat::Endurance, Stat::Strength];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stat::Vigor => "Vigor",
+            Stat::Endurance => "Endurance",
+            Stat::Strength => "Strength",
+        }
+    }
+}
+
+/// What a stat gives at level 0, and per level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatCurve {
+    pub base: f32,
+    pub per_level: f32,
+}
+
+impl StatCurve {
+    pub fn at(&self, level: u32) -> f32 {
+        self.base + self.per_level * level as f32
+    }
+}
+
+/// The project's progression balance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progression {
+    /// Max health
+    pub vigor: StatCurve,
+    /// Max stamina
+    pub endurance: StatCurve,
+    /// Attack damage multiplier
+    pub strength: StatCurve,
+    /// Currency cost of the first level-up
+    pub base_cost: u32,
+    /// Every level-up costs this many times the previous one
+    pub cost_growth: f32,
+}
+
+impl Default for Progression {
+    fn default() -> Self {
+        Self {
+            vigor: StatCurve { base: PLAYER_MAX_HP, per_level: 10.0 },
+            endurance: StatCurve { base: 100.0, per_level: 8.0 },
+            strength: StatCurve { base: 1.0, per_level: 0.1 },
+            base_cost: 100,
+            cost_growth: 1.15,
+        }
+    }
+}
+
+impl Progression {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read progression: {}", e))?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse progression: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize progression: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn curve(&self, stat: Stat) -> &StatCurve {
+        match stat {
+            Stat::Vigor => &self.vigor,
+            Stat::Endurance => &self.endurance,
+            Stat::Strength => &self.strength,
+        }
+    }
+
+    pub fn curve_mut(&mut self, stat: Stat) -> &mut StatCurve {
+        match stat {
+            Stat::Vigor => &mut self.vigor,
+            Stat::Endurance => &mut self.endurance,
+            Stat::Strength => &mut self.strength,
+        }
+    }
+
+    /// Cost of the level-up taking the player past `level` total levels
+    pub fn level_up_cost(&self, level: u32) -> u32 {
+        (self.base_cost as f32 * self.cost_growth.max(1.0).powi(level as i32)).round() as u32
+    }
+}
+
+/// The player's levels, currency, equipped gear and items (saved with the game)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub vigor: u32,
+    pub endurance: u32,
+    pub strength: u32,
+    pub currency: u32,
+    #[serde(default)]
+    pub loadout: Loadout,
+    /// Items picked up, by name
+    #[serde(default)]
+    pub items: BTreeMap<String, u32>,
+}
+
+impl PlayerStats {
+    pub fn get(&self, stat: Stat) -> u32 {
+        match stat {
+            Stat::Vigor => self.vigor,
+            Stat::Endurance => self.endurance,
+            Stat::Strength => self.strength,
+        }
+    }
+
+    /// Take a loot drop: currency is spent on levels, the rest is kept
+    pub fn receive(&mut self, drop: &ItemDrop) {
+        if drop.item == CURRENCY_ITEM {
+            self.currency += drop.amount;
+        } else {
+            *self.items.entry(drop.item.clone()).or_default() += drop.amount;
+        }
+    }
+
+    /// Total levels gained
+    pub fn level(&self) -> u32 {
+        self.vigor + self.endurance + self.strength
+    }
+
+    /// A stat's value at the player's level
+    pub fn value(&self, stat: Stat, progression: &Progression) -> f32 {
+        progression.curve(stat).at(self.get(stat))
+    }
+
+    /// Spend currency on a level of `stat`; false if it can't be afforded
+    pub fn level_up(&mut self, stat: Stat, progression: &Progression) -> bool {
+        let cost = progression.level_up_cost(self.level());
+        if self.currency < cost {
+            return false;
+        }
+        self.currency -= cost;
+        match stat {
+            Stat::Vigor => self.vigor += 1,
+            Stat::Endurance => self.endurance += 1,
+            Stat::Strength => self.strength += 1,
+        }
+        true
+    }
+}
+
+/// Currency the player dropped on dying
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DroppedCurrency {
+    pub position: Vec3,
+    pub amount: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_ups_cost_more_each_time() {
+        let progression = Progression::default();
+        let mut stats = PlayerStats { currency: 250, ..Default::default() };
+        assert!(stats.level_up(Stat::Vigor, &progression));
+        assert_eq!(stats.currency, 150);
+        assert_eq!(progression.level_up_cost(1), 115);
+        assert!(stats.level_up(Stat::Strength, &progression));
+        assert!(!stats.level_up(Stat::Strength, &progression), "35 left, 132 needed");
+        assert_eq!(stats.value(Stat::Vigor, &progression), 110.0);
+        assert!((stats.value(Stat::Strength, &progression) - 1.1).abs() < 1e-5);
+
+        let ron = ron::to_string(&progression).unwrap();
+        assert_eq!(Progression::from_ron(&ron).unwrap(), progression);
+    }
+}
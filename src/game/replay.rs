@@ -0,0 +1,314 @@
+//! Replays - deterministic recording and playback of play sessions
+//!
+//! Play mode runs at a fixed timestep with a seeded RNG, so a session is fully
+//! described by its level, seed and per-step input. Input is stored
+//! run-length encoded, keeping replays small enough to share.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::ui::Rect;
+
+/// Simulation steps per second
+pub const REPLAY_TICK_RATE: u32 = 60;
+/// Fixed timestep in seconds
+pub const FIXED_DT: f32 = 1.0 / REPLAY_TICK_RATE as f32;
+/// Replay file format version
+pub const REPLAY_VERSION: u32 = 1;
+/// Directory replays are saved to
+pub const REPLAY_DIR: &str = "replays";
+
+/// Input buttons (bit flags)
+pub mod button {
+    pub const FORWARD: u16 = 1 << 0;
+    pub const BACK: u16 = 1 << 1;
+    pub const LEFT: u16 = 1 << 2;
+    pub const RIGHT: u16 = 1 << 3;
+    pub const TURN_LEFT: u16 = 1 << 4;
+    pub const TURN_RIGHT: u16 = 1 << 5;
+    pub const LOOK_UP: u16 = 1 << 6;
+    pub const LOOK_DOWN: u16 = 1 << 7;
+    pub const ATTACK: u16 = 1 << 8;
+    pub const INTERACT: u16 = 1 << 9;
+    pub const SKIP: u16 = 1 << 10;
+    pub const LEVEL_VIGOR: u16 = 1 << 11;
+    pub const LEVEL_ENDURANCE: u16 = 1 << 12;
+    pub const LEVEL_STRENGTH: u16 = 1 << 13;
+    pub const CYCLE_WEAPON: u16 = 1 << 14;
+    pub const CYCLE_ARMOR: u16 = 1 << 15;
+}
+
+/// Input state for one simulation step
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFrame(pub u16);
+
+impl InputFrame {
+    /// Read the keyboard
+    pub fn capture() -> Self {
+        let bindings = [
+            (KeyCode::W, button::FORWARD),
+            (KeyCode::S, button::BACK),
+            (KeyCode::A, button::LEFT),
+            (KeyCode::D, button::RIGHT),
+            (KeyCode::Left, button::TURN_LEFT),
+            (KeyCode::Right, button::TURN_RIGHT),
+            (KeyCode::Up, button::LOOK_UP),
+            (KeyCode::Down, button::LOOK_DOWN),
+            (KeyCode::J, button::ATTACK),
+            (KeyCode::E, button::INTERACT),
+            (KeyCode::Enter, button::SKIP),
+            (KeyCode::Key1, button::LEVEL_VIGOR),
+            (KeyCode::Key2, button::LEVEL_ENDURANCE),
+            (KeyCode::Key3, button::LEVEL_STRENGTH),
+            (KeyCode::Q, button::CYCLE_WEAPON),
+            (KeyCode::Key4, button::CYCLE_ARMOR),
+        ];
+        let mut bits = 0;
+        for (key, flag) in bindings {
+            if is_key_down(key) {
+                bits |= flag;
+            }
+        }
+        InputFrame(bits)
+    }
+
+    pub fn held(&self, flag: u16) -> bool {
+        self.0 & flag != 0
+    }
+}
+
+/// Small deterministic RNG (xorshift32) for gameplay randomness
+#[derive(Debug, Clone)]
+pub struct GameRng {
+    state: u32,
+}
+
+impl GameRng {
+    pub fn new(seed: u32) -> Self {
+        // Zero is a fixed point of xorshift
+        Self { state: if seed == 0 { 0x9E37_79B9 } else { seed } }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+}
+
+/// Turns variable frame times into a whole number of fixed steps
+#[derive(Debug, Default)]
+pub struct FixedStep {
+    accumulator: f32,
+}
+
+impl FixedStep {
+    /// Number of fixed steps to run this frame (capped to avoid a spiral of death)
+    pub fn steps(&mut self, frame_dt: f32) -> u32 {
+        self.accumulator = (self.accumulator + frame_dt).min(FIXED_DT * 8.0);
+        let mut steps = 0;
+        while self.accumulator >= FIXED_DT {
+            self.accumulator -= FIXED_DT;
+            steps += 1;
+        }
+        steps
+    }
+}
+
+/// A recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub level: PathBuf,
+    pub seed: u32,
+    /// Run-length encoded input: (step count, buttons)
+    pub input: Vec<(u32, InputFrame)>,
+}
+
+impl Replay {
+    pub fn new(level: PathBuf, seed: u32) -> Self {
+        Self { version: REPLAY_VERSION, level, seed, input: Vec::new() }
+    }
+
+    /// Append one step of input
+    pub fn push(&mut self, frame: InputFrame) {
+        match self.input.last_mut() {
+            Some((count, last)) if *last == frame => *count += 1,
+            _ => self.input.push((1, frame)),
+        }
+    }
+
+    /// Total number of steps
+    pub fn len(&self) -> u32 {
+        self.input.iter().map(|(count, _)| count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    /// Input for a given step
+    pub fn frame(&self, step: u32) -> Option<InputFrame> {
+        let mut start = 0;
+        for (count, frame) in &self.input {
+            if step < start + count {
+                return Some(*frame);
+            }
+            start += count;
+        }
+        None
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let text = ron::to_string(self).map_err(|e| format!("Failed to serialize replay: {}", e))?;
+        std::fs::write(path, text).map_err(|e| format!("Failed to write replay: {}", e))
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        let replay: Replay = ron::from_str(s).map_err(|e| format!("Failed to parse replay: {}", e))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!("Unsupported replay version {}", replay.version));
+        }
+        Ok(replay)
+    }
+}
+
+/// Playback state and controls
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub step: u32,
+    pub paused: bool,
+    /// Index into `PLAYBACK_SPEEDS`
+    pub speed: usize,
+    step_clock: FixedStep,
+    step_once: bool,
+}
+
+/// Playback speed multipliers
+pub const PLAYBACK_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, step: 0, paused: false, speed: 2, step_clock: FixedStep::default(), step_once: false }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.replay.len()
+    }
+
+    /// Handle playback keys: Space pause, Period step, -/= speed
+    pub fn handle_input(&mut self) {
+        if is_key_pressed(KeyCode::Space) {
+            self.paused = !self.paused;
+        }
+        if is_key_pressed(KeyCode::Period) {
+            self.paused = true;
+            self.step_once = true;
+        }
+        if is_key_pressed(KeyCode::Minus) {
+            self.speed = self.speed.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Equal) {
+            self.speed = (self.speed + 1).min(PLAYBACK_SPEEDS.len() - 1);
+        }
+    }
+
+    /// Inputs to simulate this frame
+    pub fn advance(&mut self, frame_dt: f32) -> Vec<InputFrame> {
+        let steps = if self.step_once {
+            self.step_once = false;
+            1
+        } else if self.paused {
+            0
+        } else {
+            self.step_clock.steps(frame_dt * PLAYBACK_SPEEDS[self.speed])
+        };
+
+        let mut frames = Vec::new();
+        for _ in 0..steps {
+            let Some(frame) = self.replay.frame(self.step) else { break };
+            frames.push(frame);
+            self.step += 1;
+        }
+        frames
+    }
+
+    /// Draw the playback controller bar
+    pub fn draw(&self, rect: Rect) {
+        let bar_h = 28.0;
+        let y = rect.bottom() - bar_h;
+        draw_rectangle(rect.x, y, rect.w, bar_h, Color::from_rgba(0, 0, 0, 180));
+
+        let state = if self.is_finished() {
+            "END"
+        } else if self.paused {
+            "PAUSED"
+        } else {
+            "PLAY"
+        };
+        let secs = self.step as f32 * FIXED_DT;
+        let total = self.replay.len() as f32 * FIXED_DT;
+        let label = format!(
+            "REPLAY {}  {:.1}s / {:.1}s  x{}  [Space] pause  [.] step  [-/=] speed",
+            state, secs, total, PLAYBACK_SPEEDS[self.speed]
+        );
+        draw_text(&label, rect.x + 8.0, y + 12.0, 14.0, WHITE);
+
+        // Progress bar
+        let progress = if total > 0.0 { (secs / total).min(1.0) } else { 0.0 };
+        let track_w = rect.w - 16.0;
+        draw_rectangle(rect.x + 8.0, y + 19.0, track_w, 4.0, Color::from_rgba(60, 60, 70, 255));
+        draw_rectangle(rect.x + 8.0, y + 19.0, track_w * progress, 4.0, Color::from_rgba(0, 190, 230, 255));
+    }
+}
+
+/// Path for a new replay file
+pub fn next_replay_path(seed: u32) -> PathBuf {
+    PathBuf::from(REPLAY_DIR).join(format!("replay_{:08x}.ron", seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rle_round_trip() {
+        let mut replay = Replay::new(PathBuf::from("level.ron"), 42);
+        let inputs = [0, 0, 0, button::FORWARD, button::FORWARD, 0];
+        for bits in inputs {
+            replay.push(InputFrame(bits));
+        }
+        assert_eq!(replay.input.len(), 3);
+        assert_eq!(replay.len(), 6);
+
+        let parsed = Replay::from_ron(&ron::to_string(&replay).unwrap()).unwrap();
+        let decoded: Vec<u16> = (0..parsed.len()).map(|i| parsed.frame(i).unwrap().0).collect();
+        assert_eq!(decoded, inputs);
+        assert_eq!(parsed.frame(6), None);
+    }
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = GameRng::new(1234);
+        let mut b = GameRng::new(1234);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn test_fixed_step_accumulates() {
+        let mut clock = FixedStep::default();
+        assert_eq!(clock.steps(FIXED_DT * 0.5), 0);
+        assert_eq!(clock.steps(FIXED_DT * 0.6), 1);
+        // Long hitches are capped
+        assert_eq!(clock.steps(10.0), 8);
+    }
+}
@@ -0,0 +1,224 @@
+//! Room audio playback
+//!
+//! Plays the ambient loop and reverb of the room the camera is in
+//! (`world::RoomAudio`), switching when the camera crosses into a room that
+//! sounds different:
+//! - the loop is a sample instrument held on its own channel; WAVs without a
+//!   loop point loop whole
+//! - the reverb preset maps to `ReverbParams` on the engine's whole mix
+//! - decoded loops are cached by path, and a file that fails to load is
+//!   only reported once
+//! - one-shot sound cues from the simulation (footsteps and other animation
+//!   events) play `assets/sfx/<name>.wav`, quieter further from the camera
+//!   and through the same reverb
+//! - desktop only for now: the web build can't read WAV files synchronously,
+//!   so it gets the reverb but no loops
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::rasterizer::Vec3;
+use crate::tracker::{AudioEngine, ReverbParams, SampleInstrument, SAMPLE_PROGRAM_BASE};
+use crate::world::{ReverbPreset, Room, RoomAudio};
+
+/// Channel the ambient loop plays on (clear of the tracker's song channels)
+const LOOP_CHANNEL: i32 = 15;
+
+/// Sample instrument slot of the loop
+const LOOP_INSTRUMENT: usize = 0;
+
+/// Key the loop is played at (its recorded pitch)
+const LOOP_KEY: u8 = 60;
+
+/// Directory of the sound effects played for sound cues
+pub const SFX_DIR: &str = "assets/sfx";
+
+/// Channels sound cues take turns on (a channel plays one sound at a time)
+const SFX_CHANNELS: i32 = 8;
+
+/// Distance at which a sound cue fades out completely (eight sectors)
+const SFX_RANGE: f32 = 8192.0;
+
+/// A sound to play once, queued by the simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundCue {
+    /// Sound effect name (`assets/sfx/<name>.wav`)
+    pub name: String,
+    /// Where it happened
+    pub position: Vec3,
+}
+
+/// Velocity of a cue heard from `listener` (0 = out of earshot)
+fn cue_velocity(cue: &SoundCue, listener: Vec3) -> i32 {
+    let falloff = 1.0 - (cue.position - listener).len() / SFX_RANGE;
+    (falloff.clamp(0.0, 1.0) * 127.0) as i32
+}
+
+/// Reverb settings of a preset (None = dry)
+pub fn reverb_params(preset: ReverbPreset) -> Option<ReverbParams> {
+    match preset {
+        ReverbPreset::None => None,
+        ReverbPreset::SmallStone => Some(ReverbParams { decay: 0.72, damping: 0.5, wet: 0.25 }),
+        ReverbPreset::Cathedral => Some(ReverbParams { decay: 0.93, damping: 0.25, wet: 0.45 }),
+        ReverbPreset::Outdoors => Some(ReverbParams { decay: 0.35, damping: 0.7, wet: 0.08 }),
+    }
+}
+
+/// Plays room audio in play mode
+pub struct RoomAudioPlayer {
+    engine: AudioEngine,
+    /// Settings being played (None until the first update)
+    playing: Option<RoomAudio>,
+    /// Decoded loops by path (None = failed to load)
+    loops: HashMap<String, Option<Arc<SampleInstrument>>>,
+    /// Sample slot of each sound effect loaded so far (None = failed to load)
+    sounds: HashMap<String, Option<usize>>,
+    /// Channel the next sound cue plays on
+    next_channel: i32,
+}
+
+impl RoomAudioPlayer {
+    pub fn new() -> Self {
+        Self { engine: AudioEngine::new(), playing: None, loops: HashMap::new(), sounds: HashMap::new(), next_channel: 0 }
+    }
+
+    /// Play the frame's sound cues as heard from `listener`
+    pub fn play_cues(&mut self, cues: impl IntoIterator<Item = SoundCue>, listener: Vec3) {
+        for cue in cues {
+            let velocity = cue_velocity(&cue, listener);
+            if velocity == 0 {
+                continue;
+            }
+            let Some(slot) = self.sound_slot(&cue.name) else { continue };
+            let channel = self.next_channel;
+            self.next_channel = (self.next_channel + 1) % SFX_CHANNELS;
+            self.engine.set_program(channel, SAMPLE_PROGRAM_BASE as i32 + slot as i32);
+            self.engine.note_on(channel, LOOP_KEY as i32, velocity);
+        }
+    }
+
+    /// Sample slot of a sound effect, loading it on first use (slot 0 is the loop's)
+    fn sound_slot(&mut self, name: &str) -> Option<usize> {
+        if let Some(slot) = self.sounds.get(name) {
+            return *slot;
+        }
+        let next = LOOP_INSTRUMENT + 1 + self.sounds.values().flatten().count();
+        let slot = load_sound(name).map(|instrument| {
+            self.engine.set_sample_instrument(next, Some(instrument));
+            next
+        });
+        self.sounds.insert(name.to_string(), slot);
+        slot
+    }
+
+    /// Follow the camera's room (None = silence); call once per frame
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused_variables))]
+    pub fn update(&mut self, room: Option<&Room>, dt: f32) {
+        let audio = room.map(|r| r.audio.clone()).unwrap_or_default();
+        if self.playing.as_ref() != Some(&audio) {
+            self.switch_to(&audio);
+            self.playing = Some(audio);
+        }
+        #[cfg(target_arch = "wasm32")]
+        self.engine.render_audio(dt as f64);
+    }
+
+    fn switch_to(&mut self, audio: &RoomAudio) {
+        self.engine.set_room_reverb(reverb_params(audio.reverb));
+        let same_loop = self.playing.as_ref().is_some_and(|p| p.ambient_loop == audio.ambient_loop && p.volume == audio.volume);
+        if same_loop {
+            return;
+        }
+        self.engine.note_off(LOOP_CHANNEL, LOOP_KEY as i32);
+        let Some(instrument) = audio.ambient_loop.as_deref().and_then(|path| self.load(path)) else {
+            return;
+        };
+        self.engine.set_sample_instrument(LOOP_INSTRUMENT, Some(instrument));
+        self.engine.set_program(LOOP_CHANNEL, SAMPLE_PROGRAM_BASE as i32 + LOOP_INSTRUMENT as i32);
+        self.engine.note_on(LOOP_CHANNEL, LOOP_KEY as i32, (audio.volume.clamp(0.0, 1.0) * 127.0) as i32);
+    }
+
+    fn load(&mut self, path: &str) -> Option<Arc<SampleInstrument>> {
+        self.loops.entry(path.to_string()).or_insert_with(|| load_loop(path)).clone()
+    }
+}
+
+impl Default for RoomAudioPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode a WAV as a looping instrument
+#[cfg(not(target_arch = "wasm32"))]
+fn load_loop(path: &str) -> Option<Arc<SampleInstrument>> {
+    use crate::tracker::{load_wav, SampleInstrumentDef};
+    match load_wav(std::path::Path::new(path)) {
+        Ok(data) => {
+            let mut def = SampleInstrumentDef::new(path.to_string(), path.to_string(), &data);
+            def.base_note = LOOP_KEY;
+            def.loop_range = def.loop_range.or(Some((0, data.frames.len())));
+            Some(Arc::new(SampleInstrument { def, data: Arc::new(data) }))
+        }
+        Err(e) => {
+            log::warn!("Room ambient loop not played: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_loop(path: &str) -> Option<Arc<SampleInstrument>> {
+    log::warn!("Room ambient loops aren't supported on the web yet ({})", path);
+    None
+}
+
+/// Decode `assets/sfx/<name>.wav` as a one-shot instrument
+#[cfg(not(target_arch = "wasm32"))]
+fn load_sound(name: &str) -> Option<Arc<SampleInstrument>> {
+    use crate::tracker::{load_wav, SampleInstrumentDef};
+    let path = std::path::Path::new(SFX_DIR).join(format!("{}.wav", name));
+    if !path.exists() {
+        // Events without a sound of their own are silent
+        return None;
+    }
+    match load_wav(&path) {
+        Ok(data) => {
+            let path = path.to_string_lossy().into_owned();
+            let mut def = SampleInstrumentDef::new(name.to_string(), path, &data);
+            def.base_note = LOOP_KEY;
+            Some(Arc::new(SampleInstrument { def, data: Arc::new(data) }))
+        }
+        Err(e) => {
+            log::warn!("Sound effect not played: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_sound(_name: &str) -> Option<Arc<SampleInstrument>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_grow_with_space() {
+        assert!(reverb_params(ReverbPreset::None).is_none());
+        let decay = |p| reverb_params(p).unwrap().decay;
+        assert!(decay(ReverbPreset::Outdoors) < decay(ReverbPreset::SmallStone));
+        assert!(decay(ReverbPreset::SmallStone) < decay(ReverbPreset::Cathedral));
+    }
+
+    #[test]
+    fn test_cues_fade_with_distance() {
+        let cue = SoundCue { name: "footstep".to_string(), position: Vec3::new(0.0, 0.0, 0.0) };
+        assert_eq!(cue_velocity(&cue, Vec3::ZERO), 127);
+        let near = cue_velocity(&cue, Vec3::new(1024.0, 0.0, 0.0));
+        let far = cue_velocity(&cue, Vec3::new(4096.0, 0.0, 0.0));
+        assert!(near > far && far > 0);
+        assert_eq!(cue_velocity(&cue, Vec3::new(SFX_RANGE, 0.0, 0.0)), 0);
+    }
+}
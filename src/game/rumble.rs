@@ -0,0 +1,138 @@
+//! Controller rumble
+//!
+//! The simulation queues a `Rumble` in `Simulation::rumble` when the player
+//! lands a hit, kills something or gets hurt; the runtime hands them to a
+//! `RumblePlayer`, which runs the controller's motors through a
+//! `RumbleMotor` hook. The web build drives the browser's Gamepad API; native
+//! builds have no gamepad backend yet, so a platform layer that gets one sets
+//! `RumblePlayer::motor`. Players can turn vibration off in Options.
+
+/// One burst of vibration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rumble {
+    /// Motor strength (0.0 - 1.0)
+    pub strength: f32,
+    /// Seconds
+    pub duration: f32,
+}
+
+impl Rumble {
+    /// The player's attack hit an enemy or boss
+    pub const HIT: Rumble = Rumble { strength: 0.35, duration: 0.12 };
+    /// The player's attack killed it
+    pub const KILL: Rumble = Rumble { strength: 0.6, duration: 0.25 };
+
+    /// The player lost `fraction` of their max health
+    pub fn hurt(fraction: f32) -> Self {
+        Self { strength: (0.4 + fraction).min(1.0), duration: 0.3 }
+    }
+}
+
+/// Hook to a controller's vibration motors
+pub trait RumbleMotor {
+    /// Run the motors at `strength` (0.0 - 1.0) for `seconds`, replacing
+    /// whatever they were doing
+    fn rumble(&mut self, strength: f32, seconds: f32);
+}
+
+/// Plays the simulation's rumbles on the controller
+pub struct RumblePlayer {
+    pub motor: Option<Box<dyn RumbleMotor>>,
+    /// Vibration option
+    pub enabled: bool,
+    /// The burst playing, and seconds left of it
+    playing: Option<(Rumble, f32)>,
+}
+
+impl RumblePlayer {
+    /// A player on the platform's gamepad, if it has one
+    pub fn new() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let motor: Option<Box<dyn RumbleMotor>> = Some(Box::new(web::GamepadMotor));
+        #[cfg(not(target_arch = "wasm32"))]
+        let motor = None;
+        Self { motor, enabled: true, playing: None }
+    }
+
+    /// Play the frame's rumbles: the strongest wins, and a weaker one doesn't
+    /// cut a stronger burst short
+    pub fn play(&mut self, rumbles: impl IntoIterator<Item = Rumble>) {
+        let Some(rumble) = rumbles.into_iter().max_by(|a, b| a.strength.total_cmp(&b.strength)) else { return };
+        if !self.enabled || self.playing.is_some_and(|(p, _)| p.strength > rumble.strength) {
+            return;
+        }
+        self.playing = Some((rumble, rumble.duration));
+        if let Some(motor) = self.motor.as_mut() {
+            motor.rumble(rumble.strength, rumble.duration);
+        }
+    }
+
+    /// Advance by the frame time
+    pub fn update(&mut self, dt: f32) {
+        if let Some((rumble, left)) = self.playing {
+            self.playing = (left > dt).then_some((rumble, left - dt));
+        }
+    }
+
+    /// Stop the motors (pausing, leaving play)
+    pub fn stop(&mut self) {
+        if self.playing.take().is_some() {
+            if let Some(motor) = self.motor.as_mut() {
+                motor.rumble(0.0, 0.0);
+            }
+        }
+    }
+}
+
+impl Default for RumblePlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use super::RumbleMotor;
+
+    extern "C" {
+        fn bonnie_rumble(strength: f32, seconds: f32);
+    }
+
+    /// First connected gamepad, through the browser's Gamepad API
+    pub struct GamepadMotor;
+
+    impl RumbleMotor for GamepadMotor {
+        fn rumble(&mut self, strength: f32, seconds: f32) {
+            unsafe { bonnie_rumble(strength, seconds) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<f32>>>);
+
+    impl RumbleMotor for Recorder {
+        fn rumble(&mut self, strength: f32, _seconds: f32) {
+            self.0.borrow_mut().push(strength);
+        }
+    }
+
+    #[test]
+    fn test_stronger_bursts_win() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut player = RumblePlayer { motor: Some(Box::new(Recorder(calls.clone()))), enabled: true, playing: None };
+        player.play([Rumble::HIT, Rumble::KILL]);
+        player.play([Rumble::HIT]);
+        player.update(0.3);
+        player.play([Rumble::HIT]);
+        player.stop();
+        player.enabled = false;
+        player.play([Rumble::hurt(1.0)]);
+        assert_eq!(*calls.borrow(), [Rumble::KILL.strength, Rumble::HIT.strength, 0.0]);
+    }
+}
@@ -0,0 +1,863 @@
+//! Player runtime - the stand-alone game loop (no editor UI)
+//!
+//! Boots into the front end, then loads levels and renders them with the
+//! software rasterizer. Used by exported games: the player starts when a
+//! `game.ron` manifest sits next to the executable, or always when built
+//! with the `player` feature (WASM exports).
+//!
+//! Around the `Simulation` it adds replays, save games, level transitions,
+//! music, room mesh streaming and the final blit (see `run_player`).
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::editor::TexturePack;
+use crate::modeler::{model_path, Model};
+use crate::rasterizer::{apply_color_grade, apply_crt, BlendMode, Camera, Color as RasterColor, ColorGrade, Framebuffer, RasterSettings, Vec3, world_to_screen};
+use crate::rasterizer::{HEIGHT, WIDTH};
+use crate::tracker::{song_path, Song};
+use crate::ui::Rect;
+use crate::world::{draw_weather, load_level_from_str, Level, Room};
+use super::{draw_hud, enemy_models, next_replay_path, FrontEnd, FrontEndAction, FrontEndConfig, FrontEndScreen};
+use super::{BossInfo, HudLayout, HudState, HUD_LAYOUT_PATH, InputFrame, PlayerStats, Progression, Replay, ReplayPlayer, MusicPlayer, RoomAudioPlayer, RoomStreamer};
+use super::{Equipment, GameText, LevelLink, LootTables, MemoryCard, RumblePlayer, SaveGame, MEMORY_CARD_DIR, SAVE_GAME_PATH, WorldMap, TEXT_PATH, WORLD_MAP_PATH, Simulation, Stat, DEFAULT_STREAM_HOPS, EQUIPMENT_PATH, FIXED_DT, LOOT_PATH, PROGRESSION_PATH, REPLAY_DIR};
+
+/// Manifest file that marks a directory as an exported game
+pub const GAME_MANIFEST_PATH: &str = "game.ron";
+
+/// Seconds the screen takes to fade out, and back in, between levels
+const TRANSITION_FADE: f32 = 0.4;
+
+/// Exported game description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameManifest {
+    pub name: String,
+    #[serde(default)]
+    pub front_end: FrontEndConfig,
+    /// Levels bundled with the game (relative paths)
+    #[serde(default)]
+    pub levels: Vec<PathBuf>,
+    /// Portal hops around the player's room kept meshed
+    #[serde(default = "default_stream_hops")]
+    pub stream_hops: usize,
+    /// Id of the song played in the front end and in levels naming none
+    #[serde(default)]
+    pub music: Option<String>,
+}
+
+fn default_stream_hops() -> usize {
+    DEFAULT_STREAM_HOPS
+}
+
+impl GameManifest {
+    /// True if an exported game manifest is present (native only)
+    pub fn is_present() -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            std::path::Path::new(GAME_MANIFEST_PATH).exists()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+
+    /// Load the manifest (async so it also works on WASM)
+    pub async fn load() -> Result<Self, String> {
+        let contents = load_string(GAME_MANIFEST_PATH)
+            .await
+            .map_err(|e| format!("Failed to read game manifest: {}", e))?;
+        ron::from_str(&contents).map_err(|e| format!("Failed to parse game manifest: {}", e))
+    }
+}
+
+/// A level being played
+pub(super) struct PlaySession {
+    level_path: PathBuf,
+    pub(super) sim: Simulation,
+    recording: Option<Replay>,
+    playback: Option<ReplayPlayer>,
+    pub(super) streamer: RoomStreamer,
+    /// Where progress is saved (None: it isn't, e.g. the memory cards are full)
+    save_path: Option<PathBuf>,
+    /// Checkpoint and stats last written to the save game
+    saved_checkpoint: Option<i32>,
+    saved_stats: PlayerStats,
+    /// Fade to black on top of the simulation's own (level transitions)
+    pub(super) fade: f32,
+}
+
+impl PlaySession {
+    pub(super) fn new(level: Level, level_path: PathBuf, seed: u32) -> Self {
+        let mut streamer = RoomStreamer::new(DEFAULT_STREAM_HOPS);
+        streamer.level_path = Some(level_path.clone());
+        Self {
+            level_path,
+            sim: Simulation::new(level, seed),
+            recording: None,
+            playback: None,
+            streamer,
+            save_path: Some(PathBuf::from(SAVE_GAME_PATH)),
+            saved_checkpoint: None,
+            saved_stats: PlayerStats::default(),
+            fade: 0.0,
+        }
+    }
+
+    /// Room the camera is in, as last seen by the streamer
+    pub(super) fn camera_room(&self) -> Option<&Room> {
+        self.streamer.current_room().and_then(|r| self.sim.level.rooms.get(r))
+    }
+
+    /// Save the game when a new checkpoint has been reached or the stats
+    /// changed (not while replaying). Returns the checkpoint saved.
+    fn save_progress(&mut self) -> Option<i32> {
+        let changed = |id: &i32| self.saved_checkpoint != Some(*id) || self.saved_stats != self.sim.stats;
+        let saving = self.playback.is_none() && self.save_path.is_some();
+        let id = self.sim.checkpoint.filter(|id| saving && changed(id))?;
+        self.saved_checkpoint = Some(id);
+        self.saved_stats = self.sim.stats.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &self.save_path {
+            let save = SaveGame { level: self.level_path.clone(), checkpoint: id, stats: self.sim.stats.clone() };
+            if let Err(e) = save.save(path) {
+                log::error!("{}", e);
+                return None;
+            }
+        }
+        Some(id)
+    }
+
+    /// Restart the level and record from the first step
+    fn start_recording(&mut self, seed: u32) {
+        let level = std::mem::replace(&mut self.sim.level, Level::new());
+        let hops = self.streamer.hops;
+        let progression = self.sim.progression.clone();
+        let equipment = self.sim.equipment.clone();
+        let loot = self.sim.loot.clone();
+        let models = std::mem::take(&mut self.sim.models);
+        let save_path = self.save_path.take();
+        *self = Self::new(level, self.level_path.clone(), seed);
+        self.streamer.hops = hops;
+        self.save_path = save_path;
+        self.sim.set_progression(progression);
+        self.sim.set_equipment(equipment);
+        self.sim.set_loot(loot);
+        self.sim.set_models(models);
+        self.recording = Some(Replay::new(self.level_path.clone(), seed));
+    }
+
+    /// Restart the level with the replay's seed and play its input back
+    fn start_playback(level: Level, replay: Replay) -> Self {
+        let mut session = Self::new(level, replay.level.clone(), replay.seed);
+        session.playback = Some(ReplayPlayer::new(replay));
+        session
+    }
+
+    /// Advance by the frame time, feeding live or recorded input
+    pub(super) fn update(&mut self, frame_dt: f32) {
+        // Never step the player in a room whose sectors aren't there
+        self.streamer.load_current(&mut self.sim.level, self.sim.camera.position);
+        if let Some(playback) = self.playback.as_mut() {
+            playback.handle_input();
+            for input in playback.advance(frame_dt) {
+                self.sim.step(input);
+            }
+            return;
+        }
+
+        let input = InputFrame::capture();
+        let steps = self.sim.update(frame_dt, input);
+        if let Some(replay) = self.recording.as_mut() {
+            for _ in 0..steps {
+                replay.push(input);
+            }
+        }
+    }
+
+    /// Load the split rooms the next frame needs, before it steps (WASM reads
+    /// them asynchronously)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) async fn fetch_rooms(&mut self) {
+        self.streamer.fetch(&mut self.sim.level, self.sim.camera.position).await;
+    }
+
+    pub(super) fn render(&mut self, fb: &mut Framebuffer, packs: &[TexturePack], settings: &RasterSettings) {
+        fb.begin_field(settings.is_interlaced());
+        fb.clear(RasterColor::new(0, 0, 0));
+
+        self.streamer.update(&mut self.sim.level, self.sim.camera.position, packs);
+        self.streamer.render(fb, &self.sim.level, &self.sim.camera, settings);
+        self.sim.draw_models(fb, &self.sim.camera, settings);
+        if let Some(room) = self.camera_room() {
+            draw_weather(fb, room, &self.sim.camera, get_time() as f32);
+        }
+        if let Some(dropped) = self.sim.dropped {
+            draw_dropped_currency(fb, &self.sim.camera, dropped.position);
+        }
+        self.sim.combat.draw_debug(fb, &self.sim.camera);
+        // Death and level transition fades
+        let keep = 1.0 - self.sim.fade().max(self.fade);
+        if keep < 1.0 {
+            for pixel in fb.pixels.chunks_exact_mut(4) {
+                for c in &mut pixel[..3] {
+                    *c = (*c as f32 * keep) as u8;
+                }
+            }
+        }
+        fb.finish_field(settings.interlace_combing);
+    }
+}
+
+/// Moving to another level: fade out, load it, fade back in
+struct LevelTransition {
+    link: LevelLink,
+    /// Seconds into the current fade
+    elapsed: f32,
+    /// Whether the next level is in (fading back in)
+    arrived: bool,
+}
+
+impl LevelTransition {
+    fn new(link: LevelLink) -> Self {
+        Self { link, elapsed: 0.0, arrived: false }
+    }
+
+    /// How far the screen has faded to black (0.0 - 1.0)
+    fn fade(&self) -> f32 {
+        let t = (self.elapsed / TRANSITION_FADE).min(1.0);
+        if self.arrived { 1.0 - t } else { t }
+    }
+
+    fn finished(&self) -> bool {
+        self.arrived && self.elapsed >= TRANSITION_FADE
+    }
+}
+
+/// Mark dropped currency with a pulsing glow, hidden behind geometry
+fn draw_dropped_currency(fb: &mut Framebuffer, camera: &Camera, position: Vec3) {
+    let depth = (position - camera.position).dot(camera.basis_z);
+    let Some((cx, cy)) = world_to_screen(position, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height).filter(|_| depth > 0.0) else {
+        return;
+    };
+    let radius = 2 + (get_time() * 4.0).sin().abs().round() as i32;
+    let glow = RasterColor::new(120, 200, 90);
+    for y in cy as i32 - radius..=cy as i32 + radius {
+        for x in cx as i32 - radius..=cx as i32 + radius {
+            let inside = x >= 0 && y >= 0 && (x as usize) < fb.width && (y as usize) < fb.height;
+            if inside && depth < fb.zbuffer[y as usize * fb.width + x as usize] {
+                fb.set_pixel_blended(x as usize, y as usize, glow, BlendMode::Add);
+            }
+        }
+    }
+}
+
+/// The level-up menu shown while resting at a checkpoint
+fn draw_level_up(rect: Rect, sim: &Simulation, text: &GameText, language: usize) {
+    let size = (rect.h / 30.0).round().max(12.0);
+    let (x, mut y) = (rect.x + size, rect.y + rect.h * 0.3);
+    let cost = sim.progression.level_up_cost(sim.stats.level());
+    draw_rectangle(x - size * 0.5, y - size * 1.5, size * 16.0, size * 9.0, Color::from_rgba(0, 0, 0, 170));
+    let header = format!("Level {}   Currency {}   Cost {}", sim.stats.level(), sim.stats.currency, cost);
+    draw_text(&header, x, y, size, YELLOW);
+    for (i, stat) in Stat::ALL.iter().enumerate() {
+        y += size * 1.3;
+        let color = if sim.stats.currency >= cost { WHITE } else { GRAY };
+        let line = format!("[{}] {} {}  ({:.1})", i + 1, stat.label(), sim.stats.get(*stat), sim.stats.value(*stat, &sim.progression));
+        draw_text(&line, x, y, size, color);
+    }
+    y += size * 1.3;
+    let weapon = sim.weapon().map_or("Bare hands", |w| text.item_name(&w.name, language));
+    draw_text(&format!("[Q] {}  ({:.0} dmg)", weapon, sim.attack_damage()), x, y, size, WHITE);
+    y += size * 1.3;
+    let armor = sim.armor().map_or("No armor".to_string(), |a| {
+        format!("{}  ({:.0}%)", text.item_name(&a.name, language), a.defense_for(&sim.stats) * 100.0)
+    });
+    draw_text(&format!("[4] {}", armor), x, y, size, WHITE);
+}
+
+/// Screen rectangle the framebuffer is blitted to (aspect-fit and centered,
+/// so 4:3 modes get pillarboxed on wide screens and 16:9 ones letterboxed)
+pub(super) fn blit_rect(fb: &Framebuffer) -> Rect {
+    let (sw, sh) = (screen_width(), screen_height());
+    let scale = (sw / fb.width as f32).min(sh / fb.height as f32);
+    let (w, h) = (fb.width as f32 * scale, fb.height as f32 * scale);
+    Rect::new(((sw - w) * 0.5).round(), ((sh - h) * 0.5).round(), w, h)
+}
+
+const UPSCALE_VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+varying lowp vec2 uv;
+varying lowp vec4 color;
+uniform mat4 Model;
+uniform mat4 Projection;
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+"#;
+
+/// Sharp-bilinear: nearest inside each texel, bilinear only across the
+/// last fraction of a screen pixel at texel edges
+const UPSCALE_FRAGMENT: &str = r#"#version 100
+precision mediump float;
+varying lowp vec2 uv;
+varying lowp vec4 color;
+uniform sampler2D Texture;
+uniform vec2 TextureSize;
+uniform vec2 OutputSize;
+void main() {
+    vec2 texel = uv * TextureSize;
+    vec2 scale = max(floor(OutputSize / TextureSize), vec2(1.0));
+    vec2 region = 0.5 - 0.5 / scale;
+    vec2 center_dist = fract(texel) - 0.5;
+    vec2 f = (center_dist - clamp(center_dist, -region, region)) * scale + 0.5;
+    gl_FragColor = texture2D(Texture, (floor(texel) + f) / TextureSize) * color;
+}
+"#;
+
+/// Compile the sharp-bilinear upscale shader (None if the GPU rejects it,
+/// in which case blits fall back to nearest filtering)
+pub(super) fn load_upscale_material() -> Option<Material> {
+    let params = MaterialParams {
+        uniforms: vec![
+            UniformDesc::new("TextureSize", UniformType::Float2),
+            UniformDesc::new("OutputSize", UniformType::Float2),
+        ],
+        ..Default::default()
+    };
+    let source = ShaderSource::Glsl { vertex: UPSCALE_VERTEX, fragment: UPSCALE_FRAGMENT };
+    load_material(source, params)
+        .map_err(|e| log::warn!("Upscale shader unavailable: {:?}", e))
+        .ok()
+}
+
+/// Draw the framebuffer through the CRT pass (if enabled) and the level's color grade,
+/// upscaled with `upscale` (sharp-bilinear) when given, nearest otherwise
+pub(super) fn blit(fb: &Framebuffer, rect: Rect, settings: &RasterSettings, grade: &ColorGrade, upscale: Option<&Material>) {
+    let texture = if settings.crt.enabled {
+        let (mut pixels, w, h) = apply_crt(fb, &settings.crt);
+        apply_color_grade(&mut pixels, grade);
+        Texture2D::from_rgba8(w as u16, h as u16, &pixels)
+    } else if !grade.is_identity() {
+        let mut pixels = fb.pixels.clone();
+        apply_color_grade(&mut pixels, grade);
+        Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &pixels)
+    } else {
+        Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &fb.pixels)
+    };
+    if let Some(material) = upscale {
+        texture.set_filter(FilterMode::Linear);
+        material.set_uniform("TextureSize", (texture.width(), texture.height()));
+        material.set_uniform("OutputSize", (rect.w, rect.h));
+        gl_use_material(material);
+    } else {
+        texture.set_filter(FilterMode::Nearest);
+    }
+    draw_texture_ex(
+        &texture,
+        rect.x,
+        rect.y,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(macroquad::math::Vec2::new(rect.w, rect.h)),
+            ..Default::default()
+        },
+    );
+    if upscale.is_some() {
+        gl_use_default_material();
+    }
+}
+
+/// Load and parse a level (async so it also works on WASM)
+pub(super) async fn load_level(path: &Path) -> Result<Level, String> {
+    let contents = load_string(&path.to_string_lossy())
+        .await
+        .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+    load_level_from_str(&contents).map_err(|e| format!("Failed to load {}: {}", path.display(), e))
+}
+
+/// Seed for a new recording
+pub(super) fn new_seed() -> u32 {
+    (macroquad::miniquad::date::now() * 1000.0) as u64 as u32
+}
+
+/// The exported game's save, if there is one
+#[cfg(not(target_arch = "wasm32"))]
+fn saved_game() -> Option<SaveGame> {
+    SaveGame::load(Path::new(super::SAVE_GAME_PATH)).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn saved_game() -> Option<SaveGame> {
+    None
+}
+
+/// Whether there's a save to continue from: a memory card block, or the
+/// single save
+fn has_save(front_end: &FrontEnd) -> bool {
+    if front_end.config.memory_card {
+        front_end.cards.iter().any(|c| c.used() > 0)
+    } else {
+        saved_game().is_some()
+    }
+}
+
+/// First free memory card block, as the path to save to
+fn free_block(cards: &[MemoryCard]) -> Option<PathBuf> {
+    cards.iter().find_map(|card| card.first_free().map(|b| card.block_path(b)))
+}
+
+/// Most recently written replay in `REPLAY_DIR`
+#[cfg(not(target_arch = "wasm32"))]
+fn latest_replay() -> Option<Replay> {
+    let newest = std::fs::read_dir(REPLAY_DIR)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "ron"))
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())?;
+    let contents = std::fs::read_to_string(newest.path()).ok()?;
+    Replay::from_ron(&contents).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn latest_replay() -> Option<Replay> {
+    None
+}
+
+/// Stop recording, saving the replay to disk on native builds (empty recordings are dropped)
+fn finish_recording(play: &mut PlaySession) -> Option<(Replay, String)> {
+    let replay = play.recording.take().filter(|r| !r.is_empty())?;
+    let path = next_replay_path(replay.seed);
+    #[cfg(not(target_arch = "wasm32"))]
+    let message = match replay.save(&path) {
+        Ok(()) => format!("Saved replay {}", path.display()),
+        Err(e) => e,
+    };
+    #[cfg(target_arch = "wasm32")]
+    let message = format!("Recorded {} steps", replay.len());
+    Some((replay, message))
+}
+
+/// Read the song `id` from the project's songs (errors are logged)
+async fn load_song(id: &str) -> Option<Song> {
+    let path = song_path(id);
+    match load_string(&path.to_string_lossy()).await {
+        Ok(s) => MusicPlayer::parse_song(&s).map_err(|e| log::error!("{}: {}", path.display(), e)).ok(),
+        Err(e) => {
+            log::error!("Failed to read song {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Read the models the level's enemies are drawn with (errors are logged
+/// and the enemy goes without)
+async fn load_models(level: &Level) -> HashMap<String, Model> {
+    let mut models = HashMap::new();
+    for name in enemy_models(level) {
+        let path = model_path(&name);
+        match load_string(&path.to_string_lossy()).await {
+            Ok(s) => match Model::from_ron(&s) {
+                Ok(model) => {
+                    models.insert(name, model);
+                }
+                Err(e) => log::error!("{}: {}", path.display(), e),
+            },
+            Err(e) => log::error!("Failed to read model {}: {}", path.display(), e),
+        }
+    }
+    models
+}
+
+/// Run the game until the player quits
+///
+/// - F5 restarts the level and records, F5 again saves the replay, F9 plays
+///   back the last one; F3 toggles the combat hitbox wireframes
+/// - reaching a checkpoint or changing stats saves the game (native only), to
+///   the memory card block it was loaded from when the card is enabled
+/// - resting at a checkpoint shows the level-up menu (keys 1-3) and the gear
+/// - walking into an exit fades out to the level `world.ron` links it to and
+///   back in at the linked entrance, carrying stats and health over
+/// - each level plays its song (`Level::music`), else the manifest's `music`
+/// - only rooms within `stream_hops` portal hops are loaded and meshed
+///   (`RoomStreamer`)
+/// - the final blit can go through a sharp-bilinear shader so non-integer
+///   window sizes don't shimmer
+pub async fn run_player(manifest: GameManifest) {
+    let packs = TexturePack::load_from_manifest().await;
+
+    let hud_layout = match load_string(HUD_LAYOUT_PATH).await {
+        Ok(s) => HudLayout::from_ron(&s).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            HudLayout::default()
+        }),
+        Err(_) => HudLayout::default(),
+    };
+    let progression = match load_string(PROGRESSION_PATH).await {
+        Ok(s) => Progression::from_ron(&s).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            Progression::default()
+        }),
+        Err(_) => Progression::default(),
+    };
+    let equipment = match load_string(EQUIPMENT_PATH).await {
+        Ok(s) => Equipment::from_ron(&s).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            Equipment::default()
+        }),
+        Err(_) => Equipment::default(),
+    };
+    let loot = match load_string(LOOT_PATH).await {
+        Ok(s) => LootTables::from_ron(&s).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            LootTables::default()
+        }),
+        Err(_) => LootTables::default(),
+    };
+    let world_map = match load_string(WORLD_MAP_PATH).await {
+        Ok(s) => WorldMap::from_ron(&s).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            WorldMap::default()
+        }),
+        Err(_) => WorldMap::default(),
+    };
+    let game_text = match load_string(TEXT_PATH).await {
+        Ok(s) => GameText::from_ron(&s).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            GameText::default()
+        }),
+        Err(_) => GameText::default(),
+    };
+    let mut hud_state = HudState { hp: 100.0, max_hp: 100.0, stamina: 100.0, max_stamina: 100.0, ..Default::default() };
+
+    let mut front_end = FrontEnd::new(manifest.front_end.clone(), false);
+    front_end.text = game_text;
+    if manifest.front_end.memory_card {
+        front_end.cards = MemoryCard::open_all(Path::new(MEMORY_CARD_DIR));
+    }
+    front_end.can_continue = has_save(&front_end);
+    let mut session: Option<PlaySession> = None;
+    let mut settings = RasterSettings::default();
+    let mut fb = Framebuffer::new(WIDTH, HEIGHT);
+    let upscale = load_upscale_material();
+    let mut error: Option<String> = None;
+    let mut last_replay: Option<Replay> = None;
+    let mut notice: Option<(String, f64)> = None;
+    let mut room_audio = RoomAudioPlayer::new();
+    let mut music = MusicPlayer::new();
+    // Song id requested last (loaded or not, so a missing song is only reported once)
+    let mut music_id: Option<String> = None;
+    let mut transition: Option<LevelTransition> = None;
+    let mut rumble = RumblePlayer::new();
+
+    log::info!("Starting {}", manifest.name);
+
+    loop {
+        let dt = get_frame_time();
+        clear_background(BLACK);
+
+        let wanted = session.as_ref().and_then(|play| play.sim.level.music.clone()).or_else(|| manifest.music.clone());
+        if wanted != music_id {
+            let song = match &wanted {
+                Some(id) => load_song(id).await,
+                None => None,
+            };
+            music.play(song);
+            music_id = wanted;
+        }
+
+        match session.as_mut() {
+            None => {
+                room_audio.update(None, dt);
+                music.update([], dt);
+                let screen = Rect::new(0.0, 0.0, screen_width(), screen_height());
+                let action = front_end.update(dt);
+                // Continuing: the single save, or a memory card block
+                let resume = match &action {
+                    FrontEndAction::Continue => saved_game().map(|save| (save, PathBuf::from(SAVE_GAME_PATH))),
+                    FrontEndAction::LoadBlock(c, b) => front_end
+                        .cards
+                        .get(*c)
+                        .and_then(|card| Some((card.blocks.get(*b)?.clone()?, card.block_path(*b)))),
+                    _ => None,
+                };
+                match action {
+                    FrontEndAction::NewGame(path) => match load_level(&path).await {
+                        Ok(level) => {
+                            error = None;
+                            let mut play = PlaySession::new(level, path, new_seed());
+                            play.streamer.hops = manifest.stream_hops;
+                            play.sim.set_progression(progression.clone());
+                            play.sim.set_equipment(equipment.clone());
+                            play.sim.set_loot(loot.clone());
+                            play.sim.set_models(load_models(&play.sim.level).await);
+                            if manifest.front_end.memory_card {
+                                play.save_path = free_block(&front_end.cards);
+                                if play.save_path.is_none() {
+                                    notice = Some(("No free memory card blocks: progress won't be saved".to_string(), get_time() + 4.0));
+                                }
+                            }
+                            session = Some(play);
+                        }
+                        Err(e) => error = Some(e),
+                    },
+                    FrontEndAction::Continue | FrontEndAction::LoadBlock(..) => match resume {
+                        Some((save, save_path)) => match load_level(&save.level).await {
+                            Ok(level) => {
+                                error = None;
+                                let mut play = PlaySession::new(level, save.level, new_seed());
+                                play.streamer.hops = manifest.stream_hops;
+                                play.sim.set_progression(progression.clone());
+                                play.sim.set_equipment(equipment.clone());
+                                play.sim.set_loot(loot.clone());
+                                play.sim.set_models(load_models(&play.sim.level).await);
+                                play.sim.set_stats(save.stats.clone());
+                                play.sim.cutscene = None;
+                                play.sim.start_at_checkpoint(save.checkpoint);
+                                play.save_path = Some(save_path);
+                                play.saved_checkpoint = Some(save.checkpoint);
+                                play.saved_stats = save.stats;
+                                session = Some(play);
+                            }
+                            Err(e) => error = Some(e),
+                        },
+                        None => error = Some("No save game".to_string()),
+                    },
+                    FrontEndAction::CopyBlock(c, b) => {
+                        let other = (c + 1) % front_end.cards.len();
+                        let source = front_end.cards[c].clone();
+                        let message = match source.copy_to(b, &mut front_end.cards[other]) {
+                            Ok(block) => format!("Copied to memory card {}, block {}", other + 1, block + 1),
+                            Err(e) => e,
+                        };
+                        notice = Some((message, get_time() + 3.0));
+                    }
+                    FrontEndAction::DeleteBlock(c, b) => {
+                        let message = match front_end.cards[c].delete(b) {
+                            Ok(()) => format!("Deleted block {} of memory card {}", b + 1, c + 1),
+                            Err(e) => e,
+                        };
+                        notice = Some((message, get_time() + 3.0));
+                        front_end.can_continue = has_save(&front_end);
+                    }
+                    FrontEndAction::None => {}
+                    FrontEndAction::Quit => break,
+                }
+                front_end.draw(screen);
+                if let Some(e) = &error {
+                    draw_text(e, 10.0, screen.bottom() - 10.0, 16.0, RED);
+                }
+            }
+            Some(play) => {
+                if is_key_pressed(KeyCode::Escape) {
+                    if let Some((replay, message)) = finish_recording(play) {
+                        last_replay = Some(replay);
+                        notice = Some((message, get_time() + 3.0));
+                    }
+                    session = None;
+                    transition = None;
+                    rumble.stop();
+                    front_end.set_screen(FrontEndScreen::Main);
+                    next_frame().await;
+                    continue;
+                }
+
+                settings.resolution = front_end.options.resolution();
+                settings.crt.enabled = front_end.options.crt;
+                front_end.options.apply_geometry(&mut settings);
+                let (w, h) = settings.resolution();
+                fb.resize(w, h);
+
+                if is_key_pressed(KeyCode::F3) {
+                    play.sim.combat.show_hitboxes = !play.sim.combat.show_hitboxes;
+                }
+                if is_key_pressed(KeyCode::F5) && play.playback.is_none() && transition.is_none() {
+                    if let Some((replay, message)) = finish_recording(play) {
+                        last_replay = Some(replay);
+                        notice = Some((message, get_time() + 3.0));
+                    } else {
+                        play.start_recording(new_seed());
+                    }
+                }
+                if is_key_pressed(KeyCode::F9) && transition.is_none() {
+                    if let Some((replay, _)) = finish_recording(play) {
+                        last_replay = Some(replay);
+                    }
+                    match last_replay.clone().or_else(latest_replay) {
+                        Some(replay) => match load_level(&replay.level).await {
+                            Ok(level) => {
+                                *play = PlaySession::start_playback(level, replay);
+                                play.streamer.hops = manifest.stream_hops;
+                                play.sim.set_progression(progression.clone());
+                                play.sim.set_equipment(equipment.clone());
+                                play.sim.set_loot(loot.clone());
+                                play.sim.set_models(load_models(&play.sim.level).await);
+                            }
+                            Err(e) => notice = Some((e, get_time() + 3.0)),
+                        },
+                        None => notice = Some(("No replay recorded".to_string(), get_time() + 3.0)),
+                    }
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                play.fetch_rooms().await;
+                // The level holds still while fading out to the next one
+                if transition.as_ref().is_none_or(|t| t.arrived) {
+                    play.update(dt);
+                }
+                if let Some(exit) = play.sim.exit.take() {
+                    match world_map.destination(&play.level_path, &play.sim.level, &exit) {
+                        Some(link) => transition = Some(LevelTransition::new(link)),
+                        None => notice = Some((format!("Exit \"{}\" doesn't lead anywhere", exit), get_time() + 3.0)),
+                    }
+                }
+                if let Some(t) = transition.as_mut() {
+                    t.elapsed += dt;
+                    if !t.arrived && t.elapsed >= TRANSITION_FADE {
+                        match load_level(&t.link.to).await {
+                            Ok(level) => {
+                                // A replay covers a single level
+                                if let Some((replay, message)) = finish_recording(play) {
+                                    last_replay = Some(replay);
+                                    notice = Some((message, get_time() + 3.0));
+                                }
+                                let (stats, hp) = (play.sim.stats.clone(), play.sim.player.hp);
+                                let save_path = play.save_path.take();
+                                *play = PlaySession::new(level, t.link.to.clone(), new_seed());
+                                play.save_path = save_path;
+                                play.streamer.hops = manifest.stream_hops;
+                                play.sim.set_progression(progression.clone());
+                                play.sim.set_equipment(equipment.clone());
+                                play.sim.set_loot(loot.clone());
+                                play.sim.set_models(load_models(&play.sim.level).await);
+                                play.sim.set_stats(stats);
+                                play.sim.player.hp = hp.min(play.sim.player.max_hp);
+                                play.sim.cutscene = None;
+                                play.sim.arrive_at(t.link.spawn);
+                            }
+                            // Fade back in where the player was
+                            Err(e) => notice = Some((e, get_time() + 3.0)),
+                        }
+                        t.arrived = true;
+                        t.elapsed = 0.0;
+                    }
+                    play.fade = t.fade();
+                }
+                if transition.as_ref().is_some_and(|t| t.finished()) {
+                    transition = None;
+                    play.fade = 0.0;
+                }
+                rumble.enabled = front_end.options.vibration;
+                rumble.play(play.sim.rumble.drain(..));
+                rumble.update(dt);
+                let language = front_end.options.language;
+                let looted: Vec<String> = play.sim.looted
+                    .drain(..)
+                    .map(|d| format!("{} x{}", front_end.text.item_name(&d.item, language), d.amount))
+                    .collect();
+                if !looted.is_empty() {
+                    notice = Some((format!("Got {}", looted.join(", ")), get_time() + 2.0));
+                }
+                play.render(&mut fb, &packs, &settings);
+                room_audio.update(play.camera_room(), dt);
+                room_audio.play_cues(play.sim.sounds.drain(..), play.sim.camera.position);
+                music.update(play.sim.events.music_cues.drain(..), dt);
+                if play.save_progress().is_some() {
+                    if manifest.front_end.memory_card {
+                        front_end.cards = MemoryCard::open_all(Path::new(MEMORY_CARD_DIR));
+                    }
+                    front_end.can_continue = has_save(&front_end);
+                    notice = Some(("Progress saved".to_string(), get_time() + 2.0));
+                }
+                hud_state.set_health(&play.sim.player);
+                // Nothing spends stamina until the player controller lands
+                hud_state.max_stamina = play.sim.max_stamina();
+                hud_state.stamina = hud_state.max_stamina;
+                hud_state.boss = play.sim.engaged_boss()
+                    .map(|(b, health)| BossInfo { name: b.name.clone(), hp: health.hp, max_hp: health.max_hp });
+
+                let rect = blit_rect(&fb);
+                let material = upscale.as_ref().filter(|_| front_end.options.sharp_bilinear);
+                blit(&fb, rect, &settings, &play.sim.level.ambience, material);
+                if play.sim.cutscene.is_none() {
+                    draw_hud(rect, &hud_layout, &hud_state);
+                    if play.sim.at_checkpoint() {
+                        draw_level_up(rect, &play.sim, &front_end.text, front_end.options.language);
+                    }
+                }
+                if let Some(playback) = &play.playback {
+                    playback.draw(rect);
+                } else if let Some(replay) = &play.recording {
+                    let label = format!("REC {:.1}s  [F5] stop", replay.len() as f32 * FIXED_DT);
+                    draw_text(&label, rect.x + 8.0, rect.y + 18.0, 16.0, RED);
+                }
+            }
+        }
+
+        if let Some((message, expiry)) = &notice {
+            if get_time() < *expiry {
+                draw_text(message, 10.0, 20.0, 16.0, YELLOW);
+            } else {
+                notice = None;
+            }
+        }
+
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{button, INTRO_TRIGGER};
+
+    #[test]
+    fn test_manifest_defaults() {
+        let manifest: GameManifest = ron::from_str("(name: \"Demo\")").unwrap();
+        assert_eq!(manifest.name, "Demo");
+        assert!(manifest.levels.is_empty());
+        assert_eq!(manifest.stream_hops, DEFAULT_STREAM_HOPS);
+        assert!(manifest.music.is_none());
+        assert_eq!(manifest.front_end.first_level, PathBuf::from("assets/levels/level_001.ron"));
+    }
+
+    #[test]
+    fn test_intro_path_starts_cutscene() {
+        let mut level = Level::new();
+        let mut path = crate::world::CameraPath::new("intro");
+        path.trigger = Some(INTRO_TRIGGER.to_string());
+        level.camera_paths.push(path);
+        let session = PlaySession::new(level, PathBuf::from("intro.ron"), 1);
+        assert!(session.sim.cutscene.is_some());
+    }
+
+    #[test]
+    fn test_playback_reproduces_recording() {
+        let level = Level::new();
+        let inputs = [button::FORWARD, button::FORWARD, button::TURN_LEFT, 0, button::LEFT | button::LOOK_UP];
+
+        let mut live = PlaySession::new(level.clone(), PathBuf::from("test.ron"), 7);
+        live.start_recording(7);
+        for bits in inputs {
+            let input = InputFrame(bits);
+            live.recording.as_mut().unwrap().push(input);
+            live.sim.step(input);
+        }
+        let replay = live.recording.take().unwrap();
+
+        let mut replayed = PlaySession::start_playback(level, replay);
+        let frames = replayed.playback.as_mut().unwrap().advance(FIXED_DT * inputs.len() as f32 + 0.001);
+        assert_eq!(frames.len(), inputs.len());
+        for input in frames {
+            replayed.sim.step(input);
+        }
+        let (a, b) = (replayed.sim.camera.position, live.sim.camera.position);
+        assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+        assert_eq!(replayed.sim.camera.rotation_x, live.sim.camera.rotation_x);
+    }
+}
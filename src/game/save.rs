@@ -0,0 +1,135 @@
+//! Save games
+//!
+//! A save is the level being played, the id of the last checkpoint reached
+//! and the player's stats. Exported games write one whenever a new checkpoint
+//! is reached or the stats change, and offer it as the front end's Continue.
+//! Currency dropped on death isn't kept. Native only: the web build doesn't
+//! keep saves yet.
+//!
+//! Games whose front end enables the memory card keep saves in blocks on two
+//! virtual memory cards instead (`memcard/card1/block01.ron`, ...), loaded,
+//! copied and deleted from the front end's Memory Card screen.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use super::PlayerStats;
+
+/// Where exported games keep their save
+pub const SAVE_GAME_PATH: &str = "save.ron";
+
+/// Folder the memory cards are kept in, one subfolder per card
+pub const MEMORY_CARD_DIR: &str = "memcard";
+
+/// Memory card slots
+pub const MEMORY_CARD_SLOTS: usize = 2;
+
+/// Save blocks on a memory card
+pub const MEMORY_CARD_BLOCKS: usize = 15;
+
+/// The player's progress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub level: PathBuf,
+    /// Id of the checkpoint to continue from
+    pub checkpoint: i32,
+    #[serde(default)]
+    pub stats: PlayerStats,
+}
+
+impl SaveGame {
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = ron::to_string(self).map_err(|e| format!("Failed to serialize save: {}", e))?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(path, text).map_err(|e| format!("Failed to write save: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read save: {}", e))?;
+        ron::from_str(&text).map_err(|e| format!("Failed to parse save: {}", e))
+    }
+}
+
+/// A memory card: a folder of numbered save blocks
+#[derive(Debug, Clone)]
+pub struct MemoryCard {
+    dir: PathBuf,
+    /// `MEMORY_CARD_BLOCKS` blocks, None when free
+    pub blocks: Vec<Option<SaveGame>>,
+}
+
+impl MemoryCard {
+    /// Read the card in a folder (missing or unreadable blocks are free)
+    pub fn open(dir: &Path) -> Self {
+        let mut card = Self { dir: dir.to_path_buf(), blocks: Vec::new() };
+        card.blocks = (0..MEMORY_CARD_BLOCKS).map(|b| SaveGame::load(&card.block_path(b)).ok()).collect();
+        card
+    }
+
+    /// The cards in every slot under `root`
+    pub fn open_all(root: &Path) -> Vec<Self> {
+        (1..=MEMORY_CARD_SLOTS).map(|slot| Self::open(&root.join(format!("card{}", slot)))).collect()
+    }
+
+    pub fn block_path(&self, block: usize) -> PathBuf {
+        self.dir.join(format!("block{:02}.ron", block + 1))
+    }
+
+    pub fn first_free(&self) -> Option<usize> {
+        self.blocks.iter().position(|b| b.is_none())
+    }
+
+    pub fn used(&self) -> usize {
+        self.blocks.iter().filter(|b| b.is_some()).count()
+    }
+
+    /// Write a save to a block
+    pub fn write(&mut self, block: usize, save: &SaveGame) -> Result<(), String> {
+        save.save(&self.block_path(block))?;
+        self.blocks[block] = Some(save.clone());
+        Ok(())
+    }
+
+    pub fn delete(&mut self, block: usize) -> Result<(), String> {
+        std::fs::remove_file(self.block_path(block)).map_err(|e| format!("Failed to delete save: {}", e))?;
+        self.blocks[block] = None;
+        Ok(())
+    }
+
+    /// Copy a block to the first free block of another card, returning it
+    pub fn copy_to(&self, block: usize, other: &mut MemoryCard) -> Result<usize, String> {
+        let save = self.blocks.get(block).cloned().flatten().ok_or("Nothing to copy")?;
+        let free = other.first_free().ok_or("No free blocks")?;
+        other.write(free, &save)?;
+        Ok(free)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_card_blocks() {
+        let root = std::env::temp_dir().join(format!("bonnie_memcard_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let mut cards = MemoryCard::open_all(&root);
+        assert_eq!(cards.len(), MEMORY_CARD_SLOTS);
+        assert_eq!(cards[0].first_free(), Some(0));
+
+        let save = SaveGame { level: PathBuf::from("a.ron"), checkpoint: 2, stats: PlayerStats::default() };
+        cards[0].write(0, &save).unwrap();
+        cards[0].write(3, &save).unwrap();
+        assert_eq!(cards[0].first_free(), Some(1));
+        let (first, rest) = cards.split_at_mut(1);
+        assert_eq!(first[0].copy_to(3, &mut rest[0]), Ok(0));
+        assert!(first[0].copy_to(1, &mut rest[0]).is_err());
+        cards[0].delete(3).unwrap();
+
+        let reopened = MemoryCard::open_all(&root);
+        assert_eq!(reopened[0].used(), 1);
+        assert_eq!(reopened[1].blocks[0].as_ref(), Some(&save));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
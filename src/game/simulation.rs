@@ -0,0 +1,895 @@
+//! Simulation - the fixed-step play-mode world
+//!
+//! Everything gameplay advances each step, independent of where it is shown:
+//! - the exported game's play session wraps it with recording and replays
+//! - the editor runs it inside its 3D viewport ("simulate"), on a copy of the
+//!   level being edited
+//!
+//! The systems it drives live in their own modules (`combat`, `enemy`,
+//! `boss`, `checkpoint`, `loot`, `world_map`...); see `Simulation::step`.
+
+use std::collections::{HashMap, HashSet};
+use crate::modeler::{draw_model, Animation, AnimationPlayer, Model, PartTransform, EVENT_HIT_END, EVENT_HIT_START};
+use crate::rasterizer::{Camera, Framebuffer, RasterSettings, Vec3};
+use crate::world::{EntityRef, Level, NavGraph};
+use super::{below_kill_plane, button, exit_at, exit_name, entrance_pose, AttackDef, BossFight, Combat, Combatant, DamageResult, Enemy, ENEMY_SIGHT_RANGE, SIGNAL_ATTACK, SIGNAL_IDLE, SIGNAL_WALK, checkpoint_in_range, checkpoint_pose, CutscenePlayer, DroppedCurrency, EventScheduler, FixedStep, GameRng, Health, InputFrame, LogicState};
+use super::{ArmorDef, Equipment, ItemDrop, LootTables, PlayerStats, Progression, Rumble, SoundCue, Stat, WeaponDef, BASE_SWING_TIME, CHEST_ARCHETYPE, FIXED_DT, PLAYER_ATTACK_DAMAGE, PLAYER_MAX_POISE, RECOVER_RANGE, TRIGGER_ARCHETYPE};
+
+/// Trigger name of the camera path played when a level starts
+pub const INTRO_TRIGGER: &str = "intro";
+
+/// Entity archetype marking where play starts
+pub const PLAYER_START_ARCHETYPE: &str = "player_start";
+
+/// Eye height above a player start (half a sector)
+pub const PLAYER_EYE_HEIGHT: f32 = 512.0;
+
+/// How close a trigger must be to fire it with Interact (one sector)
+pub const INTERACT_RANGE: f32 = 1024.0;
+
+/// Entity archetype of regular enemies
+pub const ENEMY_ARCHETYPE: &str = "enemy";
+
+/// Combatant index (and attack index) of the player in `Simulation::combat`
+pub const PLAYER_COMBATANT: usize = 0;
+
+/// How close the player must be for an enemy to swing (within reach of
+/// its hit volume)
+pub const ENEMY_ATTACK_RANGE: f32 = 768.0;
+
+/// Play-mode world state, advanced in fixed steps
+pub struct Simulation {
+    pub level: Level,
+    pub camera: Camera,
+    pub cutscene: Option<CutscenePlayer>,
+    /// Doors, platforms and triggers
+    pub logic: LogicState,
+    /// Timed and triggered level events
+    pub events: EventScheduler,
+    /// The player's health, kept in step with their combatant
+    pub player: Health,
+    /// Boss encounters, one per boss entity
+    pub bosses: Vec<BossFight>,
+    /// Id of the last checkpoint reached
+    pub checkpoint: Option<i32>,
+    /// Stat levels and currency
+    pub stats: PlayerStats,
+    /// The project's progression balance
+    pub progression: Progression,
+    /// The project's weapons and armor
+    pub equipment: Equipment,
+    /// The project's loot tables
+    pub loot: LootTables,
+    /// The player and every regular enemy: health, hurtboxes and attacks
+    pub combat: Combat,
+    /// Enemy of each combatant after the player's
+    pub enemies: Vec<Enemy>,
+    /// Walkable sectors and waypoints enemies find their paths over
+    pub nav: NavGraph,
+    /// Models the enemies are drawn with, by name
+    pub models: HashMap<String, Model>,
+    /// Chests already opened
+    opened: HashSet<EntityRef>,
+    /// Loot received since last taken (for pickup notices)
+    pub looted: Vec<ItemDrop>,
+    /// Name of the exit the player walked into, until the runtime takes it
+    pub exit: Option<String>,
+    /// Exit the player is standing in (exits fire on entering)
+    in_exit: Option<EntityRef>,
+    /// Spawn id of the entrance the player arrived through from another level
+    pub entrance: Option<i32>,
+    /// Rumble queued this step, until the runtime plays it
+    pub rumble: Vec<Rumble>,
+    /// Sounds of enemy animation events (footsteps, effects), until the
+    /// runtime plays them
+    pub sounds: Vec<SoundCue>,
+    /// Currency dropped by the last death, waiting to be recovered
+    pub dropped: Option<DroppedCurrency>,
+    /// Last eye position inside a room, where currency drops on death
+    last_safe: Vec3,
+    /// Steps since the player died, while fading out and back in
+    respawn: Option<u32>,
+    /// Swings so far, picking the next move of the weapon's moveset
+    combo: usize,
+    /// Input of the previous step, to tell presses from holds
+    last_input: InputFrame,
+    /// Gameplay randomness - only ever drawn from inside `step`
+    rng: GameRng,
+    clock: FixedStep,
+}
+
+impl Simulation {
+    /// Start at the level's first `player_start` entity, or in the middle of
+    /// the first room when there is none
+    pub fn new(level: Level, seed: u32) -> Self {
+        let mut camera = Camera::new();
+        match spawn_point(&level) {
+            Some((position, facing)) => {
+                camera.position = position;
+                camera.rotation_y = facing;
+                camera.update_basis();
+            }
+            // Spawn in the middle of the first room
+            None => {
+                if let Some(room) = level.rooms.first() {
+                    camera.position = room.world_bounds().center();
+                }
+            }
+        }
+        let cutscene = CutscenePlayer::from_trigger(&level, INTRO_TRIGGER);
+        let logic = LogicState::new(&level);
+        let events = EventScheduler::new(&level);
+        let (mut combat, enemies) = spawn_combat(&level);
+        let bosses = BossFight::all(&level, &mut combat);
+        // A split level can't be measured without its sectors: it saved its graph
+        let nav = level.streamed.as_ref().map_or_else(|| NavGraph::build(&level), |s| s.nav.clone());
+        let in_exit = exit_at(&level, camera.position);
+        let progression = Progression::default();
+        let stats = PlayerStats::default();
+        let last_safe = camera.position;
+        Self {
+            level,
+            camera,
+            cutscene,
+            logic,
+            events,
+            bosses,
+            player: Health::new(stats.value(Stat::Vigor, &progression), PLAYER_MAX_POISE),
+            checkpoint: None,
+            last_safe,
+            stats,
+            progression,
+            equipment: Equipment::default(),
+            loot: LootTables::default(),
+            combat,
+            enemies,
+            nav,
+            models: HashMap::new(),
+            opened: HashSet::new(),
+            looted: Vec::new(),
+            exit: None,
+            in_exit,
+            entrance: None,
+            rumble: Vec::new(),
+            sounds: Vec::new(),
+            dropped: None,
+            respawn: None,
+            combo: 0,
+            last_input: InputFrame::default(),
+            rng: GameRng::new(seed),
+            clock: FixedStep::default(),
+        }
+    }
+
+    /// Advance by the frame time with the same input for every step; returns
+    /// how many steps ran
+    pub fn update(&mut self, frame_dt: f32, input: InputFrame) -> u32 {
+        let steps = self.clock.steps(frame_dt);
+        for _ in 0..steps {
+            self.step(input);
+        }
+        steps
+    }
+
+    /// One fixed simulation step. Must only depend on `input` and simulation
+    /// state (including `rng`) to stay replayable.
+    ///
+    /// - level logic and events run every step, cutscenes included
+    /// - Interact (E) fires triggers in reach and opens chests; warp portals
+    ///   move the player to their destination
+    /// - checkpoints become the respawn point and the place to level up or
+    ///   change armor; an entrance arrived through counts until then
+    /// - the player swings the equipped weapon's moveset, enemies strike back
+    ///   or follow their `behavior`, and bosses fight once their arena is entered
+    /// - defeated enemies, bosses and opened chests roll their loot
+    /// - walking into an `exit` reports it in `exit` for the runtime
+    /// - hits, kills and getting hurt queue controller rumble in `rumble`
+    pub fn step(&mut self, input: InputFrame) {
+        let pressed = |flag| input.held(flag) && !self.last_input.held(flag);
+        let interact = pressed(button::INTERACT);
+        let attack = pressed(button::ATTACK);
+        let cycle_weapon = pressed(button::CYCLE_WEAPON);
+        let cycle_armor = pressed(button::CYCLE_ARMOR);
+        let level_up: Vec<Stat> = [button::LEVEL_VIGOR, button::LEVEL_ENDURANCE, button::LEVEL_STRENGTH]
+            .into_iter()
+            .zip(Stat::ALL)
+            .filter(|(flag, _)| pressed(*flag))
+            .map(|(_, stat)| stat)
+            .collect();
+        self.last_input = input;
+        self.logic.update(&self.level, FIXED_DT);
+        let fired = self.logic.take_fired();
+        self.events.update(&self.level, &mut self.logic, &fired, FIXED_DT);
+
+        // Dying: the level keeps running while the screen fades out and back in
+        if let Some(steps) = self.respawn {
+            let fade = self.respawn_fade();
+            let now = steps + 1;
+            self.respawn = (now < fade * 2).then_some(now);
+            if now == fade {
+                self.respawn_at_checkpoint();
+            }
+            return;
+        }
+        if self.player.is_dead() {
+            self.respawn = Some(0);
+            // A drop not recovered in time is lost to the new one
+            let amount = std::mem::take(&mut self.stats.currency);
+            self.dropped = (amount > 0).then_some(DroppedCurrency { position: self.last_safe, amount });
+            return;
+        }
+        let hp = self.player.hp;
+
+        if let Some(player) = self.cutscene.as_mut() {
+            if input.held(button::SKIP) || !player.update(FIXED_DT, &self.level, &mut self.camera) {
+                self.cutscene = None;
+            }
+            return;
+        }
+
+        // Free camera until the player controller lands
+        let move_speed = 2000.0 * FIXED_DT;
+        let turn_speed = 2.0 * FIXED_DT;
+        let before = self.camera.position;
+        let cam = &mut self.camera;
+        if input.held(button::FORWARD) {
+            cam.position = cam.position + cam.basis_z * move_speed;
+        }
+        if input.held(button::BACK) {
+            cam.position = cam.position - cam.basis_z * move_speed;
+        }
+        if input.held(button::LEFT) {
+            cam.position = cam.position - cam.basis_x * move_speed;
+        }
+        if input.held(button::RIGHT) {
+            cam.position = cam.position + cam.basis_x * move_speed;
+        }
+        if input.held(button::TURN_LEFT) {
+            cam.rotate(0.0, turn_speed);
+        }
+        if input.held(button::TURN_RIGHT) {
+            cam.rotate(0.0, -turn_speed);
+        }
+        if input.held(button::LOOK_UP) {
+            cam.rotate(-turn_speed, 0.0);
+        }
+        if input.held(button::LOOK_DOWN) {
+            cam.rotate(turn_speed, 0.0);
+        }
+        if let Some(destination) = self.level.warp_destination(before, self.camera.position) {
+            self.camera.position = destination;
+        }
+        if below_kill_plane(&self.level, self.camera.position) {
+            self.player.hp = 0.0;
+        }
+        if self.level.find_room_at(self.camera.position).is_some() {
+            self.last_safe = self.camera.position;
+        }
+        let exit = exit_at(&self.level, self.camera.position);
+        if exit.is_some() && exit != self.in_exit {
+            self.exit = exit.and_then(|r| self.level.entity(r)).map(exit_name);
+        }
+        self.in_exit = exit;
+        if cycle_weapon {
+            self.stats.loadout.weapon = self.equipment.next_weapon(self.stats.loadout.weapon.as_deref());
+        }
+        if let Some(id) = checkpoint_in_range(&self.level, self.camera.position) {
+            self.checkpoint = Some(id);
+            for stat in level_up {
+                if self.stats.level_up(stat, &self.progression) && stat == Stat::Vigor {
+                    self.player = self.fresh_health();
+                }
+            }
+            if cycle_armor {
+                self.stats.loadout.armor = self.equipment.next_armor(self.stats.loadout.armor.as_deref());
+                self.wear_armor();
+            }
+        }
+        if self.dropped.is_some_and(|d| (d.position - self.camera.position).len() <= RECOVER_RANGE) {
+            self.stats.currency += self.dropped.take().map_or(0, |d| d.amount);
+        }
+        // The player's combatant follows the camera
+        let player = &mut self.combat.combatants[PLAYER_COMBATANT];
+        player.position = self.camera.position - Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+        player.yaw = self.camera.rotation_y;
+        player.health = self.player.clone();
+        let swing = (attack && player.can_act()).then(|| self.player_attack());
+        if let Some(def) = &swing {
+            self.combat.attacks[PLAYER_COMBATANT] = def.clone();
+            self.combat.start_attack(PLAYER_COMBATANT, PLAYER_COMBATANT);
+            self.combo += 1;
+        }
+        let feet = self.combat.combatants[PLAYER_COMBATANT].position;
+        for fight in &mut self.bosses {
+            fight.step(&self.level, &mut self.logic, &mut self.combat, feet);
+        }
+        let mut defeated = self.update_combat();
+        for fight in &mut self.bosses {
+            let was_defeated = fight.defeated;
+            self.stats.currency += fight.resolve(&self.level, &mut self.logic, &self.combat);
+            if fight.defeated && !was_defeated {
+                defeated.push(fight.boss);
+            }
+        }
+        if self.player.hp < hp {
+            self.rumble.push(Rumble::hurt((hp - self.player.hp) / self.player.max_hp.max(1.0)));
+        }
+        for r in defeated {
+            self.drop_loot(r);
+        }
+
+        if interact {
+            let eye = self.camera.position;
+            let in_reach = |archetype: &str| -> Vec<EntityRef> {
+                self.level
+                    .entities_of(archetype)
+                    .filter(|(_, e)| self.events.is_spawned(e))
+                    .filter(|(r, _)| self.level.entity_world_pos(*r).is_some_and(|p| (p - eye).len() <= INTERACT_RANGE))
+                    .map(|(r, _)| r)
+                    .collect()
+            };
+            let (triggers, chests) = (in_reach(TRIGGER_ARCHETYPE), in_reach(CHEST_ARCHETYPE));
+            for r in triggers {
+                self.logic.activate(&self.level, r);
+            }
+            for r in chests {
+                if self.opened.insert(r) {
+                    self.drop_loot(r);
+                }
+            }
+        }
+    }
+
+    /// Let enemies in reach swing at the player, move the others and
+    /// resolve the hits; returns the enemies killed
+    ///
+    /// Enemy animation events drive combat and sound: "hit_start" and
+    /// "hit_end" open and close a melee swing's hit window, "hit_start" is
+    /// when a ranged enemy's shot goes off (at once if its clip has none), and
+    /// any other event queues the sound of that name in `sounds`.
+    fn update_combat(&mut self) -> Vec<EntityRef> {
+        let feet = self.combat.combatants[PLAYER_COMBATANT].position;
+        let mut hits = Vec::new();
+        for (i, enemy) in self.enemies.iter_mut().enumerate() {
+            let model = enemy.model.as_ref().and_then(|name| self.models.get(name));
+            let shoots_on_event = model
+                .and_then(attack_clip)
+                .is_some_and(|clip| clip.find_event(EVENT_HIT_START).is_some());
+            let spawned = self.level.entity(enemy.entity).is_some_and(|e| self.events.is_spawned(e));
+            let c = &mut self.combat.combatants[i + 1];
+            // Enemies wait in rooms whose sectors aren't loaded (see `RoomStreamer`)
+            let room = self.level.find_room_at_with_hint(c.position, Some(enemy.entity.room)).unwrap_or(enemy.entity.room);
+            c.active = spawned && self.level.is_room_loaded(room);
+            let to_player = feet - c.position;
+            let signal = if !c.can_act() {
+                None
+            } else if enemy.ranged && Enemy::sees(&self.level, c.position, feet) {
+                // Shoot, then wait out the attack's timeline before the next shot
+                c.yaw = to_player.x.atan2(to_player.z);
+                if !shoots_on_event {
+                    hits.extend(self.combat.hitscan(&self.level, i + 1, i + 1, to_player, ENEMY_SIGHT_RANGE));
+                }
+                self.combat.start_attack(i + 1, i + 1);
+                Some(SIGNAL_ATTACK)
+            } else if !enemy.ranged && to_player.len() <= ENEMY_ATTACK_RANGE {
+                c.yaw = to_player.x.atan2(to_player.z);
+                self.combat.start_attack(i + 1, i + 1);
+                Some(SIGNAL_ATTACK)
+            } else {
+                let position = enemy.step(&self.level, &self.nav, c.position, feet, FIXED_DT);
+                let moved = position - c.position;
+                c.position = position;
+                if moved.x != 0.0 || moved.z != 0.0 {
+                    c.yaw = moved.x.atan2(moved.z);
+                    Some(SIGNAL_WALK)
+                } else {
+                    Some(SIGNAL_IDLE)
+                }
+            };
+
+            let (Some(anim), Some(model)) = (enemy.anim.as_mut(), model) else { continue };
+            if let Some(signal) = signal {
+                anim.send(model, signal);
+            }
+            let position = self.combat.combatants[i + 1].position;
+            for event in anim.update(model, FIXED_DT) {
+                match event.name.as_str() {
+                    EVENT_HIT_START if enemy.ranged => {
+                        hits.extend(self.combat.hitscan(&self.level, i + 1, i + 1, feet - position, ENEMY_SIGHT_RANGE));
+                    }
+                    EVENT_HIT_START => self.combat.set_hit_window(i + 1, true),
+                    EVENT_HIT_END => self.combat.set_hit_window(i + 1, false),
+                    _ => self.sounds.push(SoundCue { name: event.name, position }),
+                }
+            }
+        }
+
+        hits.extend(self.combat.update(&self.level, FIXED_DT));
+        self.player = self.combat.combatants[PLAYER_COMBATANT].health.clone();
+        let mut killed = Vec::new();
+        for hit in hits {
+            if hit.target == PLAYER_COMBATANT {
+                continue;
+            }
+            let kill = hit.result == DamageResult::Killed;
+            // Bosses come after the enemies; their fights see to their deaths
+            if let Some(enemy) = self.enemies.get(hit.target - 1).filter(|_| kill) {
+                killed.push(enemy.entity);
+            }
+            // Only the player's own hits shake the pad
+            if hit.attacker == PLAYER_COMBATANT {
+                self.rumble.push(if kill { Rumble::KILL } else { Rumble::HIT });
+            }
+        }
+        killed
+    }
+
+    /// Where an enemy stands now (enemies walk away from their entity's position)
+    pub fn enemy_position(&self, r: EntityRef) -> Option<Vec3> {
+        let i = self.enemies.iter().position(|e| e.entity == r)?;
+        Some(self.combat.combatants[i + 1].position)
+    }
+
+    /// Roll an entity's loot table and hand the drops to the player
+    fn drop_loot(&mut self, r: EntityRef) {
+        let table = self.level
+            .entity(r)
+            .and_then(|e| e.get("loot"))
+            .and_then(|v| v.as_str())
+            .and_then(|name| self.loot.find(name));
+        let Some(table) = table else { return };
+        for drop in table.roll(&mut self.rng) {
+            self.stats.receive(&drop);
+            self.looted.push(drop);
+        }
+    }
+
+    /// Use the project's loot tables (at the start of play)
+    pub fn set_loot(&mut self, loot: LootTables) {
+        self.loot = loot;
+    }
+
+    /// Use the models the level's enemies are drawn with (at the start of play)
+    pub fn set_models(&mut self, models: HashMap<String, Model>) {
+        self.models = models;
+        self.animate_enemies();
+    }
+
+    /// Start the state machine of every enemy with a model, timing its
+    /// attack to the clip of the model's "attack" state when there is one
+    fn animate_enemies(&mut self) {
+        for (i, enemy) in self.enemies.iter_mut().enumerate() {
+            let Some(model) = enemy.model.as_ref().and_then(|name| self.models.get(name)) else { continue };
+            enemy.anim = Some(AnimationPlayer::new(model));
+            if let Some(clip) = attack_clip(model) {
+                let attack = &mut self.combat.attacks[i + 1];
+                *attack = AttackDef {
+                    damage: attack.damage,
+                    poise_damage: attack.poise_damage,
+                    hit_volumes: std::mem::take(&mut attack.hit_volumes),
+                    ..AttackDef::from_animation(&attack.name, clip)
+                };
+            }
+        }
+    }
+
+    /// Draw every enemy still standing that has a model, where it is now
+    pub fn draw_models(&self, fb: &mut Framebuffer, camera: &Camera, settings: &RasterSettings) {
+        for (i, enemy) in self.enemies.iter().enumerate() {
+            let c = &self.combat.combatants[i + 1];
+            let Some(model) = enemy.model.as_ref().and_then(|name| self.models.get(name)) else { continue };
+            if !c.is_target() {
+                continue;
+            }
+            let pose = match &enemy.anim {
+                Some(anim) => anim.pose(model),
+                None => vec![PartTransform::default(); model.parts.len()],
+            };
+            draw_model(fb, model, &pose, c.position, c.yaw, camera, settings);
+        }
+    }
+
+    /// Start at a checkpoint (continuing from a save)
+    pub fn start_at_checkpoint(&mut self, id: i32) {
+        self.checkpoint = Some(id);
+        if let Some(pose) = checkpoint_pose(&self.level, id) {
+            self.place_camera(pose);
+        }
+    }
+
+    /// Arrive from another level at spawn `id`: the entrance with that
+    /// spawn, else the checkpoint with that id
+    pub fn arrive_at(&mut self, id: i32) {
+        if let Some(pose) = entrance_pose(&self.level, id) {
+            self.entrance = Some(id);
+            self.place_camera(pose);
+        } else if let Some(pose) = checkpoint_pose(&self.level, id) {
+            self.checkpoint = Some(id);
+            self.place_camera(pose);
+        }
+    }
+
+    /// Use the project's progression balance (at the start of play)
+    pub fn set_progression(&mut self, progression: Progression) {
+        self.progression = progression;
+        self.player = self.fresh_health();
+    }
+
+    /// Continue with saved stats (at the start of play)
+    pub fn set_stats(&mut self, stats: PlayerStats) {
+        self.stats = stats;
+        self.fill_loadout();
+        self.player = self.fresh_health();
+    }
+
+    /// Use the project's weapons and armor (at the start of play)
+    pub fn set_equipment(&mut self, equipment: Equipment) {
+        self.equipment = equipment;
+        self.fill_loadout();
+        self.player = self.fresh_health();
+    }
+
+    /// Players with nothing equipped start with the first weapon and armor
+    fn fill_loadout(&mut self) {
+        if self.stats.loadout == Default::default() {
+            self.stats.loadout = self.equipment.starting_loadout();
+        }
+    }
+
+    /// The equipped weapon, if it still exists in the project
+    pub fn weapon(&self) -> Option<&WeaponDef> {
+        self.stats.loadout.weapon.as_deref().and_then(|name| self.equipment.weapon(name))
+    }
+
+    /// The equipped armor, if it still exists in the project
+    pub fn armor(&self) -> Option<&ArmorDef> {
+        self.stats.loadout.armor.as_deref().and_then(|name| self.equipment.armor(name))
+    }
+
+    /// Damage of the player's attack: the weapon's, or the placeholder
+    /// attack's bare-handed
+    pub fn attack_damage(&self) -> f32 {
+        match self.weapon() {
+            Some(weapon) => weapon.attack_damage(&self.stats, &self.progression),
+            None => PLAYER_ATTACK_DAMAGE * self.stats.value(Stat::Strength, &self.progression),
+        }
+    }
+
+    fn swing_time(&self) -> f32 {
+        self.weapon().map_or(BASE_SWING_TIME, |w| w.swing_time())
+    }
+
+    /// The player's next attack: the equipped weapon's next move (the
+    /// moveset takes turns swing after swing), or a placeholder swing
+    /// bare-handed or with a weapon that has no moves
+    fn player_attack(&self) -> AttackDef {
+        if let Some(weapon) = self.weapon() {
+            let mut moves = weapon.attacks(&self.stats, &self.progression);
+            if !moves.is_empty() {
+                return moves.swap_remove(self.combo % moves.len());
+            }
+        }
+        let mut def = AttackDef::new("attack");
+        def.damage = self.attack_damage();
+        def.fps = (def.total_frames as f32 / self.swing_time()).round().clamp(1.0, 60.0) as u8;
+        def
+    }
+
+    /// Apply a change of armor without healing
+    fn wear_armor(&mut self) {
+        let fresh = self.fresh_health();
+        self.player.defense = fresh.defense;
+        self.player.max_poise = fresh.max_poise;
+        self.player.poise_regen = fresh.poise_regen;
+        self.player.poise = self.player.poise.min(fresh.max_poise);
+    }
+
+    /// Whether the player is resting at a checkpoint, where they can level up
+    pub fn at_checkpoint(&self) -> bool {
+        self.respawn.is_none() && checkpoint_in_range(&self.level, self.camera.position).is_some()
+    }
+
+    /// Max stamina at the player's endurance
+    pub fn max_stamina(&self) -> f32 {
+        self.stats.value(Stat::Endurance, &self.progression)
+    }
+
+    /// Full health at the player's vigor, protected by their armor
+    fn fresh_health(&self) -> Health {
+        let armor = self.armor();
+        let poise = PLAYER_MAX_POISE + armor.map_or(0.0, |a| a.poise);
+        let mut health = Health::new(self.stats.value(Stat::Vigor, &self.progression), poise);
+        health.defense = armor.map_or(0.0, |a| a.defense_for(&self.stats));
+        health
+    }
+
+    /// How far the screen has faded to black (0.0 - 1.0) around a respawn
+    pub fn fade(&self) -> f32 {
+        let Some(steps) = self.respawn else { return 0.0 };
+        let t = steps as f32 / self.respawn_fade() as f32;
+        if t < 1.0 { t } else { (2.0 - t).max(0.0) }
+    }
+
+    /// Steps each half of the respawn fade takes (at least one)
+    fn respawn_fade(&self) -> u32 {
+        (self.level.respawn.fade / FIXED_DT).ceil().max(1.0) as u32
+    }
+
+    /// Bring the player back at the last checkpoint (or the entrance they
+    /// came in through, or the level's start) and reset the level per its
+    /// respawn rules
+    fn respawn_at_checkpoint(&mut self) {
+        let pose = self.checkpoint
+            .and_then(|id| checkpoint_pose(&self.level, id))
+            .or_else(|| self.entrance.and_then(|id| entrance_pose(&self.level, id)))
+            .or_else(|| spawn_point(&self.level))
+            .or_else(|| self.level.rooms.first().map(|r| (r.world_bounds().center(), 0.0)));
+        if let Some(pose) = pose {
+            self.place_camera(pose);
+        }
+        self.player = self.fresh_health();
+        let rules = self.level.respawn;
+        if rules.reset_enemies {
+            self.events = EventScheduler::new(&self.level);
+            let show_hitboxes = self.combat.show_hitboxes;
+            (self.combat, self.enemies) = spawn_combat(&self.level);
+            self.combat.show_hitboxes = show_hitboxes;
+            for fight in &mut self.bosses {
+                fight.join(&self.level, &mut self.combat);
+            }
+            self.animate_enemies();
+        }
+        if rules.reset_doors {
+            self.logic = LogicState::new(&self.level);
+        }
+        // Unfinished boss fights always start over
+        for fight in &mut self.bosses {
+            fight.reset(&self.level, &mut self.logic, &mut self.combat);
+        }
+    }
+
+    /// The boss the player is fighting and its health, if any
+    pub fn engaged_boss(&self) -> Option<(&BossFight, &Health)> {
+        self.bosses.iter().find(|b| b.engaged).map(|b| (b, b.health(&self.combat)))
+    }
+
+    fn place_camera(&mut self, (position, facing): (Vec3, f32)) {
+        self.camera.position = position;
+        self.camera.rotation_x = 0.0;
+        self.camera.rotation_y = facing;
+        self.camera.update_basis();
+        // Arriving on an exit doesn't take it
+        self.in_exit = exit_at(&self.level, position);
+    }
+}
+
+/// Combat with the player first and then every regular enemy at full
+/// health (its `health` property), each attacking for its `damage`; boss
+/// fights join after them
+fn spawn_combat(level: &Level) -> (Combat, Vec<Enemy>) {
+    let mut combat = Combat::new();
+    combat.attacks.push(AttackDef::new("attack"));
+    combat.combatants.push(Combatant::new(Vec3::ZERO, 0, Health::new(1.0, PLAYER_MAX_POISE)));
+    let mut enemies = Vec::new();
+    for (r, e) in level.entities_of(ENEMY_ARCHETYPE) {
+        let Some(position) = level.entity_world_pos(r) else { continue };
+        let hp = e.get("health").and_then(|v| v.as_int()).unwrap_or(100).max(1) as f32;
+        let enemy = Enemy::new(level, r, position);
+        let mut attack = AttackDef::new(ENEMY_ARCHETYPE);
+        attack.damage = e.get("damage").and_then(|v| v.as_int()).unwrap_or(10).max(0) as f32;
+        if enemy.ranged {
+            // Shots are hitscan; the timeline only paces them
+            attack.hit_volumes.clear();
+        }
+        combat.attacks.push(attack);
+        combat.combatants.push(Combatant::new(position, 1, Health::new(hp, hp)));
+        enemies.push(enemy);
+    }
+    (combat, enemies)
+}
+
+/// Clip of a model's "attack" state, if it has one
+fn attack_clip(model: &Model) -> Option<&Animation> {
+    let machine = &model.state_machine;
+    let state = machine.find_state(SIGNAL_ATTACK)?;
+    model.animations.iter().find(|a| a.name == machine.states[state].animation)
+}
+
+/// Eye position and heading (radians) of the first player start
+fn spawn_point(level: &Level) -> Option<(Vec3, f32)> {
+    let (r, entity) = level.entities_of(PLAYER_START_ARCHETYPE).next()?;
+    let position = level.entity_world_pos(r)? + Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let facing = entity.get("facing").and_then(|v| v.as_float()).unwrap_or(0.0);
+    Some((position, facing.to_radians()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, Entity, Portal, PortalKind};
+
+    #[test]
+    fn test_spawns_at_player_start() {
+        let mut level = create_test_level();
+        let mut start = Entity {
+            archetype: PLAYER_START_ARCHETYPE.to_string(),
+            position: Vec3::new(256.0, 0.0, 768.0),
+            properties: Default::default(),
+        };
+        start.set("facing", crate::world::PropertyValue::Float(90.0));
+        level.add_entity(0, start);
+
+        let sim = Simulation::new(level, 1);
+        let pos = sim.camera.position;
+        assert_eq!((pos.x, pos.y, pos.z), (256.0, PLAYER_EYE_HEIGHT, 768.0));
+        assert!((sim.camera.rotation_y - 90f32.to_radians()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_attacks_trade_blows_through_combat() {
+        let mut sim = Simulation::new(enemy_level(&[]), 1);
+        sim.cutscene = None;
+        sim.camera.position = Vec3::new(512.0, PLAYER_EYE_HEIGHT, 300.0);
+        sim.camera.rotation_y = 0.0;
+        sim.camera.update_basis();
+        sim.step(InputFrame(button::ATTACK));
+        for _ in 0..60 {
+            sim.step(InputFrame::default());
+        }
+        let enemy = &sim.combat.combatants[1].health;
+        assert_eq!(enemy.max_hp - enemy.hp, sim.attack_damage());
+        assert_eq!(sim.player.max_hp - sim.player.hp, 10.0);
+    }
+
+    #[test]
+    fn test_weapon_moveset_takes_turns_and_breaks_poise() {
+        let mut sword = WeaponDef::new("Sword");
+        sword.moveset = vec!["slash".to_string(), "thrust".to_string()];
+        sword.poise_damage = 40.0;
+
+        let mut sim = Simulation::new(enemy_level(&[]), 1);
+        sim.cutscene = None;
+        sim.set_equipment(Equipment { weapons: vec![sword], armor: Vec::new() });
+        sim.camera.position = Vec3::new(512.0, PLAYER_EYE_HEIGHT, 300.0);
+        sim.camera.rotation_y = 0.0;
+        sim.camera.update_basis();
+        let mut moves = Vec::new();
+        for _ in 0..2 {
+            sim.step(InputFrame(button::ATTACK));
+            moves.push(sim.combat.attacks[PLAYER_COMBATANT].name.clone());
+            for _ in 0..60 {
+                sim.step(InputFrame::default());
+            }
+        }
+        assert_eq!(moves, ["slash", "thrust"]);
+        let enemy = &sim.combat.combatants[1].health;
+        assert!(enemy.poise < enemy.max_poise, "poise = {}", enemy.poise);
+    }
+
+    #[test]
+    fn test_ranged_enemies_shoot_on_sight() {
+        let mut sim = Simulation::new(enemy_level(&[("attack", "ranged")]), 1);
+        sim.cutscene = None;
+        sim.camera.position = Vec3::new(512.0, PLAYER_EYE_HEIGHT, 100.0);
+        sim.step(InputFrame::default());
+        assert_eq!(sim.player.max_hp - sim.player.hp, 10.0);
+    }
+
+    #[test]
+    fn test_enemy_models_animate_and_time_the_attack() {
+        let mut sim = animated_enemy(false, &[(2, crate::modeler::EVENT_HIT_START), (4, crate::modeler::EVENT_HIT_END)]);
+        let def = &sim.combat.attacks[1];
+        assert_eq!((def.total_frames, def.active_start, def.active_end), (20, 2, 4));
+        assert_eq!(def.damage, 10.0);
+
+        sim.step(InputFrame::default());
+        let state = sim.enemies[0].anim.as_ref().map(|a| a.state);
+        let name = state.map(|s| sim.models["grunt"].state_machine.states[s].name.as_str());
+        assert_eq!(name, Some(SIGNAL_ATTACK));
+    }
+
+    /// The test level with an enemy in front of its player spot, with
+    /// `properties` set on it
+    fn enemy_level(properties: &[(&str, &str)]) -> Level {
+        let mut level = create_test_level();
+        let mut enemy = Entity {
+            archetype: ENEMY_ARCHETYPE.to_string(),
+            position: Vec3::new(512.0, 0.0, 900.0),
+            properties: Default::default(),
+        };
+        for (key, value) in properties {
+            enemy.set(key, crate::world::PropertyValue::Text(value.to_string()));
+        }
+        level.add_entity(0, enemy);
+        level
+    }
+
+    /// An enemy from [`enemy_level`], drawn with a model whose attack clip
+    /// carries `events`
+    fn animated_enemy(ranged: bool, events: &[(u32, &str)]) -> Simulation {
+        let mut properties = vec![("model", "grunt")];
+        if ranged {
+            properties.push(("attack", "ranged"));
+        }
+        let level = enemy_level(&properties);
+
+        let mut model = Model::test_cube();
+        let mut swing = crate::modeler::Animation::new("swing");
+        swing.looping = false;
+        swing.set_keyframe(crate::modeler::Keyframe::new(20, 1));
+        for (frame, name) in events {
+            swing.add_event(*frame, name);
+        }
+        model.animations.push(swing);
+        let sm = &mut model.state_machine;
+        let idle = sm.add_state(SIGNAL_IDLE, "", (0.0, 0.0));
+        let attack = sm.add_state(SIGNAL_ATTACK, "swing", (100.0, 0.0));
+        sm.add_transition(idle, attack);
+
+        let mut sim = Simulation::new(level, 1);
+        sim.cutscene = None;
+        sim.set_models(HashMap::from([("grunt".to_string(), model)]));
+        sim.camera.position = Vec3::new(512.0, PLAYER_EYE_HEIGHT, 300.0);
+        sim
+    }
+
+    #[test]
+    fn test_animation_events_queue_sounds() {
+        let mut sim = animated_enemy(false, &[(1, crate::modeler::EVENT_FOOTSTEP), (3, crate::modeler::EVENT_FX_SPAWN)]);
+        for _ in 0..15 {
+            sim.step(InputFrame::default());
+        }
+        let names: Vec<&str> = sim.sounds.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, [crate::modeler::EVENT_FOOTSTEP, crate::modeler::EVENT_FX_SPAWN]);
+        assert_eq!(sim.sounds[0].position, sim.combat.combatants[1].position);
+    }
+
+    #[test]
+    fn test_hit_events_open_and_close_the_hit_window() {
+        let mut sim = animated_enemy(false, &[(2, crate::modeler::EVENT_HIT_START), (4, crate::modeler::EVENT_HIT_END)]);
+        // Close the window the clip events open, so only the events can land the hit
+        sim.combat.attacks[1].active_start = 100;
+        sim.combat.attacks[1].active_end = 100;
+        for _ in 0..60 {
+            sim.step(InputFrame::default());
+        }
+        assert_eq!(sim.player.max_hp - sim.player.hp, 10.0);
+    }
+
+    #[test]
+    fn test_ranged_shots_go_off_on_the_hit_event() {
+        let mut sim = animated_enemy(true, &[(6, crate::modeler::EVENT_HIT_START)]);
+        sim.step(InputFrame::default());
+        assert_eq!(sim.player.hp, sim.player.max_hp);
+        for _ in 0..30 {
+            sim.step(InputFrame::default());
+        }
+        assert_eq!(sim.player.max_hp - sim.player.hp, 10.0);
+    }
+
+    #[test]
+    fn test_walking_out_through_a_warp_portal_teleports() {
+        let mut level = create_test_level();
+        level.rooms.push(level.rooms[0].clone());
+        level.rooms[1].position = Vec3::new(8192.0, 0.0, 0.0);
+        // Doorway across the middle of room 0, facing -Z
+        let doorway = [
+            Vec3::new(0.0, 0.0, 512.0),
+            Vec3::new(1024.0, 0.0, 512.0),
+            Vec3::new(1024.0, 1024.0, 512.0),
+            Vec3::new(0.0, 1024.0, 512.0),
+        ];
+        let mut portal = Portal::new(1, doorway, Vec3::new(0.0, 0.0, -1.0));
+        portal.kind = PortalKind::Warp { destination: Vec3::new(512.0, 512.0, 512.0) };
+        level.rooms[0].portals.push(portal);
+
+        let mut sim = Simulation::new(level, 1);
+        sim.cutscene = None;
+        sim.camera.position = Vec3::new(512.0, 512.0, 500.0);
+        sim.camera.rotation_y = 0.0;
+        sim.camera.update_basis();
+        sim.step(InputFrame(button::FORWARD));
+        let pos = sim.camera.position;
+        assert_eq!((pos.x, pos.y, pos.z), (8704.0, 512.0, 512.0));
+    }
+}
@@ -0,0 +1,428 @@
+//! Room streaming - only rooms near the player are loaded and meshed
+//!
+//! Big worlds don't load every room up front. Only rooms within `hops` portal
+//! hops of the player's room are resident (loaded, meshed and drawn); the
+//! rest hold their metadata only until the player comes close:
+//! - exported levels are split (see `world::split_rooms`): a room's sectors
+//!   are read from its own file when it becomes resident, and dropped again
+//!   when it falls out of range
+//! - the player's own room is loaded and meshed right away so it never pops in
+//! - other rooms are read and meshed on a background thread (native), or
+//!   fetched before the frame (`fetch`) and meshed in it (WASM)
+//! - rooms that fall out of range drop their meshes
+//!
+//! Levels straight from the editor aren't split: all their sectors stay in
+//! memory and only meshes stream.
+//!
+//! Mirror portals in view get a second, reflected pass over the resident meshes.
+//!
+//! Exported levels come with their textures packed into atlases and their UVs
+//! rewritten to match (see `editor::compile_atlases`); the atlases load like
+//! any other texture pack.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::editor::TexturePack;
+use crate::rasterizer::{composite_mirror, reflect_mesh, render_mesh, Camera, Face, Framebuffer, RasterSettings, Texture, Vec3, Vertex};
+use crate::world::{room_data_path, Frustum, Level, Portal, PortalKind, Room, SectorGrid};
+
+/// Portal hops kept resident when the game doesn't say otherwise
+pub const DEFAULT_STREAM_HOPS: usize = 2;
+
+/// Textures of one level as the renderer sees them: just the pack textures
+/// its faces reference, each in its own slot
+struct LevelTextures {
+    /// Slot by (pack, name)
+    slots: HashMap<(String, String), usize>,
+    textures: Vec<Texture>,
+}
+
+impl LevelTextures {
+    fn build(level: &Level, packs: &[TexturePack]) -> Self {
+        let by_name: HashMap<(&str, &str), &Texture> = packs
+            .iter()
+            .flat_map(|p| p.textures.iter().map(move |t| ((p.name.as_str(), t.name.as_str()), t)))
+            .collect();
+
+        // Slot 0 is the fallback for faces without a texture
+        let mut slots: HashMap<(String, String), usize> = HashMap::new();
+        let mut textures: Vec<Texture> = Vec::new();
+        if let Some((pack, tex)) = packs.iter().find_map(|p| p.textures.first().map(|t| (p, t))) {
+            slots.insert((pack.name.clone(), tex.name.clone()), 0);
+            textures.push(tex.clone());
+        }
+        for tex in level.used_textures() {
+            let Some(found) = by_name.get(&(tex.pack.as_str(), tex.name.as_str())) else { continue };
+            slots.entry((tex.pack.clone(), tex.name.clone())).or_insert_with(|| {
+                textures.push((*found).clone());
+                textures.len() - 1
+            });
+        }
+        Self { slots, textures }
+    }
+}
+
+/// Render data of one room
+struct RoomMesh {
+    vertices: Vec<Vertex>,
+    faces: Vec<Face>,
+}
+
+fn mesh_room(room: &Room, textures: &LevelTextures) -> RoomMesh {
+    let (vertices, faces) = room.to_game_render_data(|tex| {
+        if !tex.is_valid() {
+            return Some(0);
+        }
+        textures.slots.get(&(tex.pack.clone(), tex.name.clone())).copied()
+    });
+    RoomMesh { vertices, faces }
+}
+
+/// Rooms reachable from `start` through at most `hops` portals (including `start`)
+pub fn rooms_within(level: &Level, start: usize, hops: usize) -> HashSet<usize> {
+    let mut found = HashSet::new();
+    if start >= level.rooms.len() {
+        return found;
+    }
+    found.insert(start);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((room, depth)) = queue.pop_front() {
+        if depth == hops {
+            continue;
+        }
+        for portal in &level.rooms[room].portals {
+            let target = portal.target_room;
+            if target < level.rooms.len() && found.insert(target) {
+                queue.push_back((target, depth + 1));
+            }
+        }
+    }
+    found
+}
+
+/// Read a split room's sectors from its file (native only). A room that
+/// can't be read is left empty rather than retried every frame.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_room(path: &std::path::Path) -> SectorGrid {
+    crate::world::load_room_data(path).unwrap_or_else(|e| {
+        log::warn!("Room data not loaded from {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+/// A room to mesh, with the file to read its sectors from first if the level
+/// doesn't hold them
+#[cfg(not(target_arch = "wasm32"))]
+struct MeshJob {
+    id: usize,
+    room: Room,
+    file: Option<PathBuf>,
+}
+
+/// Background thread that reads and meshes rooms (native only). Finished
+/// jobs come back with the sectors that were read, if any.
+#[cfg(not(target_arch = "wasm32"))]
+struct MeshWorker {
+    jobs: std::sync::mpsc::Sender<MeshJob>,
+    done: std::sync::mpsc::Receiver<(usize, Option<SectorGrid>, RoomMesh)>,
+    /// Rooms sent but not received back yet
+    pending: HashSet<usize>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MeshWorker {
+    fn spawn(textures: Arc<LevelTextures>) -> Self {
+        let (jobs, job_rx) = std::sync::mpsc::channel::<MeshJob>();
+        let (done_tx, done) = std::sync::mpsc::channel();
+        // Exits once the streamer (and with it `jobs`) is dropped
+        std::thread::spawn(move || {
+            for MeshJob { id, mut room, file } in job_rx {
+                if let Some(file) = &file {
+                    room.sectors = read_room(file);
+                }
+                let mesh = mesh_room(&room, &textures);
+                let sectors = file.map(|_| room.sectors);
+                if done_tx.send((id, sectors, mesh)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { jobs, done, pending: HashSet::new() }
+    }
+}
+
+/// Keeps the meshes of the rooms around the player
+pub struct RoomStreamer {
+    /// How many portal hops from the player's room stay resident
+    pub hops: usize,
+    /// File the level was loaded from, next to which a split level keeps its
+    /// room files
+    pub level_path: Option<PathBuf>,
+    /// Room the player was last seen in
+    current_room: Option<usize>,
+    resident: HashSet<usize>,
+    meshes: BTreeMap<usize, RoomMesh>,
+    textures: Option<Arc<LevelTextures>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    worker: Option<MeshWorker>,
+}
+
+impl RoomStreamer {
+    pub fn new(hops: usize) -> Self {
+        Self {
+            hops,
+            level_path: None,
+            current_room: None,
+            resident: HashSet::new(),
+            meshes: BTreeMap::new(),
+            textures: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            worker: None,
+        }
+    }
+
+    /// Room the player was last seen in
+    pub fn current_room(&self) -> Option<usize> {
+        self.current_room
+    }
+
+    /// Find the player's room and the rooms resident around it
+    fn locate(&mut self, level: &Level, position: Vec3) -> Option<usize> {
+        // Keep the last room while the camera is outside every room
+        self.current_room = level
+            .find_room_at_with_hint(position, self.current_room)
+            .or(self.current_room)
+            .filter(|&r| r < level.rooms.len())
+            .or((!level.rooms.is_empty()).then_some(0));
+        self.resident = self.current_room.map(|current| rooms_within(level, current, self.hops)).unwrap_or_default();
+        self.current_room
+    }
+
+    /// File holding the sectors of a room of a split level
+    fn room_file(&self, room: usize) -> Option<PathBuf> {
+        self.level_path.as_ref().map(|path| room_data_path(path, room))
+    }
+
+    /// Recompute the resident rooms around `position`, unloading and dropping
+    /// the meshes of rooms that left range and loading and meshing the ones
+    /// that entered it
+    pub fn update(&mut self, level: &mut Level, position: Vec3, packs: &[TexturePack]) {
+        let textures = self.textures.get_or_insert_with(|| Arc::new(LevelTextures::build(level, packs))).clone();
+
+        let current = self.locate(level, position);
+        for id in 0..level.rooms.len() {
+            if !self.resident.contains(&id) {
+                level.unload_room(id);
+            }
+        }
+        let Some(current) = current else {
+            self.meshes.clear();
+            return;
+        };
+
+        self.collect_finished(level);
+        self.meshes.retain(|id, _| self.resident.contains(id));
+        if !self.meshes.contains_key(&current) && self.load_now(level, current) {
+            self.meshes.insert(current, mesh_room(&level.rooms[current], &textures));
+        }
+
+        let missing: Vec<usize> = self.resident.iter().filter(|id| !self.meshes.contains_key(id)).copied().collect();
+        self.request(level, missing, textures);
+    }
+
+    /// Load the player's room before the simulation steps in it (on WASM,
+    /// `fetch` already did)
+    pub fn load_current(&mut self, level: &mut Level, position: Vec3) {
+        if let Some(current) = self.locate(level, position) {
+            self.load_now(level, current);
+        }
+    }
+
+    /// Make sure a room's sectors are in memory, reading them if needed
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_now(&self, level: &mut Level, room: usize) -> bool {
+        if !level.is_room_loaded(room) {
+            let sectors = self.room_file(room).map(|file| read_room(&file)).unwrap_or_default();
+            level.load_room(room, sectors);
+        }
+        true
+    }
+
+    /// Rooms are fetched before the frame on WASM (see `fetch`)
+    #[cfg(target_arch = "wasm32")]
+    fn load_now(&self, level: &mut Level, room: usize) -> bool {
+        level.is_room_loaded(room)
+    }
+
+    /// Take the rooms the worker finished since the last update
+    #[cfg(not(target_arch = "wasm32"))]
+    fn collect_finished(&mut self, level: &mut Level) {
+        let Some(worker) = self.worker.as_mut() else {
+            return;
+        };
+        for (id, sectors, mesh) in worker.done.try_iter() {
+            worker.pending.remove(&id);
+            if !self.resident.contains(&id) {
+                continue;
+            }
+            if let Some(sectors) = sectors.filter(|_| !level.is_room_loaded(id)) {
+                level.load_room(id, sectors);
+            }
+            self.meshes.entry(id).or_insert(mesh);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn collect_finished(&mut self, _level: &mut Level) {}
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request(&mut self, level: &Level, missing: Vec<usize>, textures: Arc<LevelTextures>) {
+        let files: Vec<Option<PathBuf>> = missing
+            .iter()
+            .map(|&id| if level.is_room_loaded(id) { None } else { self.room_file(id) })
+            .collect();
+        let worker = self.worker.get_or_insert_with(|| MeshWorker::spawn(textures));
+        for (id, file) in missing.into_iter().zip(files) {
+            if !worker.pending.insert(id) {
+                continue;
+            }
+            let job = MeshJob { id, room: level.rooms[id].clone(), file };
+            if worker.jobs.send(job).is_err() {
+                worker.pending.remove(&id);
+            }
+        }
+    }
+
+    /// No threads on WASM: mesh in this frame the rooms `fetch` loaded
+    #[cfg(target_arch = "wasm32")]
+    fn request(&mut self, level: &Level, missing: Vec<usize>, textures: Arc<LevelTextures>) {
+        for id in missing.into_iter().filter(|&id| level.is_room_loaded(id)) {
+            self.meshes.insert(id, mesh_room(&level.rooms[id], &textures));
+        }
+    }
+
+    /// Load the sectors of the split rooms about to be resident around
+    /// `position`, the player's room first (WASM has no threads to read them on)
+    #[cfg(target_arch = "wasm32")]
+    pub async fn fetch(&mut self, level: &mut Level, position: Vec3) {
+        let Some(current) = self.locate(level, position) else {
+            return;
+        };
+        let mut wanted: Vec<usize> = self.resident.iter().copied().filter(|&id| !level.is_room_loaded(id)).collect();
+        wanted.sort_by_key(|&id| (id != current, id));
+        for id in wanted {
+            let Some(file) = self.room_file(id) else {
+                level.load_room(id, Vec::new());
+                continue;
+            };
+            let sectors: Result<SectorGrid, String> = match macroquad::prelude::load_string(&file.to_string_lossy()).await {
+                Ok(contents) => crate::world::room_data_from_str(&contents).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let sectors = sectors.unwrap_or_else(|e| {
+                log::warn!("Room data not loaded from {}: {}", file.display(), e);
+                Vec::new()
+            });
+            level.load_room(id, sectors);
+        }
+    }
+
+    /// Draw the resident rooms that are in view, then the mirrors among them
+    pub fn render(&self, fb: &mut Framebuffer, level: &Level, camera: &Camera, settings: &RasterSettings) {
+        let textures = self.textures.as_ref().map_or(&[][..], |t| &t.textures[..]);
+        let frustum = Frustum::new(camera, fb.width, fb.height);
+        let mut visible = Vec::new();
+        for (&id, mesh) in &self.meshes {
+            if let Some(room) = level.rooms.get(id).filter(|r| frustum.contains_room(r)) {
+                render_mesh(fb, &mesh.vertices, &mesh.faces, textures, camera, settings);
+                visible.push(room);
+            }
+        }
+        for room in visible {
+            for portal in room.portals.iter().filter(|p| p.kind == PortalKind::Mirror) {
+                self.render_mirror(fb, room, portal, camera, textures, settings);
+            }
+        }
+    }
+
+    /// Reflect every resident room across a mirror portal facing the camera
+    fn render_mirror(&self, fb: &mut Framebuffer, room: &Room, portal: &Portal, camera: &Camera, textures: &[Texture], settings: &RasterSettings) {
+        let quad = portal.vertices.map(|v| v + room.position);
+        if (camera.position - quad[0]).dot(portal.normal) <= 0.0 {
+            return;
+        }
+        let mut reflection = Framebuffer::new(fb.width, fb.height);
+        reflection.field = fb.field;
+        for mesh in self.meshes.values() {
+            let (vertices, faces) = reflect_mesh(&mesh.vertices, &mesh.faces, quad[0], portal.normal);
+            render_mesh(&mut reflection, &vertices, &faces, textures, camera, settings);
+        }
+        composite_mirror(fb, &reflection, quad, portal.normal, camera);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, load_level_from_str, save_split_level, Portal};
+
+    /// Rooms 0-1-2-3 linked in a line by portals
+    fn corridor() -> Level {
+        let mut level = create_test_level();
+        let template = level.rooms[0].clone();
+        level.rooms = (0..4)
+            .map(|i| {
+                let mut room = template.clone();
+                room.id = i;
+                room.portals.clear();
+                for target in [i.wrapping_sub(1), i + 1].into_iter().filter(|&t| t < 4) {
+                    room.portals.push(Portal::new(target, [Vec3::ZERO; 4], Vec3::ZERO));
+                }
+                room
+            })
+            .collect();
+        level
+    }
+
+    #[test]
+    fn test_resident_rooms_follow_portal_hops() {
+        let level = corridor();
+        assert_eq!(rooms_within(&level, 0, 0), HashSet::from([0]));
+        assert_eq!(rooms_within(&level, 0, 2), HashSet::from([0, 1, 2]));
+        assert_eq!(rooms_within(&level, 1, 2), HashSet::from([0, 1, 2, 3]));
+        assert!(rooms_within(&level, 9, 2).is_empty());
+    }
+
+    #[test]
+    fn test_player_room_is_meshed_immediately() {
+        let mut level = corridor();
+        let mut streamer = RoomStreamer::new(0);
+        streamer.update(&mut level, Vec3::new(-1.0e6, 0.0, 0.0), &[]);
+        assert_eq!(streamer.meshes.len(), 1);
+    }
+
+    #[test]
+    fn test_split_rooms_out_of_range_hold_metadata_only() {
+        let dir = std::env::temp_dir().join(format!("bonnie_streaming_{}", std::process::id()));
+        let path = dir.join("corridor.ron");
+        let whole = corridor();
+        save_split_level(&whole, &path).unwrap();
+        let mut level = load_level_from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!((0..4).all(|id| !level.is_room_loaded(id)));
+
+        let mut streamer = RoomStreamer::new(0);
+        streamer.level_path = Some(path);
+        let outside = Vec3::new(-1.0e6, 0.0, 0.0);
+        streamer.update(&mut level, outside, &[]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(level.rooms[0].sectors, whole.rooms[0].sectors);
+        assert!((1..4).all(|id| !level.is_room_loaded(id) && level.rooms[id].sectors.is_empty()));
+        assert!(!streamer.meshes[&0].faces.is_empty());
+
+        // Leaving range drops the sectors again
+        streamer.current_room = Some(3);
+        streamer.update(&mut level, outside, &[]);
+        assert!(!level.is_room_loaded(0) && level.rooms[0].sectors.is_empty());
+    }
+}
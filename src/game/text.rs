@@ -0,0 +1,177 @@
+//! Game text - localized in-game strings
+//!
+//! Separate from the editor's UI translations (`i18n`): the player-facing
+//! text of a game (dialogue, item names, menu entries) lives in the
+//! project's `assets/project/text.ron` as a table of keys with one column
+//! per language, edited on the Project panel's Text page:
+//!
+//! ```ron
+//! (
+//!     languages: ["en", "it"],
+//!     rows: [
+//!         (key: "menu.new_game", values: ["New Game", "Nuova partita"]),
+//!         (key: "item.medipack", values: ["Medipack", ""]),
+//!     ],
+//! )
+//! ```
+//!
+//! The first column is the source language: empty cells fall back to it,
+//! and keys missing from the table to the text the game was built with.
+//! The player picks the language on the front end's Options screen.
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+/// Location of the project's game text
+pub const TEXT_PATH: &str = "assets/project/text.ron";
+
+/// One key and its text in each language
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextRow {
+    pub key: String,
+    /// Text per language column (may be shorter than the language list)
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl TextRow {
+    /// Text in a language column ("" when untranslated)
+    pub fn value(&self, language: usize) -> &str {
+        self.values.get(language).map_or("", |v| v.as_str())
+    }
+}
+
+/// The project's string table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameText {
+    /// Language codes, one per column, the source language first
+    pub languages: Vec<String>,
+    pub rows: Vec<TextRow>,
+}
+
+impl Default for GameText {
+    fn default() -> Self {
+        Self { languages: vec!["en".to_string()], rows: Vec::new() }
+    }
+}
+
+impl GameText {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read game text: {}", e))?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse game text: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize game text: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn row(&self, key: &str) -> Option<&TextRow> {
+        self.rows.iter().find(|r| r.key == key)
+    }
+
+    /// Text of a key in a language, falling back to the source language
+    pub fn get(&self, key: &str, language: usize) -> Option<&str> {
+        let row = self.row(key)?;
+        [row.value(language), row.value(0)].into_iter().find(|v| !v.is_empty())
+    }
+
+    /// Text of a key in a language, or `fallback` when the table has none
+    pub fn text<'a>(&'a self, key: &str, language: usize, fallback: &'a str) -> &'a str {
+        self.get(key, language).unwrap_or(fallback)
+    }
+
+    /// Display name of an item, weapon or armor (`item.<name>` keys)
+    pub fn item_name<'a>(&'a self, item: &'a str, language: usize) -> &'a str {
+        self.text(&format!("item.{}", item), language, item)
+    }
+
+    /// Add a row with a key not in the table yet, returning its index
+    pub fn add_row(&mut self, key: &str) -> usize {
+        let mut unique = key.to_string();
+        let mut n = 2;
+        while self.row(&unique).is_some() {
+            unique = format!("{}_{}", key, n);
+            n += 1;
+        }
+        self.rows.push(TextRow { key: unique, values: vec![String::new(); self.languages.len()] });
+        self.rows.len() - 1
+    }
+
+    /// Set the text of a row in a language column
+    pub fn set(&mut self, row: usize, language: usize, text: &str) {
+        let Some(row) = self.rows.get_mut(row) else { return };
+        if row.values.len() <= language {
+            row.values.resize(language + 1, String::new());
+        }
+        row.values[language] = text.to_string();
+    }
+
+    /// Add an empty language column
+    pub fn add_language(&mut self, code: &str) {
+        self.languages.push(code.to_string());
+    }
+
+    /// Remove a language column (the source language stays)
+    pub fn remove_language(&mut self, language: usize) {
+        if language == 0 || language >= self.languages.len() {
+            return;
+        }
+        self.languages.remove(language);
+        for row in &mut self.rows {
+            if language < row.values.len() {
+                row.values.remove(language);
+            }
+        }
+    }
+
+    /// Rows with no text of their own in a language column
+    pub fn missing(&self, language: usize) -> usize {
+        self.rows.iter().filter(|r| r.value(language).is_empty()).count()
+    }
+
+    /// Code of a language column ("" when out of range)
+    pub fn language_code(&self, language: usize) -> &str {
+        self.languages.get(language).map_or("", |c| c.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_falls_back_to_source_language() {
+        let mut text = GameText::default();
+        text.add_language("it");
+        let row = text.add_row("menu.quit");
+        text.set(row, 0, "Quit");
+        text.set(row, 1, "Esci");
+        let row = text.add_row("item.medipack");
+        text.set(row, 0, "Medipack");
+
+        assert_eq!(text.get("menu.quit", 1), Some("Esci"));
+        assert_eq!(text.get("item.medipack", 1), Some("Medipack"));
+        assert_eq!(text.text("menu.options", 1, "Options"), "Options");
+        assert_eq!(text.item_name("medipack", 1), "Medipack");
+        assert_eq!(text.item_name("key", 1), "key");
+        assert_eq!(text.missing(1), 1);
+        assert_eq!(text.add_row("menu.quit"), 2);
+        assert_eq!(text.rows[2].key, "menu.quit_2");
+
+        text.remove_language(1);
+        text.remove_language(0);
+        assert_eq!(text.languages, ["en"]);
+        assert_eq!(text.get("menu.quit", 1), Some("Quit"));
+
+        let ron = ron::to_string(&text).unwrap();
+        assert_eq!(GameText::from_ron(&ron).unwrap(), text);
+    }
+}
@@ -0,0 +1,128 @@
+//! Viewer mode - read-only walkthrough of a single level
+//!
+//! Opens a level straight into the free camera, with no editor or front end,
+//! for sharing playable previews of maps:
+//! - native: `bonnie-engine --view assets/levels/level_001.ron`
+//! - web: `index.html?view=assets/levels/level_001.ron`
+//!
+//! The level's intro camera path plays first, as in the game (Enter skips it).
+
+use macroquad::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::editor::TexturePack;
+use crate::rasterizer::{Framebuffer, RasterSettings, TextPalette, GLYPH_HEIGHT, HEIGHT, WIDTH};
+use super::{blit, blit_rect, load_level, new_seed, PlaySession, RoomAudioPlayer};
+
+/// Command-line flag that opens the viewer (native)
+pub const VIEW_FLAG: &str = "--view";
+
+/// Controls shown until the viewer hides them
+const CONTROLS_HINT: &str = "WASD move  Arrows look  H hide";
+
+/// Level to open in viewer mode, from `--view <path>` (or `--view=<path>`)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn viewer_level() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    viewer_level_from_args(&args)
+}
+
+/// Level to open in viewer mode, from the page's `?view=<path>` parameter
+#[cfg(target_arch = "wasm32")]
+pub fn viewer_level() -> Option<PathBuf> {
+    extern "C" {
+        fn bonnie_get_view_param_len() -> usize;
+        fn bonnie_copy_view_param(ptr: *mut u8, max_len: usize) -> usize;
+    }
+    let len = unsafe { bonnie_get_view_param_len() };
+    if len == 0 {
+        return None;
+    }
+    let mut buf = vec![0u8; len];
+    unsafe {
+        bonnie_copy_view_param(buf.as_mut_ptr(), len);
+    }
+    Some(PathBuf::from(String::from_utf8_lossy(&buf).to_string()))
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn viewer_level_from_args(args: &[String]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == VIEW_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix(VIEW_FLAG).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Walk through a level until the viewer quits (Escape, native only)
+pub async fn run_viewer(path: PathBuf) {
+    let packs = TexturePack::load_from_manifest().await;
+    let settings = RasterSettings::default();
+    let mut fb = Framebuffer::new(WIDTH, HEIGHT);
+    let name = level_name(&path);
+
+    let mut session = match load_level(&path).await {
+        Ok(level) => Some(PlaySession::new(level, path.clone(), new_seed())),
+        Err(e) => {
+            log::error!("{}", e);
+            None
+        }
+    };
+    let mut show_hint = true;
+    let mut room_audio = RoomAudioPlayer::new();
+    log::info!("Viewing {}", path.display());
+
+    loop {
+        clear_background(BLACK);
+        if cfg!(not(target_arch = "wasm32")) && is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        if is_key_pressed(KeyCode::H) {
+            show_hint = !show_hint;
+        }
+
+        match session.as_mut() {
+            Some(play) => {
+                #[cfg(target_arch = "wasm32")]
+                play.fetch_rooms().await;
+                play.update(get_frame_time());
+                play.render(&mut fb, &packs, &settings);
+                room_audio.update(play.camera_room(), get_frame_time());
+                if show_hint && play.sim.cutscene.is_none() {
+                    fb.draw_text(&name, 4, 4, &TextPalette::GOLD);
+                    fb.draw_text(CONTROLS_HINT, 4, (fb.height - GLYPH_HEIGHT - 3) as i32, &TextPalette::WHITE);
+                }
+                blit(&fb, blit_rect(&fb), &settings, &play.sim.level.ambience, None);
+            }
+            None => {
+                let message = format!("Could not open {}", path.display());
+                draw_text(&message, 10.0, 24.0, 18.0, RED);
+            }
+        }
+
+        next_frame().await;
+    }
+}
+
+/// Level file name without extension, for the overlay
+fn level_name(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_flag_parsing() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(viewer_level_from_args(&args(&["--view", "a.ron"])), Some(PathBuf::from("a.ron")));
+        assert_eq!(viewer_level_from_args(&args(&["--view=b.ron"])), Some(PathBuf::from("b.ron")));
+        assert_eq!(viewer_level_from_args(&args(&["--view"])), None);
+        assert_eq!(viewer_level_from_args(&args(&["diff", "a.ron", "b.ron"])), None);
+    }
+}
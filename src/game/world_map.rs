@@ -0,0 +1,231 @@
+//! World map - how the project's levels connect
+//!
+//! Levels are nodes and their exits are edges. An exit is an `exit` entity
+//! the player walks into; the map links it, by the level's path and the
+//! exit's `name`, to a level and a spawn id there to arrive at: an
+//! `entrance` entity with that `spawn`, or else the checkpoint with that id.
+//! Kept in the project's `assets/project/world.ron` with the graph layout,
+//! edited in the editor's World Map view:
+//!
+//! ```ron
+//! (
+//!     nodes: [(level: "assets/levels/level_001.ron", x: 40.0, y: 60.0)],
+//!     links: [(from: "assets/levels/level_001.ron", exit: "east gate",
+//!              to: "assets/levels/level_002.ron", spawn: 1)],
+//! )
+//! ```
+//!
+//! Exits the map doesn't link lead where their own `level` and `spawn`
+//! properties say, so two levels can be strung together without a map.
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::Vec3;
+use crate::world::{Entity, EntityRef, Level};
+use super::{checkpoint_ids, PLAYER_EYE_HEIGHT};
+
+/// Location of the project's world map
+pub const WORLD_MAP_PATH: &str = "assets/project/world.ron";
+
+/// Entity archetype the player leaves a level through
+pub const EXIT_ARCHETYPE: &str = "exit";
+
+/// Entity archetype the player arrives at from another level's exit
+pub const ENTRANCE_ARCHETYPE: &str = "entrance";
+
+/// Where a level sits in the graph view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapNode {
+    pub level: PathBuf,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Where one exit leads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelLink {
+    pub from: PathBuf,
+    /// `name` of the exit entity in `from`
+    pub exit: String,
+    pub to: PathBuf,
+    /// Spawn id in `to` the player arrives at (entrance, else checkpoint)
+    pub spawn: i32,
+}
+
+/// The project's level graph
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldMap {
+    pub nodes: Vec<MapNode>,
+    pub links: Vec<LevelLink>,
+}
+
+impl WorldMap {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read world map: {}", e))?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse world map: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize world map: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Where an exit of a level leads, if it's linked
+    pub fn link(&self, from: &Path, exit: &str) -> Option<&LevelLink> {
+        self.links.iter().find(|l| l.from == from && l.exit == exit)
+    }
+
+    /// Where an exit of a level leads: its link on the map, else the
+    /// exit's own `level` and `spawn` properties
+    pub fn destination(&self, from: &Path, level: &Level, exit: &str) -> Option<LevelLink> {
+        if let Some(link) = self.link(from, exit) {
+            return Some(link.clone());
+        }
+        let (_, entity) = level.entities_of(EXIT_ARCHETYPE).find(|(_, e)| exit_name(e) == exit)?;
+        let to = entity.get("level").and_then(|v| v.as_str()).filter(|s| !s.is_empty())?;
+        let spawn = entity.get("spawn").and_then(|v| v.as_int()).unwrap_or(1);
+        Some(LevelLink { from: from.to_path_buf(), exit: exit.to_string(), to: PathBuf::from(to), spawn })
+    }
+
+    /// Link an exit, replacing where it led before
+    pub fn set_link(&mut self, from: &Path, exit: &str, to: &Path, spawn: i32) {
+        self.unlink(from, exit);
+        self.links.push(LevelLink { from: from.to_path_buf(), exit: exit.to_string(), to: to.to_path_buf(), spawn });
+    }
+
+    pub fn unlink(&mut self, from: &Path, exit: &str) {
+        self.links.retain(|l| !(l.from == from && l.exit == exit));
+    }
+
+    pub fn node(&self, level: &Path) -> Option<&MapNode> {
+        self.nodes.iter().find(|n| n.level == level)
+    }
+
+    pub fn node_mut(&mut self, level: &Path) -> Option<&mut MapNode> {
+        self.nodes.iter_mut().find(|n| n.level == level)
+    }
+
+    /// Give levels not on the map yet a place, in a row below the others
+    pub fn place_new(&mut self, levels: &[PathBuf]) {
+        let mut x = 20.0;
+        let y = self.nodes.iter().map(|n| n.y + 160.0).fold(20.0, f32::max);
+        for level in levels {
+            if self.node(level).is_none() {
+                self.nodes.push(MapNode { level: level.clone(), x, y });
+                x += 200.0;
+            }
+        }
+    }
+}
+
+/// Names of a level's exits, in entity order
+pub fn level_exits(level: &Level) -> Vec<String> {
+    level.entities_of(EXIT_ARCHETYPE).map(|(_, e)| exit_name(e)).collect()
+}
+
+/// `name` of an exit entity ("exit" when unset)
+pub fn exit_name(entity: &Entity) -> String {
+    entity.get("name").and_then(|v| v.as_str()).unwrap_or("exit").to_string()
+}
+
+/// `spawn` id of an entrance entity
+fn entrance_spawn(entity: &Entity) -> i32 {
+    entity.get("spawn").and_then(|v| v.as_int()).unwrap_or(1)
+}
+
+/// Eye position and heading (radians) of the entrance with spawn `id`
+pub fn entrance_pose(level: &Level, id: i32) -> Option<(Vec3, f32)> {
+    let (r, entity) = level.entities_of(ENTRANCE_ARCHETYPE).find(|(_, e)| entrance_spawn(e) == id)?;
+    let position = level.entity_world_pos(r)? + Vec3::new(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let facing = entity.get("facing").and_then(|v| v.as_float()).unwrap_or(0.0);
+    Some((position, facing.to_radians()))
+}
+
+/// Spawn ids a level can be arrived at (entrances and checkpoints), sorted
+pub fn level_spawns(level: &Level) -> Vec<i32> {
+    let mut ids: Vec<i32> = level.entities_of(ENTRANCE_ARCHETYPE).map(|(_, e)| entrance_spawn(e)).collect();
+    ids.extend(checkpoint_ids(level));
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// The exit whose trigger radius contains `eye`, if any
+pub fn exit_at(level: &Level, eye: Vec3) -> Option<EntityRef> {
+    level.entities_of(EXIT_ARCHETYPE).find_map(|(r, e)| {
+        let radius = e.get("radius").and_then(|v| v.as_float()).unwrap_or(512.0);
+        let base = level.entity_world_pos(r)?;
+        let (dx, dz) = (eye.x - base.x, eye.z - base.z);
+        let above = eye.y - base.y;
+        (dx * dx + dz * dz <= radius * radius && (0.0..=2048.0).contains(&above)).then_some(r)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, PropertyValue};
+
+    #[test]
+    fn test_links_replace_and_round_trip() {
+        let (a, b, c) = (Path::new("a.ron"), Path::new("b.ron"), Path::new("c.ron"));
+        let mut map = WorldMap::default();
+        map.set_link(a, "north", b, 1);
+        map.set_link(a, "north", c, 2);
+        map.set_link(b, "back", a, 1);
+        assert_eq!(map.links.len(), 2);
+        let link = map.link(a, "north").unwrap();
+        assert_eq!((link.to.as_path(), link.spawn), (c, 2));
+        map.unlink(a, "north");
+        assert!(map.link(a, "north").is_none());
+
+        map.place_new(&[a.to_path_buf(), b.to_path_buf()]);
+        map.place_new(&[a.to_path_buf(), c.to_path_buf()]);
+        assert_eq!(map.nodes.len(), 3);
+        assert!(map.node(c).unwrap().y > map.node(a).unwrap().y);
+
+        let ron = ron::to_string(&map).unwrap();
+        assert_eq!(WorldMap::from_ron(&ron).unwrap(), map);
+    }
+
+    #[test]
+    fn test_unlinked_exit_uses_its_properties() {
+        let mut level = create_test_level();
+        let mut exit = Entity {
+            archetype: EXIT_ARCHETYPE.to_string(),
+            position: Vec3::new(512.0, 0.0, 512.0),
+            properties: Default::default(),
+        };
+        exit.set("name", PropertyValue::Text("door".to_string()));
+        exit.set("level", PropertyValue::Text("b.ron".to_string()));
+        exit.set("spawn", PropertyValue::Int(3));
+        level.add_entity(0, exit);
+        let mut entrance = Entity {
+            archetype: ENTRANCE_ARCHETYPE.to_string(),
+            position: Vec3::new(256.0, 0.0, 256.0),
+            properties: Default::default(),
+        };
+        entrance.set("spawn", PropertyValue::Int(3));
+        level.add_entity(0, entrance);
+
+        let mut map = WorldMap::default();
+        let a = Path::new("a.ron");
+        let target = map.destination(a, &level, "door").unwrap();
+        assert_eq!((target.to.as_path(), target.spawn), (Path::new("b.ron"), 3));
+        assert!(map.destination(a, &level, "window").is_none());
+        map.set_link(a, "door", Path::new("c.ron"), 1);
+        assert_eq!(map.destination(a, &level, "door").unwrap().to, Path::new("c.ron"));
+
+        assert_eq!(level_spawns(&level), vec![3]);
+        let (eye, _) = entrance_pose(&level, 3).unwrap();
+        assert_eq!((eye.x, eye.y, eye.z), (256.0, PLAYER_EYE_HEIGHT, 256.0));
+        assert!(entrance_pose(&level, 4).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! Localization of UI strings
+//!
+//! String tables live in `assets/lang/<code>.ron` as a map of key -> text and
+//! are embedded at build time (so WASM needs no file access):
+//! - `tr("editor.undo")` looks a key up in the current language
+//! - `trf("editor.room_label", &[&3])` also fills `{0}`, `{1}`, ... placeholders
+//! - keys missing from a translation fall back to English, then to the key
+//!
+//! To add a language, copy `en.ron`, translate the values and list the file
+//! in `LANGUAGES`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// A bundled translation
+pub struct Language {
+    /// ISO 639-1 code, also the file name
+    pub code: &'static str,
+    /// Name shown in the language selector (in the language itself)
+    pub name: &'static str,
+    source: &'static str,
+}
+
+/// Language used when a key is missing from a translation
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// All bundled languages, English first
+pub const LANGUAGES: &[Language] = &[
+    Language { code: "en", name: "English", source: include_str!("../assets/lang/en.ron") },
+    Language { code: "it", name: "Italiano", source: include_str!("../assets/lang/it.ron") },
+];
+
+type StringTable = HashMap<String, String>;
+
+struct Catalog {
+    code: &'static str,
+    strings: StringTable,
+    fallback: StringTable,
+}
+
+thread_local! {
+    static CATALOG: RefCell<Catalog> = RefCell::new(Catalog {
+        code: FALLBACK_LANGUAGE,
+        strings: StringTable::new(),
+        fallback: parse_table(FALLBACK_LANGUAGE).unwrap_or_default(),
+    });
+}
+
+fn find_language(code: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|l| l.code == code)
+}
+
+fn parse_table(code: &str) -> Result<StringTable, String> {
+    let language = find_language(code).ok_or_else(|| format!("Unknown language '{}'", code))?;
+    ron::from_str(language.source).map_err(|e| format!("Failed to parse assets/lang/{}.ron: {}", code, e))
+}
+
+/// Switch the UI language
+pub fn set_language(code: &str) -> Result<(), String> {
+    let language = find_language(code).ok_or_else(|| format!("Unknown language '{}'", code))?;
+    let strings = if language.code == FALLBACK_LANGUAGE { StringTable::new() } else { parse_table(code)? };
+    CATALOG.with(|c| {
+        let mut catalog = c.borrow_mut();
+        catalog.code = language.code;
+        catalog.strings = strings;
+    });
+    Ok(())
+}
+
+/// Code of the current language
+pub fn current_language() -> &'static str {
+    CATALOG.with(|c| c.borrow().code)
+}
+
+/// Next bundled language after the current one (wraps around)
+pub fn next_language() -> &'static Language {
+    let current = current_language();
+    let index = LANGUAGES.iter().position(|l| l.code == current).unwrap_or(0);
+    &LANGUAGES[(index + 1) % LANGUAGES.len()]
+}
+
+/// Name of the current language
+pub fn current_language_name() -> &'static str {
+    find_language(current_language()).map_or(FALLBACK_LANGUAGE, |l| l.name)
+}
+
+/// Translate a key
+pub fn tr(key: &str) -> String {
+    CATALOG.with(|c| {
+        let catalog = c.borrow();
+        catalog
+            .strings
+            .get(key)
+            .or_else(|| catalog.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    })
+}
+
+/// Translate a key and fill `{0}`, `{1}`, ... with `args`
+pub fn trf(key: &str, args: &[&dyn Display]) -> String {
+    fill_placeholders(&tr(key), args)
+}
+
+fn fill_placeholders(text: &str, args: &[&dyn Display]) -> String {
+    let mut out = text.to_string();
+    for (i, arg) in args.iter().enumerate() {
+        out = out.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<usize> {
+        (0..10).filter(|i| text.contains(&format!("{{{}}}", i))).collect()
+    }
+
+    #[test]
+    fn test_translations_match_english() {
+        let english = parse_table(FALLBACK_LANGUAGE).unwrap();
+        for language in LANGUAGES {
+            let table = parse_table(language.code).unwrap();
+            for (key, text) in &table {
+                let original = english.get(key).unwrap_or_else(|| panic!("{}: unknown key {}", language.code, key));
+                assert_eq!(placeholders(text), placeholders(original), "{}: placeholders differ for {}", language.code, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_fallback() {
+        set_language("it").unwrap();
+        assert_eq!(current_language(), "it");
+        assert_eq!(tr("common.undo"), "Annulla");
+        assert_eq!(tr("no.such.key"), "no.such.key");
+        assert_eq!(trf("editor.room_label", &[&3]), "Stanza: 3");
+        set_language("en").unwrap();
+        assert_eq!(tr("common.undo"), "Undo");
+        assert!(set_language("xx").is_err());
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        assert_eq!(fill_placeholders("{0} of {1}", &[&2, &"five"]), "2 of five");
+        assert_eq!(fill_placeholders("no args", &[&1]), "no args");
+    }
+}
@@ -3,15 +3,16 @@
 //! Displays introduction, motivation, and FAQ for Bonnie Engine.
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, draw_link_row};
+use crate::i18n::tr;
+use crate::ui::{Rect, draw_link_row, ui_pixel_scale};
 use crate::VERSION;
 
 /// Colors matching the editor theme
-const BG_COLOR: Color = Color::new(0.10, 0.10, 0.12, 1.0);
-const TEXT_COLOR: Color = Color::new(0.9, 0.9, 0.9, 1.0);
-const MUTED_COLOR: Color = Color::new(0.6, 0.6, 0.65, 1.0);
-const ACCENT_COLOR: Color = Color::new(0.0, 0.75, 0.9, 1.0);
-const SECTION_BG: Color = Color::new(0.12, 0.12, 0.14, 1.0);
+pub const BG_COLOR: Color = Color::new(0.10, 0.10, 0.12, 1.0);
+pub const TEXT_COLOR: Color = Color::new(0.9, 0.9, 0.9, 1.0);
+pub const MUTED_COLOR: Color = Color::new(0.6, 0.6, 0.65, 1.0);
+pub const ACCENT_COLOR: Color = Color::new(0.0, 0.75, 0.9, 1.0);
+pub const SECTION_BG: Color = Color::new(0.12, 0.12, 0.14, 1.0);
 
 /// State for the landing page (scroll position)
 pub struct LandingState {
@@ -26,8 +27,8 @@ impl LandingState {
 
 /// Draw the landing page
 pub fn draw_landing(rect: Rect, state: &mut LandingState) {
-    // DPI scale for high-DPI displays (layout only, not font sizes)
-    let dpi = screen_dpi_scale();
+    // Physical pixels per UI pixel (DPI and UI scale)
+    let dpi = ui_pixel_scale();
 
     // Background
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
@@ -38,7 +39,7 @@ pub fn draw_landing(rect: Rect, state: &mut LandingState) {
     state.scroll_y = state.scroll_y.min(0.0); // Can't scroll above top
 
     // Enable scissor clipping to prevent content from overflowing into tab bar
-    // Scissor uses physical pixels, so scale by DPI
+    // Scissor uses physical pixels, so scale by DPI and UI scale
     gl_use_default_material();
     unsafe {
         get_internal_gl().quad_gl.scissor(
@@ -62,106 +63,68 @@ pub fn draw_landing(rect: Rect, state: &mut LandingState) {
     draw_text(&title, content_x, y + 32.0, 32.0, ACCENT_COLOR);
     y += 44.0;
 
-    draw_text("A PS1-Style Modern Game Engine", content_x, y + 18.0, 18.0, MUTED_COLOR);
+    draw_text(&tr("landing.subtitle"), content_x, y + 18.0, 18.0, MUTED_COLOR);
     y += 54.0;
 
     // === INTRO SECTION ===
-    y = draw_section(content_x, y, content_width, "What is this?", &[
-        "Bonnie Engine is a complete game development environment built from scratch in",
-        "Rust, designed to recreate the authentic PlayStation 1 aesthetic.",
-        "",
-        "Everything you see - the software rasterizer, the editor UI, the level format -",
-        "is custom code. The world-building system takes heavy inspiration from the",
-        "Tomb Raider series, which remains one of the best examples of how complex 3D",
-        "worlds could be achieved on PS1 hardware.",
-        "",
-        "A key principle: everything runs as a single platform, both natively and in the",
-        "browser. Same code, same tools, same experience - no compromises on either side.",
-    ]);
+    y = draw_section(content_x, y, content_width, &tr("landing.what.title"), &tr("landing.what.body"));
 
     // === WHY SECTION ===
-    y = draw_section(content_x, y, content_width, "Why build this?", &[
-        "It started with a question: what would a Souls-like have looked like on a PS1?",
-        "There are great examples like Bloodborne PSX by Lilith Walther, built in Unity.",
-        "I wanted to try my own approach from scratch.",
-        "",
-        "But I can see this expanding beyond Souls-like games. The engine could support",
-        "tactical RPGs (think FF Tactics), platformers, survival horror, or any genre that",
-        "benefits from the PS1 aesthetic. The goal is a flexible creative tool.",
-        "",
-        "Modern retro-style games typically achieve the aesthetic top-down with shaders",
-        "and post-processing, often with great results. I wanted to try the opposite: a",
-        "bottom-up approach with a real software rasterizer that works like the PS1's GTE.",
-        "",
-        "I tried several approaches before landing here: first LOVR, then Picotron, even",
-        "coding for actual PS1 hardware. Each had limitations - primitive SDKs, distribution",
-        "headaches, or not enough flexibility. Rust + WASM turned out to be the sweet spot:",
-        "native performance, browser deployment, and a modern toolchain.",
-    ]);
+    y = draw_section(content_x, y, content_width, &tr("landing.why.title"), &tr("landing.why.body"));
 
     // === WHERE TO START SECTION ===
-    y = draw_section(content_x, y, content_width, "Where to start", &[
-        "Use the tabs at the top to switch between the available tools:",
-        "",
-        "World - Build levels with floors, ceilings, walls, and portals. Inspired by the",
-        "Tomb Raider Level Editor (TRLE), with a 2D grid view and 3D preview.",
-        "",
-        "Assets - Create and animate 3D models, props, and characters using PS1-style",
-        "segmented animation. Each body part is a separate mesh that can be keyframed.",
-        "",
-        "Music - Compose music using a pattern-based tracker. Supports SF2 soundfonts",
-        "with 8 channels and classic tracker effects.",
-    ]);
+    y = draw_section(content_x, y, content_width, &tr("landing.start.title"), &tr("landing.start.body"));
 
     // === FAQ SECTION ===
-    draw_text("FAQ", content_x, y + 16.0, 16.0, ACCENT_COLOR);
+    draw_text(&tr("landing.faq"), content_x, y + 16.0, 16.0, ACCENT_COLOR);
     y += 30.0;
 
     y = draw_faq_item(content_x, y, content_width,
-        "Is this a game or an engine?",
-        "Both! The goal is to ship a complete Souls-like game, but the engine and\neditor are part of the package. Think of it like RPG Maker but for PS1 games.",
+        &tr("landing.faq.game.q"),
+        &tr("landing.faq.game.a"),
     );
 
     y = draw_faq_item(content_x, y, content_width,
-        "Why not use Unity/Unreal/Godot?",
-        "Those engines are designed for modern games. Getting true PS1-style rendering\nrequires fighting against their design. Building from scratch lets me embrace\nthe limitations rather than simulate them.",
+        &tr("landing.faq.engines.q"),
+        &tr("landing.faq.engines.a"),
     );
 
     y = draw_faq_item(content_x, y, content_width,
-        "Will this be on Steam?",
-        "Probably! The native build is intended for Steam distribution.\nThe web version may be offered as a free demo or a SaaS product - not to\nmonetize, just to cover hosting costs if the userbase grows.\n\nThis will always be fully open source. Even if there's a paid Steam version,\nyou can always clone the repo and build it yourself for free.",
+        &tr("landing.faq.steam.q"),
+        &tr("landing.faq.steam.a"),
     );
 
     y = draw_faq_item(content_x, y, content_width,
-        "Can I use this to make my own game?",
-        "Absolutely - feel free to use this however you like! Contributing assets or ideas to my\nproject would be awesome, but you're welcome to build your own thing too. Just keep in mind\nthis isn't a general-purpose engine - it's tailored to my specific vision, so it may lack\nfeatures you'd expect. Note: Some code and assets have their own licenses. Please review\nTHIRD_PARTY.md before using or distributing anything.",
+        &tr("landing.faq.own_game.q"),
+        &tr("landing.faq.own_game.a"),
     );
 
     y = draw_faq_item(content_x, y, content_width,
-        "Will you add scripting language support?",
-        "Maybe, but it's not the immediate plan. The focus is on building a PS1-like\nplatform with modern, flexible tools. Scripting might come later if there's\na clear need for it.",
+        &tr("landing.faq.scripting.q"),
+        &tr("landing.faq.scripting.a"),
     );
 
     y = draw_faq_item(content_x, y, content_width,
-        "What's with the name \"Bonnie\"?",
-        "Back in my short but intense music career as a metal guitarist, we'd record\ndemos on a cheap laptop with makeshift gear in whatever garage was available.\nWe jokingly called it \"Bonnie Studios\" - a playful twist on my last name.\nThis engine carries on that DIY spirit.",
+        &tr("landing.faq.name.q"),
+        &tr("landing.faq.name.a"),
     );
 
     // === FOOTER ===
     y += 20.0;
-    draw_text("Created by Emanuele Bonura", content_x, y + 16.0, 16.0, TEXT_COLOR);
+    draw_text(&tr("landing.created_by"), content_x, y + 16.0, 16.0, TEXT_COLOR);
     y += 28.0;
 
     // Clickable links row
     let link_color = MUTED_COLOR;
     let hover_color = ACCENT_COLOR;
+    let coffee = tr("landing.coffee");
     draw_link_row(
         content_x,
         y + 14.0,
         &[
             ("GitHub", "https://github.com/EBonura/bonnie-engine"),
             ("itch.io", "https://bonnie-games.itch.io/"),
-            ("Buy Me a Coffee", "https://buymeacoffee.com/bonniegames"),
+            (coffee.as_str(), "https://buymeacoffee.com/bonniegames"),
         ],
         "  |  ",
         14.0,
@@ -183,7 +146,8 @@ pub fn draw_landing(rect: Rect, state: &mut LandingState) {
 }
 
 /// Draw a section with title and body text
-fn draw_section(x: f32, y: f32, width: f32, title: &str, lines: &[&str]) -> f32 {
+fn draw_section(x: f32, y: f32, width: f32, title: &str, body: &str) -> f32 {
+    let lines: Vec<&str> = body.lines().collect();
     let x = x.round();
     let y = y.round();
     let text_x = x + 16.0;
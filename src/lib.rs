@@ -0,0 +1,7 @@
+//! Bonnie Engine library: the rasterizer and the level format
+//!
+//! Everything else lives in the binary (`main.rs`); these two modules are split
+//! out so the benchmarks can link against them.
+
+pub mod rasterizer;
+pub mod world;
@@ -10,21 +10,30 @@
 /// Version from Cargo.toml
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-mod rasterizer;
-mod world;
+use bonnie_engine::{rasterizer, world};
 mod ui;
 mod editor;
 mod landing;
 mod modeler;
 mod tracker;
+mod game;
 mod app;
+mod crash;
+mod console;
+mod i18n;
+mod settings;
+mod plugin;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 
 use macroquad::prelude::*;
 use rasterizer::{Framebuffer, Texture, HEIGHT, WIDTH};
 use world::{create_empty_level, load_level, save_level};
-use ui::{UiContext, MouseState, Rect, draw_fixed_tabs, TabEntry, layout as tab_layout, icon};
-use editor::{EditorAction, draw_editor, draw_example_browser, BrowserAction, discover_examples};
-use app::{AppState, Tool};
+use ui::{UiContext, MouseState, Rect, Shortcut, draw_fixed_tabs, draw_dialog, draw_error_dialog, draw_text_button, ErrorDialog, TabEntry, TouchInput, layout as tab_layout, icon};
+use ui::{auto_ui_scale, begin_ui_scale, format_ui_scale, next_ui_scale_preset, set_ui_scale, ui_scale, ui_mouse_position, ui_screen_width, ui_screen_height};
+use i18n::{tr, trf};
+use editor::{EditorAction, draw_editor, draw_example_browser, draw_compare_dialog, draw_project_panel, draw_stats_dialog, draw_budget_dialog, draw_vram_view, draw_texture_editor, draw_world_map_view, scan_levels, BrowserAction, CompareAction, ProjectAction, WorldMapAction, discover_examples, load_example};
+use app::{AppState, Guarded, Tool, UnsavedChoice};
 use std::path::PathBuf;
 
 fn window_conf() -> Conf {
@@ -43,16 +52,57 @@ fn window_conf() -> Conf {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
+fn main() {
+    // Command-line tools run without opening a window
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if let Some(code) = cli::run(&args) {
+            std::process::exit(code);
+        }
+        // A detached 3D viewport is a second copy of the engine in its own window
+        if args.iter().any(|a| a == editor::VIEWPORT_FLAG) {
+            macroquad::Window::from_config(editor::viewport_window_conf(), editor::run_viewport_window());
+            return;
+        }
+    }
+    macroquad::Window::from_config(window_conf(), run());
+}
+
+async fn run() {
+    console::init();
+    crash::install_panic_hook();
+
+    // Exported games boot straight into the player runtime
+    if cfg!(feature = "player") || game::GameManifest::is_present() {
+        match game::GameManifest::load().await {
+            Ok(manifest) => {
+                game::run_player(manifest).await;
+                return;
+            }
+            Err(e) => log::error!("{}", e),
+        }
+    }
+
+    // Viewer mode opens a shared level without the editor
+    if let Some(path) = game::viewer_level() {
+        game::run_viewer(path).await;
+        return;
+    }
+
     // Initialize framebuffer (used by 3D viewport in editor)
     let mut fb = Framebuffer::new(WIDTH, HEIGHT);
 
     // Start with empty level (user can open levels via browser)
     let level = create_empty_level();
 
-    // Mouse state tracking
+    // Mouse and touch state tracking
     let mut last_left_down = false;
+    let mut touch_input = TouchInput::new();
+
+    // Closing the window asks about unsaved changes first (see the main loop)
+    #[cfg(not(target_arch = "wasm32"))]
+    prevent_quit();
 
     // UI context
     let mut ui_ctx = UiContext::new();
@@ -60,19 +110,37 @@ async fn main() {
     // Load icon font (Lucide)
     let icon_font = match load_ttf_font("assets/fonts/lucide.ttf").await {
         Ok(font) => {
-            println!("Loaded Lucide icon font");
+            log::info!("Loaded Lucide icon font");
             Some(font)
         }
         Err(e) => {
-            println!("Failed to load Lucide font: {}, icons will be missing", e);
+            log::warn!("Failed to load Lucide font: {}, icons will be missing", e);
             None
         }
     };
 
     // App state with all tools
     let mut app = AppState::new(level, None, icon_font);
+    if let Err(e) = i18n::set_language(&app.settings.language) {
+        log::warn!("{}, using {}", e, i18n::FALLBACK_LANGUAGE);
+    }
+
+    // Co-op editing (--host [port] [--lan] [--key <key>] / --join <addr> [--key <key>])
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        match editor::CollabSession::from_args(&args, &app.world_editor.editor_state.level) {
+            Some(Ok(session)) => app.world_editor.collab = Some(session),
+            Some(Err(e)) => app.errors.push("Co-op editing failed", e),
+            None => {}
+        }
+    }
 
     // Track if this is the first time opening World Editor (to show browser)
+    // A co-op session edits the shared level, so it skips the browser
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut world_editor_first_open = app.world_editor.collab.is_none();
+    #[cfg(target_arch = "wasm32")]
     let mut world_editor_first_open = true;
 
     // Load textures from manifest (WASM needs async loading)
@@ -80,16 +148,25 @@ async fn main() {
     {
         use editor::TexturePack;
         app.world_editor.editor_state.texture_packs = TexturePack::load_from_manifest().await;
-        println!("WASM: Loaded {} texture packs", app.world_editor.editor_state.texture_packs.len());
+        log::info!("WASM: Loaded {} texture packs", app.world_editor.editor_state.texture_packs.len());
     }
 
-    println!("=== Bonnie Engine ===");
+    log::info!("Bonnie Engine v{}", VERSION);
 
     loop {
-        // Update UI context with mouse state
-        let mouse_pos = mouse_position();
+        // UI scale (user override or auto-detected), applied to everything drawn this frame
+        set_ui_scale(app.settings.ui_scale.unwrap_or_else(|| auto_ui_scale(screen_dpi_scale(), screen_height())));
+        begin_ui_scale();
+
+        // Update UI context with mouse state (in UI pixels); touch gestures take over while active
+        let mouse_pos = ui_mouse_position();
         let left_down = is_mouse_button_down(MouseButton::Left);
-        let mouse_state = MouseState {
+        let touch_points: Vec<(f32, f32)> = touches()
+            .iter()
+            .filter(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|t| (t.position.x / ui_scale(), t.position.y / ui_scale()))
+            .collect();
+        let mouse_state = touch_input.update(&touch_points).unwrap_or(MouseState {
             x: mouse_pos.0,
             y: mouse_pos.1,
             left_down,
@@ -97,218 +174,544 @@ async fn main() {
             left_pressed: left_down && !last_left_down,
             left_released: !left_down && last_left_down,
             scroll: mouse_wheel().1,
-        };
+        });
         last_left_down = left_down;
         ui_ctx.begin_frame(mouse_state);
 
-        // Block background input if example browser modal is open
+        // Block background input if example browser or a dialog is open
         // Save the real mouse state so we can restore it for the modal
         let real_mouse = mouse_state;
-        if app.world_editor.example_browser.open {
+        let dialog_open = ui_ctx.dialog_open();
+        let modal = dialog_open
+            || app.world_editor.example_browser.open
+            || app.world_editor.compare.open
+            || app.world_editor.project.open
+            || app.world_editor.world_map.open
+            || app.world_editor.editor_state.show_stats
+            || app.world_editor.editor_state.budget.is_some()
+            || app.world_editor.editor_state.vram.is_some()
+            || app.world_editor.editor_state.texture_editor.is_some()
+            || app.errors.is_open();
+        if modal {
             ui_ctx.begin_modal();
         }
 
-        let screen_w = screen_width();
-        let screen_h = screen_height();
+        // Exchange co-op edits even while another tab is open
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(collab) = &mut app.world_editor.collab {
+            collab.poll(&mut app.world_editor.editor_state);
+        }
+
+        let screen_w = ui_screen_width();
+        let screen_h = ui_screen_height();
 
         // Clear background
         clear_background(Color::from_rgba(30, 30, 35, 255));
 
         // Draw tab bar at top
         let tab_bar_rect = Rect::new(0.0, 0.0, screen_w, tab_layout::BAR_HEIGHT);
-        let tabs = [
-            TabEntry::new(icon::HOUSE, "Home"),
-            TabEntry::new(icon::GLOBE, "World"),
-            TabEntry::new(icon::PERSON_STANDING, "Assets"),
-            TabEntry::new(icon::MUSIC, "Music"),
+        let mut tabs = vec![
+            TabEntry::new(icon::HOUSE, tr("tabs.home")),
+            TabEntry::new(icon::GLOBE, tr("tabs.world")),
+            TabEntry::new(icon::PERSON_STANDING, tr("tabs.assets")),
+            TabEntry::new(icon::MUSIC, tr("tabs.music")),
         ];
+        tabs.extend(app.plugins.iter().map(|p| TabEntry::new(p.icon(), p.name())));
         if let Some(clicked) = draw_fixed_tabs(&mut ui_ctx, tab_bar_rect, &tabs, app.active_tool_index(), app.icon_font.as_ref()) {
-            if let Some(tool) = Tool::from_index(clicked) {
-                // Open browser on first World Editor visit
-                if tool == Tool::WorldEditor && world_editor_first_open {
-                    world_editor_first_open = false;
-                    let levels = discover_examples();
-                    app.world_editor.example_browser.open(levels);
-                }
-                app.set_active_tool(tool);
+            if let Some(tool) = app.tool_at(clicked) {
+                if !app.ask_unsaved(&mut ui_ctx, Guarded::SwitchTool(tool)) {
+                    switch_tool(&mut app, tool, &mut world_editor_first_open);
+                }
+            }
+        }
+
+        // UI scale and language selectors at the right end of the tab bar
+        let scale_rect = Rect::new(screen_w - 196.0, 4.0, 96.0, tab_layout::BAR_HEIGHT - 8.0);
+        let scale_label = match app.settings.ui_scale {
+            Some(scale) => format_ui_scale(scale),
+            None => trf("settings.ui_scale_auto", &[&format_ui_scale(ui_scale())]),
+        };
+        if ui_ctx.mouse.inside(&scale_rect) {
+            ui_ctx.set_tooltip(&trf("settings.ui_scale", &[&scale_label]), ui_ctx.mouse.x, ui_ctx.mouse.y);
+        }
+        if draw_text_button(&mut ui_ctx, scale_rect, &scale_label, Color::from_rgba(45, 45, 52, 255)) {
+            app.settings.ui_scale = next_ui_scale_preset(app.settings.ui_scale);
+            if let Err(e) = app.settings.save() {
+                app.errors.push("Could not save settings", e);
             }
         }
 
+        let lang_rect = Rect::new(screen_w - 96.0, 4.0, 88.0, tab_layout::BAR_HEIGHT - 8.0);
+        if ui_ctx.mouse.inside(&lang_rect) {
+            ui_ctx.set_tooltip(&trf("settings.language", &[&i18n::current_language_name()]), ui_ctx.mouse.x, ui_ctx.mouse.y);
+        }
+        if draw_text_button(&mut ui_ctx, lang_rect, i18n::current_language_name(), Color::from_rgba(45, 45, 52, 255)) {
+            cycle_language(&mut app);
+        }
+
         // Content area below tab bar
         let content_rect = Rect::new(0.0, tab_layout::BAR_HEIGHT, screen_w, screen_h - tab_layout::BAR_HEIGHT);
 
-        // Draw active tool content
-        match app.active_tool {
-            Tool::Home => {
-                landing::draw_landing(content_rect, &mut app.landing);
+        // Draw active tool content (a panicking tool is replaced by its crash screen)
+        let tool = app.active_tool;
+        if let Some(message) = app.crashed.get(&tool).cloned() {
+            match crash::draw_crash_screen(&mut ui_ctx, content_rect, tool, &message) {
+                crash::CrashAction::SaveAndRestart => match crash::save_recovery_copy(&app, tool) {
+                    Ok(path) => {
+                        app.restart_tool(tool);
+                        if tool == Tool::WorldEditor {
+                            app.world_editor.editor_state.set_status(&format!("Recovery copy saved to {}", path.display()), 5.0);
+                        }
+                    }
+                    Err(e) => app.errors.push("Recovery copy failed", e),
+                },
+                crash::CrashAction::Restart => app.restart_tool(tool),
+                crash::CrashAction::None => {}
             }
+        } else if let Err(message) = crash::catch_tool_panic(|| draw_active_tool(&mut app, &mut ui_ctx, &mut fb, content_rect, real_mouse)) {
+            app.crashed.insert(tool, message);
+        }
 
-            Tool::WorldEditor => {
-                let ws = &mut app.world_editor;
+        // App-wide shortcuts go to the active tool (not while typing or behind a dialog)
+        if !modal && !ui_ctx.is_typing() && !app.crashed.contains_key(&tool) {
+            if let Some(shortcut) = Shortcut::pressed() {
+                let action = app.route_shortcut(shortcut);
+                let action = app.guard_editor_action(&mut ui_ctx, action);
+                handle_editor_action(action, &mut app.world_editor, &mut app.errors);
+            }
+        }
 
-                // Check for pending import from browser (WASM only)
-                #[cfg(target_arch = "wasm32")]
-                {
-                    extern "C" {
-                        fn bonnie_check_import() -> i32;
-                        fn bonnie_get_import_data_len() -> usize;
-                        fn bonnie_get_import_filename_len() -> usize;
-                        fn bonnie_copy_import_data(ptr: *mut u8, max_len: usize) -> usize;
-                        fn bonnie_copy_import_filename(ptr: *mut u8, max_len: usize) -> usize;
-                        fn bonnie_clear_import();
-                    }
+        if let Err(e) = app.sync_resolution() {
+            app.errors.push("Could not save settings", e);
+        }
+
+        // Unsaved changes dialog answered: go ahead unless cancelled or the save failed
+        if let Some(reply) = ui_ctx.take_reply(app::UNSAVED_DIALOG) {
+            if let Some(action) = resolve_unsaved(&mut app, reply.button) {
+                match action {
+                    Guarded::SwitchTool(tool) => switch_tool(&mut app, tool, &mut world_editor_first_open),
+                    Guarded::Editor(action) => handle_editor_action(action, &mut app.world_editor, &mut app.errors),
+                    Guarded::Quit => break,
+                }
+            }
+        }
 
-                    let has_import = unsafe { bonnie_check_import() };
+        // Window close (desktop) asks about unsaved changes first
+        #[cfg(not(target_arch = "wasm32"))]
+        if is_quit_requested() && !app.ask_unsaved(&mut ui_ctx, Guarded::Quit) {
+            break;
+        }
 
-                    if has_import != 0 {
-                        let data_len = unsafe { bonnie_get_import_data_len() };
-                        let filename_len = unsafe { bonnie_get_import_filename_len() };
+        // Console overlay (F12)
+        console::draw_console(&mut ui_ctx, content_rect, &mut app.console);
 
-                        let mut data_buf = vec![0u8; data_len];
-                        let mut filename_buf = vec![0u8; filename_len];
+        // Dialogs go over every tool (one opened this frame shows from the next,
+        // so the click that opened it can't answer it)
+        if dialog_open {
+            if !app.errors.is_open() {
+                ui_ctx.end_modal(real_mouse);
+            }
+            draw_dialog(&mut ui_ctx);
+        }
 
-                        unsafe {
-                            bonnie_copy_import_data(data_buf.as_mut_ptr(), data_len);
-                            bonnie_copy_import_filename(filename_buf.as_mut_ptr(), filename_len);
-                            bonnie_clear_import();
-                        }
+        // Errors are drawn over everything, including other modals
+        if app.errors.is_open() {
+            ui_ctx.end_modal(real_mouse);
+            draw_error_dialog(&mut ui_ctx, &mut app.errors);
+        }
 
-                        let data = String::from_utf8_lossy(&data_buf).to_string();
-                        let filename = String::from_utf8_lossy(&filename_buf).to_string();
+        // Draw tooltips last (on top of everything)
+        ui_ctx.draw_tooltip();
 
-                        match ron::from_str::<world::Level>(&data) {
-                            Ok(level) => {
-                                ws.editor_layout.apply_config(&level.editor_layout);
-                                ws.editor_state.load_level(level, PathBuf::from(&filename));
-                                ws.editor_state.set_status(&format!("Uploaded {}", filename), 3.0);
-                            }
-                            Err(e) => {
-                                ws.editor_state.set_status(&format!("Upload failed: {}", e), 5.0);
-                            }
+        next_frame().await;
+    }
+}
+
+/// Make `tool` the active tab (the World Editor opens the level browser on
+/// its first visit)
+fn switch_tool(app: &mut AppState, tool: Tool, world_editor_first_open: &mut bool) {
+    if tool == Tool::WorldEditor && *world_editor_first_open {
+        *world_editor_first_open = false;
+        let levels = discover_examples();
+        let ws = &mut app.world_editor;
+        ws.example_browser.open(levels, &ws.editor_state.texture_packs);
+    }
+    app.set_active_tool(tool);
+}
+
+/// Act on the unsaved changes dialog's answer. Returns the held back action
+/// when it can go ahead: changes were saved (or discarded).
+fn resolve_unsaved(app: &mut AppState, button: usize) -> Option<Guarded> {
+    let action = app.pending.take()?;
+    let tools = app.unsaved_tools(&action);
+    match app.unsaved_choice(&action, button) {
+        UnsavedChoice::Save => {
+            for &tool in &tools {
+                match tool {
+                    Tool::WorldEditor => handle_editor_action(EditorAction::Save, &mut app.world_editor, &mut app.errors),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    Tool::Modeler => {
+                        if let Err(e) = app.modeler.modeler_state.save_model() {
+                            app.errors.push("Save failed", e);
                         }
                     }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    Tool::Tracker => {
+                        if let Err(e) = app.tracker.save_songs() {
+                            app.errors.push("Save failed", e);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // A failed save (reported in the error dialog) keeps everything as it was
+            tools.iter().all(|&t| !app.has_unsaved_changes(t)).then_some(action)
+        }
+        UnsavedChoice::Discard => {
+            // Switching tabs leaves the tool alive, so its changes are reverted;
+            // the other actions replace them or close the app anyway
+            if matches!(action, Guarded::SwitchTool(_)) {
+                for &tool in &tools {
+                    app.discard_changes(tool);
+                }
+            }
+            Some(action)
+        }
+        UnsavedChoice::Cancel => None,
+    }
+}
+
+/// Switch to the next bundled language and remember it
+fn cycle_language(app: &mut AppState) {
+    let language = i18n::next_language();
+    if let Err(e) = i18n::set_language(language.code) {
+        app.errors.push("Language change failed", e);
+        return;
+    }
+    app.settings.language = language.code.to_string();
+    if let Err(e) = app.settings.save() {
+        app.errors.push("Could not save settings", e);
+    }
+    log::info!("{}", trf("settings.language_changed", &[&language.name]));
+}
+
+/// Draw and update the active tool for one frame
+fn draw_active_tool(app: &mut AppState, ui_ctx: &mut UiContext, fb: &mut Framebuffer, content_rect: Rect, real_mouse: MouseState) {
+    match app.active_tool {
+        Tool::Home => {
+            landing::draw_landing(content_rect, &mut app.landing);
+        }
+
+        Tool::WorldEditor => {
+            let ws = &mut app.world_editor;
+
+            // Check for pending import from browser (WASM only)
+            #[cfg(target_arch = "wasm32")]
+            {
+                extern "C" {
+                    fn bonnie_check_import() -> i32;
+                    fn bonnie_get_import_data_len() -> usize;
+                    fn bonnie_get_import_filename_len() -> usize;
+                    fn bonnie_copy_import_data(ptr: *mut u8, max_len: usize) -> usize;
+                    fn bonnie_copy_import_filename(ptr: *mut u8, max_len: usize) -> usize;
+                    fn bonnie_clear_import();
                 }
 
-                // Build textures array from texture packs
-                let editor_textures: Vec<Texture> = ws.editor_state.texture_packs
-                    .iter()
-                    .flat_map(|pack| &pack.textures)
-                    .cloned()
-                    .collect();
-
-                // Draw editor UI
-                let action = draw_editor(
-                    &mut ui_ctx,
-                    &mut ws.editor_layout,
-                    &mut ws.editor_state,
-                    &editor_textures,
-                    &mut fb,
-                    content_rect,
-                    app.icon_font.as_ref(),
-                );
+                let has_import = unsafe { bonnie_check_import() };
+
+                if has_import != 0 {
+                    let data_len = unsafe { bonnie_get_import_data_len() };
+                    let filename_len = unsafe { bonnie_get_import_filename_len() };
+
+                    let mut data_buf = vec![0u8; data_len];
+                    let mut filename_buf = vec![0u8; filename_len];
+
+                    unsafe {
+                        bonnie_copy_import_data(data_buf.as_mut_ptr(), data_len);
+                        bonnie_copy_import_filename(filename_buf.as_mut_ptr(), filename_len);
+                        bonnie_clear_import();
+                    }
 
-                // Handle editor actions (including opening example browser)
-                handle_editor_action(action, ws);
+                    let data = String::from_utf8_lossy(&data_buf).to_string();
+                    let filename = String::from_utf8_lossy(&filename_buf).to_string();
 
-                // Draw example browser overlay if open
-                if ws.example_browser.open {
-                    // End modal blocking so the browser itself can receive input
+                    match world::load_level_from_str(&data) {
+                        Ok(level) => {
+                            ws.editor_layout.apply_config(&level.editor_layout);
+                            ws.editor_state.load_level(level, PathBuf::from(&filename));
+                            ws.editor_state.set_status(&format!("Uploaded {}", filename), 3.0);
+                        }
+                        Err(e) => {
+                            app.errors.push(&format!("Upload of {} failed", filename), e);
+                        }
+                    }
+                }
+            }
+
+            // Build textures array from texture packs
+            let editor_textures: Vec<Texture> = ws.editor_state.texture_packs
+                .iter()
+                .flat_map(|pack| &pack.textures)
+                .cloned()
+                .collect();
+
+            // Draw editor UI
+            let action = draw_editor(
+                ui_ctx,
+                &mut ws.editor_layout,
+                &mut ws.editor_state,
+                &editor_textures,
+                fb,
+                content_rect,
+                app.icon_font.as_ref(),
+            );
+
+            // Actions replacing a level with unsaved changes ask first
+            let action = app.guard_editor_action(ui_ctx, action);
+            let ws = &mut app.world_editor;
+
+            // Handle editor actions (including opening example browser)
+            handle_editor_action(action, ws, &mut app.errors);
+
+            // Open, feed or close the detached 3D viewport's window
+            #[cfg(not(target_arch = "wasm32"))]
+            sync_viewport_window(ws, &mut app.errors);
+
+            // Draw example browser overlay if open
+            if ws.example_browser.open {
+                // End modal blocking so the browser itself can receive input
+                // (unless an error dialog is waiting on top of it)
+                if !app.errors.is_open() {
                     ui_ctx.end_modal(real_mouse);
+                }
+
+                let browser_action = draw_example_browser(
+                    ui_ctx,
+                    &mut ws.example_browser,
+                    app.icon_font.as_ref(),
+                    &ws.editor_state.texture_packs,
+                    fb,
+                );
 
-                    let browser_action = draw_example_browser(
-                        &mut ui_ctx,
-                        &mut ws.example_browser,
-                        app.icon_font.as_ref(),
-                        &ws.editor_state.texture_packs,
-                        &mut fb,
-                    );
-
-                    match browser_action {
-                        BrowserAction::SelectPreview(index) => {
-                            // Load the preview synchronously
-                            if let Some(example) = ws.example_browser.examples.get(index) {
-                                let path = example.path.clone();
-                                #[cfg(not(target_arch = "wasm32"))]
-                                {
-                                    match load_level(&path) {
-                                        Ok(level) => {
-                                            println!("Loaded example level with {} rooms", level.rooms.len());
-                                            ws.example_browser.set_preview(level);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Failed to load example {}: {}", path.display(), e);
-                                            ws.editor_state.set_status(&format!("Failed to load: {}", e), 3.0);
-                                        }
-                                    }
+                match browser_action {
+                    BrowserAction::SelectPreview(index) => {
+                        // Load the preview synchronously (web builds carry the levels)
+                        if let Some(example) = ws.example_browser.examples.get(index) {
+                            match load_example(example) {
+                                Ok(level) => {
+                                    log::info!("Loaded example level with {} rooms", level.rooms.len());
+                                    ws.example_browser.set_preview(level);
+                                }
+                                Err(e) => {
+                                    app.errors.push(&format!("Failed to load {}", example.path.display()), e);
                                 }
                             }
                         }
-                        BrowserAction::OpenLevel => {
-                            // Load the selected level with its file path preserved
-                            if let Some(level) = ws.example_browser.preview_level.take() {
-                                let (name, path) = ws.example_browser.selected_example()
-                                    .map(|e| (e.name.clone(), e.path.clone()))
-                                    .unwrap_or_else(|| ("example".to_string(), PathBuf::from("assets/levels/untitled.ron")));
-                                ws.editor_layout.apply_config(&level.editor_layout);
-                                // Use with_file to preserve the file path for saving
-                                ws.editor_state = editor::EditorState::with_file(level, path);
-                                ws.editor_state.set_status(&format!("Opened: {}", name), 3.0);
-                                ws.example_browser.close();
-                            }
-                        }
-                        BrowserAction::NewLevel => {
-                            // Start with a fresh empty level
-                            let new_level = create_empty_level();
-                            ws.editor_state = editor::EditorState::new(new_level);
-                            ws.editor_layout.apply_config(&ws.editor_state.level.editor_layout);
-                            ws.editor_state.set_status("New level created", 3.0);
+                    }
+                    BrowserAction::OpenLevel => {
+                        // Load the selected level with its file path preserved
+                        if let Some(level) = ws.example_browser.preview_level.take() {
+                            let (name, path) = ws.example_browser.selected_example()
+                                .map(|e| (e.name.clone(), e.path.clone()))
+                                .unwrap_or_else(|| ("example".to_string(), PathBuf::from("assets/levels/untitled.ron")));
+                            ws.editor_layout.apply_config(&level.editor_layout);
+                            // Use with_file to preserve the file path for saving
+                            ws.editor_state = editor::EditorState::with_file(level, path);
+                            ws.editor_state.set_status(&format!("Opened: {}", name), 3.0);
                             ws.example_browser.close();
                         }
-                        BrowserAction::Cancel => {
-                            ws.example_browser.close();
+                    }
+                    BrowserAction::NewLevel => {
+                        // Start with a fresh empty level
+                        let new_level = create_empty_level();
+                        ws.editor_state = editor::EditorState::new(new_level);
+                        ws.editor_layout.apply_config(&ws.editor_state.level.editor_layout);
+                        ws.editor_state.set_status("New level created", 3.0);
+                        ws.example_browser.close();
+                    }
+                    BrowserAction::Cancel => {
+                        ws.example_browser.close();
+                    }
+                    BrowserAction::None => {}
+                }
+            }
+
+            // Draw compare/merge dialog overlay if open
+            if ws.compare.open {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                match draw_compare_dialog(ui_ctx, &mut ws.compare) {
+                    CompareAction::ApplyMerge => {
+                        if let Some(merge) = ws.compare.merge.take() {
+                            let label = format!("Merged {}", ws.compare.other_name);
+                            ws.editor_state.apply_merged_level(merge.level, &label);
                         }
-                        BrowserAction::None => {}
+                        ws.compare.close();
                     }
+                    CompareAction::Close => ws.compare.close(),
+                    CompareAction::None => {}
                 }
             }
 
-            Tool::Modeler => {
-                // Update animation playback
-                let delta = get_frame_time() as f64;
-                app.modeler.modeler_state.update_playback(delta);
-
-                // Draw modeler UI
-                let _action = modeler::draw_modeler(
-                    &mut ui_ctx,
-                    &mut app.modeler.modeler_layout,
-                    &mut app.modeler.modeler_state,
-                    &mut fb,
-                    content_rect,
-                    app.icon_font.as_ref(),
-                );
+            // Draw project panel overlay if open
+            if ws.project.open {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                match draw_project_panel(ui_ctx, &mut ws.project) {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ProjectAction::Save => {
+                        let saved = ws.project.progression.save(std::path::Path::new(game::PROGRESSION_PATH))
+                            .and_then(|()| ws.project.equipment.save(std::path::Path::new(game::EQUIPMENT_PATH)))
+                            .and_then(|()| ws.project.loot.save(std::path::Path::new(game::LOOT_PATH)))
+                            .and_then(|()| ws.project.text.save(std::path::Path::new(game::TEXT_PATH)));
+                        match saved {
+                            Ok(()) => {
+                                ws.project.dirty = false;
+                                ws.editor_state.set_status("Saved project data", 3.0);
+                            }
+                            Err(e) => app.errors.push("Save failed", e),
+                        }
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    ProjectAction::Save => ws.editor_state.set_status("Project data can only be saved in the desktop app", 3.0),
+                    ProjectAction::Close => ws.project.close(),
+                    ProjectAction::None => {}
+                }
+            }
 
-                // TODO: Handle modeler actions (New, Save, Load, Export, Import)
+            // Draw world map view overlay if open
+            if ws.world_map.open {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                match draw_world_map_view(ui_ctx, &mut ws.world_map) {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    WorldMapAction::Save => match ws.world_map.map.save(std::path::Path::new(game::WORLD_MAP_PATH)) {
+                        Ok(()) => {
+                            ws.world_map.dirty = false;
+                            ws.editor_state.set_status(&format!("Saved {}", game::WORLD_MAP_PATH), 3.0);
+                        }
+                        Err(e) => app.errors.push("Save failed", e),
+                    },
+                    #[cfg(target_arch = "wasm32")]
+                    WorldMapAction::Save => ws.editor_state.set_status("The world map can only be saved in the desktop app", 3.0),
+                    WorldMapAction::Close => ws.world_map.close(),
+                    WorldMapAction::None => {}
+                }
             }
 
-            Tool::Tracker => {
-                // Update playback timing
-                let delta = get_frame_time() as f64;
-                app.tracker.update_playback(delta);
+            // Draw level statistics dialog overlay if open
+            if ws.editor_state.show_stats {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                if draw_stats_dialog(ui_ctx, &ws.editor_state) {
+                    ws.editor_state.show_stats = false;
+                }
+            }
 
-                // Draw tracker UI
-                tracker::draw_tracker(&mut ui_ctx, content_rect, &mut app.tracker, app.icon_font.as_ref());
+            // Draw performance budget dialog overlay if open
+            if ws.editor_state.budget.is_some() {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                if draw_budget_dialog(ui_ctx, &mut ws.editor_state) {
+                    ws.editor_state.budget = None;
+                }
+            }
+
+            // Draw VRAM view overlay if open
+            if let Some(view) = &ws.editor_state.vram {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                if draw_vram_view(ui_ctx, view) {
+                    ws.editor_state.vram = None;
+                }
+            }
+
+            // Draw texture editor overlay if open
+            if ws.editor_state.texture_editor.is_some() {
+                if !app.errors.is_open() {
+                    ui_ctx.end_modal(real_mouse);
+                }
+                if draw_texture_editor(ui_ctx, &mut ws.editor_state) {
+                    ws.editor_state.texture_editor = None;
+                }
             }
         }
 
-        // Draw tooltips last (on top of everything)
-        ui_ctx.draw_tooltip();
+        Tool::Modeler => {
+            // Update animation playback
+            let delta = get_frame_time() as f64;
+            app.modeler.modeler_state.update_playback(delta);
+
+            // Draw modeler UI
+            let _action = modeler::draw_modeler(
+                ui_ctx,
+                &mut app.modeler.modeler_layout,
+                &mut app.modeler.modeler_state,
+                fb,
+                content_rect,
+                app.icon_font.as_ref(),
+            );
+
+            // TODO: Handle modeler actions (New, Save, Load, Export, Import)
+        }
 
-        next_frame().await;
+        Tool::Tracker => {
+            // Update playback timing
+            let delta = get_frame_time() as f64;
+            app.tracker.update_playback(delta);
+
+            // Draw tracker UI
+            tracker::draw_tracker(ui_ctx, content_rect, &mut app.tracker, app.icon_font.as_ref());
+        }
+
+        Tool::Plugin(i) => {
+            let mut services = plugin::AppServices {
+                editor: &mut app.world_editor.editor_state,
+                icon_font: app.icon_font.as_ref(),
+            };
+            if let Some(plugin) = app.plugins.get_mut(i) {
+                plugin.draw(ui_ctx, content_rect, &mut services);
+            }
+        }
     }
 }
 
+/// Keep the detached 3D viewport's window in step with the layout: opened on
+/// detach, closed on dock, docked back once the user closes it, and sent the
+/// level as it changes
+#[cfg(not(target_arch = "wasm32"))]
+fn sync_viewport_window(ws: &mut app::WorldEditorState, errors: &mut ErrorDialog) {
+    if !ws.editor_layout.viewport_detached {
+        ws.viewport_window = None;
+        return;
+    }
+    match ws.viewport_window.as_mut().map(|w| w.is_open()) {
+        Some(true) => {
+            if let Some(window) = &mut ws.viewport_window {
+                window.sync(&ws.editor_state.level, get_frame_time());
+            }
+        }
+        Some(false) => {
+            ws.viewport_window = None;
+            ws.editor_layout.viewport_detached = false;
+        }
+        None => match editor::ViewportWindow::open(&ws.editor_state.level) {
+            Ok(window) => ws.viewport_window = Some(window),
+            Err(e) => {
+                errors.push("Detach failed", e);
+                ws.editor_layout.viewport_detached = false;
+            }
+        },
+    }
+}
 
-fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
+fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState, errors: &mut ErrorDialog) {
     match action {
         EditorAction::Play => {
             ws.editor_state.set_status("Game preview coming soon", 2.0);
@@ -321,6 +724,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
         }
         EditorAction::Save => {
             ws.editor_state.level.editor_layout = ws.editor_layout.to_config();
+            ws.editor_state.stamp_metadata();
 
             if let Some(path) = &ws.editor_state.current_file.clone() {
                 match save_level(&ws.editor_state.level, path) {
@@ -329,7 +733,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                         ws.editor_state.set_status(&format!("Saved to {}", path.display()), 3.0);
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Save failed: {}", e), 5.0);
+                        errors.push("Save failed", e);
                     }
                 }
             } else {
@@ -344,7 +748,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                         ws.editor_state.set_status(&format!("Saved to {}", default_path.display()), 3.0);
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Save failed: {}", e), 5.0);
+                        errors.push("Save failed", e);
                     }
                 }
             }
@@ -352,6 +756,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
         #[cfg(not(target_arch = "wasm32"))]
         EditorAction::SaveAs => {
             ws.editor_state.level.editor_layout = ws.editor_layout.to_config();
+            ws.editor_state.stamp_metadata();
             let default_dir = PathBuf::from("assets/levels");
             let _ = std::fs::create_dir_all(&default_dir);
 
@@ -368,7 +773,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                         ws.editor_state.set_status(&format!("Saved as {}", save_path.display()), 3.0);
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Save failed: {}", e), 5.0);
+                        errors.push("Save failed", e);
                     }
                 }
             }
@@ -394,7 +799,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                         ws.editor_state.set_status(&format!("Loaded {}", path.display()), 3.0);
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Load failed: {}", e), 5.0);
+                        errors.push("Load failed", e);
                     }
                 }
             }
@@ -406,8 +811,9 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
         #[cfg(target_arch = "wasm32")]
         EditorAction::Export => {
             ws.editor_state.level.editor_layout = ws.editor_layout.to_config();
+            ws.editor_state.stamp_metadata();
 
-            match ron::ser::to_string_pretty(&ws.editor_state.level, ron::ser::PrettyConfig::default()) {
+            match world::level_to_string(&ws.editor_state.level) {
                 Ok(ron_str) => {
                     let filename = ws.editor_state.current_file
                         .as_ref()
@@ -430,7 +836,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                     ws.editor_state.set_status(&format!("Downloaded {}", filename), 3.0);
                 }
                 Err(e) => {
-                    ws.editor_state.set_status(&format!("Export failed: {}", e), 5.0);
+                    errors.push("Export failed", e);
                 }
             }
         }
@@ -461,16 +867,112 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                     ws.editor_state.set_status(&format!("Loaded {}", path.display()), 3.0);
                 }
                 Err(e) => {
-                    ws.editor_state.set_status(&format!("Load failed: {}", e), 5.0);
+                    errors.push("Load failed", e);
                 }
             }
         }
+        EditorAction::ProjectPanel => {
+            // Unsaved edits from last time the panel was open are kept
+            if !ws.project.dirty {
+                let progression = game::Progression::load(std::path::Path::new(game::PROGRESSION_PATH)).unwrap_or_default();
+                let equipment = game::Equipment::load(std::path::Path::new(game::EQUIPMENT_PATH)).unwrap_or_default();
+                let loot = game::LootTables::load(std::path::Path::new(game::LOOT_PATH)).unwrap_or_default();
+                let text = game::GameText::load(std::path::Path::new(game::TEXT_PATH)).unwrap_or_default();
+                ws.project.show(progression, equipment, loot, text);
+            }
+            ws.project.open = true;
+        }
+        EditorAction::WorldMap => {
+            // Unsaved edits from last time the view was open are kept
+            if !ws.world_map.dirty {
+                let map = game::WorldMap::load(std::path::Path::new(game::WORLD_MAP_PATH)).unwrap_or_default();
+                ws.world_map.show(map, scan_levels(&discover_examples()));
+            }
+            ws.world_map.open = true;
+        }
         EditorAction::BrowseExamples => {
             // Open the level browser
             let levels = discover_examples();
-            ws.example_browser.open(levels);
+            ws.example_browser.open(levels, &ws.editor_state.texture_packs);
             ws.editor_state.set_status("Browse levels", 2.0);
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        EditorAction::ExportGame => {
+            let config = game::FrontEndConfig::load(std::path::Path::new(game::FRONT_END_CONFIG_PATH)).unwrap_or_default();
+            let name: String = config.title
+                .to_lowercase()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+
+            let dialog = rfd::FileDialog::new().set_title("Export game to folder");
+            if let Some(folder) = dialog.pick_folder() {
+                let out_dir = folder.join(&name);
+                match editor::export_game(&name, &out_dir) {
+                    Ok(report) => {
+                        for warning in &report.warnings {
+                            log::warn!("Export warning: {}", warning);
+                        }
+                        let msg = format!(
+                            "Exported {} ({} levels, {} atlases, {} files, {} warnings) to {}",
+                            name, report.levels, report.atlases, report.files_copied, report.warnings.len(), out_dir.display()
+                        );
+                        ws.editor_state.set_status(&msg, 5.0);
+                    }
+                    Err(e) => {
+                        errors.push("Export failed", e);
+                    }
+                }
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        EditorAction::CompareMerge => {
+            let default_dir = PathBuf::from("assets/levels");
+            let Some(other_path) = rfd::FileDialog::new()
+                .set_title("Compare with another version")
+                .add_filter("RON Level", &["ron"])
+                .set_directory(&default_dir)
+                .pick_file()
+            else {
+                return;
+            };
+            let other = match load_level(&other_path) {
+                Ok(level) => level,
+                Err(e) => {
+                    errors.push("Compare failed", e);
+                    return;
+                }
+            };
+
+            // A common base version turns the comparison into a three-way merge
+            let base_path = rfd::FileDialog::new()
+                .set_title("Pick the common base version to merge (cancel to only compare)")
+                .add_filter("RON Level", &["ron"])
+                .set_directory(&default_dir)
+                .pick_file();
+            let merge = match base_path.map(load_level) {
+                Some(Ok(base)) => Some(world::merge_levels(&base, &ws.editor_state.level, &other)),
+                Some(Err(e)) => {
+                    errors.push("Merge failed", e);
+                    return;
+                }
+                None => None,
+            };
+
+            let name = other_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            // When merging, list what applying the merge would change
+            let diff = world::diff_levels(&ws.editor_state.level, merge.as_ref().map_or(&other, |m| &m.level));
+            ws.editor_state.set_status(&format!("{}: {}", name, diff.summary()), 3.0);
+            ws.compare.show(name, diff, merge);
+        }
+        #[cfg(target_arch = "wasm32")]
+        EditorAction::CompareMerge => {
+            ws.editor_state.set_status("Compare/Merge is only available in the desktop app", 3.0);
+        }
+        #[cfg(target_arch = "wasm32")]
+        EditorAction::ExportGame => {
+            ws.editor_state.set_status("Game export is only available in the desktop app", 3.0);
+        }
         EditorAction::Exit | EditorAction::None => {}
     }
 }
@@ -0,0 +1,202 @@
+//! Polygon budget - live cost readout for the model being edited
+//!
+//! Keeps artists inside PS1-era limits:
+//! - triangle and vertex counts
+//! - texture memory (atlas size at 16-bit, as it would sit in VRAM)
+//! - estimated raster cost at 320x240: covered pixels plus per-triangle setup,
+//!   as a share of one frame, measured from the current modeler camera
+//!
+//! Limits come from the project's `assets/project/budget.ron`:
+//!
+//! ```ron
+//! (
+//!     max_triangles: 800,
+//!     max_vertices: 600,
+//!     max_texture_kb: 128,
+//!     max_raster_percent: 30.0,
+//!     warn_ratio: 0.8,
+//! )
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::rasterizer::{Camera, WIDTH, HEIGHT, world_to_screen};
+use super::model::{Model, PartTransform};
+use super::viewport::{compute_world_matrices, transform_point};
+
+/// Default location of the project's budget config
+pub const BUDGET_CONFIG_PATH: &str = "assets/project/budget.ron";
+
+/// Pixels of work charged per drawn triangle (setup, clipping, edge walking)
+const TRIANGLE_SETUP_PIXELS: f32 = 24.0;
+
+/// Per-project budget limits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolygonBudget {
+    pub max_triangles: usize,
+    pub max_vertices: usize,
+    /// Atlas memory at 16 bits per texel
+    pub max_texture_kb: usize,
+    /// Raster cost as a percentage of a 320x240 frame
+    pub max_raster_percent: f32,
+    /// Fraction of a limit at which the readout turns to a warning
+    #[serde(default = "default_warn_ratio")]
+    pub warn_ratio: f32,
+}
+
+fn default_warn_ratio() -> f32 {
+    0.8
+}
+
+impl Default for PolygonBudget {
+    fn default() -> Self {
+        Self {
+            max_triangles: 800,
+            max_vertices: 600,
+            max_texture_kb: 128,
+            max_raster_percent: 30.0,
+            warn_ratio: default_warn_ratio(),
+        }
+    }
+}
+
+impl PolygonBudget {
+    /// Load limits from a RON file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read budget config: {}", e))?;
+        ron::from_str(&contents).map_err(|e| format!("Failed to parse budget config: {}", e))
+    }
+
+    /// The project's limits, or the defaults when it has no config
+    pub fn load_project() -> Self {
+        Self::load(Path::new(BUDGET_CONFIG_PATH)).unwrap_or_default()
+    }
+
+    /// Classify a value against its limit
+    pub fn level(&self, value: f32, max: f32) -> BudgetLevel {
+        if value > max {
+            BudgetLevel::Over
+        } else if value >= max * self.warn_ratio {
+            BudgetLevel::Warn
+        } else {
+            BudgetLevel::Ok
+        }
+    }
+}
+
+/// How close a figure is to its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BudgetLevel {
+    Ok,
+    Warn,
+    Over,
+}
+
+/// Measured cost of a model
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BudgetStats {
+    pub triangles: usize,
+    pub vertices: usize,
+    pub texture_kb: usize,
+    /// Estimated screen pixels filled at 320x240
+    pub fill_pixels: f32,
+    /// Fill plus triangle setup, as a percentage of one frame
+    pub raster_percent: f32,
+}
+
+impl BudgetStats {
+    /// Measure a model in a pose; raster cost covers its visible parts seen from a camera
+    pub fn measure(model: &Model, pose: &[PartTransform], camera: &Camera) -> Self {
+        let matrices = compute_world_matrices(model, pose);
+        let mut stats = Self {
+            texture_kb: model.atlas.dimension() * model.atlas.dimension() * 2 / 1024,
+            ..Self::default()
+        };
+
+        let mut drawn_triangles = 0.0;
+        for (part, matrix) in model.parts.iter().zip(&matrices) {
+            stats.triangles += part.faces.len();
+            stats.vertices += part.vertices.len();
+            if !part.visible {
+                continue;
+            }
+
+            let screen: Vec<Option<(f32, f32)>> = part.vertices
+                .iter()
+                .map(|v| world_to_screen(transform_point(matrix, v.position), camera.position, camera.basis_x, camera.basis_y, camera.basis_z, WIDTH, HEIGHT))
+                .collect();
+
+            for face in &part.faces {
+                let corners = face.indices.map(|i| screen.get(i).copied().flatten());
+                let [Some(a), Some(b), Some(c)] = corners else {
+                    continue;
+                };
+                let area = triangle_area(a, b, c);
+                // A closed mesh faces away from the camera about half the time
+                let share = if face.double_sided { 1.0 } else { 0.5 };
+                stats.fill_pixels += area * share;
+                drawn_triangles += share;
+            }
+        }
+
+        let frame_pixels = (WIDTH * HEIGHT) as f32;
+        stats.raster_percent = (stats.fill_pixels + drawn_triangles * TRIANGLE_SETUP_PIXELS) / frame_pixels * 100.0;
+        stats
+    }
+
+    /// Worst level across all figures
+    pub fn level(&self, budget: &PolygonBudget) -> BudgetLevel {
+        [
+            budget.level(self.triangles as f32, budget.max_triangles as f32),
+            budget.level(self.vertices as f32, budget.max_vertices as f32),
+            budget.level(self.texture_kb as f32, budget.max_texture_kb as f32),
+            budget.level(self.raster_percent, budget.max_raster_percent),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(BudgetLevel::Ok)
+    }
+}
+
+/// Screen area of a triangle, clipped to the frame's bounding box
+fn triangle_area(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    let area = ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() * 0.5;
+    // Triangles reaching off screen only cost the part inside the frame
+    let min_x = a.0.min(b.0).min(c.0).max(0.0);
+    let max_x = a.0.max(b.0).max(c.0).min(WIDTH as f32);
+    let min_y = a.1.min(b.1).min(c.1).max(0.0);
+    let max_y = a.1.max(b.1).max(c.1).min(HEIGHT as f32);
+    let visible_box = (max_x - min_x).max(0.0) * (max_y - min_y).max(0.0);
+    area.min(visible_box)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+
+    #[test]
+    fn test_levels_follow_warn_ratio() {
+        let budget = PolygonBudget::default();
+        assert_eq!(budget.level(100.0, 800.0), BudgetLevel::Ok);
+        assert_eq!(budget.level(700.0, 800.0), BudgetLevel::Warn);
+        assert_eq!(budget.level(801.0, 800.0), BudgetLevel::Over);
+    }
+
+    #[test]
+    fn test_measures_test_cube() {
+        let model = Model::test_cube();
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(0.0, 0.0, -400.0);
+        camera.look_at(Vec3::ZERO);
+
+        let stats = BudgetStats::measure(&model, &[], &camera);
+        assert_eq!(stats.triangles, 12);
+        assert_eq!(stats.vertices, 8);
+        assert_eq!(stats.texture_kb, 32);
+        assert!(stats.fill_pixels > 0.0);
+        assert!(stats.raster_percent > 0.0 && stats.raster_percent < 100.0);
+        assert_eq!(stats.level(&PolygonBudget::default()), BudgetLevel::Ok);
+    }
+}
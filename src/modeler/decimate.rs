@@ -0,0 +1,193 @@
+//! Automatic mesh decimation for LOD levels
+//!
+//! Vertex clustering: vertices are snapped to a grid and every cell collapses
+//! to one vertex, dropping the triangles that degenerate. Coarse, but cheap
+//! and predictable - the faceted look is right at home on a PS1 model far away.
+//! The cell size is searched so the result lands at or under a target
+//! triangle count.
+
+use std::collections::{HashMap, HashSet};
+use crate::rasterizer::Vec3;
+use super::model::{LodMesh, ModelFace, ModelVertex};
+
+/// Steps of the cell size search
+const SEARCH_STEPS: usize = 16;
+
+/// Reduce a mesh to about `ratio` (0.0-1.0) of its triangles
+pub fn decimate(vertices: &[ModelVertex], faces: &[ModelFace], ratio: f32) -> LodMesh {
+    let target = ((faces.len() as f32 * ratio.clamp(0.0, 1.0)).round() as usize).max(1);
+    if faces.len() <= target || vertices.is_empty() {
+        return LodMesh { vertices: vertices.to_vec(), faces: faces.to_vec() };
+    }
+
+    let (min, max) = bounds(vertices);
+    let extent = (max.x - min.x).max(max.y - min.y).max(max.z - min.z).max(0.001);
+
+    // Smallest cell (most detail) that still meets the target
+    let (mut lo, mut hi) = (0.0, extent * 2.0);
+    let mut best = LodMesh { vertices: vertices.to_vec(), faces: faces.to_vec() };
+    for _ in 0..SEARCH_STEPS {
+        let cell = (lo + hi) * 0.5;
+        let mesh = cluster(vertices, faces, min, cell);
+        if mesh.faces.is_empty() || mesh.faces.len() <= target {
+            hi = cell;
+        } else {
+            lo = cell;
+        }
+        if !mesh.faces.is_empty() && closer(&mesh, &best, target) {
+            best = mesh;
+        }
+    }
+    best
+}
+
+/// Whether `a` is a better result than `b`: under the target with the most
+/// detail, otherwise as few triangles as possible
+fn closer(a: &LodMesh, b: &LodMesh, target: usize) -> bool {
+    let (a_len, b_len) = (a.faces.len(), b.faces.len());
+    match (a_len <= target, b_len <= target) {
+        (true, false) => true,
+        (false, true) => false,
+        (true, true) => a_len > b_len,
+        (false, false) => a_len < b_len,
+    }
+}
+
+/// Collapse vertices sharing a grid cell of size `cell`
+fn cluster(vertices: &[ModelVertex], faces: &[ModelFace], origin: Vec3, cell: f32) -> LodMesh {
+    // Group vertices by cell
+    let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+    let mut cell_of = Vec::with_capacity(vertices.len());
+    for (i, v) in vertices.iter().enumerate() {
+        let rel = v.position - origin;
+        let key = (
+            (rel.x / cell).floor() as i32,
+            (rel.y / cell).floor() as i32,
+            (rel.z / cell).floor() as i32,
+        );
+        cells.entry(key).or_default().push(i);
+        cell_of.push(key);
+    }
+
+    // One vertex per cell: at the average position, keeping the UV and color
+    // of the member closest to it (averaging UVs would smear across seams)
+    let mut keys: Vec<_> = cells.keys().copied().collect();
+    keys.sort_unstable();
+    let mut new_index = HashMap::with_capacity(keys.len());
+    let mut new_vertices = Vec::with_capacity(keys.len());
+    for key in keys {
+        let members = &cells[&key];
+        let sum = members.iter().fold(Vec3::ZERO, |acc, &i| acc + vertices[i].position);
+        let center = sum * (1.0 / members.len() as f32);
+        let closest = members
+            .iter()
+            .copied()
+            .min_by(|&a, &b| {
+                let da = (vertices[a].position - center).len();
+                let db = (vertices[b].position - center).len();
+                da.total_cmp(&db)
+            })
+            .unwrap_or(members[0]);
+        new_index.insert(key, new_vertices.len());
+        new_vertices.push(ModelVertex { position: center, ..vertices[closest] });
+    }
+
+    // Remap faces, dropping collapsed and duplicate triangles
+    let mut seen = HashSet::new();
+    let mut new_faces = Vec::new();
+    for face in faces {
+        let indices = face.indices.map(|i| new_index[&cell_of[i]]);
+        if indices[0] == indices[1] || indices[1] == indices[2] || indices[0] == indices[2] {
+            continue;
+        }
+        // Same triangle in any rotation (winding kept)
+        let start = (0..3).min_by_key(|&k| indices[k]).unwrap_or(0);
+        let key = [indices[start], indices[(start + 1) % 3], indices[(start + 2) % 3]];
+        if seen.insert(key) {
+            new_faces.push(ModelFace { indices, ..*face });
+        }
+    }
+
+    // Drop vertices no face uses any more
+    let mut used = vec![false; new_vertices.len()];
+    for face in &new_faces {
+        for &i in &face.indices {
+            used[i] = true;
+        }
+    }
+    let mut remap = vec![0; new_vertices.len()];
+    let mut kept = Vec::new();
+    for (i, v) in new_vertices.into_iter().enumerate() {
+        if used[i] {
+            remap[i] = kept.len();
+            kept.push(v);
+        }
+    }
+    for face in &mut new_faces {
+        face.indices = face.indices.map(|i| remap[i]);
+    }
+
+    LodMesh { vertices: kept, faces: new_faces }
+}
+
+fn bounds(vertices: &[ModelVertex]) -> (Vec3, Vec3) {
+    let mut min = vertices[0].position;
+    let mut max = vertices[0].position;
+    for v in vertices {
+        min = Vec3::new(min.x.min(v.position.x), min.y.min(v.position.y), min.z.min(v.position.z));
+        max = Vec3::new(max.x.max(v.position.x), max.y.max(v.position.y), max.z.max(v.position.z));
+    }
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec2;
+    use crate::modeler::Model;
+
+    /// Flat n x n quad grid (2 triangles per quad)
+    fn grid(n: usize) -> (Vec<ModelVertex>, Vec<ModelFace>) {
+        let mut vertices = Vec::new();
+        for z in 0..=n {
+            for x in 0..=n {
+                vertices.push(ModelVertex::new(Vec3::new(x as f32 * 10.0, 0.0, z as f32 * 10.0), Vec2::new(0.0, 0.0)));
+            }
+        }
+        let mut faces = Vec::new();
+        for z in 0..n {
+            for x in 0..n {
+                let i = z * (n + 1) + x;
+                faces.push(ModelFace::new([i, i + 1, i + n + 2]));
+                faces.push(ModelFace::new([i, i + n + 2, i + n + 1]));
+            }
+        }
+        (vertices, faces)
+    }
+
+    #[test]
+    fn test_decimation_meets_target() {
+        let (vertices, faces) = grid(16);
+        let mesh = decimate(&vertices, &faces, 0.25);
+        assert!(!mesh.faces.is_empty());
+        assert!(mesh.faces.len() <= faces.len() / 4);
+        assert!(mesh.faces.iter().all(|f| f.indices.iter().all(|&i| i < mesh.vertices.len())));
+    }
+
+    #[test]
+    fn test_lod_levels_follow_distance() {
+        let mut model = Model::test_cube();
+        model.add_decimated_lod(0.5, 4000.0);
+        model.add_authored_lod(2000.0);
+        assert_eq!(model.lod_level(100.0), 0);
+        assert_eq!(model.lod_level(2500.0), 1);
+        assert_eq!(model.lod_level(5000.0), 2);
+        assert_eq!(model.lods[0].decimated, None);
+
+        // Swapping twice restores the full model
+        let faces = model.face_count();
+        model.swap_lod(2);
+        model.swap_lod(2);
+        assert_eq!(model.face_count(), faces);
+    }
+}
@@ -1,9 +1,13 @@
 //! Modeler UI layout and rendering
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, SplitPanel, draw_panel, panel_content_rect, Toolbar, icon};
+use crate::i18n::{tr, trf};
+use crate::ui::{shortcut_modifier_down, Rect, Shortcut, UiContext, SplitPanel, draw_panel, panel_content_rect, Toolbar, icon, draw_text_button};
 use crate::rasterizer::Framebuffer;
-use super::state::{ModelerState, ModelerView, SelectMode, TransformTool};
+use super::state::{GraphSelection, ModelerState, ModelerView, SelectMode, TransformTool, LOD_DISTANCE_STEP};
+use super::model::EVENT_PRESETS;
+use super::budget::{BudgetLevel, BudgetStats};
+use super::state_machine::TransitionTrigger;
 use super::viewport::draw_modeler_viewport;
 
 // Colors (matching tracker/editor style)
@@ -12,6 +16,10 @@ const HEADER_COLOR: Color = Color::new(0.15, 0.15, 0.18, 1.0);
 const TEXT_COLOR: Color = Color::new(0.8, 0.8, 0.85, 1.0);
 const TEXT_DIM: Color = Color::new(0.4, 0.4, 0.45, 1.0);
 const ACCENT_COLOR: Color = Color::new(0.0, 0.75, 0.9, 1.0);
+const EVENT_COLOR: Color = Color::new(0.9, 0.5, 0.7, 1.0);
+const BUDGET_OK_COLOR: Color = Color::new(0.4, 0.8, 0.4, 1.0);
+const BUDGET_WARN_COLOR: Color = Color::new(0.95, 0.75, 0.2, 1.0);
+const BUDGET_OVER_COLOR: Color = Color::new(0.95, 0.3, 0.3, 1.0);
 
 /// Actions that can be triggered by the modeler UI
 #[derive(Debug, Clone, PartialEq)]
@@ -31,7 +39,7 @@ pub struct ModelerLayout {
     pub main_split: SplitPanel,
     /// Right split (center viewport | right panels)
     pub right_split: SplitPanel,
-    /// Left vertical split (hierarchy/dopesheet | UV editor)
+    /// Left vertical split (hierarchy/dopesheet | UV editor or state graph)
     pub left_split: SplitPanel,
     /// Right vertical split (atlas | properties)
     pub right_panel_split: SplitPanel,
@@ -49,6 +57,13 @@ impl ModelerLayout {
             timeline_height: 80.0,
         }
     }
+
+    /// A divider is being dragged
+    pub fn is_resizing(&self, ctx: &UiContext) -> bool {
+        [&self.main_split, &self.right_split, &self.left_split, &self.right_panel_split]
+            .iter()
+            .any(|split| split.is_dragging(ctx))
+    }
 }
 
 impl Default for ModelerLayout {
@@ -95,7 +110,7 @@ pub fn draw_modeler(
     // Right split: center viewport | right panels
     let (center_rect, right_rect) = layout.right_split.update(ctx, rest_rect);
 
-    // Left split: hierarchy/dopesheet | UV editor
+    // Left split: hierarchy/dopesheet | UV editor (state graph when animating)
     let (hierarchy_rect, uv_rect) = layout.left_split.update(ctx, left_rect);
 
     // Right split: atlas | properties
@@ -103,27 +118,33 @@ pub fn draw_modeler(
 
     // Draw panels based on view mode
     let left_top_label = match state.view {
-        ModelerView::Animate => "Dopesheet",
-        _ => "Hierarchy",
+        ModelerView::Animate => tr("modeler.panel.dopesheet"),
+        _ => tr("modeler.panel.hierarchy"),
     };
-    draw_panel(hierarchy_rect, Some(left_top_label), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(hierarchy_rect, Some(&left_top_label), Color::from_rgba(35, 35, 40, 255));
     draw_hierarchy_panel(ctx, panel_content_rect(hierarchy_rect, true), state);
 
-    draw_panel(uv_rect, Some("UV Editor"), Color::from_rgba(35, 35, 40, 255));
-    draw_uv_editor(ctx, panel_content_rect(uv_rect, true), state);
+    if state.view == ModelerView::Animate {
+        draw_panel(uv_rect, Some(&tr("modeler.panel.state_graph")), Color::from_rgba(35, 35, 40, 255));
+        draw_state_graph(ctx, panel_content_rect(uv_rect, true), state);
+    } else {
+        draw_panel(uv_rect, Some(&tr("modeler.panel.uv")), Color::from_rgba(35, 35, 40, 255));
+        draw_uv_editor(ctx, panel_content_rect(uv_rect, true), state);
+    }
 
-    draw_panel(center_rect, Some("3D Viewport"), Color::from_rgba(25, 25, 30, 255));
-    draw_viewport(ctx, panel_content_rect(center_rect, true), state, fb);
+    draw_panel(center_rect, Some(&tr("common.viewport_3d")), Color::from_rgba(25, 25, 30, 255));
+    let resizing = layout.is_resizing(ctx);
+    draw_viewport(ctx, panel_content_rect(center_rect, true), state, fb, resizing);
 
-    draw_panel(atlas_rect, Some("Atlas"), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(atlas_rect, Some(&tr("modeler.panel.atlas")), Color::from_rgba(35, 35, 40, 255));
     draw_atlas_panel(ctx, panel_content_rect(atlas_rect, true), state);
 
-    draw_panel(props_rect, Some("Properties"), Color::from_rgba(35, 35, 40, 255));
+    draw_panel(props_rect, Some(&tr("common.properties")), Color::from_rgba(35, 35, 40, 255));
     draw_properties_panel(ctx, panel_content_rect(props_rect, true), state);
 
     // Draw timeline if in animate mode
     if let Some(tl_rect) = timeline_rect {
-        draw_panel(tl_rect, Some("Timeline"), Color::from_rgba(30, 30, 35, 255));
+        draw_panel(tl_rect, Some(&tr("modeler.panel.timeline")), Color::from_rgba(30, 30, 35, 255));
         draw_timeline(ctx, panel_content_rect(tl_rect, true), state, icon_font);
     }
 
@@ -143,23 +164,23 @@ fn draw_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, icon_
     let mut toolbar = Toolbar::new(rect);
 
     // File operations
-    if toolbar.icon_button(ctx, icon::FILE_PLUS, icon_font, "New") {
+    if toolbar.icon_button(ctx, icon::FILE_PLUS, icon_font, &tr("common.new")) {
         action = ModelerAction::New;
     }
-    if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Open") {
+    if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, &tr("common.open")) {
         action = ModelerAction::Load;
     }
-    if toolbar.icon_button(ctx, icon::SAVE, icon_font, "Save") {
+    if toolbar.icon_button(ctx, icon::SAVE, icon_font, &tr("common.save")) {
         action = ModelerAction::Save;
     }
 
     toolbar.separator();
 
     // Undo/Redo
-    if toolbar.icon_button(ctx, icon::UNDO, icon_font, "Undo") {
+    if toolbar.icon_button(ctx, icon::UNDO, icon_font, &tr("common.undo")) {
         state.undo();
     }
-    if toolbar.icon_button(ctx, icon::REDO, icon_font, "Redo") {
+    if toolbar.icon_button(ctx, icon::REDO, icon_font, &tr("common.redo")) {
         state.redo();
     }
 
@@ -167,15 +188,15 @@ fn draw_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, icon_
 
     // Tool buttons
     let tools = [
-        (icon::MOVE, "Select", TransformTool::Select),
-        (icon::MOVE, "Move (G)", TransformTool::Move),
-        (icon::MOVE, "Rotate (R)", TransformTool::Rotate),  // TODO: proper rotate icon
-        (icon::MOVE, "Scale (S)", TransformTool::Scale),    // TODO: proper scale icon
+        (icon::MOVE, "common.select", TransformTool::Select),
+        (icon::MOVE, "modeler.tool.move", TransformTool::Move),
+        (icon::MOVE, "modeler.tool.rotate", TransformTool::Rotate),  // TODO: proper rotate icon
+        (icon::MOVE, "modeler.tool.scale", TransformTool::Scale),    // TODO: proper scale icon
     ];
 
-    for (icon_char, tooltip, tool) in tools {
+    for (icon_char, tooltip_key, tool) in tools {
         let is_active = state.tool == tool;
-        if toolbar.icon_button_active(ctx, icon_char, icon_font, tooltip, is_active) {
+        if toolbar.icon_button_active(ctx, icon_char, icon_font, &tr(tooltip_key), is_active) {
             state.tool = tool;
         }
     }
@@ -183,7 +204,7 @@ fn draw_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, icon_
     toolbar.separator();
 
     // View mode selector
-    toolbar.label("Mode:");
+    toolbar.label(&tr("modeler.mode"));
     for view in ModelerView::ALL {
         let is_active = state.view == view;
         // Use different icons per view (placeholder for now)
@@ -220,24 +241,24 @@ fn draw_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, icon_
     }
 
     // PS1 effect toggles
-    if toolbar.icon_button_active(ctx, icon::WAVES, icon_font, "Affine Textures (warpy)", state.raster_settings.affine_textures) {
+    if toolbar.icon_button_active(ctx, icon::WAVES, icon_font, &tr("modeler.fx.affine"), state.raster_settings.affine_textures) {
         state.raster_settings.affine_textures = !state.raster_settings.affine_textures;
         let mode = if state.raster_settings.affine_textures { "ON" } else { "OFF" };
         state.set_status(&format!("Affine textures: {}", mode), 1.5);
     }
-    if toolbar.icon_button_active(ctx, icon::MAGNET, icon_font, "Vertex Snap (jittery)", state.raster_settings.vertex_snap) {
+    if toolbar.icon_button_active(ctx, icon::MAGNET, icon_font, &tr("modeler.fx.vertex_snap"), state.raster_settings.vertex_snap) {
         state.raster_settings.vertex_snap = !state.raster_settings.vertex_snap;
         let mode = if state.raster_settings.vertex_snap { "ON" } else { "OFF" };
         state.set_status(&format!("Vertex snap: {}", mode), 1.5);
     }
-    if toolbar.icon_button_active(ctx, icon::MONITOR, icon_font, "Low Resolution (320x240)", state.raster_settings.low_resolution) {
-        state.raster_settings.low_resolution = !state.raster_settings.low_resolution;
-        let mode = if state.raster_settings.low_resolution { "320x240" } else { "640x480" };
-        state.set_status(&format!("Resolution: {}", mode), 1.5);
+    let resolution = state.raster_settings.resolution;
+    if toolbar.icon_button_active(ctx, icon::MONITOR, icon_font, &trf("modeler.fx.resolution", &[&resolution.label()]), resolution.is_low()) {
+        state.raster_settings.resolution = resolution.next();
+        state.set_status(&format!("Resolution: {}", state.raster_settings.resolution.label()), 1.5);
     }
     // Shading toggle (cycle through None -> Flat -> Gouraud)
     let shading_active = state.raster_settings.shading != crate::rasterizer::ShadingMode::None;
-    if toolbar.icon_button_active(ctx, icon::SUN, icon_font, "Shading (None/Flat/Gouraud)", shading_active) {
+    if toolbar.icon_button_active(ctx, icon::SUN, icon_font, &tr("modeler.fx.shading"), shading_active) {
         use crate::rasterizer::ShadingMode;
         state.raster_settings.shading = match state.raster_settings.shading {
             ShadingMode::None => ShadingMode::Flat,
@@ -310,7 +331,7 @@ fn draw_hierarchy_panel(_ctx: &mut UiContext, rect: Rect, state: &ModelerState)
     }
 
     if state.model.parts.is_empty() {
-        draw_text("No parts", rect.x, y + 14.0, 14.0, TEXT_DIM);
+        draw_text(&tr("modeler.no_parts"), rect.x, y + 14.0, 14.0, TEXT_DIM);
     } else {
         draw_part_tree(&state.model.parts, None, 0, &mut y, &rect, line_height, indent, state);
     }
@@ -363,8 +384,184 @@ fn draw_uv_editor(_ctx: &mut UiContext, rect: Rect, state: &ModelerState) {
     );
 }
 
-fn draw_viewport(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, fb: &mut Framebuffer) {
-    draw_modeler_viewport(ctx, rect, state, fb);
+/// Animation state machine: nodes are states, arrows are transitions.
+/// Drag nodes to arrange them; while previewing, clicking a node transitions to it.
+fn draw_state_graph(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState) {
+    let line_height = 18.0;
+    let button_bg = Color::from_rgba(55, 55, 65, 255);
+    let active_bg = Color::from_rgba(0, 120, 150, 255);
+    let node_w = 84.0;
+    let node_h = 20.0;
+
+    // Buttons
+    let button_w = ((rect.w - 20.0) / 6.0).max(10.0);
+    let button = |i: usize| Rect::new(rect.x + (button_w + 4.0) * i as f32, rect.y, button_w, line_height);
+    if draw_text_button(ctx, button(0), &tr("modeler.sm_add"), button_bg) {
+        state.add_graph_state();
+    }
+    let link_bg = if state.graph_link_from.is_some() { active_bg } else { button_bg };
+    if draw_text_button(ctx, button(1), &tr("modeler.sm_link"), link_bg) {
+        state.graph_link_from = match (state.graph_link_from, state.graph_selection) {
+            (None, Some(GraphSelection::State(i))) => Some(i),
+            _ => None,
+        };
+        if state.graph_link_from.is_some() {
+            state.set_status("Click the target state", 1.5);
+        }
+    }
+    if draw_text_button(ctx, button(2), &tr("modeler.sm_delete"), button_bg) {
+        state.remove_graph_selection();
+    }
+    if draw_text_button(ctx, button(3), &tr("modeler.sm_initial"), button_bg) {
+        state.set_initial_state();
+    }
+    let instant_bg = if state.model.state_machine.instant { active_bg } else { button_bg };
+    if draw_text_button(ctx, button(4), &tr("modeler.sm_instant"), instant_bg) {
+        state.save_undo();
+        state.model.state_machine.instant = !state.model.state_machine.instant;
+    }
+    let preview_bg = if state.graph_preview.is_some() { active_bg } else { button_bg };
+    if draw_text_button(ctx, button(5), &tr("modeler.sm_preview"), preview_bg) {
+        state.toggle_graph_preview();
+    }
+
+    // Selected transition: trigger and blend length
+    let mut y = rect.y + line_height + 4.0;
+    if let Some(GraphSelection::Transition(i)) = state.graph_selection {
+        if let Some(t) = state.model.state_machine.transitions.get(i) {
+            let sm = &state.model.state_machine;
+            let trigger = match &t.trigger {
+                TransitionTrigger::Signal(name) => format!("on '{}'", name),
+                TransitionTrigger::Finished => "on end".to_string(),
+            };
+            let text = format!("{} > {}  {}  {}f", sm.states[t.from].name, sm.states[t.to].name, trigger, t.blend_frames);
+            draw_text(&text, rect.x + 2.0, y + 12.0, 12.0, TEXT_COLOR);
+
+            let plus = Rect::new(rect.right() - 16.0, y, 16.0, line_height - 2.0);
+            let minus = Rect::new(plus.x - 18.0, y, 16.0, line_height - 2.0);
+            let toggle = Rect::new(minus.x - 48.0, y, 46.0, line_height - 2.0);
+            if draw_text_button(ctx, toggle, &tr("modeler.sm_trigger"), button_bg) {
+                state.cycle_transition_trigger(i);
+            }
+            if draw_text_button(ctx, minus, "-", button_bg) {
+                state.change_blend_frames(i, -1);
+            }
+            if draw_text_button(ctx, plus, "+", button_bg) {
+                state.change_blend_frames(i, 1);
+            }
+        }
+        y += line_height;
+    }
+
+    let canvas = Rect::new(rect.x, y, rect.w, (rect.bottom() - y).max(0.0));
+    draw_rectangle(canvas.x, canvas.y, canvas.w, canvas.h, Color::from_rgba(28, 28, 32, 255));
+    let node_rect = |pos: (f32, f32)| Rect::new(canvas.x + pos.0, canvas.y + pos.1, node_w, node_h);
+    let sm = &state.model.state_machine;
+    if sm.states.is_empty() {
+        draw_text("Add a state for the current clip", canvas.x + 4.0, canvas.y + 14.0, 12.0, TEXT_DIM);
+        return;
+    }
+
+    // Transitions, offset sideways so A > B and B > A stay apart
+    let mouse = (ctx.mouse.x, ctx.mouse.y);
+    let mut hovered_transition = None;
+    for (i, t) in sm.transitions.iter().enumerate() {
+        let (Some(from), Some(to)) = (sm.states.get(t.from), sm.states.get(t.to)) else {
+            continue;
+        };
+        let a = node_rect(from.position);
+        let b = node_rect(to.position);
+        let (ax, ay) = (a.x + node_w * 0.5, a.y + node_h * 0.5);
+        let (bx, by) = (b.x + node_w * 0.5, b.y + node_h * 0.5);
+        let (dx, dy) = (bx - ax, by - ay);
+        let len = (dx * dx + dy * dy).sqrt().max(0.001);
+        let (ux, uy) = (dx / len, dy / len);
+        let (ox, oy) = (-uy * 4.0, ux * 4.0);
+        let (ax, ay, bx, by) = (ax + ox, ay + oy, bx + ox, by + oy);
+
+        let selected = state.graph_selection == Some(GraphSelection::Transition(i));
+        let color = if selected { ACCENT_COLOR } else { TEXT_DIM };
+        draw_line(ax, ay, bx, by, if selected { 2.0 } else { 1.0 }, color);
+
+        // Arrow head at the middle of the edge
+        let (mx, my) = ((ax + bx) * 0.5, (ay + by) * 0.5);
+        draw_triangle(
+            vec2(mx + ux * 6.0, my + uy * 6.0),
+            vec2(mx - ux * 4.0 - uy * 4.0, my - uy * 4.0 + ux * 4.0),
+            vec2(mx - ux * 4.0 + uy * 4.0, my - uy * 4.0 - ux * 4.0),
+            color,
+        );
+        if t.trigger == TransitionTrigger::Finished {
+            draw_circle(mx, my, 2.0, EVENT_COLOR);
+        }
+
+        // Distance from the mouse to the segment
+        let along = ((mouse.0 - ax) * ux + (mouse.1 - ay) * uy).clamp(0.0, len);
+        let (px, py) = (ax + ux * along, ay + uy * along);
+        if ((mouse.0 - px).powi(2) + (mouse.1 - py).powi(2)).sqrt() < 5.0 {
+            hovered_transition = Some(i);
+        }
+    }
+
+    // Nodes
+    let current = state.graph_preview.as_ref().map(|p| p.state);
+    let mut hovered_state = None;
+    for (i, s) in sm.states.iter().enumerate() {
+        let r = node_rect(s.position);
+        let selected = state.graph_selection == Some(GraphSelection::State(i));
+        let bg = if current == Some(i) {
+            Color::from_rgba(0, 100, 125, 255)
+        } else if ctx.mouse.inside(&r) {
+            Color::from_rgba(60, 60, 70, 255)
+        } else {
+            Color::from_rgba(48, 48, 56, 255)
+        };
+        draw_rectangle(r.x, r.y, r.w, r.h, bg);
+        let border = if selected { ACCENT_COLOR } else { Color::from_rgba(80, 80, 90, 255) };
+        draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, border);
+        let label = if i == sm.initial { format!("> {}", s.name) } else { s.name.clone() };
+        draw_text(&label, r.x + 4.0, r.y + 14.0, 12.0, TEXT_COLOR);
+        if ctx.mouse.inside(&r) {
+            hovered_state = Some(i);
+        }
+    }
+
+    // Dragging a node
+    if let Some((i, grab_x, grab_y)) = state.graph_drag {
+        if ctx.mouse.left_down {
+            if let Some(s) = state.model.state_machine.states.get_mut(i) {
+                s.position = (
+                    (ctx.mouse.x - canvas.x - grab_x).clamp(0.0, (canvas.w - node_w).max(0.0)),
+                    (ctx.mouse.y - canvas.y - grab_y).clamp(0.0, (canvas.h - node_h).max(0.0)),
+                );
+                state.dirty = true;
+            }
+        } else {
+            state.graph_drag = None;
+        }
+        return;
+    }
+
+    if !ctx.mouse.clicked(&canvas) {
+        return;
+    }
+    match (hovered_state, state.graph_link_from) {
+        (Some(to), Some(from)) => state.link_graph_states(from, to),
+        (Some(i), None) if state.graph_preview.is_some() => state.preview_goto_state(i),
+        (Some(i), None) => {
+            let pos = state.model.state_machine.states[i].position;
+            state.graph_selection = Some(GraphSelection::State(i));
+            state.graph_drag = Some((i, ctx.mouse.x - canvas.x - pos.0, ctx.mouse.y - canvas.y - pos.1));
+        }
+        (None, _) => {
+            state.graph_link_from = None;
+            state.graph_selection = hovered_transition.map(GraphSelection::Transition);
+        }
+    }
+}
+
+fn draw_viewport(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, fb: &mut Framebuffer, resizing: bool) {
+    draw_modeler_viewport(ctx, rect, state, fb, resizing);
 }
 
 fn draw_atlas_panel(_ctx: &mut UiContext, rect: Rect, state: &ModelerState) {
@@ -393,7 +590,7 @@ fn draw_atlas_panel(_ctx: &mut UiContext, rect: Rect, state: &ModelerState) {
     );
 }
 
-fn draw_properties_panel(_ctx: &mut UiContext, rect: Rect, state: &ModelerState) {
+fn draw_properties_panel(ctx: &mut UiContext, rect: Rect, state: &mut ModelerState) {
     let mut y = rect.y;
     let line_height = 18.0;
 
@@ -402,7 +599,7 @@ fn draw_properties_panel(_ctx: &mut UiContext, rect: Rect, state: &ModelerState)
 
     match &state.selection {
         super::state::ModelerSelection::None => {
-            draw_text("Nothing selected", rect.x, y + 14.0, 12.0, TEXT_COLOR);
+            draw_text(&tr("common.nothing_selected"), rect.x, y + 14.0, 12.0, TEXT_COLOR);
         }
         super::state::ModelerSelection::Parts(parts) => {
             draw_text(&format!("{} part(s)", parts.len()), rect.x, y + 14.0, 12.0, TEXT_COLOR);
@@ -424,20 +621,161 @@ fn draw_properties_panel(_ctx: &mut UiContext, rect: Rect, state: &ModelerState)
     draw_text("Tool:", rect.x, y + 14.0, 12.0, TEXT_DIM);
     y += line_height;
     draw_text(state.tool.label(), rect.x, y + 14.0, 12.0, TEXT_COLOR);
+    y += line_height * 2.0;
+
+    y = draw_budget(rect, y, state);
+    y += line_height;
+
+    draw_lod_section(ctx, rect, y, state);
+}
+
+/// LOD list (click a level to edit it), distance buttons and add/remove
+fn draw_lod_section(ctx: &mut UiContext, rect: Rect, mut y: f32, state: &mut ModelerState) {
+    let line_height = 18.0;
+    let button_bg = Color::from_rgba(55, 55, 65, 255);
+    if y > rect.bottom() - line_height * 3.0 {
+        return;
+    }
+
+    draw_text(&tr("modeler.lod"), rect.x, y + 14.0, 12.0, TEXT_DIM);
+    y += line_height;
+
+    for level in 0..=state.model.lods.len() {
+        if y > rect.bottom() - line_height * 2.0 {
+            break;
+        }
+        let row = Rect::new(rect.x, y, rect.w, line_height - 2.0);
+        let editing = level == state.editing_lod;
+        if editing {
+            draw_rectangle(row.x, row.y, row.w, row.h, Color::from_rgba(45, 60, 70, 255));
+        } else if ctx.mouse.inside(&row) {
+            draw_rectangle(row.x, row.y, row.w, row.h, Color::from_rgba(45, 45, 52, 255));
+        }
+
+        let faces = state.model.lod_face_count(level);
+        let text = match level.checked_sub(1).and_then(|i| state.model.lods.get(i)) {
+            None => format!("Full  {} faces", faces),
+            Some(lod) => match lod.decimated {
+                Some(ratio) => format!("LOD{} {:.0}+  {} faces ({:.0}%)", level, lod.distance, faces, ratio * 100.0),
+                None => format!("LOD{} {:.0}+  {} faces", level, lod.distance, faces),
+            },
+        };
+        draw_text(&text, rect.x + 4.0, y + 12.0, 12.0, if editing { ACCENT_COLOR } else { TEXT_COLOR });
+
+        // Distance -/+ on LOD rows
+        let mut on_button = false;
+        if level > 0 {
+            let plus = Rect::new(row.right() - 16.0, y, 16.0, line_height - 2.0);
+            let minus = Rect::new(plus.x - 18.0, y, 16.0, line_height - 2.0);
+            on_button = ctx.mouse.inside(&plus) || ctx.mouse.inside(&minus);
+            if draw_text_button(ctx, minus, "-", button_bg) {
+                state.change_lod_distance(level, -LOD_DISTANCE_STEP);
+                return;
+            }
+            if draw_text_button(ctx, plus, "+", button_bg) {
+                state.change_lod_distance(level, LOD_DISTANCE_STEP);
+                return;
+            }
+        }
+
+        if !on_button && ctx.mouse.clicked(&row) {
+            state.edit_lod(level);
+            return;
+        }
+        y += line_height;
+    }
+
+    y += 4.0;
+    if y > rect.bottom() - line_height {
+        return;
+    }
+    let button_w = ((rect.w - 12.0) / 4.0).max(10.0);
+    let buttons = [
+        Rect::new(rect.x, y, button_w, line_height),
+        Rect::new(rect.x + button_w + 4.0, y, button_w, line_height),
+        Rect::new(rect.x + (button_w + 4.0) * 2.0, y, button_w, line_height),
+        Rect::new(rect.x + (button_w + 4.0) * 3.0, y, button_w, line_height),
+    ];
+    if draw_text_button(ctx, buttons[0], &tr("modeler.lod_decimate"), button_bg) {
+        state.add_lod(true);
+    }
+    if draw_text_button(ctx, buttons[1], &tr("modeler.lod_copy"), button_bg) {
+        state.add_lod(false);
+    }
+    if draw_text_button(ctx, buttons[2], &tr("modeler.lod_remove"), button_bg) {
+        state.remove_lod();
+    }
+    let preview_bg = if state.lod_preview { Color::from_rgba(0, 120, 150, 255) } else { button_bg };
+    if draw_text_button(ctx, buttons[3], &tr("modeler.lod_auto"), preview_bg) {
+        state.lod_preview = !state.lod_preview;
+    }
+}
+
+fn budget_color(level: BudgetLevel) -> Color {
+    match level {
+        BudgetLevel::Ok => BUDGET_OK_COLOR,
+        BudgetLevel::Warn => BUDGET_WARN_COLOR,
+        BudgetLevel::Over => BUDGET_OVER_COLOR,
+    }
+}
+
+/// Budget readout: each figure against its project limit, colored by how close it is.
+/// Returns the y below it.
+fn draw_budget(rect: Rect, mut y: f32, state: &ModelerState) -> f32 {
+    let line_height = 18.0;
+    let budget = &state.budget;
+    let stats = BudgetStats::measure(&state.model, &state.get_current_pose(), &state.camera);
+
+    // The header takes the worst figure's color once anything is over
+    let header_color = match stats.level(budget) {
+        BudgetLevel::Ok => TEXT_DIM,
+        level => budget_color(level),
+    };
+    draw_text(&tr("modeler.budget"), rect.x, y + 14.0, 12.0, header_color);
+    y += line_height;
+
+    let rows = [
+        ("Tris", format!("{} / {}", stats.triangles, budget.max_triangles), stats.triangles as f32, budget.max_triangles as f32),
+        ("Verts", format!("{} / {}", stats.vertices, budget.max_vertices), stats.vertices as f32, budget.max_vertices as f32),
+        ("Texture", format!("{} / {} KB", stats.texture_kb, budget.max_texture_kb), stats.texture_kb as f32, budget.max_texture_kb as f32),
+        ("Raster", format!("{:.1}% / {:.0}%", stats.raster_percent, budget.max_raster_percent), stats.raster_percent, budget.max_raster_percent),
+    ];
+
+    for (label, text, value, max) in rows {
+        if y > rect.bottom() - line_height {
+            return y;
+        }
+        let color = budget_color(budget.level(value, max));
+        draw_text(label, rect.x, y + 12.0, 12.0, TEXT_DIM);
+        draw_text(&text, rect.x + 52.0, y + 12.0, 12.0, color);
+
+        // Fill bar under the figure
+        let bar_w = (rect.w - 4.0).max(0.0);
+        let fill = if max > 0.0 { (value / max).min(1.0) } else { 1.0 };
+        draw_rectangle(rect.x, y + 15.0, bar_w, 2.0, Color::from_rgba(50, 50, 55, 255));
+        draw_rectangle(rect.x, y + 15.0, bar_w * fill, 2.0, color);
+        y += line_height;
+    }
+
+    if y <= rect.bottom() - line_height {
+        draw_text(&format!("~{:.0} px filled at 320x240", stats.fill_pixels), rect.x, y + 12.0, 11.0, TEXT_DIM);
+        y += line_height;
+    }
+    y
 }
 
 fn draw_timeline(_ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, icon_font: Option<&Font>) {
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, HEADER_COLOR);
 
     // Transport controls
-    let mut toolbar = Toolbar::new(Rect::new(rect.x, rect.y, 200.0, 32.0));
+    let mut toolbar = Toolbar::new(Rect::new(rect.x, rect.y, rect.w, 32.0));
 
-    if toolbar.icon_button(_ctx, icon::SKIP_BACK, icon_font, "Stop & Rewind") {
+    if toolbar.icon_button(_ctx, icon::SKIP_BACK, icon_font, &tr("common.stop_rewind")) {
         state.stop_playback();
     }
 
     let play_icon = if state.playing { icon::PAUSE } else { icon::PLAY };
-    if toolbar.icon_button(_ctx, play_icon, icon_font, if state.playing { "Pause" } else { "Play" }) {
+    if toolbar.icon_button(_ctx, play_icon, icon_font, &tr(if state.playing { "common.pause" } else { "common.play" })) {
         state.toggle_playback();
     }
 
@@ -453,13 +791,35 @@ fn draw_timeline(_ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, ico
     toolbar.separator();
 
     // Keyframe buttons
-    if toolbar.icon_button(_ctx, icon::PLUS, icon_font, "Insert Keyframe (I)") {
+    if toolbar.icon_button(_ctx, icon::PLUS, icon_font, &tr("modeler.insert_key")) {
         state.insert_keyframe();
     }
-    if toolbar.icon_button(_ctx, icon::MINUS, icon_font, "Delete Keyframe (K)") {
+    if toolbar.icon_button(_ctx, icon::MINUS, icon_font, &tr("modeler.delete_key")) {
         state.delete_keyframe();
     }
 
+    toolbar.separator();
+
+    // Event track buttons
+    toolbar.label(&format!("Event: {}", EVENT_PRESETS[state.event_preset]));
+    if toolbar.icon_button(_ctx, icon::CIRCLE_CHEVRON_RIGHT, icon_font, &tr("modeler.next_event")) {
+        state.next_event_preset();
+    }
+    if toolbar.icon_button(_ctx, icon::PLUS, icon_font, &tr("modeler.add_event")) {
+        state.add_event();
+    }
+    if toolbar.icon_button(_ctx, icon::MINUS, icon_font, &tr("modeler.remove_events")) {
+        state.remove_events();
+    }
+
+    // Events on the current frame
+    let frame_events: Vec<String> = state.current_animation()
+        .map(|a| a.events_at(state.current_frame).map(|e| e.name.clone()).collect())
+        .unwrap_or_default();
+    if !frame_events.is_empty() {
+        toolbar.label(&frame_events.join(", "));
+    }
+
     // Timeline scrubber area
     let scrub_rect = Rect::new(rect.x + 10.0, rect.y + 40.0, rect.w - 20.0, 30.0);
     draw_rectangle(scrub_rect.x, scrub_rect.y, scrub_rect.w, scrub_rect.h, Color::from_rgba(20, 20, 25, 255));
@@ -492,6 +852,16 @@ fn draw_timeline(_ctx: &mut UiContext, rect: Rect, state: &mut ModelerState, ico
                 draw_poly(x, scrub_rect.y + 12.0, 4, 5.0, 45.0, ACCENT_COLOR);
             }
         }
+
+        // Event markers (small flags along the bottom edge)
+        for event in &anim.events {
+            if event.frame <= frames_visible as u32 {
+                let x = scrub_rect.x + event.frame as f32 * frame_width;
+                let y = scrub_rect.bottom() - 2.0;
+                draw_line(x, y - 8.0, x, y, 1.0, EVENT_COLOR);
+                draw_triangle(vec2(x, y - 8.0), vec2(x + 5.0, y - 6.0), vec2(x, y - 4.0), EVENT_COLOR);
+            }
+        }
     }
 
     // Draw playhead
@@ -514,23 +884,32 @@ fn draw_status_bar(rect: Rect, state: &ModelerState) {
         ModelerView::UV => "G:Move S:Scale U:Unwrap",
         ModelerView::Paint => "LMB:Paint Shift+LMB:Pick [/]:Brush Size",
         ModelerView::Hierarchy => "Drag to reparent | Del:Delete part",
-        ModelerView::Animate => "Space:Play I:Insert Key K:Delete Key",
+        ModelerView::Animate => "Space:Play I:Insert Key K:Delete Key N:Add Event",
     };
     draw_text(hints, rect.right() - (hints.len() as f32 * 6.0) - 8.0, rect.y + 15.0, 12.0, TEXT_DIM);
 }
 
-fn handle_keyboard(state: &mut ModelerState) {
-    let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
-             || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper);
-
-    // Undo/Redo
-    if ctrl && is_key_pressed(KeyCode::Z) {
-        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-            state.redo();
-        } else {
-            state.undo();
-        }
+/// The modeler's part of the app-wide shortcuts (models are saved by name
+/// to `MODELS_DIR`, where entities load them from; there is no browser to
+/// open them with yet)
+pub fn handle_modeler_shortcut(state: &mut ModelerState, shortcut: Shortcut) {
+    match shortcut {
+        Shortcut::Undo => state.undo(),
+        Shortcut::Redo => state.redo(),
+        #[cfg(not(target_arch = "wasm32"))]
+        Shortcut::Save | Shortcut::SaveAs => match state.save_model() {
+            Ok(path) => state.set_status(&format!("Saved {}", path.display()), 2.0),
+            Err(e) => state.set_status(&e, 3.0),
+        },
+        #[cfg(target_arch = "wasm32")]
+        Shortcut::Save | Shortcut::SaveAs => state.set_status("Models can't be saved in the browser", 2.0),
+        Shortcut::New | Shortcut::Open => state.set_status("Models can't be opened yet", 2.0),
     }
+}
+
+fn handle_keyboard(state: &mut ModelerState) {
+    // Ctrl / Cmd combinations are app-wide shortcuts (see `handle_modeler_shortcut`)
+    let ctrl = shortcut_modifier_down();
 
     // View mode cycling
     if is_key_pressed(KeyCode::Tab) {
@@ -584,6 +963,9 @@ fn handle_keyboard(state: &mut ModelerState) {
         if is_key_pressed(KeyCode::K) {
             state.delete_keyframe();
         }
+        if is_key_pressed(KeyCode::N) && !ctrl {
+            state.add_event();
+        }
         if is_key_pressed(KeyCode::Left) {
             if state.current_frame > 0 {
                 state.current_frame -= 1;
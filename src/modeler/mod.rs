@@ -9,8 +9,12 @@ mod model;
 mod state;
 mod layout;
 mod viewport;
+mod budget;
+mod decimate;
+mod state_machine;
 
 pub use model::*;
 pub use state::*;
 pub use layout::*;
 pub use viewport::*;
+pub use state_machine::AnimationPlayer;
@@ -1,7 +1,17 @@
 //! Model data structures for segmented/hierarchy animation
 
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use crate::rasterizer::{Vec2, Vec3, Color};
+use super::state_machine::AnimStateMachine;
+
+/// Where the project's models live, one `<name>.ron` each
+pub const MODELS_DIR: &str = "assets/models";
+
+/// File of the model entities refer to as `name`
+pub fn model_path(name: &str) -> PathBuf {
+    PathBuf::from(MODELS_DIR).join(format!("{}.ron", name))
+}
 
 /// A segmented 3D model (PS1-style hierarchy animation)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +20,12 @@ pub struct Model {
     pub parts: Vec<ModelPart>,
     pub animations: Vec<Animation>,
     pub atlas: TextureAtlas,
+    /// Cheaper versions of the parts for far away, by increasing distance
+    #[serde(default)]
+    pub lods: Vec<ModelLod>,
+    /// Clip transitions played at runtime
+    #[serde(default)]
+    pub state_machine: AnimStateMachine,
 }
 
 impl Model {
@@ -19,9 +35,30 @@ impl Model {
             parts: Vec::new(),
             animations: Vec::new(),
             atlas: TextureAtlas::new(AtlasSize::S128),
+            lods: Vec::new(),
+            state_machine: AnimStateMachine::default(),
         }
     }
 
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(s: &str) -> Result<Self, String> {
+        ron::from_str(s).map_err(|e| format!("Failed to parse model: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize model: {}", e))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
     /// Create a simple test cube model
     pub fn test_cube() -> Self {
         let mut model = Self::new("cube");
@@ -104,6 +141,89 @@ impl Model {
     pub fn face_count(&self) -> usize {
         self.parts.iter().map(|p| p.faces.len()).sum()
     }
+
+    /// Detail level to draw at a camera distance: 0 is the full model,
+    /// `n` is `lods[n - 1]`
+    pub fn lod_level(&self, distance: f32) -> usize {
+        self.lods.iter().take_while(|lod| distance >= lod.distance).count()
+    }
+
+    /// Mesh of a part at a detail level (parts a LOD lacks use the full mesh)
+    pub fn part_mesh(&self, part: usize, level: usize) -> (&[ModelVertex], &[ModelFace]) {
+        let lod_mesh = level.checked_sub(1)
+            .and_then(|i| self.lods.get(i))
+            .and_then(|lod| lod.meshes.get(part));
+        match lod_mesh {
+            Some(mesh) => (&mesh.vertices, &mesh.faces),
+            None => {
+                let p = &self.parts[part];
+                (&p.vertices, &p.faces)
+            }
+        }
+    }
+
+    /// Total face count at a detail level
+    pub fn lod_face_count(&self, level: usize) -> usize {
+        (0..self.parts.len()).map(|i| self.part_mesh(i, level).1.len()).sum()
+    }
+
+    /// Add a LOD decimated from the full model to about `ratio` of its triangles
+    pub fn add_decimated_lod(&mut self, ratio: f32, distance: f32) -> usize {
+        let meshes = self.parts.iter()
+            .map(|p| super::decimate::decimate(&p.vertices, &p.faces, ratio))
+            .collect();
+        self.insert_lod(ModelLod { distance, meshes, decimated: Some(ratio) })
+    }
+
+    /// Add a LOD starting as a copy of the full model, to be edited by hand
+    pub fn add_authored_lod(&mut self, distance: f32) -> usize {
+        let meshes = self.parts.iter()
+            .map(|p| LodMesh { vertices: p.vertices.clone(), faces: p.faces.clone() })
+            .collect();
+        self.insert_lod(ModelLod { distance, meshes, decimated: None })
+    }
+
+    /// Keep LODs ordered by distance; returns the new LOD's level
+    pub fn insert_lod(&mut self, lod: ModelLod) -> usize {
+        let index = self.lods.iter().take_while(|l| l.distance <= lod.distance).count();
+        self.lods.insert(index, lod);
+        index + 1
+    }
+
+    /// Exchange the parts' meshes with those of a LOD level, so the editing
+    /// tools work on it. Swapping the same level again restores the model.
+    pub fn swap_lod(&mut self, level: usize) {
+        let Some(lod) = level.checked_sub(1).and_then(|i| self.lods.get_mut(i)) else {
+            return;
+        };
+        // Parts added after the LOD was made start as copies of the full part
+        for part in self.parts.iter().skip(lod.meshes.len()) {
+            lod.meshes.push(LodMesh { vertices: part.vertices.clone(), faces: part.faces.clone() });
+        }
+        for (part, mesh) in self.parts.iter_mut().zip(&mut lod.meshes) {
+            std::mem::swap(&mut part.vertices, &mut mesh.vertices);
+            std::mem::swap(&mut part.faces, &mut mesh.faces);
+        }
+    }
+}
+
+/// A cheaper version of a model, used from `distance` on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelLod {
+    /// Camera distance from which this level is drawn
+    pub distance: f32,
+    /// One mesh per model part (same hierarchy and animation as the full model)
+    pub meshes: Vec<LodMesh>,
+    /// Triangle ratio if generated by decimation, None if authored by hand
+    #[serde(default)]
+    pub decimated: Option<f32>,
+}
+
+/// Geometry of one part at a LOD
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LodMesh {
+    pub vertices: Vec<ModelVertex>,
+    pub faces: Vec<ModelFace>,
 }
 
 /// A single part of the model (its own mesh + transform)
@@ -300,6 +420,13 @@ impl AtlasSize {
 // Animation
 // ============================================================================
 
+/// Built-in event names offered by the timeline
+pub const EVENT_FOOTSTEP: &str = "footstep";
+pub const EVENT_HIT_START: &str = "hit_start";
+pub const EVENT_HIT_END: &str = "hit_end";
+pub const EVENT_FX_SPAWN: &str = "fx_spawn";
+pub const EVENT_PRESETS: [&str; 4] = [EVENT_FOOTSTEP, EVENT_HIT_START, EVENT_HIT_END, EVENT_FX_SPAWN];
+
 /// Named animation clip
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Animation {
@@ -307,6 +434,9 @@ pub struct Animation {
     pub fps: u8,
     pub looping: bool,
     pub keyframes: Vec<Keyframe>,
+    /// Events fired when playback reaches their frame
+    #[serde(default)]
+    pub events: Vec<AnimationEvent>,
 }
 
 impl Animation {
@@ -316,6 +446,7 @@ impl Animation {
             fps: 15,
             looping: true,
             keyframes: Vec::new(),
+            events: Vec::new(),
         }
     }
 
@@ -329,6 +460,27 @@ impl Animation {
         self.last_frame() as f32 / self.fps as f32
     }
 
+    /// Pose at a (possibly fractional) frame, interpolated between keyframes
+    pub fn pose_at(&self, frame: f32, num_parts: usize) -> Vec<PartTransform> {
+        let Some(first) = self.keyframes.first() else {
+            return vec![PartTransform::default(); num_parts];
+        };
+        let prev = self.keyframes.iter().rev().find(|kf| kf.frame as f32 <= frame).unwrap_or(first);
+        let next = self.keyframes.iter().find(|kf| kf.frame as f32 >= frame).unwrap_or(prev);
+
+        // If same keyframe, no interpolation needed
+        if prev.frame == next.frame {
+            return prev.transforms.clone();
+        }
+
+        let t = (frame - prev.frame as f32) / (next.frame - prev.frame) as f32;
+        prev.transforms
+            .iter()
+            .zip(next.transforms.iter())
+            .map(|(a, b)| a.lerp(b, t))
+            .collect()
+    }
+
     /// Find keyframe at exact frame, or None
     pub fn get_keyframe(&self, frame: u32) -> Option<&Keyframe> {
         self.keyframes.iter().find(|kf| kf.frame == frame)
@@ -354,6 +506,37 @@ impl Animation {
     pub fn remove_keyframe(&mut self, frame: u32) {
         self.keyframes.retain(|kf| kf.frame != frame);
     }
+
+    /// Events on an exact frame
+    pub fn events_at(&self, frame: u32) -> impl Iterator<Item = &AnimationEvent> {
+        self.events.iter().filter(move |e| e.frame == frame)
+    }
+
+    /// First frame carrying an event with this name
+    pub fn find_event(&self, name: &str) -> Option<u32> {
+        self.events.iter().find(|e| e.name == name).map(|e| e.frame)
+    }
+
+    /// Add an event (no-op if the same event already exists on that frame)
+    pub fn add_event(&mut self, frame: u32, name: &str) {
+        if self.events.iter().any(|e| e.frame == frame && e.name == name) {
+            return;
+        }
+        self.events.push(AnimationEvent { frame, name: name.to_string() });
+        self.events.sort_by_key(|e| e.frame);
+    }
+
+    /// Remove all events at frame
+    pub fn remove_events(&mut self, frame: u32) {
+        self.events.retain(|e| e.frame != frame);
+    }
+}
+
+/// Named event on the animation timeline (e.g. "footstep", "hit_start")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationEvent {
+    pub frame: u32,
+    pub name: String,
 }
 
 /// Single keyframe (stores transform for each part)
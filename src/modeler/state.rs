@@ -2,7 +2,15 @@
 
 use std::path::PathBuf;
 use crate::rasterizer::{Camera, Vec2, Vec3, Color, RasterSettings};
-use super::model::{Model, PartTransform};
+use super::model::{model_path, Model, PartTransform, AnimationEvent, EVENT_PRESETS};
+use super::budget::PolygonBudget;
+use super::state_machine::{AnimationPlayer, TransitionTrigger};
+
+/// Distance of the first LOD added to a model (two sectors)
+pub const FIRST_LOD_DISTANCE: f32 = 2048.0;
+
+/// Distance change per click of the LOD distance buttons
+pub const LOD_DISTANCE_STEP: f32 = 256.0;
 
 /// Modeler view modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -99,6 +107,13 @@ impl ModelerSelection {
     }
 }
 
+/// Selected item in the state machine graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphSelection {
+    State(usize),
+    Transition(usize),
+}
+
 /// Active transform tool
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TransformTool {
@@ -169,6 +184,8 @@ pub struct ModelerState {
     // Camera
     pub camera: Camera,
     pub raster_settings: RasterSettings,
+    /// Project limits for the budget readout
+    pub budget: PolygonBudget,
 
     // UV Editor state
     pub uv_zoom: f32,
@@ -183,12 +200,31 @@ pub struct ModelerState {
     // Hierarchy state
     pub hierarchy_expanded: Vec<bool>,
 
+    // LOD state
+    /// Detail level swapped into the parts for editing (0 = full model)
+    pub editing_lod: usize,
+    /// Viewport shows the level play mode would pick at the camera distance
+    pub lod_preview: bool,
+
     // Animation state
     pub current_animation: usize,
     pub current_frame: u32,
     pub playing: bool,
     pub playback_time: f64,
     pub selected_keyframes: Vec<usize>,
+    /// Index into EVENT_PRESETS used when adding events
+    pub event_preset: usize,
+    /// Events fired during the last `update_playback` call (for combat/SFX listeners)
+    pub fired_events: Vec<AnimationEvent>,
+
+    // State machine graph
+    pub graph_selection: Option<GraphSelection>,
+    /// Source state while linking a transition (next state clicked is the target)
+    pub graph_link_from: Option<usize>,
+    /// Node being dragged, with the grab offset
+    pub graph_drag: Option<(usize, f32, f32)>,
+    /// Runs the state machine in the viewport instead of the timeline
+    pub graph_preview: Option<AnimationPlayer>,
 
     // Edit state
     pub undo_stack: Vec<Model>,
@@ -205,6 +241,8 @@ pub struct ModelerState {
     // Viewport mouse state
     pub viewport_last_mouse: (f32, f32),
     pub viewport_mouse_captured: bool,
+    /// The camera was moved last frame (the viewport renders at reduced resolution meanwhile)
+    pub camera_moving: bool,
 }
 
 impl ModelerState {
@@ -226,6 +264,7 @@ impl ModelerState {
 
             camera,
             raster_settings: RasterSettings::default(),
+            budget: PolygonBudget::load_project(),
 
             uv_zoom: 1.0,
             uv_offset: Vec2::default(),
@@ -237,11 +276,21 @@ impl ModelerState {
 
             hierarchy_expanded: Vec::new(),
 
+            editing_lod: 0,
+            lod_preview: false,
+
             current_animation: 0,
             current_frame: 0,
             playing: false,
             playback_time: 0.0,
             selected_keyframes: Vec::new(),
+            event_preset: 0,
+            fired_events: Vec::new(),
+
+            graph_selection: None,
+            graph_link_from: None,
+            graph_drag: None,
+            graph_preview: None,
 
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
@@ -255,6 +304,7 @@ impl ModelerState {
 
             viewport_last_mouse: (0.0, 0.0),
             viewport_mouse_captured: false,
+            camera_moving: false,
         }
     }
 
@@ -318,38 +368,15 @@ impl ModelerState {
     pub fn get_current_pose(&self) -> Vec<PartTransform> {
         let num_parts = self.model.parts.len();
 
-        let anim = match self.current_animation() {
-            Some(a) if !a.keyframes.is_empty() => a,
-            _ => return vec![PartTransform::default(); num_parts],
-        };
-
-        // Find surrounding keyframes
-        let frame = self.current_frame;
-        let mut prev_kf = &anim.keyframes[0];
-        let mut next_kf = &anim.keyframes[0];
-
-        for kf in &anim.keyframes {
-            if kf.frame <= frame {
-                prev_kf = kf;
-            }
-            if kf.frame >= frame && next_kf.frame <= frame {
-                next_kf = kf;
-            }
+        // State machine preview drives the pose while it runs
+        if let Some(player) = &self.graph_preview {
+            return player.pose(&self.model);
         }
 
-        // If same keyframe, no interpolation needed
-        if prev_kf.frame == next_kf.frame {
-            return prev_kf.transforms.clone();
+        match self.current_animation() {
+            Some(anim) => anim.pose_at(self.current_frame as f32, num_parts),
+            None => vec![PartTransform::default(); num_parts],
         }
-
-        // Interpolate
-        let t = (frame - prev_kf.frame) as f32 / (next_kf.frame - prev_kf.frame) as f32;
-
-        prev_kf.transforms
-            .iter()
-            .zip(next_kf.transforms.iter())
-            .map(|(a, b)| a.lerp(b, t))
-            .collect()
     }
 
     /// Toggle playback
@@ -369,6 +396,16 @@ impl ModelerState {
 
     /// Update animation playback
     pub fn update_playback(&mut self, delta: f64) {
+        self.fired_events.clear();
+
+        if let Some(player) = &mut self.graph_preview {
+            self.fired_events = player.update(&self.model, delta as f32);
+            if let Some(event) = self.fired_events.last() {
+                let msg = format!("Event: {}", event.name);
+                self.set_status(&msg, 0.5);
+            }
+            return;
+        }
         if !self.playing {
             return;
         }
@@ -401,6 +438,16 @@ impl ModelerState {
                     break;
                 }
             }
+
+            // Deliver events on the frame we just entered
+            if let Some(anim) = self.model.animations.get(self.current_animation) {
+                self.fired_events.extend(anim.events_at(self.current_frame).cloned());
+            }
+        }
+
+        if let Some(event) = self.fired_events.last() {
+            let msg = format!("Event: {}", event.name);
+            self.set_status(&msg, 0.5);
         }
     }
 
@@ -435,6 +482,240 @@ impl ModelerState {
         }
     }
 
+    /// Add the selected preset event at the current frame
+    pub fn add_event(&mut self) {
+        let frame = self.current_frame;
+        let name = EVENT_PRESETS[self.event_preset % EVENT_PRESETS.len()];
+
+        if self.model.animations.is_empty() {
+            self.model.animations.push(super::model::Animation::new("default"));
+        }
+
+        let Some(anim) = self.current_animation_mut() else { return };
+        anim.add_event(frame, name);
+        self.dirty = true;
+        self.set_status(&format!("Event '{}' added at frame {}", name, frame), 1.5);
+    }
+
+    /// Remove all events at the current frame
+    pub fn remove_events(&mut self) {
+        let frame = self.current_frame;
+
+        if let Some(anim) = self.current_animation_mut() {
+            anim.remove_events(frame);
+            self.dirty = true;
+            self.set_status(&format!("Events removed at frame {}", frame), 1.5);
+        }
+    }
+
+    /// Cycle the event preset used by `add_event`
+    pub fn next_event_preset(&mut self) {
+        self.event_preset = (self.event_preset + 1) % EVENT_PRESETS.len();
+    }
+
+    /// Detail level the viewport draws
+    pub fn preview_lod_level(&self) -> usize {
+        if self.lod_preview && self.editing_lod == 0 {
+            self.model.lod_level(self.camera.position.len())
+        } else {
+            0
+        }
+    }
+
+    /// Write the model to its file, `MODELS_DIR/<name>.ron` the first time;
+    /// returns where it went
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_model(&mut self) -> Result<PathBuf, String> {
+        let path = self.current_file.clone().unwrap_or_else(|| model_path(&self.model.name));
+        // The file holds the full model, not a LOD swapped in for editing
+        let mut model = self.model.clone();
+        model.swap_lod(self.editing_lod);
+        model.save(&path)?;
+        self.current_file = Some(path.clone());
+        self.dirty = false;
+        Ok(path)
+    }
+
+    /// Swap a detail level into the parts so the tools edit it (0 = full model).
+    /// Undo history is per level, so switching clears it.
+    pub fn edit_lod(&mut self, level: usize) {
+        if level == self.editing_lod || level > self.model.lods.len() {
+            return;
+        }
+        self.model.swap_lod(self.editing_lod);
+        self.model.swap_lod(level);
+        self.editing_lod = level;
+        self.selection.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        let name = if level == 0 { "full model".to_string() } else { format!("LOD {}", level) };
+        self.set_status(&format!("Editing {}", name), 1.5);
+    }
+
+    /// Add a LOD past the last one: decimated to half the previous level's
+    /// triangles, or a copy of the full model to edit by hand
+    pub fn add_lod(&mut self, decimated: bool) {
+        self.edit_lod(0);
+        self.save_undo();
+        let count = self.model.lods.len();
+        let distance = self.model.lods.last().map(|l| l.distance * 2.0).unwrap_or(FIRST_LOD_DISTANCE);
+        let level = if decimated {
+            let ratio = 0.5f32.powi(count as i32 + 1);
+            self.model.add_decimated_lod(ratio, distance)
+        } else {
+            self.model.add_authored_lod(distance)
+        };
+        self.set_status(&format!(
+            "Added LOD {}: {} faces from {:.0}",
+            level, self.model.lod_face_count(level), distance
+        ), 2.0);
+        if !decimated {
+            self.edit_lod(level);
+        }
+    }
+
+    /// Remove the LOD being edited, or the last one when editing the full model
+    pub fn remove_lod(&mut self) {
+        let level = if self.editing_lod > 0 { self.editing_lod } else { self.model.lods.len() };
+        if level == 0 {
+            return;
+        }
+        self.edit_lod(0);
+        self.save_undo();
+        self.model.lods.remove(level - 1);
+        self.set_status(&format!("Removed LOD {}", level), 1.5);
+    }
+
+    /// Move the distance a LOD starts at (levels re-sort by distance)
+    pub fn change_lod_distance(&mut self, level: usize, delta: f32) {
+        let Some(lod) = level.checked_sub(1).and_then(|i| self.model.lods.get(i)) else {
+            return;
+        };
+        let distance = (lod.distance + delta).max(LOD_DISTANCE_STEP);
+        let was_editing = self.editing_lod == level;
+        self.edit_lod(0);
+        self.save_undo();
+
+        let mut lod = self.model.lods.remove(level - 1);
+        lod.distance = distance;
+        let new_level = self.model.insert_lod(lod);
+        if was_editing {
+            self.edit_lod(new_level);
+        }
+    }
+
+    /// Start or stop running the state machine in the viewport
+    pub fn toggle_graph_preview(&mut self) {
+        if self.graph_preview.take().is_some() {
+            self.set_status("State machine preview stopped", 1.0);
+        } else if self.model.state_machine.states.is_empty() {
+            self.set_status("Add a state first", 1.5);
+        } else {
+            self.playing = false;
+            self.graph_preview = Some(AnimationPlayer::new(&self.model));
+            self.set_status("Previewing state machine: click a state to transition", 2.0);
+        }
+    }
+
+    /// Add a state playing the current clip
+    pub fn add_graph_state(&mut self) {
+        let Some(clip) = self.current_animation().map(|a| a.name.clone()) else {
+            self.set_status("Create an animation clip first", 1.5);
+            return;
+        };
+        self.save_undo();
+        let sm = &mut self.model.state_machine;
+        let name = if sm.find_state(&clip).is_none() { clip.clone() } else { format!("{}_{}", clip, sm.states.len()) };
+        let count = sm.states.len() as f32;
+        let index = sm.add_state(&name, &clip, (10.0 + (count % 3.0) * 100.0, 10.0 + (count / 3.0).floor() * 40.0));
+        self.graph_selection = Some(GraphSelection::State(index));
+    }
+
+    /// Link two states with a new transition
+    pub fn link_graph_states(&mut self, from: usize, to: usize) {
+        self.graph_link_from = None;
+        let mut model = self.model.clone();
+        match model.state_machine.add_transition(from, to) {
+            Some(index) => {
+                self.save_undo();
+                self.model = model;
+                self.graph_selection = Some(GraphSelection::Transition(index));
+            }
+            None => self.set_status("States already linked", 1.0),
+        }
+    }
+
+    /// Delete the selected state or transition
+    pub fn remove_graph_selection(&mut self) {
+        let Some(selection) = self.graph_selection.take() else {
+            return;
+        };
+        self.save_undo();
+        self.graph_preview = None;
+        let sm = &mut self.model.state_machine;
+        match selection {
+            GraphSelection::State(i) => sm.remove_state(i),
+            GraphSelection::Transition(i) if i < sm.transitions.len() => {
+                sm.transitions.remove(i);
+            }
+            GraphSelection::Transition(_) => {}
+        }
+    }
+
+    /// Make the selected state the one the machine starts in
+    pub fn set_initial_state(&mut self) {
+        if let Some(GraphSelection::State(i)) = self.graph_selection {
+            if i >= self.model.state_machine.states.len() {
+                return;
+            }
+            self.save_undo();
+            self.model.state_machine.initial = i;
+        }
+    }
+
+    /// Switch a transition between its target's signal and clip end
+    pub fn cycle_transition_trigger(&mut self, index: usize) {
+        let sm = &self.model.state_machine;
+        let Some(t) = sm.transitions.get(index) else {
+            return;
+        };
+        let trigger = match t.trigger {
+            TransitionTrigger::Signal(_) => TransitionTrigger::Finished,
+            TransitionTrigger::Finished => TransitionTrigger::Signal(sm.states[t.to].name.clone()),
+        };
+        self.save_undo();
+        self.model.state_machine.transitions[index].trigger = trigger;
+    }
+
+    /// Lengthen or shorten a transition's blend
+    pub fn change_blend_frames(&mut self, index: usize, delta: i32) {
+        if let Some(t) = self.model.state_machine.transitions.get(index) {
+            let frames = (t.blend_frames as i32 + delta).max(0) as u32;
+            self.save_undo();
+            self.model.state_machine.transitions[index].blend_frames = frames;
+        }
+    }
+
+    /// In preview, move to a state the way play mode would: through a transition from the current one
+    pub fn preview_goto_state(&mut self, target: usize) {
+        let Some(player) = &mut self.graph_preview else {
+            return;
+        };
+        let sm = &self.model.state_machine;
+        let Some(t) = sm.transitions.iter().find(|t| t.from == player.state && t.to == target) else {
+            self.set_status("No transition from the current state", 1.0);
+            return;
+        };
+        let (trigger, frames) = (t.trigger.clone(), t.blend_frames);
+        match trigger {
+            TransitionTrigger::Signal(signal) => {
+                player.send(&self.model, &signal);
+                self.set_status(&format!("Signal: {}", signal), 1.0);
+            }
+            TransitionTrigger::Finished => player.enter(&self.model, target, frames),
+        }
+    }
+
     /// Cycle to next view mode
     pub fn next_view(&mut self) {
         let next = (self.view.index() + 1) % ModelerView::ALL.len();
@@ -0,0 +1,290 @@
+//! Animation state machine - clip transitions for models at runtime
+//!
+//! A small graph saved with the model:
+//! - each state plays one animation clip (looping or not, as the clip says)
+//! - transitions fire on a named signal from the game ("walk", "attack"),
+//!   or when a non-looping clip reaches its last frame
+//! - a transition blends from the last pose over N frames, or switches
+//!   instantly (0 frames, or everywhere with the PS1-style `instant` option)
+//!
+//! `AnimationPlayer` runs the graph in play mode; the modeler previews it.
+
+use serde::{Deserialize, Serialize};
+use super::model::{Animation, AnimationEvent, Model, PartTransform};
+
+/// Blend length of a newly linked transition (frames)
+pub const DEFAULT_BLEND_FRAMES: u32 = 4;
+
+/// Frame rate used to time blends when a state has no clip
+const FALLBACK_FPS: f32 = 15.0;
+
+/// State graph of a model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnimStateMachine {
+    pub states: Vec<AnimState>,
+    pub transitions: Vec<AnimTransition>,
+    /// State entered when the player starts
+    pub initial: usize,
+    /// Ignore blend lengths and switch clips on the spot
+    #[serde(default)]
+    pub instant: bool,
+}
+
+/// A node of the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimState {
+    pub name: String,
+    /// Name of the animation clip played in this state
+    pub animation: String,
+    /// Node position in the graph panel
+    pub position: (f32, f32),
+}
+
+/// What makes a transition fire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransitionTrigger {
+    /// Named signal sent by the game
+    Signal(String),
+    /// The state's clip played to its end (non-looping clips only)
+    Finished,
+}
+
+/// An edge of the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimTransition {
+    pub from: usize,
+    pub to: usize,
+    pub trigger: TransitionTrigger,
+    /// Frames to blend from the old pose (0 = instant switch)
+    pub blend_frames: u32,
+}
+
+impl AnimStateMachine {
+    /// Add a state, returning its index
+    pub fn add_state(&mut self, name: &str, animation: &str, position: (f32, f32)) -> usize {
+        self.states.push(AnimState {
+            name: name.to_string(),
+            animation: animation.to_string(),
+            position,
+        });
+        self.states.len() - 1
+    }
+
+    /// Remove a state and every transition touching it
+    pub fn remove_state(&mut self, index: usize) {
+        if index >= self.states.len() {
+            return;
+        }
+        self.states.remove(index);
+        self.transitions.retain(|t| t.from != index && t.to != index);
+        let shift = |i: usize| if i > index { i - 1 } else { i };
+        for t in &mut self.transitions {
+            t.from = shift(t.from);
+            t.to = shift(t.to);
+        }
+        self.initial = shift(self.initial).min(self.states.len().saturating_sub(1));
+    }
+
+    /// Link two states, fired by a signal named after the target state.
+    /// Returns the transition index, or None if already linked.
+    pub fn add_transition(&mut self, from: usize, to: usize) -> Option<usize> {
+        if from == to || from >= self.states.len() || to >= self.states.len() {
+            return None;
+        }
+        if self.transitions.iter().any(|t| t.from == from && t.to == to) {
+            return None;
+        }
+        self.transitions.push(AnimTransition {
+            from,
+            to,
+            trigger: TransitionTrigger::Signal(self.states[to].name.clone()),
+            blend_frames: DEFAULT_BLEND_FRAMES,
+        });
+        Some(self.transitions.len() - 1)
+    }
+
+    /// Index of a state by name
+    pub fn find_state(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|s| s.name == name)
+    }
+
+    fn transition_from(&self, state: usize, trigger: &TransitionTrigger) -> Option<&AnimTransition> {
+        self.transitions.iter().find(|t| t.from == state && &t.trigger == trigger)
+    }
+}
+
+/// Runs a model's state machine: clip playback, transitions and blending
+#[derive(Debug, Clone)]
+pub struct AnimationPlayer {
+    /// Current state index
+    pub state: usize,
+    /// Frame in the current state's clip (fractional)
+    pub frame: f32,
+    /// Pose blended away from: (pose, frames elapsed, blend length)
+    blend: Option<(Vec<PartTransform>, f32, u32)>,
+}
+
+impl AnimationPlayer {
+    /// Start in the machine's initial state
+    pub fn new(model: &Model) -> Self {
+        Self { state: model.state_machine.initial, frame: 0.0, blend: None }
+    }
+
+    /// Send a signal; returns true if it triggered a transition
+    pub fn send(&mut self, model: &Model, signal: &str) -> bool {
+        let trigger = TransitionTrigger::Signal(signal.to_string());
+        match model.state_machine.transition_from(self.state, &trigger) {
+            Some(t) => {
+                let (to, frames) = (t.to, t.blend_frames);
+                self.enter(model, to, frames);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Jump to a state (blending like a transition of `blend_frames`)
+    pub fn enter(&mut self, model: &Model, state: usize, blend_frames: u32) {
+        let frames = if model.state_machine.instant { 0 } else { blend_frames };
+        self.blend = (frames > 0).then(|| (self.pose(model), 0.0, frames));
+        self.state = state;
+        self.frame = 0.0;
+    }
+
+    /// Advance by `delta` seconds; returns the clip events passed on the way
+    pub fn update(&mut self, model: &Model, delta: f32) -> Vec<AnimationEvent> {
+        let mut events = Vec::new();
+        let clip = self.clip(model);
+        let fps = clip.map(|c| c.fps.max(1) as f32).unwrap_or(FALLBACK_FPS);
+        let step = delta * fps;
+
+        if let Some((_, elapsed, length)) = &mut self.blend {
+            *elapsed += step;
+            if *elapsed >= *length as f32 {
+                self.blend = None;
+            }
+        }
+
+        let Some(clip) = clip else {
+            return events;
+        };
+        let last = clip.last_frame();
+        let end = self.frame + step;
+
+        // Events on every whole frame entered
+        let mut f = self.frame.floor() as u32 + 1;
+        while f as f32 <= end {
+            if !clip.looping && f > last {
+                break;
+            }
+            let frame = if clip.looping { f % (last + 1) } else { f };
+            events.extend(clip.events_at(frame).cloned());
+            f += 1;
+        }
+
+        if clip.looping {
+            self.frame = end % (last + 1) as f32;
+        } else if end >= last as f32 {
+            self.frame = last as f32;
+            if let Some(t) = model.state_machine.transition_from(self.state, &TransitionTrigger::Finished) {
+                let (to, frames) = (t.to, t.blend_frames);
+                self.enter(model, to, frames);
+            }
+        } else {
+            self.frame = end;
+        }
+        events
+    }
+
+    /// Current pose, mid-blend if a transition is in progress
+    pub fn pose(&self, model: &Model) -> Vec<PartTransform> {
+        let num_parts = model.parts.len();
+        let current = match self.clip(model) {
+            Some(clip) => clip.pose_at(self.frame, num_parts),
+            None => vec![PartTransform::default(); num_parts],
+        };
+        match &self.blend {
+            Some((from, elapsed, length)) => {
+                let t = (elapsed / *length as f32).clamp(0.0, 1.0);
+                from.iter().zip(&current).map(|(a, b)| a.lerp(b, t)).collect()
+            }
+            None => current,
+        }
+    }
+
+    fn clip<'a>(&self, model: &'a Model) -> Option<&'a Animation> {
+        let state = model.state_machine.states.get(self.state)?;
+        model.animations.iter().find(|a| a.name == state.animation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeler::Keyframe;
+    use crate::rasterizer::Vec3;
+
+    /// Cube with a looping "idle" (x = 0) and a one-shot "attack" (x = 10)
+    fn model() -> Model {
+        let mut model = Model::test_cube();
+        for (name, x, looping) in [("idle", 0.0, true), ("attack", 10.0, false)] {
+            let mut anim = Animation::new(name);
+            anim.looping = looping;
+            for frame in [0, 10] {
+                let mut kf = Keyframe::new(frame, 1);
+                kf.transforms[0].position = Vec3::new(x, 0.0, 0.0);
+                anim.set_keyframe(kf);
+            }
+            model.animations.push(anim);
+        }
+        let sm = &mut model.state_machine;
+        let idle = sm.add_state("idle", "idle", (0.0, 0.0));
+        let attack = sm.add_state("attack", "attack", (100.0, 0.0));
+        sm.add_transition(idle, attack);
+        let back = sm.add_transition(attack, idle).unwrap();
+        sm.transitions[back].trigger = TransitionTrigger::Finished;
+        model
+    }
+
+    #[test]
+    fn test_signal_blends_into_next_state() {
+        let model = model();
+        let mut player = AnimationPlayer::new(&model);
+        assert!(!player.send(&model, "walk"));
+        assert!(player.send(&model, "attack"));
+        assert_eq!(player.state, 1);
+
+        // Half way through the 4-frame blend at 15 fps
+        player.update(&model, 2.0 / 15.0);
+        let x = player.pose(&model)[0].position.x;
+        assert!((x - 5.0).abs() < 0.01, "x = {}", x);
+
+        player.update(&model, 2.0 / 15.0);
+        assert!(player.blend.is_none());
+    }
+
+    #[test]
+    fn test_finished_clip_returns_and_instant_skips_blend() {
+        let mut model = model();
+        model.state_machine.instant = true;
+        let mut player = AnimationPlayer::new(&model);
+        player.send(&model, "attack");
+        assert!(player.blend.is_none());
+        assert_eq!(player.pose(&model)[0].position.x, 10.0);
+
+        player.update(&model, 1.0);
+        assert_eq!(player.state, 0);
+    }
+
+    #[test]
+    fn test_removing_state_reindexes_transitions() {
+        let mut model = model();
+        let sm = &mut model.state_machine;
+        let walk = sm.add_state("walk", "idle", (0.0, 50.0));
+        sm.add_transition(walk, 1);
+        sm.remove_state(0);
+        assert_eq!(sm.states.len(), 2);
+        assert_eq!(sm.transitions.len(), 1);
+        assert_eq!((sm.transitions[0].from, sm.transitions[0].to), (1, 0));
+    }
+}
@@ -4,45 +4,11 @@ use macroquad::prelude::*;
 use crate::ui::{Rect, UiContext};
 use crate::rasterizer::{
     Framebuffer, render_mesh, Color as RasterColor, Vec3, Vec2 as RasterVec2,
-    Vertex as RasterVertex, Face as RasterFace, WIDTH, HEIGHT,
+    Vertex as RasterVertex, Face as RasterFace, world_to_screen, Camera, RasterSettings,
 };
 use super::state::{ModelerState, ModelerSelection, SelectMode};
 use super::model::{Model, PartTransform};
 
-/// Project a world-space point to framebuffer coordinates
-fn world_to_screen(
-    world_pos: Vec3,
-    camera_pos: Vec3,
-    basis_x: Vec3,
-    basis_y: Vec3,
-    basis_z: Vec3,
-    fb_width: usize,
-    fb_height: usize,
-) -> Option<(f32, f32)> {
-    let rel = world_pos - camera_pos;
-    let cam_z = rel.dot(basis_z);
-
-    // Behind camera
-    if cam_z <= 0.1 {
-        return None;
-    }
-
-    let cam_x = rel.dot(basis_x);
-    let cam_y = rel.dot(basis_y);
-
-    // Same projection as the rasterizer
-    const SCALE: f32 = 0.75;
-    let vs = (fb_width.min(fb_height) as f32 / 2.0) * SCALE;
-    let ud = 5.0;
-    let us = ud - 1.0;
-
-    let denom = cam_z + ud;
-    let sx = (cam_x * us / denom) * vs + (fb_width as f32 / 2.0);
-    let sy = (cam_y * us / denom) * vs + (fb_height as f32 / 2.0);
-
-    Some((sx, sy))
-}
-
 /// Build a 4x4 rotation matrix from euler angles (degrees)
 fn rotation_matrix(rot: Vec3) -> [[f32; 4]; 4] {
     let (sx, cx) = rot.x.to_radians().sin_cos();
@@ -59,7 +25,7 @@ fn rotation_matrix(rot: Vec3) -> [[f32; 4]; 4] {
 }
 
 /// Transform a point by a 4x4 matrix
-fn transform_point(m: &[[f32; 4]; 4], p: Vec3) -> Vec3 {
+pub(super) fn transform_point(m: &[[f32; 4]; 4], p: Vec3) -> Vec3 {
     Vec3::new(
         m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
         m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
@@ -101,7 +67,7 @@ fn identity_matrix() -> [[f32; 4]; 4] {
 }
 
 /// Compute world matrices for all parts given animation pose
-fn compute_world_matrices(model: &Model, pose: &[PartTransform]) -> Vec<[[f32; 4]; 4]> {
+pub(super) fn compute_world_matrices(model: &Model, pose: &[PartTransform]) -> Vec<[[f32; 4]; 4]> {
     let mut matrices = Vec::with_capacity(model.parts.len());
 
     for (i, part) in model.parts.iter().enumerate() {
@@ -130,18 +96,89 @@ fn compute_world_matrices(model: &Model, pose: &[PartTransform]) -> Vec<[[f32; 4
     matrices
 }
 
-/// Draw the 3D modeler viewport
+/// Triangles of a posed model at a detail level, placed in the world by
+/// `place` (hidden parts are left out)
+fn model_mesh(
+    model: &Model,
+    world_matrices: &[[[f32; 4]; 4]],
+    lod_level: usize,
+    place: impl Fn(Vec3) -> Vec3,
+) -> (Vec<RasterVertex>, Vec<RasterFace>) {
+    let mut all_vertices: Vec<RasterVertex> = Vec::new();
+    let mut all_faces: Vec<RasterFace> = Vec::new();
+
+    for (part_idx, part) in model.parts.iter().enumerate() {
+        if !part.visible {
+            continue;
+        }
+
+        let world_mat = &world_matrices[part_idx];
+        let vertex_offset = all_vertices.len();
+        let (vertices, faces) = model.part_mesh(part_idx, lod_level);
+
+        // Transform vertices
+        for vert in vertices {
+            let world_pos = place(transform_point(world_mat, vert.position));
+
+            // Calculate normal (simplified - just use up vector for now)
+            let normal = Vec3::new(0.0, 1.0, 0.0);
+
+            all_vertices.push(RasterVertex {
+                pos: world_pos,
+                uv: RasterVec2::new(vert.uv.x, vert.uv.y),
+                normal,
+            });
+        }
+
+        // Add faces with offset indices
+        for face in faces {
+            all_faces.push(RasterFace {
+                v0: face.indices[0] + vertex_offset,
+                v1: face.indices[1] + vertex_offset,
+                v2: face.indices[2] + vertex_offset,
+                texture_id: None, // TODO: Use atlas texture
+                double_sided: false,
+            });
+        }
+    }
+
+    (all_vertices, all_faces)
+}
+
+/// Draw a posed model standing at `position` (its origin), turned `yaw`
+/// radians about Y, at the detail level for its distance from the camera
+pub fn draw_model(
+    fb: &mut Framebuffer,
+    model: &Model,
+    pose: &[PartTransform],
+    position: Vec3,
+    yaw: f32,
+    camera: &Camera,
+    settings: &RasterSettings,
+) {
+    let world_matrices = compute_world_matrices(model, pose);
+    let lod_level = model.lod_level((position - camera.position).len());
+    let (sin, cos) = yaw.sin_cos();
+    let (vertices, faces) = model_mesh(model, &world_matrices, lod_level, |p| {
+        position + Vec3::new(p.x * cos + p.z * sin, p.y, p.z * cos - p.x * sin)
+    });
+    render_mesh(fb, &vertices, &faces, &[], camera, settings);
+}
+
+/// Draw the 3D modeler viewport (at reduced resolution while `resizing` its
+/// panel or moving the camera)
 pub fn draw_modeler_viewport(
     ctx: &mut UiContext,
     rect: Rect,
     state: &mut ModelerState,
     fb: &mut Framebuffer,
+    resizing: bool,
 ) {
-    // Resize framebuffer based on resolution setting
-    let (target_w, target_h) = if state.raster_settings.low_resolution {
-        (WIDTH, HEIGHT) // 320x240
+    // Resize framebuffer based on resolution setting, halved while the view moves
+    let (target_w, target_h) = if resizing || state.camera_moving {
+        state.raster_settings.reduced_resolution()
     } else {
-        (crate::rasterizer::WIDTH_HI, crate::rasterizer::HEIGHT_HI) // 640x480
+        state.raster_settings.resolution()
     };
     fb.resize(target_w, target_h);
 
@@ -174,7 +211,7 @@ pub fn draw_modeler_viewport(
         }
     };
 
-    // Camera rotation with right mouse button (orbit)
+    // Camera rotation with right mouse button or two-finger drag (orbit)
     if ctx.mouse.right_down && inside_viewport {
         if state.viewport_mouse_captured {
             let dx = (mouse_pos.1 - state.viewport_last_mouse.1) * 0.005;
@@ -188,6 +225,7 @@ pub fn draw_modeler_viewport(
 
     // Keyboard camera movement (WASD + Q/E)
     let move_speed = 10.0; // Smaller scale for models
+    let camera_before = state.camera.position;
     if inside_viewport || state.viewport_mouse_captured {
         if is_key_down(KeyCode::W) {
             state.camera.position = state.camera.position + state.camera.basis_z * move_speed;
@@ -209,15 +247,16 @@ pub fn draw_modeler_viewport(
         }
     }
 
-    // Mouse wheel zoom
+    // Mouse wheel / pinch zoom
     if inside_viewport {
-        let scroll = mouse_wheel().1;
+        let scroll = ctx.mouse.scroll;
         if scroll != 0.0 {
             let zoom_speed = 20.0;
             let zoom_dir = if scroll > 0.0 { 1.0 } else { -1.0 };
             state.camera.position = state.camera.position + state.camera.basis_z * zoom_speed * zoom_dir;
         }
     }
+    state.camera_moving = state.viewport_mouse_captured || state.camera.position != camera_before;
 
     // Update mouse position for next frame
     state.viewport_last_mouse = mouse_pos;
@@ -234,53 +273,24 @@ pub fn draw_modeler_viewport(
     // Compute world matrices for all parts
     let world_matrices = compute_world_matrices(&state.model, &pose);
 
-    // Build render data for all parts
-    let mut all_vertices: Vec<RasterVertex> = Vec::new();
-    let mut all_faces: Vec<RasterFace> = Vec::new();
-
-    for (part_idx, part) in state.model.parts.iter().enumerate() {
-        if !part.visible {
-            continue;
-        }
-
-        let world_mat = &world_matrices[part_idx];
-        let vertex_offset = all_vertices.len();
-
-        // Transform vertices
-        for vert in &part.vertices {
-            let world_pos = transform_point(world_mat, vert.position);
-
-            // Calculate normal (simplified - just use up vector for now)
-            let normal = Vec3::new(0.0, 1.0, 0.0);
-
-            all_vertices.push(RasterVertex {
-                pos: world_pos,
-                uv: RasterVec2::new(vert.uv.x, vert.uv.y),
-                normal,
-            });
-        }
+    // LOD preview picks the level by camera distance to the model origin, like play mode
+    let lod_level = state.preview_lod_level();
 
-        // Add faces with offset indices
-        for face in &part.faces {
-            all_faces.push(RasterFace {
-                v0: face.indices[0] + vertex_offset,
-                v1: face.indices[1] + vertex_offset,
-                v2: face.indices[2] + vertex_offset,
-                texture_id: None, // TODO: Use atlas texture
-            });
-        }
-    }
+    let (all_vertices, all_faces) = model_mesh(&state.model, &world_matrices, lod_level, |p| p);
 
     // Render using software rasterizer
     let empty_textures: Vec<crate::rasterizer::Texture> = Vec::new();
     render_mesh(fb, &all_vertices, &all_faces, &empty_textures, &state.camera, &state.raster_settings);
 
-    // Draw part/vertex/edge/face overlays based on selection mode
-    draw_selection_overlays(ctx, fb, state, &world_matrices, screen_to_fb);
+    // Overlays and picking work on the parts, which a previewed LOD is not
+    if lod_level == 0 {
+        // Draw part/vertex/edge/face overlays based on selection mode
+        draw_selection_overlays(ctx, fb, state, &world_matrices, screen_to_fb);
 
-    // Handle click selection
-    if inside_viewport && ctx.mouse.left_pressed && !ctx.mouse.right_down {
-        handle_selection_click(ctx, state, &world_matrices, screen_to_fb, fb.width, fb.height);
+        // Handle click selection
+        if inside_viewport && ctx.mouse.left_pressed && !ctx.mouse.right_down {
+            handle_selection_click(ctx, state, &world_matrices, screen_to_fb, fb.width, fb.height);
+        }
     }
 
     // Convert framebuffer to texture and draw
@@ -314,6 +324,18 @@ pub fn draw_modeler_viewport(
         12.0,
         Color::from_rgba(180, 180, 180, 255),
     );
+
+    // Detail level being shown
+    let lod_label = if state.editing_lod > 0 {
+        Some(format!("Editing LOD {}", state.editing_lod))
+    } else if state.lod_preview {
+        Some(format!("LOD {} (auto)", lod_level))
+    } else {
+        None
+    };
+    if let Some(label) = lod_label {
+        draw_text(&label, rect.x + 5.0, rect.y + 14.0, 12.0, Color::from_rgba(0, 190, 230, 255));
+    }
 }
 
 /// Draw floor grid
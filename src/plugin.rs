@@ -0,0 +1,149 @@
+//! Tool plugins
+//!
+//! Extra tabs beyond the built-in tools (e.g. a quest editor), added without
+//! touching the tab list in `main.rs`:
+//! - a plugin implements `ToolPlugin` and is registered in `register_plugins`,
+//!   usually behind a cargo feature
+//! - each registered plugin gets a tab after the built-in ones and draws its
+//!   whole content area every frame it's active
+//! - `AppServices` hands it the World Editor's level, undo history and status
+//!   bar, and the icon font
+//! - a panicking plugin gets the same crash screen as the built-in tools
+
+use macroquad::prelude::Font;
+use crate::editor::EditorState;
+use crate::ui::{Rect, Shortcut, UiContext};
+
+/// What the app lends a plugin while it draws (only read by plugins, so
+/// unused in builds without any)
+#[cfg_attr(not(feature = "level-stats-plugin"), allow(dead_code))]
+pub struct AppServices<'a> {
+    /// World Editor state: the open level, undo (`save_undo`) and status bar (`set_status`)
+    pub editor: &'a mut EditorState,
+    /// Lucide icon font, when loaded
+    pub icon_font: Option<&'a Font>,
+}
+
+/// A tool with its own tab
+pub trait ToolPlugin {
+    /// Tab label
+    fn name(&self) -> &str;
+
+    /// Tab icon (a Lucide codepoint, see `ui::icon`)
+    fn icon(&self) -> char;
+
+    /// Draw the plugin's content and handle its input for one frame
+    fn draw(&mut self, ctx: &mut UiContext, rect: Rect, services: &mut AppServices);
+
+    /// Handle an app-wide shortcut (Ctrl+Z, Ctrl+S...) while the plugin's tab is active
+    fn shortcut(&mut self, _shortcut: Shortcut, _services: &mut AppServices) {}
+
+    /// Drop the plugin's state, after it crashed
+    fn reset(&mut self) {}
+}
+
+/// Plugins in tab order
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: Vec<Box<dyn ToolPlugin>>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a plugin; its tab goes after the ones already registered
+    #[cfg_attr(not(any(test, feature = "level-stats-plugin")), allow(dead_code))]
+    pub fn register(&mut self, plugin: Box<dyn ToolPlugin>) {
+        log::info!("Registered tool plugin {}", plugin.name());
+        self.plugins.push(plugin);
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut (dyn ToolPlugin + 'static)> {
+        self.plugins.get_mut(index).map(|p| p.as_mut())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ToolPlugin> {
+        self.plugins.iter().map(|p| p.as_ref())
+    }
+}
+
+/// Register the plugins compiled into this build
+#[cfg_attr(not(feature = "level-stats-plugin"), allow(unused_variables))]
+pub fn register_plugins(registry: &mut PluginRegistry) {
+    #[cfg(feature = "level-stats-plugin")]
+    registry.register(Box::new(level_stats::LevelStatsPlugin));
+}
+
+/// Example plugin: a read-only summary of the open level
+#[cfg(feature = "level-stats-plugin")]
+mod level_stats {
+    use macroquad::prelude::*;
+    use super::{AppServices, ToolPlugin};
+    use crate::ui::{draw_icon_centered, icon, Rect, UiContext};
+
+    pub struct LevelStatsPlugin;
+
+    impl ToolPlugin for LevelStatsPlugin {
+        fn name(&self) -> &str {
+            "Level Stats"
+        }
+
+        fn icon(&self) -> char {
+            icon::TIMER
+        }
+
+        fn draw(&mut self, _ctx: &mut UiContext, rect: Rect, services: &mut AppServices) {
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(30, 30, 35, 255));
+            draw_icon_centered(services.icon_font, self.icon(), &Rect::new(rect.x + 8.0, rect.y + 8.0, 24.0, 24.0), 18.0, WHITE);
+            draw_text(self.name(), rect.x + 36.0, rect.y + 26.0, 18.0, WHITE);
+            let level = &services.editor.level;
+            let sectors: usize = level.rooms.iter().map(|r| r.iter_sectors().count()).sum();
+            let entities: usize = level.rooms.iter().map(|r| r.entities.len()).sum();
+            let lines = [
+                format!("Rooms: {}", level.rooms.len()),
+                format!("Sectors: {}", sectors),
+                format!("Entities: {}", entities),
+                format!("Camera paths: {}", level.camera_paths.len()),
+            ];
+            for (i, line) in lines.iter().enumerate() {
+                draw_text(line, rect.x + 16.0, rect.y + 64.0 + i as f32 * 22.0, 18.0, WHITE);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Named(&'static str);
+
+    impl ToolPlugin for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn icon(&self) -> char {
+            crate::ui::icon::PLUS
+        }
+
+        fn draw(&mut self, _ctx: &mut UiContext, _rect: Rect, _services: &mut AppServices) {}
+    }
+
+    #[test]
+    fn test_plugins_keep_registration_order() {
+        let mut registry = PluginRegistry::new();
+        assert_eq!(registry.len(), 0);
+        registry.register(Box::new(Named("quests")));
+        registry.register(Box::new(Named("dialogue")));
+        assert_eq!(registry.iter().map(|p| p.name()).collect::<Vec<_>>(), ["quests", "dialogue"]);
+        assert_eq!(registry.get_mut(1).map(|p| p.name().to_string()), Some("dialogue".to_string()));
+        assert!(registry.get_mut(2).is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! Texture atlases - many small textures packed into a few big ones
+//!
+//! Game export packs the textures each level uses into atlases, so most faces
+//! share a handful of textures (see `editor::compile_atlases`):
+//! - `pack_atlases` places textures on shelves, tallest first
+//! - `AtlasRegion::map_uv` moves a face's UVs into its texture's region
+//!
+//! Sampling wraps, and a sub-rectangle of an atlas can't, so faces whose UVs
+//! leave 0..1 (tiled textures) keep their standalone texture.
+
+use super::{Texture, Vec2};
+
+/// Side length of a texture atlas in texels
+pub const ATLAS_SIZE: usize = 512;
+
+/// Where a texture ended up inside an atlas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasRegion {
+    /// Index into the atlases returned by `pack_atlases`
+    pub atlas: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Atlas side length
+    pub size: usize,
+}
+
+impl AtlasRegion {
+    /// Map a 0..1 UV of the original texture into the atlas.
+    /// Texel centers are inset by half a texel so edges never bleed into
+    /// the neighbouring region.
+    pub fn map_uv(&self, uv: Vec2) -> Vec2 {
+        let size = self.size as f32;
+        let u = (self.x as f32 + 0.5 + uv.x * (self.width as f32 - 1.0)) / size;
+        // The rasterizer samples at (u, 1 - v)
+        let row = self.y as f32 + 0.5 + (1.0 - uv.y) * (self.height as f32 - 1.0);
+        Vec2::new(u, 1.0 - row / size)
+    }
+}
+
+/// Pack textures into `size` x `size` atlases. Returns the atlases and each
+/// texture's region (None for textures bigger than an atlas).
+pub fn pack_atlases(textures: &[&Texture], size: usize) -> (Vec<Texture>, Vec<Option<AtlasRegion>>) {
+    let mut order: Vec<usize> = (0..textures.len()).collect();
+    order.sort_by(|&a, &b| textures[b].height.cmp(&textures[a].height).then(a.cmp(&b)));
+
+    let mut atlases: Vec<Texture> = Vec::new();
+    let mut regions = vec![None; textures.len()];
+    // Cursor of the current atlas: (x, shelf top, shelf height)
+    let (mut x, mut shelf_y, mut shelf_h) = (0, 0, 0);
+    for i in order {
+        let tex = textures[i];
+        if tex.width == 0 || tex.height == 0 || tex.width > size || tex.height > size {
+            continue;
+        }
+        if x + tex.width > size {
+            // Next shelf
+            shelf_y += shelf_h;
+            x = 0;
+            shelf_h = 0;
+        }
+        if atlases.is_empty() || shelf_y + tex.height > size {
+            let mut atlas = Texture::new(size, size);
+            atlas.name = format!("atlas_{}", atlases.len());
+            atlases.push(atlas);
+            (x, shelf_y, shelf_h) = (0, 0, 0);
+        }
+        let atlas_idx = atlases.len() - 1;
+        let atlas = &mut atlases[atlas_idx];
+        for row in 0..tex.height {
+            let src = &tex.pixels[row * tex.width..(row + 1) * tex.width];
+            let dst = (shelf_y + row) * size + x;
+            atlas.pixels[dst..dst + tex.width].copy_from_slice(src);
+        }
+        regions[i] = Some(AtlasRegion { atlas: atlas_idx, x, y: shelf_y, width: tex.width, height: tex.height, size });
+        x += tex.width;
+        shelf_h = shelf_h.max(tex.height);
+    }
+    (atlases, regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Color;
+
+    fn solid(width: usize, height: usize, color: Color) -> Texture {
+        let mut tex = Texture::new(width, height);
+        tex.pixels.fill(color);
+        tex
+    }
+
+    #[test]
+    fn test_packed_regions_sample_their_own_texture() {
+        let red = solid(32, 32, Color::new(255, 0, 0));
+        let green = solid(64, 16, Color::new(0, 255, 0));
+        let (atlases, regions) = pack_atlases(&[&red, &green], 64);
+        // Green goes on a second shelf of the same atlas
+        assert_eq!(atlases.len(), 1);
+        assert!(regions.iter().all(|r| r.is_some()));
+
+        // Every corner of each texture maps to a texel of that texture
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(u, v)| Vec2::new(u, v));
+        for (region, expected) in regions.iter().zip([Color::new(255, 0, 0), Color::new(0, 255, 0)]) {
+            let region = region.unwrap();
+            for uv in corners {
+                let uv = region.map_uv(uv);
+                assert_eq!(atlases[region.atlas].sample(uv.x, 1.0 - uv.y).to_bytes(), expected.to_bytes());
+            }
+        }
+
+        // Too big for the atlas: left out
+        let big = solid(128, 8, Color::WHITE);
+        assert_eq!(pack_atlases(&[&big], 64).1, vec![None]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Fixed-point math (20.12), like the PS1 GTE
+//!
+//! Used by the optional fixed-point pipeline (`RasterSettings::fixed_point`):
+//! - Camera transform with 1.3.12-style basis vectors
+//! - Integer perspective divide
+//! - Barycentric / UV / depth interpolation with 12 fractional bits
+//!
+//! Integer math gives bit-identical results on every platform (including
+//! WASM) and reproduces the GTE's precision artifacts.
+
+use std::ops::{Add, Mul, Neg, Sub};
+use super::math::{Vec2, Vec3};
+
+/// Fractional bits
+pub const FIXED_SHIFT: u32 = 12;
+/// 1.0 in fixed point
+pub const FIXED_ONE: i32 = 1 << FIXED_SHIFT;
+
+/// 20.12 fixed-point number
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+    pub const ONE: Fixed = Fixed(FIXED_ONE);
+
+    pub fn from_f32(v: f32) -> Self {
+        Fixed((v * FIXED_ONE as f32).round() as i32)
+    }
+
+    pub fn from_int(v: i32) -> Self {
+        Fixed(v << FIXED_SHIFT)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / FIXED_ONE as f32
+    }
+
+    /// Integer part (rounds towards negative infinity)
+    pub fn floor(self) -> i32 {
+        self.0 >> FIXED_SHIFT
+    }
+
+    /// Division (None on divide by zero)
+    pub fn checked_div(self, rhs: Fixed) -> Option<Fixed> {
+        if rhs.0 == 0 {
+            return None;
+        }
+        let quotient = ((self.0 as i64) << FIXED_SHIFT) / rhs.0 as i64;
+        Some(Fixed(quotient.clamp(i32::MIN as i64, i32::MAX as i64) as i32))
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+    /// Saturates instead of wrapping (the GTE clamps its outputs too)
+    fn mul(self, rhs: Fixed) -> Fixed {
+        let product = (self.0 as i64 * rhs.0 as i64) >> FIXED_SHIFT;
+        Fixed(product.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(self.0.wrapping_neg())
+    }
+}
+
+/// Fixed-point 3D vector
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixedVec3 {
+    pub x: Fixed,
+    pub y: Fixed,
+    pub z: Fixed,
+}
+
+impl FixedVec3 {
+    pub fn from_vec3(v: Vec3) -> Self {
+        Self {
+            x: Fixed::from_f32(v.x),
+            y: Fixed::from_f32(v.y),
+            z: Fixed::from_f32(v.z),
+        }
+    }
+
+    pub fn to_vec3(self) -> Vec3 {
+        Vec3::new(self.x.to_f32(), self.y.to_f32(), self.z.to_f32())
+    }
+
+    pub fn dot(self, other: FixedVec3) -> Fixed {
+        // Accumulate at full precision, shift once (like the GTE's 44-bit MAC)
+        let sum = self.x.0 as i64 * other.x.0 as i64
+            + self.y.0 as i64 * other.y.0 as i64
+            + self.z.0 as i64 * other.z.0 as i64;
+        Fixed((sum >> FIXED_SHIFT).clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+}
+
+impl Sub for FixedVec3 {
+    type Output = FixedVec3;
+    fn sub(self, rhs: FixedVec3) -> FixedVec3 {
+        FixedVec3 { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z }
+    }
+}
+
+/// Camera-space transform in fixed point (see `perspective_transform`)
+pub fn transform_fixed(pos: Vec3, cam_pos: Vec3, basis: [Vec3; 3]) -> FixedVec3 {
+    let rel = FixedVec3::from_vec3(pos) - FixedVec3::from_vec3(cam_pos);
+    FixedVec3 {
+        x: rel.dot(FixedVec3::from_vec3(basis[0])),
+        y: rel.dot(FixedVec3::from_vec3(basis[1])),
+        z: rel.dot(FixedVec3::from_vec3(basis[2])),
+    }
+}
+
+/// Fixed-point projection (see `project`). Screen x/y carry 12 fractional bits
+/// unless snapped; z is the camera-space depth.
+pub fn project_fixed(v: FixedVec3, snap: bool, width: usize, height: usize) -> Vec3 {
+    const DISTANCE: i32 = 5;
+    const SCALE: Fixed = Fixed(3 * FIXED_ONE / 4);
+
+    let ud = Fixed::from_int(DISTANCE);
+    let us = Fixed::from_int(DISTANCE - 1);
+    let vs = Fixed::from_int(width.min(height) as i32 / 2) * SCALE;
+    let half_w = Fixed::from_int(width as i32) * Fixed(FIXED_ONE / 2);
+    let half_h = Fixed::from_int(height as i32) * Fixed(FIXED_ONE / 2);
+
+    let denom = v.z + ud;
+    let (Some(px), Some(py)) = ((v.x * us).checked_div(denom), (v.y * us).checked_div(denom)) else {
+        return Vec3::new(half_w.to_f32(), half_h.to_f32(), v.z.to_f32());
+    };
+
+    let mut sx = px * vs + half_w;
+    let mut sy = py * vs + half_h;
+    if snap {
+        sx = Fixed::from_int(sx.floor());
+        sy = Fixed::from_int(sy.floor());
+    }
+    Vec3::new(sx.to_f32(), sy.to_f32(), v.z.to_f32())
+}
+
+/// Barycentric coordinates with 12 fractional bits (see `barycentric`)
+pub fn barycentric_fixed(px: i32, py: i32, v1: Vec3, v2: Vec3, v3: Vec3) -> Vec3 {
+    // i128 keeps far off-screen (saturated) vertices from overflowing
+    let f = |v: f32| Fixed::from_f32(v).0 as i128;
+    let (x1, y1, x2, y2, x3, y3) = (f(v1.x), f(v1.y), f(v2.x), f(v2.y), f(v3.x), f(v3.y));
+    let (px, py) = ((px as i128) << FIXED_SHIFT, (py as i128) << FIXED_SHIFT);
+
+    // Products carry 24 fractional bits
+    let d = (y2 - y3) * (x1 - x3) + (x3 - x2) * (y1 - y3);
+    if d == 0 {
+        return Vec3::new(-1.0, -1.0, -1.0);
+    }
+    let nu = (y2 - y3) * (px - x3) + (x3 - x2) * (py - y3);
+    let nv = (y3 - y1) * (px - x3) + (x1 - x3) * (py - y3);
+    let to_fixed = |n: i128| Fixed(((n << FIXED_SHIFT) / d).clamp(i32::MIN as i128, i32::MAX as i128) as i32);
+    let (u, v) = (to_fixed(nu), to_fixed(nv));
+    let w = Fixed::ONE - u - v;
+    Vec3::new(u.to_f32(), v.to_f32(), w.to_f32())
+}
+
+/// Interpolate three values with fixed-point barycentrics
+pub fn interpolate_fixed(bc: Vec3, a: f32, b: f32, c: f32) -> f32 {
+    let (u, v, w) = (Fixed::from_f32(bc.x), Fixed::from_f32(bc.y), Fixed::from_f32(bc.z));
+    (u * Fixed::from_f32(a) + v * Fixed::from_f32(b) + w * Fixed::from_f32(c)).to_f32()
+}
+
+/// Interpolate three UVs with fixed-point barycentrics
+pub fn interpolate_uv_fixed(bc: Vec3, uv1: Vec2, uv2: Vec2, uv3: Vec2) -> (f32, f32) {
+    (
+        interpolate_fixed(bc, uv1.x, uv2.x, uv3.x),
+        interpolate_fixed(bc, uv1.y, uv2.y, uv3.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::{barycentric, project};
+
+    #[test]
+    fn test_fixed_arithmetic() {
+        let a = Fixed::from_f32(1.5);
+        let b = Fixed::from_f32(-2.25);
+        assert_eq!((a * b).to_f32(), -3.375);
+        assert_eq!(b.checked_div(a).unwrap().to_f32(), -1.5);
+        assert_eq!(a.checked_div(Fixed(0)), None);
+        assert_eq!(b.floor(), -3);
+    }
+
+    #[test]
+    fn test_fixed_matches_float_pipeline() {
+        let v = Vec3::new(120.0, -40.0, 300.0);
+        let float = project(v, true, 320, 240);
+        let fixed = project_fixed(FixedVec3::from_vec3(v), true, 320, 240);
+        assert!((float.x - fixed.x).abs() <= 1.0);
+        assert!((float.y - fixed.y).abs() <= 1.0);
+
+        let (v1, v2, v3) = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(40.0, 0.0, 0.0), Vec3::new(0.0, 40.0, 0.0));
+        let bf = barycentric(Vec3::new(10.0, 10.0, 0.0), v1, v2, v3);
+        let bx = barycentric_fixed(10, 10, v1, v2, v3);
+        assert!((bf.x - bx.x).abs() < 0.001 && (bf.y - bx.y).abs() < 0.001);
+    }
+}
@@ -0,0 +1,274 @@
+//! Bitmap font for in-framebuffer text
+//!
+//! Text drawn straight into the `Framebuffer` at its native resolution, so it
+//! goes through the same upscale and CRT pass as the 3D image (like PS1 games,
+//! which blitted font pages into VRAM):
+//! - 5x7 glyphs in a 6x8 cell, printable ASCII (anything else draws as `?`)
+//! - `TextPalette` gives each glyph a vertical gradient and optional drop shadow
+//! - `\n` starts a new line
+
+use super::render::Framebuffer;
+use super::types::Color;
+
+/// Glyph cell size in pixels (glyph plus one pixel of spacing)
+pub const GLYPH_WIDTH: usize = 6;
+pub const GLYPH_HEIGHT: usize = 8;
+
+/// Rows of ink in a glyph (the last cell row is spacing)
+const GLYPH_ROWS: usize = 7;
+
+/// Colors for bitmap text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextPalette {
+    /// Color of the glyphs' top row
+    pub top: Color,
+    /// Color of the glyphs' bottom row (rows in between are interpolated)
+    pub bottom: Color,
+    /// Drawn one pixel down and right of the glyphs
+    pub shadow: Option<Color>,
+}
+
+impl TextPalette {
+    /// Plain white with a dark shadow
+    pub const WHITE: TextPalette = TextPalette {
+        top: Color { r: 255, g: 255, b: 255, a: 255 },
+        bottom: Color { r: 200, g: 200, b: 200, a: 255 },
+        shadow: Some(Color { r: 16, g: 16, b: 24, a: 255 }),
+    };
+    /// Pale blue system font, for debug and editor readouts
+    pub const SYSTEM: TextPalette = TextPalette {
+        top: Color { r: 220, g: 230, b: 255, a: 255 },
+        bottom: Color { r: 120, g: 140, b: 200, a: 255 },
+        shadow: Some(Color { r: 8, g: 8, b: 32, a: 255 }),
+    };
+    /// Gold, for titles and item names
+    pub const GOLD: TextPalette = TextPalette {
+        top: Color { r: 255, g: 240, b: 160, a: 255 },
+        bottom: Color { r: 200, g: 130, b: 40, a: 255 },
+        shadow: Some(Color { r: 32, g: 16, b: 0, a: 255 }),
+    };
+    /// Red, for warnings and errors
+    pub const ALERT: TextPalette = TextPalette {
+        top: Color { r: 255, g: 140, b: 120, a: 255 },
+        bottom: Color { r: 200, g: 30, b: 30, a: 255 },
+        shadow: Some(Color { r: 32, g: 0, b: 0, a: 255 }),
+    };
+
+    /// Single color, no shadow
+    pub fn solid(color: Color) -> Self {
+        Self { top: color, bottom: color, shadow: None }
+    }
+
+    /// Color of glyph row `row` (0 = top)
+    fn row_color(&self, row: usize) -> Color {
+        let t = row as f32 / (GLYPH_ROWS - 1) as f32;
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::with_alpha(
+            mix(self.top.r, self.bottom.r),
+            mix(self.top.g, self.bottom.g),
+            mix(self.top.b, self.bottom.b),
+            mix(self.top.a, self.bottom.a),
+        )
+    }
+}
+
+/// Size in pixels of `text` drawn with the bitmap font (widest line, all lines)
+pub fn text_size(text: &str) -> (usize, usize) {
+    let width = text.lines().map(|line| line.chars().count()).max().unwrap_or(0) * GLYPH_WIDTH;
+    let height = text.split('\n').count() * GLYPH_HEIGHT;
+    (width, height)
+}
+
+impl Framebuffer {
+    /// Draw bitmap text with its top-left corner at (x, y), clipped to the framebuffer
+    pub fn draw_text(&mut self, text: &str, x: i32, y: i32, palette: &TextPalette) {
+        if let Some(shadow) = palette.shadow {
+            self.draw_glyphs(text, x + 1, y + 1, |_| shadow);
+        }
+        self.draw_glyphs(text, x, y, |row| palette.row_color(row));
+    }
+
+    /// Draw bitmap text horizontally centered on `center_x`
+    pub fn draw_text_centered(&mut self, text: &str, center_x: i32, y: i32, palette: &TextPalette) {
+        let (width, _) = text_size(text);
+        self.draw_text(text, center_x - width as i32 / 2, y, palette);
+    }
+
+    fn draw_glyphs(&mut self, text: &str, x: i32, y: i32, color: impl Fn(usize) -> Color) {
+        for (line_index, line) in text.split('\n').enumerate() {
+            let line_y = y + (line_index * GLYPH_HEIGHT) as i32;
+            for (char_index, ch) in line.chars().enumerate() {
+                let glyph_x = x + (char_index * GLYPH_WIDTH) as i32;
+                for (col, bits) in glyph(ch).iter().enumerate() {
+                    for row in 0..GLYPH_ROWS {
+                        if bits & (1 << row) == 0 {
+                            continue;
+                        }
+                        let (px, py) = (glyph_x + col as i32, line_y + row as i32);
+                        if px >= 0 && py >= 0 {
+                            self.set_pixel(px as usize, py as usize, color(row));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Column bitmaps for a character (bit 0 = top row)
+fn glyph(ch: char) -> &'static [u8; 5] {
+    let code = ch as usize;
+    if (0x20..0x7F).contains(&code) {
+        &FONT_5X7[code - 0x20]
+    } else {
+        &FONT_5X7['?' as usize - 0x20]
+    }
+}
+
+/// Printable ASCII (0x20-0x7E), five columns per glyph
+const FONT_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x14, 0x08, 0x3E, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(fb: &Framebuffer, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * fb.width + x) * 4;
+        [fb.pixels[i], fb.pixels[i + 1], fb.pixels[i + 2], fb.pixels[i + 3]]
+    }
+
+    #[test]
+    fn test_text_size() {
+        assert_eq!(text_size("Hi"), (12, 8));
+        assert_eq!(text_size("abc\nx"), (18, 16));
+        assert_eq!(text_size(""), (0, 8));
+    }
+
+    #[test]
+    fn test_draw_text_gradient_and_shadow() {
+        let mut fb = Framebuffer::new(16, 16);
+        fb.clear(Color::BLACK);
+        let palette = TextPalette { top: Color::WHITE, bottom: Color::RED, shadow: Some(Color::BLUE) };
+        fb.draw_text("|", 0, 0, &palette);
+
+        // '|' is a full-height bar in column 2
+        assert_eq!(pixel(&fb, 2, 0), [255, 255, 255, 255]);
+        assert_eq!(pixel(&fb, 2, 6), [255, 0, 0, 255]);
+        // Shadow shows below the last row, and nothing is drawn in the spacing column
+        assert_eq!(pixel(&fb, 3, 7), [0, 0, 255, 255]);
+        assert_eq!(pixel(&fb, 5, 3), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_offscreen_and_unknown_characters() {
+        // Partly offscreen text is clipped rather than wrapping or panicking
+        let mut fb = Framebuffer::new(8, 8);
+        fb.clear(Color::BLACK);
+        fb.draw_text("WW\nWW", -3, -4, &TextPalette::WHITE);
+        fb.draw_text("WW", 6, 6, &TextPalette::WHITE);
+
+        // Characters outside the font draw as '?'
+        let mut unknown = Framebuffer::new(8, 8);
+        let mut question = Framebuffer::new(8, 8);
+        unknown.draw_text("\u{e000}", 0, 0, &TextPalette::solid(Color::GREEN));
+        question.draw_text("?", 0, 0, &TextPalette::solid(Color::GREEN));
+        assert_eq!(unknown.pixels, question.pixels);
+        assert_eq!(pixel(&question, 2, 4), [0, 255, 0, 255]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Baked lighting - directional shading painted into a texture
+//!
+//! PS1 artists shaded their textures by hand: bricks lit from above have a
+//! bright top edge and a dark bottom one, with no normal maps involved.
+//! `bake_light` fakes that from a height field, either the texture's own
+//! brightness (emboss) or a bevel rising from the edges of its color regions,
+//! and brightens or darkens each texel by how much its slope faces the
+//! light. Neighbours wrap around the borders so tiling textures stay seamless.
+
+use std::collections::VecDeque;
+use super::{Color, Texture};
+
+/// Texels a bevel takes to rise from an edge to the flat top
+const BEVEL_WIDTH: usize = 3;
+
+/// Where the light comes from, clockwise from the top
+pub const LIGHT_DIRECTIONS: [&str; 8] = [
+    "top", "top-right", "right", "bottom-right", "bottom", "bottom-left", "left", "top-left",
+];
+
+/// Height field the shading is derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightSource {
+    /// Brighter texels stand out
+    Emboss,
+    /// Color regions are raised, bevelled down to their edges
+    Bevel,
+}
+
+impl HeightSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            HeightSource::Emboss => "Emboss",
+            HeightSource::Bevel => "Bevel",
+        }
+    }
+}
+
+/// Bake settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightBake {
+    pub source: HeightSource,
+    /// Index into `LIGHT_DIRECTIONS`
+    pub direction: usize,
+    /// Brightness change for a slope of one height unit per texel
+    pub strength: f32,
+}
+
+impl Default for LightBake {
+    fn default() -> Self {
+        Self { source: HeightSource::Bevel, direction: 7, strength: 0.75 }
+    }
+}
+
+/// Heights (0..1) of every texel, row by row
+fn heights(texture: &Texture, source: HeightSource) -> Vec<f32> {
+    let (w, h) = (texture.width, texture.height);
+    match source {
+        HeightSource::Emboss => texture
+            .pixels
+            .iter()
+            .map(|c| (c.r as f32 * 0.299 + c.g as f32 * 0.587 + c.b as f32 * 0.114) / 255.0)
+            .collect(),
+        HeightSource::Bevel => {
+            // Distance (4-way) to the nearest texel with a differing neighbour
+            let key = |c: Color| (c.r >> 3, c.g >> 3, c.b >> 3, c.a >= 128);
+            let at = |x: usize, y: usize| key(texture.pixels[y * w + x]);
+            let mut distance = vec![usize::MAX; w * h];
+            let mut queue = VecDeque::new();
+            for y in 0..h {
+                for x in 0..w {
+                    let c = at(x, y);
+                    let edge = neighbours(x, y, w, h).iter().any(|&(nx, ny)| at(nx, ny) != c);
+                    if edge {
+                        distance[y * w + x] = 0;
+                        queue.push_back((x, y));
+                    }
+                }
+            }
+            while let Some((x, y)) = queue.pop_front() {
+                let d = distance[y * w + x] + 1;
+                for (nx, ny) in neighbours(x, y, w, h) {
+                    if d < distance[ny * w + nx] && d <= BEVEL_WIDTH {
+                        distance[ny * w + nx] = d;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            distance.iter().map(|&d| d.min(BEVEL_WIDTH) as f32 / BEVEL_WIDTH as f32).collect()
+        }
+    }
+}
+
+/// Left, right, up and down neighbours, wrapping around
+fn neighbours(x: usize, y: usize, w: usize, h: usize) -> [(usize, usize); 4] {
+    [((x + w - 1) % w, y), ((x + 1) % w, y), (x, (y + h - 1) % h), (x, (y + 1) % h)]
+}
+
+/// The texture with directional shading baked in (alpha is kept)
+pub fn bake_light(texture: &Texture, bake: &LightBake) -> Texture {
+    let (w, h) = (texture.width, texture.height);
+    let mut baked = texture.clone();
+    if w == 0 || h == 0 {
+        return baked;
+    }
+    let height = heights(texture, bake.source);
+    let angle = (bake.direction % LIGHT_DIRECTIONS.len()) as f32 * std::f32::consts::FRAC_PI_4;
+    // Towards the light, in texel space (y down)
+    let (lx, ly) = (angle.sin(), -angle.cos());
+    for y in 0..h {
+        for x in 0..w {
+            let [left, right, up, down] = neighbours(x, y, w, h).map(|(nx, ny)| height[ny * w + nx]);
+            let (gx, gy) = ((right - left) / 2.0, (down - up) / 2.0);
+            // Slopes rising away from the light face it
+            let facing = -(gx * lx + gy * ly);
+            let shade = (1.0 + bake.strength * facing).clamp(0.0, 2.0);
+            let c = texture.pixels[y * w + x];
+            let lit = |v: u8| (v as f32 * shade).round().clamp(0.0, 255.0) as u8;
+            baked.pixels[y * w + x] = Color::with_alpha(lit(c.r), lit(c.g), lit(c.b), c.a);
+        }
+    }
+    baked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A light square on a dark background
+    fn tile() -> Texture {
+        let mut tex = Texture::new(16, 16);
+        for y in 0..16 {
+            for x in 0..16 {
+                let inside = (2..14).contains(&x) && (2..14).contains(&y);
+                tex.pixels[y * 16 + x] = if inside { Color::new(128, 128, 128) } else { Color::new(40, 40, 40) };
+            }
+        }
+        tex
+    }
+
+    #[test]
+    fn test_flat_texture_is_unchanged() {
+        let mut flat = Texture::new(8, 8);
+        flat.pixels.fill(Color::new(100, 150, 200));
+        for source in [HeightSource::Emboss, HeightSource::Bevel] {
+            let bake = LightBake { source, ..LightBake::default() };
+            assert_eq!(bake_light(&flat, &bake).pixels, flat.pixels);
+        }
+    }
+
+    #[test]
+    fn test_bevel_lights_the_side_facing_the_light() {
+        let tex = tile();
+        let bake = LightBake { source: HeightSource::Bevel, direction: 0, strength: 1.0 };
+        let baked = bake_light(&tex, &bake);
+        // Lit from the top: the square's top flank brightens, its bottom one darkens
+        let top = baked.pixels[3 * 16 + 8].r;
+        let bottom = baked.pixels[12 * 16 + 8].r;
+        assert!(top > 128 && bottom < 128, "top {} bottom {}", top, bottom);
+        // The middle is flat
+        assert_eq!(baked.pixels[8 * 16 + 8], tex.pixels[8 * 16 + 8]);
+    }
+}
@@ -5,7 +5,7 @@ use std::ops::{Add, Sub, Mul};
 use serde::{Serialize, Deserialize};
 
 /// 3D Vector
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -87,7 +87,7 @@ impl Mul<f32> for Vec3 {
 }
 
 /// 2D Vector (for texture coordinates)
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -108,6 +108,24 @@ pub fn perspective_transform(v: Vec3, cam_x: Vec3, cam_y: Vec3, cam_z: Vec3) ->
     }
 }
 
+/// Project a world-space point to framebuffer coordinates (None if behind the camera)
+pub fn world_to_screen(
+    world_pos: Vec3,
+    camera_pos: Vec3,
+    basis_x: Vec3,
+    basis_y: Vec3,
+    basis_z: Vec3,
+    fb_width: usize,
+    fb_height: usize,
+) -> Option<(f32, f32)> {
+    let cam = perspective_transform(world_pos - camera_pos, basis_x, basis_y, basis_z);
+    if cam.z <= 0.1 {
+        return None;
+    }
+    let screen = project(cam, false, fb_width, fb_height);
+    Some((screen.x, screen.y))
+}
+
 /// Project a 3D point to 2D screen coordinates
 /// If `snap` is true, coordinates are floored to integers (PS1 jitter effect)
 /// Returns Vec3 where x,y are screen coords and z is the ORIGINAL camera-space depth
@@ -211,7 +229,7 @@ pub fn ray_triangle_intersect(
 }
 
 /// Generate a ray from screen coordinates through the camera
-/// Returns (ray_origin, ray_direction)
+/// Returns (ray_origin, ray_direction), the exact inverse of `project`
 /// screen_x, screen_y: pixel coordinates
 /// screen_width, screen_height: framebuffer dimensions
 /// camera: the camera to cast from
@@ -229,15 +247,17 @@ pub fn screen_to_ray(
     const DISTANCE: f32 = 5.0;
     const SCALE: f32 = 0.75;
 
+    let ud = DISTANCE;
+    let us = ud - 1.0;
     let vs = (screen_width.min(screen_height) as f32 / 2.0) * SCALE;
 
     // Convert screen coordinates to normalized device coordinates
     let ndc_x = (screen_x - screen_width as f32 / 2.0) / vs;
     let ndc_y = (screen_y - screen_height as f32 / 2.0) / vs;
 
-    // The ray direction in camera space
-    // At z=1 (unit distance in front of camera), the point would be at (ndc_x, ndc_y, 1)
-    let cam_space_dir = Vec3::new(ndc_x, ndc_y, 1.0).normalize();
+    // project() divides by (z + DISTANCE), so rays converge DISTANCE behind the camera:
+    // a camera-space point (x, y, z) lands on (ndc_x, ndc_y) when x = ndc_x * (z + ud) / us
+    let cam_space_dir = Vec3::new(ndc_x / us, ndc_y / us, 1.0).normalize();
 
     // Transform ray direction from camera space to world space
     let world_dir = Vec3::new(
@@ -246,7 +266,7 @@ pub fn screen_to_ray(
         cam_space_dir.x * cam_x.z + cam_space_dir.y * cam_y.z + cam_space_dir.z * cam_z.z,
     ).normalize();
 
-    (cam_pos, world_dir)
+    (cam_pos - cam_z.scale(ud), world_dir)
 }
 
 #[cfg(test)]
@@ -277,4 +297,16 @@ mod tests {
         let bc = barycentric(p, v1, v2, v3);
         assert!(bc.x >= 0.0 && bc.y >= 0.0 && bc.z >= 0.0);
     }
+
+    #[test]
+    fn test_screen_to_ray_inverts_project() {
+        let (x, y, z) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let point = Vec3::new(300.0, -150.0, 2000.0);
+        let screen = project(point, false, 320, 240);
+        let (origin, dir) = screen_to_ray(screen.x, screen.y, 320, 240, Vec3::ZERO, x, y, z);
+        // The ray passes through the original point
+        let t = (point.z - origin.z) / dir.z;
+        let on_ray = origin + dir.scale(t);
+        assert!((on_ray.x - point.x).abs() < 0.5 && (on_ray.y - point.y).abs() < 0.5);
+    }
 }
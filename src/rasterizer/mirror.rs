@@ -0,0 +1,121 @@
+//! Mirror pass - planar reflections drawn through a quad
+//!
+//! A mirror is a second render of the scene with the geometry reflected
+//! across the mirror plane, composited into the quad the mirror covers:
+//! - `reflect_mesh` mirrors positions and normals and flips the winding, so
+//!   backface culling still works; faces entirely behind the mirror are dropped
+//! - the reflected mesh goes through `render_mesh` into its own framebuffer
+//! - `composite_mirror` copies that into the frame inside the projected quad,
+//!   wherever the main pass has nothing in front of the mirror plane
+//!
+//! One bounce only: the reflection pass doesn't draw other mirrors.
+
+use super::{perspective_transform, Camera, Face, Framebuffer, Vec3, Vertex, world_to_screen};
+
+/// Reflect a point across the plane through `origin` with unit `normal`
+pub fn reflect_point(p: Vec3, origin: Vec3, normal: Vec3) -> Vec3 {
+    p - normal.scale(2.0 * (p - origin).dot(normal))
+}
+
+/// Mesh reflected across a plane (`normal` is unit length and points to the
+/// side that gets reflected)
+pub fn reflect_mesh(vertices: &[Vertex], faces: &[Face], origin: Vec3, normal: Vec3) -> (Vec<Vertex>, Vec<Face>) {
+    let reflected = vertices
+        .iter()
+        .map(|v| Vertex {
+            pos: reflect_point(v.pos, origin, normal),
+            normal: v.normal - normal.scale(2.0 * v.normal.dot(normal)),
+            ..*v
+        })
+        .collect();
+    // Geometry behind the mirror would come out in front of it
+    let in_front = |i: usize| (vertices[i].pos - origin).dot(normal) > -1.0;
+    let faces = faces
+        .iter()
+        .filter(|f| in_front(f.v0) || in_front(f.v1) || in_front(f.v2))
+        .map(|f| Face { v1: f.v2, v2: f.v1, ..*f })
+        .collect();
+    (reflected, faces)
+}
+
+/// Copy a reflection render into `fb` inside the mirror quad (world-space
+/// corners, in order around the quad). Pixels the main pass drew in front of
+/// the mirror are kept; covered pixels take the mirror's depth.
+pub fn composite_mirror(fb: &mut Framebuffer, reflection: &Framebuffer, quad: [Vec3; 4], normal: Vec3, camera: &Camera) {
+    if reflection.width != fb.width || reflection.height != fb.height {
+        return;
+    }
+    let (w, h) = (fb.width, fb.height);
+    let mut screen = [(0.0f32, 0.0f32); 4];
+    for (s, corner) in screen.iter_mut().zip(quad) {
+        // Mirrors that reach behind the camera are skipped rather than clipped
+        let Some(p) = world_to_screen(corner, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, w, h) else { return };
+        *s = p;
+    }
+    let min_x = screen.iter().map(|s| s.0).fold(f32::MAX, f32::min).max(0.0) as usize;
+    let max_x = (screen.iter().map(|s| s.0).fold(f32::MIN, f32::max).ceil().max(0.0) as usize).min(w);
+    let min_y = screen.iter().map(|s| s.1).fold(f32::MAX, f32::min).max(0.0) as usize;
+    let max_y = (screen.iter().map(|s| s.1).fold(f32::MIN, f32::max).ceil().max(0.0) as usize).min(h);
+
+    // Mirror plane in camera space
+    let to_camera = |v: Vec3| perspective_transform(v, camera.basis_x, camera.basis_y, camera.basis_z);
+    let n = to_camera(normal);
+    let d = n.dot(to_camera(quad[0] - camera.position));
+
+    // Same projection as the rasterizer
+    const SCALE: f32 = 0.75;
+    let vs = (w.min(h) as f32 / 2.0) * SCALE;
+    let ud = 5.0;
+    let us = ud - 1.0;
+
+    let edge = |a: (f32, f32), b: (f32, f32), p: (f32, f32)| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    for y in min_y..max_y {
+        if fb.field.is_some_and(|f| y % 2 != f) {
+            continue;
+        }
+        for x in min_x..max_x {
+            let p = (x as f32 + 0.5, y as f32 + 0.5);
+            let sides = [0, 1, 2, 3].map(|i| edge(screen[i], screen[(i + 1) % 4], p));
+            if !(sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0)) {
+                continue;
+            }
+            // Depth where the pixel's view ray meets the mirror plane
+            let a = (p.0 - w as f32 / 2.0) / (vs * us);
+            let b = (p.1 - h as f32 / 2.0) / (vs * us);
+            let slope = n.x * a + n.y * b;
+            if (slope + n.z).abs() < 1e-6 {
+                continue;
+            }
+            let z = (d - ud * slope) / (slope + n.z);
+            let idx = y * w + x;
+            if z <= 0.1 || fb.zbuffer[idx] <= z || reflection.zbuffer[idx] == f32::MAX {
+                continue;
+            }
+            fb.pixels[idx * 4..idx * 4 + 4].copy_from_slice(&reflection.pixels[idx * 4..idx * 4 + 4]);
+            fb.zbuffer[idx] = z;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec2;
+
+    #[test]
+    fn test_reflection_flips_winding_and_drops_faces_behind() {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let vertex = |x: f32, y: f32, z: f32| Vertex::new(Vec3::new(x, y, z), Vec2::new(0.0, 0.0), up);
+        let vertices = [
+            vertex(0.0, 100.0, 0.0), vertex(100.0, 100.0, 0.0), vertex(0.0, 100.0, 100.0),
+            vertex(0.0, -100.0, 0.0), vertex(100.0, -100.0, 0.0), vertex(0.0, -100.0, 100.0),
+        ];
+        let faces = [Face::new(0, 1, 2), Face::new(3, 4, 5)];
+        let (reflected, kept) = reflect_mesh(&vertices, &faces, Vec3::ZERO, up);
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!((kept[0].v0, kept[0].v1, kept[0].v2), (0, 2, 1));
+        assert_eq!(reflected[0].pos.y, -100.0);
+        assert_eq!(reflected[0].normal.y, -1.0);
+    }
+}
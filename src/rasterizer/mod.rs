@@ -6,14 +6,39 @@
 //! - Vertex snapping (integer coords = PS1 jitter)
 //! - Flat and Gouraud shading
 //! - Z-buffer or painter's algorithm
+//! - Optional fixed-point (20.12) pipeline
+//! - CRT post-processing (scanlines, curvature, color bleed)
+//! - Bitmap font for text drawn at native resolution
+//! - Mirror pass (reflected second render composited into a quad)
+//! - Texture atlas packing
+//! - Simulated PS1 VRAM layout (texture pages, CLUTs, framebuffers)
+//! - Indexed color (median-cut palettes, palette swaps between variants)
+//! - Baked directional lighting (emboss or bevel height, hand-shaded look)
 
 mod math;
 mod types;
 mod render;
+mod postfx;
+mod fixed;
+mod font;
+mod mirror;
+mod atlas;
+mod vram;
+mod palette;
+mod light_bake;
+#[cfg(feature = "simd")]
+mod simd;
 
 pub use math::*;
 pub use types::*;
 pub use render::*;
+pub use postfx::*;
+pub use font::*;
+pub use mirror::*;
+pub use atlas::*;
+pub use vram::*;
+pub use palette::*;
+pub use light_bake::*;
 
 /// Screen dimensions (authentic PS1 resolution)
 pub const WIDTH: usize = 320;
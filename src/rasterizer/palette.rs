@@ -0,0 +1,300 @@
+//! Indexed color - a texture as palette indices plus its CLUT
+//!
+//! PS1 textures are mostly 4-bit (16 colors) or 8-bit (256 colors) indices
+//! into a CLUT, which is how one set of pixels gets reused with different
+//! palettes (a red and a blue banner). `IndexedTexture::quantize` reduces a
+//! true-color texture with median cut at the PS1's 15-bit precision;
+//! `palette_from` reads a variant's colors through the same indices so the
+//! two can swap palettes.
+
+use std::collections::HashMap;
+use super::{Color, Texture};
+
+/// Round a color to 5 bits per channel (what VRAM stores); alpha becomes
+/// fully opaque or fully transparent
+pub fn to_15bit(c: Color) -> Color {
+    let q = |v: u8| (v >> 3) << 3 | v >> 5;
+    Color::with_alpha(q(c.r), q(c.g), q(c.b), if c.a >= 128 { 255 } else { 0 })
+}
+
+/// A texture as palette indices
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedTexture {
+    pub width: usize,
+    pub height: usize,
+    /// Palette entry of each pixel, row by row
+    pub indices: Vec<u8>,
+    /// The CLUT (up to 256 entries)
+    pub palette: Vec<Color>,
+}
+
+/// Colors of a median-cut box, with their pixel counts
+struct ColorBox(Vec<(Color, usize)>);
+
+impl ColorBox {
+    /// Widest channel and its range
+    fn widest(&self) -> (usize, u8) {
+        (0..3)
+            .map(|ch| {
+                let values = self.0.iter().map(|(c, _)| channel(*c, ch));
+                let (lo, hi) = values.fold((255, 0), |(lo, hi), v| (v.min(lo), v.max(hi)));
+                (ch, hi - lo)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0))
+    }
+
+    /// Count-weighted average
+    fn average(&self) -> Color {
+        let total: usize = self.0.iter().map(|(_, n)| n).sum::<usize>().max(1);
+        let mean = |ch| (self.0.iter().map(|(c, n)| channel(*c, ch) as usize * n).sum::<usize>() / total) as u8;
+        let alpha = self.0.first().map_or(255, |(c, _)| c.a);
+        to_15bit(Color::with_alpha(mean(0), mean(1), mean(2), alpha))
+    }
+}
+
+fn channel(c: Color, ch: usize) -> u8 {
+    match ch {
+        0 => c.r,
+        1 => c.g,
+        _ => c.b,
+    }
+}
+
+fn distance(a: Color, b: Color) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.r, b.r) + d(a.g, b.g) + d(a.b, b.b) + if a.a == b.a { 0 } else { 1 << 20 }
+}
+
+impl IndexedTexture {
+    /// Reduce a texture to at most `colors` palette entries (2..=256)
+    pub fn quantize(texture: &Texture, colors: usize) -> Self {
+        let colors = colors.clamp(2, 256);
+        let mut counts: HashMap<Color, usize> = HashMap::new();
+        for &c in &texture.pixels {
+            *counts.entry(to_15bit(c)).or_default() += 1;
+        }
+        let mut unique: Vec<(Color, usize)> = counts.into_iter().collect();
+        unique.sort_by_key(|(c, _)| (c.a, c.r, c.g, c.b));
+
+        let palette = if unique.len() <= colors {
+            unique.iter().map(|(c, _)| *c).collect()
+        } else {
+            // Transparent texels keep an entry of their own
+            let (clear, opaque): (Vec<_>, Vec<_>) = unique.into_iter().partition(|(c, _)| c.a == 0);
+            let mut palette: Vec<Color> = clear.first().map(|(c, _)| *c).into_iter().collect();
+            let mut boxes = vec![ColorBox(opaque)];
+            while palette.len() + boxes.len() < colors {
+                // Split the box with the widest channel range at its median pixel
+                let Some((i, (ch, range))) = boxes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| b.0.len() > 1)
+                    .map(|(i, b)| (i, b.widest()))
+                    .max_by_key(|&(_, (_, range))| range)
+                else {
+                    break;
+                };
+                if range == 0 {
+                    break;
+                }
+                let mut members = std::mem::take(&mut boxes[i].0);
+                members.sort_by_key(|(c, _)| channel(*c, ch));
+                let half = members.iter().map(|(_, n)| n).sum::<usize>() / 2;
+                let mut seen = 0;
+                let split = members
+                    .iter()
+                    .position(|(_, n)| {
+                        seen += n;
+                        seen > half
+                    })
+                    .unwrap_or(0)
+                    .clamp(1, members.len() - 1);
+                let upper = members.split_off(split);
+                boxes[i].0 = members;
+                boxes.push(ColorBox(upper));
+            }
+            palette.extend(boxes.iter().map(ColorBox::average));
+            palette
+        };
+
+        let mut indexed = Self { width: texture.width, height: texture.height, indices: Vec::new(), palette };
+        indexed.indices = indexed.map_pixels(texture);
+        indexed
+    }
+
+    /// Nearest palette entry of every pixel of a texture the same size
+    fn map_pixels(&self, texture: &Texture) -> Vec<u8> {
+        let mut nearest: HashMap<Color, u8> = HashMap::new();
+        texture
+            .pixels
+            .iter()
+            .map(|&c| {
+                let c = to_15bit(c);
+                *nearest.entry(c).or_insert_with(|| {
+                    (0..self.palette.len()).min_by_key(|&i| distance(c, self.palette[i])).unwrap_or(0) as u8
+                })
+            })
+            .collect()
+    }
+
+    /// The texture as true color again
+    pub fn to_texture(&self, name: &str) -> Texture {
+        let mut texture = Texture::new(self.width, self.height);
+        texture.name = name.to_string();
+        for (pixel, &i) in texture.pixels.iter_mut().zip(&self.indices) {
+            *pixel = self.palette.get(i as usize).copied().unwrap_or(Color::BLACK);
+        }
+        texture
+    }
+
+    /// Pixels using each palette entry
+    pub fn usage(&self) -> Vec<usize> {
+        let mut usage = vec![0; self.palette.len()];
+        for &i in &self.indices {
+            if let Some(n) = usage.get_mut(i as usize) {
+                *n += 1;
+            }
+        }
+        usage
+    }
+
+    /// Palette entry of the pixel at (x, y)
+    pub fn index_at(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.indices.get(y * self.width + x).copied()
+    }
+
+    /// Set the pixel at (x, y) to palette entry `index`. Returns true if it changed.
+    pub fn paint(&mut self, x: usize, y: usize, index: u8) -> bool {
+        if self.index_at(x, y).is_none_or(|old| old == index) {
+            return false;
+        }
+        self.indices[y * self.width + x] = index;
+        true
+    }
+
+    /// Set the pixels connected to (x, y) through the same palette entry
+    /// (4-way) to `index`. Returns true if anything changed.
+    pub fn fill(&mut self, x: usize, y: usize, index: u8) -> bool {
+        let Some(old) = self.index_at(x, y) else { return false };
+        if old == index {
+            return false;
+        }
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if !self.paint(x, y, index) {
+                continue;
+            }
+            if x > 0 && self.index_at(x - 1, y) == Some(old) {
+                stack.push((x - 1, y));
+            }
+            if self.index_at(x + 1, y) == Some(old) {
+                stack.push((x + 1, y));
+            }
+            if y > 0 && self.index_at(x, y - 1) == Some(old) {
+                stack.push((x, y - 1));
+            }
+            if self.index_at(x, y + 1) == Some(old) {
+                stack.push((x, y + 1));
+            }
+        }
+        true
+    }
+
+    /// Palette of a variant texture (same size, same pixels, other colors)
+    /// through these indices: each entry is the average color of the
+    /// variant's pixels that use it here. None if the sizes differ.
+    pub fn palette_from(&self, variant: &Texture) -> Option<Vec<Color>> {
+        if (variant.width, variant.height) != (self.width, self.height) {
+            return None;
+        }
+        let mut sums = vec![[0usize; 5]; self.palette.len()];
+        for (&i, &c) in self.indices.iter().zip(&variant.pixels) {
+            let s = &mut sums[i as usize];
+            s[0] += c.r as usize;
+            s[1] += c.g as usize;
+            s[2] += c.b as usize;
+            s[3] += c.a as usize;
+            s[4] += 1;
+        }
+        let palette = sums
+            .iter()
+            .zip(&self.palette)
+            .map(|(s, &own)| match s[4] {
+                0 => own,
+                n => to_15bit(Color::with_alpha((s[0] / n) as u8, (s[1] / n) as u8, (s[2] / n) as u8, (s[3] / n) as u8)),
+            })
+            .collect();
+        Some(palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Horizontal gradient with `steps` distinct colors
+    fn gradient(steps: usize) -> Texture {
+        let mut tex = Texture::new(64, 8);
+        for (i, p) in tex.pixels.iter_mut().enumerate() {
+            let v = ((i % 64) * steps / 64 * 255 / steps.max(2)) as u8;
+            *p = Color::new(v, 255 - v, 40);
+        }
+        tex
+    }
+
+    #[test]
+    fn test_few_colors_are_kept_exactly() {
+        let tex = gradient(8);
+        let indexed = IndexedTexture::quantize(&tex, 16);
+        assert_eq!(indexed.palette.len(), 8);
+        let back = indexed.to_texture("back");
+        for (a, b) in back.pixels.iter().zip(&tex.pixels) {
+            assert_eq!(*a, to_15bit(*b));
+        }
+        assert_eq!(indexed.usage().iter().sum::<usize>(), 64 * 8);
+    }
+
+    #[test]
+    fn test_median_cut_stays_close() {
+        let tex = gradient(64);
+        let indexed = IndexedTexture::quantize(&tex, 16);
+        assert_eq!(indexed.palette.len(), 16);
+        let back = indexed.to_texture("back");
+        let worst = back.pixels.iter().zip(&tex.pixels).map(|(a, b)| distance(*a, *b)).max().unwrap();
+        assert!(worst < 24 * 24 * 3, "worst error {}", worst);
+    }
+
+    #[test]
+    fn test_fill_stops_at_other_entries() {
+        let mut indexed = IndexedTexture { width: 4, height: 3, indices: vec![0; 12], palette: vec![Color::BLACK, Color::WHITE, Color::new(255, 0, 0)] };
+        // A wall of entry 1 down column 2
+        for y in 0..3 {
+            assert!(indexed.paint(2, y, 1));
+        }
+        assert!(!indexed.paint(2, 0, 1));
+        assert!(indexed.fill(0, 0, 2));
+        assert_eq!(indexed.indices, vec![2, 2, 1, 0, 2, 2, 1, 0, 2, 2, 1, 0]);
+        assert!(!indexed.fill(1, 1, 2));
+        assert!(!indexed.paint(4, 0, 1));
+    }
+
+    #[test]
+    fn test_variant_palette_swaps_colors() {
+        let red = gradient(4);
+        let mut blue = red.clone();
+        for p in &mut blue.pixels {
+            *p = Color::new(p.b, p.g, p.r);
+        }
+        let indexed = IndexedTexture::quantize(&red, 16);
+        let palette = indexed.palette_from(&blue).unwrap();
+        let swapped = IndexedTexture { palette, ..indexed.clone() }.to_texture("blue");
+        for (a, b) in swapped.pixels.iter().zip(&blue.pixels) {
+            assert_eq!(*a, to_15bit(*b));
+        }
+        assert!(indexed.palette_from(&Texture::new(8, 8)).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Post-processing applied to the finished framebuffer at blit time
+//!
+//! CRT filter stack (software pass, applied in this order):
+//! - Barrel distortion (screen curvature)
+//! - Chromatic aberration (R/B channels offset towards the edges)
+//! - Composite blur (horizontal color bleed)
+//! - Scanlines (every other output row darkened)
+//!
+//! 240-line framebuffers are doubled vertically so scanlines fall between
+//! source lines, like a real CRT showing a 240p signal.
+//!
+//! Color grading (gamma, contrast, saturation, tint) is set per level for
+//! cheap mood control. It runs on the pixels handed to the display, never on
+//! the framebuffer itself (interlacing keeps last frame's lines around).
+
+use serde::{Deserialize, Serialize};
+use super::Framebuffer;
+
+/// CRT filter settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrtSettings {
+    pub enabled: bool,
+    /// Scanline darkening (0.0 = none, 1.0 = black gaps)
+    pub scanlines: f32,
+    /// Barrel distortion strength (0.0 = flat)
+    pub curvature: f32,
+    /// Channel offset at the screen edge, in source pixels
+    pub chromatic: f32,
+    /// Horizontal color bleed (0.0 - 1.0)
+    pub blur: f32,
+}
+
+impl Default for CrtSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scanlines: 0.35,
+            curvature: 0.08,
+            chromatic: 0.8,
+            blur: 0.5,
+        }
+    }
+}
+
+/// Color grading stored with a level ("ambience")
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorGrade {
+    /// Above 1.0 brightens midtones, below darkens them
+    pub gamma: f32,
+    /// Stretch around mid-grey (1.0 = unchanged)
+    pub contrast: f32,
+    /// 0.0 = greyscale, 1.0 = unchanged, above boosts color
+    pub saturation: f32,
+    /// Per-channel RGB multiplier (1.0 = neutral)
+    pub tint: [f32; 3],
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        Self {
+            gamma: 1.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            tint: [1.0; 3],
+        }
+    }
+}
+
+impl ColorGrade {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Gamma, contrast and tint only depend on the channel's own value,
+    /// so they bake into one 256-entry table per channel
+    fn channel_tables(&self) -> [[u8; 256]; 3] {
+        let inv_gamma = 1.0 / self.gamma.max(0.01);
+        let mut tables = [[0u8; 256]; 3];
+        for (channel, table) in tables.iter_mut().enumerate() {
+            for (i, out) in table.iter_mut().enumerate() {
+                let v = (i as f32 / 255.0).powf(inv_gamma);
+                let v = (v - 0.5) * self.contrast.max(0.0) + 0.5;
+                *out = (v * self.tint[channel].max(0.0) * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+        tables
+    }
+}
+
+/// Apply a color grade to RGBA pixels in place
+pub fn apply_color_grade(pixels: &mut [u8], grade: &ColorGrade) {
+    if grade.is_identity() {
+        return;
+    }
+    let tables = grade.channel_tables();
+    let saturation = grade.saturation.max(0.0);
+    for px in pixels.chunks_exact_mut(4) {
+        let rgb = [tables[0][px[0] as usize], tables[1][px[1] as usize], tables[2][px[2] as usize]];
+        if saturation == 1.0 {
+            px[..3].copy_from_slice(&rgb);
+            continue;
+        }
+        let luma = 0.299 * rgb[0] as f32 + 0.587 * rgb[1] as f32 + 0.114 * rgb[2] as f32;
+        for (out, c) in px.iter_mut().zip(rgb) {
+            *out = (luma + (c as f32 - luma) * saturation).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Sample one channel with clamped coordinates
+#[inline]
+fn sample(fb: &Framebuffer, x: f32, y: f32, channel: usize) -> f32 {
+    let xi = (x as i32).clamp(0, fb.width as i32 - 1) as usize;
+    let yi = (y as i32).clamp(0, fb.height as i32 - 1) as usize;
+    fb.pixels[(yi * fb.width + xi) * 4 + channel] as f32
+}
+
+/// Apply the CRT stack, returning RGBA pixels and their dimensions
+pub fn apply_crt(fb: &Framebuffer, settings: &CrtSettings) -> (Vec<u8>, usize, usize) {
+    let scale_y = if fb.height <= 240 { 2 } else { 1 };
+    let out_w = fb.width;
+    let out_h = fb.height * scale_y;
+    let mut out = vec![0u8; out_w * out_h * 4];
+
+    let blur = settings.blur.clamp(0.0, 1.0) * 0.5;
+    let scanline = 1.0 - settings.scanlines.clamp(0.0, 1.0);
+
+    for oy in 0..out_h {
+        // Normalized coordinates (-1..1) from the pixel center
+        let ny = (oy as f32 + 0.5) / out_h as f32 * 2.0 - 1.0;
+        let row_dim = if oy % 2 == 1 { scanline } else { 1.0 };
+
+        for ox in 0..out_w {
+            let nx = (ox as f32 + 0.5) / out_w as f32 * 2.0 - 1.0;
+
+            // Barrel distortion: push samples outwards with distance from center
+            let r2 = nx * nx + ny * ny;
+            let warp = 1.0 + settings.curvature * r2;
+            let (dx, dy) = (nx * warp, ny * warp);
+            let idx = (oy * out_w + ox) * 4;
+            if dx.abs() > 1.0 || dy.abs() > 1.0 {
+                out[idx + 3] = 255;
+                continue;
+            }
+
+            let sx = (dx + 1.0) * 0.5 * fb.width as f32;
+            let sy = (dy + 1.0) * 0.5 * fb.height as f32;
+            let fringe = settings.chromatic * dx;
+
+            for (channel, offset) in [(0, fringe), (1, 0.0), (2, -fringe)] {
+                let x = sx + offset;
+                let center = sample(fb, x, sy, channel);
+                let bleed = (sample(fb, x - 1.0, sy, channel) + sample(fb, x + 1.0, sy, channel)) * 0.5;
+                let value = center * (1.0 - blur) + bleed * blur;
+                out[idx + channel] = (value * row_dim).clamp(0.0, 255.0) as u8;
+            }
+            out[idx + 3] = 255;
+        }
+    }
+
+    (out, out_w, out_h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Color;
+
+    #[test]
+    fn test_low_res_doubles_lines() {
+        let fb = Framebuffer::new(320, 240);
+        let (pixels, w, h) = apply_crt(&fb, &CrtSettings::default());
+        assert_eq!((w, h), (320, 480));
+        assert_eq!(pixels.len(), 320 * 480 * 4);
+    }
+
+    #[test]
+    fn test_flat_settings_preserve_center() {
+        let mut fb = Framebuffer::new(64, 480);
+        fb.clear(Color::new(200, 100, 50));
+        let flat = CrtSettings { enabled: true, scanlines: 0.0, curvature: 0.0, chromatic: 0.0, blur: 0.0 };
+        let (pixels, w, h) = apply_crt(&fb, &flat);
+        let idx = ((h / 2) * w + w / 2) * 4;
+        assert_eq!(&pixels[idx..idx + 4], &[200, 100, 50, 255]);
+    }
+
+    #[test]
+    fn test_color_grade() {
+        let mut fb = Framebuffer::new(4, 4);
+        fb.clear(Color::new(200, 100, 50));
+        apply_color_grade(&mut fb.pixels, &ColorGrade::default());
+        assert_eq!(&fb.pixels[..4], &[200, 100, 50, 255]);
+
+        // No saturation leaves grey; a red tint only scales red
+        let grey = ColorGrade { saturation: 0.0, ..Default::default() };
+        apply_color_grade(&mut fb.pixels, &grey);
+        assert!(fb.pixels[0] == fb.pixels[1] && fb.pixels[1] == fb.pixels[2]);
+        let value = fb.pixels[0];
+        apply_color_grade(&mut fb.pixels, &ColorGrade { tint: [0.5, 1.0, 1.0], ..Default::default() });
+        assert_eq!(fb.pixels[0], (value as f32 * 0.5).round() as u8);
+        assert_eq!(fb.pixels[1], value);
+    }
+}
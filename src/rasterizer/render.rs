@@ -1,8 +1,11 @@
 //! Core rendering functions
 //! Triangle rasterization with PS1-style effects
 
-use super::math::{barycentric, perspective_transform, project, Vec3};
+use super::math::{barycentric, perspective_transform, project, world_to_screen, Vec3};
+use super::fixed::{barycentric_fixed, interpolate_fixed, interpolate_uv_fixed, project_fixed, transform_fixed};
 use super::types::{BlendMode, Color, Face, RasterSettings, ShadingMode, Texture, Vertex};
+#[cfg(feature = "simd")]
+use super::simd::{coverage4, interpolate4, texel_index4, AttributeSetup, SpanSetup};
 
 /// Framebuffer for software rendering
 pub struct Framebuffer {
@@ -10,8 +13,19 @@ pub struct Framebuffer {
     pub zbuffer: Vec<f32>,  // Depth buffer
     pub width: usize,
     pub height: usize,
+    /// Field being rendered in interlaced mode (0 = even lines, 1 = odd lines)
+    pub field: Option<usize>,
+    /// Field rendered last frame (alternates every frame)
+    field_parity: usize,
+    /// Optional ID buffer: which triangle is visible at each pixel (see `enable_picking`)
+    pub pick_ids: Option<Vec<u32>>,
+    /// Added to face indices written to `pick_ids` (lets several meshes share one buffer)
+    pub pick_base: u32,
 }
 
+/// `pick_ids` value for pixels no triangle covers
+pub const NO_PICK: u32 = u32::MAX;
+
 impl Framebuffer {
     pub fn new(width: usize, height: usize) -> Self {
         Self {
@@ -19,6 +33,75 @@ impl Framebuffer {
             zbuffer: vec![f32::MAX; width * height],
             width,
             height,
+            field: None,
+            field_parity: 0,
+            pick_ids: None,
+            pick_base: 0,
+        }
+    }
+
+    /// Turn the ID buffer on or off. While on, every triangle pixel that wins
+    /// the depth test also records `pick_base + face index`, so picking is a
+    /// single lookup and respects occlusion.
+    pub fn enable_picking(&mut self, enabled: bool) {
+        if enabled != self.pick_ids.is_some() {
+            self.pick_ids = enabled.then(|| vec![NO_PICK; self.width * self.height]);
+        }
+    }
+
+    /// ID of the triangle visible at a pixel
+    pub fn pick_id(&self, x: usize, y: usize) -> Option<u32> {
+        let ids = self.pick_ids.as_ref()?;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(ids[y * self.width + x]).filter(|&id| id != NO_PICK)
+    }
+
+    /// Distinct IDs within `radius` pixels of (x, y), nearest first
+    pub fn pick_ids_near(&self, x: usize, y: usize, radius: usize) -> Vec<u32> {
+        let Some(ids) = &self.pick_ids else { return Vec::new() };
+        let mut found: Vec<(usize, u32)> = Vec::new();
+        for py in y.saturating_sub(radius)..(y + radius + 1).min(self.height) {
+            for px in x.saturating_sub(radius)..(x + radius + 1).min(self.width) {
+                let id = ids[py * self.width + px];
+                if id == NO_PICK {
+                    continue;
+                }
+                let dist = px.abs_diff(x).pow(2) + py.abs_diff(y).pow(2);
+                match found.iter_mut().find(|(_, f)| *f == id) {
+                    Some(entry) => entry.0 = entry.0.min(dist),
+                    None => found.push((dist, id)),
+                }
+            }
+        }
+        found.sort();
+        found.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Start a frame. In interlaced mode only the next field's lines are
+    /// cleared and rasterized; the other field keeps last frame's image.
+    pub fn begin_field(&mut self, interlaced: bool) {
+        if interlaced {
+            self.field_parity ^= 1;
+            self.field = Some(self.field_parity);
+        } else {
+            self.field = None;
+        }
+    }
+
+    /// Finish an interlaced frame. With combing the fields are woven together
+    /// (moving edges comb like 480i on a progressive display); without it the
+    /// fresh field is line-doubled over the stale one.
+    pub fn finish_field(&mut self, combing: bool) {
+        let Some(field) = self.field.take() else { return };
+        if combing {
+            return;
+        }
+        let row = self.width * 4;
+        for y in (field..self.height).step_by(2) {
+            let target = if y + 1 < self.height { y + 1 } else if y > 0 { y - 1 } else { continue };
+            self.pixels.copy_within(y * row..(y + 1) * row, target * row);
         }
     }
 
@@ -28,17 +111,26 @@ impl Framebuffer {
             self.height = height;
             self.pixels = vec![0; width * height * 4];
             self.zbuffer = vec![f32::MAX; width * height];
+            if self.pick_ids.is_some() {
+                self.pick_ids = Some(vec![NO_PICK; width * height]);
+            }
         }
     }
 
     pub fn clear(&mut self, color: Color) {
         for i in 0..(self.width * self.height) {
+            if self.field.is_some_and(|f| (i / self.width) % 2 != f) {
+                continue;
+            }
             let bytes = color.to_bytes();
             self.pixels[i * 4] = bytes[0];
             self.pixels[i * 4 + 1] = bytes[1];
             self.pixels[i * 4 + 2] = bytes[2];
             self.pixels[i * 4 + 3] = bytes[3];
             self.zbuffer[i] = f32::MAX;
+            if let Some(ids) = &mut self.pick_ids {
+                ids[i] = NO_PICK;
+            }
         }
     }
 
@@ -236,6 +328,36 @@ impl Framebuffer {
             }
         }
     }
+
+    /// Draw a world-space line, clipped against the camera near plane
+    pub fn draw_line_3d(&mut self, p0: Vec3, p1: Vec3, camera: &Camera, color: Color) {
+        const NEAR_PLANE: f32 = 0.1;
+
+        let z0 = (p0 - camera.position).dot(camera.basis_z);
+        let z1 = (p1 - camera.position).dot(camera.basis_z);
+
+        // Both behind camera - skip entirely
+        if z0 <= NEAR_PLANE && z1 <= NEAR_PLANE {
+            return;
+        }
+
+        // Clip line to near plane if needed
+        let (p0, p1) = if z0 <= NEAR_PLANE {
+            let t = (NEAR_PLANE - z0) / (z1 - z0);
+            (p0 + (p1 - p0) * t, p1)
+        } else if z1 <= NEAR_PLANE {
+            let t = (NEAR_PLANE - z0) / (z1 - z0);
+            (p0, p0 + (p1 - p0) * t)
+        } else {
+            (p0, p1)
+        };
+
+        let s0 = world_to_screen(p0, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, self.width, self.height);
+        let s1 = world_to_screen(p1, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, self.width, self.height);
+        if let (Some((x0, y0)), Some((x1, y1))) = (s0, s1) {
+            self.draw_line(x0 as i32, y0 as i32, x1 as i32, y1 as i32, color);
+        }
+    }
 }
 
 /// Camera state
@@ -281,6 +403,14 @@ impl Camera {
         self.basis_y = self.basis_z.cross(self.basis_x);
     }
 
+    /// Point the camera at a world-space target
+    pub fn look_at(&mut self, target: Vec3) {
+        let dir = (target - self.position).normalize();
+        self.rotation_x = (-dir.y).clamp(-0.999, 0.999).asin();
+        self.rotation_y = dir.x.atan2(dir.z);
+        self.update_basis();
+    }
+
     pub fn rotate(&mut self, dx: f32, dy: f32) {
         self.rotation_y += dy;
         self.rotation_x = (self.rotation_x + dx).clamp(
@@ -347,6 +477,97 @@ fn apply_dither(color: Color, x: usize, y: usize) -> Color {
     Color::with_alpha(r, g, b, color.a)
 }
 
+/// Shade and write one covered pixel
+#[inline]
+fn shade_pixel(
+    fb: &mut Framebuffer,
+    surface: &Surface,
+    texture: Option<&Texture>,
+    settings: &RasterSettings,
+    (x, y): (usize, usize),
+    bc: Vec3,
+    flat_shade: f32,
+) {
+    // Interpolate depth
+    let z = if settings.fixed_point {
+        interpolate_fixed(bc, surface.v1.z, surface.v2.z, surface.v3.z)
+    } else {
+        bc.x * surface.v1.z + bc.y * surface.v2.z + bc.z * surface.v3.z
+    };
+
+    // Z-buffer test
+    if settings.use_zbuffer {
+        let idx = y * fb.width + x;
+        if z >= fb.zbuffer[idx] {
+            return;
+        }
+    }
+
+    // Interpolate UV coordinates
+    let (u, v) = if settings.affine_textures && settings.fixed_point {
+        interpolate_uv_fixed(bc, surface.uv1, surface.uv2, surface.uv3)
+    } else if settings.affine_textures {
+        // Affine (PS1 style) - linear interpolation
+        let u = bc.x * surface.uv1.x + bc.y * surface.uv2.x + bc.z * surface.uv3.x;
+        let v = bc.x * surface.uv1.y + bc.y * surface.uv2.y + bc.z * surface.uv3.y;
+        (u, v)
+    } else {
+        // Perspective-correct interpolation
+        let mut bcc = bc;
+        bcc.x = bc.x / surface.v1.z;
+        bcc.y = bc.y / surface.v2.z;
+        bcc.z = bc.z / surface.v3.z;
+        let bd = bcc.x + bcc.y + bcc.z;
+        bcc.x /= bd;
+        bcc.y /= bd;
+        bcc.z /= bd;
+
+        let u = bcc.x * surface.uv1.x + bcc.y * surface.uv2.x + bcc.z * surface.uv3.x;
+        let v = bcc.x * surface.uv1.y + bcc.y * surface.uv2.y + bcc.z * surface.uv3.y;
+        (u, v)
+    };
+
+    // Sample texture or use white
+    let mut color = if let Some(tex) = texture {
+        tex.sample(u, 1.0 - v)
+    } else {
+        Color::WHITE
+    };
+
+    // Apply shading
+    let shade = match settings.shading {
+        ShadingMode::None => 1.0,
+        ShadingMode::Flat => flat_shade,
+        ShadingMode::Gouraud => {
+            // Interpolate per-vertex shading
+            let s1 = shade_intensity(surface.vn1, settings.light_dir, settings.ambient);
+            let s2 = shade_intensity(surface.vn2, settings.light_dir, settings.ambient);
+            let s3 = shade_intensity(surface.vn3, settings.light_dir, settings.ambient);
+            bc.x * s1 + bc.y * s2 + bc.z * s3
+        }
+    };
+
+    color = color.shade(shade);
+
+    // Apply PS1-style ordered dithering
+    if settings.dithering {
+        color = apply_dither(color, x, y);
+    }
+
+    write_pixel(fb, surface, (x, y), z, color);
+}
+
+/// Depth-tested pixel write (and its triangle ID when picking)
+#[inline]
+fn write_pixel(fb: &mut Framebuffer, surface: &Surface, (x, y): (usize, usize), z: f32, color: Color) {
+    if fb.set_pixel_with_depth(x, y, z, color) {
+        let id = fb.pick_base + surface.face_idx as u32;
+        if let Some(ids) = &mut fb.pick_ids {
+            ids[y * fb.width + x] = id;
+        }
+    }
+}
+
 /// Rasterize a single triangle
 fn rasterize_triangle(
     fb: &mut Framebuffer,
@@ -367,78 +588,115 @@ fn rasterize_triangle(
         1.0
     };
 
-    // Rasterize
+    // The fixed-point pipeline always takes the scalar path
+    #[cfg(feature = "simd")]
+    if !settings.fixed_point {
+        rasterize_spans_simd(fb, surface, texture, settings, (min_x, max_x, min_y, max_y), flat_shade);
+        return;
+    }
+
+    // Rasterize (only the current field's lines when interlaced)
+    let field = fb.field;
     for y in min_y..max_y {
+        if field.is_some_and(|f| y % 2 != f) {
+            continue;
+        }
         for x in min_x..max_x {
-            let p = Vec3::new(x as f32, y as f32, 0.0);
-            let bc = barycentric(p, surface.v1, surface.v2, surface.v3);
+            let bc = if settings.fixed_point {
+                barycentric_fixed(x as i32, y as i32, surface.v1, surface.v2, surface.v3)
+            } else {
+                barycentric(Vec3::new(x as f32, y as f32, 0.0), surface.v1, surface.v2, surface.v3)
+            };
 
             // Check if inside triangle
-            const ERR: f32 = -0.0001;
-            if bc.x >= ERR && bc.y >= ERR && bc.z >= ERR {
-                // Interpolate depth
-                let z = bc.x * surface.v1.z + bc.y * surface.v2.z + bc.z * surface.v3.z;
-
-                // Z-buffer test
-                if settings.use_zbuffer {
-                    let idx = y * fb.width + x;
-                    if z >= fb.zbuffer[idx] {
-                        continue;
-                    }
-                }
+            if bc.x >= COVERAGE_EPSILON && bc.y >= COVERAGE_EPSILON && bc.z >= COVERAGE_EPSILON {
+                shade_pixel(fb, surface, texture, settings, (x, y), bc, flat_shade);
+            }
+        }
+    }
+}
 
-                // Interpolate UV coordinates
-                let (u, v) = if settings.affine_textures {
-                    // Affine (PS1 style) - linear interpolation
-                    let u = bc.x * surface.uv1.x + bc.y * surface.uv2.x + bc.z * surface.uv3.x;
-                    let v = bc.x * surface.uv1.y + bc.y * surface.uv2.y + bc.z * surface.uv3.y;
-                    (u, v)
-                } else {
-                    // Perspective-correct interpolation
-                    let mut bcc = bc;
-                    bcc.x = bc.x / surface.v1.z;
-                    bcc.y = bc.y / surface.v2.z;
-                    bcc.z = bc.z / surface.v3.z;
-                    let bd = bcc.x + bcc.y + bcc.z;
-                    bcc.x /= bd;
-                    bcc.y /= bd;
-                    bcc.z /= bd;
-
-                    let u = bcc.x * surface.uv1.x + bcc.y * surface.uv2.x + bcc.z * surface.uv3.x;
-                    let v = bcc.x * surface.uv1.y + bcc.y * surface.uv2.y + bcc.z * surface.uv3.y;
-                    (u, v)
-                };
+/// Barycentric inside-test tolerance (keeps shared edges crack-free)
+const COVERAGE_EPSILON: f32 = -0.0001;
+
+/// Attribute planes matching `shade_pixel` (v is flipped like `tex.sample(u, 1.0 - v)`)
+#[cfg(feature = "simd")]
+fn span_attributes(setup: &SpanSetup, surface: &Surface, settings: &RasterSettings, flat_shade: f32) -> AttributeSetup {
+    let (v1, v2, v3) = (surface.v1, surface.v2, surface.v3);
+    let (uv1, uv2, uv3) = (surface.uv1, surface.uv2, surface.uv3);
+    // Affine mapping divides by a constant 1; perspective by the 1/z plane
+    let w = if settings.affine_textures {
+        [1.0; 3]
+    } else {
+        [1.0 / v1.z, 1.0 / v2.z, 1.0 / v3.z]
+    };
+    let shade = match settings.shading {
+        ShadingMode::None => [0.0, 0.0, 1.0],
+        ShadingMode::Flat => [0.0, 0.0, flat_shade],
+        ShadingMode::Gouraud => setup.plane(
+            shade_intensity(surface.vn1, settings.light_dir, settings.ambient),
+            shade_intensity(surface.vn2, settings.light_dir, settings.ambient),
+            shade_intensity(surface.vn3, settings.light_dir, settings.ambient),
+        ),
+    };
+    AttributeSetup {
+        z: setup.z,
+        u: setup.plane(uv1.x * w[0], uv2.x * w[1], uv3.x * w[2]),
+        v: setup.plane((1.0 - uv1.y) * w[0], (1.0 - uv2.y) * w[1], (1.0 - uv3.y) * w[2]),
+        q: setup.plane(w[0], w[1], w[2]),
+        shade,
+    }
+}
 
-                // Sample texture or use white
-                let mut color = if let Some(tex) = texture {
-                    tex.sample(u, 1.0 - v)
-                } else {
-                    Color::WHITE
-                };
+/// SIMD span filling: coverage, depth, texture coordinates, shading and
+/// texel addresses are computed 4 pixels at a time. Texel loads, dithering
+/// and writes are per covered pixel.
+#[cfg(feature = "simd")]
+fn rasterize_spans_simd(
+    fb: &mut Framebuffer,
+    surface: &Surface,
+    texture: Option<&Texture>,
+    settings: &RasterSettings,
+    (min_x, max_x, min_y, max_y): (usize, usize, usize, usize),
+    flat_shade: f32,
+) {
+    let Some(setup) = SpanSetup::new(surface.v1, surface.v2, surface.v3) else { return };
+    let attributes = span_attributes(&setup, surface, settings, flat_shade);
 
-                // Apply shading
-                let shade = match settings.shading {
-                    ShadingMode::None => 1.0,
-                    ShadingMode::Flat => flat_shade,
-                    ShadingMode::Gouraud => {
-                        // Interpolate per-vertex shading
-                        let s1 = shade_intensity(surface.vn1, settings.light_dir, settings.ambient);
-                        let s2 = shade_intensity(surface.vn2, settings.light_dir, settings.ambient);
-                        let s3 = shade_intensity(surface.vn3, settings.light_dir, settings.ambient);
-                        bc.x * s1 + bc.y * s2 + bc.z * s3
+    let field = fb.field;
+    for y in min_y..max_y {
+        if field.is_some_and(|f| y % 2 != f) {
+            continue;
+        }
+        let row = y * fb.width;
+        let mut x = min_x;
+        while x < max_x {
+            // Lanes past the end of the span are never shaded
+            let lanes = (max_x - x).min(4);
+            let mut depth = [f32::MAX; 4];
+            depth[..lanes].copy_from_slice(&fb.zbuffer[row + x..row + x + lanes]);
+
+            let mask = coverage4(&setup, x as f32, y as f32, &depth, settings.use_zbuffer, COVERAGE_EPSILON);
+            if mask != 0 {
+                let attr = interpolate4(&attributes, x as f32, y as f32);
+                let texels = texture.map(|tex| (tex, texel_index4(&attr.u, &attr.v, tex.width, tex.height)));
+                for lane in 0..lanes {
+                    if mask & (1 << lane) == 0 {
+                        continue;
                     }
-                };
-
-                color = color.shade(shade);
-
-                // Apply PS1-style ordered dithering
-                if settings.dithering {
-                    color = apply_dither(color, x, y);
+                    let px = x + lane;
+                    let mut color = match texels {
+                        Some((tex, idx)) => tex.pixels[idx[lane]],
+                        None => Color::WHITE,
+                    };
+                    color = color.shade(attr.shade[lane]);
+                    if settings.dithering {
+                        color = apply_dither(color, px, y);
+                    }
+                    write_pixel(fb, surface, (px, y), attr.z[lane], color);
                 }
-
-                // Write pixel
-                fb.set_pixel_with_depth(x, y, z, color);
             }
+            x += 4;
         }
     }
 }
@@ -457,14 +715,18 @@ pub fn render_mesh(
     let mut cam_space_positions: Vec<Vec3> = Vec::with_capacity(vertices.len());
     let mut cam_space_normals: Vec<Vec3> = Vec::with_capacity(vertices.len());
 
+    let basis = [camera.basis_x, camera.basis_y, camera.basis_z];
     for v in vertices {
-        // Transform position to camera space
-        let rel_pos = v.pos - camera.position;
-        let cam_pos = perspective_transform(rel_pos, camera.basis_x, camera.basis_y, camera.basis_z);
+        // Transform position to camera space and project to screen
+        let (cam_pos, screen_pos) = if settings.fixed_point {
+            let fixed_pos = transform_fixed(v.pos, camera.position, basis);
+            (fixed_pos.to_vec3(), project_fixed(fixed_pos, settings.vertex_snap, fb.width, fb.height))
+        } else {
+            let rel_pos = v.pos - camera.position;
+            let cam_pos = perspective_transform(rel_pos, camera.basis_x, camera.basis_y, camera.basis_z);
+            (cam_pos, project(cam_pos, settings.vertex_snap, fb.width, fb.height))
+        };
         cam_space_positions.push(cam_pos);
-
-        // Project to screen
-        let screen_pos = project(cam_pos, settings.vertex_snap, fb.width, fb.height);
         projected.push(screen_pos);
 
         // Transform normal to camera space
@@ -523,10 +785,13 @@ pub fn render_mesh(
 
         if is_backface {
             // Back-face: collect for wireframe rendering (always, regardless of backface_cull setting)
-            backface_wireframes.push((v1, v2, v3));
+            // Double-sided faces are solid from behind, so they skip the wireframe
+            if !face.double_sided {
+                backface_wireframes.push((v1, v2, v3));
+            }
 
-            // If backface culling is disabled, also render as solid
-            if !settings.backface_cull {
+            // If backface culling is disabled (or the face is double-sided), also render as solid
+            if !settings.backface_cull || face.double_sided {
                 surfaces.push(Surface {
                     v1,
                     v2,
@@ -691,3 +956,109 @@ pub fn create_test_cube() -> (Vec<Vertex>, Vec<Face>) {
 
     (vertices, faces)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::math::Vec2;
+
+    fn quad_facing_camera(vertices: &mut Vec<Vertex>, faces: &mut Vec<Face>, half: f32, z: f32) {
+        let base = vertices.len();
+        let normal = Vec3::new(0.0, 0.0, -1.0);
+        for (x, y) in [(-half, -half), (half, -half), (half, half), (-half, half)] {
+            vertices.push(Vertex::new(Vec3::new(x, y, z), Vec2::new(0.0, 0.0), normal));
+        }
+        faces.push(Face::new(base, base + 1, base + 2));
+        faces.push(Face::new(base, base + 2, base + 3));
+    }
+
+    #[test]
+    fn test_pick_ids_respect_depth() {
+        let mut fb = Framebuffer::new(64, 48);
+        fb.enable_picking(true);
+        fb.clear(Color::new(0, 0, 0));
+        fb.pick_base = 10;
+
+        // Big quad far away (faces 0-1), small quad in front of it (faces 2-3)
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        quad_facing_camera(&mut vertices, &mut faces, 5000.0, 1000.0);
+        quad_facing_camera(&mut vertices, &mut faces, 20.0, 500.0);
+        render_mesh(&mut fb, &vertices, &faces, &[], &Camera::new(), &RasterSettings::default());
+
+        let center = fb.pick_id(32, 24).unwrap();
+        assert!(center == 12 || center == 13, "near quad should win, got {}", center);
+        let corner = fb.pick_id(1, 1).unwrap();
+        assert!(corner == 10 || corner == 11, "far quad should show at the corner, got {}", corner);
+        assert_eq!(fb.pick_ids_near(32, 24, 0), vec![center]);
+
+        fb.clear(Color::new(0, 0, 0));
+        assert_eq!(fb.pick_id(32, 24), None);
+        fb.enable_picking(false);
+        assert!(fb.pick_ids_near(32, 24, 4).is_empty());
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_spans_match_scalar() {
+        // Neighbouring texels (across the wrap too) differ by 2 per channel,
+        // so a sample rounded into the next texel stays within the tolerance
+        let ramp = |t: usize, size: usize| (2 * t.min(size - t)) as u8;
+        let mut tex = Texture::new(16, 8);
+        for ty in 0..8 {
+            for tx in 0..16 {
+                tex.pixels[ty * 16 + tx] = Color::new(ramp(tx, 16), 64 + ramp(ty, 8), 200);
+            }
+        }
+        let surface = Surface {
+            v1: Vec3::new(3.0, 2.0, 400.0),
+            v2: Vec3::new(60.0, 10.0, 900.0),
+            v3: Vec3::new(20.0, 45.0, 250.0),
+            vn1: Vec3::new(0.0, 0.0, -1.0),
+            vn2: Vec3::new(-0.6, 0.0, -0.8),
+            vn3: Vec3::new(0.0, -0.6, -0.8),
+            uv1: Vec2::new(0.0, 0.0),
+            uv2: Vec2::new(3.0, 0.5),
+            uv3: Vec2::new(-0.5, 2.0),
+            normal: Vec3::new(0.0, 0.0, -1.0),
+            face_idx: 0,
+        };
+
+        for affine_textures in [true, false] {
+            // Dithering quantizes to 5 bits, which would blow up a 1-step difference
+            let settings = RasterSettings { affine_textures, dithering: false, ..RasterSettings::default() };
+            let mut simd = Framebuffer::new(64, 48);
+            let mut scalar = Framebuffer::new(64, 48);
+            simd.clear(Color::BLACK);
+            scalar.clear(Color::BLACK);
+
+            rasterize_spans_simd(&mut simd, &surface, Some(&tex), &settings, (0, 64, 0, 48), 1.0);
+            for y in 0..48 {
+                for x in 0..64 {
+                    let bc = barycentric(Vec3::new(x as f32, y as f32, 0.0), surface.v1, surface.v2, surface.v3);
+                    if bc.x >= COVERAGE_EPSILON && bc.y >= COVERAGE_EPSILON && bc.z >= COVERAGE_EPSILON {
+                        shade_pixel(&mut scalar, &surface, Some(&tex), &settings, (x, y), bc, 1.0);
+                    }
+                }
+            }
+
+            for y in 0..48 {
+                for x in 0..64 {
+                    // Skip pixels right on an edge where rounding may differ
+                    let bc = barycentric(Vec3::new(x as f32, y as f32, 0.0), surface.v1, surface.v2, surface.v3);
+                    if [bc.x, bc.y, bc.z].iter().any(|c| c.abs() < 0.001) {
+                        continue;
+                    }
+                    // One texel step plus one shade rounding step
+                    let i = y * 64 + x;
+                    let (a, b) = (&simd.pixels[i * 4..i * 4 + 4], &scalar.pixels[i * 4..i * 4 + 4]);
+                    assert!(a.iter().zip(b).all(|(a, b)| a.abs_diff(*b) <= 3),
+                        "affine: {}, pixel ({}, {}): {:?} vs {:?}", affine_textures, x, y, a, b);
+                    let (za, zb) = (simd.zbuffer[i], scalar.zbuffer[i]);
+                    assert!(za == zb || (za - zb).abs() <= zb * 1e-4,
+                        "affine: {}, pixel ({}, {}): depth {} vs {}", affine_textures, x, y, za, zb);
+                }
+            }
+        }
+    }
+}
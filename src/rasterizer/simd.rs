@@ -0,0 +1,310 @@
+//! SIMD span filling (enabled with the `simd` feature)
+//!
+//! Barycentric coordinates are linear in screen space, so coverage, depth,
+//! texture coordinates, shading and texel addresses for 4 neighbouring pixels
+//! can be evaluated in one go:
+//! - x86_64: SSE2 (always available on the target)
+//! - aarch64: NEON
+//! - anything else (incl. WASM): scalar fallback
+
+use super::math::Vec3;
+
+/// Per-triangle setup: each attribute is `a * x + b * y + c`
+pub struct SpanSetup {
+    pub u: [f32; 3],
+    pub v: [f32; 3],
+    pub z: [f32; 3],
+}
+
+impl SpanSetup {
+    /// Returns None for degenerate triangles (matches `barycentric`)
+    pub fn new(v1: Vec3, v2: Vec3, v3: Vec3) -> Option<Self> {
+        let d = (v2.y - v3.y) * (v1.x - v3.x) + (v3.x - v2.x) * (v1.y - v3.y);
+        if d.abs() < 0.0001 {
+            return None;
+        }
+
+        let u = [
+            (v2.y - v3.y) / d,
+            (v3.x - v2.x) / d,
+            (-(v2.y - v3.y) * v3.x - (v3.x - v2.x) * v3.y) / d,
+        ];
+        let v = [
+            (v3.y - v1.y) / d,
+            (v1.x - v3.x) / d,
+            (-(v3.y - v1.y) * v3.x - (v1.x - v3.x) * v3.y) / d,
+        ];
+        let mut setup = Self { u, v, z: [0.0; 3] };
+        setup.z = setup.plane(v1.z, v2.z, v3.z);
+        Some(setup)
+    }
+
+    /// Plane of a per-vertex attribute: `a3 + u * (a1 - a3) + v * (a2 - a3)`
+    pub fn plane(&self, a1: f32, a2: f32, a3: f32) -> [f32; 3] {
+        let (d1, d2) = (a1 - a3, a2 - a3);
+        [
+            self.u[0] * d1 + self.v[0] * d2,
+            self.u[1] * d1 + self.v[1] * d2,
+            self.u[2] * d1 + self.v[2] * d2 + a3,
+        ]
+    }
+
+    /// Scalar barycentric coordinates at a pixel
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[inline]
+    pub fn barycentric(&self, x: f32, y: f32) -> Vec3 {
+        let u = self.u[0] * x + self.u[1] * y + self.u[2];
+        let v = self.v[0] * x + self.v[1] * y + self.v[2];
+        Vec3::new(u, v, 1.0 - u - v)
+    }
+}
+
+/// Coverage (and optional depth) mask for pixels x..x+4 on row y; bit i = pixel x+i
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn coverage4(s: &SpanSetup, x: f32, y: f32, depth: &[f32; 4], use_z: bool, epsilon: f32) -> u32 {
+    use std::arch::x86_64::*;
+
+    // SAFETY: SSE2 is part of the x86_64 baseline; `depth` holds 4 floats for the unaligned load
+    unsafe {
+        let xs = _mm_add_ps(_mm_set1_ps(x), _mm_set_ps(3.0, 2.0, 1.0, 0.0));
+        let u = _mm_add_ps(_mm_mul_ps(xs, _mm_set1_ps(s.u[0])), _mm_set1_ps(s.u[1] * y + s.u[2]));
+        let v = _mm_add_ps(_mm_mul_ps(xs, _mm_set1_ps(s.v[0])), _mm_set1_ps(s.v[1] * y + s.v[2]));
+        let w = _mm_sub_ps(_mm_sub_ps(_mm_set1_ps(1.0), u), v);
+
+        let eps = _mm_set1_ps(epsilon);
+        let mut mask = _mm_and_ps(_mm_and_ps(_mm_cmpge_ps(u, eps), _mm_cmpge_ps(v, eps)), _mm_cmpge_ps(w, eps));
+        if use_z {
+            let z = _mm_add_ps(_mm_mul_ps(xs, _mm_set1_ps(s.z[0])), _mm_set1_ps(s.z[1] * y + s.z[2]));
+            mask = _mm_and_ps(mask, _mm_cmplt_ps(z, _mm_loadu_ps(depth.as_ptr())));
+        }
+        _mm_movemask_ps(mask) as u32
+    }
+}
+
+/// Coverage (and optional depth) mask for pixels x..x+4 on row y; bit i = pixel x+i
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub fn coverage4(s: &SpanSetup, x: f32, y: f32, depth: &[f32; 4], use_z: bool, epsilon: f32) -> u32 {
+    use std::arch::aarch64::*;
+
+    const LANES: [f32; 4] = [0.0, 1.0, 2.0, 3.0];
+    // SAFETY: NEON is part of the aarch64 baseline; all loads/stores are 4-element arrays
+    unsafe {
+        let xs = vaddq_f32(vdupq_n_f32(x), vld1q_f32(LANES.as_ptr()));
+        let u = vmlaq_f32(vdupq_n_f32(s.u[1] * y + s.u[2]), xs, vdupq_n_f32(s.u[0]));
+        let v = vmlaq_f32(vdupq_n_f32(s.v[1] * y + s.v[2]), xs, vdupq_n_f32(s.v[0]));
+        let w = vsubq_f32(vsubq_f32(vdupq_n_f32(1.0), u), v);
+
+        let eps = vdupq_n_f32(epsilon);
+        let mut mask = vandq_u32(vandq_u32(vcgeq_f32(u, eps), vcgeq_f32(v, eps)), vcgeq_f32(w, eps));
+        if use_z {
+            let z = vmlaq_f32(vdupq_n_f32(s.z[1] * y + s.z[2]), xs, vdupq_n_f32(s.z[0]));
+            mask = vandq_u32(mask, vcltq_f32(z, vld1q_f32(depth.as_ptr())));
+        }
+
+        let mut lanes = [0u32; 4];
+        vst1q_u32(lanes.as_mut_ptr(), mask);
+        (lanes[0] & 1) | (lanes[1] & 1) << 1 | (lanes[2] & 1) << 2 | (lanes[3] & 1) << 3
+    }
+}
+
+/// Coverage (and optional depth) mask for pixels x..x+4 on row y; bit i = pixel x+i
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+pub fn coverage4(s: &SpanSetup, x: f32, y: f32, depth: &[f32; 4], use_z: bool, epsilon: f32) -> u32 {
+    let mut mask = 0;
+    for (i, d) in depth.iter().enumerate() {
+        let px = x + i as f32;
+        let bc = s.barycentric(px, y);
+        let mut inside = bc.x >= epsilon && bc.y >= epsilon && bc.z >= epsilon;
+        if use_z {
+            inside &= s.z[0] * px + s.z[1] * y + s.z[2] < *d;
+        }
+        if inside {
+            mask |= 1 << i;
+        }
+    }
+    mask
+}
+
+/// Attribute planes for shading a triangle's spans
+///
+/// Texture coordinates are stored divided by depth, with `q = 1/z`, so
+/// `u / q` is perspective-correct; affine mapping uses `q = 1`.
+pub struct AttributeSetup {
+    pub z: [f32; 3],
+    pub u: [f32; 3],
+    pub v: [f32; 3],
+    pub q: [f32; 3],
+    pub shade: [f32; 3],
+}
+
+/// Interpolated attributes of pixels x..x+4 on one row
+#[derive(Default)]
+pub struct Lanes4 {
+    pub z: [f32; 4],
+    pub u: [f32; 4],
+    pub v: [f32; 4],
+    pub shade: [f32; 4],
+}
+
+/// Depth, texture coordinates and shade for pixels x..x+4 on row y
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn interpolate4(a: &AttributeSetup, x: f32, y: f32) -> Lanes4 {
+    use std::arch::x86_64::*;
+
+    let mut out = Lanes4::default();
+    // SAFETY: SSE2 is part of the x86_64 baseline; all stores are 4-element arrays
+    unsafe {
+        let xs = _mm_add_ps(_mm_set1_ps(x), _mm_set_ps(3.0, 2.0, 1.0, 0.0));
+        let plane = |p: &[f32; 3]| _mm_add_ps(_mm_mul_ps(xs, _mm_set1_ps(p[0])), _mm_set1_ps(p[1] * y + p[2]));
+        let q = plane(&a.q);
+        _mm_storeu_ps(out.z.as_mut_ptr(), plane(&a.z));
+        _mm_storeu_ps(out.u.as_mut_ptr(), _mm_div_ps(plane(&a.u), q));
+        _mm_storeu_ps(out.v.as_mut_ptr(), _mm_div_ps(plane(&a.v), q));
+        _mm_storeu_ps(out.shade.as_mut_ptr(), plane(&a.shade));
+    }
+    out
+}
+
+/// Depth, texture coordinates and shade for pixels x..x+4 on row y
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub fn interpolate4(a: &AttributeSetup, x: f32, y: f32) -> Lanes4 {
+    use std::arch::aarch64::*;
+
+    const LANES: [f32; 4] = [0.0, 1.0, 2.0, 3.0];
+    let mut out = Lanes4::default();
+    // SAFETY: NEON is part of the aarch64 baseline; all loads/stores are 4-element arrays
+    unsafe {
+        let xs = vaddq_f32(vdupq_n_f32(x), vld1q_f32(LANES.as_ptr()));
+        let plane = |p: &[f32; 3]| vmlaq_f32(vdupq_n_f32(p[1] * y + p[2]), xs, vdupq_n_f32(p[0]));
+        let q = plane(&a.q);
+        vst1q_f32(out.z.as_mut_ptr(), plane(&a.z));
+        vst1q_f32(out.u.as_mut_ptr(), vdivq_f32(plane(&a.u), q));
+        vst1q_f32(out.v.as_mut_ptr(), vdivq_f32(plane(&a.v), q));
+        vst1q_f32(out.shade.as_mut_ptr(), plane(&a.shade));
+    }
+    out
+}
+
+/// Depth, texture coordinates and shade for pixels x..x+4 on row y
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+pub fn interpolate4(a: &AttributeSetup, x: f32, y: f32) -> Lanes4 {
+    let plane = |p: &[f32; 3], px: f32| p[0] * px + p[1] * y + p[2];
+    let mut out = Lanes4::default();
+    for i in 0..4 {
+        let px = x + i as f32;
+        let q = plane(&a.q, px);
+        out.z[i] = plane(&a.z, px);
+        out.u[i] = plane(&a.u, px) / q;
+        out.v[i] = plane(&a.v, px) / q;
+        out.shade[i] = plane(&a.shade, px);
+    }
+    out
+}
+
+/// Texel indices for 4 samples, addressed like `Texture::sample` (truncate, wrap)
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn texel_index4(u: &[f32; 4], v: &[f32; 4], width: usize, height: usize) -> [usize; 4] {
+    use std::arch::x86_64::*;
+
+    // t * size truncated and wrapped into 0..size (negative and NaN give 0, like `as usize`)
+    #[inline(always)]
+    unsafe fn wrap(t: __m128, size: f32) -> __m128 {
+        let zero = _mm_setzero_ps();
+        let size4 = _mm_set1_ps(size);
+        let t = _mm_cvtepi32_ps(_mm_cvttps_epi32(_mm_max_ps(_mm_mul_ps(t, size4), zero)));
+        let turns = _mm_cvtepi32_ps(_mm_cvttps_epi32(_mm_mul_ps(t, _mm_set1_ps(1.0 / size))));
+        let mut r = _mm_sub_ps(t, _mm_mul_ps(turns, size4));
+        // Fix up rounding in `1 / size`, then keep huge inputs in range
+        r = _mm_sub_ps(r, _mm_and_ps(_mm_cmpge_ps(r, size4), size4));
+        r = _mm_add_ps(r, _mm_and_ps(_mm_cmplt_ps(r, zero), size4));
+        _mm_min_ps(_mm_max_ps(r, zero), _mm_set1_ps(size - 1.0))
+    }
+
+    let mut idx = [0i32; 4];
+    // SAFETY: SSE2 is part of the x86_64 baseline; all loads/stores are 4-element arrays
+    unsafe {
+        let tx = wrap(_mm_loadu_ps(u.as_ptr()), width as f32);
+        let ty = wrap(_mm_loadu_ps(v.as_ptr()), height as f32);
+        let i = _mm_add_ps(_mm_mul_ps(ty, _mm_set1_ps(width as f32)), tx);
+        _mm_storeu_si128(idx.as_mut_ptr() as *mut __m128i, _mm_cvttps_epi32(i));
+    }
+    idx.map(|i| i as usize)
+}
+
+/// Texel indices for 4 samples, addressed like `Texture::sample` (truncate, wrap)
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub fn texel_index4(u: &[f32; 4], v: &[f32; 4], width: usize, height: usize) -> [usize; 4] {
+    use std::arch::aarch64::*;
+
+    // t * size truncated and wrapped into 0..size (negative and NaN give 0, like `as usize`)
+    #[inline(always)]
+    unsafe fn wrap(t: float32x4_t, size: f32) -> float32x4_t {
+        let zero = vdupq_n_f32(0.0);
+        let size4 = vdupq_n_f32(size);
+        let t = vcvtq_f32_s32(vcvtq_s32_f32(vmaxq_f32(vmulq_n_f32(t, size), zero)));
+        let turns = vcvtq_f32_s32(vcvtq_s32_f32(vmulq_n_f32(t, 1.0 / size)));
+        let mut r = vsubq_f32(t, vmulq_f32(turns, size4));
+        // Fix up rounding in `1 / size`, then keep huge inputs in range
+        r = vbslq_f32(vcgeq_f32(r, size4), vsubq_f32(r, size4), r);
+        r = vbslq_f32(vcltq_f32(r, zero), vaddq_f32(r, size4), r);
+        vminq_f32(vmaxq_f32(r, zero), vdupq_n_f32(size - 1.0))
+    }
+
+    let mut idx = [0i32; 4];
+    // SAFETY: NEON is part of the aarch64 baseline; all loads/stores are 4-element arrays
+    unsafe {
+        let tx = wrap(vld1q_f32(u.as_ptr()), width as f32);
+        let ty = wrap(vld1q_f32(v.as_ptr()), height as f32);
+        let i = vmlaq_f32(tx, ty, vdupq_n_f32(width as f32));
+        vst1q_s32(idx.as_mut_ptr(), vcvtq_s32_f32(i));
+    }
+    idx.map(|i| i as usize)
+}
+
+/// Texel indices for 4 samples, addressed like `Texture::sample` (truncate, wrap)
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+pub fn texel_index4(u: &[f32; 4], v: &[f32; 4], width: usize, height: usize) -> [usize; 4] {
+    std::array::from_fn(|i| {
+        let tx = ((u[i] * width as f32) as usize) % width;
+        let ty = ((v[i] * height as f32) as usize) % height;
+        ty * width + tx
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::barycentric;
+
+    #[test]
+    fn test_coverage_matches_barycentric() {
+        let (v1, v2, v3) = (Vec3::new(2.0, 1.0, 5.0), Vec3::new(30.0, 4.0, 9.0), Vec3::new(10.0, 25.0, 2.0));
+        let setup = SpanSetup::new(v1, v2, v3).unwrap();
+        let depth = [f32::MAX; 4];
+
+        for y in 0..32 {
+            for x in (0..32).step_by(4) {
+                let mask = coverage4(&setup, x as f32, y as f32, &depth, false, -0.0001);
+                for lane in 0..4 {
+                    let p = Vec3::new((x + lane) as f32, y as f32, 0.0);
+                    let bc = barycentric(p, v1, v2, v3);
+                    // Skip pixels right on an edge where rounding may differ
+                    if [bc.x, bc.y, bc.z].iter().any(|c| c.abs() < 0.001) {
+                        continue;
+                    }
+                    let inside = bc.x >= 0.0 && bc.y >= 0.0 && bc.z >= 0.0;
+                    assert_eq!(mask & (1 << lane) != 0, inside, "pixel ({}, {})", x + lane, y);
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
 //! Core types for the rasterizer
 
 use super::math::{Vec2, Vec3};
+use super::postfx::CrtSettings;
 use serde::{Deserialize, Serialize};
 
 /// RGBA color (0-255 per channel)
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -121,6 +122,8 @@ pub struct Face {
     pub v1: usize,
     pub v2: usize,
     pub texture_id: Option<usize>,
+    /// Back side is drawn solid even when backface culling is on
+    pub double_sided: bool,
 }
 
 impl Face {
@@ -130,6 +133,7 @@ impl Face {
             v1,
             v2,
             texture_id: None,
+            double_sided: false,
         }
     }
 
@@ -139,8 +143,15 @@ impl Face {
             v1,
             v2,
             texture_id: Some(texture_id),
+            double_sided: false,
         }
     }
+
+    /// Set whether the back side is drawn solid
+    pub fn two_sided(mut self, double_sided: bool) -> Self {
+        self.double_sided = double_sided;
+        self
+    }
 }
 
 /// Simple texture (array of colors)
@@ -341,6 +352,113 @@ pub enum BlendMode {
     AddQuarter,// Mode 3: B + 0.25*F (subtle glow)
 }
 
+/// Internal framebuffer resolution.
+///
+/// Pixels stay square: wider modes (including the 16:9 ones) show a wider
+/// view instead of being stretched, and viewports letterbox or pillarbox to
+/// the framebuffer's aspect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resolution {
+    /// 256x224 (PS1 narrow mode)
+    R256x224,
+    /// 320x240 (PS1 standard)
+    #[default]
+    R320x240,
+    /// 368x240 (PS1 overscan)
+    R368x240,
+    /// 512x240 (PS1 hi-res horizontal)
+    R512x240,
+    /// 640x480 (PS1 interlaced)
+    R640x480,
+    /// 426x240 (16:9 at PS1 line count)
+    R426x240,
+    /// 854x480 (16:9 interlaced)
+    R854x480,
+    /// Any other size (set in settings.ron), clamped to `CUSTOM_RANGE`
+    Custom(usize, usize),
+}
+
+impl Resolution {
+    /// Presets cycled by the toolbar, in order
+    pub const PRESETS: [Resolution; 7] = [
+        Resolution::R256x224,
+        Resolution::R320x240,
+        Resolution::R368x240,
+        Resolution::R512x240,
+        Resolution::R640x480,
+        Resolution::R426x240,
+        Resolution::R854x480,
+    ];
+
+    /// Smallest and largest custom dimension
+    pub const CUSTOM_RANGE: (usize, usize) = (64, 1920);
+
+    /// Framebuffer width and height
+    pub fn size(self) -> (usize, usize) {
+        match self {
+            Resolution::R256x224 => (256, 224),
+            Resolution::R320x240 => (super::WIDTH, super::HEIGHT),
+            Resolution::R368x240 => (368, 240),
+            Resolution::R512x240 => (512, 240),
+            Resolution::R640x480 => (super::WIDTH_HI, super::HEIGHT_HI),
+            Resolution::R426x240 => (426, 240),
+            Resolution::R854x480 => (854, 480),
+            Resolution::Custom(w, h) => {
+                let (min, max) = Self::CUSTOM_RANGE;
+                (w.clamp(min, max), h.clamp(min, max))
+            }
+        }
+    }
+
+    /// Modes up to 240 lines (progressive on the PS1)
+    pub fn is_low(self) -> bool {
+        self.size().1 <= 240
+    }
+
+    /// Next preset (a custom size goes back to the first one)
+    pub fn next(self) -> Self {
+        match Self::PRESETS.iter().position(|p| *p == self) {
+            Some(i) => Self::PRESETS[(i + 1) % Self::PRESETS.len()],
+            None => Self::PRESETS[0],
+        }
+    }
+
+    /// 16:9-ish or wider
+    pub fn is_widescreen(self) -> bool {
+        let (w, h) = self.size();
+        w as f32 / h as f32 >= 1.7
+    }
+
+    /// 240 or 480-line mode of the given shape (the game's Options screen)
+    pub fn standard(low: bool, widescreen: bool) -> Self {
+        match (low, widescreen) {
+            (true, false) => Resolution::R320x240,
+            (false, false) => Resolution::R640x480,
+            (true, true) => Resolution::R426x240,
+            (false, true) => Resolution::R854x480,
+        }
+    }
+
+    /// Display aspect ratio ("4:3", "16:9", or "2.13:1" for odd shapes)
+    pub fn aspect_label(self) -> String {
+        let (w, h) = self.size();
+        let aspect = w as f32 / h as f32;
+        if (aspect - 16.0 / 9.0).abs() < 0.02 {
+            "16:9".to_string()
+        } else if (aspect - 4.0 / 3.0).abs() < 0.02 {
+            "4:3".to_string()
+        } else {
+            format!("{:.2}:1", aspect)
+        }
+    }
+
+    /// "WxH (aspect)" for the UI
+    pub fn label(self) -> String {
+        let (w, h) = self.size();
+        format!("{}x{} ({})", w, h, self.aspect_label())
+    }
+}
+
 /// Rasterizer settings
 #[derive(Debug, Clone)]
 pub struct RasterSettings {
@@ -358,10 +476,38 @@ pub struct RasterSettings {
     pub light_dir: Vec3,
     /// Ambient light intensity (0.0-1.0)
     pub ambient: f32,
-    /// Use PS1 low resolution (320x240) instead of high resolution
-    pub low_resolution: bool,
+    /// Internal framebuffer resolution
+    pub resolution: Resolution,
     /// Enable PS1-style ordered dithering (4x4 Bayer matrix)
     pub dithering: bool,
+    /// 480i-style interlacing: render alternating fields per frame (high resolution only)
+    pub interlaced: bool,
+    /// Weave fields together, showing combing on motion (otherwise line-double)
+    pub interlace_combing: bool,
+    /// Fixed-point (20.12) transform and interpolation, like the PS1 GTE
+    pub fixed_point: bool,
+    /// CRT post-processing applied at blit time
+    pub crt: CrtSettings,
+}
+
+impl RasterSettings {
+    /// Interlacing only applies to 480-line modes (240p was progressive on PS1)
+    pub fn is_interlaced(&self) -> bool {
+        self.interlaced && !self.resolution.is_low()
+    }
+
+    /// Framebuffer size for the current resolution mode
+    pub fn resolution(&self) -> (usize, usize) {
+        self.resolution.size()
+    }
+
+    /// Framebuffer size while the view is being moved: half of the current
+    /// resolution, so big levels keep up with the mouse
+    pub fn reduced_resolution(&self) -> (usize, usize) {
+        let (w, h) = self.resolution();
+        let min = Resolution::CUSTOM_RANGE.0;
+        ((w / 2).max(min), (h / 2).max(min))
+    }
 }
 
 impl Default for RasterSettings {
@@ -374,8 +520,12 @@ impl Default for RasterSettings {
             backface_cull: true,
             light_dir: Vec3::new(-1.0, -1.0, -1.0).normalize(),
             ambient: 0.3,
-            low_resolution: true,   // PS1 default: 320x240
+            resolution: Resolution::R320x240, // PS1 default
             dithering: true,        // PS1 default: ordered dithering enabled
+            interlaced: false,
+            interlace_combing: true,
+            fixed_point: false,
+            crt: CrtSettings::default(),
         }
     }
 }
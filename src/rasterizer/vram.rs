@@ -0,0 +1,316 @@
+//! Simulated PS1 VRAM - how a set of textures would fit into 1MB
+//!
+//! The PS1 keeps the framebuffers, textures and palettes (CLUTs) in one
+//! 1024x512 grid of 16-bit words. Textures are sampled through texture pages
+//! (64 words wide, 256 lines tall) and store 4, 8 or 15 bits per texel:
+//! - 4-bit: up to 16 colors, 4 texels per word, plus a 16-entry CLUT
+//! - 8-bit: up to 256 colors, 2 texels per word, plus a 256-entry CLUT
+//! - 15-bit: direct color, 1 texel per word
+//!
+//! `pack_vram` reserves the framebuffers in the top-left corner, then places
+//! each texture at the smallest depth its colors allow, never across the
+//! page row boundary and within the 256 texels a page can address. Textures
+//! that don't fit are listed in `VramLayout::overflow`.
+
+use std::collections::HashSet;
+use super::Texture;
+
+/// VRAM width in 16-bit words
+pub const VRAM_WIDTH: usize = 1024;
+/// VRAM height in lines
+pub const VRAM_HEIGHT: usize = 512;
+/// Texture page width in words
+pub const PAGE_WIDTH: usize = 64;
+/// Texture page height in lines
+pub const PAGE_HEIGHT: usize = 256;
+/// Texels a page addresses across from its left edge
+const PAGE_TEXELS: usize = 256;
+
+/// Bits stored per texel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TexelDepth {
+    Bit4,
+    Bit8,
+    Bit15,
+}
+
+impl TexelDepth {
+    /// Smallest depth that holds every color of a texture (colors are
+    /// counted at the PS1's 15-bit precision)
+    pub fn of(texture: &Texture) -> Self {
+        let mut colors = HashSet::new();
+        for c in &texture.pixels {
+            colors.insert((c.r >> 3, c.g >> 3, c.b >> 3));
+            if colors.len() > 256 {
+                return TexelDepth::Bit15;
+            }
+        }
+        if colors.len() > 16 {
+            TexelDepth::Bit8
+        } else {
+            TexelDepth::Bit4
+        }
+    }
+
+    pub fn texels_per_word(self) -> usize {
+        match self {
+            TexelDepth::Bit4 => 4,
+            TexelDepth::Bit8 => 2,
+            TexelDepth::Bit15 => 1,
+        }
+    }
+
+    /// Entries of the texture's CLUT (0 for direct color)
+    pub fn clut_entries(self) -> usize {
+        match self {
+            TexelDepth::Bit4 => 16,
+            TexelDepth::Bit8 => 256,
+            TexelDepth::Bit15 => 0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TexelDepth::Bit4 => "4-bit",
+            TexelDepth::Bit8 => "8-bit",
+            TexelDepth::Bit15 => "15-bit",
+        }
+    }
+}
+
+/// What a VRAM block holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VramContent {
+    /// Display buffer n
+    Framebuffer(usize),
+    /// Texture n of the packed list
+    Texture(usize),
+    /// Palette of texture n
+    Clut(usize),
+}
+
+/// A rectangle of VRAM, in words and lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VramBlock {
+    pub content: VramContent,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl VramBlock {
+    pub fn bytes(&self) -> usize {
+        self.width * self.height * 2
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Where everything ended up
+#[derive(Debug, Clone, Default)]
+pub struct VramLayout {
+    pub blocks: Vec<VramBlock>,
+    /// Depth chosen for each texture
+    pub depths: Vec<TexelDepth>,
+    /// Textures that didn't fit (by index)
+    pub overflow: Vec<usize>,
+}
+
+impl VramLayout {
+    pub fn used_bytes(&self) -> usize {
+        self.blocks.iter().map(|b| b.bytes()).sum()
+    }
+
+    /// Bytes textures that didn't fit would need on top
+    pub fn overflow_bytes(&self, textures: &[&Texture]) -> usize {
+        self.overflow
+            .iter()
+            .map(|&i| {
+                let depth = self.depths[i];
+                let (w, h) = texture_words(textures[i], depth);
+                (w * h + depth.clut_entries()) * 2
+            })
+            .sum()
+    }
+
+    /// Texture pages (of the 32) holding at least one texture
+    pub fn pages_used(&self) -> usize {
+        let mut pages = HashSet::new();
+        for b in self.blocks.iter().filter(|b| matches!(b.content, VramContent::Texture(_))) {
+            for px in b.x / PAGE_WIDTH..=(b.x + b.width - 1) / PAGE_WIDTH {
+                pages.insert((px, b.y / PAGE_HEIGHT));
+            }
+        }
+        pages.len()
+    }
+
+    pub fn block_at(&self, x: usize, y: usize) -> Option<&VramBlock> {
+        self.blocks.iter().find(|b| b.contains(x, y))
+    }
+}
+
+/// Words and lines a texture takes at a depth
+fn texture_words(texture: &Texture, depth: TexelDepth) -> (usize, usize) {
+    (texture.width.div_ceil(depth.texels_per_word()), texture.height)
+}
+
+/// Free rectangle filled shelf by shelf
+struct Region {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    /// Cursor: x on the current shelf, shelf top, shelf height
+    cursor: (usize, usize, usize),
+}
+
+impl Region {
+    fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height, cursor: (x, y, 0) }
+    }
+
+    /// Place a `w` x `h` block whose texels (`texels_per_word` per word, 0
+    /// for palettes) must stay addressable from one texture page
+    fn place(&mut self, w: usize, h: usize, texels_per_word: usize) -> Option<(usize, usize)> {
+        if w > self.width || w * texels_per_word > PAGE_TEXELS {
+            return None;
+        }
+        let (right, bottom) = (self.x + self.width, self.y + self.height);
+        let (mut x, mut shelf_y, mut shelf_h) = self.cursor;
+        loop {
+            if x + w > right {
+                shelf_y += shelf_h;
+                x = self.x;
+                shelf_h = 0;
+            }
+            if shelf_y + h > bottom {
+                return None;
+            }
+            let page = x / PAGE_WIDTH * PAGE_WIDTH;
+            if (x - page + w) * texels_per_word > PAGE_TEXELS {
+                x = page + PAGE_WIDTH;
+                continue;
+            }
+            self.cursor = (x + w, shelf_y, shelf_h.max(h));
+            return Some((x, shelf_y));
+        }
+    }
+}
+
+/// Lay out `buffers` framebuffers of `fb_width` x `fb_height` and the
+/// textures in VRAM
+pub fn pack_vram(textures: &[&Texture], fb_width: usize, fb_height: usize, buffers: usize) -> VramLayout {
+    let mut layout = VramLayout::default();
+    for n in 0..buffers {
+        let block = VramBlock { content: VramContent::Framebuffer(n), x: 0, y: n * fb_height, width: fb_width, height: fb_height };
+        layout.blocks.push(block);
+    }
+
+    // Free space: beside the framebuffers, and under them, per page row
+    let fb_bottom = fb_height * buffers;
+    let mut regions = Vec::new();
+    for row in (0..VRAM_HEIGHT).step_by(PAGE_HEIGHT) {
+        let covered = buffers > 0 && row < fb_bottom;
+        let left = if covered { fb_width.div_ceil(PAGE_WIDTH) * PAGE_WIDTH } else { 0 };
+        regions.push(Region::new(left, row, VRAM_WIDTH.saturating_sub(left), PAGE_HEIGHT));
+        if covered && fb_bottom < row + PAGE_HEIGHT {
+            regions.push(Region::new(0, fb_bottom, left, row + PAGE_HEIGHT - fb_bottom));
+        }
+    }
+
+    // Tallest textures first, then the palettes of the ones placed
+    layout.depths = textures.iter().map(|t| TexelDepth::of(t)).collect();
+    let mut order: Vec<usize> = (0..textures.len()).collect();
+    order.sort_by(|&a, &b| textures[b].height.cmp(&textures[a].height).then(a.cmp(&b)));
+    let mut placed = Vec::new();
+    for i in order {
+        let depth = layout.depths[i];
+        let (w, h) = texture_words(textures[i], depth);
+        if w == 0 || h == 0 {
+            continue;
+        }
+        match regions.iter_mut().find_map(|r| r.place(w, h, depth.texels_per_word())) {
+            Some((x, y)) => {
+                layout.blocks.push(VramBlock { content: VramContent::Texture(i), x, y, width: w, height: h });
+                placed.push(i);
+            }
+            None => layout.overflow.push(i),
+        }
+    }
+    for i in placed {
+        let entries = layout.depths[i].clut_entries();
+        if entries == 0 {
+            continue;
+        }
+        // Palettes fill the leftovers, the space under the framebuffers first
+        match regions.iter_mut().rev().find_map(|r| r.place(entries, 1, 0)) {
+            Some((x, y)) => layout.blocks.push(VramBlock { content: VramContent::Clut(i), x, y, width: entries, height: 1 }),
+            None => layout.overflow.push(i),
+        }
+    }
+    layout.overflow.sort_unstable();
+    layout.overflow.dedup();
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Color;
+
+    fn texture(size: usize, colors: usize) -> Texture {
+        let mut tex = Texture::new(size, size);
+        for (i, p) in tex.pixels.iter_mut().enumerate() {
+            let c = i % colors;
+            *p = Color::new((c % 32 * 8) as u8, (c / 32 * 8) as u8, 0);
+        }
+        tex
+    }
+
+    #[test]
+    fn test_depth_follows_color_count() {
+        assert_eq!(TexelDepth::of(&texture(64, 16)), TexelDepth::Bit4);
+        assert_eq!(TexelDepth::of(&texture(64, 17)), TexelDepth::Bit8);
+        assert_eq!(TexelDepth::of(&texture(64, 300)), TexelDepth::Bit15);
+    }
+
+    #[test]
+    fn test_textures_pack_around_framebuffers() {
+        let textures = [texture(64, 16), texture(128, 200), texture(256, 16)];
+        let refs: Vec<&Texture> = textures.iter().collect();
+        let layout = pack_vram(&refs, 320, 240, 2);
+        assert!(layout.overflow.is_empty());
+
+        let buffers = layout.blocks.iter().filter(|b| matches!(b.content, VramContent::Framebuffer(_))).count();
+        assert_eq!(buffers, 2);
+        for (i, a) in layout.blocks.iter().enumerate() {
+            assert!(a.x + a.width <= VRAM_WIDTH && a.y + a.height <= VRAM_HEIGHT);
+            // Nothing overlaps, and no texture crosses the page row
+            for b in &layout.blocks[i + 1..] {
+                let apart = a.x + a.width <= b.x || b.x + b.width <= a.x || a.y + a.height <= b.y || b.y + b.height <= a.y;
+                assert!(apart, "{:?} overlaps {:?}", a, b);
+            }
+            if matches!(a.content, VramContent::Texture(_)) {
+                assert_eq!(a.y / PAGE_HEIGHT, (a.y + a.height - 1) / PAGE_HEIGHT);
+            }
+        }
+        // 4-bit 256x256 is one page; the 8-bit 128x128 is 64 words wide
+        let tex = |n| layout.blocks.iter().find(|b| b.content == VramContent::Texture(n)).unwrap();
+        assert_eq!((tex(2).width, tex(1).width, tex(0).width), (64, 64, 16));
+        assert_eq!(layout.blocks.iter().filter(|b| matches!(b.content, VramContent::Clut(_))).count(), 3);
+        assert_eq!(layout.pages_used(), 3);
+    }
+
+    #[test]
+    fn test_too_many_textures_overflow() {
+        let big = texture(256, 300);
+        let refs = vec![&big; 20];
+        let layout = pack_vram(&refs, 320, 240, 2);
+        assert!(!layout.overflow.is_empty());
+        assert!(layout.used_bytes() <= VRAM_WIDTH * VRAM_HEIGHT * 2);
+        assert!(layout.overflow_bytes(&refs) > 0);
+    }
+}
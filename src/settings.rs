@@ -0,0 +1,62 @@
+//! User settings
+//!
+//! Preferences that apply to the whole app (language, UI scale, 3D
+//! viewport resolution), saved
+//! as `settings.ron` in the working directory. WASM builds have no file
+//! access, so settings there last for the session only.
+
+use serde::{Deserialize, Serialize};
+use crate::i18n::FALLBACK_LANGUAGE;
+use crate::rasterizer::Resolution;
+
+/// Where settings are stored
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+/// Persistent user preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    /// UI language code (see `i18n::LANGUAGES`)
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// UI scale override (None = auto-detect, see `ui::auto_ui_scale`)
+    #[serde(default)]
+    pub ui_scale: Option<f32>,
+    /// Internal resolution of the world editor and modeler viewports
+    #[serde(default)]
+    pub resolution: Resolution,
+}
+
+fn default_language() -> String {
+    FALLBACK_LANGUAGE.to_string()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { language: default_language(), ui_scale: None, resolution: Resolution::default() }
+    }
+}
+
+impl Settings {
+    /// Load settings, falling back to defaults if missing or unreadable
+    pub fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(contents) = std::fs::read_to_string(SETTINGS_PATH) {
+            match ron::from_str(&contents) {
+                Ok(settings) => return settings,
+                Err(e) => log::warn!("Ignoring {}: {}", SETTINGS_PATH, e),
+            }
+        }
+        Self::default()
+    }
+
+    /// Save settings (no-op on WASM)
+    pub fn save(&self) -> Result<(), String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+            std::fs::write(SETTINGS_PATH, contents).map_err(|e| format!("Failed to write {}: {}", SETTINGS_PATH, e))?;
+        }
+        Ok(())
+    }
+}
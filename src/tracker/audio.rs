@@ -1,15 +1,16 @@
 //! Audio engine using rustysynth for SF2 playback
 //!
+//! Sample instruments (see `sampler`) are mixed on top of the synth, and an
+//! optional reverb (see `reverb`) runs over the mix.
+//!
 //! Platform-specific audio output:
 //! - Native: cpal for direct audio device access
 //! - WASM: Web Audio API via JavaScript FFI
 
 use std::sync::{Arc, Mutex};
-#[cfg(not(target_arch = "wasm32"))]
-use std::path::Path;
-#[cfg(not(target_arch = "wasm32"))]
-use std::fs::File;
 use rustysynth::{SoundFont, Synthesizer, SynthesizerSettings};
+use super::reverb::{Reverb, ReverbParams};
+use super::sampler::{SampleInstrument, Sampler, SAMPLE_PROGRAM_BASE};
 
 /// Sample rate for audio output
 pub const SAMPLE_RATE: u32 = 44100;
@@ -18,8 +19,40 @@ pub const SAMPLE_RATE: u32 = 44100;
 struct AudioState {
     /// The synthesizer
     synth: Option<Synthesizer>,
+    /// Sample instrument player
+    sampler: Sampler,
+    /// Current program per MIDI channel (sample instruments never reach the synth)
+    programs: [u8; 16],
     /// Whether audio is playing
     playing: bool,
+    /// Reverb over the whole mix (room acoustics in play mode)
+    reverb: Option<Reverb>,
+    /// Volume of the whole mix (0.0 - 1.0), for fades
+    gain: f32,
+}
+
+impl AudioState {
+    /// Render the synth (or silence) and mix the sampler on top
+    fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        match self.synth {
+            Some(ref mut synth) => synth.render(left, right),
+            None => {
+                left.fill(0.0);
+                right.fill(0.0);
+            }
+        }
+        if self.sampler.is_active() {
+            self.sampler.render(left, right);
+        }
+        if let Some(reverb) = &mut self.reverb {
+            reverb.process(left, right);
+        }
+        if self.gain < 1.0 {
+            for sample in left.iter_mut().chain(right.iter_mut()) {
+                *sample *= self.gain;
+            }
+        }
+    }
 }
 
 // =============================================================================
@@ -50,26 +83,20 @@ mod native {
             move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
                 let mut state = state.lock().unwrap();
 
-                if let Some(ref mut synth) = state.synth {
-                    let samples_needed = data.len() / 2;
-                    if left_buffer.len() < samples_needed {
-                        left_buffer.resize(samples_needed, 0.0);
-                        right_buffer.resize(samples_needed, 0.0);
-                    }
-
-                    synth.render(&mut left_buffer[..samples_needed], &mut right_buffer[..samples_needed]);
-
-                    for i in 0..samples_needed {
-                        data[i * 2] = left_buffer[i];
-                        data[i * 2 + 1] = right_buffer[i];
-                    }
-                } else {
-                    for sample in data.iter_mut() {
-                        *sample = 0.0;
-                    }
+                let samples_needed = data.len() / 2;
+                if left_buffer.len() < samples_needed {
+                    left_buffer.resize(samples_needed, 0.0);
+                    right_buffer.resize(samples_needed, 0.0);
+                }
+
+                state.render(&mut left_buffer[..samples_needed], &mut right_buffer[..samples_needed]);
+
+                for i in 0..samples_needed {
+                    data[i * 2] = left_buffer[i];
+                    data[i * 2 + 1] = right_buffer[i];
                 }
             },
-            |err| eprintln!("Audio stream error: {}", err),
+            |err| log::error!("Audio stream error: {}", err),
             None,
         ).ok()?;
 
@@ -141,6 +168,8 @@ pub struct AudioEngine {
     _stream: Option<cpal::Stream>,
     /// Loaded soundfont info
     soundfont_name: Option<String>,
+    /// Instrument list (bank, program, name), decoded once per soundfont
+    presets: Arc<Vec<(u8, u8, String)>>,
     /// Audio render buffers (WASM only - we render on demand)
     #[cfg(target_arch = "wasm32")]
     left_buffer: Vec<f32>,
@@ -156,7 +185,11 @@ impl AudioEngine {
     pub fn new() -> Self {
         let state = Arc::new(Mutex::new(AudioState {
             synth: None,
+            sampler: Sampler::new(SAMPLE_RATE),
+            programs: [0; 16],
             playing: false,
+            reverb: None,
+            gain: 1.0,
         }));
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -166,6 +199,7 @@ impl AudioEngine {
                 state,
                 _stream: stream,
                 soundfont_name: None,
+                presets: Arc::new(gm_presets()),
             }
         }
 
@@ -175,6 +209,7 @@ impl AudioEngine {
             Self {
                 state,
                 soundfont_name: None,
+                presets: Arc::new(gm_presets()),
                 left_buffer: vec![0.0; 2048],
                 right_buffer: vec![0.0; 2048],
                 sample_accumulator: 0.0,
@@ -182,35 +217,14 @@ impl AudioEngine {
         }
     }
 
-    /// Load a soundfont from file (native only)
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn load_soundfont(&mut self, path: &Path) -> Result<(), String> {
-        let file = File::open(path)
-            .map_err(|e| format!("Failed to open soundfont: {}", e))?;
-
-        let mut reader = std::io::BufReader::new(file);
-        self.load_soundfont_from_reader(&mut reader, path.file_name()
-            .map(|n| n.to_string_lossy().to_string()))
-    }
-
-    /// Load a soundfont from bytes (works on all platforms including WASM)
-    pub fn load_soundfont_from_bytes(&mut self, bytes: &[u8], name: Option<String>) -> Result<(), String> {
-        let mut cursor = std::io::Cursor::new(bytes);
-        self.load_soundfont_from_reader(&mut cursor, name)
-    }
-
-    /// Internal: Load soundfont from any reader
-    fn load_soundfont_from_reader<R: std::io::Read>(&mut self, reader: &mut R, name: Option<String>) -> Result<(), String> {
-        let soundfont = SoundFont::new(reader)
-            .map_err(|e| format!("Failed to parse soundfont: {:?}", e))?;
-
-        let soundfont = Arc::new(soundfont);
-
+    /// Start playing through a parsed soundfont
+    pub fn set_soundfont(&mut self, soundfont: &Arc<SoundFont>, name: Option<String>) -> Result<(), String> {
         let settings = SynthesizerSettings::new(SAMPLE_RATE as i32);
-        let synth = Synthesizer::new(&soundfont, &settings)
+        let synth = Synthesizer::new(soundfont, &settings)
             .map_err(|e| format!("Failed to create synthesizer: {:?}", e))?;
 
         self.soundfont_name = name;
+        self.presets = Arc::new(soundfont_presets(soundfont));
 
         let mut state = self.state.lock().unwrap();
         state.synth = Some(synth);
@@ -233,7 +247,7 @@ impl AudioEngine {
     #[cfg(target_arch = "wasm32")]
     pub fn render_audio(&mut self, delta: f64) {
         let mut state = self.state.lock().unwrap();
-        if let Some(ref mut synth) = state.synth {
+        if state.synth.is_some() || state.sampler.is_active() {
             // Calculate exact samples needed based on actual elapsed time
             // delta is in seconds, sample_rate is 44100 samples/sec
             self.sample_accumulator += delta * SAMPLE_RATE as f64;
@@ -254,7 +268,7 @@ impl AudioEngine {
                 self.left_buffer.resize(samples, 0.0);
                 self.right_buffer.resize(samples, 0.0);
             }
-            synth.render(&mut self.left_buffer[..samples], &mut self.right_buffer[..samples]);
+            state.render(&mut self.left_buffer[..samples], &mut self.right_buffer[..samples]);
             wasm::write_audio(&self.left_buffer[..samples], &self.right_buffer[..samples]);
         }
     }
@@ -262,7 +276,11 @@ impl AudioEngine {
     /// Play a note (note on)
     pub fn note_on(&self, channel: i32, key: i32, velocity: i32) {
         let mut state = self.state.lock().unwrap();
-        if let Some(ref mut synth) = state.synth {
+        let program = state.programs[(channel & 15) as usize];
+        if program >= SAMPLE_PROGRAM_BASE {
+            let index = (program - SAMPLE_PROGRAM_BASE) as usize;
+            state.sampler.note_on(channel as u8, key.clamp(0, 127) as u8, velocity.clamp(0, 127) as u8, index);
+        } else if let Some(ref mut synth) = state.synth {
             synth.note_on(channel, key, velocity);
         }
     }
@@ -270,6 +288,7 @@ impl AudioEngine {
     /// Stop a note (note off)
     pub fn note_off(&self, channel: i32, key: i32) {
         let mut state = self.state.lock().unwrap();
+        state.sampler.note_off(channel as u8, key.clamp(0, 127) as u8);
         if let Some(ref mut synth) = state.synth {
             synth.note_off(channel, key);
         }
@@ -278,6 +297,7 @@ impl AudioEngine {
     /// Stop all notes
     pub fn all_notes_off(&self) {
         let mut state = self.state.lock().unwrap();
+        state.sampler.all_notes_off();
         if let Some(ref mut synth) = state.synth {
             for channel in 0..16 {
                 for key in 0..128 {
@@ -290,11 +310,35 @@ impl AudioEngine {
     /// Set the instrument (program) for a channel
     pub fn set_program(&self, channel: i32, program: i32) {
         let mut state = self.state.lock().unwrap();
+        state.programs[(channel & 15) as usize] = program.clamp(0, 255) as u8;
+        if program >= SAMPLE_PROGRAM_BASE as i32 {
+            return;
+        }
         if let Some(ref mut synth) = state.synth {
             synth.process_midi_message(channel, 0xC0, program, 0);
         }
     }
 
+    /// Set (or turn off) the reverb over the whole mix. Changing the
+    /// parameters keeps the current tail ringing.
+    pub fn set_room_reverb(&self, params: Option<ReverbParams>) {
+        let mut state = self.state.lock().unwrap();
+        match (params, state.reverb.as_mut()) {
+            (Some(params), Some(reverb)) => reverb.params = params,
+            (params, _) => state.reverb = params.map(|p| Reverb::new(SAMPLE_RATE, p)),
+        }
+    }
+
+    /// Install (or clear) sample instrument `index`, played by program `SAMPLE_PROGRAM_BASE + index`
+    pub fn set_sample_instrument(&self, index: usize, instrument: Option<Arc<SampleInstrument>>) {
+        self.state.lock().unwrap().sampler.set_instrument(index, instrument);
+    }
+
+    /// Set the volume of the whole mix (0.0 - 1.0)
+    pub fn set_gain(&self, gain: f32) {
+        self.state.lock().unwrap().gain = gain.clamp(0.0, 1.0);
+    }
+
     /// Set channel volume (CC 7)
     pub fn set_volume(&self, channel: i32, volume: i32) {
         let mut state = self.state.lock().unwrap();
@@ -362,46 +406,69 @@ impl AudioEngine {
         }
     }
 
-    /// Get list of preset names from the loaded soundfont
-    pub fn get_preset_names(&self) -> Vec<(u8, u8, String)> {
-        let gm_names = [
-            "Acoustic Grand Piano", "Bright Acoustic Piano", "Electric Grand Piano",
-            "Honky-tonk Piano", "Electric Piano 1", "Electric Piano 2", "Harpsichord",
-            "Clavinet", "Celesta", "Glockenspiel", "Music Box", "Vibraphone",
-            "Marimba", "Xylophone", "Tubular Bells", "Dulcimer", "Drawbar Organ",
-            "Percussive Organ", "Rock Organ", "Church Organ", "Reed Organ",
-            "Accordion", "Harmonica", "Tango Accordion", "Acoustic Guitar (nylon)",
-            "Acoustic Guitar (steel)", "Electric Guitar (jazz)", "Electric Guitar (clean)",
-            "Electric Guitar (muted)", "Overdriven Guitar", "Distortion Guitar",
-            "Guitar Harmonics", "Acoustic Bass", "Electric Bass (finger)",
-            "Electric Bass (pick)", "Fretless Bass", "Slap Bass 1", "Slap Bass 2",
-            "Synth Bass 1", "Synth Bass 2", "Violin", "Viola", "Cello", "Contrabass",
-            "Tremolo Strings", "Pizzicato Strings", "Orchestral Harp", "Timpani",
-            "String Ensemble 1", "String Ensemble 2", "Synth Strings 1", "Synth Strings 2",
-            "Choir Aahs", "Voice Oohs", "Synth Voice", "Orchestra Hit", "Trumpet",
-            "Trombone", "Tuba", "Muted Trumpet", "French Horn", "Brass Section",
-            "Synth Brass 1", "Synth Brass 2", "Soprano Sax", "Alto Sax", "Tenor Sax",
-            "Baritone Sax", "Oboe", "English Horn", "Bassoon", "Clarinet", "Piccolo",
-            "Flute", "Recorder", "Pan Flute", "Blown Bottle", "Shakuhachi", "Whistle",
-            "Ocarina", "Lead 1 (square)", "Lead 2 (sawtooth)", "Lead 3 (calliope)",
-            "Lead 4 (chiff)", "Lead 5 (charang)", "Lead 6 (voice)", "Lead 7 (fifths)",
-            "Lead 8 (bass + lead)", "Pad 1 (new age)", "Pad 2 (warm)", "Pad 3 (polysynth)",
-            "Pad 4 (choir)", "Pad 5 (bowed)", "Pad 6 (metallic)", "Pad 7 (halo)",
-            "Pad 8 (sweep)", "FX 1 (rain)", "FX 2 (soundtrack)", "FX 3 (crystal)",
-            "FX 4 (atmosphere)", "FX 5 (brightness)", "FX 6 (goblins)", "FX 7 (echoes)",
-            "FX 8 (sci-fi)", "Sitar", "Banjo", "Shamisen", "Koto", "Kalimba",
-            "Bagpipe", "Fiddle", "Shanai", "Tinkle Bell", "Agogo", "Steel Drums",
-            "Woodblock", "Taiko Drum", "Melodic Tom", "Synth Drum", "Reverse Cymbal",
-            "Guitar Fret Noise", "Breath Noise", "Seashore", "Bird Tweet",
-            "Telephone Ring", "Helicopter", "Applause", "Gunshot",
-        ];
-
-        gm_names.iter().enumerate()
-            .map(|(i, name)| (0, i as u8, name.to_string()))
-            .collect()
+    /// Instrument list (bank, program, name) of the loaded soundfont.
+    /// Cheap to call every frame: the list is shared, not rebuilt.
+    pub fn get_preset_names(&self) -> Arc<Vec<(u8, u8, String)>> {
+        Arc::clone(&self.presets)
     }
 }
 
+/// Melodic (bank 0) presets of a soundfont by program, GM names if it has none
+fn soundfont_presets(soundfont: &SoundFont) -> Vec<(u8, u8, String)> {
+    let mut presets: Vec<(u8, u8, String)> = soundfont
+        .get_presets()
+        .iter()
+        .filter(|p| p.get_bank_number() == 0 && (0..128).contains(&p.get_patch_number()))
+        .map(|p| (0, p.get_patch_number() as u8, p.get_name().trim().to_string()))
+        .collect();
+    presets.sort_by_key(|(_, program, _)| *program);
+    presets.dedup_by_key(|(_, program, _)| *program);
+    if presets.is_empty() {
+        gm_presets()
+    } else {
+        presets
+    }
+}
+
+/// General MIDI instrument names, shown until a soundfont is loaded
+fn gm_presets() -> Vec<(u8, u8, String)> {
+    let gm_names = [
+        "Acoustic Grand Piano", "Bright Acoustic Piano", "Electric Grand Piano",
+        "Honky-tonk Piano", "Electric Piano 1", "Electric Piano 2", "Harpsichord",
+        "Clavinet", "Celesta", "Glockenspiel", "Music Box", "Vibraphone",
+        "Marimba", "Xylophone", "Tubular Bells", "Dulcimer", "Drawbar Organ",
+        "Percussive Organ", "Rock Organ", "Church Organ", "Reed Organ",
+        "Accordion", "Harmonica", "Tango Accordion", "Acoustic Guitar (nylon)",
+        "Acoustic Guitar (steel)", "Electric Guitar (jazz)", "Electric Guitar (clean)",
+        "Electric Guitar (muted)", "Overdriven Guitar", "Distortion Guitar",
+        "Guitar Harmonics", "Acoustic Bass", "Electric Bass (finger)",
+        "Electric Bass (pick)", "Fretless Bass", "Slap Bass 1", "Slap Bass 2",
+        "Synth Bass 1", "Synth Bass 2", "Violin", "Viola", "Cello", "Contrabass",
+        "Tremolo Strings", "Pizzicato Strings", "Orchestral Harp", "Timpani",
+        "String Ensemble 1", "String Ensemble 2", "Synth Strings 1", "Synth Strings 2",
+        "Choir Aahs", "Voice Oohs", "Synth Voice", "Orchestra Hit", "Trumpet",
+        "Trombone", "Tuba", "Muted Trumpet", "French Horn", "Brass Section",
+        "Synth Brass 1", "Synth Brass 2", "Soprano Sax", "Alto Sax", "Tenor Sax",
+        "Baritone Sax", "Oboe", "English Horn", "Bassoon", "Clarinet", "Piccolo",
+        "Flute", "Recorder", "Pan Flute", "Blown Bottle", "Shakuhachi", "Whistle",
+        "Ocarina", "Lead 1 (square)", "Lead 2 (sawtooth)", "Lead 3 (calliope)",
+        "Lead 4 (chiff)", "Lead 5 (charang)", "Lead 6 (voice)", "Lead 7 (fifths)",
+        "Lead 8 (bass + lead)", "Pad 1 (new age)", "Pad 2 (warm)", "Pad 3 (polysynth)",
+        "Pad 4 (choir)", "Pad 5 (bowed)", "Pad 6 (metallic)", "Pad 7 (halo)",
+        "Pad 8 (sweep)", "FX 1 (rain)", "FX 2 (soundtrack)", "FX 3 (crystal)",
+        "FX 4 (atmosphere)", "FX 5 (brightness)", "FX 6 (goblins)", "FX 7 (echoes)",
+        "FX 8 (sci-fi)", "Sitar", "Banjo", "Shamisen", "Koto", "Kalimba",
+        "Bagpipe", "Fiddle", "Shanai", "Tinkle Bell", "Agogo", "Steel Drums",
+        "Woodblock", "Taiko Drum", "Melodic Tom", "Synth Drum", "Reverse Cymbal",
+        "Guitar Fret Noise", "Breath Noise", "Seashore", "Bird Tweet",
+        "Telephone Ring", "Helicopter", "Applause", "Gunshot",
+    ];
+
+    gm_names.iter().enumerate()
+        .map(|(i, name)| (0, i as u8, name.to_string()))
+        .collect()
+}
+
 impl Default for AudioEngine {
     fn default() -> Self {
         Self::new()
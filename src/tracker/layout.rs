@@ -1,8 +1,11 @@
 //! Tracker UI layout and rendering
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, Toolbar, icon, draw_knob};
-use super::state::{TrackerState, TrackerView};
+use crate::i18n::{tr, trf};
+use crate::ui::{edit_text, shortcut_modifier_down, Dialog, DialogId, FocusId, Rect, Shortcut, TextEdit, UiContext, Toolbar, icon, draw_knob, ui_pixel_scale};
+use super::state::{ChannelDisplay, PlaybackMode, TrackerState, TrackerView};
+use super::pattern::{CueSwitch, MAX_SWING};
+use super::sampler::{MAX_ENVELOPE_TIME, SAMPLE_PROGRAM_BASE};
 
 // Colors
 const BG_COLOR: Color = Color::new(0.11, 0.11, 0.13, 1.0);
@@ -29,20 +32,49 @@ const INST_WIDTH: f32 = 24.0;
 const VOL_WIDTH: f32 = 24.0;
 const FX_WIDTH: f32 = 16.0;
 const FXPARAM_WIDTH: f32 = 24.0;
+const NARROW_CHANNEL_WIDTH: f32 = NOTE_WIDTH + 8.0;
+const COLLAPSED_CHANNEL_WIDTH: f32 = 18.0;
+const H_SCROLLBAR_HEIGHT: f32 = 10.0;
+/// Pixels per Shift+wheel notch
+const H_SCROLL_STEP: f32 = 60.0;
+const SONG_BROWSER_WIDTH: f32 = 180.0;
+
+// Keyboard focus: the views take notes and shortcuts, the song browser
+// takes none, text fields take typing
+const VIEW_FOCUS: FocusId = "tracker.view";
+const BROWSER_FOCUS: FocusId = "tracker.songs";
+const CUE_NAME_FOCUS: FocusId = "tracker.cue_name";
+const KNOB_FOCUS: FocusId = "tracker.knob";
+
+const RENAME_SONG_DIALOG: DialogId = "tracker.rename_song";
+const DELETE_SONG_DIALOG: DialogId = "tracker.delete_song";
 
 /// Draw the tracker interface
 pub fn draw_tracker(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_font: Option<&Font>) {
+    // First open of the tab starts loading the soundfont and reads the songs
+    state.update_soundfont();
+    state.open_library();
+
     // Background
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
 
     // Split into header and main area
     let header_height = 60.0;
     let header_rect = Rect::new(rect.x, rect.y, rect.w, header_height);
-    let main_rect = Rect::new(rect.x, rect.y + header_height, rect.w, rect.h - header_height);
+    let body_rect = Rect::new(rect.x, rect.y + header_height, rect.w, rect.h - header_height);
+    let browser_rect = Rect::new(body_rect.x, body_rect.y, SONG_BROWSER_WIDTH, body_rect.h);
+    let main_rect = Rect::new(browser_rect.right(), body_rect.y, (body_rect.w - SONG_BROWSER_WIDTH).max(0.0), body_rect.h);
 
     // Draw header (transport, info)
     draw_header(ctx, header_rect, state, icon_font);
 
+    // Clicking a panel gives it the keyboard
+    ctx.focus_on_click(&browser_rect, BROWSER_FOCUS);
+    ctx.focus_on_click(&main_rect, VIEW_FOCUS);
+
+    // Song browser sidebar
+    draw_song_browser(ctx, browser_rect, state);
+
     // Draw main content based on view
     match state.view {
         TrackerView::Pattern => draw_pattern_view(ctx, main_rect, state),
@@ -64,14 +96,14 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
 
     // View mode buttons
     let view_icons = [
-        (TrackerView::Pattern, icon::GRID, "Pattern Editor"),
-        (TrackerView::Arrangement, icon::LIST_MUSIC, "Arrangement"),
-        (TrackerView::Instruments, icon::PIANO, "Instruments"),
+        (TrackerView::Pattern, icon::GRID, "tracker.view.pattern"),
+        (TrackerView::Arrangement, icon::LIST_MUSIC, "tracker.view.arrangement"),
+        (TrackerView::Instruments, icon::PIANO, "tracker.view.instruments"),
     ];
 
-    for (view, icon_char, tooltip) in view_icons {
+    for (view, icon_char, tooltip_key) in view_icons {
         let is_active = state.view == view;
-        if toolbar.icon_button_active(ctx, icon_char, icon_font, tooltip, is_active) {
+        if toolbar.icon_button_active(ctx, icon_char, icon_font, &tr(tooltip_key), is_active) {
             state.view = view;
         }
     }
@@ -79,41 +111,82 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
     toolbar.separator();
 
     // Transport controls
-    if toolbar.icon_button(ctx, icon::SKIP_BACK, icon_font, "Stop & Rewind") {
+    if toolbar.icon_button(ctx, icon::SKIP_BACK, icon_font, &tr("common.stop_rewind")) {
         state.stop_playback();
     }
 
     // Play from start
-    if toolbar.icon_button(ctx, icon::PLAY, icon_font, "Play from Start") {
+    if toolbar.icon_button(ctx, icon::PLAY, icon_font, &tr("tracker.play_start")) {
         state.play_from_start();
     }
 
     // Play/pause from cursor
     let play_icon = if state.playing { icon::PAUSE } else { icon::SKIP_FORWARD };
-    let play_tooltip = if state.playing { "Pause" } else { "Play from Cursor" };
-    if toolbar.icon_button_active(ctx, play_icon, icon_font, play_tooltip, state.playing) {
+    let play_tooltip = tr(if state.playing { "common.pause" } else { "tracker.play_cursor" });
+    if toolbar.icon_button_active(ctx, play_icon, icon_font, &play_tooltip, state.playing) {
         state.toggle_playback();
     }
 
+    // What happens at the end of a pattern (cycles)
+    let (mode_icon, mode_key) = playback_mode_icon(state.playback_mode);
+    if toolbar.icon_button(ctx, mode_icon, icon_font, &tr(mode_key)) {
+        state.playback_mode = state.playback_mode.next();
+        state.set_status(&tr(playback_mode_icon(state.playback_mode).1), 1.0);
+    }
+
+    // Record (notes go into the pattern) or jam
+    if toolbar.icon_button_active(ctx, icon::CIRCLE_DOT, icon_font, &tr("tracker.record"), state.edit_mode) {
+        toggle_edit_mode(state);
+    }
+
     toolbar.separator();
 
     // BPM controls
     toolbar.label(&format!("BPM:{:3}", state.song.bpm));
-    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Decrease BPM") {
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, &tr("tracker.bpm_down")) {
         state.song.bpm = (state.song.bpm as i32 - 5).clamp(40, 300) as u16;
     }
-    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Increase BPM") {
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, &tr("tracker.bpm_up")) {
         state.song.bpm = (state.song.bpm as i32 + 5).clamp(40, 300) as u16;
     }
 
     toolbar.separator();
 
+    // Swing controls (edit the current pattern's own swing when it has one)
+    let pattern_swing = state.current_pattern().and_then(|p| p.swing);
+    let swing = pattern_swing.unwrap_or(state.song.swing);
+    toolbar.label(&format!("Swing:{:2}%{}", swing, if pattern_swing.is_some() { "*" } else { "" }));
+    let mut new_swing = None;
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, &tr("tracker.swing_down")) {
+        new_swing = Some(swing.saturating_sub(5));
+    }
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, &tr("tracker.swing_up")) {
+        new_swing = Some((swing + 5).min(MAX_SWING));
+    }
+    if let Some(value) = new_swing {
+        match state.current_pattern_mut().filter(|p| p.swing.is_some()) {
+            Some(pattern) => pattern.swing = Some(value),
+            None => state.song.swing = value,
+        }
+        state.dirty = true;
+    }
+    let swing_icon = if pattern_swing.is_some() { icon::UNLINK } else { icon::LINK };
+    if toolbar.icon_button_active(ctx, swing_icon, icon_font, &tr("tracker.swing_pattern"), pattern_swing.is_some()) {
+        let song_swing = state.song.swing;
+        if let Some(pattern) = state.current_pattern_mut() {
+            pattern.swing = if pattern.swing.is_some() { None } else { Some(song_swing) };
+        }
+        state.dirty = true;
+    }
+
+    toolbar.separator();
+
     // Octave controls
     toolbar.label(&format!("Oct:{}", state.octave));
-    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Octave Down") {
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, &tr("tracker.octave_down")) {
         state.octave = state.octave.saturating_sub(1);
     }
-    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Octave Up") {
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, &tr("tracker.octave_up")) {
         state.octave = (state.octave + 1).min(9);
     }
 
@@ -121,10 +194,10 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
 
     // Step controls
     toolbar.label(&format!("Step:{}", state.edit_step));
-    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Decrease Step") {
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, &tr("tracker.step_down")) {
         state.edit_step = state.edit_step.saturating_sub(1);
     }
-    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Increase Step") {
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, &tr("tracker.step_up")) {
         state.edit_step = (state.edit_step + 1).min(16);
     }
 
@@ -132,10 +205,10 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
 
     // Channel count controls
     toolbar.label(&format!("Ch:{}", state.num_channels()));
-    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Remove Channel") {
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, &tr("tracker.channel_remove")) {
         state.remove_channel();
     }
-    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Add Channel") {
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, &tr("tracker.channel_add")) {
         state.add_channel();
     }
 
@@ -154,10 +227,14 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
     );
 
     // Soundfont status
-    let sf_status = state.audio.soundfont_name()
-        .map(|n| format!("SF: {}", n))
-        .unwrap_or_else(|| "No Soundfont".to_string());
-    draw_text(&sf_status, rect.x + 350.0, y2 + 14.0, 12.0, if state.audio.is_loaded() { TEXT_DIM } else { Color::new(0.8, 0.3, 0.3, 1.0) });
+    let sf_status = if state.soundfont.is_loading() {
+        tr("tracker.loading_soundfont")
+    } else {
+        state.audio.soundfont_name()
+            .map(|n| format!("SF: {}", n))
+            .unwrap_or_else(|| tr("tracker.no_soundfont"))
+    };
+    draw_text(&sf_status, rect.x + 350.0, y2 + 14.0, 12.0, if state.audio.is_loaded() || state.soundfont.is_loading() { TEXT_DIM } else { Color::new(0.8, 0.3, 0.3, 1.0) });
 
     // Status message
     if let Some(status) = state.get_status() {
@@ -165,6 +242,9 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
     }
 }
 
+/// Seconds an effect cell written from a knob stays highlighted
+const WRITE_FLASH_TIME: f32 = 0.8;
+
 /// Height of the channel strip header (instrument selector, etc.)
 const CHANNEL_STRIP_HEIGHT: f32 = 36.0;
 
@@ -172,8 +252,10 @@ const CHANNEL_STRIP_HEIGHT: f32 = 36.0;
 fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState) {
     let num_channels = state.num_channels();
 
-    // Calculate visible rows (accounting for channel strip header)
-    state.visible_rows = ((rect.h - CHANNEL_STRIP_HEIGHT - ROW_HEIGHT) / ROW_HEIGHT) as usize;
+    // Calculate visible rows (accounting for channel strip header and scrollbar)
+    let grid_y_start = rect.y + CHANNEL_STRIP_HEIGHT + ROW_HEIGHT;
+    let grid_rect = Rect::new(rect.x, grid_y_start, rect.w, rect.h - CHANNEL_STRIP_HEIGHT - ROW_HEIGHT - H_SCROLLBAR_HEIGHT);
+    state.visible_rows = (grid_rect.h / ROW_HEIGHT) as usize;
 
     // Get pattern info without holding borrow
     let (pattern_length, rows_per_beat) = match state.current_pattern() {
@@ -181,95 +263,166 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
         None => return,
     };
 
+    // === Horizontal layout: channels scroll, the row numbers stay put ===
+    let channels_rect = Rect::new(rect.x + ROW_NUM_WIDTH, rect.y, rect.w - ROW_NUM_WIDTH, rect.h - H_SCROLLBAR_HEIGHT);
+    let widths: Vec<f32> = (0..num_channels).map(|ch| channel_width(state.channel_display[ch])).collect();
+    let total_w: f32 = widths.iter().sum();
+    let max_scroll_x = (total_w - channels_rect.w).max(0.0);
+    let scrollbar_rect = Rect::new(channels_rect.x, rect.bottom() - H_SCROLLBAR_HEIGHT, channels_rect.w, H_SCROLLBAR_HEIGHT);
+    let thumb_w = (channels_rect.w / total_w.max(1.0) * scrollbar_rect.w).clamp(20.0, scrollbar_rect.w);
+
+    // Mouse wheel scrolls rows, Shift+wheel scrolls channels
+    let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+    let scroll = ctx.mouse.scroll;
+    if scroll != 0.0 && shift && ctx.mouse.inside(&rect) {
+        state.scroll_x -= scroll.signum() * H_SCROLL_STEP;
+    } else if scroll != 0.0 && ctx.mouse.inside(&grid_rect) {
+        let scroll_amount = if scroll > 0.0 { -4 } else { 4 }; // Scroll 4 rows at a time
+        let new_scroll = (state.scroll_row as i32 + scroll_amount).max(0) as usize;
+        state.scroll_row = new_scroll.min(pattern_length.saturating_sub(state.visible_rows));
+    }
+
+    // Scrollbar drag
+    if max_scroll_x > 0.0 && ctx.mouse.left_pressed && ctx.mouse.inside(&scrollbar_rect) {
+        state.dragging_scrollbar = true;
+    }
+    if state.dragging_scrollbar {
+        if ctx.mouse.left_down && max_scroll_x > 0.0 {
+            let t = (ctx.mouse.x - scrollbar_rect.x - thumb_w / 2.0) / (scrollbar_rect.w - thumb_w).max(1.0);
+            state.scroll_x = t.clamp(0.0, 1.0) * max_scroll_x;
+        } else {
+            state.dragging_scrollbar = false;
+        }
+    }
+
+    // Bring the cursor's channel into view when it moves
+    if state.current_channel != state.scrolled_to_channel {
+        if let Some(&w) = widths.get(state.current_channel) {
+            let left: f32 = widths[..state.current_channel].iter().sum();
+            if left < state.scroll_x {
+                state.scroll_x = left;
+            } else if left + w > state.scroll_x + channels_rect.w {
+                state.scroll_x = left + w - channels_rect.w;
+            }
+        }
+        state.scrolled_to_channel = state.current_channel;
+    }
+    state.scroll_x = state.scroll_x.clamp(0.0, max_scroll_x);
+
+    let mut channel_xs = Vec::with_capacity(num_channels);
+    let mut x = channels_rect.x - state.scroll_x;
+    for w in &widths {
+        channel_xs.push(x);
+        x += w;
+    }
+    let channel_at = |mx: f32| (0..num_channels).find(|&ch| mx >= channel_xs[ch] && mx < channel_xs[ch] + widths[ch]);
+    let mouse_in_channels = ctx.mouse.inside(&channels_rect);
+
     // === Channel strip header (instrument selector) ===
     draw_rectangle(rect.x, rect.y, rect.w, CHANNEL_STRIP_HEIGHT, Color::new(0.12, 0.12, 0.14, 1.0));
+    set_clip(Some(channels_rect));
 
-    let mut x = rect.x + ROW_NUM_WIDTH;
     for ch in 0..num_channels {
-        let ch_x = x;
+        let ch_x = channel_xs[ch];
+        let ch_w = widths[ch];
+        let display = state.channel_display[ch];
         let is_current = ch == state.current_channel;
-        let strip_rect = Rect::new(ch_x, rect.y, CHANNEL_WIDTH - 1.0, CHANNEL_STRIP_HEIGHT);
+        let strip_rect = Rect::new(ch_x, rect.y, ch_w - 1.0, CHANNEL_STRIP_HEIGHT);
 
         // Background for selected channel
         if is_current {
-            draw_rectangle(ch_x, rect.y, CHANNEL_WIDTH - 1.0, CHANNEL_STRIP_HEIGHT, Color::new(0.18, 0.2, 0.24, 1.0));
+            draw_rectangle(ch_x, rect.y, ch_w - 1.0, CHANNEL_STRIP_HEIGHT, Color::new(0.18, 0.2, 0.24, 1.0));
         }
 
         // Click to select channel
-        if ctx.mouse.inside(&strip_rect) && is_mouse_button_pressed(MouseButton::Left) {
+        if mouse_in_channels && ctx.mouse.inside(&strip_rect) && is_mouse_button_pressed(MouseButton::Left) {
             state.current_channel = ch;
         }
 
-        // Channel number
+        // Channel number doubles as the drag handle for reordering
+        let collapsed = display == ChannelDisplay::Collapsed;
+        let handle_rect = Rect::new(ch_x, rect.y, if collapsed { ch_w - 1.0 } else { ch_w - 17.0 }, 15.0);
+        if mouse_in_channels && ctx.mouse.inside(&handle_rect) && ctx.mouse.left_pressed {
+            state.dragging_channel = Some(ch);
+        }
         let ch_color = if is_current { NOTE_COLOR } else { TEXT_COLOR };
-        draw_text(&format!("Ch {}", ch + 1), ch_x + 4.0, rect.y + 12.0, 11.0, ch_color);
-
-        // Instrument selector: [-] [instrument name] [+]
-        let inst = state.song.get_channel_instrument(ch);
-        let presets = state.audio.get_preset_names();
-        let inst_name = presets
-            .iter()
-            .find(|(_, p, _)| *p == inst)
-            .map(|(_, _, n)| n.as_str())
-            .unwrap_or("---");
-
-        // Truncate instrument name to fit
-        let display_name: String = if inst_name.len() > 12 {
-            format!("{:.12}", inst_name)
+        let ch_label = if collapsed { format!("{}", ch + 1) } else { format!("Ch {}", ch + 1) };
+        draw_text(&ch_label, ch_x + 4.0, rect.y + 12.0, 11.0, ch_color);
+
+        // Display mode toggle: full -> narrow -> collapsed -> full
+        let toggle_rect = if collapsed {
+            Rect::new(ch_x + 2.0, rect.y + 18.0, ch_w - 5.0, 14.0)
         } else {
-            inst_name.to_string()
+            Rect::new(ch_x + ch_w - 16.0, rect.y + 1.0, 14.0, 13.0)
         };
+        let toggle_hover = mouse_in_channels && ctx.mouse.inside(&toggle_rect);
+        draw_rectangle(toggle_rect.x, toggle_rect.y, toggle_rect.w, toggle_rect.h,
+            if toggle_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
+        draw_text(if collapsed { ">" } else { "<" }, toggle_rect.x + 4.0, toggle_rect.y + 10.0, 11.0, TEXT_COLOR);
+        if toggle_hover && ctx.mouse.left_pressed {
+            state.channel_display[ch] = display.next();
+        }
+
+        // Instrument selector: [-] [instrument name] [+] (full width only)
+        if display == ChannelDisplay::Full {
+            let inst = state.song.get_channel_instrument(ch);
+            let inst_name = state.instrument_name(inst);
 
-        // [-] button
-        let minus_rect = Rect::new(ch_x + 2.0, rect.y + 16.0, 16.0, 16.0);
-        let minus_hover = ctx.mouse.inside(&minus_rect);
-        draw_rectangle(minus_rect.x, minus_rect.y, minus_rect.w, minus_rect.h,
-            if minus_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
-        draw_text("-", minus_rect.x + 5.0, minus_rect.y + 12.0, 12.0, TEXT_COLOR);
-        if minus_hover && is_mouse_button_pressed(MouseButton::Left) {
-            let new_inst = inst.saturating_sub(1);
-            state.song.set_channel_instrument(ch, new_inst);
-            if ch == state.current_channel {
-                state.audio.set_program(ch as i32, new_inst as i32);
+            // Truncate instrument name to fit
+            let display_name: String = if inst_name.len() > 12 {
+                format!("{:.12}", inst_name)
+            } else {
+                inst_name.to_string()
+            };
+
+            // [-] button
+            let minus_rect = Rect::new(ch_x + 2.0, rect.y + 16.0, 16.0, 16.0);
+            let minus_hover = mouse_in_channels && ctx.mouse.inside(&minus_rect);
+            draw_rectangle(minus_rect.x, minus_rect.y, minus_rect.w, minus_rect.h,
+                if minus_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
+            draw_text("-", minus_rect.x + 5.0, minus_rect.y + 12.0, 12.0, TEXT_COLOR);
+            if minus_hover && is_mouse_button_pressed(MouseButton::Left) {
+                let new_inst = inst.saturating_sub(1);
+                state.song.set_channel_instrument(ch, new_inst);
+                if ch == state.current_channel {
+                    state.audio.set_program(ch as i32, new_inst as i32);
+                }
             }
-        }
 
-        // Instrument name (clickable to open instrument picker)
-        let name_x = ch_x + 20.0;
-        draw_text(&format!("{:03}:{}", inst, display_name), name_x, rect.y + 28.0, 10.0, INST_COLOR);
-
-        // [+] button
-        let plus_rect = Rect::new(ch_x + CHANNEL_WIDTH - 20.0, rect.y + 16.0, 16.0, 16.0);
-        let plus_hover = ctx.mouse.inside(&plus_rect);
-        draw_rectangle(plus_rect.x, plus_rect.y, plus_rect.w, plus_rect.h,
-            if plus_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
-        draw_text("+", plus_rect.x + 4.0, plus_rect.y + 12.0, 12.0, TEXT_COLOR);
-        if plus_hover && is_mouse_button_pressed(MouseButton::Left) {
-            let new_inst = (inst + 1).min(127);
-            state.song.set_channel_instrument(ch, new_inst);
-            if ch == state.current_channel {
-                state.audio.set_program(ch as i32, new_inst as i32);
+            // Instrument name (clickable to open instrument picker)
+            let name_x = ch_x + 20.0;
+            draw_text(&format!("{:03}:{}", inst, display_name), name_x, rect.y + 28.0, 10.0, INST_COLOR);
+
+            // [+] button
+            let plus_rect = Rect::new(ch_x + CHANNEL_WIDTH - 20.0, rect.y + 16.0, 16.0, 16.0);
+            let plus_hover = mouse_in_channels && ctx.mouse.inside(&plus_rect);
+            draw_rectangle(plus_rect.x, plus_rect.y, plus_rect.w, plus_rect.h,
+                if plus_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
+            draw_text("+", plus_rect.x + 4.0, plus_rect.y + 12.0, 12.0, TEXT_COLOR);
+            if plus_hover && is_mouse_button_pressed(MouseButton::Left) {
+                let new_inst = (inst + 1).min(state.last_instrument());
+                state.song.set_channel_instrument(ch, new_inst);
+                if ch == state.current_channel {
+                    state.audio.set_program(ch as i32, new_inst as i32);
+                }
             }
         }
-
-        x += CHANNEL_WIDTH;
-
-        // Channel separator
-        draw_line(x - 1.0, rect.y, x - 1.0, rect.y + rect.h, 1.0, Color::new(0.25, 0.25, 0.3, 1.0));
     }
 
     // === Column headers (Note, Inst, Vol, etc.) ===
     let header_y = rect.y + CHANNEL_STRIP_HEIGHT;
+    set_clip(None);
     draw_rectangle(rect.x, header_y, rect.w, ROW_HEIGHT, HEADER_COLOR);
+    set_clip(Some(channels_rect));
 
-    x = rect.x + ROW_NUM_WIDTH;
     for ch in 0..num_channels {
-        let ch_x = x;
-        let header_rect = Rect::new(ch_x, header_y, CHANNEL_WIDTH, ROW_HEIGHT);
+        let ch_x = channel_xs[ch];
+        let ch_w = widths[ch];
+        let header_rect = Rect::new(ch_x, header_y, ch_w, ROW_HEIGHT);
 
         // Highlight on hover
-        if ctx.mouse.inside(&header_rect) {
-            draw_rectangle(ch_x, header_y, CHANNEL_WIDTH, ROW_HEIGHT, Color::new(0.25, 0.25, 0.3, 1.0));
+        if mouse_in_channels && ctx.mouse.inside(&header_rect) {
+            draw_rectangle(ch_x, header_y, ch_w, ROW_HEIGHT, Color::new(0.25, 0.25, 0.3, 1.0));
 
             // Click to select channel
             if is_mouse_button_pressed(MouseButton::Left) {
@@ -280,28 +433,22 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
         // Column labels
         let is_current = ch == state.current_channel;
         let label_color = if is_current { NOTE_COLOR } else { TEXT_DIM };
-        draw_text("Not", ch_x + 4.0, header_y + 13.0, 10.0, label_color);
-        draw_text("In", ch_x + NOTE_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
-        draw_text("Vl", ch_x + NOTE_WIDTH + INST_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
-        draw_text("Fx", ch_x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
-
-        x += CHANNEL_WIDTH;
-    }
-
-    // Handle mouse clicks and scrolling on pattern grid
-    let grid_y_start = rect.y + CHANNEL_STRIP_HEIGHT + ROW_HEIGHT;
-    let grid_rect = Rect::new(rect.x, grid_y_start, rect.w, rect.h - CHANNEL_STRIP_HEIGHT - ROW_HEIGHT);
-
-    // Mouse wheel scrolling
-    if ctx.mouse.inside(&grid_rect) {
-        let scroll = mouse_wheel().1;
-        if scroll != 0.0 {
-            let scroll_amount = if scroll > 0.0 { -4 } else { 4 }; // Scroll 4 rows at a time
-            let new_scroll = (state.scroll_row as i32 + scroll_amount).max(0) as usize;
-            state.scroll_row = new_scroll.min(pattern_length.saturating_sub(state.visible_rows));
+        match state.channel_display[ch] {
+            ChannelDisplay::Full => {
+                draw_text("Not", ch_x + 4.0, header_y + 13.0, 10.0, label_color);
+                draw_text("In", ch_x + NOTE_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
+                draw_text("Vl", ch_x + NOTE_WIDTH + INST_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
+                draw_text("Fx", ch_x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
+            }
+            ChannelDisplay::Narrow => {
+                draw_text("Not", ch_x + 4.0, header_y + 13.0, 10.0, label_color);
+            }
+            ChannelDisplay::Collapsed => {}
         }
     }
+    set_clip(None);
 
+    // Handle mouse clicks on pattern grid
     if ctx.mouse.inside(&grid_rect) && is_mouse_button_pressed(MouseButton::Left) {
         let mouse_x = ctx.mouse.x;
         let mouse_y = ctx.mouse.y;
@@ -314,26 +461,22 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
             state.current_row = clicked_row;
 
             // Calculate clicked channel and column
-            let rel_x = mouse_x - rect.x - ROW_NUM_WIDTH;
-            if rel_x >= 0.0 {
-                let clicked_channel = (rel_x / CHANNEL_WIDTH) as usize;
-                if clicked_channel < num_channels {
-                    state.current_channel = clicked_channel;
-
-                    // Calculate column within channel
-                    let col_x = rel_x - (clicked_channel as f32 * CHANNEL_WIDTH);
-                    state.current_column = if col_x < NOTE_WIDTH {
-                        0 // Note
-                    } else if col_x < NOTE_WIDTH + INST_WIDTH {
-                        1 // Instrument
-                    } else if col_x < NOTE_WIDTH + INST_WIDTH + VOL_WIDTH {
-                        2 // Volume
-                    } else if col_x < NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH {
-                        3 // Effect
-                    } else {
-                        4 // Effect param
-                    };
-                }
+            if let Some(clicked_channel) = channel_at(mouse_x).filter(|_| mouse_x >= channels_rect.x) {
+                state.current_channel = clicked_channel;
+
+                // Calculate column within channel (narrow and collapsed channels only show notes)
+                let col_x = mouse_x - channel_xs[clicked_channel];
+                state.current_column = if state.channel_display[clicked_channel] != ChannelDisplay::Full || col_x < NOTE_WIDTH {
+                    0 // Note
+                } else if col_x < NOTE_WIDTH + INST_WIDTH {
+                    1 // Instrument
+                } else if col_x < NOTE_WIDTH + INST_WIDTH + VOL_WIDTH {
+                    2 // Volume
+                } else if col_x < NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH {
+                    3 // Effect
+                } else {
+                    4 // Effect param
+                };
             }
         }
     }
@@ -348,11 +491,12 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
     let start_row = state.scroll_row;
     let visible_rows = state.visible_rows;
     let end_row = (start_row + visible_rows).min(pattern.length);
-    let pattern_num_channels = pattern.num_channels();
+    let pattern_num_channels = pattern.num_channels().min(num_channels);
+    let row_y = |row_idx: usize| grid_y_start + (row_idx - start_row) as f32 * ROW_HEIGHT;
 
+    // Row backgrounds and numbers (not scrolled sideways)
     for row_idx in start_row..end_row {
-        let screen_row = row_idx - start_row;
-        let y = rect.y + CHANNEL_STRIP_HEIGHT + ROW_HEIGHT + screen_row as f32 * ROW_HEIGHT;
+        let y = row_y(row_idx);
 
         // Row background
         let row_bg = if state.playing && row_idx == state.playback_row && state.playback_pattern_idx == state.current_pattern_idx {
@@ -371,82 +515,410 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
         // Row number
         let row_color = if row_idx % (rows_per_beat as usize) == 0 { TEXT_COLOR } else { TEXT_DIM };
         draw_text(&format!("{:02X}", row_idx), rect.x + 4.0, y + 14.0, 12.0, row_color);
+    }
 
-        // Draw each channel
-        let mut x = rect.x + ROW_NUM_WIDTH;
-        for ch in 0..pattern_num_channels {
-            let note = &pattern.channels[ch][row_idx];
+    // Channel cells
+    set_clip(Some(channels_rect));
+    for row_idx in start_row..end_row {
+        let y = row_y(row_idx);
 
-            // Cursor highlight
-            if row_idx == state.current_row && ch == state.current_channel {
-                let col_x = x + match state.current_column {
-                    0 => 0.0,
-                    1 => NOTE_WIDTH,
-                    2 => NOTE_WIDTH + INST_WIDTH,
-                    3 => NOTE_WIDTH + INST_WIDTH + VOL_WIDTH,
-                    _ => NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH,
-                };
-                let col_w = match state.current_column {
-                    0 => NOTE_WIDTH,
-                    1 => INST_WIDTH,
-                    2 => VOL_WIDTH,
-                    3 => FX_WIDTH,
-                    _ => FXPARAM_WIDTH,
-                };
-                draw_rectangle(col_x, y, col_w, ROW_HEIGHT, CURSOR_COLOR);
+        for (ch, &x) in channel_xs.iter().enumerate().take(pattern_num_channels) {
+            let note = &pattern.channels[ch][row_idx];
+            let display = state.channel_display[ch];
+            let is_cursor = row_idx == state.current_row && ch == state.current_channel;
+
+            match display {
+                ChannelDisplay::Full => {
+                    // Cursor highlight
+                    if is_cursor {
+                        let col_x = x + match state.current_column {
+                            0 => 0.0,
+                            1 => NOTE_WIDTH,
+                            2 => NOTE_WIDTH + INST_WIDTH,
+                            3 => NOTE_WIDTH + INST_WIDTH + VOL_WIDTH,
+                            _ => NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH,
+                        };
+                        let col_w = match state.current_column {
+                            0 => NOTE_WIDTH,
+                            1 => INST_WIDTH,
+                            2 => VOL_WIDTH,
+                            3 => FX_WIDTH,
+                            _ => FXPARAM_WIDTH,
+                        };
+                        draw_rectangle(col_x, y, col_w, ROW_HEIGHT, CURSOR_COLOR);
+                    }
+
+                    // Note
+                    let note_str = note.pitch_name().unwrap_or_else(|| "---".to_string());
+                    let note_color = if note.pitch.is_some() { NOTE_COLOR } else { TEXT_DIM };
+                    draw_text(&note_str, x + 2.0, y + 14.0, 12.0, note_color);
+
+                    // Instrument
+                    let inst_str = note.instrument.map(|i| format!("{:02X}", i)).unwrap_or_else(|| "--".to_string());
+                    let inst_color = if note.instrument.is_some() { INST_COLOR } else { TEXT_DIM };
+                    draw_text(&inst_str, x + NOTE_WIDTH + 2.0, y + 14.0, 12.0, inst_color);
+
+                    // Volume
+                    let vol_str = note.volume.map(|v| format!("{:02X}", v)).unwrap_or_else(|| "--".to_string());
+                    let vol_color = if note.volume.is_some() { VOL_COLOR } else { TEXT_DIM };
+                    draw_text(&vol_str, x + NOTE_WIDTH + INST_WIDTH + 2.0, y + 14.0, 12.0, vol_color);
+
+                    // Flash the effect cell a knob just wrote
+                    if let Some((pat_idx, fx_row, fx_ch, written_at)) = state.written_effect {
+                        let age = (get_time() - written_at) as f32;
+                        if pat_idx == state.current_pattern_idx && fx_row == row_idx && fx_ch == ch && age < WRITE_FLASH_TIME {
+                            let alpha = 0.6 * (1.0 - age / WRITE_FLASH_TIME);
+                            draw_rectangle(x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH, y, FX_WIDTH + FXPARAM_WIDTH, ROW_HEIGHT, Color::new(FX_COLOR.r, FX_COLOR.g, FX_COLOR.b, alpha));
+                        }
+                    }
+
+                    // Effect
+                    let fx_str = note.effect.map(|e| e.to_string()).unwrap_or_else(|| "-".to_string());
+                    let fx_color = if note.effect.is_some() { FX_COLOR } else { TEXT_DIM };
+                    draw_text(&fx_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + 2.0, y + 14.0, 12.0, fx_color);
+
+                    // Effect param
+                    let fxp_str = note.effect_param.map(|p| format!("{:02X}", p)).unwrap_or_else(|| "--".to_string());
+                    draw_text(&fxp_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH + 2.0, y + 14.0, 12.0, fx_color);
+                }
+                ChannelDisplay::Narrow => {
+                    if is_cursor {
+                        draw_rectangle(x, y, NOTE_WIDTH, ROW_HEIGHT, CURSOR_COLOR);
+                    }
+                    let note_str = note.pitch_name().unwrap_or_else(|| "---".to_string());
+                    let note_color = if note.pitch.is_some() { NOTE_COLOR } else { TEXT_DIM };
+                    draw_text(&note_str, x + 2.0, y + 14.0, 12.0, note_color);
+                }
+                ChannelDisplay::Collapsed => {
+                    if is_cursor {
+                        draw_rectangle(x, y, COLLAPSED_CHANNEL_WIDTH - 1.0, ROW_HEIGHT, CURSOR_COLOR);
+                    }
+                    // Just a mark where notes are, to keep the song's shape visible
+                    if note.pitch.is_some() {
+                        draw_rectangle(x + 6.0, y + 6.0, 5.0, 5.0, NOTE_COLOR);
+                    }
+                }
             }
+        }
+    }
 
-            // Note
-            let note_str = note.pitch_name().unwrap_or_else(|| "---".to_string());
-            let note_color = if note.pitch.is_some() { NOTE_COLOR } else { TEXT_DIM };
-            draw_text(&note_str, x + 2.0, y + 14.0, 12.0, note_color);
+    // Channel separators
+    for ch in 0..num_channels {
+        let sep_x = channel_xs[ch] + widths[ch] - 1.0;
+        draw_line(sep_x, rect.y, sep_x, rect.y + rect.h - H_SCROLLBAR_HEIGHT, 1.0, Color::new(0.25, 0.25, 0.3, 1.0));
+    }
 
-            // Instrument
-            let inst_str = note.instrument.map(|i| format!("{:02X}", i)).unwrap_or_else(|| "--".to_string());
-            let inst_color = if note.instrument.is_some() { INST_COLOR } else { TEXT_DIM };
-            draw_text(&inst_str, x + NOTE_WIDTH + 2.0, y + 14.0, 12.0, inst_color);
+    // Channel being dragged: mark where it will land
+    let drop_target = state.dragging_channel.map(|_| {
+        channel_at(ctx.mouse.x).unwrap_or(if ctx.mouse.x < channels_rect.x { 0 } else { num_channels - 1 })
+    });
+    if let Some(target) = drop_target {
+        draw_rectangle_lines(channel_xs[target], rect.y, widths[target], rect.h - H_SCROLLBAR_HEIGHT, 2.0, NOTE_COLOR);
+    }
+    set_clip(None);
+
+    // Horizontal scrollbar
+    draw_rectangle(scrollbar_rect.x, scrollbar_rect.y, scrollbar_rect.w, scrollbar_rect.h, Color::new(0.15, 0.15, 0.18, 1.0));
+    if max_scroll_x > 0.0 {
+        let thumb_x = scrollbar_rect.x + state.scroll_x / max_scroll_x * (scrollbar_rect.w - thumb_w);
+        let thumb_color = if state.dragging_scrollbar { Color::new(0.45, 0.45, 0.5, 1.0) } else { Color::new(0.35, 0.35, 0.4, 1.0) };
+        draw_rectangle(thumb_x, scrollbar_rect.y + 1.0, thumb_w, scrollbar_rect.h - 2.0, thumb_color);
+    }
 
-            // Volume
-            let vol_str = note.volume.map(|v| format!("{:02X}", v)).unwrap_or_else(|| "--".to_string());
-            let vol_color = if note.volume.is_some() { VOL_COLOR } else { TEXT_DIM };
-            draw_text(&vol_str, x + NOTE_WIDTH + INST_WIDTH + 2.0, y + 14.0, 12.0, vol_color);
+    // Drop the dragged channel (after drawing, since it changes the layout)
+    if let (Some(from), Some(to)) = (state.dragging_channel, drop_target) {
+        if !ctx.mouse.left_down {
+            state.dragging_channel = None;
+            if from != to {
+                state.move_channel(from, to);
+                state.set_status(&format!("Moved channel {} to {}", from + 1, to + 1), 1.0);
+            }
+        }
+    }
+}
+
+/// Width of a channel column in the pattern view
+fn channel_width(display: ChannelDisplay) -> f32 {
+    match display {
+        ChannelDisplay::Full => CHANNEL_WIDTH,
+        ChannelDisplay::Narrow => NARROW_CHANNEL_WIDTH,
+        ChannelDisplay::Collapsed => COLLAPSED_CHANNEL_WIDTH,
+    }
+}
 
-            // Effect
-            let fx_str = note.effect.map(|e| e.to_string()).unwrap_or_else(|| "-".to_string());
-            let fx_color = if note.effect.is_some() { FX_COLOR } else { TEXT_DIM };
-            draw_text(&fx_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + 2.0, y + 14.0, 12.0, fx_color);
+/// Clip drawing to a rect (None turns clipping off)
+fn set_clip(rect: Option<Rect>) {
+    let dpi = ui_pixel_scale();
+    gl_use_default_material();
+    unsafe {
+        get_internal_gl().quad_gl.scissor(rect.map(|r| {
+            ((r.x * dpi) as i32, (r.y * dpi) as i32, (r.w * dpi) as i32, (r.h * dpi) as i32)
+        }));
+    }
+}
 
-            // Effect param
-            let fxp_str = note.effect_param.map(|p| format!("{:02X}", p)).unwrap_or_else(|| "--".to_string());
-            draw_text(&fxp_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH + 2.0, y + 14.0, 12.0, fx_color);
+/// Switch between recording notes into the pattern and jamming
+fn toggle_edit_mode(state: &mut TrackerState) {
+    state.edit_mode = !state.edit_mode;
+    state.set_status(&tr(if state.edit_mode { "tracker.record_on" } else { "tracker.jam_on" }), 1.0);
+}
 
-            x += CHANNEL_WIDTH;
-        }
+/// Toolbar icon and tooltip key of a playback mode
+fn playback_mode_icon(mode: PlaybackMode) -> (char, &'static str) {
+    match mode {
+        PlaybackMode::LoopPattern => (icon::REPEAT_1, "tracker.loop_pattern"),
+        PlaybackMode::LoopSong => (icon::REPEAT, "tracker.loop_song"),
+        PlaybackMode::PlayOnce => (icon::ARROW_RIGHT_TO_LINE, "tracker.play_once"),
     }
 }
 
-/// Draw the arrangement view (placeholder)
-fn draw_arrangement_view(_ctx: &mut UiContext, rect: Rect, state: &TrackerState) {
+/// Draw the arrangement view: the pattern order, and the song's cue points
+fn draw_arrangement_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState) {
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
 
     // Header
-    draw_text("Song Arrangement", rect.x + 10.0, rect.y + 24.0, 16.0, TEXT_COLOR);
+    draw_text(&tr("tracker.song_arrangement"), rect.x + 10.0, rect.y + 24.0, 16.0, TEXT_COLOR);
+
+    // Loop region: the song loops from its start to its end (toolbar loop mode)
+    let loop_rect = Rect::new(rect.x + 10.0, rect.y + 34.0, 96.0, 20.0);
+    if flat_button(ctx, loop_rect, &tr("tracker.loop_start"), false) {
+        state.song.loop_start = state.current_pattern_idx;
+        if state.song.loop_end.is_some_and(|end| end < state.current_pattern_idx) {
+            state.song.loop_end = None;
+        }
+        state.dirty = true;
+    }
+    if flat_button(ctx, Rect::new(loop_rect.right() + 8.0, loop_rect.y, 96.0, 20.0), &tr("tracker.loop_end"), false) {
+        let last = state.song.arrangement.len().saturating_sub(1);
+        state.song.loop_end = (state.current_pattern_idx < last).then_some(state.current_pattern_idx);
+        state.song.loop_start = state.song.loop_start.min(state.current_pattern_idx);
+        state.dirty = true;
+    }
 
-    // Draw arrangement as list
-    let mut y = rect.y + 50.0;
+    // Draw arrangement as list (click a pattern to put the cursor on it)
+    let mut y = rect.y + 62.0;
+    let mut pick = None;
+    let (loop_start, loop_end) = state.song.loop_range();
     for (i, &pattern_idx) in state.song.arrangement.iter().enumerate() {
         let is_current = i == state.current_pattern_idx;
         let bg = if is_current { ROW_HIGHLIGHT } else if i % 2 == 0 { ROW_EVEN } else { ROW_ODD };
-        draw_rectangle(rect.x + 10.0, y, 200.0, 24.0, bg);
+        let row = Rect::new(rect.x + 10.0, y, 200.0, 24.0);
+        draw_rectangle(row.x, row.y, row.w, row.h, bg);
+        if (loop_start..=loop_end).contains(&i) {
+            draw_rectangle(row.x, row.y, 3.0, row.h, FX_COLOR);
+        }
         draw_text(
             &format!("{:02}: Pattern {:02}", i, pattern_idx),
             rect.x + 20.0, y + 16.0, 14.0,
             if is_current { NOTE_COLOR } else { TEXT_COLOR }
         );
+        let marker = match (i == loop_start, i == loop_end) {
+            (true, true) => "[ ]",
+            (true, false) => "[",
+            (false, true) => "]",
+            _ => "",
+        };
+        draw_text(marker, row.right() - 28.0, y + 16.0, 14.0, FX_COLOR);
+        if ctx.mouse.clicked(&row) {
+            pick = Some(i);
+        }
         y += 26.0;
     }
+    if let Some(i) = pick {
+        state.current_pattern_idx = i;
+        state.current_row = 0;
+        state.scroll_row = 0;
+    }
 
     draw_text("(Press + to add pattern, - to remove)", rect.x + 10.0, rect.y + rect.h - 30.0, 12.0, TEXT_DIM);
+
+    draw_cue_list(ctx, Rect::new(rect.x + 240.0, rect.y, (rect.w - 250.0).max(0.0), rect.h), state);
+}
+
+/// Flat button in the tracker's colors
+fn flat_button(ctx: &mut UiContext, rect: Rect, label: &str, active: bool) -> bool {
+    let hovered = ctx.mouse.inside(&rect);
+    let bg = if active {
+        Color::new(0.45, 0.3, 0.5, 1.0)
+    } else if hovered {
+        Color::new(0.25, 0.25, 0.3, 1.0)
+    } else {
+        Color::new(0.18, 0.18, 0.22, 1.0)
+    };
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, bg);
+    draw_text(label, rect.x + 6.0, rect.y + rect.h - 6.0, 12.0, TEXT_COLOR);
+    ctx.mouse.clicked(&rect)
+}
+
+/// Cue points: jump ("Go", immediately or with a fade), rename ("Aa"),
+/// delete ("x"), and add one at the cursor
+fn draw_cue_list(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState) {
+    draw_text(&tr("tracker.cues"), rect.x, rect.y + 24.0, 16.0, TEXT_COLOR);
+
+    // Add at the cursor, and how jumps switch
+    let add_rect = Rect::new(rect.x, rect.y + 34.0, 110.0, 20.0);
+    if flat_button(ctx, add_rect, &tr("tracker.cue_add"), false) {
+        state.renaming_cue = None;
+        let index = state.add_cue();
+        state.set_status(&format!("Added {}", state.song.cues[index].name), 1.5);
+    }
+    let fade = state.cue_switch == CueSwitch::Fade;
+    let switch_label = tr(if fade { "tracker.cue_fade" } else { "tracker.cue_immediate" });
+    if flat_button(ctx, Rect::new(add_rect.right() + 8.0, add_rect.y, 120.0, 20.0), &switch_label, fade) {
+        state.cue_switch = if fade { CueSwitch::Immediate } else { CueSwitch::Fade };
+    }
+
+    // Typing into the cue being renamed: Enter keeps the name, Escape cancels
+    if let Some((index, mut name)) = state.renaming_cue.take() {
+        match edit_text(ctx, CUE_NAME_FOCUS, &mut name, 32) {
+            TextEdit::Typing if index < state.song.cues.len() => state.renaming_cue = Some((index, name)),
+            TextEdit::Commit => state.rename_cue(index, &name),
+            _ => {}
+        }
+    }
+
+    let mut y = rect.y + 62.0;
+    let (mut jump, mut rename, mut delete) = (None, None, None);
+    for (i, cue) in state.song.cues.iter().enumerate() {
+        let row = Rect::new(rect.x, y, rect.w.min(360.0), 22.0);
+        draw_rectangle(row.x, row.y, row.w, row.h, if i % 2 == 0 { ROW_EVEN } else { ROW_ODD });
+        let label = match &state.renaming_cue {
+            Some((index, name)) if *index == i => format!("{}_", name),
+            _ => cue.name.clone(),
+        };
+        draw_text(&label, row.x + 8.0, row.y + 15.0, 14.0, NOTE_COLOR);
+        let place = format!("{:02}:{:02X}", cue.position, cue.row);
+        draw_text(&place, row.right() - 150.0, row.y + 15.0, 14.0, TEXT_DIM);
+        if flat_button(ctx, Rect::new(row.right() - 96.0, row.y + 2.0, 34.0, 18.0), "Go", false) {
+            jump = Some(i);
+        }
+        if flat_button(ctx, Rect::new(row.right() - 58.0, row.y + 2.0, 28.0, 18.0), "Aa", false) {
+            rename = Some(i);
+        }
+        if flat_button(ctx, Rect::new(row.right() - 26.0, row.y + 2.0, 22.0, 18.0), "x", false) {
+            delete = Some(i);
+        }
+        y += 24.0;
+    }
+    if state.song.cues.is_empty() {
+        draw_text("No cue points: add one at the cursor", rect.x, y + 14.0, 12.0, TEXT_DIM);
+    }
+
+    if let Some(i) = jump {
+        let switch = state.cue_switch;
+        state.jump_to_cue(i, switch);
+    }
+    if let Some(i) = rename {
+        state.renaming_cue = Some((i, state.song.cues[i].name.clone()));
+        ctx.set_focus(CUE_NAME_FOCUS);
+    }
+    if let Some(i) = delete {
+        state.renaming_cue = None;
+        state.remove_cue(i);
+    }
+}
+
+/// The project's songs: click one to edit it, A/B flips back to the song
+/// edited before. Renaming and deleting ask in a dialog.
+fn draw_song_browser(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState) {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, HEADER_COLOR);
+    draw_line(rect.right() - 1.0, rect.y, rect.right() - 1.0, rect.bottom(), 1.0, BG_COLOR);
+    draw_text(&tr("tracker.songs"), rect.x + 8.0, rect.y + 20.0, 14.0, TEXT_COLOR);
+
+    // Two rows of buttons, three each
+    let button_w = ((rect.w - 8.0 * 4.0) / 3.0).floor();
+    let button = |column: usize, row: usize| {
+        Rect::new(rect.x + 8.0 + column as f32 * (button_w + 8.0), rect.y + 30.0 + row as f32 * 24.0, button_w, 20.0)
+    };
+    if flat_button(ctx, button(0, 0), &tr("tracker.song_new"), false) {
+        state.new_song();
+    }
+    if flat_button(ctx, button(1, 0), &tr("tracker.song_copy"), false) {
+        state.duplicate_song();
+    }
+    let slot_id = state.library.get(state.current_song).map(|s| s.id.clone()).unwrap_or_default();
+    if flat_button(ctx, button(2, 0), &tr("tracker.song_rename"), false) {
+        state.renaming_cue = None;
+        let message = trf("tracker.song_rename_message", &[&slot_id]);
+        ctx.open_dialog(RENAME_SONG_DIALOG, Dialog::prompt(&tr("tracker.song_rename"), &message, &state.song.name));
+    }
+    if flat_button(ctx, button(0, 1), &tr("tracker.song_delete"), false) {
+        let message = trf("tracker.song_delete_message", &[&state.song.name, &slot_id]);
+        ctx.open_dialog(DELETE_SONG_DIALOG, Dialog::confirm(&tr("tracker.song_delete"), &message, &tr("tracker.song_delete")));
+    }
+    if flat_button(ctx, button(1, 1), "A/B", false) {
+        state.swap_songs();
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if flat_button(ctx, button(2, 1), &tr("tracker.song_save"), false) {
+            save_songs(state);
+        }
+    }
+
+    // Answers to the dialogs (button 0 is OK / Delete)
+    if let Some(reply) = ctx.take_reply(RENAME_SONG_DIALOG) {
+        if reply.button == 0 {
+            state.rename_song(&reply.text);
+        }
+    }
+    if let Some(reply) = ctx.take_reply(DELETE_SONG_DIALOG) {
+        if reply.button == 0 {
+            let index = state.current_song;
+            if let Err(e) = state.delete_song(index) {
+                state.set_status(&e, 3.0);
+            }
+        }
+    }
+
+    // The songs: name, with the id levels reference it by below
+    let mut y = rect.y + 82.0;
+    let mut pick = None;
+    for (i, slot) in state.library.iter().enumerate() {
+        if y + 32.0 > rect.bottom() {
+            break;
+        }
+        let row = Rect::new(rect.x + 4.0, y, rect.w - 8.0, 32.0);
+        let is_current = i == state.current_song;
+        let bg = if is_current { ROW_HIGHLIGHT } else if i % 2 == 0 { ROW_EVEN } else { ROW_ODD };
+        draw_rectangle(row.x, row.y, row.w, row.h, bg);
+        let name = if is_current { &state.song.name } else { &slot.song.name };
+        let marker = if state.song_dirty(i) { "*" } else { "" };
+        draw_text(&format!("{}{}", name, marker), row.x + 6.0, row.y + 14.0, 14.0, if is_current { NOTE_COLOR } else { TEXT_COLOR });
+        let ab = if state.previous_song == Some(i) { "  (B)" } else { "" };
+        draw_text(&format!("{}{}", slot.id, ab), row.x + 6.0, row.y + 27.0, 12.0, TEXT_DIM);
+        if ctx.mouse.clicked(&row) {
+            pick = Some(i);
+        }
+        y += 34.0;
+    }
+    if let Some(i) = pick {
+        state.switch_song(i);
+    }
+}
+
+/// Save the changed songs, reporting in the status bar
+#[cfg(not(target_arch = "wasm32"))]
+fn save_songs(state: &mut TrackerState) {
+    match state.save_songs() {
+        Ok(0) => state.set_status("Nothing to save", 1.5),
+        Ok(n) => state.set_status(&format!("Saved {} song(s) to {}", n, super::library::SONGS_DIR), 2.0),
+        Err(e) => state.set_status(&e, 4.0),
+    }
+}
+
+/// The tracker's part of the app-wide shortcuts: New adds a song, Save
+/// writes the changed ones (there's no undo history yet)
+pub fn handle_tracker_shortcut(state: &mut TrackerState, shortcut: Shortcut) {
+    match shortcut {
+        Shortcut::New => state.new_song(),
+        #[cfg(not(target_arch = "wasm32"))]
+        Shortcut::Save | Shortcut::SaveAs => save_songs(state),
+        #[cfg(target_arch = "wasm32")]
+        Shortcut::Save | Shortcut::SaveAs => state.set_status("Songs can only be saved in the desktop app", 2.0),
+        Shortcut::Open => state.set_status("Pick a song in the Songs sidebar", 1.5),
+        Shortcut::Undo | Shortcut::Redo => state.set_status("The tracker has no undo yet", 1.5),
+    }
 }
 
 /// Piano key layout for drawing
@@ -494,10 +966,34 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
 
     // === LEFT: Instrument List ===
     draw_rectangle(list_rect.x, list_rect.y, list_rect.w, list_rect.h, Color::new(0.09, 0.09, 0.11, 1.0));
-    draw_text("Instruments (GM)", list_rect.x + 10.0, list_rect.y + 20.0, 14.0, TEXT_COLOR);
+    draw_text(&tr("tracker.instruments_gm"), list_rect.x + 10.0, list_rect.y + 20.0, 14.0, TEXT_COLOR);
+
+    // Load a WAV file as a sample instrument
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let load_rect = Rect::new(list_rect.right() - 95.0, list_rect.y + 7.0, 85.0, 18.0);
+        let load_hovered = ctx.mouse.inside(&load_rect);
+        draw_rectangle(load_rect.x, load_rect.y, load_rect.w, load_rect.h,
+            if load_hovered { Color::new(0.25, 0.25, 0.3, 1.0) } else { Color::new(0.18, 0.18, 0.22, 1.0) });
+        draw_text(&tr("tracker.load_sample"), load_rect.x + 6.0, load_rect.y + 13.0, 12.0, TEXT_COLOR);
+        if load_hovered && is_mouse_button_pressed(MouseButton::Left) {
+            let dialog = rfd::FileDialog::new().add_filter("WAV Sample", &["wav"]);
+            if let Some(path) = dialog.pick_file() {
+                match state.add_sample_instrument(&path) {
+                    Ok(()) => {
+                        let name = state.instrument_name(state.current_instrument());
+                        state.set_status(&format!("Loaded sample: {}", name), 2.0);
+                    }
+                    Err(e) => state.set_status(&e, 3.0),
+                }
+            }
+        }
+    }
 
-    // Scrollable instrument list
-    let presets = state.audio.get_preset_names();
+    // Scrollable instrument list: GM presets, then the song's samples
+    let mut presets = state.audio.get_preset_names().as_ref().clone();
+    presets.extend(state.song.samples.iter().enumerate()
+        .map(|(i, s)| (0, SAMPLE_PROGRAM_BASE + i as u8, s.name.clone())));
     let item_height = 18.0;
     let list_start_y = list_rect.y + 35.0;
     let list_height = list_rect.h - 45.0;
@@ -507,7 +1003,7 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
     // Handle mouse wheel scrolling over the instrument list
     let list_content_rect = Rect::new(list_rect.x, list_start_y, list_rect.w, list_height);
     if ctx.mouse.inside(&list_content_rect) {
-        let scroll = mouse_wheel().1;
+        let scroll = ctx.mouse.scroll;
         if scroll != 0.0 {
             let scroll_amount = if scroll > 0.0 { -3 } else { 3 }; // Scroll 3 items at a time
             let new_scroll = (state.instrument_scroll as i32 + scroll_amount).max(0) as usize;
@@ -653,10 +1149,7 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
     // Current instrument info below piano
     let info_y = piano_y + white_key_h + 30.0;
     let current_inst = state.current_instrument();
-    let current_name = presets.iter()
-        .find(|(_, p, _)| *p == current_inst)
-        .map(|(_, _, n)| n.as_str())
-        .unwrap_or("Unknown");
+    let current_name = state.instrument_name(current_inst);
 
     draw_text(&format!("Current: {:03} - {}", current_inst, current_name),
               piano_x, info_y, 16.0, INST_COLOR);
@@ -665,7 +1158,7 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
     let effects_y = info_y + 30.0;
     let ch = state.current_channel;
 
-    draw_text("Effects Preview", piano_x, effects_y, 14.0, TEXT_COLOR);
+    draw_text(&tr("tracker.effects_preview"), piano_x, effects_y, 14.0, TEXT_COLOR);
 
     let knob_radius = 28.0;
     let knob_spacing = 70.0;
@@ -680,52 +1173,16 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
         (4, "Expr", state.preview_expression[ch], false),
     ];
 
-    // Handle text input for knob editing
+    // Typing a knob value (digits only): Enter sets it, Escape cancels
     if let Some(editing_idx) = state.editing_knob {
-        // Handle keyboard input for editing
-        for key in 0..10 {
-            let keycode = match key {
-                0 => KeyCode::Key0,
-                1 => KeyCode::Key1,
-                2 => KeyCode::Key2,
-                3 => KeyCode::Key3,
-                4 => KeyCode::Key4,
-                5 => KeyCode::Key5,
-                6 => KeyCode::Key6,
-                7 => KeyCode::Key7,
-                8 => KeyCode::Key8,
-                9 => KeyCode::Key9,
-                _ => continue,
-            };
-            if is_key_pressed(keycode) && state.knob_edit_text.len() < 3 {
-                state.knob_edit_text.push(char::from_digit(key as u32, 10).unwrap());
-            }
-        }
-
-        // Backspace
-        if is_key_pressed(KeyCode::Backspace) {
-            state.knob_edit_text.pop();
-        }
-
-        // Enter to confirm
-        if is_key_pressed(KeyCode::Enter) {
+        let edit = edit_text(ctx, KNOB_FOCUS, &mut state.knob_edit_text, 3);
+        state.knob_edit_text.retain(|c| c.is_ascii_digit());
+        if edit == TextEdit::Commit {
             if let Ok(val) = state.knob_edit_text.parse::<u8>() {
-                let clamped = val.min(127);
-                match editing_idx {
-                    0 => state.set_preview_pan(clamped),
-                    1 => state.set_preview_reverb(clamped),
-                    2 => state.set_preview_chorus(clamped),
-                    3 => state.set_preview_modulation(clamped),
-                    4 => state.set_preview_expression(clamped),
-                    _ => {}
-                }
+                state.set_knob(editing_idx, val.min(127));
             }
-            state.editing_knob = None;
-            state.knob_edit_text.clear();
         }
-
-        // Escape to cancel
-        if is_key_pressed(KeyCode::Escape) {
+        if edit != TextEdit::Typing {
             state.editing_knob = None;
             state.knob_edit_text.clear();
         }
@@ -749,20 +1206,14 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
 
         // Handle knob value change
         if let Some(new_val) = result.value {
-            match idx {
-                0 => state.set_preview_pan(new_val),
-                1 => state.set_preview_reverb(new_val),
-                2 => state.set_preview_chorus(new_val),
-                3 => state.set_preview_modulation(new_val),
-                4 => state.set_preview_expression(new_val),
-                _ => {}
-            }
+            state.set_knob(*idx, new_val);
         }
 
         // Handle editing start
         if result.editing {
             state.editing_knob = Some(*idx);
             state.knob_edit_text = format!("{}", value);
+            ctx.set_focus(KNOB_FOCUS);
         }
     }
 
@@ -773,25 +1224,137 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
 
     draw_rectangle(reset_rect.x, reset_rect.y, reset_rect.w, reset_rect.h,
         if reset_hovered { Color::new(0.25, 0.25, 0.3, 1.0) } else { Color::new(0.18, 0.18, 0.22, 1.0) });
-    draw_text("Reset All", reset_rect.x + 22.0, reset_rect.y + 14.0, 12.0, TEXT_COLOR);
+    draw_text(&tr("tracker.reset_all"), reset_rect.x + 22.0, reset_rect.y + 14.0, 12.0, TEXT_COLOR);
 
     if reset_hovered && is_mouse_button_pressed(MouseButton::Left) {
         state.reset_preview_effects();
         state.set_status("Effects reset to defaults", 1.0);
     }
 
+    // Write-to-pattern toggle: knob changes become effect commands at the cursor row
+    let write_rect = Rect::new(reset_rect.right() + 10.0, reset_y, 160.0, 20.0);
+    let write_hovered = ctx.mouse.inside(&write_rect);
+    let write_bg = if state.write_effects {
+        Color::new(0.45, 0.3, 0.5, 1.0)
+    } else if write_hovered {
+        Color::new(0.25, 0.25, 0.3, 1.0)
+    } else {
+        Color::new(0.18, 0.18, 0.22, 1.0)
+    };
+    draw_rectangle(write_rect.x, write_rect.y, write_rect.w, write_rect.h, write_bg);
+    let write_label = if state.write_effects {
+        format!("{} {:02X}", tr("tracker.write_to_row"), state.current_row)
+    } else {
+        tr("tracker.write_to_pattern")
+    };
+    draw_text(&write_label, write_rect.x + 8.0, write_rect.y + 14.0, 12.0, if state.write_effects { FX_COLOR } else { TEXT_COLOR });
+
+    if write_hovered && is_mouse_button_pressed(MouseButton::Left) {
+        state.write_effects = !state.write_effects;
+    }
+
     // Help text
     let help_y = reset_y + 35.0;
     draw_text("Click keys to preview | Use keyboard (Z-M, Q-U) to enter notes",
               piano_x, help_y, 12.0, TEXT_DIM);
     draw_text("[ ] = prev/next instrument | +/- = octave up/down",
               piano_x, help_y + 17.0, 12.0, TEXT_DIM);
-    draw_text("Drag knobs to adjust | Click value to type",
+    draw_text("Drag knobs to adjust | Click value to type | Write mode stores them at the cursor row",
               piano_x, help_y + 34.0, 12.0, TEXT_DIM);
+
+    if let Some(index) = state.current_sample_index() {
+        draw_sample_editor(ctx, piano_x, help_y + 65.0, knob_radius, knob_spacing, index, state);
+    }
+}
+
+/// Base note, volume, envelope and loop of the current channel's sample instrument
+fn draw_sample_editor(
+    ctx: &mut UiContext,
+    x: f32,
+    y: f32,
+    knob_radius: f32,
+    knob_spacing: f32,
+    index: usize,
+    state: &mut TrackerState,
+) {
+    draw_text(&tr("tracker.sample_instrument"), x, y, 14.0, TEXT_COLOR);
+
+    // Envelope times map to the knob on a square curve (finer control of short times)
+    let time_to_knob = |t: f32| ((t / MAX_ENVELOPE_TIME).sqrt() * 127.0).round().clamp(0.0, 127.0) as u8;
+    let knob_to_time = |v: u8| (v as f32 / 127.0).powi(2) * MAX_ENVELOPE_TIME;
+
+    let def = state.song.samples[index].clone();
+    let knob_data = [
+        ("Base", def.base_note),
+        ("Vol", (def.volume * 100.0).round().clamp(0.0, 127.0) as u8),
+        ("Atk", time_to_knob(def.envelope.attack)),
+        ("Dec", time_to_knob(def.envelope.decay)),
+        ("Sus", (def.envelope.sustain * 127.0).round() as u8),
+        ("Rel", time_to_knob(def.envelope.release)),
+    ];
+
+    let knob_y = y + 50.0;
+    let mut changed = None;
+    for (i, (label, value)) in knob_data.iter().enumerate() {
+        let knob_x = x + 35.0 + i as f32 * knob_spacing;
+        let result = draw_knob(ctx, knob_x, knob_y, knob_radius, *value, label, false, false);
+        if let Some(new_val) = result.value {
+            changed = Some((i, new_val));
+        }
+    }
+
+    if let Some((i, v)) = changed {
+        let def = &mut state.song.samples[index];
+        match i {
+            0 => def.base_note = v,
+            1 => def.volume = v as f32 / 100.0,
+            2 => def.envelope.attack = knob_to_time(v),
+            3 => def.envelope.decay = knob_to_time(v),
+            4 => def.envelope.sustain = v as f32 / 127.0,
+            _ => def.envelope.release = knob_to_time(v),
+        }
+        state.apply_sample_instrument(index);
+        state.dirty = true;
+    }
+
+    // Loop toggle: one-shot, or looping the whole sample
+    let loop_y = knob_y + knob_radius + 35.0;
+    let loop_rect = Rect::new(x, loop_y, 160.0, 20.0);
+    let loop_hovered = ctx.mouse.inside(&loop_rect);
+    let looped = def.loop_range;
+    draw_rectangle(loop_rect.x, loop_rect.y, loop_rect.w, loop_rect.h, if looped.is_some() {
+        Color::new(0.3, 0.4, 0.5, 1.0)
+    } else if loop_hovered {
+        Color::new(0.25, 0.25, 0.3, 1.0)
+    } else {
+        Color::new(0.18, 0.18, 0.22, 1.0)
+    });
+    let loop_label = match looped {
+        Some((start, end)) => format!("{} {}-{}", tr("tracker.sample_loop"), start, end),
+        None => tr("tracker.sample_one_shot"),
+    };
+    draw_text(&loop_label, loop_rect.x + 8.0, loop_rect.y + 14.0, 12.0, TEXT_COLOR);
+    if loop_hovered && is_mouse_button_pressed(MouseButton::Left) {
+        let len = state.sample_len(index);
+        state.song.samples[index].loop_range = match looped {
+            Some(_) => None,
+            None => Some((0, len)),
+        };
+        state.apply_sample_instrument(index);
+        state.dirty = true;
+    }
+
+    draw_text(&format!("{} ({} frames)", def.path, state.sample_len(index)),
+              x, loop_y + 38.0, 12.0, TEXT_DIM);
 }
 
 /// Handle keyboard and mouse input
-fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
+fn handle_input(ctx: &mut UiContext, state: &mut TrackerState) {
+    // Keys go elsewhere: a text field, the song browser or a dialog
+    if !ctx.accepts_keys(VIEW_FOCUS) {
+        return;
+    }
+
     // Navigation
     if is_key_pressed(KeyCode::Up) {
         state.cursor_up();
@@ -836,8 +1399,13 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
         }
     }
 
-    // Playback
+    // Record (notes go into the pattern) or jam (notes only play)
     if is_key_pressed(KeyCode::Space) {
+        toggle_edit_mode(state);
+    }
+
+    // Playback
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
         state.toggle_playback();
     }
     if is_key_pressed(KeyCode::Escape) {
@@ -861,7 +1429,7 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
         state.set_status(&format!("Instrument: {:02}", new_inst), 1.0);
     }
     if is_key_pressed(KeyCode::RightBracket) {
-        let new_inst = (state.current_instrument() + 1).min(127);
+        let new_inst = (state.current_instrument() + 1).min(state.last_instrument());
         state.set_current_instrument(new_inst);
         state.set_status(&format!("Instrument: {:02}", new_inst), 1.0);
     }
@@ -877,10 +1445,15 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
     }
 
     // Delete
-    if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
+    if state.edit_mode && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace)) {
         state.delete_note();
     }
 
+    // Ctrl / Cmd combinations are app-wide shortcuts, not notes (Ctrl+Z would be a C)
+    if shortcut_modifier_down() {
+        return;
+    }
+
     // Note entry (only in Pattern view, when in edit mode and in note column)
     if state.view == TrackerView::Pattern && state.edit_mode && state.current_column == 0 {
         // Check for note keys
@@ -909,7 +1482,7 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
 
     // Effect entry (in Pattern view, edit mode, effect column = 3)
     if state.view == TrackerView::Pattern && state.edit_mode && state.current_column == 3 {
-        // Effect letters: 0-9, A-F for standard effects, + our new ones (C, E, H, M, P, R)
+        // Effect letters: 0-9, A-F for standard effects, + our new ones (C, E, G, H, M, P, R)
         let effect_keys = [
             (KeyCode::Key0, '0'), (KeyCode::Key1, '1'), (KeyCode::Key2, '2'),
             (KeyCode::Key3, '3'), (KeyCode::Key4, '4'), (KeyCode::Key5, '5'),
@@ -917,7 +1490,7 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
             (KeyCode::Key9, '9'),
             (KeyCode::A, 'A'), (KeyCode::B, 'B'), (KeyCode::C, 'C'),
             (KeyCode::D, 'D'), (KeyCode::E, 'E'), (KeyCode::F, 'F'),
-            (KeyCode::H, 'H'), (KeyCode::M, 'M'), (KeyCode::P, 'P'), (KeyCode::R, 'R'),
+            (KeyCode::G, 'G'), (KeyCode::H, 'H'), (KeyCode::M, 'M'), (KeyCode::P, 'P'), (KeyCode::R, 'R'),
         ];
 
         for (key, ch) in effect_keys {
@@ -953,8 +1526,8 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
         }
     }
 
-    // In Instruments view, allow keyboard to preview sounds without entering notes
-    if state.view == TrackerView::Instruments {
+    // In Instruments view, and in the pattern while jamming, keys preview sounds without entering notes
+    if state.view == TrackerView::Instruments || (state.view == TrackerView::Pattern && !state.edit_mode) {
         let note_keys = [
             KeyCode::Z, KeyCode::S, KeyCode::X, KeyCode::D, KeyCode::C,
             KeyCode::V, KeyCode::G, KeyCode::B, KeyCode::H, KeyCode::N,
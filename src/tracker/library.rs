@@ -0,0 +1,109 @@
+//! Song library - the project's songs, one RON file each
+//!
+//! Songs live in `assets/songs/<id>.ron`. The id is the file stem and stays
+//! put when a song is renamed (only `Song::name` changes), so levels and the
+//! game manifest reference songs by it.
+
+use std::path::PathBuf;
+use super::pattern::Song;
+
+/// Directory holding the project's songs
+pub const SONGS_DIR: &str = "assets/songs";
+
+/// A song of the library
+#[derive(Debug, Clone)]
+pub struct SongSlot {
+    /// File stem, what levels reference
+    pub id: String,
+    pub song: Song,
+    /// Changed since it was loaded or saved
+    pub dirty: bool,
+}
+
+/// File of the song with id `id`
+pub fn song_path(id: &str) -> PathBuf {
+    PathBuf::from(SONGS_DIR).join(format!("{}.ron", id))
+}
+
+/// An id not used in the library yet, `base` or `base_2`, `base_3`...
+pub fn unique_song_id(library: &[SongSlot], base: &str) -> String {
+    let mut id = base.to_string();
+    let mut n = 2;
+    while library.iter().any(|s| s.id == id) {
+        id = format!("{}_{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// Ids of the songs in `SONGS_DIR`, sorted
+#[cfg(not(target_arch = "wasm32"))]
+pub fn list_song_ids() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(SONGS_DIR) else { return Vec::new() };
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Every song in `SONGS_DIR` (songs that fail to parse are logged and skipped)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_song_library() -> Vec<SongSlot> {
+    list_song_ids()
+        .into_iter()
+        .filter_map(|id| {
+            let path = song_path(&id);
+            let song = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| ron::from_str::<Song>(&s).map_err(|e| e.to_string()));
+            match song {
+                Ok(song) => Some(SongSlot { id, song, dirty: false }),
+                Err(e) => {
+                    log::error!("Failed to load song {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Write a song to its file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_song(id: &str, song: &Song) -> Result<(), String> {
+    let path = song_path(id);
+    std::fs::create_dir_all(SONGS_DIR).map_err(|e| format!("Failed to create {}: {}", SONGS_DIR, e))?;
+    let text = ron::ser::to_string_pretty(song, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize song: {}", e))?;
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Remove a song's file (a song never saved has none)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn delete_song_file(id: &str) -> Result<(), String> {
+    let path = song_path(id);
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to delete {}: {}", path.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_song_id_skips_taken_ids() {
+        let slot = |id: &str| SongSlot { id: id.to_string(), song: Song::new(), dirty: false };
+        assert_eq!(unique_song_id(&[], "song"), "song");
+        let library = [slot("song"), slot("song_2"), slot("boss")];
+        assert_eq!(unique_song_id(&library, "song"), "song_3");
+        assert_eq!(unique_song_id(&library, "boss"), "boss_2");
+        assert_eq!(song_path("boss"), PathBuf::from("assets/songs/boss.ron"));
+    }
+}
@@ -1,14 +1,25 @@
 //! Tracker/Music Editor
 //!
-//! A pattern-based music tracker with SF2 soundfont support.
+//! A pattern-based music tracker with SF2 soundfont and WAV sample instruments.
+//! The soundfont loads lazily, the first time the tab is opened. A project
+//! holds several songs (see `library`), browsed in the tracker's sidebar.
 //! Inspired by Picotron's tracker design.
 
 mod state;
 mod audio;
+mod soundfont;
+mod sampler;
+mod reverb;
 mod pattern;
 mod layout;
+mod library;
 
 pub use state::TrackerState;
 pub use audio::AudioEngine;
+pub use reverb::ReverbParams;
+pub use sampler::{SampleInstrument, SampleInstrumentDef, SAMPLE_PROGRAM_BASE};
+#[cfg(not(target_arch = "wasm32"))]
+pub use sampler::load_wav;
 pub use pattern::*;
-pub use layout::draw_tracker;
+pub use layout::{draw_tracker, handle_tracker_shortcut};
+pub use library::*;
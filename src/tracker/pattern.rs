@@ -1,6 +1,7 @@
 //! Pattern and song data structures
 
 use serde::{Deserialize, Serialize};
+use super::sampler::SampleInstrumentDef;
 
 /// A single note event in the tracker
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -56,6 +57,14 @@ impl Note {
             && self.effect.is_none()
     }
 
+    /// Ticks this row's notes are delayed by (effect Gxx), 0 if none
+    pub fn delay_ticks(&self) -> u8 {
+        match (self.effect, self.effect_param) {
+            (Some('G'), Some(ticks)) => ticks.min(TICKS_PER_ROW - 1),
+            _ => 0,
+        }
+    }
+
     /// Check if this is a note-off
     pub fn is_off(&self) -> bool {
         self.pitch == Some(0xFF)
@@ -91,6 +100,15 @@ pub const DEFAULT_CHANNELS: usize = 4;
 /// Default pattern length (rows)
 pub const DEFAULT_PATTERN_LEN: usize = 64;
 
+/// Subdivisions of a row for note delays (effect Gxx)
+pub const TICKS_PER_ROW: u8 = 16;
+
+/// Maximum swing, in percent of a row
+pub const MAX_SWING: u8 = 75;
+
+/// Largest offset (in rows) a row's notes can be pushed back, so they never reach the next row
+const MAX_ROW_OFFSET: f64 = 0.95;
+
 /// A pattern is a grid of notes across channels
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pattern {
@@ -98,6 +116,9 @@ pub struct Pattern {
     pub length: usize,
     /// Notes per channel [channel][row] - using Vec for serde compatibility
     pub channels: Vec<Vec<Note>>,
+    /// Swing for this pattern in percent, None = use the song's
+    #[serde(default)]
+    pub swing: Option<u8>,
 }
 
 impl Pattern {
@@ -111,6 +132,7 @@ impl Pattern {
         Self {
             length: len,
             channels: vec![vec![Note::EMPTY; len]; ch_count],
+            swing: None,
         }
     }
 
@@ -133,6 +155,14 @@ impl Pattern {
         self.channels.len()
     }
 
+    /// Move a channel's notes to another position, shifting the ones between
+    pub fn move_channel(&mut self, from: usize, to: usize) {
+        if from < self.channels.len() && to < self.channels.len() {
+            let notes = self.channels.remove(from);
+            self.channels.insert(to, notes);
+        }
+    }
+
     /// Get a note at a specific position
     pub fn get(&self, channel: usize, row: usize) -> Option<&Note> {
         self.channels.get(channel)?.get(row)
@@ -154,6 +184,26 @@ impl Default for Pattern {
     }
 }
 
+/// A named place in the song playback can jump to ("combat start", "boss phase 2")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CuePoint {
+    pub name: String,
+    /// Index into the arrangement
+    pub position: usize,
+    /// Row within that pattern
+    pub row: usize,
+}
+
+/// How playback moves to a cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CueSwitch {
+    /// Jump on the next frame
+    #[default]
+    Immediate,
+    /// Fade out, jump, fade back in (one synth can't play both sides of a true crossfade)
+    Fade,
+}
+
 /// A song is a sequence of pattern indices (arrangement)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Song {
@@ -171,6 +221,21 @@ pub struct Song {
     pub instrument_names: Vec<String>,
     /// Per-channel instrument (GM program number 0-127)
     pub channel_instruments: Vec<u8>,
+    /// Swing in percent of a row: every odd row is played this much late
+    #[serde(default)]
+    pub swing: u8,
+    /// Sample instruments, played by channel programs from `SAMPLE_PROGRAM_BASE` up
+    #[serde(default)]
+    pub samples: Vec<SampleInstrumentDef>,
+    /// Cue points the game (level events) and the arrangement view jump to
+    #[serde(default)]
+    pub cues: Vec<CuePoint>,
+    /// Arrangement index a looping song goes back to
+    #[serde(default)]
+    pub loop_start: usize,
+    /// Last arrangement index before looping back (None = the end)
+    #[serde(default)]
+    pub loop_end: Option<usize>,
 }
 
 impl Song {
@@ -183,6 +248,11 @@ impl Song {
             arrangement: vec![0],
             instrument_names: Vec::new(),
             channel_instruments: vec![0; DEFAULT_CHANNELS], // Piano for all channels
+            swing: 0,
+            samples: Vec::new(),
+            cues: Vec::new(),
+            loop_start: 0,
+            loop_end: None,
         }
     }
 
@@ -213,6 +283,19 @@ impl Song {
         }
     }
 
+    /// Move a channel (instrument and notes in every pattern) to another position
+    pub fn move_channel(&mut self, from: usize, to: usize) {
+        let count = self.channel_instruments.len();
+        if from >= count || to >= count || from == to {
+            return;
+        }
+        let instrument = self.channel_instruments.remove(from);
+        self.channel_instruments.insert(to, instrument);
+        for pattern in &mut self.patterns {
+            pattern.move_channel(from, to);
+        }
+    }
+
     /// Set instrument for a channel
     pub fn set_channel_instrument(&mut self, channel: usize, instrument: u8) {
         if let Some(inst) = self.channel_instruments.get_mut(channel) {
@@ -242,6 +325,38 @@ impl Song {
         idx
     }
 
+    /// First and last arrangement index of the loop, kept inside the arrangement
+    pub fn loop_range(&self) -> (usize, usize) {
+        let last = self.arrangement.len().saturating_sub(1);
+        let end = self.loop_end.unwrap_or(last).min(last);
+        (self.loop_start.min(end), end)
+    }
+
+    /// Arrangement index played after `position` when looping the song
+    pub fn next_position(&self, position: usize) -> usize {
+        let (start, end) = self.loop_range();
+        if position == end || position + 1 >= self.arrangement.len() {
+            start
+        } else {
+            position + 1
+        }
+    }
+
+    /// A cue's position, kept inside the arrangement and its pattern
+    pub fn cue_target(&self, cue: &CuePoint) -> Option<(usize, usize)> {
+        let position = cue.position.min(self.arrangement.len().checked_sub(1)?);
+        let length = self.patterns.get(self.arrangement[position])?.length;
+        Some((position, cue.row.min(length.saturating_sub(1))))
+    }
+
+    /// How late a row's notes play, as a fraction of a row: swing on odd rows
+    /// (the pattern's own swing if set) plus the note's delay ticks
+    pub fn row_offset(&self, pattern: &Pattern, row: usize, delay_ticks: u8) -> f64 {
+        let swing = pattern.swing.unwrap_or(self.swing).min(MAX_SWING);
+        let swing_offset = if row % 2 == 1 { swing as f64 / 100.0 } else { 0.0 };
+        (swing_offset + delay_ticks as f64 / TICKS_PER_ROW as f64).min(MAX_ROW_OFFSET)
+    }
+
     /// Calculate tick duration in seconds
     pub fn tick_duration(&self) -> f64 {
         60.0 / (self.bpm as f64 * self.rows_per_beat as f64)
@@ -277,6 +392,8 @@ pub enum Effect {
     PatternBreak(u8),
     /// Set speed/BPM (Fxx)
     SetSpeed(u8),
+    /// Note delay (Gxx) - play this row's note xx ticks late (16 ticks per row)
+    NoteDelay(u8),
     /// Set pan (Pxx) - 00=left, 40=center, 7F=right
     SetPan(u8),
     /// Reverb send (Rxx) - 00-7F
@@ -303,6 +420,7 @@ impl Effect {
             'D' => Effect::PatternBreak(param),
             'E' => Effect::SetExpression(param),
             'F' => Effect::SetSpeed(param),
+            'G' => Effect::NoteDelay(param),
             'H' => Effect::SetChorus(param),
             'M' => Effect::SetModulation(param),
             'P' => Effect::SetPan(param),
@@ -325,6 +443,7 @@ impl Effect {
             Effect::PatternBreak(_) => Some('D'),
             Effect::SetExpression(_) => Some('E'),
             Effect::SetSpeed(_) => Some('F'),
+            Effect::NoteDelay(_) => Some('G'),
             Effect::SetChorus(_) => Some('H'),
             Effect::SetModulation(_) => Some('M'),
             Effect::SetPan(_) => Some('P'),
@@ -346,6 +465,7 @@ impl Effect {
             Effect::PatternBreak(r) => *r,
             Effect::SetExpression(v) => *v,
             Effect::SetSpeed(s) => *s,
+            Effect::NoteDelay(t) => *t,
             Effect::SetChorus(v) => *v,
             Effect::SetModulation(v) => *v,
             Effect::SetPan(p) => *p,
@@ -353,3 +473,37 @@ impl Effect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(positions: usize) -> Song {
+        let mut song = Song::new();
+        song.arrangement = vec![0; positions];
+        song
+    }
+
+    #[test]
+    fn test_song_loops_between_its_loop_points() {
+        let mut song = song(5);
+        assert_eq!(song.loop_range(), (0, 4));
+        assert_eq!(song.next_position(4), 0);
+
+        song.loop_start = 1;
+        song.loop_end = Some(3);
+        assert_eq!((song.next_position(1), song.next_position(3)), (2, 1));
+        // Playing past the loop end still wraps at the arrangement's end
+        assert_eq!(song.next_position(4), 1);
+
+        song.loop_end = Some(9);
+        assert_eq!(song.loop_range(), (1, 4));
+    }
+
+    #[test]
+    fn test_cue_target_stays_in_the_song() {
+        let song = song(2);
+        let cue = CuePoint { name: "boss".to_string(), position: 7, row: 200 };
+        assert_eq!(song.cue_target(&cue), Some((1, DEFAULT_PATTERN_LEN - 1)));
+    }
+}
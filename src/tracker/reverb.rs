@@ -0,0 +1,139 @@
+//! Reverb
+//!
+//! A small Schroeder/Freeverb-style reverb run over the engine's whole
+//! output (synth and samples), used for room acoustics in play mode:
+//! - four parallel damped comb filters per side, then two allpass filters
+//! - the right side's delays are slightly longer, for stereo width
+//! - `ReverbParams` sets the tail length, high-frequency damping and mix
+
+/// Comb filter delays in samples at 44.1kHz (Freeverb's tunings)
+const COMB_DELAYS: [usize; 4] = [1116, 1188, 1277, 1356];
+
+/// Allpass filter delays in samples at 44.1kHz
+const ALLPASS_DELAYS: [usize; 2] = [556, 441];
+
+/// Extra delay of the right side's filters
+const STEREO_SPREAD: usize = 23;
+
+/// Allpass feedback
+const ALLPASS_FEEDBACK: f32 = 0.5;
+
+/// Input gain into the filters (keeps the summed combs from clipping)
+const INPUT_GAIN: f32 = 0.015;
+
+/// Reverb settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReverbParams {
+    /// Comb feedback, 0.0 - 0.98: longer tails as it rises
+    pub decay: f32,
+    /// 0.0 (bright) - 1.0 (dull): how fast high frequencies die out
+    pub damping: f32,
+    /// Wet level mixed with the dry signal, 0.0 - 1.0
+    pub wet: f32,
+}
+
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    filter: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len], index: 0, filter: 0.0 }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.filter = output * (1.0 - damping) + self.filter * damping;
+        self.buffer[self.index] = input + self.filter * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len], index: 0 }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * ALLPASS_FEEDBACK;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// One side's filter bank
+struct Side {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+impl Side {
+    fn new(sample_rate: u32, spread: usize) -> Self {
+        let scale = |len: usize| ((len + spread) as u64 * sample_rate as u64 / 44_100).max(1) as usize;
+        Self {
+            combs: COMB_DELAYS.iter().map(|&d| Comb::new(scale(d))).collect(),
+            allpasses: ALLPASS_DELAYS.iter().map(|&d| Allpass::new(scale(d))).collect(),
+        }
+    }
+
+    fn process(&mut self, input: f32, params: &ReverbParams) -> f32 {
+        let mut out: f32 = self.combs.iter_mut().map(|c| c.process(input, params.decay, params.damping)).sum();
+        for allpass in &mut self.allpasses {
+            out = allpass.process(out);
+        }
+        out
+    }
+}
+
+/// Stereo reverb state
+pub struct Reverb {
+    pub params: ReverbParams,
+    left: Side,
+    right: Side,
+}
+
+impl Reverb {
+    pub fn new(sample_rate: u32, params: ReverbParams) -> Self {
+        Self { params, left: Side::new(sample_rate, 0), right: Side::new(sample_rate, STEREO_SPREAD) }
+    }
+
+    /// Add the reverb tail to the buffers in place
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let params = ReverbParams { decay: self.params.decay.clamp(0.0, 0.98), ..self.params };
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let input = (*l + *r) * INPUT_GAIN;
+            let wet_l = self.left.process(input, &params);
+            let wet_r = self.right.process(input, &params);
+            *l += wet_l * params.wet;
+            *r += wet_r * params.wet;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impulse_leaves_a_decaying_tail() {
+        let mut reverb = Reverb::new(44_100, ReverbParams { decay: 0.8, damping: 0.3, wet: 1.0 });
+        let (mut left, mut right) = (vec![0.0; 44_100], vec![0.0; 44_100]);
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right);
+
+        let energy = |s: &[f32]| s.iter().map(|x| x * x).sum::<f32>();
+        let (early, late) = (energy(&left[1..11_025]), energy(&left[33_075..]));
+        assert!(early > 0.0 && late < early);
+        assert_ne!(left[2000..2100], right[2000..2100], "sides differ for stereo width");
+    }
+}
@@ -0,0 +1,446 @@
+//! Sample-based instruments
+//!
+//! WAV files played by a small sampler that is mixed on top of the SF2 synth:
+//! - 8/16/24-bit PCM or 32-bit float WAV, stereo is mixed down to mono
+//! - optional loop (read from the file's `smpl` chunk when it has one)
+//! - base note: the key that plays the sample at its recorded pitch
+//! - per-instrument volume and ADSR envelope
+//!
+//! Channel programs from `SAMPLE_PROGRAM_BASE` up select sample instrument
+//! `program - SAMPLE_PROGRAM_BASE` instead of an SF2 preset. Like a classic
+//! tracker, each channel plays one sample voice at a time.
+
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+/// First channel program that plays a sample instrument
+pub const SAMPLE_PROGRAM_BASE: u8 = 128;
+
+/// Maximum number of sample instruments in a song
+pub const MAX_SAMPLE_INSTRUMENTS: usize = 64;
+
+/// Longest attack/decay/release time in seconds
+pub const MAX_ENVELOPE_TIME: f32 = 4.0;
+
+/// Voices playing at once (oldest are cut when exceeded)
+const MAX_VOICES: usize = 32;
+
+/// Output gain of a voice at full velocity and volume
+const VOICE_GAIN: f32 = 0.5;
+
+/// Volume envelope (times in seconds, sustain as 0.0-1.0)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self { attack: 0.0, decay: 0.0, sustain: 1.0, release: 0.05 }
+    }
+}
+
+/// A sample instrument as saved in the song (the audio stays in the WAV file)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleInstrumentDef {
+    /// Display name
+    pub name: String,
+    /// Path of the WAV file
+    pub path: String,
+    /// MIDI key that plays the sample at its original pitch
+    #[serde(default = "default_base_note")]
+    pub base_note: u8,
+    /// Loop start and end in sample frames (None = play once)
+    #[serde(default)]
+    pub loop_range: Option<(usize, usize)>,
+    /// Volume multiplier (1.0 = as recorded)
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub envelope: Envelope,
+}
+
+fn default_base_note() -> u8 {
+    60
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+impl SampleInstrumentDef {
+    /// Definition for a freshly loaded file, using its embedded loop if any
+    pub fn new(name: String, path: String, data: &SampleData) -> Self {
+        Self {
+            name,
+            path,
+            base_note: default_base_note(),
+            loop_range: data.loop_range,
+            volume: default_volume(),
+            envelope: Envelope::default(),
+        }
+    }
+}
+
+/// Decoded sample audio (mono)
+#[derive(Debug, Clone)]
+pub struct SampleData {
+    pub frames: Vec<f32>,
+    pub sample_rate: u32,
+    /// Loop found in the file's `smpl` chunk
+    pub loop_range: Option<(usize, usize)>,
+}
+
+/// A sample instrument ready to play
+#[derive(Debug, Clone)]
+pub struct SampleInstrument {
+    pub def: SampleInstrumentDef,
+    pub data: Arc<SampleData>,
+}
+
+impl SampleInstrument {
+    /// Loop points clamped to the sample, None if the loop is empty or unset
+    fn valid_loop(&self) -> Option<(usize, usize)> {
+        let (start, end) = self.def.loop_range?;
+        let end = end.min(self.data.frames.len());
+        (start < end).then_some((start, end))
+    }
+}
+
+/// Decode a WAV file into mono samples
+pub fn parse_wav(bytes: &[u8]) -> Result<SampleData, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a WAV file".to_string());
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut loop_range = None;
+
+    // Walk the chunks (each padded to an even size)
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+        let end = (pos + 8)
+            .checked_add(size)
+            .filter(|&end| end <= bytes.len())
+            .ok_or("WAV chunk runs past the end of the file")?;
+        let body = &bytes[pos + 8..end];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let tag = u16::from_le_bytes([body[0], body[1]]);
+                let channels = u16::from_le_bytes([body[2], body[3]]);
+                let rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                // WAVE_FORMAT_EXTENSIBLE keeps the real format in the sub-format GUID
+                let tag = if tag == 0xFFFE && body.len() >= 26 { u16::from_le_bytes([body[24], body[25]]) } else { tag };
+                format = Some((tag, channels, rate, bits));
+            }
+            b"data" => data = Some(body),
+            // First sample loop: start and end (inclusive) frames
+            b"smpl" if body.len() >= 60 && u32::from_le_bytes([body[28], body[29], body[30], body[31]]) > 0 => {
+                let start = u32::from_le_bytes([body[44], body[45], body[46], body[47]]) as usize;
+                let end = u32::from_le_bytes([body[48], body[49], body[50], body[51]]) as usize;
+                loop_range = Some((start, end.checked_add(1).ok_or("Invalid WAV sample loop")?));
+            }
+            _ => {}
+        }
+        // The pad byte may be missing after the last chunk
+        pos = end + (size & 1);
+    }
+
+    let (tag, channels, sample_rate, bits) = format.ok_or("WAV file has no format chunk")?;
+    let data = data.ok_or("WAV file has no data chunk")?;
+    if channels == 0 || sample_rate == 0 {
+        return Err("Invalid WAV format".to_string());
+    }
+
+    let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+        (1, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (1, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2147483648.0,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => return Err(format!("Unsupported WAV format (format {}, {} bits)", tag, bits)),
+    };
+
+    let sample_bytes = bits as usize / 8;
+    let frame_bytes = sample_bytes * channels as usize;
+    let frames = data
+        .chunks_exact(frame_bytes)
+        .map(|frame| {
+            let sum: f32 = frame.chunks_exact(sample_bytes).map(decode).sum();
+            sum / channels as f32
+        })
+        .collect();
+
+    Ok(SampleData { frames, sample_rate, loop_range })
+}
+
+/// Read and decode a WAV file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_wav(path: &std::path::Path) -> Result<SampleData, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    parse_wav(&bytes).map_err(|e| format!("{:?}: {}", path, e))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// One playing note
+struct Voice {
+    channel: u8,
+    key: u8,
+    instrument: Arc<SampleInstrument>,
+    /// Read position in sample frames
+    position: f64,
+    /// Frames advanced per output sample
+    step: f64,
+    gain: f32,
+    stage: Stage,
+    level: f32,
+    /// Level when the release started
+    release_from: f32,
+}
+
+impl Voice {
+    /// Advance the envelope by one output sample; false once the voice is silent
+    fn advance_envelope(&mut self, rate: f32) -> bool {
+        let env = self.instrument.def.envelope;
+        let per_second = |time: f32| if time > 0.0 { 1.0 / (time * rate) } else { 1.0 };
+        match self.stage {
+            Stage::Attack => {
+                self.level += per_second(env.attack);
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.level -= (1.0 - env.sustain) * per_second(env.decay);
+                if self.level <= env.sustain {
+                    self.level = env.sustain;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => {}
+            Stage::Release => {
+                self.level -= self.release_from * per_second(env.release);
+                if self.level <= 0.0 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn release(&mut self) {
+        if self.stage != Stage::Release {
+            self.stage = Stage::Release;
+            self.release_from = self.level.max(0.0001);
+        }
+    }
+}
+
+/// Plays sample instruments, rendered into the same buffers as the synth
+pub struct Sampler {
+    instruments: Vec<Option<Arc<SampleInstrument>>>,
+    voices: Vec<Voice>,
+    sample_rate: u32,
+}
+
+impl Sampler {
+    pub fn new(sample_rate: u32) -> Self {
+        Self { instruments: Vec::new(), voices: Vec::new(), sample_rate }
+    }
+
+    /// Install (or clear) the instrument at an index
+    pub fn set_instrument(&mut self, index: usize, instrument: Option<Arc<SampleInstrument>>) {
+        if index >= self.instruments.len() {
+            self.instruments.resize(index + 1, None);
+        }
+        self.instruments[index] = instrument;
+    }
+
+    /// Start a note, releasing whatever the channel was playing
+    pub fn note_on(&mut self, channel: u8, key: u8, velocity: u8, index: usize) {
+        self.channel_off(channel);
+        if velocity == 0 {
+            return;
+        }
+        let Some(instrument) = self.instruments.get(index).cloned().flatten() else {
+            return;
+        };
+        if instrument.data.frames.is_empty() {
+            return;
+        }
+
+        let semitones = key as f64 - instrument.def.base_note as f64;
+        let step = 2f64.powf(semitones / 12.0) * instrument.data.sample_rate as f64 / self.sample_rate as f64;
+        let gain = velocity as f32 / 127.0 * instrument.def.volume * VOICE_GAIN;
+
+        if self.voices.len() >= MAX_VOICES {
+            self.voices.remove(0);
+        }
+        self.voices.push(Voice {
+            channel,
+            key,
+            instrument,
+            position: 0.0,
+            step,
+            gain,
+            stage: Stage::Attack,
+            level: 0.0,
+            release_from: 0.0,
+        });
+    }
+
+    /// Release a note
+    pub fn note_off(&mut self, channel: u8, key: u8) {
+        for voice in self.voices.iter_mut().filter(|v| v.channel == channel && v.key == key) {
+            voice.release();
+        }
+    }
+
+    /// Release every note of a channel
+    pub fn channel_off(&mut self, channel: u8) {
+        for voice in self.voices.iter_mut().filter(|v| v.channel == channel) {
+            voice.release();
+        }
+    }
+
+    /// Release all notes
+    pub fn all_notes_off(&mut self) {
+        for voice in &mut self.voices {
+            voice.release();
+        }
+    }
+
+    /// Whether anything is playing
+    pub fn is_active(&self) -> bool {
+        !self.voices.is_empty()
+    }
+
+    /// Mix the playing voices into the buffers (adds to what is there)
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let rate = self.sample_rate as f32;
+        let len = left.len().min(right.len());
+        self.voices.retain_mut(|voice| {
+            let instrument = Arc::clone(&voice.instrument);
+            let frames = &instrument.data.frames;
+            let looped = instrument.valid_loop();
+            for i in 0..len {
+                let index = voice.position as usize;
+                if index >= frames.len() {
+                    return false;
+                }
+                // Linear interpolation between neighbouring frames
+                let next = match looped {
+                    Some((start, end)) if index + 1 >= end => frames[start],
+                    _ => frames.get(index + 1).copied().unwrap_or(0.0),
+                };
+                let frac = (voice.position - index as f64) as f32;
+                let sample = frames[index] + (next - frames[index]) * frac;
+
+                let out = sample * voice.gain * voice.level;
+                left[i] += out;
+                right[i] += out;
+
+                if !voice.advance_envelope(rate) {
+                    return false;
+                }
+                voice.position += voice.step;
+                if let Some((start, end)) = looped {
+                    while voice.position >= end as f64 {
+                        voice.position -= (end - start) as f64;
+                    }
+                }
+            }
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a 16-bit mono PCM WAV
+    fn wav_16(samples: &[i16], rate: u32) -> Vec<u8> {
+        let data_len = samples.len() as u32 * 2;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&rate.to_le_bytes());
+        bytes.extend_from_slice(&(rate * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for s in samples {
+            bytes.extend_from_slice(&s.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn instrument(frames: Vec<f32>, loop_range: Option<(usize, usize)>) -> Arc<SampleInstrument> {
+        let data = SampleData { frames, sample_rate: 44100, loop_range };
+        let def = SampleInstrumentDef::new("test".to_string(), String::new(), &data);
+        Arc::new(SampleInstrument { def, data: Arc::new(data) })
+    }
+
+    #[test]
+    fn test_parses_16_bit_pcm() {
+        let data = parse_wav(&wav_16(&[0, 16384, -32768], 22050)).unwrap();
+        assert_eq!(data.sample_rate, 22050);
+        assert_eq!(data.frames, vec![0.0, 0.5, -1.0]);
+        assert!(data.loop_range.is_none());
+        assert!(parse_wav(b"not a wav file").is_err());
+    }
+
+    #[test]
+    fn test_rejects_chunks_past_the_end() {
+        let mut bytes = wav_16(&[0, 16384], 22050);
+        let len = bytes.len();
+        // A data chunk claiming more bytes than the file holds
+        bytes[len - 8..len - 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_wav(&bytes).is_err());
+        // A chunk header cut short ends the walk
+        let mut bytes = wav_16(&[0, 16384], 22050);
+        bytes.extend_from_slice(b"LIST");
+        assert_eq!(parse_wav(&bytes).unwrap().frames, vec![0.0, 0.5]);
+    }
+
+    #[test]
+    fn test_one_shot_ends_and_loop_keeps_playing() {
+        let mut sampler = Sampler::new(44100);
+        sampler.set_instrument(0, Some(instrument(vec![1.0; 8], None)));
+        sampler.set_instrument(1, Some(instrument(vec![1.0; 8], Some((2, 8)))));
+
+        sampler.note_on(0, 60, 127, 0);
+        sampler.note_on(1, 60, 127, 1);
+        let (mut left, mut right) = (vec![0.0; 64], vec![0.0; 64]);
+        sampler.render(&mut left, &mut right);
+
+        // Only the looping voice is left, still sounding
+        assert_eq!(sampler.voices.len(), 1);
+        assert!(left[63] > 0.0);
+
+        sampler.all_notes_off();
+        let (mut left, mut right) = (vec![0.0; 44100], vec![0.0; 44100]);
+        sampler.render(&mut left, &mut right);
+        assert!(!sampler.is_active());
+    }
+}
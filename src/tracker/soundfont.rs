@@ -0,0 +1,150 @@
+//! Soundfont loading
+//!
+//! The soundfont is only loaded when the Music tab is first opened, so the
+//! other tools never pay for it:
+//! - native: read and parsed on a background thread, the UI keeps drawing
+//! - WASM: parsed from the bytes the page prefetched (no threads there)
+//!
+//! Parsed soundfonts are cached for the whole session, so a recreated
+//! tracker (e.g. after crash recovery) gets its instruments instantly.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use rustysynth::SoundFont;
+
+/// Soundfont filename
+pub const SOUNDFONT_NAME: &str = "TimGM6mb.sf2";
+
+/// Parsed soundfonts by file name
+fn cache() -> &'static Mutex<HashMap<String, Arc<SoundFont>>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Arc<SoundFont>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Parse a soundfont from any reader
+pub fn parse_soundfont<R: std::io::Read>(reader: &mut R) -> Result<Arc<SoundFont>, String> {
+    SoundFont::new(reader)
+        .map(Arc::new)
+        .map_err(|e| format!("Failed to parse soundfont: {:?}", e))
+}
+
+/// The default soundfont, from the cache or parsed on first use
+fn load_default_soundfont() -> Result<Arc<SoundFont>, String> {
+    if let Some(soundfont) = cache().lock().unwrap().get(SOUNDFONT_NAME) {
+        return Ok(Arc::clone(soundfont));
+    }
+    let soundfont = read_default_soundfont()?;
+    cache().lock().unwrap().insert(SOUNDFONT_NAME.to_string(), Arc::clone(&soundfont));
+    Ok(soundfont)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_default_soundfont() -> Result<Arc<SoundFont>, String> {
+    let Some(path) = find_soundfont() else {
+        let cwd = std::env::current_dir().map(|d| d.display().to_string()).unwrap_or_default();
+        return Err(format!("Soundfont {} not found in any search path (cwd: {})", SOUNDFONT_NAME, cwd));
+    };
+    let file = std::fs::File::open(&path).map_err(|e| format!("Failed to open soundfont {:?}: {}", path, e))?;
+    let soundfont = parse_soundfont(&mut std::io::BufReader::new(file))?;
+    log::info!("Loaded soundfont: {:?}", path);
+    Ok(soundfont)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_default_soundfont() -> Result<Arc<SoundFont>, String> {
+    // Prefetched by the page before the WASM module loaded
+    if !super::audio::wasm::is_soundfont_cached() {
+        return Err("Soundfont not available in WASM cache".to_string());
+    }
+    let bytes = super::audio::wasm::get_cached_soundfont()
+        .ok_or_else(|| "Failed to copy soundfont from WASM cache".to_string())?;
+    let soundfont = parse_soundfont(&mut std::io::Cursor::new(bytes))?;
+    log::info!("Loaded soundfont from WASM cache: {}", SOUNDFONT_NAME);
+    Ok(soundfont)
+}
+
+/// Find the soundfont in various locations (development, deployed, macOS app bundle)
+#[cfg(not(target_arch = "wasm32"))]
+fn find_soundfont() -> Option<PathBuf> {
+    let candidates = [
+        // Development: relative to cwd
+        PathBuf::from(format!("assets/soundfonts/{}", SOUNDFONT_NAME)),
+        // Deployed: next to executable
+        std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.join("assets/soundfonts").join(SOUNDFONT_NAME))).unwrap_or_default(),
+        // macOS app bundle: Contents/Resources
+        std::env::current_exe().ok().and_then(|p| p.parent().and_then(|d| d.parent()).map(|d| d.join("Resources/assets/soundfonts").join(SOUNDFONT_NAME))).unwrap_or_default(),
+        // Fallback: just the filename in cwd
+        PathBuf::from(SOUNDFONT_NAME),
+    ];
+
+    candidates.into_iter().find(|path| path.exists() && !path.as_os_str().is_empty())
+}
+
+/// Progress of loading the default soundfont
+pub enum SoundfontLoader {
+    /// Not requested yet (Music tab never opened)
+    Idle,
+    /// Parsing on a background thread
+    #[cfg(not(target_arch = "wasm32"))]
+    Loading(Receiver<Result<Arc<SoundFont>, String>>),
+    /// Finished (successfully or not), nothing more to do
+    Done,
+}
+
+impl SoundfontLoader {
+    /// Start loading if not started yet. Returns the soundfont once, in the
+    /// frame it becomes available (immediately when cached).
+    pub fn poll(&mut self) -> Option<Result<Arc<SoundFont>, String>> {
+        match self {
+            SoundfontLoader::Idle => {
+                if let Some(soundfont) = cache().lock().unwrap().get(SOUNDFONT_NAME) {
+                    *self = SoundfontLoader::Done;
+                    return Some(Ok(Arc::clone(soundfont)));
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    let (sender, receiver) = mpsc::channel();
+                    std::thread::spawn(move || {
+                        let _ = sender.send(load_default_soundfont());
+                    });
+                    *self = SoundfontLoader::Loading(receiver);
+                    None
+                }
+                #[cfg(target_arch = "wasm32")]
+                {
+                    *self = SoundfontLoader::Done;
+                    Some(load_default_soundfont())
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            SoundfontLoader::Loading(receiver) => match receiver.try_recv() {
+                Ok(result) => {
+                    *self = SoundfontLoader::Done;
+                    Some(result)
+                }
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => {
+                    *self = SoundfontLoader::Done;
+                    Some(Err("Soundfont loader thread stopped".to_string()))
+                }
+            },
+            SoundfontLoader::Done => None,
+        }
+    }
+
+    /// Whether the soundfont is still being parsed
+    pub fn is_loading(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            matches!(self, SoundfontLoader::Loading(_))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+}
@@ -1,8 +1,15 @@
 //! Tracker editor state
 
 use super::audio::AudioEngine;
-use super::pattern::{Song, Note, Effect, MAX_CHANNELS};
+use super::soundfont::{SoundfontLoader, SOUNDFONT_NAME};
+use super::sampler::{SampleData, SampleInstrument, SAMPLE_PROGRAM_BASE};
+use super::pattern::{CuePoint, CueSwitch, Song, Note, Effect, MAX_CHANNELS};
+use super::library::{unique_song_id, SongSlot};
 use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Seconds a cue fade takes each way (out, then back in)
+const CUE_FADE_SECONDS: f64 = 0.5;
 
 /// Tracker view mode
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -15,14 +22,69 @@ pub enum TrackerView {
     Instruments,
 }
 
+/// What playback does at the end of a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+    /// Repeat the pattern being played
+    LoopPattern,
+    /// Go on through the arrangement, back to the song's loop start after its loop end
+    #[default]
+    LoopSong,
+    /// Stop after the last pattern of the arrangement
+    PlayOnce,
+}
+
+impl PlaybackMode {
+    /// Next mode when cycling with the toolbar button
+    pub fn next(self) -> Self {
+        match self {
+            PlaybackMode::LoopPattern => PlaybackMode::LoopSong,
+            PlaybackMode::LoopSong => PlaybackMode::PlayOnce,
+            PlaybackMode::PlayOnce => PlaybackMode::LoopPattern,
+        }
+    }
+}
+
+/// How a channel is shown in the pattern view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelDisplay {
+    /// All columns
+    #[default]
+    Full,
+    /// Note column only
+    Narrow,
+    /// Thin strip, no columns
+    Collapsed,
+}
+
+impl ChannelDisplay {
+    /// Next mode when cycling with the channel's toggle button
+    pub fn next(self) -> Self {
+        match self {
+            ChannelDisplay::Full => ChannelDisplay::Narrow,
+            ChannelDisplay::Narrow => ChannelDisplay::Collapsed,
+            ChannelDisplay::Collapsed => ChannelDisplay::Full,
+        }
+    }
+}
+
 /// Tracker editor state
 pub struct TrackerState {
     /// The current song being edited
     pub song: Song,
+    /// The project's songs (the current one's slot is only updated when
+    /// switching away or saving)
+    pub library: Vec<SongSlot>,
+    /// Library index of `song`
+    pub current_song: usize,
+    /// Song edited before the current one, for A/B switching
+    pub previous_song: Option<usize>,
     /// Current file path
     pub current_file: Option<PathBuf>,
     /// Audio engine for playback
     pub audio: AudioEngine,
+    /// Loading progress of the soundfont
+    pub soundfont: SoundfontLoader,
     /// Current view mode
     pub view: TrackerView,
 
@@ -55,12 +117,30 @@ pub struct TrackerState {
     pub playback_pattern_idx: usize,
     /// Time accumulator for playback timing
     pub playback_time: f64,
+    /// What happens at the end of a pattern
+    pub playback_mode: PlaybackMode,
+    /// Cue being faded to, and seconds into the fade
+    cue_fade: Option<(usize, f64)>,
+    /// How the arrangement view's cue buttons jump
+    pub cue_switch: CueSwitch,
+    /// Cue being renamed, with the name typed so far
+    pub renaming_cue: Option<(usize, String)>,
 
     // View state
     /// First visible row in pattern view
     pub scroll_row: usize,
     /// Number of visible rows
     pub visible_rows: usize,
+    /// Horizontal scroll of the pattern view in pixels
+    pub scroll_x: f32,
+    /// Display mode per channel
+    pub channel_display: [ChannelDisplay; MAX_CHANNELS],
+    /// Channel whose header is being dragged to reorder it
+    pub dragging_channel: Option<usize>,
+    /// Whether the horizontal scrollbar thumb is being dragged
+    pub dragging_scrollbar: bool,
+    /// Cursor channel last scrolled into view
+    pub scrolled_to_channel: usize,
 
     // Selection
     /// Selection start (pattern_idx, row, channel)
@@ -74,6 +154,8 @@ pub struct TrackerState {
     pub status_message: Option<(String, f64)>,
     /// Last played note per channel (for sustain detection - same note = no re-trigger)
     last_played_notes: [Option<u8>; MAX_CHANNELS],
+    /// Channels of the current playback row already played (rows can be swung or delayed)
+    row_triggered: [bool; MAX_CHANNELS],
 
     // Effect preview values (per channel, for testing in instruments view)
     /// Pan value per channel (0=left, 64=center, 127=right)
@@ -89,82 +171,33 @@ pub struct TrackerState {
 
     /// Instrument list scroll offset
     pub instrument_scroll: usize,
+    /// Decoded audio of each `song.samples` entry (None if its file failed to load)
+    sample_data: Vec<Option<Arc<SampleData>>>,
 
     /// Which knob is being edited (for text input)
     /// None = not editing, Some(index) = editing knob at index
     pub editing_knob: Option<usize>,
     /// Text being edited for knob value
     pub knob_edit_text: String,
-}
-
-/// Soundfont filename
-const SOUNDFONT_NAME: &str = "TimGM6mb.sf2";
-
-/// Find the soundfont in various locations (development, deployed, macOS app bundle)
-#[cfg(not(target_arch = "wasm32"))]
-fn find_soundfont() -> Option<PathBuf> {
-    let candidates = [
-        // Development: relative to cwd
-        PathBuf::from(format!("assets/soundfonts/{}", SOUNDFONT_NAME)),
-        // Deployed: next to executable
-        std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.join("assets/soundfonts").join(SOUNDFONT_NAME))).unwrap_or_default(),
-        // macOS app bundle: Contents/Resources
-        std::env::current_exe().ok().and_then(|p| p.parent().and_then(|d| d.parent()).map(|d| d.join("Resources/assets/soundfonts").join(SOUNDFONT_NAME))).unwrap_or_default(),
-        // Fallback: just the filename in cwd
-        PathBuf::from(SOUNDFONT_NAME),
-    ];
-
-    for path in candidates {
-        if path.exists() && path.as_os_str().len() > 0 {
-            return Some(path);
-        }
-    }
-    None
+    /// Knob changes also write their effect command at the cursor row
+    pub write_effects: bool,
+    /// Last effect written from a knob: (pattern index, row, channel, time), for the cell flash
+    pub written_effect: Option<(usize, usize, usize, f64)>,
 }
 
 impl TrackerState {
     pub fn new() -> Self {
-        let mut audio = AudioEngine::new();
-
-        // Load soundfont - different strategies for native vs WASM
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM: get from JavaScript cache (prefetched before WASM loaded)
-            if super::audio::wasm::is_soundfont_cached() {
-                if let Some(bytes) = super::audio::wasm::get_cached_soundfont() {
-                    match audio.load_soundfont_from_bytes(&bytes, Some(SOUNDFONT_NAME.to_string())) {
-                        Ok(()) => println!("Loaded soundfont from WASM cache: {}", SOUNDFONT_NAME),
-                        Err(e) => eprintln!("Failed to load soundfont from cache: {}", e),
-                    }
-                }
-            } else {
-                eprintln!("Soundfont not available in WASM cache");
-            }
-        }
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // On native: load from filesystem
-            if let Some(sf_path) = find_soundfont() {
-                match audio.load_soundfont(&sf_path) {
-                    Ok(()) => println!("Loaded soundfont: {:?}", sf_path),
-                    Err(e) => eprintln!("Failed to load soundfont {:?}: {}", sf_path, e),
-                }
-            } else {
-                eprintln!("Soundfont {} not found in any search path", SOUNDFONT_NAME);
-                if let Ok(cwd) = std::env::current_dir() {
-                    eprintln!("Current working directory: {:?}", cwd);
-                }
-                if let Ok(exe) = std::env::current_exe() {
-                    eprintln!("Executable location: {:?}", exe);
-                }
-            }
-        }
+        // The soundfont loads on first open of the Music tab (see `update_soundfont`)
+        let audio = AudioEngine::new();
 
         Self {
             song: Song::new(),
+            library: Vec::new(),
+            current_song: 0,
+            previous_song: None,
             current_file: None,
             audio,
+            soundfont: SoundfontLoader::Idle,
             view: TrackerView::Pattern,
 
             current_pattern_idx: 0,
@@ -181,9 +214,18 @@ impl TrackerState {
             playback_row: 0,
             playback_pattern_idx: 0,
             playback_time: 0.0,
+            playback_mode: PlaybackMode::LoopSong,
+            cue_fade: None,
+            cue_switch: CueSwitch::Immediate,
+            renaming_cue: None,
 
             scroll_row: 0,
             visible_rows: 32,
+            scroll_x: 0.0,
+            channel_display: [ChannelDisplay::Full; MAX_CHANNELS],
+            dragging_channel: None,
+            dragging_scrollbar: false,
+            scrolled_to_channel: 0,
 
             selection_start: None,
             selection_end: None,
@@ -191,6 +233,7 @@ impl TrackerState {
             dirty: false,
             status_message: None,
             last_played_notes: [None; MAX_CHANNELS],
+            row_triggered: [false; MAX_CHANNELS],
 
             // Effect previews - initialize to defaults
             preview_pan: [64; MAX_CHANNELS],        // Center
@@ -199,8 +242,29 @@ impl TrackerState {
             preview_modulation: [0; MAX_CHANNELS],  // No modulation
             preview_expression: [127; MAX_CHANNELS], // Full expression
             instrument_scroll: 0,
+            sample_data: Vec::new(),
             editing_knob: None,
             knob_edit_text: String::new(),
+            write_effects: false,
+            written_effect: None,
+        }
+    }
+
+    /// Load the soundfont if needed and start using it once parsed (call every frame the tab is shown)
+    pub fn update_soundfont(&mut self) {
+        match self.soundfont.poll() {
+            Some(Ok(soundfont)) => {
+                if let Err(e) = self.audio.set_soundfont(&soundfont, Some(SOUNDFONT_NAME.to_string())) {
+                    log::error!("{}", e);
+                    return;
+                }
+                // Channels picked their instruments before the synth existed
+                for ch in 0..self.num_channels() {
+                    self.audio.set_program(ch as i32, self.song.get_channel_instrument(ch) as i32);
+                }
+            }
+            Some(Err(e)) => log::error!("{}", e),
+            None => {}
         }
     }
 
@@ -243,6 +307,80 @@ impl TrackerState {
         self.audio.set_program(self.current_channel as i32, instrument as i32);
     }
 
+    /// Highest selectable program (GM presets, then the song's sample instruments)
+    pub fn last_instrument(&self) -> u8 {
+        127 + self.song.samples.len() as u8
+    }
+
+    /// Display name of a program
+    pub fn instrument_name(&self, program: u8) -> String {
+        if let Some(index) = program.checked_sub(SAMPLE_PROGRAM_BASE) {
+            return self.song.samples.get(index as usize).map(|s| s.name.clone()).unwrap_or_else(|| "---".to_string());
+        }
+        self.audio.get_preset_names()
+            .iter()
+            .find(|(_, p, _)| *p == program)
+            .map(|(_, _, n)| n.clone())
+            .unwrap_or_else(|| "---".to_string())
+    }
+
+    /// Sample instrument index of the current channel, if it plays one
+    pub fn current_sample_index(&self) -> Option<usize> {
+        let index = self.current_instrument().checked_sub(SAMPLE_PROGRAM_BASE)? as usize;
+        (index < self.song.samples.len()).then_some(index)
+    }
+
+    /// Reload every sample instrument of the song from its WAV file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_sample_instruments(&mut self) {
+        self.sample_data = self.song.samples.iter()
+            .map(|def| match super::sampler::load_wav(std::path::Path::new(&def.path)) {
+                Ok(data) => Some(Arc::new(data)),
+                Err(e) => {
+                    log::error!("{}", e);
+                    None
+                }
+            })
+            .collect();
+        for index in 0..self.song.samples.len() {
+            self.apply_sample_instrument(index);
+        }
+    }
+
+    /// Load a WAV file as a new sample instrument and select it for the current channel
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_sample_instrument(&mut self, path: &std::path::Path) -> Result<(), String> {
+        if self.song.samples.len() >= super::sampler::MAX_SAMPLE_INSTRUMENTS {
+            return Err(format!("At most {} sample instruments per song", super::sampler::MAX_SAMPLE_INSTRUMENTS));
+        }
+        let data = super::sampler::load_wav(path)?;
+        let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "Sample".to_string());
+        let def = super::sampler::SampleInstrumentDef::new(name, path.to_string_lossy().to_string(), &data);
+
+        let index = self.song.samples.len();
+        self.song.samples.push(def);
+        self.sample_data.resize(index, None);
+        self.sample_data.push(Some(Arc::new(data)));
+        self.apply_sample_instrument(index);
+        self.set_current_instrument(SAMPLE_PROGRAM_BASE + index as u8);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Send sample instrument `index` to the audio engine (after loading or editing it)
+    pub fn apply_sample_instrument(&mut self, index: usize) {
+        let instrument = match (self.song.samples.get(index), self.sample_data.get(index)) {
+            (Some(def), Some(Some(data))) => Some(Arc::new(SampleInstrument { def: def.clone(), data: Arc::clone(data) })),
+            _ => None,
+        };
+        self.audio.set_sample_instrument(index, instrument);
+    }
+
+    /// Length in frames of a sample instrument's audio (0 if not loaded)
+    pub fn sample_len(&self, index: usize) -> usize {
+        self.sample_data.get(index).and_then(|d| d.as_ref()).map(|d| d.frames.len()).unwrap_or(0)
+    }
+
     /// Set preview pan for current channel and apply to audio
     pub fn set_preview_pan(&mut self, value: u8) {
         self.preview_pan[self.current_channel] = value;
@@ -273,6 +411,46 @@ impl TrackerState {
         self.audio.set_expression(self.current_channel as i32, value as i32);
     }
 
+    /// Set an effect knob (0=pan, 1=reverb, 2=chorus, 3=mod, 4=expression) for
+    /// the current channel. In write mode the effect is also stored in the pattern.
+    pub fn set_knob(&mut self, knob: usize, value: u8) {
+        let effect = match knob {
+            0 => {
+                self.set_preview_pan(value);
+                Effect::SetPan(value)
+            }
+            1 => {
+                self.set_preview_reverb(value);
+                Effect::SetReverb(value)
+            }
+            2 => {
+                self.set_preview_chorus(value);
+                Effect::SetChorus(value)
+            }
+            3 => {
+                self.set_preview_modulation(value);
+                Effect::SetModulation(value)
+            }
+            4 => {
+                self.set_preview_expression(value);
+                Effect::SetExpression(value)
+            }
+            _ => return,
+        };
+        if self.write_effects {
+            self.write_effect(effect);
+        }
+    }
+
+    /// Write an effect command at the cursor row of the current channel
+    fn write_effect(&mut self, effect: Effect) {
+        let Some(effect_char) = effect.to_char() else { return };
+        let param = effect.param();
+        self.set_effect(effect_char, param);
+        self.written_effect = Some((self.current_pattern_idx, self.current_row, self.current_channel, macroquad::time::get_time()));
+        self.set_status(&format!("Wrote {}{:02X} at row {:02X}", effect_char, param, self.current_row), 1.0);
+    }
+
     /// Reset all effect previews to defaults for current channel
     pub fn reset_preview_effects(&mut self) {
         let ch = self.current_channel;
@@ -303,6 +481,49 @@ impl TrackerState {
         }
     }
 
+    /// Move a channel to another position (notes, instrument, previews and display mode follow it)
+    pub fn move_channel(&mut self, from: usize, to: usize) {
+        let count = self.num_channels();
+        if from >= count || to >= count || from == to {
+            return;
+        }
+        self.song.move_channel(from, to);
+
+        fn move_item<T: Copy>(items: &mut [T], from: usize, to: usize) {
+            if from < to {
+                items[from..=to].rotate_left(1);
+            } else {
+                items[to..=from].rotate_right(1);
+            }
+        }
+        move_item(&mut self.channel_display, from, to);
+        move_item(&mut self.preview_pan, from, to);
+        move_item(&mut self.preview_reverb, from, to);
+        move_item(&mut self.preview_chorus, from, to);
+        move_item(&mut self.preview_modulation, from, to);
+        move_item(&mut self.preview_expression, from, to);
+
+        // Channels are MIDI channels, so reassign programs to their new slots
+        self.audio.all_notes_off();
+        self.last_played_notes = [None; MAX_CHANNELS];
+        for ch in 0..count {
+            self.audio.set_program(ch as i32, self.song.get_channel_instrument(ch) as i32);
+        }
+
+        // Keep the cursor on the channel it was on
+        let cursor = self.current_channel;
+        self.current_channel = if cursor == from {
+            to
+        } else if from < cursor && cursor <= to {
+            cursor - 1
+        } else if to <= cursor && cursor < from {
+            cursor + 1
+        } else {
+            cursor
+        };
+        self.dirty = true;
+    }
+
     /// Move cursor up
     pub fn cursor_up(&mut self) {
         if self.current_row > 0 {
@@ -498,6 +719,7 @@ impl TrackerState {
             self.playback_pattern_idx = self.current_pattern_idx;
             self.playback_time = 0.0;
             self.last_played_notes = [None; MAX_CHANNELS];
+            self.row_triggered = [false; MAX_CHANNELS];
         } else {
             self.audio.all_notes_off();
             self.last_played_notes = [None; MAX_CHANNELS];
@@ -512,6 +734,7 @@ impl TrackerState {
         self.playback_time = 0.0;
         self.playing = true;
         self.last_played_notes = [None; MAX_CHANNELS];
+        self.row_triggered = [false; MAX_CHANNELS];
     }
 
     /// Stop playback and return cursor to start
@@ -526,6 +749,240 @@ impl TrackerState {
         self.last_played_notes = [None; MAX_CHANNELS];
     }
 
+    /// Read the project's songs (call every frame the tab is shown; only the
+    /// first call does anything). With none saved yet, the library starts
+    /// with the current song.
+    pub fn open_library(&mut self) {
+        if !self.library.is_empty() {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.library = super::library::load_song_library();
+        }
+        if self.library.is_empty() {
+            let id = unique_song_id(&self.library, "song");
+            self.library.push(SongSlot { id, song: self.song.clone(), dirty: self.dirty });
+        }
+        self.open_song(0);
+    }
+
+    /// Whether library song `index` has unsaved changes
+    pub fn song_dirty(&self, index: usize) -> bool {
+        if index == self.current_song {
+            self.dirty
+        } else {
+            self.library.get(index).is_some_and(|s| s.dirty)
+        }
+    }
+
+    /// Make library song `index` the one edited and played
+    pub fn switch_song(&mut self, index: usize) {
+        if index == self.current_song || index >= self.library.len() {
+            return;
+        }
+        self.stash_song();
+        self.previous_song = Some(self.current_song);
+        self.open_song(index);
+    }
+
+    /// Switch back to the song edited before this one (A/B comparison)
+    pub fn swap_songs(&mut self) {
+        if let Some(index) = self.previous_song {
+            self.switch_song(index);
+        }
+    }
+
+    /// Add an empty song to the library and switch to it
+    pub fn new_song(&mut self) {
+        let id = unique_song_id(&self.library, "song");
+        self.library.push(SongSlot { id, song: Song::new(), dirty: true });
+        self.switch_song(self.library.len() - 1);
+    }
+
+    /// Add a copy of the current song to the library and switch to it
+    pub fn duplicate_song(&mut self) {
+        let Some(current) = self.library.get(self.current_song) else { return };
+        let id = unique_song_id(&self.library, &current.id);
+        let mut song = self.song.clone();
+        song.name = format!("{} copy", song.name);
+        self.library.push(SongSlot { id, song, dirty: true });
+        self.switch_song(self.library.len() - 1);
+    }
+
+    /// Rename the current song (blank names are ignored). Its id, which
+    /// levels reference, stays the same.
+    pub fn rename_song(&mut self, name: &str) {
+        let name = name.trim();
+        if !name.is_empty() && name != self.song.name {
+            self.song.name = name.to_string();
+            self.dirty = true;
+        }
+    }
+
+    /// Whether any song of the library has unsaved changes
+    pub fn has_unsaved_songs(&self) -> bool {
+        self.dirty || (0..self.library.len()).any(|i| self.song_dirty(i))
+    }
+
+    /// Throw away unsaved changes: the library is read again from
+    /// `SONGS_DIR` (songs never saved are gone)
+    pub fn revert_songs(&mut self) {
+        self.library.clear();
+        self.previous_song = None;
+        self.set_song(Song::new());
+        self.open_library();
+    }
+
+    /// Remove library song `index` and its file. The last song can't be deleted.
+    pub fn delete_song(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.library.len() {
+            return Ok(());
+        }
+        if self.library.len() == 1 {
+            return Err("A project needs at least one song".to_string());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        super::library::delete_song_file(&self.library[index].id)?;
+        self.library.remove(index);
+        let shift = |i: usize| if i > index { i - 1 } else { i };
+        self.previous_song = self.previous_song.filter(|&i| i != index).map(shift);
+        if index == self.current_song {
+            self.open_song(index.min(self.library.len() - 1));
+        } else {
+            self.current_song = shift(self.current_song);
+        }
+        Ok(())
+    }
+
+    /// Write every changed song to its file. Returns how many were saved.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_songs(&mut self) -> Result<usize, String> {
+        self.stash_song();
+        let result = self.library.iter_mut().filter(|s| s.dirty).try_fold(0, |saved, slot| -> Result<usize, String> {
+            super::library::save_song(&slot.id, &slot.song)?;
+            slot.dirty = false;
+            Ok(saved + 1)
+        });
+        self.dirty = self.library.get(self.current_song).is_some_and(|s| s.dirty);
+        result
+    }
+
+    /// Keep the current song's edits in its library slot
+    fn stash_song(&mut self) {
+        if let Some(slot) = self.library.get_mut(self.current_song) {
+            slot.song = self.song.clone();
+            slot.dirty = self.dirty;
+        }
+    }
+
+    /// Load library song `index` for editing, without stashing the current one
+    fn open_song(&mut self, index: usize) {
+        let slot = &self.library[index];
+        let (song, dirty) = (slot.song.clone(), slot.dirty);
+        self.current_song = index;
+        self.set_song(song);
+        self.dirty = dirty;
+    }
+
+    /// Replace the song being edited and played: playback stops, the cursor
+    /// goes back to the start and the channels take the song's instruments
+    pub fn set_song(&mut self, song: Song) {
+        self.song = song;
+        self.dirty = false;
+        self.renaming_cue = None;
+        self.end_cue_fade();
+        self.stop_playback();
+        self.selection_start = None;
+        self.selection_end = None;
+        self.current_channel = 0;
+        self.current_column = 0;
+        self.scroll_x = 0.0;
+        self.scrolled_to_channel = 0;
+        for ch in 0..self.num_channels() {
+            self.audio.set_program(ch as i32, self.song.get_channel_instrument(ch) as i32);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.load_sample_instruments();
+    }
+
+    /// Add a cue point at the cursor. Returns its index.
+    pub fn add_cue(&mut self) -> usize {
+        let name = format!("Cue {}", self.song.cues.len() + 1);
+        self.song.cues.push(CuePoint { name, position: self.current_pattern_idx, row: self.current_row });
+        self.dirty = true;
+        self.song.cues.len() - 1
+    }
+
+    /// Rename a cue (blank names are ignored)
+    pub fn rename_cue(&mut self, index: usize, name: &str) {
+        let name = name.trim();
+        if let Some(cue) = self.song.cues.get_mut(index).filter(|_| !name.is_empty()) {
+            cue.name = name.to_string();
+            self.dirty = true;
+        }
+    }
+
+    pub fn remove_cue(&mut self, index: usize) {
+        if index < self.song.cues.len() {
+            self.song.cues.remove(index);
+            self.dirty = true;
+        }
+    }
+
+    /// Move playback to cue `index`, starting it if stopped (a fade only
+    /// applies while playing). Returns false if there's no such cue.
+    pub fn jump_to_cue(&mut self, index: usize, switch: CueSwitch) -> bool {
+        let Some(target) = self.song.cues.get(index).and_then(|c| self.song.cue_target(c)) else {
+            return false;
+        };
+        if switch == CueSwitch::Fade && self.playing {
+            self.cue_fade = Some((index, 0.0));
+        } else {
+            self.end_cue_fade();
+            self.seek(target);
+        }
+        true
+    }
+
+    /// Play from an arrangement position and row
+    fn seek(&mut self, (position, row): (usize, usize)) {
+        self.audio.all_notes_off();
+        self.playback_pattern_idx = position;
+        self.playback_row = row;
+        self.playback_time = 0.0;
+        self.playing = true;
+        self.last_played_notes = [None; MAX_CHANNELS];
+        self.row_triggered = [false; MAX_CHANNELS];
+        self.current_pattern_idx = position;
+        self.current_row = row;
+        self.ensure_row_visible();
+    }
+
+    /// Advance a cue fade: out, jump halfway through, back in
+    fn update_cue_fade(&mut self, delta: f64) {
+        let Some((cue, elapsed)) = self.cue_fade else { return };
+        let now = elapsed + delta;
+        if elapsed < CUE_FADE_SECONDS && now >= CUE_FADE_SECONDS {
+            if let Some(target) = self.song.cues.get(cue).and_then(|c| self.song.cue_target(c)) {
+                self.seek(target);
+            }
+        }
+        if now >= CUE_FADE_SECONDS * 2.0 {
+            self.end_cue_fade();
+            return;
+        }
+        self.audio.set_gain(((now - CUE_FADE_SECONDS).abs() / CUE_FADE_SECONDS) as f32);
+        self.cue_fade = Some((cue, now));
+    }
+
+    /// Drop a fade in progress, back at full volume
+    fn end_cue_fade(&mut self) {
+        if self.cue_fade.take().is_some() {
+            self.audio.set_gain(1.0);
+        }
+    }
+
     /// Update playback (called each frame)
     pub fn update_playback(&mut self, delta: f64) {
         // On WASM, we need to render audio each frame to push samples to Web Audio
@@ -535,21 +992,61 @@ impl TrackerState {
         }
 
         if !self.playing {
+            self.end_cue_fade();
             return;
         }
+        self.update_cue_fade(delta);
 
         self.playback_time += delta;
-        let tick_duration = self.song.tick_duration();
-
-        while self.playback_time >= tick_duration {
-            self.playback_time -= tick_duration;
-            self.play_current_row();
+        let row_duration = self.song.tick_duration();
+
+        // playback_time is the time into the current row; swing and note
+        // delays start some channels part-way through it
+        loop {
+            self.play_due_channels(row_duration);
+            if self.playback_time < row_duration {
+                break;
+            }
+            self.playback_time -= row_duration;
             self.advance_playback();
+            if !self.playing {
+                break;
+            }
+            self.row_triggered = [false; MAX_CHANNELS];
         }
     }
 
-    /// Play notes at current playback row
-    fn play_current_row(&mut self) {
+    /// Play the channels of the current playback row whose offset has passed
+    fn play_due_channels(&mut self, row_duration: f64) {
+        let pattern_num = match self.song.arrangement.get(self.playback_pattern_idx) {
+            Some(&n) => n,
+            None => return,
+        };
+        let pattern = match self.song.patterns.get(pattern_num) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let mut due = [false; MAX_CHANNELS];
+        for (channel, is_due) in due.iter_mut().enumerate().take(self.song.num_channels()) {
+            if self.row_triggered[channel] {
+                continue;
+            }
+            let delay = pattern.get(channel, self.playback_row).map(|n| n.delay_ticks()).unwrap_or(0);
+            let offset = self.song.row_offset(pattern, self.playback_row, delay) * row_duration;
+            if self.playback_time >= offset {
+                *is_due = true;
+                self.row_triggered[channel] = true;
+            }
+        }
+
+        if due.contains(&true) {
+            self.play_current_row(&due);
+        }
+    }
+
+    /// Play notes at current playback row (only the channels marked in `channels`)
+    fn play_current_row(&mut self, channels: &[bool; MAX_CHANNELS]) {
         let pattern_num = match self.song.arrangement.get(self.playback_pattern_idx) {
             Some(&n) => n,
             None => return,
@@ -566,7 +1063,7 @@ impl TrackerState {
         let mut notes_to_play: Vec<(usize, Option<u8>, Option<u8>, Option<u8>, Option<u8>)> = Vec::new();
         let mut effects_to_apply: Vec<(usize, Effect)> = Vec::new();
 
-        for channel in 0..num_channels {
+        for channel in (0..num_channels).filter(|&ch| channels.get(ch) == Some(&true)) {
             if let Some(note) = pattern.get(channel, playback_row) {
                 // Collect note data
                 let inst = note.instrument.unwrap_or_else(|| self.song.get_channel_instrument(channel));
@@ -584,8 +1081,10 @@ impl TrackerState {
         for (channel, pitch, inst, volume, _) in notes_to_play {
             if let Some(p) = pitch {
                 if p == 0xFF {
-                    // Note off
-                    self.audio.note_off(channel as i32, 0);
+                    // Note off (releases the note the channel is holding)
+                    if let Some(last) = self.last_played_notes[channel] {
+                        self.audio.note_off(channel as i32, last as i32);
+                    }
                     self.last_played_notes[channel] = None;
                 } else {
                     // Check if same note is already playing (sustain behavior like Picotron)
@@ -652,6 +1151,9 @@ impl TrackerState {
                     self.song.bpm = bpm as u16;
                 }
             }
+            Effect::NoteDelay(_) => {
+                // Timing only, handled when scheduling the row
+            }
             Effect::PatternBreak(row) => {
                 // Jump to next pattern at specified row
                 // This will be handled in advance_playback
@@ -693,10 +1195,21 @@ impl TrackerState {
         self.playback_row += 1;
         if self.playback_row >= pattern_len {
             self.playback_row = 0;
-            self.playback_pattern_idx += 1;
-            if self.playback_pattern_idx >= self.song.arrangement.len() {
-                // Loop or stop
-                self.playback_pattern_idx = 0; // Loop for now
+            match self.playback_mode {
+                PlaybackMode::LoopPattern => {}
+                PlaybackMode::LoopSong => {
+                    self.playback_pattern_idx = self.song.next_position(self.playback_pattern_idx);
+                }
+                PlaybackMode::PlayOnce => {
+                    if self.playback_pattern_idx + 1 >= self.song.arrangement.len() {
+                        // Stop where the song ended
+                        self.playing = false;
+                        self.audio.all_notes_off();
+                        self.last_played_notes = [None; MAX_CHANNELS];
+                        return;
+                    }
+                    self.playback_pattern_idx += 1;
+                }
             }
         }
 
@@ -0,0 +1,261 @@
+//! Dialogs
+//!
+//! Modal questions and messages drawn over every tool:
+//! - `UiContext::open_dialog` shows a `Dialog` (title, message, buttons and
+//!   an optional text field); the owner picks the answer up with
+//!   `UiContext::take_reply` on a later frame. Used for confirmations,
+//!   rename prompts and unsaved-changes questions.
+//! - `ErrorDialog::push` queues an error from anywhere in the app (file I/O,
+//!   exports); `draw_error_dialog` shows the oldest one until dismissed
+
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+use crate::i18n::tr;
+use super::{edit_text, FocusId, Rect, TextEdit, UiContext, draw_text_button, ui_screen_width, ui_screen_height, ACCENT_COLOR};
+
+/// Stable name of a dialog, so its owner can recognise the answer,
+/// e.g. "tracker.delete_song"
+pub type DialogId = &'static str;
+
+/// Focus of a dialog's text field
+const DIALOG_FOCUS: FocusId = "dialog";
+
+/// Color of a dialog button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonStyle {
+    Accent,
+    Plain,
+    /// Destructive actions (delete, discard)
+    Danger,
+}
+
+impl ButtonStyle {
+    fn color(self) -> Color {
+        match self {
+            ButtonStyle::Accent => ACCENT_COLOR,
+            ButtonStyle::Plain => Color::from_rgba(60, 60, 70, 255),
+            ButtonStyle::Danger => Color::from_rgba(150, 55, 55, 255),
+        }
+    }
+}
+
+/// A question waiting for an answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialog {
+    pub title: String,
+    pub message: String,
+    /// Left to right: Enter presses the first, Escape the last
+    pub buttons: Vec<(String, ButtonStyle)>,
+    /// Text field under the message, for dialogs asking for a name
+    pub input: Option<String>,
+}
+
+impl Dialog {
+    /// A dialog without buttons yet (add them with `button`)
+    pub fn new(title: &str, message: &str) -> Self {
+        Self { title: title.to_string(), message: message.to_string(), buttons: Vec::new(), input: None }
+    }
+
+    /// Add a button after the existing ones
+    pub fn button(mut self, label: &str, style: ButtonStyle) -> Self {
+        self.buttons.push((label.to_string(), style));
+        self
+    }
+
+    /// Show a text field starting with `text`
+    pub fn with_input(mut self, text: &str) -> Self {
+        self.input = Some(text.to_string());
+        self
+    }
+
+    /// Ask before a destructive action: `action` (button 0) or Cancel
+    pub fn confirm(title: &str, message: &str, action: &str) -> Self {
+        Self::new(title, message)
+            .button(action, ButtonStyle::Danger)
+            .button(&tr("common.cancel"), ButtonStyle::Plain)
+    }
+
+    /// Ask for a name: OK (button 0) or Cancel
+    pub fn prompt(title: &str, message: &str, text: &str) -> Self {
+        Self::new(title, message)
+            .with_input(text)
+            .button(&tr("common.ok"), ButtonStyle::Accent)
+            .button(&tr("common.cancel"), ButtonStyle::Plain)
+    }
+}
+
+/// How a dialog was answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogReply {
+    /// Index into `Dialog::buttons`
+    pub button: usize,
+    /// Contents of the text field (empty without one)
+    pub text: String,
+}
+
+/// A single error waiting to be acknowledged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorReport {
+    pub title: String,
+    pub message: String,
+}
+
+/// Queue of errors shown one at a time
+#[derive(Debug, Default)]
+pub struct ErrorDialog {
+    queue: VecDeque<ErrorReport>,
+}
+
+impl ErrorDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an error (also logged)
+    pub fn push(&mut self, title: &str, message: impl std::fmt::Display) {
+        let message = message.to_string();
+        log::error!("{}: {}", title, message);
+        self.queue.push_back(ErrorReport { title: title.to_string(), message });
+    }
+
+    /// Whether an error is waiting to be shown
+    pub fn is_open(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// The error currently shown
+    pub fn current(&self) -> Option<&ErrorReport> {
+        self.queue.front()
+    }
+
+    /// Dismiss the error currently shown
+    pub fn dismiss(&mut self) {
+        self.queue.pop_front();
+    }
+}
+
+/// Split text into lines that fit `max_width` at the given font size
+pub fn wrap_text(text: &str, max_width: f32, font_size: u16) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if !line.is_empty() && measure_text(&candidate, None, font_size, 1.0).width > max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Header and border colors of a dialog
+struct Palette {
+    header: Color,
+    border: Color,
+}
+
+const QUESTION_PALETTE: Palette = Palette { header: Color::new(0.2, 0.2, 0.25, 1.0), border: Color::new(0.4, 0.4, 0.47, 1.0) };
+const ERROR_PALETTE: Palette = Palette { header: Color::new(0.27, 0.14, 0.14, 1.0), border: Color::new(0.47, 0.2, 0.2, 1.0) };
+
+/// Draw a dialog centered on the screen and return the button pressed
+/// (clicked, or Enter / Escape). `note` goes in the footer's left corner.
+fn draw_dialog_box(ctx: &mut UiContext, dialog: &mut Dialog, note: Option<&str>, palette: &Palette) -> Option<usize> {
+    // Darken background
+    draw_rectangle(0.0, 0.0, ui_screen_width(), ui_screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = (ui_screen_width() * 0.6).clamp(280.0, 520.0);
+    let lines = wrap_text(&dialog.message, dialog_w - 32.0, 14);
+    let header_h = 36.0;
+    let footer_h = 44.0;
+    let input_h = if dialog.input.is_some() { 36.0 } else { 0.0 };
+    let dialog_h = header_h + 16.0 + lines.len() as f32 * 18.0 + input_h + footer_h;
+    let x = ((ui_screen_width() - dialog_w) / 2.0).floor();
+    let y = ((ui_screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(x, y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(x, y, dialog_w, dialog_h, 2.0, palette.border);
+
+    // Header
+    draw_rectangle(x, y, dialog_w, header_h, palette.header);
+    draw_text(&dialog.title, x + 16.0, y + 24.0, 18.0, WHITE);
+
+    // Message
+    let mut line_y = y + header_h + 22.0;
+    for line in &lines {
+        draw_text(line, x + 16.0, line_y, 14.0, Color::from_rgba(210, 210, 210, 255));
+        line_y += 18.0;
+    }
+
+    // Text field (always focused while the dialog is up)
+    let last = dialog.buttons.len().saturating_sub(1);
+    let mut pressed = None;
+    if let Some(text) = &mut dialog.input {
+        let field = Rect::new(x + 16.0, line_y - 6.0, dialog_w - 32.0, 26.0);
+        draw_rectangle(field.x, field.y, field.w, field.h, Color::from_rgba(20, 20, 24, 255));
+        draw_rectangle_lines(field.x, field.y, field.w, field.h, 1.0, ACCENT_COLOR);
+        draw_text(&format!("{}_", text), field.x + 8.0, field.y + 18.0, 14.0, WHITE);
+        match edit_text(ctx, DIALOG_FOCUS, text, 64) {
+            TextEdit::Typing => {}
+            TextEdit::Commit => pressed = Some(0),
+            TextEdit::Cancel => pressed = Some(last),
+        }
+    } else if !ctx.is_modal_active() {
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            pressed = Some(0);
+        } else if is_key_pressed(KeyCode::Escape) {
+            pressed = Some(last);
+        }
+    }
+
+    // Footer: buttons right-aligned, in order
+    if let Some(note) = note {
+        draw_text(note, x + 16.0, y + dialog_h - 18.0, 14.0, Color::from_rgba(150, 150, 150, 255));
+    }
+    let widths: Vec<f32> = dialog
+        .buttons
+        .iter()
+        .map(|(label, _)| (measure_text(label, None, 14, 1.0).width + 24.0).max(80.0))
+        .collect();
+    let mut button_x = x + dialog_w - 10.0 - widths.iter().map(|w| w + 10.0).sum::<f32>();
+    for (i, (label, style)) in dialog.buttons.iter().enumerate() {
+        let rect = Rect::new(button_x, y + dialog_h - footer_h + 8.0, widths[i], 28.0);
+        if draw_text_button(ctx, rect, label, style.color()) {
+            pressed = Some(i);
+        }
+        button_x += widths[i] + 10.0;
+    }
+    pressed.filter(|_| !dialog.buttons.is_empty())
+}
+
+/// Draw the dialog opened with `UiContext::open_dialog`, if any, and record
+/// the answer once a button is pressed
+pub fn draw_dialog(ctx: &mut UiContext) {
+    let Some((id, mut dialog)) = ctx.take_dialog() else {
+        return;
+    };
+    match draw_dialog_box(ctx, &mut dialog, None, &QUESTION_PALETTE) {
+        Some(button) => {
+            let text = dialog.input.unwrap_or_default();
+            ctx.release_focus(DIALOG_FOCUS);
+            ctx.answer_dialog(id, DialogReply { button, text });
+        }
+        None => ctx.keep_dialog(id, dialog),
+    }
+}
+
+/// Draw the oldest queued error as a modal dialog (OK / Enter / Escape dismisses)
+pub fn draw_error_dialog(ctx: &mut UiContext, dialog: &mut ErrorDialog) {
+    let Some(report) = dialog.current() else {
+        return;
+    };
+    let mut error = Dialog::new(&report.title, &report.message).button(&tr("common.ok"), ButtonStyle::Accent);
+    let remaining = dialog.queue.len() - 1;
+    let note = (remaining > 0).then(|| format!("{} more", remaining));
+    if draw_dialog_box(ctx, &mut error, note.as_deref(), &ERROR_PALETTE).is_some() {
+        dialog.dismiss();
+    }
+}
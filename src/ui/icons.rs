@@ -11,6 +11,7 @@ pub mod icon {
     pub const SAVE_AS: char = '\u{e40f}';  // save-all (Save As)
     pub const FOLDER_OPEN: char = '\u{e247}';
     pub const FILE_PLUS: char = '\u{e0c9}';
+    pub const PACKAGE: char = '\u{e129}';       // Export game
 
     // Edit operations
     pub const UNDO: char = '\u{e19b}';
@@ -41,11 +42,20 @@ pub mod icon {
     pub const LAYERS: char = '\u{e529}';
     pub const GRID: char = '\u{e0e9}';
     pub const DOOR_CLOSED: char = '\u{e09a}';  // Portal (doorway between rooms)
+    pub const BOXES: char = '\u{e2d0}';        // Box room tool
+    pub const MERGE: char = '\u{e43f}';        // Merge rooms
+    pub const SPLIT: char = '\u{e440}';        // Split room
+    pub const SHAPES: char = '\u{e4b3}';       // Entity tool
+    pub const GAMEPAD: char = '\u{e0de}';      // Simulate in viewport
 
     // PS1 effect toggles
     pub const WAVES: char = '\u{e283}';       // Affine texture mapping (warpy)
     pub const MAGNET: char = '\u{e2b5}';      // Vertex snapping (jitter)
     pub const MONITOR: char = '\u{e11d}';     // Low resolution mode
+    pub const TV: char = '\u{e195}';          // CRT filter
+    pub const ROWS_2: char = '\u{e439}';      // Interlaced fields
+    pub const ROWS_4: char = '\u{e58b}';      // Interlace combing
+    pub const BINARY: char = '\u{e1f2}';      // Fixed-point math
     pub const SUN: char = '\u{e178}';         // Lighting/shading
     pub const BLEND: char = '\u{e59c}';       // Dithering (color blending)
 
@@ -53,6 +63,10 @@ pub mod icon {
     pub const MUSIC: char = '\u{e122}';       // Music/notes
     pub const PIANO: char = '\u{e2ea}';       // Piano (keyboard icon)
     pub const LIST_MUSIC: char = '\u{e10b}';  // Arrangement/playlist
+    pub const REPEAT: char = '\u{e146}';      // Loop song
+    pub const REPEAT_1: char = '\u{e1fd}';    // Loop pattern
+    pub const ARROW_RIGHT_TO_LINE: char = '\u{e459}'; // Play once
+    pub const CIRCLE_DOT: char = '\u{e345}';  // Record (edit mode)
 
     // Tab bar icons
     pub const HOUSE: char = '\u{e0f5}';           // Home tab
@@ -64,6 +78,14 @@ pub mod icon {
 
     // Browser / Examples
     pub const BOOK_OPEN: char = '\u{e05f}';       // Examples browser
+    pub const GIT_COMPARE: char = '\u{e359}';     // Compare/merge levels
+    pub const ARROW_UP_FROM_DOT: char = '\u{e44e}'; // Face normals overlay
+    pub const FLIP_VERTICAL: char = '\u{e35f}';  // Backface overlay
+    pub const COMPASS: char = '\u{e09b}';        // Wall direction labels
+    pub const TIMER: char = '\u{e1e0}';          // Level statistics
+    pub const GAUGE: char = '\u{e1bf}';          // Performance budget
+    pub const MEMORY_STICK: char = '\u{e445}';   // VRAM view
+    pub const PALETTE: char = '\u{e1dd}';        // Texture editor
 }
 
 /// Draw a Lucide icon centered in a rect
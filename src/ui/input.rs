@@ -1,6 +1,20 @@
 //! Input state for UI interaction
+//!
+//! Mouse and touch both end up in `MouseState`; `TouchInput` maps gestures
+//! onto it (tap = left click, two-finger drag = right drag, pinch = scroll).
+//!
+//! Keyboard input goes to whoever holds the focus (`UiContext::set_focus`):
+//! a text field being typed into, or a panel that was clicked. A focus no
+//! widget asked about for a whole frame is dropped, so a text field that
+//! disappears doesn't keep the keyboard.
+//!
+//! Ctrl (Cmd on macOS) combinations are app-wide `Shortcut`s rather than
+//! tool keys; the app routes them to the active tool.
+//!
+//! A dialog (`UiContext::open_dialog`) is drawn over everything at the end
+//! of the frame; its answer waits one frame for the owner to take it.
 
-use super::Rect;
+use super::{Dialog, DialogId, DialogReply, Rect, ui_screen_width, ui_screen_height};
 use macroquad::prelude::*;
 
 /// Mouse button state
@@ -15,6 +29,91 @@ pub struct MouseState {
     pub scroll: f32,         // Scroll wheel delta
 }
 
+/// Finger distance change (pixels) that counts as one scroll wheel notch
+pub const PINCH_NOTCH_PX: f32 = 24.0;
+
+/// Turns touch points into mouse states
+///
+/// One finger behaves like the left button. Two or more fingers hold the
+/// right button at their centroid (camera rotate / pan) and spreading or
+/// pinching them scrolls. Once a gesture used two fingers it stays a gesture
+/// until every finger is lifted, so lifting one finger doesn't click.
+#[derive(Debug, Clone, Default)]
+pub struct TouchInput {
+    /// Fingers down last frame
+    fingers: usize,
+    /// Whether two or more fingers were down since the last full release
+    multi_touch: bool,
+    /// Last reported position
+    last_pos: (f32, f32),
+    /// Finger distance at the last emitted scroll notch
+    pinch_anchor: Option<f32>,
+}
+
+impl TouchInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mouse state for this frame's touch points, or None when no touch is active
+    pub fn update(&mut self, points: &[(f32, f32)]) -> Option<MouseState> {
+        let prev_fingers = self.fingers;
+        self.fingers = points.len();
+
+        if points.is_empty() {
+            if prev_fingers == 0 {
+                return None;
+            }
+            // Release frame: finish the click (or end the gesture) at the last position
+            let was_click = !self.multi_touch;
+            self.multi_touch = false;
+            self.pinch_anchor = None;
+            return Some(MouseState {
+                x: self.last_pos.0,
+                y: self.last_pos.1,
+                left_released: was_click,
+                ..Default::default()
+            });
+        }
+
+        let n = points.len() as f32;
+        let x = points.iter().map(|p| p.0).sum::<f32>() / n;
+        let y = points.iter().map(|p| p.1).sum::<f32>() / n;
+        self.last_pos = (x, y);
+
+        if points.len() == 1 {
+            self.pinch_anchor = None;
+            if self.multi_touch {
+                return Some(MouseState { x, y, ..Default::default() });
+            }
+            return Some(MouseState {
+                x,
+                y,
+                left_down: true,
+                left_pressed: prev_fingers == 0,
+                ..Default::default()
+            });
+        }
+
+        // Two or more fingers: right drag at the centroid, pinch distance scrolls
+        self.multi_touch = true;
+        let (a, b) = (points[0], points[1]);
+        let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        let anchor = *self.pinch_anchor.get_or_insert(distance);
+        let notches = ((distance - anchor) / PINCH_NOTCH_PX).trunc();
+        if notches != 0.0 {
+            self.pinch_anchor = Some(anchor + notches * PINCH_NOTCH_PX);
+        }
+        Some(MouseState {
+            x,
+            y,
+            right_down: true,
+            scroll: notches,
+            ..Default::default()
+        })
+    }
+}
+
 /// Pending tooltip to be drawn at end of frame
 #[derive(Clone)]
 pub struct PendingTooltip {
@@ -40,6 +139,56 @@ impl MouseState {
     }
 }
 
+/// App-wide keyboard shortcut, pressed with Ctrl or Cmd held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    New,
+    Open,
+    Save,
+    SaveAs,
+    Undo,
+    Redo,
+}
+
+impl Shortcut {
+    /// Shortcut of `key` pressed with Ctrl / Cmd (and maybe Shift) held
+    pub fn from_key(key: KeyCode, shift: bool) -> Option<Shortcut> {
+        match key {
+            KeyCode::N => Some(Shortcut::New),
+            KeyCode::O => Some(Shortcut::Open),
+            KeyCode::S if shift => Some(Shortcut::SaveAs),
+            KeyCode::S => Some(Shortcut::Save),
+            KeyCode::Z if shift => Some(Shortcut::Redo),
+            KeyCode::Z => Some(Shortcut::Undo),
+            KeyCode::Y => Some(Shortcut::Redo),
+            _ => None,
+        }
+    }
+
+    /// The shortcut pressed this frame, if any
+    pub fn pressed() -> Option<Shortcut> {
+        if !shortcut_modifier_down() {
+            return None;
+        }
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        [KeyCode::N, KeyCode::O, KeyCode::S, KeyCode::Z, KeyCode::Y]
+            .into_iter()
+            .filter(|&key| is_key_pressed(key))
+            .find_map(|key| Shortcut::from_key(key, shift))
+    }
+}
+
+/// Whether Ctrl or Cmd is held, so letter keys belong to a `Shortcut`
+/// rather than to the tool
+pub fn shortcut_modifier_down() -> bool {
+    is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+        || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper)
+}
+
+/// Stable name of a widget or panel that can hold the keyboard focus,
+/// e.g. "tracker.pattern"
+pub type FocusId = &'static str;
+
 /// UI context passed through the frame
 pub struct UiContext {
     pub mouse: MouseState,
@@ -53,6 +202,18 @@ pub struct UiContext {
     pub tooltip: Option<PendingTooltip>,
     /// Whether a modal dialog is active (blocks input to background)
     modal_active: bool,
+    /// Widget or panel the keyboard goes to (kept across frames)
+    focus: Option<FocusId>,
+    /// Whether the focus holder was drawn this frame
+    focus_seen: bool,
+    /// Whether a text field is being typed into this frame
+    typing: bool,
+    /// Dialog waiting for an answer
+    dialog: Option<(DialogId, Dialog)>,
+    /// Answer to the last dialog, until its owner takes it
+    reply: Option<(DialogId, DialogReply)>,
+    /// Whether `reply` was given last frame (older answers are dropped)
+    reply_fresh: bool,
 }
 
 impl UiContext {
@@ -64,6 +225,12 @@ impl UiContext {
             id_counter: 0,
             tooltip: None,
             modal_active: false,
+            focus: None,
+            focus_seen: false,
+            typing: false,
+            dialog: None,
+            reply: None,
+            reply_fresh: false,
         }
     }
 
@@ -93,6 +260,106 @@ impl UiContext {
         self.mouse = real_mouse;
     }
 
+    /// Give the keyboard to `id` (a text field starting to edit, a panel clicked)
+    pub fn set_focus(&mut self, id: FocusId) {
+        self.focus = Some(id);
+        self.focus_seen = true;
+    }
+
+    /// Take the keyboard back from `id`, if it has it
+    pub fn release_focus(&mut self, id: FocusId) {
+        if self.focus == Some(id) {
+            self.focus = None;
+        }
+    }
+
+    /// Whether `id` holds the keyboard focus (keeps it alive for this frame)
+    pub fn has_focus(&mut self, id: FocusId) -> bool {
+        if self.focus == Some(id) {
+            self.focus_seen = true;
+            return true;
+        }
+        false
+    }
+
+    /// Take the focus for `id` unless another widget or panel holds it.
+    /// Returns whether `id` has it.
+    pub fn claim_focus(&mut self, id: FocusId) -> bool {
+        if self.focus.is_none() {
+            self.set_focus(id);
+        }
+        self.has_focus(id)
+    }
+
+    /// Whether key presses go to `id` this frame: no modal is open, and it
+    /// holds the focus or nothing does
+    pub fn accepts_keys(&mut self, id: FocusId) -> bool {
+        !self.modal_active && (self.focus.is_none() || self.has_focus(id))
+    }
+
+    /// Focus the panel `id` when the mouse is pressed inside `rect`; a press
+    /// outside takes the focus away from it
+    pub fn focus_on_click(&mut self, rect: &Rect, id: FocusId) {
+        if self.mouse.left_pressed {
+            if self.mouse.inside(rect) {
+                self.set_focus(id);
+            } else {
+                self.release_focus(id);
+            }
+        } else {
+            self.has_focus(id);
+        }
+    }
+
+    /// Whether a text field was typed into this frame (shortcuts wait)
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    /// Note that a text field holding the focus was drawn
+    pub(super) fn set_typing(&mut self) {
+        self.typing = true;
+    }
+
+    /// Show `dialog` from the next frame on (replacing any open one); its
+    /// answer comes back through `take_reply(id)`
+    pub fn open_dialog(&mut self, id: DialogId, dialog: Dialog) {
+        // Its text field claims the keyboard when first drawn
+        if dialog.input.is_some() {
+            self.focus = None;
+        }
+        self.dialog = Some((id, dialog));
+    }
+
+    /// Whether a dialog is waiting for an answer
+    pub fn dialog_open(&self) -> bool {
+        self.dialog.is_some()
+    }
+
+    /// The answer to dialog `id`, once (None until a button is pressed)
+    pub fn take_reply(&mut self, id: DialogId) -> Option<DialogReply> {
+        match &self.reply {
+            Some((reply_id, _)) if *reply_id == id => self.reply.take().map(|(_, reply)| reply),
+            _ => None,
+        }
+    }
+
+    /// Take the open dialog to draw it (see `draw_dialog`)
+    pub(super) fn take_dialog(&mut self) -> Option<(DialogId, Dialog)> {
+        self.dialog.take()
+    }
+
+    /// Put back a dialog that wasn't answered this frame
+    pub(super) fn keep_dialog(&mut self, id: DialogId, dialog: Dialog) {
+        self.dialog = Some((id, dialog));
+    }
+
+    /// Close the drawn dialog with `reply`
+    pub(super) fn answer_dialog(&mut self, id: DialogId, reply: DialogReply) {
+        self.reply = Some((id, reply));
+        self.reply_fresh = true;
+    }
+
     /// Generate a unique ID for a widget
     pub fn next_id(&mut self) -> u64 {
         self.id_counter += 1;
@@ -106,6 +373,14 @@ impl UiContext {
         self.id_counter = 0;
         self.tooltip = None;
         self.modal_active = false;
+        if !self.focus_seen {
+            self.focus = None;
+        }
+        self.focus_seen = false;
+        self.typing = false;
+        if !std::mem::take(&mut self.reply_fresh) {
+            self.reply = None;
+        }
 
         // Clear dragging if mouse released
         if !self.mouse.left_down {
@@ -137,8 +412,8 @@ impl UiContext {
             let box_h = dims.height + padding * 2.0;
 
             // Position below and to the right of cursor, but keep on screen
-            let screen_w = screen_width();
-            let screen_h = screen_height();
+            let screen_w = ui_screen_width();
+            let screen_h = ui_screen_height();
             let mut x = tip.x + 12.0;
             let mut y = tip.y + 20.0;
 
@@ -193,3 +468,106 @@ impl Default for UiContext {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_follows_clicks_and_expires() {
+        let mut ctx = UiContext::new();
+        let panel = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let click = |x| MouseState { x, y: 10.0, left_down: true, left_pressed: true, ..Default::default() };
+
+        // Nobody has the focus: every panel takes keys
+        ctx.begin_frame(MouseState::default());
+        assert!(ctx.accepts_keys("a") && ctx.accepts_keys("b"));
+
+        ctx.begin_frame(click(10.0));
+        ctx.focus_on_click(&panel, "a");
+        assert!(ctx.accepts_keys("a") && !ctx.accepts_keys("b"));
+
+        // Still drawn next frame: keeps it; a modal blocks it
+        ctx.begin_frame(MouseState::default());
+        ctx.focus_on_click(&panel, "a");
+        ctx.begin_modal();
+        assert!(!ctx.accepts_keys("a"));
+
+        // Clicking outside gives it up
+        ctx.begin_frame(click(200.0));
+        ctx.focus_on_click(&panel, "a");
+        assert!(ctx.accepts_keys("b"));
+
+        // A holder not drawn for a frame loses it
+        ctx.set_focus("field");
+        ctx.begin_frame(MouseState::default());
+        ctx.begin_frame(MouseState::default());
+        assert!(ctx.accepts_keys("b"));
+    }
+
+    #[test]
+    fn test_shortcut_keys() {
+        assert_eq!(Shortcut::from_key(KeyCode::Z, false), Some(Shortcut::Undo));
+        assert_eq!(Shortcut::from_key(KeyCode::Z, true), Some(Shortcut::Redo));
+        assert_eq!(Shortcut::from_key(KeyCode::Y, false), Some(Shortcut::Redo));
+        assert_eq!(Shortcut::from_key(KeyCode::S, true), Some(Shortcut::SaveAs));
+        assert_eq!(Shortcut::from_key(KeyCode::X, false), None);
+    }
+
+    #[test]
+    fn test_dialog_reply_waits_one_frame() {
+        let mut ctx = UiContext::new();
+        ctx.open_dialog("delete", Dialog::new("Delete?", ""));
+        assert!(ctx.dialog_open());
+
+        // Answered at the end of a frame, taken by its owner during the next
+        let (id, _) = ctx.take_dialog().unwrap();
+        ctx.answer_dialog(id, DialogReply { button: 0, text: String::new() });
+        ctx.begin_frame(MouseState::default());
+        assert!(!ctx.dialog_open());
+        assert_eq!(ctx.take_reply("rename"), None);
+        assert_eq!(ctx.take_reply("delete").map(|r| r.button), Some(0));
+        assert_eq!(ctx.take_reply("delete"), None);
+
+        // An answer nobody takes is dropped
+        ctx.answer_dialog("delete", DialogReply { button: 1, text: String::new() });
+        ctx.begin_frame(MouseState::default());
+        ctx.begin_frame(MouseState::default());
+        assert_eq!(ctx.take_reply("delete"), None);
+    }
+
+    #[test]
+    fn test_tap_is_click() {
+        let mut touch = TouchInput::new();
+        assert!(touch.update(&[]).is_none());
+        let down = touch.update(&[(10.0, 20.0)]).unwrap();
+        assert!(down.left_down && down.left_pressed);
+        let held = touch.update(&[(10.0, 20.0)]).unwrap();
+        assert!(held.left_down && !held.left_pressed);
+        let up = touch.update(&[]).unwrap();
+        assert!(up.left_released && !up.left_down);
+        assert_eq!((up.x, up.y), (10.0, 20.0));
+        assert!(touch.update(&[]).is_none());
+    }
+
+    #[test]
+    fn test_two_finger_drag_and_pinch() {
+        let mut touch = TouchInput::new();
+        let drag = touch.update(&[(0.0, 0.0), (100.0, 0.0)]).unwrap();
+        assert!(drag.right_down && !drag.left_down);
+        assert_eq!((drag.x, drag.y), (50.0, 0.0));
+        assert_eq!(drag.scroll, 0.0);
+
+        // Spreading by two notches scrolls up by two
+        let spread = touch.update(&[(0.0, 0.0), (100.0 + PINCH_NOTCH_PX * 2.5, 0.0)]).unwrap();
+        assert_eq!(spread.scroll, 2.0);
+        let pinch = touch.update(&[(0.0, 0.0), (100.0, 0.0)]).unwrap();
+        assert_eq!(pinch.scroll, -2.0);
+
+        // Lifting one finger mid-gesture doesn't click
+        let one = touch.update(&[(0.0, 0.0)]).unwrap();
+        assert!(!one.left_down && !one.left_pressed);
+        let up = touch.update(&[]).unwrap();
+        assert!(!up.left_released);
+    }
+}
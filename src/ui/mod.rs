@@ -16,6 +16,8 @@ mod widgets;
 mod input;
 mod tabbar;
 mod icons;
+mod dialog;
+mod scale;
 
 pub use rect::*;
 pub use panel::*;
@@ -23,3 +25,5 @@ pub use widgets::*;
 pub use input::*;
 pub use tabbar::*;
 pub use icons::*;
+pub use dialog::*;
+pub use scale::*;
@@ -91,6 +91,11 @@ impl SplitPanel {
         self.child_rects(bounds)
     }
 
+    /// Whether the divider is being dragged
+    pub fn is_dragging(&self, ctx: &UiContext) -> bool {
+        ctx.is_dragging(self.id)
+    }
+
     /// Clamp ratio to respect minimum sizes
     fn clamp_ratio(&self, ratio: f32, total_size: f32) -> f32 {
         let min_ratio = self.min_size / total_size;
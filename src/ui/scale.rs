@@ -0,0 +1,124 @@
+//! Global UI scale
+//!
+//! All UI code lays out in logical pixels. Each frame the app picks a scale
+//! (user override or auto-detected) and `begin_ui_scale` sets a camera that
+//! maps logical pixels to the window:
+//! - use `ui_screen_width` / `ui_screen_height` instead of `screen_width` / `screen_height`
+//! - use `ui_mouse_position` instead of `mouse_position`
+//! - scissor rects are in physical pixels, multiply by `ui_pixel_scale`
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use macroquad::prelude::*;
+
+/// Smallest selectable UI scale
+pub const MIN_UI_SCALE: f32 = 0.75;
+/// Largest selectable UI scale
+pub const MAX_UI_SCALE: f32 = 3.0;
+/// Presets offered by the scale selector (None = auto)
+pub const UI_SCALE_PRESETS: [Option<f32>; 6] = [None, Some(1.0), Some(1.25), Some(1.5), Some(2.0), Some(2.5)];
+
+/// Current scale, stored as f32 bits (1.0 until the first frame)
+static UI_SCALE: AtomicU32 = AtomicU32::new(0x3F80_0000);
+
+/// Current UI scale factor
+pub fn ui_scale() -> f32 {
+    f32::from_bits(UI_SCALE.load(Ordering::Relaxed))
+}
+
+/// Set the UI scale factor (clamped, rounded to quarter steps)
+pub fn set_ui_scale(scale: f32) {
+    let scale = ((scale * 4.0).round() / 4.0).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+    UI_SCALE.store(scale.to_bits(), Ordering::Relaxed);
+}
+
+/// Pick a scale for displays the OS doesn't report as high-DPI
+///
+/// With `high_dpi` enabled macroquad already works in logical pixels when the
+/// OS reports a DPI scale (macOS Retina, scaled Windows desktops), so nothing
+/// extra is needed. Otherwise a 4K window is 1:1 and everything is tiny, so
+/// scale up based on the window height.
+pub fn auto_ui_scale(dpi_scale: f32, window_height: f32) -> f32 {
+    if dpi_scale > 1.0 {
+        1.0
+    } else if window_height >= 2000.0 {
+        2.0
+    } else if window_height >= 1400.0 {
+        1.5
+    } else {
+        1.0
+    }
+}
+
+/// Preset after `current` in `UI_SCALE_PRESETS` (wraps around)
+pub fn next_ui_scale_preset(current: Option<f32>) -> Option<f32> {
+    let index = UI_SCALE_PRESETS.iter().position(|p| *p == current);
+    index.and_then(|i| UI_SCALE_PRESETS[(i + 1) % UI_SCALE_PRESETS.len()])
+}
+
+/// Format a scale as a percentage ("150%")
+pub fn format_ui_scale(scale: f32) -> String {
+    format!("{}%", (scale * 100.0).round() as i32)
+}
+
+/// Window width in logical UI pixels
+pub fn ui_screen_width() -> f32 {
+    screen_width() / ui_scale()
+}
+
+/// Window height in logical UI pixels
+pub fn ui_screen_height() -> f32 {
+    screen_height() / ui_scale()
+}
+
+/// Mouse position in logical UI pixels
+pub fn ui_mouse_position() -> (f32, f32) {
+    let (x, y) = mouse_position();
+    (x / ui_scale(), y / ui_scale())
+}
+
+/// Physical pixels per logical UI pixel (for scissor rects)
+pub fn ui_pixel_scale() -> f32 {
+    screen_dpi_scale() * ui_scale()
+}
+
+/// Set the camera so everything drawn after this is scaled by `ui_scale`
+pub fn begin_ui_scale() {
+    let (w, h) = (ui_screen_width(), ui_screen_height());
+    set_camera(&Camera2D {
+        target: vec2(w * 0.5, h * 0.5),
+        zoom: vec2(2.0 / w, -2.0 / h),
+        ..Default::default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_ui_scale() {
+        assert_eq!(auto_ui_scale(2.0, 2160.0), 1.0);
+        assert_eq!(auto_ui_scale(1.0, 720.0), 1.0);
+        assert_eq!(auto_ui_scale(1.0, 1440.0), 1.5);
+        assert_eq!(auto_ui_scale(1.0, 2160.0), 2.0);
+    }
+
+    #[test]
+    fn test_set_ui_scale_clamps_and_rounds() {
+        set_ui_scale(1.6);
+        assert_eq!(ui_scale(), 1.5);
+        set_ui_scale(10.0);
+        assert_eq!(ui_scale(), MAX_UI_SCALE);
+        set_ui_scale(0.1);
+        assert_eq!(ui_scale(), MIN_UI_SCALE);
+        set_ui_scale(1.0);
+    }
+
+    #[test]
+    fn test_next_ui_scale_preset() {
+        assert_eq!(next_ui_scale_preset(None), Some(1.0));
+        assert_eq!(next_ui_scale_preset(Some(2.5)), None);
+        assert_eq!(next_ui_scale_preset(Some(1.1)), None);
+        assert_eq!(format_ui_scale(1.25), "125%");
+    }
+}
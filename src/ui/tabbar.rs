@@ -47,12 +47,12 @@ pub mod layout {
 /// A tab entry with icon and label
 pub struct TabEntry {
     pub icon: char,
-    pub label: &'static str,
+    pub label: String,
 }
 
 impl TabEntry {
-    pub const fn new(icon: char, label: &'static str) -> Self {
-        Self { icon, label }
+    pub fn new(icon: char, label: impl Into<String>) -> Self {
+        Self { icon, label: label.into() }
     }
 }
 
@@ -89,7 +89,7 @@ pub fn draw_fixed_tabs(
 
     for (i, tab) in tabs.iter().enumerate() {
         // Measure text to size tab - round width to integer to prevent accumulation of fractional pixels
-        let text_dims = measure_text(tab.label, None, layout::FONT_SIZE as u16, 1.0);
+        let text_dims = measure_text(&tab.label, None, layout::FONT_SIZE as u16, 1.0);
         // Tab width: padding + icon + gap + text + padding
         let content_width = layout::ICON_SIZE + layout::ICON_LABEL_GAP + text_dims.width;
         let tab_width = (content_width + layout::TAB_PADDING_H * 2.0).round();
@@ -162,7 +162,7 @@ pub fn draw_fixed_tabs(
         let text_x = (content_start_x + layout::ICON_SIZE + layout::ICON_LABEL_GAP).round();
         let text_y = (center_y + text_dims.height * 0.5 - 1.0).round();
         draw_text_ex(
-            tab.label,
+            &tab.label,
             text_x,
             text_y,
             TextParams {
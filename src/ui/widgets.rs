@@ -1,7 +1,7 @@
 //! Basic UI widgets
 
 use macroquad::prelude::*;
-use super::{Rect, UiContext, draw_icon_centered};
+use super::{FocusId, Rect, UiContext, draw_icon_centered, ui_mouse_position};
 
 // =============================================================================
 // Scrollable List Widget
@@ -68,7 +68,7 @@ pub fn draw_scrollable_list(
 
     // Handle scrolling
     if ctx.mouse.inside(&rect) {
-        let scroll_delta = mouse_wheel().1 * 30.0;
+        let scroll_delta = ctx.mouse.scroll * 30.0;
         let max_scroll = (items.len() as f32 * row_height - rect.h).max(0.0);
         *scroll_offset = (*scroll_offset - scroll_delta).clamp(0.0, max_scroll);
     }
@@ -174,7 +174,7 @@ pub fn draw_link(
     let dims = measure_text(text, None, font_size as u16, 1.0);
     let link_rect = Rect::new(x, y - dims.height, dims.width, dims.height + 4.0);
 
-    let (mx, my) = mouse_position();
+    let (mx, my) = ui_mouse_position();
     let hovered = link_rect.contains(mx, my);
     let clicked = hovered && is_mouse_button_pressed(MouseButton::Left);
 
@@ -244,6 +244,11 @@ impl Toolbar {
         }
     }
 
+    /// X position where the next item will be placed
+    pub fn cursor_x(&self) -> f32 {
+        self.cursor_x
+    }
+
     /// Add a separator
     pub fn separator(&mut self) {
         self.cursor_x += self.spacing * 2.0;
@@ -287,9 +292,85 @@ impl Toolbar {
     }
 }
 
+// =============================================================================
+// Text Input
+// =============================================================================
+
+/// What a frame of typing into a text field did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEdit {
+    /// Still typing
+    Typing,
+    /// Enter was pressed, or another widget took the keyboard: keep the text
+    Commit,
+    /// Escape was pressed: drop the text
+    Cancel,
+}
+
+/// Type into the text field `id` (call every frame it's being edited). The
+/// field takes the keyboard focus unless something else holds it (callers
+/// starting an edit from a focused panel should `set_focus` first).
+/// Printable characters append up to `max_len` bytes and Backspace deletes;
+/// Enter and Escape end the edit.
+pub fn edit_text(ctx: &mut UiContext, id: FocusId, text: &mut String, max_len: usize) -> TextEdit {
+    if !ctx.claim_focus(id) {
+        return TextEdit::Commit;
+    }
+    if ctx.is_modal_active() {
+        return TextEdit::Typing;
+    }
+    ctx.set_typing();
+    while let Some(c) = get_char_pressed() {
+        if !c.is_control() && text.len() + c.len_utf8() <= max_len {
+            text.push(c);
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) {
+        text.pop();
+    }
+    // The focus stays this frame so the Enter or Escape isn't also a shortcut,
+    // and lapses once the field is no longer drawn
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+        TextEdit::Commit
+    } else if is_key_pressed(KeyCode::Escape) {
+        TextEdit::Cancel
+    } else {
+        TextEdit::Typing
+    }
+}
+
 /// Accent color (cyan like MuseScore)
 pub const ACCENT_COLOR: Color = Color::new(0.0, 0.75, 0.9, 1.0);
 
+/// Draw a text button
+pub fn draw_text_button(ctx: &mut UiContext, rect: Rect, text: &str, bg_color: Color) -> bool {
+    draw_text_button_enabled(ctx, rect, text, bg_color, true)
+}
+
+/// Draw a text button with enabled state
+pub fn draw_text_button_enabled(ctx: &mut UiContext, rect: Rect, text: &str, bg_color: Color, enabled: bool) -> bool {
+    let hovered = enabled && ctx.mouse.inside(&rect);
+    let clicked = hovered && ctx.mouse.left_pressed;
+
+    let color = if !enabled {
+        Color::from_rgba(50, 50, 55, 255)
+    } else if hovered {
+        Color::new(bg_color.r * 1.2, bg_color.g * 1.2, bg_color.b * 1.2, bg_color.a)
+    } else {
+        bg_color
+    };
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+
+    let text_color = if enabled { WHITE } else { Color::from_rgba(100, 100, 100, 255) };
+    let dims = measure_text(text, None, 14, 1.0);
+    let tx = rect.x + (rect.w - dims.width) / 2.0;
+    let ty = rect.y + (rect.h + dims.height) / 2.0 - 2.0;
+    draw_text(text, tx, ty, 14.0, text_color);
+
+    clicked
+}
+
 /// Draw an icon button, returns true if clicked (flat style, no background when inactive)
 pub fn icon_button(ctx: &mut UiContext, rect: Rect, icon: char, icon_font: Option<&Font>, tooltip: &str) -> bool {
     draw_flat_icon_button(ctx, rect, icon, icon_font, tooltip, false)
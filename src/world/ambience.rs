@@ -0,0 +1,106 @@
+//! Ambience presets
+//!
+//! One-click lighting moods for a level:
+//! - a preset sets every room's ambient light level and the level's color
+//!   grade (see `ColorGrade`)
+//! - built-in presets (dungeon, moonlit, torchlit, fog-heavy) come first,
+//!   followed by the project's own from `assets/project/ambience.ron`, which
+//!   the editor can append the current ambience to
+//! - the renderer has no fog or sky yet, so "fog-heavy" approximates haze
+//!   with a washed-out, low-contrast grade
+//!
+//! ```ron
+//! [
+//!     (name: "Swamp", ambient: 0.4, grade: (gamma: 1.1, contrast: 0.8, saturation: 0.6, tint: (0.9, 1.05, 0.85))),
+//! ]
+//! ```
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::ColorGrade;
+use super::Level;
+
+/// Location of the project's custom presets
+pub const AMBIENCE_PRESETS_PATH: &str = "assets/project/ambience.ron";
+
+/// A named lighting mood
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AmbiencePreset {
+    pub name: String,
+    /// Ambient light level given to every room (0.0 = dark, 1.0 = bright)
+    pub ambient: f32,
+    pub grade: ColorGrade,
+}
+
+impl AmbiencePreset {
+    fn builtin(name: &str, ambient: f32, gamma: f32, contrast: f32, saturation: f32, tint: [f32; 3]) -> Self {
+        Self { name: name.to_string(), ambient, grade: ColorGrade { gamma, contrast, saturation, tint } }
+    }
+}
+
+/// Presets that ship with the editor
+pub fn builtin_ambience_presets() -> Vec<AmbiencePreset> {
+    vec![
+        AmbiencePreset::builtin("Neutral", 0.5, 1.0, 1.0, 1.0, [1.0, 1.0, 1.0]),
+        AmbiencePreset::builtin("Dungeon", 0.2, 0.8, 1.2, 0.7, [0.95, 0.9, 0.85]),
+        AmbiencePreset::builtin("Moonlit", 0.3, 0.9, 1.1, 0.5, [0.75, 0.85, 1.15]),
+        AmbiencePreset::builtin("Torchlit", 0.35, 1.1, 1.15, 1.1, [1.2, 0.95, 0.7]),
+        AmbiencePreset::builtin("Fog-heavy", 0.6, 1.3, 0.6, 0.5, [0.95, 0.97, 1.0]),
+    ]
+}
+
+/// Load custom presets from a RON file
+pub fn load_ambience_presets(path: &Path) -> Result<Vec<AmbiencePreset>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read ambience presets: {}", e))?;
+    ron::from_str(&contents).map_err(|e| format!("Failed to parse ambience presets: {}", e))
+}
+
+/// Built-in presets followed by the project's own, if it has any
+pub fn project_ambience_presets() -> Vec<AmbiencePreset> {
+    let mut presets = builtin_ambience_presets();
+    presets.extend(load_ambience_presets(Path::new(AMBIENCE_PRESETS_PATH)).unwrap_or_default());
+    presets
+}
+
+/// Write the project's custom presets
+pub fn save_ambience_presets(path: &Path, presets: &[AmbiencePreset]) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(presets, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize ambience presets: {}", e))?;
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+impl Level {
+    /// Give every room the preset's ambient light and the level its grade
+    pub fn apply_ambience_preset(&mut self, preset: &AmbiencePreset) {
+        for room in &mut self.rooms {
+            room.ambient = preset.ambient;
+        }
+        self.ambience = preset.grade;
+    }
+
+    /// The level's current ambience as a preset (ambient light of the first room)
+    pub fn ambience_preset(&self, name: &str) -> AmbiencePreset {
+        let ambient = self.rooms.first().map_or(0.5, |r| r.ambient);
+        AmbiencePreset { name: name.to_string(), ambient, grade: self.ambience }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_test_level;
+
+    #[test]
+    fn test_preset_applies_and_round_trips() {
+        let mut level = create_test_level();
+        let moonlit = builtin_ambience_presets().into_iter().find(|p| p.name == "Moonlit").unwrap();
+        level.apply_ambience_preset(&moonlit);
+        assert!(level.rooms.iter().all(|r| r.ambient == moonlit.ambient));
+        assert_eq!(level.ambience_preset("Moonlit"), moonlit);
+
+        let ron = ron::ser::to_string(&vec![moonlit.clone()]).unwrap();
+        let loaded: Vec<AmbiencePreset> = ron::from_str(&ron).unwrap();
+        assert_eq!(loaded, [moonlit]);
+    }
+}
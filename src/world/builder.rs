@@ -0,0 +1,229 @@
+//! Level builder - construct levels from code without the editor
+//!
+//! Used by the built-in level generators, tests and procedural generation:
+//!
+//! ```
+//! use bonnie_engine::world::{Direction, LevelBuilder, RoomBuilder, TextureRef};
+//!
+//! let level = LevelBuilder::new()
+//!     .room(
+//!         RoomBuilder::new(2, 1)
+//!             .texture(TextureRef::new("retro-texture-pack", "FLOOR_1A"))
+//!             .wall_texture(TextureRef::new("retro-texture-pack", "WALL_1A"))
+//!             .floor(0, 0, 0.0)
+//!             .floor(1, 0, 256.0)
+//!             .ceiling(0, 0, 1024.0)
+//!             .wall(1, 0, Direction::East, 256.0, 1024.0),
+//!     )
+//!     .build();
+//! ```
+
+use crate::rasterizer::Vec3;
+use super::{CameraPath, Direction, HorizontalFace, Level, Room, TextureRef, VerticalFace};
+
+/// Builds a `Level` room by room
+#[derive(Debug, Clone)]
+pub struct LevelBuilder {
+    level: Level,
+}
+
+impl LevelBuilder {
+    pub fn new() -> Self {
+        Self { level: Level::new() }
+    }
+
+    /// Sector size for the level (set before adding rooms)
+    pub fn sector_size(mut self, sector_size: f32) -> Self {
+        self.level.sector_size = sector_size;
+        self
+    }
+
+    /// Height snapping step for the level
+    pub fn click_height(mut self, click_height: f32) -> Self {
+        self.level.click_height = click_height;
+        self
+    }
+
+    /// Add a room (its id is its index in the level)
+    pub fn room(mut self, room: RoomBuilder) -> Self {
+        let mut room = room.build();
+        room.id = self.level.rooms.len();
+        self.level.add_room(room);
+        self
+    }
+
+    /// Add a cutscene camera path
+    pub fn camera_path(mut self, path: CameraPath) -> Self {
+        self.level.camera_paths.push(path);
+        self
+    }
+
+    pub fn build(self) -> Level {
+        self.level
+    }
+}
+
+impl Default for LevelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a single `Room`. Faces use the current texture, set with
+/// `texture` (floors and ceilings) and `wall_texture` (walls).
+#[derive(Debug, Clone)]
+pub struct RoomBuilder {
+    room: Room,
+    texture: TextureRef,
+    wall_texture: TextureRef,
+}
+
+impl RoomBuilder {
+    /// Empty room of `width` x `depth` sectors at the origin
+    pub fn new(width: usize, depth: usize) -> Self {
+        Self {
+            room: Room::new(0, Vec3::ZERO, width, depth),
+            texture: TextureRef::none(),
+            wall_texture: TextureRef::none(),
+        }
+    }
+
+    /// World position of the sector grid origin
+    pub fn at(mut self, position: Vec3) -> Self {
+        self.room.position = position;
+        self
+    }
+
+    /// Ambient light level (0.0 = dark, 1.0 = bright)
+    pub fn ambient(mut self, ambient: f32) -> Self {
+        self.room.ambient = ambient;
+        self
+    }
+
+    /// Texture for subsequent floors and ceilings
+    pub fn texture(mut self, texture: TextureRef) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Texture for subsequent walls
+    pub fn wall_texture(mut self, texture: TextureRef) -> Self {
+        self.wall_texture = texture;
+        self
+    }
+
+    /// Flat floor at grid position
+    pub fn floor(mut self, x: usize, z: usize, height: f32) -> Self {
+        self.room.set_floor(x, z, height, self.texture.clone());
+        self
+    }
+
+    /// Sloped floor with per-corner heights [NW, NE, SE, SW]
+    pub fn sloped_floor(mut self, x: usize, z: usize, heights: [f32; 4]) -> Self {
+        self.room.ensure_sector(x, z).floor = Some(HorizontalFace::sloped(heights, self.texture.clone()));
+        self
+    }
+
+    /// Flat ceiling at grid position
+    pub fn ceiling(mut self, x: usize, z: usize, height: f32) -> Self {
+        self.room.set_ceiling(x, z, height, self.texture.clone());
+        self
+    }
+
+    /// Wall on a sector edge
+    pub fn wall(mut self, x: usize, z: usize, direction: Direction, y_bottom: f32, y_top: f32) -> Self {
+        self.room.add_wall(x, z, direction, y_bottom, y_top, self.wall_texture.clone());
+        self
+    }
+
+    /// Floor and ceiling on every sector of the grid
+    pub fn fill(mut self, floor: f32, ceiling: f32) -> Self {
+        for x in 0..self.room.width {
+            for z in 0..self.room.depth {
+                self = self.floor(x, z, floor).ceiling(x, z, ceiling);
+            }
+        }
+        self
+    }
+
+    /// Walls on every sector edge that doesn't border another sector
+    pub fn enclose(mut self, y_bottom: f32, y_top: f32) -> Self {
+        let occupied: Vec<(usize, usize)> = self.room.iter_sectors().map(|(x, z, _)| (x, z)).collect();
+        for (x, z) in occupied {
+            for direction in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                let (dx, dz) = direction.offset();
+                let (nx, nz) = (x as i32 + dx, z as i32 + dz);
+                let has_neighbor = nx >= 0
+                    && nz >= 0
+                    && self.room.get_sector(nx as usize, nz as usize).is_some();
+                if !has_neighbor {
+                    let wall = VerticalFace::new(y_bottom, y_top, self.wall_texture.clone());
+                    self.room.ensure_sector(x, z).walls_mut(direction).push(wall);
+                }
+            }
+        }
+        self
+    }
+
+    /// Portal to another room
+    pub fn portal(mut self, target_room: usize, vertices: [Vec3; 4], normal: Vec3) -> Self {
+        self.room.add_portal(target_room, vertices, normal);
+        self
+    }
+
+    /// Finish the room (computes its bounds)
+    pub fn build(mut self) -> Room {
+        self.room.recalculate_bounds();
+        self.room
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enclose_skips_shared_edges() {
+        let room = RoomBuilder::new(2, 1).fill(0.0, 1024.0).enclose(0.0, 1024.0).build();
+        let left = room.get_sector(0, 0).unwrap();
+        let right = room.get_sector(1, 0).unwrap();
+        assert!(left.walls_east.is_empty() && right.walls_west.is_empty());
+        assert_eq!(left.walls_west.len(), 1);
+        assert_eq!(right.walls_north.len() + right.walls_south.len(), 2);
+    }
+
+    #[test]
+    fn test_level_builder_assigns_room_ids() {
+        let level = LevelBuilder::new()
+            .room(RoomBuilder::new(1, 1).floor(0, 0, 0.0))
+            .room(RoomBuilder::new(1, 1).at(Vec3::new(1024.0, 0.0, 0.0)).floor(0, 0, 0.0))
+            .build();
+        assert_eq!(level.rooms.len(), 2);
+        assert_eq!(level.rooms[1].id, 1);
+        assert!(level.find_room_at(Vec3::new(1536.0, 0.0, 512.0)).is_some());
+    }
+
+    #[test]
+    fn test_level_sector_size() {
+        let mut level = LevelBuilder::new()
+            .sector_size(512.0)
+            .click_height(64.0)
+            .room(RoomBuilder::new(2, 1).fill(0.0, 1024.0))
+            .build();
+        assert_eq!(level.rooms[0].sector_size, 512.0);
+        assert_eq!(level.rooms[0].bounds.max.x, 1024.0);
+        assert!(level.find_room_at(Vec3::new(1100.0, 0.0, 100.0)).is_none());
+        assert_eq!(level.snap_to_click(100.0), 128.0);
+
+        // Survives a save/load round trip (rooms pick it up again)
+        let loaded = crate::world::load_level_from_str(&ron::to_string(&level).unwrap()).unwrap();
+        assert_eq!((loaded.sector_size, loaded.click_height), (512.0, 64.0));
+        assert_eq!(loaded.rooms[0].sector_size, 512.0);
+
+        // Resizing keeps rooms on the same cells
+        level.rooms[0].position.x = 1024.0;
+        level.set_sector_size(1024.0);
+        assert_eq!(level.rooms[0].position.x, 2048.0);
+        assert_eq!(level.rooms[0].bounds.max.x, 2048.0);
+    }
+}
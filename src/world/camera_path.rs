@@ -0,0 +1,159 @@
+//! Camera paths - spline camera moves for cutscenes
+//!
+//! A path is a list of timed keys (position + look target). Playback runs a
+//! Catmull-Rom spline through the keys, so a handful of keys gives a smooth
+//! fly-through. Paths can be started by name from triggers or scripts.
+
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::Vec3;
+use super::Level;
+
+/// A single camera keyframe
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CameraKey {
+    /// Time in seconds from the start of the path
+    pub time: f32,
+    /// Camera position (world space)
+    pub position: Vec3,
+    /// Point the camera looks at (world space)
+    pub target: Vec3,
+}
+
+/// Named camera path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CameraPath {
+    pub name: String,
+    pub keys: Vec<CameraKey>,
+    /// Trigger name that starts this path (e.g. "boss_intro")
+    #[serde(default)]
+    pub trigger: Option<String>,
+}
+
+/// Gap in seconds between a new key and the end of the path
+pub const NEW_KEY_GAP: f32 = 2.0;
+
+/// Catmull-Rom interpolation between p1 and p2
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+impl CameraPath {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            keys: Vec::new(),
+            trigger: None,
+        }
+    }
+
+    /// Total length in seconds
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map(|k| k.time).unwrap_or(0.0)
+    }
+
+    /// Insert a key, keeping keys sorted by time
+    pub fn add_key(&mut self, key: CameraKey) {
+        let idx = self.keys.partition_point(|k| k.time <= key.time);
+        self.keys.insert(idx, key);
+    }
+
+    /// Time for a key appended after the last one
+    pub fn next_key_time(&self) -> f32 {
+        if self.keys.is_empty() { 0.0 } else { self.duration() + NEW_KEY_GAP }
+    }
+
+    /// Retime a key, clamped between its neighbours so the keys stay sorted
+    pub fn set_key_time(&mut self, index: usize, time: f32) {
+        if index >= self.keys.len() {
+            return;
+        }
+        let min = if index == 0 { 0.0 } else { self.keys[index - 1].time };
+        let max = self.keys.get(index + 1).map_or(f32::MAX, |k| k.time);
+        self.keys[index].time = time.clamp(min, max);
+    }
+
+    /// Sample (position, target) at a time, clamped to the path's range
+    pub fn sample(&self, time: f32) -> Option<(Vec3, Vec3)> {
+        let first = self.keys.first()?;
+        if self.keys.len() == 1 || time <= first.time {
+            return Some((first.position, first.target));
+        }
+        let last = self.keys.last()?;
+        if time >= last.time {
+            return Some((last.position, last.target));
+        }
+
+        // Segment containing `time`
+        let i = self.keys.partition_point(|k| k.time <= time) - 1;
+        let k1 = &self.keys[i];
+        let k2 = &self.keys[i + 1];
+        let k0 = &self.keys[i.saturating_sub(1)];
+        let k3 = &self.keys[(i + 2).min(self.keys.len() - 1)];
+
+        let span = k2.time - k1.time;
+        let t = if span > 0.0 { (time - k1.time) / span } else { 0.0 };
+
+        Some((
+            catmull_rom(k0.position, k1.position, k2.position, k3.position, t),
+            catmull_rom(k0.target, k1.target, k2.target, k3.target, t),
+        ))
+    }
+}
+
+impl Level {
+    /// Find a camera path by its trigger name
+    pub fn find_camera_path_by_trigger(&self, trigger: &str) -> Option<usize> {
+        self.camera_paths
+            .iter()
+            .position(|p| p.trigger.as_deref() == Some(trigger))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(time: f32, x: f32) -> CameraKey {
+        CameraKey { time, position: Vec3::new(x, 0.0, 0.0), target: Vec3::new(x, 0.0, 100.0) }
+    }
+
+    #[test]
+    fn test_sample_passes_through_keys() {
+        let mut path = CameraPath::new("intro");
+        path.add_key(key(2.0, 100.0));
+        path.add_key(key(0.0, 0.0));
+        path.add_key(key(4.0, 300.0));
+
+        assert_eq!(path.duration(), 4.0);
+        let (pos, _) = path.sample(2.0).unwrap();
+        assert!((pos.x - 100.0).abs() < 0.001);
+        let (pos, _) = path.sample(10.0).unwrap();
+        assert!((pos.x - 300.0).abs() < 0.001);
+        let (pos, _) = path.sample(1.0).unwrap();
+        assert!(pos.x > 0.0 && pos.x < 100.0);
+    }
+
+    #[test]
+    fn test_set_key_time_stays_between_neighbours() {
+        let mut path = CameraPath::new("intro");
+        path.add_key(key(0.0, 0.0));
+        path.add_key(key(2.0, 100.0));
+        path.add_key(key(4.0, 300.0));
+
+        path.set_key_time(1, 3.5);
+        assert_eq!(path.keys[1].time, 3.5);
+        path.set_key_time(1, 9.0);
+        assert_eq!(path.keys[1].time, 4.0);
+        path.set_key_time(0, -1.0);
+        assert_eq!(path.keys[0].time, 0.0);
+        path.set_key_time(2, 6.0);
+        assert_eq!(path.duration(), 6.0);
+        assert_eq!(path.next_key_time(), 6.0 + NEW_KEY_GAP);
+    }
+}
@@ -0,0 +1,468 @@
+//! Level diff and three-way merge
+//!
+//! - `diff_levels` lists structural changes between two versions of a level:
+//!   rooms added or removed, room properties, sectors added, removed, reshaped
+//!   or retextured
+//! - `merge_levels` combines two edited copies of a common base, taking every
+//!   change only one side made; where both sides changed the same thing
+//!   differently it keeps ours and reports a conflict
+//!
+//! Rooms are matched by index. Sectors are matched by world grid cell, so a
+//! room whose grid grew or was trimmed only shows the sectors that changed.
+//! Cells are reported in the grid of the older (or base) room.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use crate::rasterizer::Vec3;
+use super::{Entity, Level, Portal, Room, RoomAudio, Sector, TextureRef, Waypoint, Weather};
+
+/// Where a change or conflict is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLocation {
+    /// Sector size or click height
+    Settings,
+    CameraPaths,
+    Events,
+    Room(usize),
+    /// A room property ("height", "ambient", "weather", "audio", "portals", "waypoints", "entities")
+    RoomProperty(usize, &'static str),
+    Sector { room: usize, cell: (i32, i32) },
+}
+
+impl fmt::Display for DiffLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffLocation::Settings => write!(f, "level settings"),
+            DiffLocation::CameraPaths => write!(f, "camera paths"),
+            DiffLocation::Events => write!(f, "events"),
+            DiffLocation::Room(room) => write!(f, "room {}", room),
+            DiffLocation::RoomProperty(room, name) => write!(f, "room {} {}", room, name),
+            DiffLocation::Sector { room, cell } => write!(f, "room {} sector ({}, {})", room, cell.0, cell.1),
+        }
+    }
+}
+
+/// What happened at a location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+    /// Only textures or UVs changed
+    Retextured,
+}
+
+/// One difference between two levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelChange {
+    pub location: DiffLocation,
+    pub kind: ChangeKind,
+}
+
+impl fmt::Display for LevelChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => "+",
+            ChangeKind::Removed => "-",
+            ChangeKind::Modified => "~",
+            ChangeKind::Retextured => "*",
+        };
+        let note = if self.kind == ChangeKind::Retextured { " (textures)" } else { "" };
+        write!(f, "{} {}{}", sign, self.location, note)
+    }
+}
+
+/// All differences between two levels, in room order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelDiff {
+    pub changes: Vec<LevelChange>,
+}
+
+impl LevelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn count(&self, f: impl Fn(&LevelChange) -> bool) -> usize {
+        self.changes.iter().filter(|c| f(c)).count()
+    }
+
+    /// One-line summary, e.g. "1 room(s) added, 4 sector(s) changed, 2 retextured"
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "No differences".to_string();
+        }
+        let is_room = |c: &LevelChange| matches!(c.location, DiffLocation::Room(_));
+        let is_sector = |c: &LevelChange| matches!(c.location, DiffLocation::Sector { .. });
+        let parts = [
+            (self.count(|c| is_room(c) && c.kind == ChangeKind::Added), "room(s) added"),
+            (self.count(|c| is_room(c) && c.kind == ChangeKind::Removed), "room(s) removed"),
+            (self.count(|c| is_sector(c) && c.kind != ChangeKind::Retextured), "sector(s) changed"),
+            (self.count(|c| c.kind == ChangeKind::Retextured), "retextured"),
+            (self.count(|c| !is_room(c) && !is_sector(c)), "other change(s)"),
+        ];
+        parts
+            .iter()
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, what)| format!("{} {}", n, what))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Result of a three-way merge
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    pub level: Level,
+    /// Places both sides changed differently (the merged level keeps ours)
+    pub conflicts: Vec<DiffLocation>,
+}
+
+/// A room taken apart: sectors keyed by grid cell relative to an anchor
+/// position, portals, waypoints and entities relative to the same anchor
+#[derive(Debug, Clone, PartialEq)]
+struct RoomParts {
+    y: f32,
+    ambient: f32,
+    weather: Weather,
+    audio: RoomAudio,
+    portals: Vec<Portal>,
+    waypoints: Vec<Waypoint>,
+    entities: Vec<Entity>,
+    sectors: BTreeMap<(i32, i32), Sector>,
+}
+
+impl RoomParts {
+    fn new(room: &Room, anchor: Vec3) -> Self {
+        let shift = Vec3::new(room.position.x - anchor.x, 0.0, room.position.z - anchor.z);
+        let (cx, cz) = ((shift.x / room.sector_size).round() as i32, (shift.z / room.sector_size).round() as i32);
+        Self {
+            y: room.position.y,
+            ambient: room.ambient,
+            weather: room.weather,
+            audio: room.audio.clone(),
+            portals: room.portals.iter().map(|p| Portal { vertices: p.vertices.map(|v| v + shift), ..p.clone() }).collect(),
+            waypoints: room.waypoints.iter().map(|w| Waypoint { position: w.position + shift, ..w.clone() }).collect(),
+            entities: room.entities.iter().map(|e| Entity { position: e.position + shift, ..e.clone() }).collect(),
+            sectors: room
+                .iter_sectors()
+                .filter(|(_, _, s)| s.has_geometry())
+                .map(|(x, z, s)| ((x as i32 + cx, z as i32 + cz), s.clone()))
+                .collect(),
+        }
+    }
+
+    fn into_room(self, id: usize, anchor: Vec3, sector_size: f32) -> Room {
+        let min_x = self.sectors.keys().map(|c| c.0).min().unwrap_or(0);
+        let max_x = self.sectors.keys().map(|c| c.0).max().unwrap_or(0);
+        let min_z = self.sectors.keys().map(|c| c.1).min().unwrap_or(0);
+        let max_z = self.sectors.keys().map(|c| c.1).max().unwrap_or(0);
+        let shift = Vec3::new(min_x as f32 * sector_size, 0.0, min_z as f32 * sector_size);
+        let position = Vec3::new(anchor.x + shift.x, self.y, anchor.z + shift.z);
+
+        let mut room = Room::new(id, position, (max_x - min_x + 1) as usize, (max_z - min_z + 1) as usize);
+        room.sector_size = sector_size;
+        room.ambient = self.ambient;
+        room.weather = self.weather;
+        room.audio = self.audio;
+        for ((x, z), sector) in self.sectors {
+            room.sectors[(x - min_x) as usize][(z - min_z) as usize] = Some(sector);
+        }
+        room.portals = self.portals.into_iter().map(|p| Portal { vertices: p.vertices.map(|v| v - shift), ..p }).collect();
+        room.waypoints = self.waypoints.into_iter().map(|w| Waypoint { position: w.position - shift, ..w }).collect();
+        room.entities = self.entities.into_iter().map(|e| Entity { position: e.position - shift, ..e }).collect();
+        room.recalculate_bounds();
+        room
+    }
+}
+
+/// Copy of a sector without textures or UVs, to tell reshaping from retexturing
+fn untextured(sector: &Sector) -> Sector {
+    let mut sector = sector.clone();
+    for face in [&mut sector.floor, &mut sector.ceiling].into_iter().flatten() {
+        face.texture = TextureRef::none();
+        face.uv = None;
+    }
+    for walls in [&mut sector.walls_north, &mut sector.walls_east, &mut sector.walls_south, &mut sector.walls_west] {
+        for wall in walls {
+            wall.texture = TextureRef::none();
+            wall.uv = None;
+        }
+    }
+    sector
+}
+
+/// List the changes from `old` to `new`
+pub fn diff_levels(old: &Level, new: &Level) -> LevelDiff {
+    let mut changes = Vec::new();
+    let mut push = |location, kind| changes.push(LevelChange { location, kind });
+
+    if (old.sector_size, old.click_height) != (new.sector_size, new.click_height) || old.ambience != new.ambience || old.respawn != new.respawn {
+        push(DiffLocation::Settings, ChangeKind::Modified);
+    }
+    if old.camera_paths != new.camera_paths {
+        push(DiffLocation::CameraPaths, ChangeKind::Modified);
+    }
+    if old.events != new.events {
+        push(DiffLocation::Events, ChangeKind::Modified);
+    }
+
+    for i in 0..old.rooms.len().max(new.rooms.len()) {
+        let (a, b) = match (old.rooms.get(i), new.rooms.get(i)) {
+            (Some(a), Some(b)) => (a, b),
+            (None, _) => {
+                push(DiffLocation::Room(i), ChangeKind::Added);
+                continue;
+            }
+            (_, None) => {
+                push(DiffLocation::Room(i), ChangeKind::Removed);
+                continue;
+            }
+        };
+        let (a, b) = (RoomParts::new(a, a.position), RoomParts::new(b, a.position));
+        let properties = [
+            ("height", a.y != b.y),
+            ("ambient", a.ambient != b.ambient),
+            ("weather", a.weather != b.weather),
+            ("audio", a.audio != b.audio),
+            ("portals", a.portals != b.portals),
+            ("waypoints", a.waypoints != b.waypoints),
+            ("entities", a.entities != b.entities),
+        ];
+        for (name, changed) in properties {
+            if changed {
+                push(DiffLocation::RoomProperty(i, name), ChangeKind::Modified);
+            }
+        }
+
+        let cells: BTreeSet<(i32, i32)> = a.sectors.keys().chain(b.sectors.keys()).copied().collect();
+        for cell in cells {
+            let location = DiffLocation::Sector { room: i, cell: (cell.0, cell.1) };
+            match (a.sectors.get(&cell), b.sectors.get(&cell)) {
+                (None, Some(_)) => push(location, ChangeKind::Added),
+                (Some(_), None) => push(location, ChangeKind::Removed),
+                (Some(x), Some(y)) if x != y => {
+                    let kind = if untextured(x) == untextured(y) { ChangeKind::Retextured } else { ChangeKind::Modified };
+                    push(location, kind);
+                }
+                _ => {}
+            }
+        }
+    }
+    LevelDiff { changes }
+}
+
+/// Three-way pick: whichever side changed wins, None if both changed differently
+fn merge3<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Option<T> {
+    if ours == theirs || theirs == base {
+        Some(ours.clone())
+    } else if ours == base {
+        Some(theirs.clone())
+    } else {
+        None
+    }
+}
+
+/// Like `merge3`, but keeps ours and records a conflict at `location`
+fn pick<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T, location: DiffLocation, conflicts: &mut Vec<DiffLocation>) -> T {
+    merge3(base, ours, theirs).unwrap_or_else(|| {
+        conflicts.push(location);
+        ours.clone()
+    })
+}
+
+/// Renumber room references in `level`: `remap` gives each old room index
+/// its new one, or None if the room is gone (references to it are dropped)
+fn remap_rooms(level: &mut Level, remap: impl Fn(usize) -> Option<usize>) {
+    for (i, room) in level.rooms.iter_mut().enumerate() {
+        room.id = i;
+        room.portals.retain_mut(|p| match remap(p.target_room) {
+            Some(t) => {
+                p.target_room = t;
+                true
+            }
+            None => false,
+        });
+        for wp in &mut room.waypoints {
+            wp.links.retain_mut(|l| match remap(l.room) {
+                Some(r) => {
+                    l.room = r;
+                    true
+                }
+                None => false,
+            });
+        }
+    }
+}
+
+/// Merge `ours` and `theirs`, two edited copies of `base`. Rooms both sides
+/// added are kept, theirs after ours.
+pub fn merge_levels(base: &Level, ours: &Level, theirs: &Level) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let mut level = ours.clone();
+
+    let (sector_size, click_height) = pick(
+        &(base.sector_size, base.click_height),
+        &(ours.sector_size, ours.click_height),
+        &(theirs.sector_size, theirs.click_height),
+        DiffLocation::Settings,
+        &mut conflicts,
+    );
+    level.sector_size = sector_size;
+    level.click_height = click_height;
+    level.ambience = pick(&base.ambience, &ours.ambience, &theirs.ambience, DiffLocation::Settings, &mut conflicts);
+    level.respawn = pick(&base.respawn, &ours.respawn, &theirs.respawn, DiffLocation::Settings, &mut conflicts);
+    level.camera_paths = pick(&base.camera_paths, &ours.camera_paths, &theirs.camera_paths, DiffLocation::CameraPaths, &mut conflicts);
+    level.events = pick(&base.events, &ours.events, &theirs.events, DiffLocation::Events, &mut conflicts);
+
+    // Theirs' new rooms go after ours' new rooms
+    let base_len = base.rooms.len();
+    let ours_added = ours.rooms.len().saturating_sub(base_len);
+    let mut theirs = theirs.clone();
+    remap_rooms(&mut theirs, |r| Some(if r >= base_len { r + ours_added } else { r }));
+
+    let mut rooms: Vec<Option<Room>> = Vec::new();
+    for (i, base_room) in base.rooms.iter().enumerate() {
+        let anchor = base_room.position;
+        let b = RoomParts::new(base_room, anchor);
+        let o = ours.rooms.get(i).map(|r| RoomParts::new(r, anchor));
+        let t = theirs.rooms.get(i).map(|r| RoomParts::new(r, anchor));
+        let merged = match (o, t) {
+            (Some(o), Some(t)) => {
+                let property = |name| DiffLocation::RoomProperty(i, name);
+                let mut parts = RoomParts {
+                    y: pick(&b.y, &o.y, &t.y, property("height"), &mut conflicts),
+                    ambient: pick(&b.ambient, &o.ambient, &t.ambient, property("ambient"), &mut conflicts),
+                    weather: pick(&b.weather, &o.weather, &t.weather, property("weather"), &mut conflicts),
+                    audio: pick(&b.audio, &o.audio, &t.audio, property("audio"), &mut conflicts),
+                    portals: pick(&b.portals, &o.portals, &t.portals, property("portals"), &mut conflicts),
+                    waypoints: pick(&b.waypoints, &o.waypoints, &t.waypoints, property("waypoints"), &mut conflicts),
+                    entities: pick(&b.entities, &o.entities, &t.entities, property("entities"), &mut conflicts),
+                    sectors: BTreeMap::new(),
+                };
+                let cells: BTreeSet<(i32, i32)> = b.sectors.keys().chain(o.sectors.keys()).chain(t.sectors.keys()).copied().collect();
+                for cell in cells {
+                    let [bs, os, ts] = [&b, &o, &t].map(|p| p.sectors.get(&cell).cloned());
+                    let location = DiffLocation::Sector { room: i, cell };
+                    if let Some(sector) = pick(&bs, &os, &ts, location, &mut conflicts) {
+                        parts.sectors.insert(cell, sector);
+                    }
+                }
+                Some(parts)
+            }
+            // Removed on at least one side
+            (o, t) => pick(&Some(b), &o, &t, DiffLocation::Room(i), &mut conflicts),
+        };
+        rooms.push(merged.map(|parts| parts.into_room(i, anchor, sector_size)));
+    }
+    rooms.extend(ours.rooms.iter().skip(base_len).cloned().map(Some));
+    rooms.extend(theirs.rooms.iter().skip(base_len).cloned().map(Some));
+
+    // Drop removed rooms and renumber the rest
+    let mut new_index = Vec::with_capacity(rooms.len());
+    let mut next = 0;
+    for room in &rooms {
+        new_index.push(room.as_ref().map(|_| next));
+        next += room.is_some() as usize;
+    }
+    level.rooms = rooms.into_iter().flatten().collect();
+    remap_rooms(&mut level, |r| new_index.get(r).copied().flatten());
+    level.apply_metrics();
+
+    MergeResult { level, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{LevelBuilder, RoomBuilder};
+
+    fn base_level() -> Level {
+        LevelBuilder::new().room(RoomBuilder::new(2, 2).fill(0.0, 1024.0)).build()
+    }
+
+    #[test]
+    fn test_diff_tells_reshape_from_retexture() {
+        let old = base_level();
+        let mut new = old.clone();
+        new.rooms[0].set_floor(0, 0, 256.0, TextureRef::none());
+        new.rooms[0].get_sector_mut(1, 1).unwrap().ceiling.as_mut().unwrap().texture = TextureRef::new("pack", "stone");
+        // Growing west shifts the grid but not the sectors
+        new.rooms[0].grow_to_include(-1, 0).unwrap();
+        new.rooms[0].set_floor(0, 0, 0.0, TextureRef::none());
+
+        let diff = diff_levels(&old, &new);
+        let sector = |x, z, kind| LevelChange { location: DiffLocation::Sector { room: 0, cell: (x, z) }, kind };
+        assert_eq!(diff.changes, vec![
+            sector(-1, 0, ChangeKind::Added),
+            sector(0, 0, ChangeKind::Modified),
+            sector(1, 1, ChangeKind::Retextured),
+        ]);
+        assert!(diff_levels(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_merge_combines_separate_edits() {
+        let base = base_level();
+        let mut ours = base.clone();
+        ours.rooms[0].set_floor(0, 0, 256.0, TextureRef::none());
+        let mut theirs = base.clone();
+        theirs.rooms[0].get_sector_mut(1, 1).unwrap().floor = None;
+        theirs.rooms[0].ambient = 0.8;
+
+        let merged = merge_levels(&base, &ours, &theirs);
+        assert!(merged.conflicts.is_empty());
+        let room = &merged.level.rooms[0];
+        assert_eq!(room.get_sector(0, 0).unwrap().floor.as_ref().unwrap().heights, [256.0; 4]);
+        assert!(room.get_sector(1, 1).unwrap().floor.is_none());
+        assert_eq!(room.ambient, 0.8);
+    }
+
+    #[test]
+    fn test_merge_conflict_keeps_ours() {
+        let base = base_level();
+        let mut ours = base.clone();
+        ours.rooms[0].set_floor(0, 0, 256.0, TextureRef::none());
+        let mut theirs = base.clone();
+        theirs.rooms[0].set_floor(0, 0, -256.0, TextureRef::none());
+
+        let merged = merge_levels(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, vec![DiffLocation::Sector { room: 0, cell: (0, 0) }]);
+        assert_eq!(merged.level.rooms[0].get_sector(0, 0).unwrap().floor.as_ref().unwrap().heights, [256.0; 4]);
+    }
+
+    #[test]
+    fn test_merge_keeps_rooms_added_on_both_sides() {
+        let base = base_level();
+        let mut ours = base.clone();
+        ours.add_room(RoomBuilder::new(1, 1).at(Vec3::new(4096.0, 0.0, 0.0)).fill(0.0, 1024.0).build());
+        let mut theirs = base.clone();
+        let added = theirs.add_room(RoomBuilder::new(1, 1).at(Vec3::new(-4096.0, 0.0, 0.0)).fill(0.0, 1024.0).build());
+        theirs.rooms[0].portals.push(Portal::new(added, [Vec3::ZERO; 4], Vec3::new(1.0, 0.0, 0.0)));
+
+        let merged = merge_levels(&base, &ours, &theirs);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.level.rooms.len(), 3);
+        assert_eq!(merged.level.rooms[2].position.x, -4096.0);
+        assert_eq!(merged.level.rooms[0].portals[0].target_room, 2);
+    }
+
+    #[test]
+    fn test_merge_removes_room() {
+        let mut base = base_level();
+        base.add_room(RoomBuilder::new(1, 1).at(Vec3::new(4096.0, 0.0, 0.0)).fill(0.0, 1024.0).build());
+        base.rooms[0].portals.push(Portal::new(1, [Vec3::ZERO; 4], Vec3::new(1.0, 0.0, 0.0)));
+        let mut ours = base.clone();
+        ours.rooms[0].ambient = 0.2;
+        let mut theirs = base.clone();
+        theirs.rooms.pop();
+        theirs.rooms[0].portals.clear();
+
+        let merged = merge_levels(&base, &ours, &theirs);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.level.rooms.len(), 1);
+        assert_eq!(merged.level.rooms[0].ambient, 0.2);
+        assert!(merged.level.rooms[0].portals.is_empty());
+        assert_eq!(diff_levels(&base, &merged.level).summary(), "1 room(s) removed, 2 other change(s)");
+    }
+}
@@ -0,0 +1,132 @@
+//! Editor macros
+//!
+//! Sector edits recorded once and stamped again elsewhere, for repetitive
+//! structures (colonnades, stair towers, window rows):
+//! - recording compares a room before and after a run of edits (placing
+//!   floors, texturing, raising edges...) and keeps every sector that
+//!   changed, relative to an anchor cell
+//! - replaying writes those sectors at a new anchor, optionally repeated
+//!   with a grid step and a height rise per repeat
+//! - saved with the level, so macros can be reused across sessions
+
+use serde::{Deserialize, Serialize};
+use super::{Room, Sector};
+
+/// One cell written by a macro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroCell {
+    /// Offset from the anchor cell
+    pub dx: i32,
+    pub dz: i32,
+    /// Sector to write, None = remove the sector
+    pub sector: Option<Sector>,
+}
+
+/// A named, recorded set of sector edits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditMacro {
+    pub name: String,
+    pub cells: Vec<MacroCell>,
+}
+
+/// How a macro is stamped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacroReplay {
+    /// Times the macro is applied
+    pub repeats: usize,
+    /// Grid offset between repeats
+    pub step: (i32, i32),
+    /// Height added per repeat
+    pub rise: f32,
+}
+
+impl Default for MacroReplay {
+    fn default() -> Self {
+        Self { repeats: 1, step: (1, 0), rise: 0.0 }
+    }
+}
+
+impl EditMacro {
+    /// Record the sectors that differ between `before` and `after`, relative
+    /// to `anchor` (a cell of `before`). `after` may have grown its grid.
+    pub fn record(name: &str, before: &Room, after: &Room, anchor: (usize, usize)) -> Self {
+        // Cells of `after` sit this far from the same cells of `before` when it grew west/north
+        let size = after.sector_size;
+        let shift_x = ((before.position.x - after.position.x) / size).round() as i32;
+        let shift_z = ((before.position.z - after.position.z) / size).round() as i32;
+        let (ax, az) = (anchor.0 as i32 + shift_x, anchor.1 as i32 + shift_z);
+
+        let mut cells = Vec::new();
+        for x in 0..after.width as i32 {
+            for z in 0..after.depth as i32 {
+                let (bx, bz) = (x - shift_x, z - shift_z);
+                let old = (bx >= 0 && bz >= 0).then(|| before.get_sector(bx as usize, bz as usize)).flatten();
+                let new = after.get_sector(x as usize, z as usize);
+                if old != new {
+                    cells.push(MacroCell { dx: x - ax, dz: z - az, sector: new.cloned() });
+                }
+            }
+        }
+        Self { name: name.to_string(), cells }
+    }
+}
+
+impl Room {
+    /// Stamp a macro at `anchor`, growing the grid as needed. Returns how
+    /// many cells were written.
+    pub fn play_macro(&mut self, edit: &EditMacro, anchor: (usize, usize), replay: &MacroReplay) -> Result<usize, String> {
+        let (mut ax, mut az) = (anchor.0 as i32, anchor.1 as i32);
+        let mut written = 0;
+        for i in 0..replay.repeats as i32 {
+            for cell in &edit.cells {
+                let x = ax + cell.dx + replay.step.0 * i;
+                let z = az + cell.dz + replay.step.1 * i;
+                let (shift_x, shift_z) = self.grow_to_include(x, z)?;
+                ax += shift_x as i32;
+                az += shift_z as i32;
+                let (x, z) = ((x + shift_x as i32) as usize, (z + shift_z as i32) as usize);
+                match &cell.sector {
+                    Some(sector) => {
+                        let mut sector = sector.clone();
+                        sector.offset_heights(replay.rise * i as f32);
+                        self.set_sector(x, z, sector);
+                    }
+                    None => self.remove_sector(x, z),
+                }
+                written += 1;
+            }
+        }
+        self.recalculate_bounds();
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::TextureRef;
+
+    #[test]
+    fn test_macro_replays_as_a_staircase() {
+        let before = Room::new(0, Vec3::ZERO, 2, 2);
+        let mut after = before.clone();
+        after.set_floor(1, 1, 256.0, TextureRef::none());
+        // Growing west while recording keeps offsets relative to the anchor
+        after.grow_to_include(-1, 0).unwrap();
+        after.set_floor(0, 0, 0.0, TextureRef::none());
+
+        let edit = EditMacro::record("step", &before, &after, (0, 0));
+        assert_eq!(edit.cells.len(), 2);
+        assert!(edit.cells.iter().any(|c| (c.dx, c.dz) == (1, 1)));
+        assert!(edit.cells.iter().any(|c| (c.dx, c.dz) == (-1, 0)));
+
+        let mut room = Room::new(0, Vec3::ZERO, 1, 1);
+        let replay = MacroReplay { repeats: 3, step: (0, 2), rise: 256.0 };
+        assert_eq!(room.play_macro(&edit, (0, 0), &replay).unwrap(), 6);
+        // The anchor moved one cell east when the room grew west
+        let floor = |x, z| room.get_sector(x, z).and_then(|s| s.floor.as_ref()).map(|f| f.heights[0]);
+        assert_eq!((floor(2, 1), floor(2, 3), floor(2, 5)), (Some(256.0), Some(512.0), Some(768.0)));
+        assert_eq!(floor(0, 4), Some(512.0));
+    }
+}
@@ -0,0 +1,338 @@
+//! Entities - placed objects with data-driven properties
+//!
+//! Entity fields are not hardcoded: each archetype ("pickup", "enemy",
+//! "door"...) declares typed properties in the project's
+//! `assets/project/entities.ron`, the editor's Properties panel renders them
+//! generically, and scripts read them back by key. Modders add custom data by
+//! editing the schema, without touching Rust.
+//!
+//! ```ron
+//! (
+//!     archetypes: [
+//!         (
+//!             name: "pickup",
+//!             color: (240, 200, 60),
+//!             properties: [
+//!                 (key: "item", kind: Choice(["medipack", "ammo"]), default: Text("medipack")),
+//!                 (key: "amount", kind: Int, default: Int(1), range: Some((1.0, 99.0))),
+//!                 (key: "respawn", kind: Bool, default: Bool(false)),
+//!             ],
+//!         ),
+//!     ],
+//! )
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::Vec3;
+use super::Level;
+
+/// Default location of the project's entity schema
+pub const ENTITY_SCHEMA_PATH: &str = "assets/project/entities.ron";
+
+/// Type of a property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyKind {
+    Bool,
+    Int,
+    Float,
+    /// Free text (set in the level file or by scripts)
+    Text,
+    /// One of a fixed list of strings
+    Choice(Vec<String>),
+}
+
+/// Value of a property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Text(String),
+}
+
+impl PropertyValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            PropertyValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Float value (ints widen)
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            PropertyValue::Float(f) => Some(*f),
+            PropertyValue::Int(i) => Some(*i as f32),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Whether this value can be stored in a property of `kind`
+    pub fn fits(&self, kind: &PropertyKind) -> bool {
+        match (self, kind) {
+            (PropertyValue::Bool(_), PropertyKind::Bool)
+            | (PropertyValue::Int(_), PropertyKind::Int)
+            | (PropertyValue::Float(_), PropertyKind::Float)
+            | (PropertyValue::Text(_), PropertyKind::Text) => true,
+            (PropertyValue::Text(s), PropertyKind::Choice(options)) => options.contains(s),
+            _ => false,
+        }
+    }
+
+    /// Short text for the Properties panel
+    pub fn label(&self) -> String {
+        match self {
+            PropertyValue::Bool(b) => if *b { "On".to_string() } else { "Off".to_string() },
+            PropertyValue::Int(i) => i.to_string(),
+            PropertyValue::Float(f) => format!("{:.2}", f),
+            PropertyValue::Text(s) => s.clone(),
+        }
+    }
+}
+
+/// A property declared by an archetype
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyDef {
+    pub key: String,
+    pub kind: PropertyKind,
+    pub default: PropertyValue,
+    /// Clamp range for numbers
+    #[serde(default)]
+    pub range: Option<(f32, f32)>,
+    /// Increment of the -/+ buttons for numbers
+    #[serde(default = "default_step")]
+    pub step: f32,
+}
+
+fn default_step() -> f32 {
+    1.0
+}
+
+impl PropertyDef {
+    /// Step a number or cycle a choice/bool by `steps` (-1 or +1 from the panel)
+    pub fn step_value(&self, value: &PropertyValue, steps: i32) -> PropertyValue {
+        let clamp = |v: f32| match self.range {
+            Some((min, max)) => v.clamp(min, max),
+            None => v,
+        };
+        match (value, &self.kind) {
+            (PropertyValue::Bool(b), _) => PropertyValue::Bool(!b),
+            (PropertyValue::Int(i), _) => {
+                let step = self.step.round().max(1.0) as i32;
+                PropertyValue::Int(clamp(i.saturating_add(step * steps) as f32) as i32)
+            }
+            (PropertyValue::Float(f), _) => PropertyValue::Float(clamp(f + self.step * steps as f32)),
+            (PropertyValue::Text(s), PropertyKind::Choice(options)) if !options.is_empty() => {
+                let current = options.iter().position(|o| o == s).unwrap_or(0) as i32;
+                let next = (current + steps).rem_euclid(options.len() as i32) as usize;
+                PropertyValue::Text(options[next].clone())
+            }
+            (value, _) => value.clone(),
+        }
+    }
+}
+
+/// Kind of entity, with the properties its instances carry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Archetype {
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<PropertyDef>,
+    /// Marker color in the editor
+    #[serde(default = "default_color")]
+    pub color: (u8, u8, u8),
+}
+
+fn default_color() -> (u8, u8, u8) {
+    (200, 200, 200)
+}
+
+/// Per-project set of archetypes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntitySchema {
+    pub archetypes: Vec<Archetype>,
+}
+
+impl EntitySchema {
+    /// Load a schema from a RON file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read entity schema: {}", e))?;
+        ron::from_str(&contents).map_err(|e| format!("Failed to parse entity schema: {}", e))
+    }
+
+    /// The project's schema, or an empty one when it has none
+    pub fn load_project() -> Self {
+        Self::load(Path::new(ENTITY_SCHEMA_PATH)).unwrap_or_default()
+    }
+
+    pub fn archetype(&self, name: &str) -> Option<&Archetype> {
+        self.archetypes.iter().find(|a| a.name == name)
+    }
+
+    /// A new entity of an archetype, with every property at its default
+    pub fn instantiate(&self, archetype: &str, position: Vec3) -> Entity {
+        let properties = self
+            .archetype(archetype)
+            .map(|a| a.properties.iter().map(|p| (p.key.clone(), p.default.clone())).collect())
+            .unwrap_or_default();
+        Entity {
+            archetype: archetype.to_string(),
+            position,
+            properties,
+        }
+    }
+}
+
+/// Reference to an entity in a specific room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityRef {
+    pub room: usize,
+    pub index: usize,
+}
+
+impl EntityRef {
+    pub fn new(room: usize, index: usize) -> Self {
+        Self { room, index }
+    }
+}
+
+/// Object placed in a room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    /// Archetype name in the project's schema
+    pub archetype: String,
+    /// Room-relative position
+    pub position: Vec3,
+    /// Property values by key (keys the schema doesn't know are kept)
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyValue>,
+}
+
+impl Entity {
+    /// Raw stored value of a property
+    pub fn get(&self, key: &str) -> Option<&PropertyValue> {
+        self.properties.get(key)
+    }
+
+    /// Value of a declared property: the stored one if it still fits the
+    /// schema, otherwise the default
+    pub fn value(&self, def: &PropertyDef) -> PropertyValue {
+        match self.properties.get(&def.key) {
+            Some(v) if v.fits(&def.kind) => v.clone(),
+            _ => def.default.clone(),
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: PropertyValue) {
+        self.properties.insert(key.to_string(), value);
+    }
+}
+
+impl Level {
+    /// Get an entity by reference
+    pub fn entity(&self, r: EntityRef) -> Option<&Entity> {
+        self.rooms.get(r.room)?.entities.get(r.index)
+    }
+
+    /// Get an entity mutably
+    pub fn entity_mut(&mut self, r: EntityRef) -> Option<&mut Entity> {
+        self.rooms.get_mut(r.room)?.entities.get_mut(r.index)
+    }
+
+    /// World-space position of an entity
+    pub fn entity_world_pos(&self, r: EntityRef) -> Option<Vec3> {
+        let room = self.rooms.get(r.room)?;
+        room.entities.get(r.index).map(|e| room.position + e.position)
+    }
+
+    /// Add an entity to a room
+    pub fn add_entity(&mut self, room: usize, entity: Entity) -> Option<EntityRef> {
+        let room_ref = self.rooms.get_mut(room)?;
+        room_ref.entities.push(entity);
+        Some(EntityRef::new(room, room_ref.entities.len() - 1))
+    }
+
+    /// Remove an entity
+    pub fn remove_entity(&mut self, r: EntityRef) {
+        if self.entity(r).is_some() {
+            self.rooms[r.room].entities.remove(r.index);
+        }
+    }
+
+    /// All entities of an archetype, with their references
+    pub fn entities_of<'a>(&'a self, archetype: &'a str) -> impl Iterator<Item = (EntityRef, &'a Entity)> + 'a {
+        self.rooms.iter().enumerate().flat_map(move |(room_idx, room)| {
+            room.entities
+                .iter()
+                .enumerate()
+                .filter(move |(_, e)| e.archetype == archetype)
+                .map(move |(i, e)| (EntityRef::new(room_idx, i), e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"(
+        archetypes: [
+            (
+                name: "pickup",
+                properties: [
+                    (key: "item", kind: Choice(["medipack", "ammo"]), default: Text("medipack")),
+                    (key: "amount", kind: Int, default: Int(1), range: Some((1.0, 3.0))),
+                ],
+            ),
+        ],
+    )"#;
+
+    #[test]
+    fn test_schema_drives_entity_values() {
+        let schema: EntitySchema = ron::from_str(SCHEMA).unwrap();
+        let mut entity = schema.instantiate("pickup", Vec3::ZERO);
+        let pickup = schema.archetype("pickup").unwrap();
+        let (item, amount) = (&pickup.properties[0], &pickup.properties[1]);
+        assert_eq!(entity.get("amount"), Some(&PropertyValue::Int(1)));
+
+        // Numbers clamp to their range, choices wrap around
+        let mut value = entity.value(amount);
+        for _ in 0..5 {
+            value = amount.step_value(&value, 1);
+        }
+        assert_eq!(value, PropertyValue::Int(3));
+        assert_eq!(item.step_value(&entity.value(item), -1), PropertyValue::Text("ammo".to_string()));
+
+        // Values that no longer fit the schema fall back to the default
+        entity.set("item", PropertyValue::Text("rocket".to_string()));
+        assert_eq!(entity.value(item), PropertyValue::Text("medipack".to_string()));
+    }
+
+    #[test]
+    fn test_entities_live_in_rooms() {
+        let mut level = super::super::create_test_level();
+        let schema: EntitySchema = ron::from_str(SCHEMA).unwrap();
+        let r = level.add_entity(0, schema.instantiate("pickup", Vec3::new(512.0, 0.0, 512.0))).unwrap();
+        assert_eq!(level.entities_of("pickup").count(), 1);
+        assert!(level.entity_world_pos(r).is_some());
+        level.remove_entity(r);
+        assert!(level.entity(r).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Level events - a scripted timeline per level
+//!
+//! Each event runs one action a set time after it starts:
+//! - it starts when the level starts, or when a trigger fires its channel
+//! - actions spawn a wave (entities with a matching `wave` property stay
+//!   dormant until then), open the doors on a channel, or start a music cue
+//! - play mode runs them with `game::EventScheduler`; the editor lists them
+//!   in the Events section of the Room panel
+//!
+//! ```ron
+//! events: [
+//!     (channel: None, delay: 5.0, action: SpawnWave(1)),
+//!     (channel: Some(2), delay: 1.5, action: OpenGate(3)),
+//! ],
+//! ```
+
+use serde::{Deserialize, Serialize};
+use super::Entity;
+
+/// Entity property holding the wave an entity belongs to (0 or unset = always present)
+pub const WAVE_PROPERTY: &str = "wave";
+
+/// What an event does when it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventAction {
+    /// Bring in the entities of a wave
+    SpawnWave(i32),
+    /// Open every door on a channel (doors already open stay open)
+    OpenGate(i32),
+    /// Jump the music to a cue point of the song, by number (from 0)
+    MusicCue(i32),
+}
+
+impl EventAction {
+    pub fn label(&self) -> String {
+        match self {
+            EventAction::SpawnWave(wave) => format!("Spawn wave {}", wave),
+            EventAction::OpenGate(channel) => format!("Open gate ch {}", channel),
+            EventAction::MusicCue(cue) => format!("Music cue {}", cue),
+        }
+    }
+
+    /// The wave, channel or cue number
+    pub fn value(&self) -> i32 {
+        match *self {
+            EventAction::SpawnWave(v) | EventAction::OpenGate(v) | EventAction::MusicCue(v) => v,
+        }
+    }
+
+    pub fn with_value(&self, value: i32) -> Self {
+        match self {
+            EventAction::SpawnWave(_) => EventAction::SpawnWave(value),
+            EventAction::OpenGate(_) => EventAction::OpenGate(value),
+            EventAction::MusicCue(_) => EventAction::MusicCue(value),
+        }
+    }
+
+    /// The next (or previous) kind of action, keeping the number
+    pub fn cycled(&self, up: bool) -> Self {
+        let kinds = [EventAction::SpawnWave(0), EventAction::OpenGate(0), EventAction::MusicCue(0)];
+        let i = kinds.iter().position(|k| std::mem::discriminant(k) == std::mem::discriminant(self)).unwrap_or(0);
+        let next = if up { (i + 1) % kinds.len() } else { (i + kinds.len() - 1) % kinds.len() };
+        kinds[next].with_value(self.value())
+    }
+}
+
+/// One scripted event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelEvent {
+    /// Trigger channel that starts the event (None = the level starting)
+    #[serde(default)]
+    pub channel: Option<i32>,
+    /// Seconds from the start to the action
+    #[serde(default)]
+    pub delay: f32,
+    pub action: EventAction,
+}
+
+impl LevelEvent {
+    pub fn new() -> Self {
+        Self { channel: None, delay: 0.0, action: EventAction::SpawnWave(1) }
+    }
+
+    /// "start" or "ch 2", followed by the delay
+    pub fn when_label(&self) -> String {
+        match self.channel {
+            Some(channel) => format!("ch {} +{:.1}s", channel, self.delay),
+            None => format!("start +{:.1}s", self.delay),
+        }
+    }
+}
+
+impl Default for LevelEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The wave an entity waits for, if it isn't present from the start
+pub fn entity_wave(entity: &Entity) -> Option<i32> {
+    entity.get(WAVE_PROPERTY).and_then(|v| v.as_int()).filter(|&w| w > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycling_keeps_the_number() {
+        let action = EventAction::SpawnWave(3);
+        assert_eq!(action.cycled(true), EventAction::OpenGate(3));
+        assert_eq!(action.cycled(false), EventAction::MusicCue(3));
+        assert_eq!(action.cycled(true).cycled(false), action);
+    }
+}
@@ -0,0 +1,229 @@
+//! Face extrusion
+//!
+//! - Floors and ceilings move up or down, and side walls are added on the
+//!   outline of the extruded block (a floor pushed down makes a pit)
+//! - A wall is pushed out by one sector, adding the sector behind it with
+//!   floor, ceiling and side walls
+//!
+//! Side walls always face the open space: into the extruded sectors for pits
+//! and ceiling recesses, into the neighbors for platforms and hanging blocks.
+//! Corner indexing follows `room_ops` (a wall on edge `d` runs from corner
+//! `d` to corner `d + 1`).
+
+use super::{Direction, HorizontalFace, Room, TextureRef, VerticalFace};
+
+/// Edges in corner order: edge `i` runs from corner `i` to corner `i + 1`
+const EDGES: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+fn neighbor(x: usize, z: usize, dir: Direction) -> (i32, i32) {
+    let (dx, dz) = dir.offset();
+    (x as i32 + dx, z as i32 + dz)
+}
+
+impl Room {
+    /// Move the floors (or ceilings) of `cells` by `dy` and wall in the
+    /// outline of the block. The grid grows if walls need cells outside it.
+    /// Returns the cells' coordinates afterwards.
+    pub fn extrude_horizontal(
+        &mut self,
+        cells: &[(usize, usize)],
+        ceiling: bool,
+        dy: f32,
+        texture: TextureRef,
+    ) -> Result<Vec<(usize, usize)>, String> {
+        let name = if ceiling { "ceiling" } else { "floor" };
+        if cells.is_empty() {
+            return Err("No sectors selected".to_string());
+        }
+        if dy == 0.0 {
+            return Err("Nothing to extrude".to_string());
+        }
+        for &(x, z) in cells {
+            let sector = self.get_sector(x, z).ok_or_else(|| format!("No sector at ({}, {})", x, z))?;
+            let face = if ceiling { &sector.ceiling } else { &sector.floor };
+            if face.is_none() {
+                return Err(format!("Sector ({}, {}) has no {}", x, z, name));
+            }
+        }
+
+        // Boundary edges of the block
+        let edges: Vec<((usize, usize), usize)> = cells
+            .iter()
+            .flat_map(|&(x, z)| (0..4).map(move |i| ((x, z), i)))
+            .filter(|&((x, z), i)| {
+                let (nx, nz) = neighbor(x, z, EDGES[i]);
+                nx < 0 || nz < 0 || !cells.contains(&(nx as usize, nz as usize))
+            })
+            .collect();
+
+        // Walls face into the block when it sinks away from the room, otherwise into the neighbors
+        let walls_inside = (dy < 0.0) != ceiling;
+        let (mut sx, mut sz) = (0, 0);
+        if !walls_inside {
+            let outside: Vec<(i32, i32)> = edges.iter().map(|&((x, z), i)| neighbor(x, z, EDGES[i])).collect();
+            let min = (outside.iter().map(|c| c.0).min().unwrap_or(0), outside.iter().map(|c| c.1).min().unwrap_or(0));
+            let max = (outside.iter().map(|c| c.0).max().unwrap_or(0), outside.iter().map(|c| c.1).max().unwrap_or(0));
+            (sx, sz) = self.grow_to_include(min.0, min.1)?;
+            self.grow_to_include(max.0 + sx as i32, max.1 + sz as i32)?;
+        }
+        let shift = |(x, z): (usize, usize)| (x + sx, z + sz);
+
+        // Old corner heights, before anything moves
+        let old: Vec<[f32; 4]> = cells
+            .iter()
+            .map(|&c| {
+                let (x, z) = shift(c);
+                let sector = self.get_sector(x, z).unwrap();
+                let face = if ceiling { &sector.ceiling } else { &sector.floor };
+                face.as_ref().unwrap().heights
+            })
+            .collect();
+
+        for &c in cells {
+            let (x, z) = shift(c);
+            let sector = self.get_sector_mut(x, z).unwrap();
+            let face = if ceiling { &mut sector.ceiling } else { &mut sector.floor };
+            if let Some(face) = face {
+                face.heights.iter_mut().for_each(|h| *h += dy);
+            }
+        }
+
+        for ((cell, i), heights) in edges.iter().map(|&(c, i)| {
+            let k = cells.iter().position(|&other| other == c).unwrap();
+            ((shift(c), i), old[k])
+        }) {
+            let (l, r) = (heights[i], heights[(i + 1) % 4]);
+            let (lo_l, hi_l) = (l.min(l + dy), l.max(l + dy));
+            let (lo_r, hi_r) = (r.min(r + dy), r.max(r + dy));
+            let dir = EDGES[i];
+            let mut wall = VerticalFace::new(0.0, 0.0, texture.clone());
+            if walls_inside {
+                wall.heights = [lo_l, lo_r, hi_r, hi_l];
+                self.ensure_sector(cell.0, cell.1).walls_mut(dir).push(wall);
+            } else {
+                // Seen from the neighbor, left and right swap
+                wall.heights = [lo_r, lo_l, hi_l, hi_r];
+                let (nx, nz) = neighbor(cell.0, cell.1, dir);
+                self.ensure_sector(nx as usize, nz as usize).walls_mut(dir.opposite()).push(wall);
+            }
+        }
+
+        self.recalculate_bounds();
+        Ok(cells.iter().map(|&c| shift(c)).collect())
+    }
+
+    /// Push wall `index` on edge `dir` of sector (x, z) out by one sector.
+    /// The sector behind it gets the wall on its far edge, side walls, and a
+    /// floor and ceiling where (x, z) has them. The grid grows if needed.
+    /// Returns the new sector's coordinates.
+    pub fn extrude_wall(&mut self, (x, z): (usize, usize), dir: Direction, index: usize) -> Result<(usize, usize), String> {
+        let Some(sector) = self.get_sector(x, z) else {
+            return Err(format!("No sector at ({}, {})", x, z));
+        };
+        let Some(wall) = sector.walls(dir).get(index).cloned() else {
+            return Err("No wall to extrude".to_string());
+        };
+        let floor_texture = sector.floor.as_ref().map(|f| f.texture.clone());
+        let ceiling_texture = sector.ceiling.as_ref().map(|c| c.texture.clone());
+
+        let (nx, nz) = neighbor(x, z, dir);
+        let behind = (nx >= 0 && nz >= 0).then(|| self.get_sector(nx as usize, nz as usize)).flatten();
+        if behind.is_some_and(|s| s.floor.is_some() || s.ceiling.is_some()) {
+            return Err("The sector behind the wall isn't empty".to_string());
+        }
+
+        let (sx, sz) = self.grow_to_include(nx, nz)?;
+        let (x, z) = (x + sx, z + sz);
+        let (nx, nz) = ((nx + sx as i32) as usize, (nz + sz as i32) as usize);
+        self.get_sector_mut(x, z).unwrap().walls_mut(dir).remove(index);
+
+        // The shared edge's left corner `d` is corner `d + 3` of the new sector
+        // and extends straight back to its corner `d`; likewise `d + 1` and `d + 2`
+        let d = EDGES.iter().position(|&e| e == dir).unwrap();
+        let [bl, br, tr, tl] = wall.heights;
+        let corners = |left: f32, right: f32| {
+            let mut h = [0.0; 4];
+            h[d] = left;
+            h[(d + 3) % 4] = left;
+            h[(d + 1) % 4] = right;
+            h[(d + 2) % 4] = right;
+            h
+        };
+        let side = |bottom: f32, top: f32| VerticalFace { heights: [bottom, bottom, top, top], ..wall.clone() };
+
+        let sector = self.ensure_sector(nx, nz);
+        if let Some(texture) = floor_texture {
+            sector.floor = Some(HorizontalFace::sloped(corners(bl, br), texture));
+        }
+        if let Some(texture) = ceiling_texture {
+            sector.ceiling = Some(HorizontalFace::sloped(corners(tl, tr), texture));
+        }
+        sector.walls_mut(EDGES[(d + 1) % 4]).push(side(br, tr));
+        sector.walls_mut(EDGES[(d + 3) % 4]).push(side(bl, tl));
+        sector.walls_mut(dir).push(wall);
+
+        self.recalculate_bounds();
+        Ok((nx, nz))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::RoomBuilder;
+
+    #[test]
+    fn test_floor_pit_walls_face_inside() {
+        let mut room = RoomBuilder::new(3, 3).fill(0.0, 1024.0).build();
+        let cells = room.extrude_horizontal(&[(1, 1)], false, -512.0, TextureRef::none()).unwrap();
+        assert_eq!(cells, vec![(1, 1)]);
+
+        let pit = room.get_sector(1, 1).unwrap();
+        assert_eq!(pit.floor.as_ref().unwrap().heights, [-512.0; 4]);
+        for dir in EDGES {
+            assert_eq!(pit.walls(dir).len(), 1);
+            assert_eq!(pit.walls(dir)[0].heights, [-512.0, -512.0, 0.0, 0.0]);
+        }
+        assert!(room.get_sector(0, 1).unwrap().walls_east.is_empty());
+    }
+
+    #[test]
+    fn test_raised_block_walls_its_outline_from_outside() {
+        let mut room = RoomBuilder::new(2, 1).floor(0, 0, 0.0).floor(1, 0, 0.0).build();
+        let cells = room.extrude_horizontal(&[(0, 0), (1, 0)], false, 256.0, TextureRef::none()).unwrap();
+
+        // Walls go in the neighbors, so the grid grows by one on every side
+        assert_eq!(cells, vec![(1, 1), (2, 1)]);
+        assert_eq!((room.width, room.depth), (4, 3));
+        assert_eq!(room.get_sector(0, 1).unwrap().walls_east[0].heights, [0.0, 0.0, 256.0, 256.0]);
+        assert_eq!(room.get_sector(1, 0).unwrap().walls_south.len(), 1);
+        // No wall between the two extruded sectors
+        assert!(room.get_sector(1, 1).unwrap().walls_east.is_empty());
+        let walls: usize = room.iter_sectors().map(|(_, _, s)| EDGES.iter().map(|&d| s.walls(d).len()).sum::<usize>()).sum();
+        assert_eq!(walls, 6);
+    }
+
+    #[test]
+    fn test_extrude_wall_adds_sector_behind() {
+        let mut room = RoomBuilder::new(2, 1)
+            .floor(0, 0, 0.0)
+            .ceiling(0, 0, 1024.0)
+            .wall(0, 0, Direction::East, 0.0, 1024.0)
+            .build();
+        let new_cell = room.extrude_wall((0, 0), Direction::East, 0).unwrap();
+        assert_eq!(new_cell, (1, 0));
+        assert!(room.get_sector(0, 0).unwrap().walls_east.is_empty());
+
+        let sector = room.get_sector(1, 0).unwrap();
+        assert_eq!(sector.floor.as_ref().unwrap().heights, [0.0; 4]);
+        assert_eq!(sector.ceiling.as_ref().unwrap().heights, [1024.0; 4]);
+        assert_eq!(sector.walls_east.len(), 1);
+        assert_eq!(sector.walls_north.len(), 1);
+        assert_eq!(sector.walls_south.len(), 1);
+        assert!(sector.walls_west.is_empty());
+
+        // Extruding again grows the grid
+        assert_eq!(room.extrude_wall((1, 0), Direction::East, 0).unwrap(), (2, 0));
+        assert_eq!(room.width, 3);
+    }
+}
@@ -4,11 +4,22 @@
 //! Rooms contain a 2D grid of sectors, each with floor, ceiling, and walls.
 
 use serde::{Serialize, Deserialize};
-use crate::rasterizer::{Vec3, Vec2, Vertex, Face as RasterFace, BlendMode};
+use crate::rasterizer::{ray_triangle_intersect, Vec3, Vec2, Vertex, Face as RasterFace, BlendMode, ColorGrade};
 
-/// TRLE sector size in world units
+/// Default sector size in world units (TRLE); levels can override it
 pub const SECTOR_SIZE: f32 = 1024.0;
 
+/// Default height subdivision ("click") in world units; levels can override it
+pub const CLICK_HEIGHT: f32 = 256.0;
+
+fn default_sector_size() -> f32 {
+    SECTOR_SIZE
+}
+
+fn default_click_height() -> f32 {
+    CLICK_HEIGHT
+}
+
 /// Texture reference by pack and name
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TextureRef {
@@ -49,7 +60,7 @@ impl Default for TextureRef {
 fn default_true() -> bool { true }
 
 /// A horizontal face (floor or ceiling)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HorizontalFace {
     /// Corner heights [NW, NE, SE, SW] - allows sloped surfaces
     /// NW = (-X, -Z), NE = (+X, -Z), SE = (+X, +Z), SW = (-X, +Z)
@@ -62,6 +73,15 @@ pub struct HorizontalFace {
     /// Is this surface walkable? (for collision/AI)
     #[serde(default = "default_true")]
     pub walkable: bool,
+    /// Does this surface block rays and movement? (false = pass-through)
+    #[serde(default = "default_true")]
+    pub solid: bool,
+    /// Render both sides instead of culling the back
+    #[serde(default)]
+    pub double_sided: bool,
+    /// Hidden in game (still collides if solid, still shown in the editor)
+    #[serde(default)]
+    pub invisible: bool,
     /// Transparency/blend mode
     #[serde(default)]
     pub blend_mode: BlendMode,
@@ -75,6 +95,9 @@ impl HorizontalFace {
             texture,
             uv: None,
             walkable: true,
+            solid: true,
+            double_sided: false,
+            invisible: false,
             blend_mode: BlendMode::Opaque,
         }
     }
@@ -86,6 +109,9 @@ impl HorizontalFace {
             texture,
             uv: None,
             walkable: true,
+            solid: true,
+            double_sided: false,
+            invisible: false,
             blend_mode: BlendMode::Opaque,
         }
     }
@@ -100,10 +126,20 @@ impl HorizontalFace {
         let h = self.heights[0];
         self.heights.iter().all(|&corner| (corner - h).abs() < 0.001)
     }
+
+    /// UVs as drawn (the default spans the whole texture)
+    pub fn uvs(&self) -> [Vec2; 4] {
+        self.uv.unwrap_or([
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ])
+    }
 }
 
 /// A vertical face (wall) on a sector edge
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VerticalFace {
     /// Corner heights: [bottom-left, bottom-right, top-right, top-left]
     pub heights: [f32; 4],
@@ -115,6 +151,12 @@ pub struct VerticalFace {
     /// Is this a solid wall for collision?
     #[serde(default = "default_true")]
     pub solid: bool,
+    /// Render both sides instead of culling the back
+    #[serde(default)]
+    pub double_sided: bool,
+    /// Hidden in game (still collides if solid, still shown in the editor)
+    #[serde(default)]
+    pub invisible: bool,
     /// Transparency/blend mode
     #[serde(default)]
     pub blend_mode: BlendMode,
@@ -128,6 +170,8 @@ impl VerticalFace {
             texture,
             uv: None,
             solid: true,
+            double_sided: false,
+            invisible: false,
             blend_mode: BlendMode::Opaque,
         }
     }
@@ -155,10 +199,20 @@ impl VerticalFace {
         let top_same = (self.heights[2] - self.heights[3]).abs() < 0.001;
         bottom_same && top_same
     }
+
+    /// UVs as drawn (the default spans the whole texture, upright)
+    pub fn uvs(&self) -> [Vec2; 4] {
+        self.uv.unwrap_or([
+            Vec2::new(0.0, 1.0),  // bottom-left
+            Vec2::new(1.0, 1.0),  // bottom-right
+            Vec2::new(1.0, 0.0),  // top-right
+            Vec2::new(0.0, 0.0),  // top-left
+        ])
+    }
 }
 
 /// A single sector in the room grid
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Sector {
     /// Floor face (None = no floor / pit)
     pub floor: Option<HorizontalFace>,
@@ -264,7 +318,7 @@ impl Direction {
 }
 
 /// Axis-aligned bounding box
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 pub struct Aabb {
     pub min: Vec3,
     pub max: Vec3,
@@ -300,10 +354,78 @@ impl Aabb {
             (self.min.z + self.max.z) * 0.5,
         )
     }
+
+    /// Check if two boxes overlap (touching counts as overlap)
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+            && self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    /// Distance along a ray to where it enters the box (0 if it starts inside)
+    pub fn ray_intersect(&self, origin: Vec3, dir: Vec3) -> Option<f32> {
+        let (mut t_min, mut t_max) = (0.0f32, f32::MAX);
+        for (o, d, lo, hi) in [
+            (origin.x, dir.x, self.min.x, self.max.x),
+            (origin.y, dir.y, self.min.y, self.max.y),
+            (origin.z, dir.z, self.min.z, self.max.z),
+        ] {
+            if d.abs() < 1e-9 {
+                if o < lo || o > hi {
+                    return None;
+                }
+            } else {
+                let (t0, t1) = ((lo - o) / d, (hi - o) / d);
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+            }
+        }
+        (t_min <= t_max).then_some(t_min)
+    }
+
+    /// Get the 12 edges of the box as line segments (for wireframe drawing)
+    pub fn edges(&self) -> [(Vec3, Vec3); 12] {
+        let (a, b) = (self.min, self.max);
+        let c = [
+            Vec3::new(a.x, a.y, a.z), Vec3::new(b.x, a.y, a.z),
+            Vec3::new(b.x, a.y, b.z), Vec3::new(a.x, a.y, b.z),
+            Vec3::new(a.x, b.y, a.z), Vec3::new(b.x, b.y, a.z),
+            Vec3::new(b.x, b.y, b.z), Vec3::new(a.x, b.y, b.z),
+        ];
+        [
+            (c[0], c[1]), (c[1], c[2]), (c[2], c[3]), (c[3], c[0]),
+            (c[4], c[5]), (c[5], c[6]), (c[6], c[7]), (c[7], c[4]),
+            (c[0], c[4]), (c[1], c[5]), (c[2], c[6]), (c[3], c[7]),
+        ]
+    }
+}
+
+/// What a portal does besides linking two rooms
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum PortalKind {
+    /// Plain opening into the target room
+    #[default]
+    Opening,
+    /// Reflects the room it faces (drawn with a second, reflected render pass)
+    Mirror,
+    /// Leaving the room through it moves the player to `destination`,
+    /// relative to the target room's origin
+    Warp { destination: Vec3 },
+}
+
+impl PortalKind {
+    /// Short name for the editor
+    pub fn label(&self) -> &'static str {
+        match self {
+            PortalKind::Opening => "Opening",
+            PortalKind::Mirror => "Mirror",
+            PortalKind::Warp { .. } => "Warp",
+        }
+    }
 }
 
 /// Portal connecting two rooms
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Portal {
     /// Target room ID
     pub target_room: usize,
@@ -311,6 +433,9 @@ pub struct Portal {
     pub vertices: [Vec3; 4],
     /// Portal facing direction (points into the room)
     pub normal: Vec3,
+    /// Mirror / warp behaviour
+    #[serde(default)]
+    pub kind: PortalKind,
 }
 
 impl Portal {
@@ -319,6 +444,7 @@ impl Portal {
             target_room,
             vertices,
             normal: normal.normalize(),
+            kind: PortalKind::Opening,
         }
     }
 
@@ -333,7 +459,7 @@ impl Portal {
 }
 
 /// A room in the level - contains a 2D grid of sectors
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Room {
     /// Unique room identifier
     pub id: usize,
@@ -351,9 +477,24 @@ pub struct Room {
     /// Bounding box (room-relative) - computed from sectors, not serialized
     #[serde(skip)]
     pub bounds: Aabb,
+    /// Sector size, copied from the level (see `Level::apply_metrics`), not serialized
+    #[serde(skip, default = "default_sector_size")]
+    pub sector_size: f32,
     /// Ambient light level (0.0 = dark, 1.0 = bright)
     #[serde(default = "default_ambient")]
     pub ambient: f32,
+    /// Rain, snow or dust (see `world::weather`)
+    #[serde(default)]
+    pub weather: super::Weather,
+    /// Ambient loop and reverb while the camera is in the room (see `world::room_audio`)
+    #[serde(default)]
+    pub audio: super::RoomAudio,
+    /// AI navigation waypoints (room-relative)
+    #[serde(default)]
+    pub waypoints: Vec<super::Waypoint>,
+    /// Placed entities (room-relative)
+    #[serde(default)]
+    pub entities: Vec<super::Entity>,
 }
 
 fn default_ambient() -> f32 {
@@ -376,7 +517,12 @@ impl Room {
             sectors,
             portals: Vec::new(),
             bounds: Aabb::default(),
+            sector_size: SECTOR_SIZE,
             ambient: 0.5,
+            weather: Default::default(),
+            audio: Default::default(),
+            waypoints: Vec::new(),
+            entities: Vec::new(),
         }
     }
 
@@ -448,8 +594,8 @@ impl Room {
             return None;
         }
 
-        let grid_x = (local_x / SECTOR_SIZE) as usize;
-        let grid_z = (local_z / SECTOR_SIZE) as usize;
+        let grid_x = (local_x / self.sector_size) as usize;
+        let grid_z = (local_z / self.sector_size) as usize;
 
         if grid_x < self.width && grid_z < self.depth {
             Some((grid_x, grid_z))
@@ -461,14 +607,15 @@ impl Room {
     /// Convert grid coordinates to world position (returns corner of sector)
     pub fn grid_to_world(&self, x: usize, z: usize) -> Vec3 {
         Vec3::new(
-            self.position.x + (x as f32) * SECTOR_SIZE,
+            self.position.x + (x as f32) * self.sector_size,
             self.position.y,
-            self.position.z + (z as f32) * SECTOR_SIZE,
+            self.position.z + (z as f32) * self.sector_size,
         )
     }
 
     /// Recalculate bounds from sectors (call after loading from file)
     pub fn recalculate_bounds(&mut self) {
+        let size = self.sector_size;
         self.bounds = Aabb::new(
             Vec3::new(f32::MAX, f32::MAX, f32::MAX),
             Vec3::new(f32::MIN, f32::MIN, f32::MIN),
@@ -477,17 +624,17 @@ impl Room {
         for x in 0..self.width {
             for z in 0..self.depth {
                 if let Some(sector) = &self.sectors[x][z] {
-                    let base_x = (x as f32) * SECTOR_SIZE;
-                    let base_z = (z as f32) * SECTOR_SIZE;
+                    let base_x = (x as f32) * size;
+                    let base_z = (z as f32) * size;
 
                     // Expand bounds for floor corners
                     if let Some(floor) = &sector.floor {
                         for (i, &h) in floor.heights.iter().enumerate() {
                             let (dx, dz) = match i {
                                 0 => (0.0, 0.0),           // NW
-                                1 => (SECTOR_SIZE, 0.0),   // NE
-                                2 => (SECTOR_SIZE, SECTOR_SIZE), // SE
-                                3 => (0.0, SECTOR_SIZE),   // SW
+                                1 => (size, 0.0),   // NE
+                                2 => (size, size), // SE
+                                3 => (0.0, size),   // SW
                                 _ => unreachable!(),
                             };
                             self.bounds.expand(Vec3::new(base_x + dx, h, base_z + dz));
@@ -499,9 +646,9 @@ impl Room {
                         for (i, &h) in ceiling.heights.iter().enumerate() {
                             let (dx, dz) = match i {
                                 0 => (0.0, 0.0),
-                                1 => (SECTOR_SIZE, 0.0),
-                                2 => (SECTOR_SIZE, SECTOR_SIZE),
-                                3 => (0.0, SECTOR_SIZE),
+                                1 => (size, 0.0),
+                                2 => (size, size),
+                                3 => (0.0, size),
                                 _ => unreachable!(),
                             };
                             self.bounds.expand(Vec3::new(base_x + dx, h, base_z + dz));
@@ -516,12 +663,12 @@ impl Room {
                     }
                     for wall in &sector.walls_east {
                         for &h in &wall.heights {
-                            self.bounds.expand(Vec3::new(base_x + SECTOR_SIZE, h, base_z));
+                            self.bounds.expand(Vec3::new(base_x + size, h, base_z));
                         }
                     }
                     for wall in &sector.walls_south {
                         for &h in &wall.heights {
-                            self.bounds.expand(Vec3::new(base_x, h, base_z + SECTOR_SIZE));
+                            self.bounds.expand(Vec3::new(base_x, h, base_z + size));
                         }
                     }
                     for wall in &sector.walls_west {
@@ -570,20 +717,38 @@ impl Room {
     }
 
     /// Convert room geometry to rasterizer format (vertices + faces)
-    /// Returns world-space vertices ready for rendering
+    /// Returns world-space vertices ready for rendering.
+    /// Every face is included (invisible ones too) so editor picking can map
+    /// triangles back to faces.
     pub fn to_render_data_with_textures<F>(&self, resolve_texture: F) -> (Vec<Vertex>, Vec<RasterFace>)
+    where
+        F: Fn(&TextureRef) -> Option<usize>,
+    {
+        self.build_render_data(resolve_texture, true)
+    }
+
+    /// Render data as the game draws it: faces flagged invisible are left out
+    pub fn to_game_render_data<F>(&self, resolve_texture: F) -> (Vec<Vertex>, Vec<RasterFace>)
+    where
+        F: Fn(&TextureRef) -> Option<usize>,
+    {
+        self.build_render_data(resolve_texture, false)
+    }
+
+    fn build_render_data<F>(&self, resolve_texture: F, include_invisible: bool) -> (Vec<Vertex>, Vec<RasterFace>)
     where
         F: Fn(&TextureRef) -> Option<usize>,
     {
         let mut vertices = Vec::new();
         let mut faces = Vec::new();
+        let shown = |invisible: bool| include_invisible || !invisible;
 
         for (grid_x, grid_z, sector) in self.iter_sectors() {
-            let base_x = self.position.x + (grid_x as f32) * SECTOR_SIZE;
-            let base_z = self.position.z + (grid_z as f32) * SECTOR_SIZE;
+            let base_x = self.position.x + (grid_x as f32) * self.sector_size;
+            let base_z = self.position.z + (grid_z as f32) * self.sector_size;
 
             // Render floor
-            if let Some(floor) = &sector.floor {
+            if let Some(floor) = sector.floor.as_ref().filter(|f| shown(f.invisible)) {
                 self.add_horizontal_face_to_render_data(
                     &mut vertices,
                     &mut faces,
@@ -596,7 +761,7 @@ impl Room {
             }
 
             // Render ceiling
-            if let Some(ceiling) = &sector.ceiling {
+            if let Some(ceiling) = sector.ceiling.as_ref().filter(|c| shown(c.invisible)) {
                 self.add_horizontal_face_to_render_data(
                     &mut vertices,
                     &mut faces,
@@ -609,16 +774,16 @@ impl Room {
             }
 
             // Render walls on each edge
-            for wall in &sector.walls_north {
+            for wall in sector.walls_north.iter().filter(|w| shown(w.invisible)) {
                 self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::North, &resolve_texture);
             }
-            for wall in &sector.walls_east {
+            for wall in sector.walls_east.iter().filter(|w| shown(w.invisible)) {
                 self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::East, &resolve_texture);
             }
-            for wall in &sector.walls_south {
+            for wall in sector.walls_south.iter().filter(|w| shown(w.invisible)) {
                 self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::South, &resolve_texture);
             }
-            for wall in &sector.walls_west {
+            for wall in sector.walls_west.iter().filter(|w| shown(w.invisible)) {
                 self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::West, &resolve_texture);
             }
         }
@@ -645,9 +810,9 @@ impl Room {
         // Corner positions: NW, NE, SE, SW
         let corners = [
             Vec3::new(base_x, face.heights[0], base_z),                         // NW
-            Vec3::new(base_x + SECTOR_SIZE, face.heights[1], base_z),           // NE
-            Vec3::new(base_x + SECTOR_SIZE, face.heights[2], base_z + SECTOR_SIZE), // SE
-            Vec3::new(base_x, face.heights[3], base_z + SECTOR_SIZE),           // SW
+            Vec3::new(base_x + self.sector_size, face.heights[1], base_z),           // NE
+            Vec3::new(base_x + self.sector_size, face.heights[2], base_z + self.sector_size), // SE
+            Vec3::new(base_x, face.heights[3], base_z + self.sector_size),           // SW
         ];
 
         // Calculate normal from cross product
@@ -661,13 +826,7 @@ impl Room {
             edge1.cross(edge2).normalize() // +X x +Z = -Y (down)
         };
 
-        // Default UVs
-        let uvs = face.uv.unwrap_or([
-            Vec2::new(0.0, 0.0),
-            Vec2::new(1.0, 0.0),
-            Vec2::new(1.0, 1.0),
-            Vec2::new(0.0, 1.0),
-        ]);
+        let uvs = face.uvs();
 
         // Add vertices
         for i in 0..4 {
@@ -677,12 +836,13 @@ impl Room {
         let texture_id = resolve_texture(&face.texture).unwrap_or(0);
 
         // Winding order: floor = CCW from above, ceiling = CW from above (so it faces down)
+        let tri = |a, b, c| RasterFace::with_texture(a, b, c, texture_id).two_sided(face.double_sided);
         if is_floor {
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 1, base_idx + 2, texture_id));
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 3, texture_id));
+            faces.push(tri(base_idx, base_idx + 1, base_idx + 2));
+            faces.push(tri(base_idx, base_idx + 2, base_idx + 3));
         } else {
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 3, base_idx + 2, texture_id));
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 1, texture_id));
+            faces.push(tri(base_idx, base_idx + 3, base_idx + 2));
+            faces.push(tri(base_idx, base_idx + 2, base_idx + 1));
         }
     }
 
@@ -710,8 +870,8 @@ impl Room {
                 // Wall at -Z edge, facing +Z (into room)
                 let corners = [
                     Vec3::new(base_x, wall.heights[0], base_z),                    // bottom-left
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z),      // bottom-right
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z),      // top-right
+                    Vec3::new(base_x + self.sector_size, wall.heights[1], base_z),      // bottom-right
+                    Vec3::new(base_x + self.sector_size, wall.heights[2], base_z),      // top-right
                     Vec3::new(base_x, wall.heights[3], base_z),                    // top-left
                 ];
                 (corners, Vec3::new(0.0, 0.0, 1.0))
@@ -719,42 +879,36 @@ impl Room {
             Direction::East => {
                 // Wall at +X edge, facing -X (into room)
                 let corners = [
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z),
+                    Vec3::new(base_x + self.sector_size, wall.heights[0], base_z),
+                    Vec3::new(base_x + self.sector_size, wall.heights[1], base_z + self.sector_size),
+                    Vec3::new(base_x + self.sector_size, wall.heights[2], base_z + self.sector_size),
+                    Vec3::new(base_x + self.sector_size, wall.heights[3], base_z),
                 ];
                 (corners, Vec3::new(-1.0, 0.0, 0.0))
             }
             Direction::South => {
                 // Wall at +Z edge, facing -Z (into room)
                 let corners = [
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x, wall.heights[1], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x, wall.heights[2], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z + SECTOR_SIZE),
+                    Vec3::new(base_x + self.sector_size, wall.heights[0], base_z + self.sector_size),
+                    Vec3::new(base_x, wall.heights[1], base_z + self.sector_size),
+                    Vec3::new(base_x, wall.heights[2], base_z + self.sector_size),
+                    Vec3::new(base_x + self.sector_size, wall.heights[3], base_z + self.sector_size),
                 ];
                 (corners, Vec3::new(0.0, 0.0, -1.0))
             }
             Direction::West => {
                 // Wall at -X edge, facing +X (into room)
                 let corners = [
-                    Vec3::new(base_x, wall.heights[0], base_z + SECTOR_SIZE),
+                    Vec3::new(base_x, wall.heights[0], base_z + self.sector_size),
                     Vec3::new(base_x, wall.heights[1], base_z),
                     Vec3::new(base_x, wall.heights[2], base_z),
-                    Vec3::new(base_x, wall.heights[3], base_z + SECTOR_SIZE),
+                    Vec3::new(base_x, wall.heights[3], base_z + self.sector_size),
                 ];
                 (corners, Vec3::new(1.0, 0.0, 0.0))
             }
         };
 
-        // Default UVs for wall
-        let uvs = wall.uv.unwrap_or([
-            Vec2::new(0.0, 1.0),  // bottom-left
-            Vec2::new(1.0, 1.0),  // bottom-right
-            Vec2::new(1.0, 0.0),  // top-right
-            Vec2::new(0.0, 0.0),  // top-left
-        ]);
+        let uvs = wall.uvs();
 
         for i in 0..4 {
             vertices.push(Vertex::new(corners[i], uvs[i], normal));
@@ -763,8 +917,8 @@ impl Room {
         let texture_id = resolve_texture(&wall.texture).unwrap_or(0);
 
         // Two triangles for the quad (CCW winding when viewed from inside room)
-        faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 1, texture_id));
-        faces.push(RasterFace::with_texture(base_idx, base_idx + 3, base_idx + 2, texture_id));
+        faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 1, texture_id).two_sided(wall.double_sided));
+        faces.push(RasterFace::with_texture(base_idx, base_idx + 3, base_idx + 2, texture_id).two_sided(wall.double_sided));
     }
 }
 
@@ -779,6 +933,13 @@ pub struct EditorLayoutConfig {
     pub left_split: f32,
     /// Right vertical split ratio (texture palette | properties)
     pub right_panel_split: f32,
+    /// Room panel split ratio (room properties | history)
+    #[serde(default = "default_room_history_split")]
+    pub room_history_split: f32,
+}
+
+fn default_room_history_split() -> f32 {
+    0.5
 }
 
 impl Default for EditorLayoutConfig {
@@ -788,6 +949,7 @@ impl Default for EditorLayoutConfig {
             right_split: 0.75,
             left_split: 0.6,
             right_panel_split: 0.6,
+            room_history_split: default_room_history_split(),
         }
     }
 }
@@ -795,27 +957,154 @@ impl Default for EditorLayoutConfig {
 /// The entire level
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Level {
+    /// Thumbnail and save information (written first so it's cheap to read)
+    #[serde(default)]
+    pub metadata: super::LevelMetadata,
     pub rooms: Vec<Room>,
     /// Editor layout configuration (optional, uses default if missing)
     #[serde(default)]
     pub editor_layout: EditorLayoutConfig,
+    /// Cutscene camera paths
+    #[serde(default)]
+    pub camera_paths: Vec<super::CameraPath>,
+    /// Sector size in world units (X-Z grid)
+    #[serde(default = "default_sector_size")]
+    pub sector_size: f32,
+    /// Height subdivision ("click") in world units (Y snapping)
+    #[serde(default = "default_click_height")]
+    pub click_height: f32,
+    /// Color grading applied when the level is rendered
+    #[serde(default)]
+    pub ambience: ColorGrade,
+    /// Named selections saved in the editor
+    #[serde(default)]
+    pub selection_sets: Vec<super::SelectionSet>,
+    /// Texture variant set and seed of the last random fill
+    #[serde(default)]
+    pub texture_variants: super::TextureVariants,
+    /// Sector edit macros recorded in the editor
+    #[serde(default)]
+    pub macros: Vec<super::EditMacro>,
+    /// Scripted event timeline run in play mode
+    #[serde(default)]
+    pub events: Vec<super::LevelEvent>,
+    /// What resets when the player respawns at a checkpoint
+    #[serde(default)]
+    pub respawn: super::RespawnRules,
+    /// Id of the song played in the level (see `tracker::SONGS_DIR`), None
+    /// for the game's default song
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Set on exported levels whose rooms' sectors are kept in separate files
+    /// (see `split_rooms`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streamed: Option<super::StreamedRooms>,
 }
 
 impl Level {
     pub fn new() -> Self {
         Self {
+            metadata: super::LevelMetadata::default(),
             rooms: Vec::new(),
             editor_layout: EditorLayoutConfig::default(),
+            camera_paths: Vec::new(),
+            sector_size: SECTOR_SIZE,
+            click_height: CLICK_HEIGHT,
+            ambience: ColorGrade::default(),
+            selection_sets: Vec::new(),
+            texture_variants: Default::default(),
+            macros: Vec::new(),
+            events: Vec::new(),
+            respawn: Default::default(),
+            music: None,
+            streamed: None,
         }
     }
 
-    /// Add a room and return its index
-    pub fn add_room(&mut self, room: Room) -> usize {
+    /// Add a room and return its index (the room takes the level's sector size)
+    pub fn add_room(&mut self, mut room: Room) -> usize {
         let id = self.rooms.len();
+        room.sector_size = self.sector_size;
+        room.recalculate_bounds();
         self.rooms.push(room);
         id
     }
 
+    /// Copy the sector size into every room and recompute bounds
+    /// (after loading, or after changing `sector_size` directly)
+    pub fn apply_metrics(&mut self) {
+        for (i, room) in self.rooms.iter_mut().enumerate() {
+            room.sector_size = self.sector_size;
+            // A split level saved the bounds its rooms had with their sectors
+            match self.streamed.as_ref().and_then(|s| s.bounds.get(i)) {
+                Some(&bounds) => room.bounds = bounds,
+                None => room.recalculate_bounds(),
+            }
+        }
+    }
+
+    /// Change the sector size, keeping rooms on the same grid cells
+    /// (room positions, portals and waypoints are scaled in X/Z; heights are kept)
+    pub fn set_sector_size(&mut self, sector_size: f32) {
+        if sector_size <= 0.0 || sector_size == self.sector_size {
+            return;
+        }
+        let ratio = sector_size / self.sector_size;
+        let scale_xz = |v: &mut Vec3| {
+            v.x *= ratio;
+            v.z *= ratio;
+        };
+        for room in &mut self.rooms {
+            scale_xz(&mut room.position);
+            for portal in &mut room.portals {
+                portal.vertices.iter_mut().for_each(scale_xz);
+                if let PortalKind::Warp { destination } = &mut portal.kind {
+                    scale_xz(destination);
+                }
+            }
+            for waypoint in &mut room.waypoints {
+                scale_xz(&mut waypoint.position);
+            }
+        }
+        self.sector_size = sector_size;
+        self.apply_metrics();
+    }
+
+    /// Snap a height to the level's click grid
+    pub fn snap_to_click(&self, height: f32) -> f32 {
+        (height / self.click_height).round() * self.click_height
+    }
+
+    /// Where a move from `from` to `to` ends up if it leaves a room through a
+    /// warp portal (world space), or None if it crosses no warp
+    pub fn warp_destination(&self, from: Vec3, to: Vec3) -> Option<Vec3> {
+        let delta = to - from;
+        let len = delta.len();
+        if len < 1e-6 {
+            return None;
+        }
+        let dir = delta.scale(1.0 / len);
+        for room in &self.rooms {
+            for portal in &room.portals {
+                let PortalKind::Warp { destination } = portal.kind else { continue };
+                // Only on the way out, so arriving through the back never warps
+                if dir.dot(portal.normal) >= 0.0 {
+                    continue;
+                }
+                let c = portal.vertices.map(|v| v + room.position);
+                let crossed = [[c[0], c[1], c[2]], [c[0], c[2], c[3]]]
+                    .into_iter()
+                    .any(|[a, b, d]| ray_triangle_intersect(from, dir, a, b, d).is_some_and(|t| t <= len));
+                if crossed {
+                    if let Some(target) = self.rooms.get(portal.target_room) {
+                        return Some(target.position + destination);
+                    }
+                }
+            }
+        }
+        None
+    }
+
     /// Find which room contains a point
     pub fn find_room_at(&self, point: Vec3) -> Option<usize> {
         for (i, room) in self.rooms.iter().enumerate() {
@@ -845,45 +1134,117 @@ impl Level {
 /// Create an empty level with a single starter room (floor only)
 /// Uses TRLE sector size (1024 units) for proper grid alignment
 pub fn create_empty_level() -> Level {
-    let mut level = Level::new();
-
-    // Create a single starter room with one sector (1x1 grid)
-    let mut room0 = Room::new(0, Vec3::ZERO, 1, 1);
-
-    // Add floor at height 0
-    let texture = TextureRef::new("retro-texture-pack", "FLOOR_1A");
-    room0.set_floor(0, 0, 0.0, texture);
-
-    room0.recalculate_bounds();
-    level.rooms.push(room0);
-
-    level
+    super::LevelBuilder::new()
+        .room(
+            super::RoomBuilder::new(1, 1)
+                .texture(TextureRef::new("retro-texture-pack", "FLOOR_1A"))
+                .floor(0, 0, 0.0),
+        )
+        .build()
 }
 
 /// Create a simple test level with a fully enclosed room
 /// Uses TRLE sector sizes (1024 units per sector)
 pub fn create_test_level() -> Level {
-    let mut level = Level::new();
-
-    // Room 0: Single sector room (1024×1024, height 1024 = 4 clicks)
-    let mut room0 = Room::new(0, Vec3::ZERO, 1, 1);
+    // Single sector room (1024x1024, height 1024 = 4 clicks)
+    super::LevelBuilder::new()
+        .room(
+            super::RoomBuilder::new(1, 1)
+                .texture(TextureRef::new("retro-texture-pack", "FLOOR_1A"))
+                .wall_texture(TextureRef::new("retro-texture-pack", "WALL_1A"))
+                .floor(0, 0, 0.0)
+                .ceiling(0, 0, 1024.0)
+                .enclose(0.0, 1024.0),
+        )
+        .build()
+}
 
-    // Floor at y=0, ceiling at y=1024
-    let floor_tex = TextureRef::new("retro-texture-pack", "FLOOR_1A");
-    let ceiling_tex = TextureRef::new("retro-texture-pack", "FLOOR_1A");
-    let wall_tex = TextureRef::new("retro-texture-pack", "WALL_1A");
+/// Create a large, dense level for rasterizer benchmarks
+/// `size` x `size` sectors with stepped floors, a ceiling, and a pillar on every third sector
+pub fn create_stress_level(size: usize) -> Level {
+    let mut room = super::RoomBuilder::new(size, size)
+        .texture(TextureRef::new("retro-texture-pack", "FLOOR_1A"))
+        .wall_texture(TextureRef::new("retro-texture-pack", "WALL_1A"));
+
+    for x in 0..size {
+        for z in 0..size {
+            let floor = ((x + z) % 4) as f32 * 256.0;
+            room = room.floor(x, z, floor).ceiling(x, z, 3072.0);
+
+            if x % 3 == 1 && z % 3 == 1 {
+                for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                    room = room.wall(x, z, dir, floor, 3072.0);
+                }
+            }
+        }
+    }
 
-    room0.set_floor(0, 0, 0.0, floor_tex);
-    room0.set_ceiling(0, 0, 1024.0, ceiling_tex);
+    super::LevelBuilder::new().room(room).build()
+}
 
-    // Four walls around the single sector
-    room0.add_wall(0, 0, Direction::North, 0.0, 1024.0, wall_tex.clone());
-    room0.add_wall(0, 0, Direction::East, 0.0, 1024.0, wall_tex.clone());
-    room0.add_wall(0, 0, Direction::South, 0.0, 1024.0, wall_tex.clone());
-    room0.add_wall(0, 0, Direction::West, 0.0, 1024.0, wall_tex);
+/// Rooms in the benchmark level
+pub const BENCHMARK_ROOMS: usize = 5;
+
+/// Create the standard benchmark level
+/// `BENCHMARK_ROOMS` rooms of 6x6 sectors in a row, each joined to the next by
+/// a one-sector doorway portal, with stepped floors and pillars that get
+/// denser room by room. Always built the same way, so `bonnie-engine bench`
+/// frame times compare across machines and engine versions.
+pub fn create_benchmark_level() -> Level {
+    const SIZE: usize = 6;
+    const DOOR: usize = SIZE / 2;
+    const HEIGHT: f32 = 3072.0;
+    let span = SIZE as f32 * SECTOR_SIZE;
+    let doorway = |x: f32| {
+        let (z0, z1) = (DOOR as f32 * SECTOR_SIZE, (DOOR + 1) as f32 * SECTOR_SIZE);
+        [Vec3::new(x, 0.0, z0), Vec3::new(x, 0.0, z1), Vec3::new(x, HEIGHT, z1), Vec3::new(x, HEIGHT, z0)]
+    };
+
+    let mut level = super::LevelBuilder::new();
+    for i in 0..BENCHMARK_ROOMS {
+        let mut room = super::RoomBuilder::new(SIZE, SIZE)
+            .at(Vec3::new(i as f32 * span, 0.0, 0.0))
+            .texture(TextureRef::new("retro-texture-pack", "FLOOR_1A"))
+            .wall_texture(TextureRef::new("retro-texture-pack", "WALL_1A"));
+        // Fewer sectors between pillars in each room further down the row
+        let spacing = BENCHMARK_ROOMS + 1 - i;
+
+        for x in 0..SIZE {
+            for z in 0..SIZE {
+                let floor = ((x + z + i) % 4) as f32 * 256.0;
+                room = room.floor(x, z, floor).ceiling(x, z, HEIGHT);
+
+                let interior = (1..SIZE - 1).contains(&x) && (1..SIZE - 1).contains(&z) && z != DOOR;
+                if interior && (x * 7 + z * 3 + i) % spacing == 0 {
+                    for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                        room = room.wall(x, z, dir, floor, HEIGHT);
+                    }
+                }
 
-    room0.recalculate_bounds();
-    level.add_room(room0);
+                let door_west = x == 0 && z == DOOR && i > 0;
+                let door_east = x == SIZE - 1 && z == DOOR && i + 1 < BENCHMARK_ROOMS;
+                if z == 0 {
+                    room = room.wall(x, z, Direction::North, 0.0, HEIGHT);
+                }
+                if z == SIZE - 1 {
+                    room = room.wall(x, z, Direction::South, 0.0, HEIGHT);
+                }
+                if x == 0 && !door_west {
+                    room = room.wall(x, z, Direction::West, 0.0, HEIGHT);
+                }
+                if x == SIZE - 1 && !door_east {
+                    room = room.wall(x, z, Direction::East, 0.0, HEIGHT);
+                }
+            }
+        }
 
-    level
+        if i > 0 {
+            room = room.portal(i - 1, doorway(0.0), Vec3::new(-1.0, 0.0, 0.0));
+        }
+        if i + 1 < BENCHMARK_ROOMS {
+            room = room.portal(i + 1, doorway(span), Vec3::new(1.0, 0.0, 0.0));
+        }
+        level = level.room(room);
+    }
+    level.build()
 }
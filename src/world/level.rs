@@ -1,11 +1,33 @@
 //! Level loading and saving
 //!
 //! Uses RON (Rusty Object Notation) for human-readable level files.
+//!
+//! Saving is canonical, so files diff well under version control:
+//! - everything is written in a fixed order (struct fields in declaration
+//!   order, rooms and sectors by index, entity properties sorted by name)
+//! - floats are rounded to `FLOAT_DECIMALS`, so arithmetic noise
+//!   (`255.99998`) doesn't churn lines that didn't really change
+//! - levels whose metadata asks for `SaveFormat::DiffFriendly` put every
+//!   field on its own line instead of inlining nested data
 
 use std::fs;
 use std::path::Path;
+use serde::{Deserialize, Serialize};
 use super::Level;
 
+/// Decimal places kept for floats in saved levels
+pub const FLOAT_DECIMALS: usize = 4;
+
+/// Layout of a saved level file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SaveFormat {
+    /// Nested data inlined past a few levels of depth: smaller files
+    #[default]
+    Compact,
+    /// One field per line, so an edit changes as few lines as possible
+    DiffFriendly,
+}
+
 /// Error type for level loading
 #[derive(Debug)]
 pub enum LevelError {
@@ -44,35 +66,113 @@ impl std::fmt::Display for LevelError {
 
 /// Load a level from a RON file
 pub fn load_level<P: AsRef<Path>>(path: P) -> Result<Level, LevelError> {
-    let contents = fs::read_to_string(path)?;
+    let contents = fs::read_to_string(path.as_ref())?;
     let mut level: Level = ron::from_str(&contents)?;
 
-    // Recalculate bounds for all rooms (not serialized)
-    for room in &mut level.rooms {
-        room.recalculate_bounds();
+    // Exported levels keep their rooms' sectors in separate files
+    if level.streamed.is_some() {
+        super::merge_rooms(&mut level, path.as_ref())?;
     }
 
+    // Room sector sizes and bounds are not serialized
+    level.apply_metrics();
+
     Ok(level)
 }
 
 /// Save a level to a RON file
 pub fn save_level<P: AsRef<Path>>(level: &Level, path: P) -> Result<(), LevelError> {
-    let config = ron::ser::PrettyConfig::new()
-        .depth_limit(4)
-        .indentor("  ".to_string());
-
-    let contents = ron::ser::to_string_pretty(level, config)?;
+    let contents = level_to_string(level)?;
     fs::write(path, contents)?;
     Ok(())
 }
 
+/// Serialize a level the way `save_level` writes it
+pub fn level_to_string(level: &Level) -> Result<String, LevelError> {
+    let mut config = ron::ser::PrettyConfig::new().indentor("  ".to_string());
+    if level.metadata.save_format == SaveFormat::Compact {
+        config = config.depth_limit(4);
+    }
+    let contents = ron::ser::to_string_pretty(level, config)?;
+    Ok(round_floats(&contents, FLOAT_DECIMALS))
+}
+
+/// Round every float literal in RON text to `decimals` places, leaving
+/// strings, identifiers and integers alone
+fn round_floats(ron: &str, decimals: usize) -> String {
+    let bytes = ron.as_bytes();
+    let mut out = String::with_capacity(ron.len());
+    let mut i = 0;
+    let mut in_string = false;
+    while i < bytes.len() {
+        let c = bytes[i];
+        // Copy a whole character (strings and names may hold UTF-8)
+        let char_end = |at: usize| at + ron[at..].chars().next().map_or(0, char::len_utf8);
+        if in_string {
+            // An escape copies the escaped character with it
+            let end = if c == b'\\' && i + 1 < bytes.len() { char_end(i + 1) } else { char_end(i) };
+            out.push_str(&ron[i..end]);
+            in_string = c != b'"';
+            i = end;
+            continue;
+        }
+        let starts_number = c.is_ascii_digit() || (c == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit));
+        let after_word = i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_');
+        if !starts_number || after_word {
+            in_string = c == b'"';
+            let end = char_end(i);
+            out.push_str(&ron[i..end]);
+            i = end;
+            continue;
+        }
+        let end = ron[i + 1..]
+            .find(|ch: char| !(ch.is_ascii_digit() || matches!(ch, '.' | 'e' | 'E' | '-' | '+')))
+            .map_or(ron.len(), |n| i + 1 + n);
+        let literal = &ron[i..end];
+        match literal.parse::<f64>() {
+            Ok(value) if literal.contains(['.', 'e', 'E']) => {
+                let rounded = format!("{:.*}", decimals, value);
+                let trimmed = rounded.trim_end_matches('0');
+                let trimmed = if trimmed.ends_with('.') { format!("{}0", trimmed) } else { trimmed.to_string() };
+                out.push_str(if trimmed == "-0.0" { "0.0" } else { &trimmed });
+            }
+            _ => out.push_str(literal),
+        }
+        i = end;
+    }
+    out
+}
+
 /// Load a level from a RON string (for embedded levels or testing)
 pub fn load_level_from_str(s: &str) -> Result<Level, LevelError> {
     let mut level: Level = ron::from_str(s)?;
 
-    for room in &mut level.rooms {
-        room.recalculate_bounds();
-    }
+    level.apply_metrics();
 
     Ok(level)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_test_level;
+
+    #[test]
+    fn test_floats_are_rounded_outside_strings() {
+        let ron = r#"(h: [255.99998, -0.00001, 1e-7, 64], name: "v1.23456", v2: 0.33333334)"#;
+        assert_eq!(round_floats(ron, 4), r#"(h: [256.0, 0.0, 0.0, 64], name: "v1.23456", v2: 0.3333)"#);
+    }
+
+    #[test]
+    fn test_saving_is_stable() {
+        for format in [SaveFormat::Compact, SaveFormat::DiffFriendly] {
+            let mut level = create_test_level();
+            level.metadata.save_format = format;
+            level.rooms[0].position.x += 0.1 + 0.2;
+            let saved = level_to_string(&level).unwrap();
+            let reloaded = load_level_from_str(&saved).unwrap();
+            assert_eq!(level_to_string(&reloaded).unwrap(), saved);
+            assert_eq!(reloaded.metadata.save_format, format);
+        }
+    }
+}
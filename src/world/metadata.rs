@@ -0,0 +1,248 @@
+//! Level file metadata
+//!
+//! Stored at the top of level files so listings can show previews without
+//! loading whole levels:
+//! - a small thumbnail (PNG, base64 encoded)
+//! - when the level was last saved, and by which editor version
+//! - editing statistics accumulated over every session (time, operations,
+//!   sectors created)
+//! - the file layout `save_level` uses (compact or diff-friendly)
+//!
+//! `read_level_metadata` parses only the metadata, skipping the geometry.
+
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::{Camera, Vec3};
+use super::{room_world_bounds, Aabb, Level, LevelError, SaveFormat};
+
+/// Thumbnail size in pixels (4:3, like the framebuffer)
+pub const THUMBNAIL_WIDTH: usize = 96;
+pub const THUMBNAIL_HEIGHT: usize = 72;
+
+/// Preview and save information embedded in a level file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LevelMetadata {
+    /// PNG thumbnail, base64 encoded
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// Last save time in seconds since the Unix epoch (0 = unknown)
+    #[serde(default)]
+    pub modified: u64,
+    /// Version of the editor that last saved the level
+    #[serde(default)]
+    pub editor_version: String,
+    /// Editing totals up to the last save
+    #[serde(default)]
+    pub stats: EditStats,
+    /// How the level file is laid out (see `save_level`)
+    #[serde(default)]
+    pub save_format: SaveFormat,
+}
+
+/// Editing totals across all sessions on a level
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct EditStats {
+    /// Seconds spent in the editor
+    #[serde(default)]
+    pub seconds: f64,
+    /// Undoable operations performed
+    #[serde(default)]
+    pub operations: u64,
+    /// Sectors added
+    #[serde(default)]
+    pub sectors_created: u64,
+    /// Editing sessions that were saved
+    #[serde(default)]
+    pub sessions: u32,
+}
+
+impl EditStats {
+    /// These totals plus another's
+    pub fn plus(&self, other: &EditStats) -> EditStats {
+        EditStats {
+            seconds: self.seconds + other.seconds,
+            operations: self.operations + other.operations,
+            sectors_created: self.sectors_created + other.sectors_created,
+            sessions: self.sessions + other.sessions,
+        }
+    }
+
+    /// Time spent as "1h 05m" (or "4m 30s" under an hour)
+    pub fn time_label(&self) -> String {
+        let total = self.seconds.max(0.0) as u64;
+        let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+        if hours > 0 {
+            format!("{}h {:02}m", hours, minutes)
+        } else {
+            format!("{}m {:02}s", minutes, seconds)
+        }
+    }
+}
+
+impl LevelMetadata {
+    /// Encode RGBA pixels as the thumbnail
+    pub fn set_thumbnail(&mut self, pixels: &[u8], width: usize, height: usize) -> Result<(), String> {
+        let img = image::RgbaImage::from_raw(width as u32, height as u32, pixels.to_vec())
+            .ok_or_else(|| format!("Buffer does not match {}x{}", width, height))?;
+        let mut png = Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageFormat::Png).map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+        self.thumbnail = Some(encode_base64(png.get_ref()));
+        Ok(())
+    }
+
+    /// Decode the thumbnail to RGBA pixels, with its width and height
+    pub fn thumbnail_pixels(&self) -> Option<(Vec<u8>, usize, usize)> {
+        let png = decode_base64(self.thumbnail.as_ref()?)?;
+        let img = image::load_from_memory_with_format(&png, image::ImageFormat::Png).ok()?.to_rgba8();
+        let (w, h) = img.dimensions();
+        Some((img.into_raw(), w as usize, h as usize))
+    }
+
+    /// Last save date as YYYY-MM-DD (UTC), if known
+    pub fn modified_date(&self) -> Option<String> {
+        if self.modified == 0 {
+            return None;
+        }
+        // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+        let days = (self.modified / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let doe = days - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + (month <= 2) as i64;
+        Some(format!("{:04}-{:02}-{:02}", year, month, day))
+    }
+}
+
+/// Just the metadata of a level file (everything else is skipped)
+#[derive(Deserialize)]
+struct LevelHeader {
+    #[serde(default)]
+    metadata: LevelMetadata,
+}
+
+/// Read a level file's metadata without building its rooms
+pub fn read_level_metadata<P: AsRef<Path>>(path: P) -> Result<LevelMetadata, LevelError> {
+    let contents = fs::read_to_string(path)?;
+    level_metadata_from_str(&contents)
+}
+
+/// Parse a level's metadata from RON
+pub fn level_metadata_from_str(s: &str) -> Result<LevelMetadata, LevelError> {
+    let header: LevelHeader = ron::from_str(s)?;
+    Ok(header.metadata)
+}
+
+/// Camera looking down at the whole level from above one corner, for thumbnails
+pub fn overview_camera(level: &Level) -> Camera {
+    let mut bounds: Option<Aabb> = None;
+    for b in level.rooms.iter().filter_map(room_world_bounds) {
+        let all = bounds.get_or_insert(b);
+        all.expand(b.min);
+        all.expand(b.max);
+    }
+    let bounds = bounds.unwrap_or_else(|| Aabb::new(Vec3::ZERO, Vec3::ZERO));
+    let center = bounds.center();
+    let distance = (bounds.max - bounds.min).len().max(2000.0) * 1.1;
+
+    // Same angles the level browser starts its orbit preview at
+    let (yaw, pitch) = (0.8f32, 0.5f32);
+    let offset = Vec3::new(pitch.cos() * yaw.sin(), pitch.sin(), pitch.cos() * yaw.cos());
+    let mut camera = Camera::new();
+    camera.position = center + offset.scale(distance);
+    camera.rotation_x = pitch;
+    camera.rotation_y = yaw + std::f32::consts::PI;
+    camera.update_basis();
+    camera
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 with padding
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_CHARS[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard base64 (whitespace is ignored), None if malformed
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(digits.len() / 4 * 3);
+    for chunk in digits.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        let mut n = 0u32;
+        for &b in &chunk[..4 - padding] {
+            let value = BASE64_CHARS.iter().position(|&c| c == b)? as u32;
+            n = (n << 6) | value;
+        }
+        n <<= 6 * padding as u32;
+        out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - padding]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        assert_eq!(encode_base64(b"Man"), "TWFu");
+        assert_eq!(encode_base64(b"Ma"), "TWE=");
+        assert_eq!(encode_base64(b"M"), "TQ==");
+        assert_eq!(decode_base64("TWE=").unwrap(), b"Ma");
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode_base64(&encode_base64(&data)).unwrap(), data);
+        assert!(decode_base64("TW!u").is_none());
+    }
+
+    #[test]
+    fn test_thumbnail_and_header_round_trip() {
+        let mut level = crate::world::create_test_level();
+        let pixels: Vec<u8> = (0..THUMBNAIL_WIDTH * THUMBNAIL_HEIGHT * 4).map(|i| (i % 251) as u8).collect();
+        level.metadata.set_thumbnail(&pixels, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT).unwrap();
+        level.metadata.modified = 1_700_000_000;
+        level.metadata.editor_version = "0.1.0".to_string();
+
+        let ron = ron::ser::to_string(&level).unwrap();
+        let metadata = level_metadata_from_str(&ron).unwrap();
+        assert_eq!(metadata, level.metadata);
+        assert_eq!(metadata.thumbnail_pixels().unwrap(), (pixels, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT));
+        assert_eq!(metadata.modified_date().as_deref(), Some("2023-11-14"));
+
+        // Levels saved before metadata existed still load
+        assert_eq!(level_metadata_from_str("(rooms: [])").unwrap(), LevelMetadata::default());
+    }
+
+    #[test]
+    fn test_edit_stats_accumulate() {
+        let saved = EditStats { seconds: 3000.0, operations: 40, sectors_created: 12, sessions: 2 };
+        let session = EditStats { seconds: 930.0, operations: 5, sectors_created: 3, sessions: 1 };
+        let total = saved.plus(&session);
+        assert_eq!((total.operations, total.sectors_created, total.sessions), (45, 15, 3));
+        assert_eq!(total.time_label(), "1h 05m");
+        assert_eq!(session.time_label(), "15m 30s");
+    }
+}
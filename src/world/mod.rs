@@ -4,9 +4,56 @@
 //! - Room-based geometry with portal connectivity
 //! - Visibility culling through portals
 //! - Tile-based collision detection
+//! - Entities, navigation, camera paths, events and per-room ambience
+//! - Editing operations (builder, block transforms, extrusion, macros, diff and merge)
+//! - Queries (ray casts, spatial index) and file metadata
+//! - Split levels whose room sectors are loaded on demand
 
 mod geometry;
 mod level;
+mod navigation;
+mod entity;
+mod camera_path;
+mod snapshot;
+mod builder;
+mod room_ops;
+mod extrude;
+mod raycast;
+mod spatial;
+mod diff;
+mod metadata;
+mod selection_set;
+mod uv_align;
+mod texture_variants;
+mod note;
+mod edit_macro;
+mod ambience;
+mod weather;
+mod shadow;
+mod room_audio;
+mod events;
+mod respawn;
+mod room_data;
 
 pub use geometry::*;
 pub use level::*;
+pub use navigation::*;
+pub use entity::*;
+pub use camera_path::*;
+pub use snapshot::*;
+pub use builder::*;
+pub use room_ops::*;
+pub use raycast::*;
+pub use spatial::*;
+pub use diff::*;
+pub use metadata::*;
+pub use selection_set::*;
+pub use texture_variants::*;
+pub use edit_macro::*;
+pub use ambience::*;
+pub use weather::*;
+pub use shadow::*;
+pub use room_audio::*;
+pub use events::*;
+pub use respawn::*;
+pub use room_data::*;
@@ -0,0 +1,480 @@
+//! Navigation - waypoint graphs and A* pathfinding
+//!
+//! AI navigation for play mode:
+//! - Waypoints are authored per room and may link across rooms
+//! - Patrol loops are chains of linked waypoints flagged as patrol nodes
+//! - A* runs over walkable sector centers plus waypoints
+//! - Walkers step up or down at most one click (the level's click height)
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::Vec3;
+use super::{Direction, Level, Room, SpatialIndex};
+
+/// Reference to a waypoint in a specific room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WaypointRef {
+    pub room: usize,
+    pub index: usize,
+}
+
+impl WaypointRef {
+    pub fn new(room: usize, index: usize) -> Self {
+        Self { room, index }
+    }
+}
+
+/// AI navigation node placed in a room
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Waypoint {
+    /// Room-relative position
+    pub position: Vec3,
+    /// Connected waypoints (links are kept symmetric, may cross rooms)
+    #[serde(default)]
+    pub links: Vec<WaypointRef>,
+    /// Part of a patrol loop
+    #[serde(default)]
+    pub patrol: bool,
+}
+
+impl Waypoint {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            links: Vec::new(),
+            patrol: false,
+        }
+    }
+}
+
+impl Level {
+    /// Get a waypoint by reference
+    pub fn waypoint(&self, r: WaypointRef) -> Option<&Waypoint> {
+        self.rooms.get(r.room)?.waypoints.get(r.index)
+    }
+
+    /// Get a waypoint mutably
+    pub fn waypoint_mut(&mut self, r: WaypointRef) -> Option<&mut Waypoint> {
+        self.rooms.get_mut(r.room)?.waypoints.get_mut(r.index)
+    }
+
+    /// World-space position of a waypoint
+    pub fn waypoint_world_pos(&self, r: WaypointRef) -> Option<Vec3> {
+        let room = self.rooms.get(r.room)?;
+        room.waypoints.get(r.index).map(|wp| room.position + wp.position)
+    }
+
+    /// Add a waypoint at a room-relative position
+    pub fn add_waypoint(&mut self, room: usize, position: Vec3) -> Option<WaypointRef> {
+        let room_ref = self.rooms.get_mut(room)?;
+        room_ref.waypoints.push(Waypoint::new(position));
+        Some(WaypointRef::new(room, room_ref.waypoints.len() - 1))
+    }
+
+    /// Connect or disconnect two waypoints; returns true if they are now linked
+    pub fn toggle_waypoint_link(&mut self, a: WaypointRef, b: WaypointRef) -> bool {
+        if a == b || self.waypoint(a).is_none() || self.waypoint(b).is_none() {
+            return false;
+        }
+
+        let linked = self.waypoint(a).is_some_and(|wp| wp.links.contains(&b));
+        if linked {
+            if let Some(wp) = self.waypoint_mut(a) {
+                wp.links.retain(|l| *l != b);
+            }
+            if let Some(wp) = self.waypoint_mut(b) {
+                wp.links.retain(|l| *l != a);
+            }
+            false
+        } else {
+            if let Some(wp) = self.waypoint_mut(a) {
+                wp.links.push(b);
+            }
+            if let Some(wp) = self.waypoint_mut(b) {
+                wp.links.push(a);
+            }
+            true
+        }
+    }
+
+    /// Remove a waypoint, dropping its links and fixing up indices in the same room
+    pub fn remove_waypoint(&mut self, r: WaypointRef) {
+        if self.waypoint(r).is_none() {
+            return;
+        }
+        self.rooms[r.room].waypoints.remove(r.index);
+
+        for room in &mut self.rooms {
+            for wp in &mut room.waypoints {
+                wp.links.retain(|l| *l != r);
+                for link in &mut wp.links {
+                    if link.room == r.room && link.index > r.index {
+                        link.index -= 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Follow patrol-flagged links from `start` until the loop closes or ends
+    pub fn patrol_route(&self, start: WaypointRef) -> Vec<WaypointRef> {
+        let mut route = Vec::new();
+        if !self.waypoint(start).is_some_and(|wp| wp.patrol) {
+            return route;
+        }
+
+        let mut current = start;
+        loop {
+            route.push(current);
+            let next = self.waypoint(current).and_then(|wp| {
+                wp.links.iter().copied().find(|l| {
+                    !route.contains(l) && self.waypoint(*l).is_some_and(|n| n.patrol)
+                })
+            });
+            match next {
+                Some(n) => current = n,
+                None => break,
+            }
+        }
+        route
+    }
+}
+
+/// What a navigation node stands for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NavNodeKind {
+    /// Center of a walkable sector
+    Sector { room: usize, x: usize, z: usize },
+    Waypoint(WaypointRef),
+}
+
+/// A node in the navigation graph (world-space position)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct NavNode {
+    pub position: Vec3,
+    pub kind: NavNodeKind,
+}
+
+/// Navigation graph built from a level's walkable sectors and waypoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NavGraph {
+    pub nodes: Vec<NavNode>,
+    /// Adjacency list (indices into `nodes`)
+    pub edges: Vec<Vec<usize>>,
+    sector_nodes: HashMap<(usize, usize, usize), usize>,
+}
+
+/// Open-set entry for A* (min-heap by estimated cost)
+#[derive(Clone, Copy)]
+struct OpenNode {
+    estimate: f32,
+    node: usize,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap pops the lowest estimate first
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// World-space center of a sector at floor level, if it is walkable
+fn walkable_center(room: &Room, x: usize, z: usize) -> Option<Vec3> {
+    let floor = room.get_sector(x, z)?.floor.as_ref()?;
+    if !floor.walkable || !floor.solid {
+        return None;
+    }
+    let corner = room.grid_to_world(x, z);
+    Some(Vec3::new(
+        corner.x + room.sector_size * 0.5,
+        corner.y + floor.avg_height(),
+        corner.z + room.sector_size * 0.5,
+    ))
+}
+
+/// Check if a solid wall on this sector edge is too tall to step over
+fn edge_blocked(room: &Room, x: usize, z: usize, dir: Direction, step_top: f32) -> bool {
+    room.get_sector(x, z).is_some_and(|s| {
+        s.walls(dir).iter().any(|w| w.solid && w.y_top() > step_top)
+    })
+}
+
+impl NavGraph {
+    /// Build the graph for a level
+    pub fn build(level: &Level) -> Self {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            sector_nodes: HashMap::new(),
+        };
+
+        // Sector nodes
+        for (room_idx, room) in level.rooms.iter().enumerate() {
+            for (x, z, _) in room.iter_sectors() {
+                if let Some(position) = walkable_center(room, x, z) {
+                    let id = graph.add_node(position, NavNodeKind::Sector { room: room_idx, x, z });
+                    graph.sector_nodes.insert((room_idx, x, z), id);
+                }
+            }
+        }
+
+        // Sector adjacency (within and across rooms), stepping at most one click
+        let max_step = level.click_height;
+        let index = SpatialIndex::build(level);
+        let sector_keys: Vec<_> = graph.sector_nodes.iter().map(|(k, v)| (*k, *v)).collect();
+        for ((room_idx, x, z), id) in sector_keys {
+            let room = &level.rooms[room_idx];
+            let from = graph.nodes[id].position;
+
+            for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                let (dx, dz) = dir.offset();
+                let target = Vec3::new(from.x + dx as f32 * room.sector_size, from.y, from.z + dz as f32 * room.sector_size);
+                let Some((other_room, ox, oz)) = Self::sector_at(level, &index, target, Some(room_idx)) else {
+                    continue;
+                };
+                let Some(&other_id) = graph.sector_nodes.get(&(other_room, ox, oz)) else {
+                    continue;
+                };
+
+                let to = graph.nodes[other_id].position;
+                if (to.y - from.y).abs() > max_step {
+                    continue;
+                }
+
+                // Walls are stored in room-relative heights
+                let step_top = from.y.max(to.y) + max_step;
+                if edge_blocked(room, x, z, dir, step_top - room.position.y)
+                    || edge_blocked(&level.rooms[other_room], ox, oz, dir.opposite(), step_top - level.rooms[other_room].position.y)
+                {
+                    continue;
+                }
+
+                graph.connect(id, other_id);
+            }
+        }
+
+        // Waypoint nodes: linked to each other and to the sector they stand in
+        let mut waypoint_nodes = HashMap::new();
+        for (room_idx, room) in level.rooms.iter().enumerate() {
+            for (index, wp) in room.waypoints.iter().enumerate() {
+                let r = WaypointRef::new(room_idx, index);
+                let position = room.position + wp.position;
+                let id = graph.add_node(position, NavNodeKind::Waypoint(r));
+                waypoint_nodes.insert(r, id);
+
+                if let Some((gx, gz)) = room.world_to_grid(position.x, position.z) {
+                    if let Some(&sector_id) = graph.sector_nodes.get(&(room_idx, gx, gz)) {
+                        graph.connect(id, sector_id);
+                    }
+                }
+            }
+        }
+        for (room_idx, room) in level.rooms.iter().enumerate() {
+            for (index, wp) in room.waypoints.iter().enumerate() {
+                let id = waypoint_nodes[&WaypointRef::new(room_idx, index)];
+                for link in &wp.links {
+                    if let Some(&other) = waypoint_nodes.get(link) {
+                        graph.connect(id, other);
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn add_node(&mut self, position: Vec3, kind: NavNodeKind) -> usize {
+        self.nodes.push(NavNode { position, kind });
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    fn connect(&mut self, a: usize, b: usize) {
+        if !self.edges[a].contains(&b) {
+            self.edges[a].push(b);
+        }
+        if !self.edges[b].contains(&a) {
+            self.edges[b].push(a);
+        }
+    }
+
+    /// Find the sector under a world position (checks `hint` room first)
+    fn sector_at(level: &Level, index: &SpatialIndex, pos: Vec3, hint: Option<usize>) -> Option<(usize, usize, usize)> {
+        let hinted = hint.into_iter().chain(index.rooms_at(pos.x, pos.z));
+        let mut best: Option<((usize, usize, usize), f32)> = None;
+        for room_idx in hinted {
+            let room = &level.rooms[room_idx];
+            let Some((gx, gz)) = room.world_to_grid(pos.x, pos.z) else { continue };
+            let Some(center) = walkable_center(room, gx, gz) else { continue };
+            // Prefer the floor closest in height (stacked rooms)
+            let dy = (center.y - pos.y).abs();
+            if best.is_none_or(|(_, d)| dy < d) {
+                best = Some(((room_idx, gx, gz), dy));
+            }
+        }
+        best.map(|(key, _)| key)
+    }
+
+    /// Nearest graph node to a world position
+    pub fn nearest_node(&self, pos: Vec3) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i, (n.position - pos).len()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// A* search between two nodes; returns node indices from start to goal
+    pub fn find_path_nodes(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        let goal_pos = self.nodes.get(goal)?.position;
+        self.nodes.get(start)?;
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut cost = vec![f32::INFINITY; self.nodes.len()];
+
+        cost[start] = 0.0;
+        open.push(OpenNode { estimate: (self.nodes[start].position - goal_pos).len(), node: start });
+
+        while let Some(OpenNode { node, .. }) = open.pop() {
+            if node == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while let Some(&prev) = came_from.get(&current) {
+                    path.push(prev);
+                    current = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for &next in &self.edges[node] {
+                let step = (self.nodes[next].position - self.nodes[node].position).len();
+                let new_cost = cost[node] + step;
+                if new_cost < cost[next] {
+                    cost[next] = new_cost;
+                    came_from.insert(next, node);
+                    let estimate = new_cost + (self.nodes[next].position - goal_pos).len();
+                    open.push(OpenNode { estimate, node: next });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find a world-space path between two points
+    pub fn find_path(&self, from: Vec3, to: Vec3) -> Option<Vec<Vec3>> {
+        let start = self.nearest_node(from)?;
+        let goal = self.nearest_node(to)?;
+        let nodes = self.find_path_nodes(start, goal)?;
+        Some(nodes.into_iter().map(|i| self.nodes[i].position).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::TextureRef;
+
+    fn corridor(len: usize) -> Level {
+        let mut level = Level::new();
+        let mut room = Room::new(0, Vec3::ZERO, len, 1);
+        for x in 0..len {
+            room.set_floor(x, 0, 0.0, TextureRef::none());
+        }
+        level.add_room(room);
+        level
+    }
+
+    #[test]
+    fn test_path_along_corridor() {
+        let level = corridor(4);
+        let graph = NavGraph::build(&level);
+        let path = graph.find_path(Vec3::new(100.0, 0.0, 500.0), Vec3::new(4000.0, 0.0, 500.0)).unwrap();
+        assert_eq!(path.len(), 4);
+    }
+
+    #[test]
+    fn test_wall_blocks_path() {
+        let mut level = corridor(3);
+        level.rooms[0].add_wall(1, 0, Direction::East, 0.0, 1024.0, TextureRef::none());
+        let graph = NavGraph::build(&level);
+        let a = graph.sector_nodes[&(0, 0, 0)];
+        let b = graph.sector_nodes[&(0, 2, 0)];
+        assert!(graph.find_path_nodes(a, b).is_none());
+    }
+
+    #[test]
+    fn test_cross_room_path() {
+        let mut level = corridor(2);
+        let mut room1 = Room::new(1, Vec3::new(2048.0, 0.0, 0.0), 2, 1);
+        room1.set_floor(0, 0, 128.0, TextureRef::none());
+        room1.set_floor(1, 0, 128.0, TextureRef::none());
+        level.add_room(room1);
+
+        let graph = NavGraph::build(&level);
+        let a = graph.sector_nodes[&(0, 0, 0)];
+        let b = graph.sector_nodes[&(1, 1, 0)];
+        assert_eq!(graph.find_path_nodes(a, b).map(|p| p.len()), Some(4));
+    }
+
+    #[test]
+    fn test_step_height_follows_click_height() {
+        let mut level = corridor(2);
+        level.rooms[0].set_floor(1, 0, 128.0, TextureRef::none());
+        let (a, b) = ((0, 0, 0), (0, 1, 0));
+        let graph = NavGraph::build(&level);
+        assert!(graph.find_path_nodes(graph.sector_nodes[&a], graph.sector_nodes[&b]).is_some());
+
+        level.click_height = 64.0;
+        let graph = NavGraph::build(&level);
+        assert!(graph.find_path_nodes(graph.sector_nodes[&a], graph.sector_nodes[&b]).is_none());
+    }
+
+    #[test]
+    fn test_waypoint_removal_fixes_links() {
+        let mut level = corridor(1);
+        let a = level.add_waypoint(0, Vec3::new(100.0, 0.0, 100.0)).unwrap();
+        let b = level.add_waypoint(0, Vec3::new(200.0, 0.0, 100.0)).unwrap();
+        let c = level.add_waypoint(0, Vec3::new(300.0, 0.0, 100.0)).unwrap();
+        level.toggle_waypoint_link(a, b);
+        level.toggle_waypoint_link(b, c);
+
+        level.remove_waypoint(a);
+        assert_eq!(level.rooms[0].waypoints.len(), 2);
+        assert_eq!(level.rooms[0].waypoints[0].links, vec![WaypointRef::new(0, 1)]);
+        assert_eq!(level.rooms[0].waypoints[1].links, vec![WaypointRef::new(0, 0)]);
+    }
+
+    #[test]
+    fn test_patrol_route_follows_flagged_nodes() {
+        let mut level = corridor(1);
+        let refs: Vec<_> = (0..3)
+            .map(|i| level.add_waypoint(0, Vec3::new(100.0 * i as f32, 0.0, 0.0)).unwrap())
+            .collect();
+        level.toggle_waypoint_link(refs[0], refs[1]);
+        level.toggle_waypoint_link(refs[1], refs[2]);
+        level.toggle_waypoint_link(refs[2], refs[0]);
+        for r in &refs {
+            level.waypoint_mut(*r).unwrap().patrol = true;
+        }
+        assert_eq!(level.patrol_route(refs[0]).len(), 3);
+    }
+}
@@ -0,0 +1,72 @@
+//! Designer notes
+//!
+//! Sticky notes left in a level ("needs a key here", "lighting TODO"):
+//! - plain entities of the `note` archetype, so they're placed, moved, saved
+//!   and undone like any other entity
+//! - the schema gives them a `text` and a `color` choice
+//! - only the editor draws them; the game has no behavior for the archetype
+//!   and never renders entities
+
+use super::{Entity, EntityRef, Level};
+
+/// Archetype name of designer notes
+pub const NOTE_ARCHETYPE: &str = "note";
+
+/// Color choices of a note, in the order the schema lists them
+pub const NOTE_COLORS: [(&str, (u8, u8, u8)); 5] = [
+    ("yellow", (255, 225, 90)),
+    ("red", (240, 90, 80)),
+    ("green", (110, 220, 120)),
+    ("blue", (100, 170, 255)),
+    ("white", (235, 235, 235)),
+];
+
+impl Entity {
+    /// Whether this entity is a designer note
+    pub fn is_note(&self) -> bool {
+        self.archetype == NOTE_ARCHETYPE
+    }
+
+    /// Text of a note (empty if it has none)
+    pub fn note_text(&self) -> &str {
+        self.get("text").and_then(|v| v.as_str()).unwrap_or("")
+    }
+
+    /// Color of a note, yellow if its choice is unknown
+    pub fn note_color(&self) -> (u8, u8, u8) {
+        let name = self.get("color").and_then(|v| v.as_str()).unwrap_or("");
+        NOTE_COLORS.iter().find(|(n, _)| *n == name).unwrap_or(&NOTE_COLORS[0]).1
+    }
+}
+
+impl Level {
+    /// Every designer note in the level
+    pub fn notes(&self) -> impl Iterator<Item = (EntityRef, &Entity)> {
+        self.entities_of(NOTE_ARCHETYPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{create_test_level, PropertyValue};
+
+    #[test]
+    fn test_notes_carry_text_and_color() {
+        let mut level = create_test_level();
+        let mut note = Entity {
+            archetype: NOTE_ARCHETYPE.to_string(),
+            position: Vec3::new(512.0, 0.0, 512.0),
+            properties: Default::default(),
+        };
+        assert_eq!((note.note_text(), note.note_color()), ("", NOTE_COLORS[0].1));
+        note.set("text", PropertyValue::Text("needs a key".to_string()));
+        note.set("color", PropertyValue::Text("blue".to_string()));
+        level.add_entity(0, note);
+
+        let (_, note) = level.notes().next().unwrap();
+        assert_eq!(note.note_text(), "needs a key");
+        assert_eq!(note.note_color(), (100, 170, 255));
+    }
+}
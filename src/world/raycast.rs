@@ -0,0 +1,295 @@
+//! Ray casting against level geometry
+//!
+//! `raycast` walks each room's sector grid cell by cell along the ray (2D DDA
+//! in X/Z), so only the sectors the ray actually crosses are tested:
+//! - editor: placing things on the surface under the cursor
+//! - gameplay: line of sight (`line_of_sight`) and hitscan attacks
+//!
+//! Faces are hit from either side. Portals have no geometry and let rays through.
+//! Gameplay queries (`raycast_solid`, `line_of_sight`) also pass through faces
+//! whose `solid` flag is off; editor queries hit every face.
+//! For many queries against a big level use `SpatialIndex::raycast`, which
+//! skips rooms away from the ray.
+
+use crate::rasterizer::{ray_triangle_intersect, Vec3};
+use super::{room_world_bounds, Direction, Level, Room, Sector};
+
+/// Which face of a sector was hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitFace {
+    Floor,
+    Ceiling,
+    /// Wall on a sector edge, with its index in that edge's wall stack
+    Wall(Direction, usize),
+}
+
+/// Result of a ray cast
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub room: usize,
+    /// Sector grid coordinates (x, z) within the room
+    pub sector: (usize, usize),
+    pub face: HitFace,
+    /// World-space hit point
+    pub point: Vec3,
+    /// Face normal, flipped to point back towards the ray origin
+    pub normal: Vec3,
+    /// Distance from the ray origin
+    pub distance: f32,
+}
+
+/// Cast a ray and return the nearest hit (`dir` doesn't need to be normalized)
+pub fn raycast(level: &Level, origin: Vec3, dir: Vec3) -> Option<Hit> {
+    raycast_max(level, origin, dir, f32::MAX)
+}
+
+/// Cast a ray, ignoring anything further than `max_distance`
+pub fn raycast_max(level: &Level, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<Hit> {
+    raycast_level(level, origin, dir, max_distance, false)
+}
+
+/// `raycast_max` for gameplay: faces that aren't solid let the ray through
+pub fn raycast_solid(level: &Level, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<Hit> {
+    raycast_level(level, origin, dir, max_distance, true)
+}
+
+/// A ray and what it may hit (`dir` normalized)
+#[derive(Clone, Copy)]
+struct Ray {
+    origin: Vec3,
+    dir: Vec3,
+    max_distance: f32,
+    /// Pass through faces that aren't solid
+    solid_only: bool,
+}
+
+impl Ray {
+    /// The same ray, ignoring anything further than `limit`
+    fn until(self, limit: f32) -> Self {
+        Self { max_distance: limit, ..self }
+    }
+}
+
+fn raycast_level(level: &Level, origin: Vec3, dir: Vec3, max_distance: f32, solid_only: bool) -> Option<Hit> {
+    if dir.len() < 1e-6 {
+        return None;
+    }
+    let ray = Ray { origin, dir: dir.normalize(), max_distance, solid_only };
+    let mut best: Option<Hit> = None;
+    for (room_idx, room) in level.rooms.iter().enumerate() {
+        let limit = best.map_or(max_distance, |h| h.distance);
+        let Some(bounds) = room_world_bounds(room) else { continue };
+        if bounds.ray_intersect(origin, ray.dir).is_none_or(|t| t > limit) {
+            continue;
+        }
+        if let Some(hit) = raycast_room_with(room, room_idx, ray.until(limit)) {
+            best = Some(hit);
+        }
+    }
+    best
+}
+
+/// Whether nothing blocks the straight line between two points
+pub fn line_of_sight(level: &Level, from: Vec3, to: Vec3) -> bool {
+    let delta = to - from;
+    // Stop just short of the target so a point resting on a surface still sees out
+    raycast_solid(level, from, delta, delta.len() - 1.0).is_none()
+}
+
+/// Cast a ray through one room's sector grid (`dir` must be normalized)
+pub fn raycast_room(room: &Room, room_idx: usize, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<Hit> {
+    raycast_room_with(room, room_idx, Ray { origin, dir, max_distance, solid_only: false })
+}
+
+fn raycast_room_with(room: &Room, room_idx: usize, ray: Ray) -> Option<Hit> {
+    let local = ray.origin - room.position;
+    let mut best: Option<Hit> = None;
+    walk_grid((local.x, local.z), ray.dir, room.sector_size, (room.width, room.depth), ray.max_distance, |gx, gz, cell_exit| {
+        if let Some(sector) = room.get_sector(gx, gz) {
+            let limit = best.map_or(ray.max_distance, |h| h.distance);
+            if let Some(hit) = raycast_sector(room, room_idx, (gx, gz), sector, ray.until(limit)) {
+                best = Some(hit);
+            }
+        }
+        // Walls sit on cell edges, so a hit just past this cell can still be the nearest
+        best.is_some_and(|h| h.distance <= cell_exit + 1.0)
+    });
+    best
+}
+
+/// Visit the cells of a `cols` x `rows` grid in X/Z that a ray crosses, in order (2D DDA).
+///
+/// `start` is the ray origin relative to the grid corner. `visit` gets the cell
+/// and the distance where the ray leaves it, and returns true to stop.
+pub(super) fn walk_grid(
+    start: (f32, f32),
+    dir: Vec3,
+    cell_size: f32,
+    (cols, rows): (usize, usize),
+    max_distance: f32,
+    mut visit: impl FnMut(usize, usize, f32) -> bool,
+) {
+    if cols == 0 || rows == 0 {
+        return;
+    }
+    // Clip the ray to the grid
+    let (ox, oz) = start;
+    let (mut t_min, mut t_max) = (0.0f32, max_distance);
+    for (o, d, size) in [(ox, dir.x, cols as f32 * cell_size), (oz, dir.z, rows as f32 * cell_size)] {
+        if d.abs() < 1e-9 {
+            if o < 0.0 || o > size {
+                return;
+            }
+        } else {
+            let (t0, t1) = (-o / d, (size - o) / d);
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+    }
+    if t_min > t_max {
+        return;
+    }
+
+    // Start in the cell where the ray enters
+    let mut cell_x = (((ox + dir.x * t_min) / cell_size) as i32).clamp(0, cols as i32 - 1);
+    let mut cell_z = (((oz + dir.z * t_min) / cell_size) as i32).clamp(0, rows as i32 - 1);
+    let step_x = if dir.x > 0.0 { 1 } else { -1 };
+    let step_z = if dir.z > 0.0 { 1 } else { -1 };
+    let next_boundary = |cell: i32, step: i32| (cell + if step > 0 { 1 } else { 0 }) as f32 * cell_size;
+    let delta_x = if dir.x.abs() < 1e-9 { f32::MAX } else { cell_size / dir.x.abs() };
+    let delta_z = if dir.z.abs() < 1e-9 { f32::MAX } else { cell_size / dir.z.abs() };
+    let mut t_next_x = if dir.x.abs() < 1e-9 { f32::MAX } else { (next_boundary(cell_x, step_x) - ox) / dir.x };
+    let mut t_next_z = if dir.z.abs() < 1e-9 { f32::MAX } else { (next_boundary(cell_z, step_z) - oz) / dir.z };
+
+    loop {
+        let cell_exit = t_next_x.min(t_next_z).min(t_max);
+        if visit(cell_x as usize, cell_z as usize, cell_exit) || cell_exit >= t_max {
+            return;
+        }
+        if t_next_x < t_next_z {
+            cell_x += step_x;
+            t_next_x += delta_x;
+        } else {
+            cell_z += step_z;
+            t_next_z += delta_z;
+        }
+        if cell_x < 0 || cell_z < 0 || cell_x >= cols as i32 || cell_z >= rows as i32 {
+            return;
+        }
+    }
+}
+
+/// Test every face of one sector
+fn raycast_sector(room: &Room, room_idx: usize, (gx, gz): (usize, usize), sector: &Sector, ray: Ray) -> Option<Hit> {
+    let Ray { origin, dir, max_distance, solid_only } = ray;
+    let size = room.sector_size;
+    let x0 = room.position.x + gx as f32 * size;
+    let z0 = room.position.z + gz as f32 * size;
+    let (x1, z1) = (x0 + size, z0 + size);
+    let horizontal = |h: [f32; 4]| [
+        Vec3::new(x0, h[0], z0),
+        Vec3::new(x1, h[1], z0),
+        Vec3::new(x1, h[2], z1),
+        Vec3::new(x0, h[3], z1),
+    ];
+    let vertical = |h: [f32; 4], (ax, az): (f32, f32), (bx, bz): (f32, f32)| [
+        Vec3::new(ax, h[0], az),
+        Vec3::new(bx, h[1], bz),
+        Vec3::new(bx, h[2], bz),
+        Vec3::new(ax, h[3], az),
+    ];
+
+    let mut quads: Vec<(HitFace, [Vec3; 4])> = Vec::new();
+    if let Some(floor) = sector.floor.as_ref().filter(|f| f.solid || !solid_only) {
+        quads.push((HitFace::Floor, horizontal(floor.heights)));
+    }
+    if let Some(ceiling) = sector.ceiling.as_ref().filter(|c| c.solid || !solid_only) {
+        quads.push((HitFace::Ceiling, horizontal(ceiling.heights)));
+    }
+    let edges = [
+        (Direction::North, (x0, z0), (x1, z0)),
+        (Direction::East, (x1, z0), (x1, z1)),
+        (Direction::South, (x1, z1), (x0, z1)),
+        (Direction::West, (x0, z1), (x0, z0)),
+    ];
+    for (direction, a, b) in edges {
+        for (i, wall) in sector.walls(direction).iter().enumerate().filter(|(_, w)| w.solid || !solid_only) {
+            quads.push((HitFace::Wall(direction, i), vertical(wall.heights, a, b)));
+        }
+    }
+
+    let mut best: Option<Hit> = None;
+    for (face, c) in quads {
+        for [a, b, d] in [[c[0], c[1], c[2]], [c[0], c[2], c[3]]] {
+            let Some(t) = ray_triangle_intersect(origin, dir, a, b, d) else { continue };
+            if t > max_distance || best.is_some_and(|h| h.distance <= t) {
+                continue;
+            }
+            let mut normal = (b - a).cross(d - a).normalize();
+            if normal.dot(dir) > 0.0 {
+                normal = normal.scale(-1.0);
+            }
+            best = Some(Hit { room: room_idx, sector: (gx, gz), face, point: origin + dir.scale(t), normal, distance: t });
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, LevelBuilder, RoomBuilder};
+
+    #[test]
+    fn test_ray_down_hits_floor() {
+        let level = create_test_level();
+        let hit = raycast(&level, Vec3::new(512.0, 500.0, 512.0), Vec3::new(0.0, -1.0, 0.0)).unwrap();
+        assert_eq!(hit.face, HitFace::Floor);
+        assert_eq!(hit.sector, (0, 0));
+        assert!((hit.distance - 500.0).abs() < 0.01);
+        assert!((hit.normal.y - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_ray_hits_wall_from_inside_and_outside() {
+        let level = create_test_level();
+        let inside = raycast(&level, Vec3::new(512.0, 512.0, 512.0), Vec3::new(1.0, 0.0, 0.0)).unwrap();
+        assert_eq!(inside.face, HitFace::Wall(Direction::East, 0));
+        assert!((inside.point.x - 1024.0).abs() < 0.01);
+        assert!(inside.normal.x < 0.0);
+
+        let outside = raycast(&level, Vec3::new(-500.0, 512.0, 512.0), Vec3::new(1.0, 0.0, 0.0)).unwrap();
+        assert_eq!(outside.face, HitFace::Wall(Direction::West, 0));
+        assert!((outside.distance - 500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_dda_walks_long_rooms() {
+        // 8x1 corridor with a floor: a shallow ray lands in the far sector
+        let room = (0..8).fold(RoomBuilder::new(8, 1), |room, x| room.floor(x, 0, 0.0));
+        let level = LevelBuilder::new().room(room).build();
+        let hit = raycast(&level, Vec3::new(100.0, 100.0, 512.0), Vec3::new(7000.0, -100.0, 0.0)).unwrap();
+        assert_eq!(hit.sector, (6, 0));
+        assert_eq!(hit.face, HitFace::Floor);
+        assert!(raycast(&level, Vec3::new(100.0, 100.0, 512.0), Vec3::new(0.0, 1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let level = create_test_level();
+        assert!(line_of_sight(&level, Vec3::new(100.0, 500.0, 100.0), Vec3::new(900.0, 500.0, 900.0)));
+        assert!(!line_of_sight(&level, Vec3::new(512.0, 500.0, 512.0), Vec3::new(2000.0, 500.0, 512.0)));
+    }
+
+    #[test]
+    fn test_passable_faces_only_stop_editor_rays() {
+        let mut level = create_test_level();
+        let (from, to) = (Vec3::new(512.0, 512.0, 512.0), Vec3::new(2000.0, 512.0, 512.0));
+        for wall in &mut level.rooms[0].get_sector_mut(0, 0).unwrap().walls_east {
+            wall.solid = false;
+        }
+        assert!(line_of_sight(&level, from, to));
+        assert!(raycast_solid(&level, from, to - from, f32::MAX).is_none());
+        assert_eq!(raycast(&level, from, to - from).unwrap().face, HitFace::Wall(Direction::East, 0));
+    }
+}
@@ -0,0 +1,26 @@
+//! Respawn rules
+//!
+//! What play mode puts back when the player dies and comes back at the last
+//! checkpoint (see `game::Simulation`), set per level in the Room panel:
+//! - enemies: waves spawned by level events despawn and the event timeline
+//!   starts over
+//! - doors and platforms: back where they were placed
+//! - how long the fade to black (and back) takes
+
+use serde::{Deserialize, Serialize};
+
+/// How a level resets on respawn
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RespawnRules {
+    pub reset_enemies: bool,
+    pub reset_doors: bool,
+    /// Seconds to fade out, and again to fade back in
+    pub fade: f32,
+}
+
+impl Default for RespawnRules {
+    fn default() -> Self {
+        Self { reset_enemies: true, reset_doors: false, fade: 1.0 }
+    }
+}
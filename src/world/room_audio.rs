@@ -0,0 +1,51 @@
+//! Room audio
+//!
+//! What a room sounds like while the camera is in it (play mode):
+//! - an ambient loop (wind, drips, machinery): a WAV file played looped
+//! - a reverb preset (small stone room, cathedral, outdoors) applied to the
+//!   whole audio mix
+//!
+//! Only the settings live here; `game::RoomAudioPlayer` plays them.
+
+use serde::{Deserialize, Serialize};
+
+/// Room acoustics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReverbPreset {
+    /// Dry
+    #[default]
+    None,
+    SmallStone,
+    Cathedral,
+    Outdoors,
+}
+
+impl ReverbPreset {
+    pub const ALL: [ReverbPreset; 4] = [ReverbPreset::None, ReverbPreset::SmallStone, ReverbPreset::Cathedral, ReverbPreset::Outdoors];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReverbPreset::None => "None",
+            ReverbPreset::SmallStone => "Small stone room",
+            ReverbPreset::Cathedral => "Cathedral",
+            ReverbPreset::Outdoors => "Outdoors",
+        }
+    }
+}
+
+/// Audio settings of a room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoomAudio {
+    /// Path of the looped ambient WAV (None = silence)
+    pub ambient_loop: Option<String>,
+    /// Loop volume, 0.0 - 1.0
+    pub volume: f32,
+    pub reverb: ReverbPreset,
+}
+
+impl Default for RoomAudio {
+    fn default() -> Self {
+        Self { ambient_loop: None, volume: 0.7, reverb: ReverbPreset::None }
+    }
+}
@@ -0,0 +1,197 @@
+//! Split levels - room sectors stored apart from the level file
+//!
+//! Exported levels are split so the game only holds the sectors of rooms near
+//! the player (see `game::RoomStreamer`):
+//! - the level file keeps every room's metadata (position, size, portals,
+//!   entities, waypoints...) plus what can't be worked out without the
+//!   sectors: room bounds, the textures in use and the navigation graph
+//! - each room's sector grid goes to its own file, `<level>.rooms/<index>.ron`
+//!
+//! `load_level` reads a split level back in whole, so the editor and tools
+//! never see one; the game parses the level file alone and loads rooms as
+//! they come into range.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use super::{Aabb, Direction, Level, LevelError, NavGraph, Sector, TextureRef};
+
+/// Sectors of one room, indexed [x][z]
+pub type SectorGrid = Vec<Vec<Option<Sector>>>;
+
+/// What a split level keeps in place of its rooms' sectors
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamedRooms {
+    /// Bounds of each room, which need the sectors to compute
+    pub bounds: Vec<Aabb>,
+    /// Every texture the rooms' faces use, in first-use order
+    pub textures: Vec<TextureRef>,
+    /// Navigation graph of the whole level
+    pub nav: NavGraph,
+    /// Rooms whose sectors are in memory
+    #[serde(skip)]
+    pub loaded: HashSet<usize>,
+}
+
+/// Directory holding the room files of the level at `level_path`
+pub fn room_data_dir(level_path: &Path) -> PathBuf {
+    let stem = level_path.file_stem().unwrap_or_default().to_string_lossy();
+    level_path.with_file_name(format!("{}.rooms", stem))
+}
+
+/// File holding the sectors of one room of the level at `level_path`
+pub fn room_data_path(level_path: &Path, room: usize) -> PathBuf {
+    room_data_dir(level_path).join(format!("{}.ron", room))
+}
+
+/// Parse one room file
+pub fn room_data_from_str(s: &str) -> Result<SectorGrid, LevelError> {
+    Ok(ron::from_str(s)?)
+}
+
+/// Read one room file
+pub fn load_room_data(path: &Path) -> Result<SectorGrid, LevelError> {
+    room_data_from_str(&fs::read_to_string(path)?)
+}
+
+/// Take every room's sectors out of `level`, keeping what the game needs to
+/// run without them. Returns the sectors by room index.
+pub fn split_rooms(level: &mut Level) -> Vec<SectorGrid> {
+    level.apply_metrics();
+    let streamed = StreamedRooms {
+        bounds: level.rooms.iter().map(|r| r.bounds).collect(),
+        textures: level.used_textures(),
+        nav: NavGraph::build(level),
+        loaded: HashSet::new(),
+    };
+    level.streamed = Some(streamed);
+    level.rooms.iter_mut().map(|r| std::mem::take(&mut r.sectors)).collect()
+}
+
+/// Split `level` and write it to `path`, with its room files next to it
+pub fn save_split_level(level: &Level, path: &Path) -> Result<(), LevelError> {
+    let mut level = level.clone();
+    let rooms = split_rooms(&mut level);
+    let dir = room_data_dir(path);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    for (index, sectors) in rooms.iter().enumerate() {
+        fs::write(room_data_path(path, index), ron::to_string(sectors)?)?;
+    }
+    super::save_level(&level, path)
+}
+
+/// Read every room file of a split level loaded from `path`, leaving an
+/// ordinary level
+pub(super) fn merge_rooms(level: &mut Level, path: &Path) -> Result<(), LevelError> {
+    for index in 0..level.rooms.len() {
+        if !level.is_room_loaded(index) {
+            level.rooms[index].sectors = load_room_data(&room_data_path(path, index))?;
+        }
+    }
+    level.streamed = None;
+    Ok(())
+}
+
+impl Level {
+    /// Whether a room's sectors are in memory (always, unless the level is split)
+    pub fn is_room_loaded(&self, room: usize) -> bool {
+        self.streamed.as_ref().is_none_or(|s| s.loaded.contains(&room))
+    }
+
+    /// Give a split room its sectors back
+    pub fn load_room(&mut self, room: usize, sectors: SectorGrid) {
+        let (Some(streamed), Some(target)) = (self.streamed.as_mut(), self.rooms.get_mut(room)) else {
+            return;
+        };
+        target.sectors = sectors;
+        streamed.loaded.insert(room);
+    }
+
+    /// Drop a split room's sectors (rooms of unsplit levels are left alone)
+    pub fn unload_room(&mut self, room: usize) {
+        let (Some(streamed), Some(target)) = (self.streamed.as_mut(), self.rooms.get_mut(room)) else {
+            return;
+        };
+        if streamed.loaded.remove(&room) {
+            target.sectors = Vec::new();
+        }
+    }
+
+    /// Every texture the level's faces use, in first-use order
+    pub fn used_textures(&self) -> Vec<TextureRef> {
+        if let Some(streamed) = &self.streamed {
+            return streamed.textures.clone();
+        }
+        let mut seen = HashSet::new();
+        let mut textures = Vec::new();
+        for room in &self.rooms {
+            for (_, _, sector) in room.iter_sectors() {
+                let horizontal = sector.floor.iter().chain(&sector.ceiling).map(|f| &f.texture);
+                let walls = [Direction::North, Direction::East, Direction::South, Direction::West]
+                    .into_iter()
+                    .flat_map(|d| sector.walls(d).iter().map(|w| &w.texture));
+                for tex in horizontal.chain(walls) {
+                    if seen.insert((tex.pack.as_str(), tex.name.as_str())) {
+                        textures.push(tex.clone());
+                    }
+                }
+            }
+        }
+        textures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, load_level, load_level_from_str, level_to_string};
+
+    #[test]
+    fn test_room_files_sit_next_to_the_level() {
+        let path = Path::new("export/assets/levels/level_001.ron");
+        assert_eq!(room_data_path(path, 3), Path::new("export/assets/levels/level_001.rooms/3.ron"));
+    }
+
+    #[test]
+    fn test_split_level_keeps_metadata_only() {
+        let level = create_test_level();
+        let mut split = level.clone();
+        let rooms = split_rooms(&mut split);
+        assert_eq!(rooms[0], level.rooms[0].sectors);
+
+        let reloaded = load_level_from_str(&level_to_string(&split).unwrap()).unwrap();
+        assert!(reloaded.rooms[0].sectors.is_empty());
+        assert!(!reloaded.is_room_loaded(0));
+        assert_eq!(reloaded.rooms[0].bounds, level.rooms[0].bounds);
+        assert_eq!(reloaded.used_textures(), level.used_textures());
+        assert_eq!(reloaded.streamed.unwrap().nav.nodes.len(), NavGraph::build(&level).nodes.len());
+    }
+
+    #[test]
+    fn test_rooms_load_and_unload() {
+        let mut level = create_test_level();
+        let sectors = split_rooms(&mut level).remove(0);
+        level.load_room(0, sectors.clone());
+        assert!(level.is_room_loaded(0));
+        assert_eq!(level.rooms[0].sectors, sectors);
+        level.unload_room(0);
+        assert!(!level.is_room_loaded(0));
+        assert!(level.rooms[0].sectors.is_empty());
+    }
+
+    #[test]
+    fn test_split_level_loads_whole() {
+        let dir = std::env::temp_dir().join(format!("bonnie_split_{}", std::process::id()));
+        let path = dir.join("level.ron");
+        let level = create_test_level();
+        save_split_level(&level, &path).unwrap();
+        let loaded = load_level(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(loaded.streamed.is_none());
+        assert_eq!(loaded.rooms[0].sectors, level.rooms[0].sectors);
+    }
+}
@@ -0,0 +1,804 @@
+//! Room editing operations on blocks of sectors
+//!
+//! - Move a block of sectors within a room
+//! - Rotate (90°) or mirror a block in place
+//! - Merge two rooms into one / split a rectangle off into a new room
+//! - Grow a room's grid to take in cells outside it, or trim its empty borders
+//! - Rotate or mirror a whole room, with its portals, waypoints and entities
+//! - Roughen floors with seeded per-corner height noise
+//!
+//! Transforms remap wall directions and per-corner heights so sloped floors
+//! and walls keep their shape. Corners are indexed clockwise from the top
+//! (NW, NE, SE, SW), and a wall on edge `d` runs from corner `d` (left) to
+//! corner `d + 1` (right), so rotating only re-indexes corners while mirroring
+//! also swaps each wall's left and right.
+
+use std::collections::HashMap;
+use crate::rasterizer::Vec3;
+use super::{Direction, Entity, HorizontalFace, Level, Portal, Room, Sector, VerticalFace, WaypointRef};
+
+/// Portal height used when neither side of a split edge has a ceiling
+const OPEN_PORTAL_HEIGHT: f32 = 2048.0;
+
+/// Largest grid (in sectors, per axis) a room may grow to while editing
+pub const MAX_ROOM_SIZE: usize = 64;
+
+/// Rigid transform applied to a block of sectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectorTransform {
+    /// 90° clockwise, seen from above (north becomes east)
+    RotateCw,
+    /// 90° counter-clockwise, seen from above (north becomes west)
+    RotateCcw,
+    /// Flip east <-> west
+    MirrorX,
+    /// Flip north <-> south
+    MirrorZ,
+}
+
+impl SectorTransform {
+    pub fn label(self) -> &'static str {
+        match self {
+            SectorTransform::RotateCw => "Rotated 90° clockwise",
+            SectorTransform::RotateCcw => "Rotated 90° counter-clockwise",
+            SectorTransform::MirrorX => "Mirrored east-west",
+            SectorTransform::MirrorZ => "Mirrored north-south",
+        }
+    }
+
+    /// Where a direction ends up
+    pub fn direction(self, dir: Direction) -> Direction {
+        use Direction::*;
+        match (self, dir) {
+            (SectorTransform::RotateCw, North) => East,
+            (SectorTransform::RotateCw, East) => South,
+            (SectorTransform::RotateCw, South) => West,
+            (SectorTransform::RotateCw, West) => North,
+            (SectorTransform::RotateCcw, d) => SectorTransform::RotateCw.direction(d).opposite(),
+            (SectorTransform::MirrorX, East) => West,
+            (SectorTransform::MirrorX, West) => East,
+            (SectorTransform::MirrorZ, North) => South,
+            (SectorTransform::MirrorZ, South) => North,
+            (_, d) => d,
+        }
+    }
+
+    /// True if the transform reverses handedness
+    pub fn is_mirror(self) -> bool {
+        matches!(self, SectorTransform::MirrorX | SectorTransform::MirrorZ)
+    }
+
+    /// New position of cell (x, z) inside a `width` x `depth` block
+    pub fn cell(self, x: usize, z: usize, width: usize, depth: usize) -> (usize, usize) {
+        match self {
+            SectorTransform::RotateCw => (depth - 1 - z, x),
+            SectorTransform::RotateCcw => (z, width - 1 - x),
+            SectorTransform::MirrorX => (width - 1 - x, z),
+            SectorTransform::MirrorZ => (x, depth - 1 - z),
+        }
+    }
+
+    /// Whether the transform swaps the X and Z extents
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, SectorTransform::RotateCw | SectorTransform::RotateCcw)
+    }
+
+    /// New position of a point inside a `width` x `depth` area (X/Z only)
+    pub fn point(self, p: Vec3, width: f32, depth: f32) -> Vec3 {
+        let (x, z) = match self {
+            SectorTransform::RotateCw => (depth - p.z, p.x),
+            SectorTransform::RotateCcw => (p.z, width - p.x),
+            SectorTransform::MirrorX => (width - p.x, p.z),
+            SectorTransform::MirrorZ => (p.x, depth - p.z),
+        };
+        Vec3::new(x, p.y, z)
+    }
+
+    /// New direction of a vector (X/Z only)
+    pub fn vector(self, v: Vec3) -> Vec3 {
+        self.point(v, 0.0, 0.0)
+    }
+
+    /// Remap floor/ceiling corner heights [NW, NE, SE, SW]
+    pub fn corners(self, h: [f32; 4]) -> [f32; 4] {
+        match self {
+            SectorTransform::RotateCw => [h[3], h[0], h[1], h[2]],
+            SectorTransform::RotateCcw => [h[1], h[2], h[3], h[0]],
+            SectorTransform::MirrorX => [h[1], h[0], h[3], h[2]],
+            SectorTransform::MirrorZ => [h[3], h[2], h[1], h[0]],
+        }
+    }
+}
+
+fn transform_horizontal(face: &HorizontalFace, t: SectorTransform) -> HorizontalFace {
+    let mut face = face.clone();
+    face.heights = t.corners(face.heights);
+    face
+}
+
+fn transform_vertical(wall: &VerticalFace, t: SectorTransform) -> VerticalFace {
+    let mut wall = wall.clone();
+    if t.is_mirror() {
+        // [bottom-left, bottom-right, top-right, top-left] -> left/right swapped
+        let h = wall.heights;
+        wall.heights = [h[1], h[0], h[3], h[2]];
+    }
+    wall
+}
+
+impl Sector {
+    /// Copy of this sector with the transform applied
+    pub fn transformed(&self, t: SectorTransform) -> Sector {
+        let mut out = Sector {
+            floor: self.floor.as_ref().map(|f| transform_horizontal(f, t)),
+            ceiling: self.ceiling.as_ref().map(|c| transform_horizontal(c, t)),
+            ..Default::default()
+        };
+        for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+            let walls = self.walls(dir).iter().map(|w| transform_vertical(w, t)).collect();
+            *out.walls_mut(t.direction(dir)) = walls;
+        }
+        out
+    }
+}
+
+impl Room {
+    /// Move sectors from `cells` to `targets` (same order), mapping each one
+    /// through `remap`. Nothing changes if any target is outside the grid or
+    /// lands on a sector that isn't part of the block.
+    fn relocate_sectors<F>(
+        &mut self,
+        cells: &[(usize, usize)],
+        targets: &[(i32, i32)],
+        remap: F,
+    ) -> Result<Vec<(usize, usize)>, String>
+    where
+        F: Fn(Sector) -> Sector,
+    {
+        if cells.is_empty() {
+            return Err("No sectors selected".to_string());
+        }
+        if let Some((x, z)) = cells.iter().find(|(x, z)| self.get_sector(*x, *z).is_none()) {
+            return Err(format!("No sector at ({}, {})", x, z));
+        }
+        for &(x, z) in targets {
+            if x < 0 || z < 0 || x as usize >= self.width || z as usize >= self.depth {
+                return Err("Can't move sectors outside the room".to_string());
+            }
+            let (x, z) = (x as usize, z as usize);
+            if self.get_sector(x, z).is_some() && !cells.contains(&(x, z)) {
+                return Err("Can't move sectors onto existing sectors".to_string());
+            }
+        }
+
+        let taken: Vec<Sector> = cells.iter().map(|&(x, z)| self.sectors[x][z].take().unwrap()).collect();
+        let placed: Vec<(usize, usize)> = targets.iter().map(|&(x, z)| (x as usize, z as usize)).collect();
+        for (sector, &(x, z)) in taken.into_iter().zip(&placed) {
+            self.sectors[x][z] = Some(remap(sector));
+        }
+        self.recalculate_bounds();
+        Ok(placed)
+    }
+
+    /// Move a block of sectors by (dx, dz) grid cells. Returns the new cells.
+    pub fn move_sectors(&mut self, cells: &[(usize, usize)], dx: i32, dz: i32) -> Result<Vec<(usize, usize)>, String> {
+        let targets: Vec<(i32, i32)> = cells.iter().map(|&(x, z)| (x as i32 + dx, z as i32 + dz)).collect();
+        self.relocate_sectors(cells, &targets, |sector| sector)
+    }
+
+    /// Rotate or mirror a block of sectors within its bounding box (anchored
+    /// at the box's north-west corner). Returns the new cells.
+    pub fn transform_sectors(&mut self, cells: &[(usize, usize)], t: SectorTransform) -> Result<Vec<(usize, usize)>, String> {
+        let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_z = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        let width = cells.iter().map(|c| c.0).max().unwrap_or(0) + 1 - min_x;
+        let depth = cells.iter().map(|c| c.1).max().unwrap_or(0) + 1 - min_z;
+
+        let targets: Vec<(i32, i32)> = cells
+            .iter()
+            .map(|&(x, z)| {
+                let (nx, nz) = t.cell(x - min_x, z - min_z, width, depth);
+                ((min_x + nx) as i32, (min_z + nz) as i32)
+            })
+            .collect();
+        self.relocate_sectors(cells, &targets, |sector| sector.transformed(t))
+    }
+
+    /// Rotate or mirror the whole room in place (its grid origin stays put).
+    /// Portals, waypoints and entities move with the sectors.
+    pub fn transform(&mut self, t: SectorTransform) {
+        let (width, depth) = (self.width, self.depth);
+        let (new_width, new_depth) = if t.swaps_axes() { (depth, width) } else { (width, depth) };
+        let mut sectors: Vec<Vec<Option<Sector>>> = (0..new_width).map(|_| (0..new_depth).map(|_| None).collect()).collect();
+        for (x, z, sector) in self.iter_sectors() {
+            let (nx, nz) = t.cell(x, z, width, depth);
+            sectors[nx][nz] = Some(sector.transformed(t));
+        }
+        self.sectors = sectors;
+        self.width = new_width;
+        self.depth = new_depth;
+
+        let (extent_x, extent_z) = (width as f32 * self.sector_size, depth as f32 * self.sector_size);
+        for portal in &mut self.portals {
+            let mut vertices = portal.vertices.map(|v| t.point(v, extent_x, extent_z));
+            if t.is_mirror() {
+                // Keep the winding
+                vertices = [vertices[1], vertices[0], vertices[3], vertices[2]];
+            }
+            portal.vertices = vertices;
+            portal.normal = t.vector(portal.normal);
+        }
+        for wp in &mut self.waypoints {
+            wp.position = t.point(wp.position, extent_x, extent_z);
+        }
+        for entity in &mut self.entities {
+            entity.position = t.point(entity.position, extent_x, extent_z);
+        }
+        self.recalculate_bounds();
+    }
+
+    /// Grow the grid so cell (x, z) exists (coordinates may be negative).
+    /// Growing west or north moves the room's origin, and its portals,
+    /// waypoints and entities with it. Returns how far existing cells moved in (x, z), or
+    /// an error if the grid would grow past `MAX_ROOM_SIZE`.
+    pub fn grow_to_include(&mut self, x: i32, z: i32) -> Result<(usize, usize), String> {
+        let shift_x = (-x).max(0) as usize;
+        let shift_z = (-z).max(0) as usize;
+        let new_width = (self.width + shift_x).max((x + shift_x as i32) as usize + 1);
+        let new_depth = (self.depth + shift_z).max((z + shift_z as i32) as usize + 1);
+        let grows = new_width > self.width || new_depth > self.depth;
+        if grows && (new_width > MAX_ROOM_SIZE || new_depth > MAX_ROOM_SIZE) {
+            return Err(format!("Rooms can't grow past {0}x{0} sectors, start a new room", MAX_ROOM_SIZE));
+        }
+        if shift_x > 0 || shift_z > 0 {
+            for _ in 0..shift_x {
+                self.sectors.insert(0, (0..self.depth).map(|_| None).collect());
+            }
+            self.width += shift_x;
+            for col in &mut self.sectors {
+                col.splice(0..0, (0..shift_z).map(|_| None));
+            }
+            self.depth += shift_z;
+
+            let offset = Vec3::new(shift_x as f32 * self.sector_size, 0.0, shift_z as f32 * self.sector_size);
+            self.position = self.position - offset;
+            self.portals = self.portals.iter().map(|p| offset_portal(p, offset)).collect();
+            for wp in &mut self.waypoints {
+                wp.position = wp.position + offset;
+            }
+            for entity in &mut self.entities {
+                entity.position = entity.position + offset;
+            }
+        }
+
+        let (x, z) = (x + shift_x as i32, z + shift_z as i32);
+        while x as usize >= self.width {
+            self.width += 1;
+            self.sectors.push((0..self.depth).map(|_| None).collect());
+        }
+        while z as usize >= self.depth {
+            self.depth += 1;
+            for col in &mut self.sectors {
+                col.push(None);
+            }
+        }
+        Ok((shift_x, shift_z))
+    }
+
+    /// Shrink the grid to the smallest box around sectors with geometry
+    /// (sectors outside it are empty and dropped). Portals, waypoints and entities keep
+    /// their world positions. Returns false if there was nothing to trim.
+    pub fn trim_empty_borders(&mut self) -> bool {
+        let used: Vec<(usize, usize)> = self.iter_sectors().filter(|(_, _, s)| s.has_geometry()).map(|(x, z, _)| (x, z)).collect();
+        if used.is_empty() {
+            return false;
+        }
+        let (x0, x1) = (used.iter().map(|c| c.0).min().unwrap(), used.iter().map(|c| c.0).max().unwrap());
+        let (z0, z1) = (used.iter().map(|c| c.1).min().unwrap(), used.iter().map(|c| c.1).max().unwrap());
+        if (x0, z0) == (0, 0) && (x1 + 1, z1 + 1) == (self.width, self.depth) {
+            return false;
+        }
+
+        let mut old = std::mem::take(&mut self.sectors);
+        self.sectors = old.drain(x0..=x1).map(|col| col.into_iter().skip(z0).take(z1 - z0 + 1).collect()).collect();
+        self.width = x1 - x0 + 1;
+        self.depth = z1 - z0 + 1;
+        let offset = Vec3::new(x0 as f32 * self.sector_size, 0.0, z0 as f32 * self.sector_size);
+        self.position = self.position + offset;
+        self.portals = self.portals.iter().map(|p| offset_portal(p, Vec3::ZERO - offset)).collect();
+        for wp in &mut self.waypoints {
+            wp.position = wp.position - offset;
+        }
+        for entity in &mut self.entities {
+            entity.position = entity.position - offset;
+        }
+        self.recalculate_bounds();
+        true
+    }
+
+    /// Perturb the floor corners of `cells` by up to `amplitude`, snapped to
+    /// `step`. Noise is per grid corner, so neighbouring floors stay joined;
+    /// corners shared with floors outside `cells` don't move. Returns how many
+    /// floors changed.
+    pub fn add_floor_noise(&mut self, cells: &[(usize, usize)], amplitude: f32, step: f32, seed: u32) -> usize {
+        let selected = |x: usize, z: usize| cells.contains(&(x, z));
+        // Corner (cx, cz) touches the sectors to its NW, NE, SW and SE
+        let pinned = |room: &Room, cx: usize, cz: usize| {
+            [(0, 0), (1, 0), (0, 1), (1, 1)].iter().any(|&(dx, dz)| {
+                let (Some(x), Some(z)) = ((cx + dx).checked_sub(1), (cz + dz).checked_sub(1)) else { return false };
+                !selected(x, z) && room.get_sector(x, z).is_some_and(|s| s.floor.is_some())
+            })
+        };
+        let offset = |cx: usize, cz: usize| {
+            let n = corner_noise(cx as u32, cz as u32, seed) * amplitude;
+            if step > 0.0 { (n / step).round() * step } else { n }
+        };
+
+        let mut changed = 0;
+        for &(x, z) in cells {
+            // Corners in [NW, NE, SE, SW] order
+            let corners = [(x, z), (x + 1, z), (x + 1, z + 1), (x, z + 1)];
+            let offsets = corners.map(|(cx, cz)| if pinned(self, cx, cz) { 0.0 } else { offset(cx, cz) });
+            let Some(floor) = self.get_sector_mut(x, z).and_then(|s| s.floor.as_mut()) else { continue };
+            for (h, d) in floor.heights.iter_mut().zip(offsets) {
+                *h += d;
+            }
+            if offsets.iter().any(|&d| d != 0.0) {
+                changed += 1;
+            }
+        }
+        self.recalculate_bounds();
+        changed
+    }
+}
+
+/// Hash a grid corner to a value in [-1, 1]
+fn corner_noise(cx: u32, cz: u32, seed: u32) -> f32 {
+    let mut h = seed ^ cx.wrapping_mul(0x9E37_79B1) ^ cz.wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A_2D39);
+    h ^= h >> 15;
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+impl Sector {
+    /// Shift every floor, ceiling and wall height by `dy`
+    pub(super) fn offset_heights(&mut self, dy: f32) {
+        for face in self.floor.iter_mut().chain(self.ceiling.iter_mut()) {
+            face.heights.iter_mut().for_each(|h| *h += dy);
+        }
+        for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+            for wall in self.walls_mut(dir) {
+                wall.heights.iter_mut().for_each(|h| *h += dy);
+            }
+        }
+    }
+}
+
+fn offset_portal(portal: &Portal, offset: Vec3) -> Portal {
+    let mut portal = portal.clone();
+    for v in &mut portal.vertices {
+        *v = *v + offset;
+    }
+    portal
+}
+
+/// Grid offset of `to` relative to `from`, if both are sector-aligned
+fn grid_offset(from: Vec3, to: Vec3, sector_size: f32) -> Option<(i32, i32)> {
+    let dx = (to.x - from.x) / sector_size;
+    let dz = (to.z - from.z) / sector_size;
+    let aligned = (dx - dx.round()).abs() < 0.001 && (dz - dz.round()).abs() < 0.001;
+    aligned.then_some((dx.round() as i32, dz.round() as i32))
+}
+
+impl Level {
+    /// Rewrite every waypoint reference through `remap` (None drops the link)
+    fn remap_waypoint_refs<F>(&mut self, remap: F)
+    where
+        F: Fn(WaypointRef) -> Option<WaypointRef>,
+    {
+        for room in &mut self.rooms {
+            for wp in &mut room.waypoints {
+                wp.links = wp.links.iter().filter_map(|l| remap(*l)).collect();
+            }
+        }
+    }
+
+    /// Remove a room, retargeting portals and waypoint links that pointed at it
+    /// to `replacement` and renumbering the rooms after it
+    fn remove_room_into(&mut self, removed: usize, replacement: usize) {
+        self.rooms.remove(removed);
+        let shift = |idx: usize| if idx > removed { idx - 1 } else { idx };
+        let replacement = shift(replacement);
+
+        for (i, room) in self.rooms.iter_mut().enumerate() {
+            room.id = i;
+            for portal in &mut room.portals {
+                portal.target_room = if portal.target_room == removed { replacement } else { shift(portal.target_room) };
+            }
+        }
+        self.remap_waypoint_refs(|r| Some(WaypointRef::new(shift(r.room), r.index)));
+        self.remove_room_from_sets(removed);
+    }
+
+    /// Rotate or mirror a whole room in place. Portals to and from it that no
+    /// longer line up with their partner are removed; returns how many.
+    pub fn transform_room(&mut self, room_idx: usize, t: SectorTransform) -> Result<usize, String> {
+        let Some(room) = self.rooms.get_mut(room_idx) else {
+            return Err("Room does not exist".to_string());
+        };
+        room.transform(t);
+
+        // A portal pair stays if both sides still describe the same opening
+        let world_center = |room: &Room, portal: &Portal| room.position + portal.center();
+        let paired = |level: &Level, from: usize, portal: &Portal| {
+            let center = world_center(&level.rooms[from], portal);
+            level.rooms.get(portal.target_room).is_some_and(|target| {
+                target.portals.iter().any(|p| p.target_room == from && (world_center(target, p) - center).len() < 1.0)
+            })
+        };
+        let mut stale: Vec<(usize, usize)> = Vec::new();
+        for (i, room) in self.rooms.iter().enumerate() {
+            for (j, portal) in room.portals.iter().enumerate() {
+                let touches = i == room_idx || portal.target_room == room_idx;
+                if touches && !paired(self, i, portal) {
+                    stale.push((i, j));
+                }
+            }
+        }
+        for &(i, j) in stale.iter().rev() {
+            self.rooms[i].portals.remove(j);
+        }
+        Ok(stale.len())
+    }
+
+    /// Merge room `other` into room `keep`: their sector grids are combined,
+    /// portals between the two are dissolved and `other` is removed.
+    /// Returns the merged room's index.
+    pub fn merge_rooms(&mut self, keep: usize, other: usize) -> Result<usize, String> {
+        if keep == other {
+            return Err("Select two different rooms to merge".to_string());
+        }
+        let (Some(a), Some(b)) = (self.rooms.get(keep), self.rooms.get(other)) else {
+            return Err("Room does not exist".to_string());
+        };
+        let Some((bx, bz)) = grid_offset(a.position, b.position, self.sector_size) else {
+            return Err("Rooms are not aligned to the same sector grid".to_string());
+        };
+
+        // Combined grid, in sectors relative to room `keep`
+        let min_x = bx.min(0);
+        let min_z = bz.min(0);
+        let width = (bx + b.width as i32).max(a.width as i32) - min_x;
+        let depth = (bz + b.depth as i32).max(a.depth as i32) - min_z;
+        let size = self.sector_size;
+        let origin = a.position + Vec3::new(min_x as f32 * size, 0.0, min_z as f32 * size);
+        let dy = b.position.y - a.position.y;
+
+        let mut merged = Room::new(a.id, origin, width as usize, depth as usize);
+        merged.ambient = a.ambient;
+        merged.weather = a.weather;
+        merged.audio = a.audio.clone();
+        merged.sector_size = size;
+        for (room, (ox, oz), height_offset, partner) in [(a, (-min_x, -min_z), 0.0, other), (b, (bx - min_x, bz - min_z), dy, keep)] {
+            for (x, z, sector) in room.iter_sectors() {
+                let (mx, mz) = ((x as i32 + ox) as usize, (z as i32 + oz) as usize);
+                if merged.sectors[mx][mz].is_some() {
+                    return Err("Rooms overlap".to_string());
+                }
+                let mut sector = sector.clone();
+                sector.offset_heights(height_offset);
+                merged.sectors[mx][mz] = Some(sector);
+            }
+
+            // Portals, waypoints and entities are room-relative
+            let shift = room.position - origin;
+            merged.portals.extend(room.portals.iter().filter(|p| p.target_room != partner).map(|p| offset_portal(p, shift)));
+            merged.waypoints.extend(room.waypoints.iter().map(|wp| {
+                let mut wp = wp.clone();
+                wp.position = wp.position + shift;
+                wp
+            }));
+            merged.entities.extend(room.entities.iter().map(|e| {
+                let mut e = e.clone();
+                e.position = e.position + shift;
+                e
+            }));
+        }
+        merged.recalculate_bounds();
+
+        let keep_waypoints = a.waypoints.len();
+        self.rooms[keep] = merged;
+        self.remap_waypoint_refs(|r| {
+            Some(if r.room == other { WaypointRef::new(keep, keep_waypoints + r.index) } else { r })
+        });
+        self.remove_room_into(other, keep);
+        Ok(if keep > other { keep - 1 } else { keep })
+    }
+
+    /// Carve the sectors in the grid rectangle (x0, z0)-(x1, z1) (inclusive)
+    /// out of `room` into a new room, adding a portal pair on every edge the
+    /// two rooms share. Returns the new room's index.
+    pub fn split_room(&mut self, room_idx: usize, (x0, z0): (usize, usize), (x1, z1): (usize, usize)) -> Result<usize, String> {
+        let Some(room) = self.rooms.get(room_idx) else {
+            return Err("Room does not exist".to_string());
+        };
+        let (x0, x1, z0, z1) = (x0.min(x1), x0.max(x1), z0.min(z1), z0.max(z1));
+        if x1 >= room.width || z1 >= room.depth {
+            return Err("Split rectangle is outside the room".to_string());
+        }
+        let inside = |x: usize, z: usize| (x0..=x1).contains(&x) && (z0..=z1).contains(&z);
+        let carved = room.iter_sectors().filter(|(x, z, _)| inside(*x, *z)).count();
+        if carved == 0 {
+            return Err("No sectors in the split rectangle".to_string());
+        }
+        if carved == room.iter_sectors().count() {
+            return Err("Split rectangle covers the whole room".to_string());
+        }
+
+        let new_idx = self.rooms.len();
+        let size = self.sector_size;
+        let offset = Vec3::new(x0 as f32 * size, 0.0, z0 as f32 * size);
+        let mut new_room = Room::new(new_idx, room.position + offset, x1 - x0 + 1, z1 - z0 + 1);
+        new_room.ambient = room.ambient;
+        new_room.weather = room.weather;
+        new_room.audio = room.audio.clone();
+        new_room.sector_size = size;
+
+        // Portals on every edge between a carved sector and one that stays
+        let mut old_portals = Vec::new();
+        for (x, z, sector) in room.iter_sectors().filter(|(x, z, _)| inside(*x, *z)) {
+            for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                let (dx, dz) = dir.offset();
+                let (nx, nz) = (x as i32 + dx, z as i32 + dz);
+                if nx < 0 || nz < 0 || inside(nx as usize, nz as usize) {
+                    continue;
+                }
+                let Some(neighbor) = room.get_sector(nx as usize, nz as usize) else {
+                    continue;
+                };
+                let vertices = shared_edge(sector, neighbor, x, z, dir, size);
+                let normal = Vec3::new(dx as f32, 0.0, dz as f32);
+                new_room.add_portal(room_idx, vertices.map(|v| v - offset), Vec3::ZERO - normal);
+                old_portals.push(Portal::new(new_idx, vertices, normal));
+            }
+        }
+
+        // Move sectors, portals, waypoints and entities inside the rectangle
+        let room = &mut self.rooms[room_idx];
+        for x in x0..=x1 {
+            for z in z0..=z1 {
+                new_room.sectors[x - x0][z - z0] = room.sectors[x][z].take();
+            }
+        }
+        let in_rect = |p: Vec3| room_relative_cell(p, size).is_some_and(|(x, z)| inside(x, z));
+        let (moved, kept): (Vec<Portal>, Vec<Portal>) = room.portals.drain(..).partition(|p| in_rect(p.center()));
+        room.portals = kept;
+        room.portals.extend(old_portals);
+        new_room.portals.extend(moved.iter().map(|p| offset_portal(p, Vec3::ZERO - offset)));
+
+        let mut waypoint_map = HashMap::new();
+        let mut kept_waypoints = Vec::new();
+        for (i, wp) in std::mem::take(&mut room.waypoints).into_iter().enumerate() {
+            if in_rect(wp.position) {
+                waypoint_map.insert(i, WaypointRef::new(new_idx, new_room.waypoints.len()));
+                let mut wp = wp;
+                wp.position = wp.position - offset;
+                new_room.waypoints.push(wp);
+            } else {
+                waypoint_map.insert(i, WaypointRef::new(room_idx, kept_waypoints.len()));
+                kept_waypoints.push(wp);
+            }
+        }
+        room.waypoints = kept_waypoints;
+        let (moved, kept): (Vec<Entity>, Vec<Entity>) = room.entities.drain(..).partition(|e| in_rect(e.position));
+        room.entities = kept;
+        new_room.entities.extend(moved.into_iter().map(|e| Entity { position: e.position - offset, ..e }));
+        room.recalculate_bounds();
+        new_room.recalculate_bounds();
+        self.rooms.push(new_room);
+
+        self.remap_waypoint_refs(|r| if r.room == room_idx { waypoint_map.get(&r.index).copied() } else { Some(r) });
+        Ok(new_idx)
+    }
+}
+
+/// Grid cell containing a room-relative point
+fn room_relative_cell(p: Vec3, sector_size: f32) -> Option<(usize, usize)> {
+    (p.x >= 0.0 && p.z >= 0.0).then(|| ((p.x / sector_size) as usize, (p.z / sector_size) as usize))
+}
+
+/// Room-relative portal quad on edge `dir` of sector (x, z), spanning the
+/// opening between the two sectors' floors and ceilings
+fn shared_edge(a: &Sector, b: &Sector, x: usize, z: usize, dir: Direction, size: f32) -> [Vec3; 4] {
+    let floor = |s: &Sector| s.floor.as_ref().map(|f| f.avg_height());
+    let ceiling = |s: &Sector| s.ceiling.as_ref().map(|c| c.avg_height());
+    let bottom = match (floor(a), floor(b)) {
+        (Some(fa), Some(fb)) => fa.min(fb),
+        (fa, fb) => fa.or(fb).unwrap_or(0.0),
+    };
+    let top = match (ceiling(a), ceiling(b)) {
+        (Some(ca), Some(cb)) => ca.max(cb),
+        (ca, cb) => ca.or(cb).unwrap_or(bottom + OPEN_PORTAL_HEIGHT),
+    };
+
+    let (bx, bz) = (x as f32 * size, z as f32 * size);
+    let (ex0, ez0, ex1, ez1) = match dir {
+        Direction::North => (bx, bz, bx + size, bz),
+        Direction::East => (bx + size, bz, bx + size, bz + size),
+        Direction::South => (bx + size, bz + size, bx, bz + size),
+        Direction::West => (bx, bz + size, bx, bz),
+    };
+    [
+        Vec3::new(ex0, bottom, ez0),
+        Vec3::new(ex1, bottom, ez1),
+        Vec3::new(ex1, top, ez1),
+        Vec3::new(ex0, top, ez0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{LevelBuilder, RoomBuilder};
+
+    fn sloped_room() -> Room {
+        // 2x1 block: west sector sloped up towards the east, wall on its north edge
+        RoomBuilder::new(3, 3)
+            .sloped_floor(0, 0, [0.0, 256.0, 256.0, 0.0])
+            .floor(1, 0, 256.0)
+            .wall(0, 0, Direction::North, 0.0, 1024.0)
+            .build()
+    }
+
+    #[test]
+    fn test_rotate_remaps_cells_walls_and_corners() {
+        let mut room = sloped_room();
+        let cells = room.transform_sectors(&[(0, 0), (1, 0)], SectorTransform::RotateCw).unwrap();
+        assert_eq!(cells, vec![(0, 0), (0, 1)]);
+
+        // West sector is now the north one; its slope rises towards the south
+        let sector = room.get_sector(0, 0).unwrap();
+        assert_eq!(sector.floor.as_ref().unwrap().heights, [0.0, 0.0, 256.0, 256.0]);
+        assert!(sector.walls_north.is_empty());
+        assert_eq!(sector.walls_east.len(), 1);
+    }
+
+    #[test]
+    fn test_mirror_round_trips() {
+        let mut room = sloped_room();
+        room.get_sector_mut(0, 0).unwrap().walls_north[0].heights = [0.0, 128.0, 1024.0, 1024.0];
+        let cells = [(0, 0), (1, 0)];
+        room.transform_sectors(&cells, SectorTransform::MirrorX).unwrap();
+        let wall = &room.get_sector(1, 0).unwrap().walls_north[0];
+        assert_eq!(wall.heights, [128.0, 0.0, 1024.0, 1024.0]);
+        room.transform_sectors(&cells, SectorTransform::MirrorX).unwrap();
+        assert_eq!(room.get_sector(0, 0).unwrap().floor.as_ref().unwrap().heights, [0.0, 256.0, 256.0, 0.0]);
+    }
+
+    #[test]
+    fn test_floor_noise_keeps_floors_joined() {
+        let mut room = (0..3).fold(RoomBuilder::new(3, 1), |room, x| room.floor(x, 0, 0.0)).build();
+        let cells = [(0, 0), (1, 0)];
+        assert_eq!(room.add_floor_noise(&cells, 256.0, 64.0, 7), 2);
+
+        let heights = |x| room.get_sector(x, 0).unwrap().floor.as_ref().unwrap().heights;
+        let (a, b) = (heights(0), heights(1));
+        // Shared edge: a's NE/SE are b's NW/SW
+        assert_eq!((a[1], a[2]), (b[0], b[3]));
+        // The edge shared with the unselected floor stays put
+        assert_eq!((b[1], b[2]), (0.0, 0.0));
+        assert!(a.iter().chain(&b).all(|h| h.abs() <= 256.0 && h % 64.0 == 0.0));
+        assert_eq!(heights(2), [0.0; 4]);
+    }
+
+    #[test]
+    fn test_move_rejects_blocked_targets() {
+        let mut room = sloped_room();
+        assert!(room.move_sectors(&[(0, 0)], 1, 0).is_err());
+        assert!(room.move_sectors(&[(0, 0)], -1, 0).is_err());
+        // Failed moves leave the room untouched
+        assert!(room.get_sector(0, 0).is_some());
+
+        let moved = room.move_sectors(&[(0, 0), (1, 0)], 1, 2).unwrap();
+        assert_eq!(moved, vec![(1, 2), (2, 2)]);
+        assert!(room.get_sector(0, 0).is_none());
+        assert_eq!(room.get_sector(1, 2).unwrap().walls_north.len(), 1);
+    }
+
+    #[test]
+    fn test_rotate_whole_room() {
+        let mut room = RoomBuilder::new(3, 2)
+            .sloped_floor(0, 0, [0.0, 256.0, 256.0, 0.0])
+            .wall(0, 0, Direction::North, 0.0, 1024.0)
+            .build();
+        room.waypoints.push(crate::world::Waypoint::new(Vec3::new(100.0, 0.0, 200.0)));
+
+        room.transform(SectorTransform::RotateCw);
+        assert_eq!((room.width, room.depth), (2, 3));
+        // The north-west sector ends up in the north-east corner, its north wall facing east
+        let sector = room.get_sector(1, 0).unwrap();
+        assert_eq!(sector.walls_east.len(), 1);
+        assert_eq!(sector.floor.as_ref().unwrap().heights, [0.0, 0.0, 256.0, 256.0]);
+        assert_eq!(room.waypoints[0].position, Vec3::new(2048.0 - 200.0, 0.0, 100.0));
+
+        for _ in 0..3 {
+            room.transform(SectorTransform::RotateCw);
+        }
+        assert_eq!(room.get_sector(0, 0).unwrap().floor.as_ref().unwrap().heights, [0.0, 256.0, 256.0, 0.0]);
+        assert_eq!(room.waypoints[0].position, Vec3::new(100.0, 0.0, 200.0));
+    }
+
+    #[test]
+    fn test_transform_room_drops_broken_portals() {
+        let mut level = LevelBuilder::new().room(RoomBuilder::new(2, 1).fill(0.0, 1024.0)).build();
+        level.split_room(0, (1, 0), (1, 0)).unwrap();
+        assert_eq!(level.rooms[1].portals.len(), 1);
+
+        // Mirroring north-south keeps the shared edge in place, east-west moves it away
+        assert_eq!(level.transform_room(1, SectorTransform::MirrorZ).unwrap(), 0);
+        assert_eq!(level.transform_room(1, SectorTransform::MirrorX).unwrap(), 2);
+        assert!(level.rooms.iter().all(|r| r.portals.is_empty()));
+    }
+
+    #[test]
+    fn test_grow_keeps_world_positions() {
+        let mut room = sloped_room();
+        room.waypoints.push(crate::world::Waypoint::new(Vec3::new(512.0, 0.0, 512.0)));
+        let origin = room.position;
+        assert_eq!(room.grow_to_include(-1, 4), Ok((1, 0)));
+        assert_eq!((room.width, room.depth), (4, 5));
+        assert_eq!(room.position.x, origin.x - 1024.0);
+        // Sectors and waypoints stay where they were in the world
+        assert_eq!(room.get_sector(1, 0).unwrap().walls_north.len(), 1);
+        assert_eq!(room.waypoints[0].position.x, 1536.0);
+        assert_eq!(room.grow_to_include(0, 0), Ok((0, 0)));
+        assert!(room.grow_to_include(MAX_ROOM_SIZE as i32, 0).is_err());
+
+        // Trimming undoes the growth
+        assert!(room.trim_empty_borders());
+        assert_eq!((room.width, room.depth, room.position), (2, 1, origin));
+        assert_eq!(room.waypoints[0].position.x, 512.0);
+        assert!(!room.trim_empty_borders());
+    }
+
+    #[test]
+    fn test_split_then_merge_round_trips() {
+        let mut level = LevelBuilder::new().room(RoomBuilder::new(3, 1).fill(0.0, 1024.0)).build();
+        let wp = level.add_waypoint(0, Vec3::new(2560.0, 0.0, 512.0)).unwrap();
+        let other = level.add_waypoint(0, Vec3::new(512.0, 0.0, 512.0)).unwrap();
+        level.toggle_waypoint_link(wp, other);
+
+        let new_idx = level.split_room(0, (2, 0), (2, 0)).unwrap();
+        assert_eq!(new_idx, 1);
+        assert_eq!(level.rooms[1].position.x, 2048.0);
+        assert_eq!(level.rooms[0].iter_sectors().count(), 2);
+        assert_eq!(level.rooms[0].portals.len(), 1);
+        assert_eq!(level.rooms[0].portals[0].target_room, 1);
+        assert_eq!(level.rooms[1].portals[0].target_room, 0);
+        // The waypoint moved with its sector and kept its link
+        assert_eq!(level.rooms[1].waypoints.len(), 1);
+        assert_eq!(level.rooms[1].waypoints[0].links, vec![WaypointRef::new(0, 0)]);
+
+        let merged = level.merge_rooms(0, 1).unwrap();
+        assert_eq!(merged, 0);
+        assert_eq!(level.rooms.len(), 1);
+        assert_eq!(level.rooms[0].iter_sectors().count(), 3);
+        assert!(level.rooms[0].portals.is_empty());
+        assert_eq!(level.rooms[0].waypoints[1].links, vec![WaypointRef::new(0, 0)]);
+    }
+
+    #[test]
+    fn test_merge_rejects_overlap_and_misalignment() {
+        let mut level = LevelBuilder::new()
+            .room(RoomBuilder::new(2, 1).fill(0.0, 1024.0))
+            .room(RoomBuilder::new(1, 1).at(Vec3::new(1024.0, 0.0, 0.0)).fill(0.0, 1024.0))
+            .room(RoomBuilder::new(1, 1).at(Vec3::new(100.0, 0.0, 5000.0)).fill(0.0, 1024.0))
+            .build();
+        assert!(level.merge_rooms(0, 1).is_err());
+        assert!(level.merge_rooms(0, 2).is_err());
+        assert_eq!(level.rooms.len(), 3);
+    }
+}
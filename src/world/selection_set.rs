@@ -0,0 +1,85 @@
+//! Named selection sets
+//!
+//! Faces and sectors saved under a name ("arena floor", "all lava") so bulk
+//! edits can be repeated without reselecting everything:
+//! - stored in the level file, recalled from the editor's Room panel
+//! - members point at room indices and grid cells; removing a room drops its
+//!   members and renumbers the rest
+//! - members whose face no longer exists are skipped when a set is recalled
+
+use serde::{Deserialize, Serialize};
+use super::{Direction, Level};
+
+/// Face within a sector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SetFace {
+    Floor,
+    Ceiling,
+    /// Wall on an edge, with its index in that edge's wall stack
+    Wall(Direction, usize),
+}
+
+/// A sector (or one of its faces) in a selection set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetMember {
+    pub room: usize,
+    pub x: usize,
+    pub z: usize,
+    /// None = the whole sector
+    #[serde(default)]
+    pub face: Option<SetFace>,
+}
+
+/// A named group of sectors and faces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionSet {
+    pub name: String,
+    pub members: Vec<SetMember>,
+}
+
+impl Level {
+    /// Whether a set member still points at existing geometry
+    pub fn set_member_exists(&self, member: &SetMember) -> bool {
+        let Some(sector) = self.rooms.get(member.room).and_then(|r| r.get_sector(member.x, member.z)) else {
+            return false;
+        };
+        match member.face {
+            None => true,
+            Some(SetFace::Floor) => sector.floor.is_some(),
+            Some(SetFace::Ceiling) => sector.ceiling.is_some(),
+            Some(SetFace::Wall(dir, i)) => i < sector.walls(dir).len(),
+        }
+    }
+
+    /// Drop set members in a removed room and renumber the rooms after it
+    pub(super) fn remove_room_from_sets(&mut self, removed: usize) {
+        for set in &mut self.selection_sets {
+            set.members.retain(|m| m.room != removed);
+            for m in &mut set.members {
+                if m.room > removed {
+                    m.room -= 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_test_level, Room};
+    use crate::rasterizer::Vec3;
+
+    #[test]
+    fn test_members_follow_room_removal() {
+        let mut level = create_test_level();
+        level.add_room(Room::new(1, Vec3::ZERO, 1, 1));
+        let member = |room| SetMember { room, x: 0, z: 0, face: Some(SetFace::Floor) };
+        level.selection_sets.push(SelectionSet { name: "floors".to_string(), members: vec![member(0), member(1)] });
+
+        assert!(level.set_member_exists(&member(0)));
+        assert!(!level.set_member_exists(&member(1)));
+        level.remove_room_from_sets(0);
+        assert_eq!(level.selection_sets[0].members, vec![member(0)]);
+    }
+}
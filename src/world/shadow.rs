@@ -0,0 +1,110 @@
+//! Blob shadows
+//!
+//! PS1-style contact shadows: a dark ellipse on the floor under a character
+//! or placed object instead of real shadow casting:
+//! - the floor is found with a solid ray cast straight down
+//! - the blob shrinks and fades as the object rises above the floor, and
+//!   disappears past `SHADOW_MAX_HEIGHT`
+//! - drawn with subtractive blending, depth tested against the floor with a
+//!   small bias so it sits on the surface without z-fighting
+
+use crate::rasterizer::{BlendMode, Camera, Color, Framebuffer, Vec3, world_to_screen};
+use super::{raycast_solid, HitFace, Level};
+
+/// Height above the floor at which a shadow has faded out completely
+pub const SHADOW_MAX_HEIGHT: f32 = 2048.0;
+
+/// How dark a shadow right on the floor is (subtracted from each channel)
+const SHADOW_DARKNESS: f32 = 90.0;
+
+/// Depth slack between the blob and the floor it lies on
+const SHADOW_DEPTH_BIAS: f32 = 48.0;
+
+/// A shadow resolved onto the floor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlobShadow {
+    /// Point on the floor under the object
+    pub center: Vec3,
+    pub radius: f32,
+    /// 1.0 on the floor, 0.0 at `SHADOW_MAX_HEIGHT`
+    pub strength: f32,
+}
+
+/// The shadow of an object of `radius` at `position` (its base), if there is
+/// floor close enough below it
+pub fn blob_shadow(level: &Level, position: Vec3, radius: f32) -> Option<BlobShadow> {
+    // Start a little above the base so objects standing on the floor find it
+    let origin = position + Vec3::new(0.0, 16.0, 0.0);
+    let hit = raycast_solid(level, origin, Vec3::new(0.0, -1.0, 0.0), SHADOW_MAX_HEIGHT + 16.0)?;
+    if hit.face != HitFace::Floor {
+        return None;
+    }
+    let height = (position.y - hit.point.y).max(0.0);
+    let strength = 1.0 - height / SHADOW_MAX_HEIGHT;
+    (strength > 0.0).then_some(BlobShadow { center: hit.point, radius: radius * (0.5 + 0.5 * strength), strength })
+}
+
+/// Darken the ellipse the shadow's circle projects to
+pub fn draw_blob_shadow(fb: &mut Framebuffer, camera: &Camera, shadow: &BlobShadow) {
+    let depth = (shadow.center - camera.position).dot(camera.basis_z);
+    if depth <= shadow.radius {
+        return;
+    }
+    let project = |p: Vec3| world_to_screen(p, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
+    let (Some(c), Some(ex), Some(ez)) = (
+        project(shadow.center),
+        project(shadow.center + Vec3::new(shadow.radius, 0.0, 0.0)),
+        project(shadow.center + Vec3::new(0.0, 0.0, shadow.radius)),
+    ) else {
+        return;
+    };
+
+    // Screen-space axes of the ellipse; a pixel is inside when its
+    // coordinates in that basis fall within the unit circle
+    let (ax, ay) = (ex.0 - c.0, ex.1 - c.1);
+    let (bx, by) = (ez.0 - c.0, ez.1 - c.1);
+    let det = ax * by - ay * bx;
+    if det.abs() < 0.5 {
+        return; // Seen edge-on
+    }
+    let reach_x = ax.abs() + bx.abs();
+    let reach_y = ay.abs() + by.abs();
+    let x0 = (c.0 - reach_x).max(0.0) as usize;
+    let y0 = (c.1 - reach_y).max(0.0) as usize;
+    let x1 = ((c.0 + reach_x).ceil().max(0.0) as usize).min(fb.width);
+    let y1 = ((c.1 + reach_y).ceil().max(0.0) as usize).min(fb.height);
+
+    let dark = (SHADOW_DARKNESS * shadow.strength) as u8;
+    let color = Color::new(dark, dark, dark);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (dx, dy) = (x as f32 + 0.5 - c.0, y as f32 + 0.5 - c.1);
+            let u = (dx * by - dy * bx) / det;
+            let v = (ax * dy - ay * dx) / det;
+            if u * u + v * v > 1.0 || fb.zbuffer[y * fb.width + x] < depth - SHADOW_DEPTH_BIAS {
+                continue;
+            }
+            fb.set_pixel_blended(x, y, color, BlendMode::Subtract);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{LevelBuilder, RoomBuilder};
+
+    #[test]
+    fn test_shadow_fades_with_height() {
+        // An open floor at height 0
+        let level = LevelBuilder::new().room(RoomBuilder::new(1, 1).floor(0, 0, 0.0)).build();
+        let center = level.rooms[0].world_bounds().center();
+        let at = |height: f32| blob_shadow(&level, Vec3::new(center.x, height, center.z), 128.0);
+
+        let on_floor = at(0.0).unwrap();
+        assert!(on_floor.center.y.abs() < 0.01);
+        assert!(on_floor.strength > 0.99 && on_floor.radius > 127.0);
+        assert!(at(SHADOW_MAX_HEIGHT / 2.0).is_some_and(|s| (s.strength - 0.5).abs() < 0.01 && s.radius < 100.0));
+        assert!(at(SHADOW_MAX_HEIGHT * 2.0).is_none());
+    }
+}
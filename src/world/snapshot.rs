@@ -0,0 +1,184 @@
+//! Headless level rendering and golden-image snapshots
+//!
+//! Renders levels to RGBA buffers without a window or macroquad context, so
+//! rasterizer and room meshing changes can be covered by snapshot tests:
+//! - `render_level` / `render_level_to_buffer` draw a level from a camera
+//! - `compare_images` diffs two RGBA buffers with a per-channel tolerance
+//! - `check_golden` compares against `tests/golden/<name>.png`
+//!
+//! A missing golden fails the check. Set `BONNIE_UPDATE_GOLDEN=1` to write
+//! goldens, after adding a snapshot or an intentional rendering change.
+
+use std::path::{Path, PathBuf};
+use crate::rasterizer::{render_mesh, Camera, Color, Framebuffer, RasterSettings, Texture};
+use super::{Frustum, Level, TextureRef};
+
+/// Directory holding golden images
+pub const GOLDEN_DIR: &str = "tests/golden";
+
+/// Environment variable that forces goldens to be rewritten
+pub const UPDATE_GOLDEN_ENV: &str = "BONNIE_UPDATE_GOLDEN";
+
+/// Render every room of a level into an existing framebuffer
+/// (rooms outside the camera frustum are skipped)
+pub fn render_level<F>(
+    fb: &mut Framebuffer,
+    level: &Level,
+    camera: &Camera,
+    textures: &[Texture],
+    settings: &RasterSettings,
+    resolve_texture: F,
+) where
+    F: Fn(&TextureRef) -> Option<usize>,
+{
+    let frustum = Frustum::new(camera, fb.width, fb.height);
+    for room in level.rooms.iter().filter(|r| frustum.contains_room(r)) {
+        let (vertices, faces) = room.to_render_data_with_textures(&resolve_texture);
+        render_mesh(fb, &vertices, &faces, textures, camera, settings);
+    }
+}
+
+/// Render a level untextured at the settings' resolution, returning RGBA pixels
+/// (`settings.resolution()` gives the buffer size)
+pub fn render_level_to_buffer(level: &Level, camera: &Camera, settings: &RasterSettings) -> Vec<u8> {
+    let (w, h) = settings.resolution();
+    let mut fb = Framebuffer::new(w, h);
+    fb.clear(Color::new(0, 0, 0));
+    render_level(&mut fb, level, camera, &[], settings, |_| None);
+    fb.pixels
+}
+
+/// Result of comparing two images
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Pixels with any channel outside the tolerance
+    pub mismatched: usize,
+    /// Largest per-channel difference
+    pub max_delta: u8,
+}
+
+impl ImageDiff {
+    pub fn is_match(&self) -> bool {
+        self.mismatched == 0
+    }
+}
+
+/// Compare two RGBA buffers of the same size
+pub fn compare_images(actual: &[u8], expected: &[u8], tolerance: u8) -> ImageDiff {
+    let mut diff = ImageDiff { mismatched: 0, max_delta: 0 };
+    for (a, e) in actual.chunks_exact(4).zip(expected.chunks_exact(4)) {
+        let delta = a.iter().zip(e).map(|(a, e)| a.abs_diff(*e)).max().unwrap_or(0);
+        diff.max_delta = diff.max_delta.max(delta);
+        if delta > tolerance {
+            diff.mismatched += 1;
+        }
+    }
+    // Size mismatches count every missing pixel
+    let (a_len, e_len) = (actual.len() / 4, expected.len() / 4);
+    diff.mismatched += a_len.abs_diff(e_len);
+    diff
+}
+
+/// Write RGBA pixels as a PNG
+pub fn save_png(path: &Path, pixels: &[u8], width: usize, height: usize) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let img = image::RgbaImage::from_raw(width as u32, height as u32, pixels.to_vec())
+        .ok_or_else(|| format!("Buffer does not match {}x{}", width, height))?;
+    img.save(path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Read a PNG as RGBA pixels
+pub fn load_png(path: &Path) -> Result<(Vec<u8>, usize, usize), String> {
+    let img = image::open(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .to_rgba8();
+    let (w, h) = img.dimensions();
+    Ok((img.into_raw(), w as usize, h as usize))
+}
+
+/// Path of a named golden image
+pub fn golden_path(name: &str) -> PathBuf {
+    Path::new(GOLDEN_DIR).join(format!("{}.png", name))
+}
+
+/// Compare a render against its golden image. On mismatch the render is
+/// written next to the golden as `<name>.actual.png` for inspection; with
+/// `BONNIE_UPDATE_GOLDEN` set the golden is (re)written instead.
+pub fn check_golden(name: &str, pixels: &[u8], width: usize, height: usize, tolerance: u8) -> Result<(), String> {
+    let path = golden_path(name);
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        return save_png(&path, pixels, width, height);
+    }
+    if !path.exists() {
+        return Err(format!(
+            "Missing golden {}, run with {}=1 to write it",
+            path.display(),
+            UPDATE_GOLDEN_ENV
+        ));
+    }
+
+    let (expected, ew, eh) = load_png(&path)?;
+    let diff = compare_images(pixels, &expected, tolerance);
+    if (ew, eh) == (width, height) && diff.is_match() {
+        return Ok(());
+    }
+
+    let actual_path = Path::new(GOLDEN_DIR).join(format!("{}.actual.png", name));
+    save_png(&actual_path, pixels, width, height)?;
+    Err(format!(
+        "{} differs from golden ({}x{} vs {}x{}, {} pixels off, max delta {}), see {}",
+        name,
+        width,
+        height,
+        ew,
+        eh,
+        diff.mismatched,
+        diff.max_delta,
+        actual_path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::{ShadingMode, Vec3};
+    use crate::world::create_stress_level;
+
+    fn snapshot_camera() -> Camera {
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(1024.0, 1536.0, 1024.0);
+        camera.rotation_x = 0.3;
+        camera.rotation_y = 0.78;
+        camera.update_basis();
+        camera
+    }
+
+    #[test]
+    fn test_compare_images() {
+        let a = [10, 20, 30, 255, 0, 0, 0, 255];
+        let b = [12, 20, 30, 255, 0, 0, 90, 255];
+        let diff = compare_images(&a, &b, 2);
+        assert_eq!(diff, ImageDiff { mismatched: 1, max_delta: 90 });
+        assert!(compare_images(&a, &a, 0).is_match());
+        assert_eq!(compare_images(&a, &a[..4], 0).mismatched, 1);
+    }
+
+    #[test]
+    fn test_headless_render_draws_geometry() {
+        let settings = RasterSettings { shading: ShadingMode::Flat, ..RasterSettings::default() };
+        let pixels = render_level_to_buffer(&create_stress_level(4), &snapshot_camera(), &settings);
+        let (w, h) = settings.resolution();
+        assert_eq!(pixels.len(), w * h * 4);
+        assert!(pixels.chunks_exact(4).any(|p| p[..3] != [0, 0, 0]));
+    }
+
+    #[test]
+    fn test_stress_level_snapshot() {
+        let settings = RasterSettings { shading: ShadingMode::Gouraud, ..RasterSettings::default() };
+        let pixels = render_level_to_buffer(&create_stress_level(4), &snapshot_camera(), &settings);
+        let (w, h) = settings.resolution();
+        check_golden("stress_level_gouraud", &pixels, w, h, 2).unwrap();
+    }
+}
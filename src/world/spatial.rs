@@ -0,0 +1,317 @@
+//! Spatial index for large levels
+//!
+//! Rooms are bucketed on a coarse X/Z grid so queries only look at rooms near
+//! the area of interest instead of every room in the level:
+//! - `SpatialIndex::raycast` walks the buckets along the ray
+//! - `rooms_at` / `rooms_in_aabb` are the broad phase for point and collision queries
+//! - `Frustum` culls rooms outside the camera view before they are meshed
+//!
+//! Inside a room the sector grid already is a uniform grid, so there is no
+//! second level. The index is derived data: `refresh` it after edits (cheap
+//! when no room moved or changed shape).
+
+use crate::rasterizer::{Camera, Vec3};
+use super::raycast::walk_grid;
+use super::{raycast_room, Aabb, Hit, Level, Room};
+
+/// Width of a bucket in sectors of the level being indexed
+pub const BUCKET_SECTORS: f32 = 8.0;
+
+/// World-space bounds of a room's geometry (None for rooms without geometry)
+pub fn room_world_bounds(room: &Room) -> Option<Aabb> {
+    (room.bounds.min.x <= room.bounds.max.x).then(|| room.world_bounds())
+}
+
+/// World-space X/Z rectangle covered by a room's sector grid: (min_x, min_z, max_x, max_z)
+fn room_footprint(room: &Room) -> [f32; 4] {
+    [
+        room.position.x,
+        room.position.z,
+        room.position.x + room.width as f32 * room.sector_size,
+        room.position.z + room.depth as f32 * room.sector_size,
+    ]
+}
+
+/// Rooms bucketed by their sector grid footprint
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    /// Per room, as of the last build
+    footprints: Vec<[f32; 4]>,
+    bounds: Vec<Option<Aabb>>,
+    /// World X/Z of the bucket grid corner
+    origin: (f32, f32),
+    /// Bucket width in world units (`BUCKET_SECTORS` of the level's sectors)
+    bucket_size: f32,
+    cols: usize,
+    rows: usize,
+    /// Room indices per bucket (row-major), in ascending order
+    buckets: Vec<Vec<usize>>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build(level: &Level) -> Self {
+        let footprints: Vec<[f32; 4]> = level.rooms.iter().map(room_footprint).collect();
+        let bounds = level.rooms.iter().map(room_world_bounds).collect();
+        let bucket_size = level.sector_size * BUCKET_SECTORS;
+        let mut index = Self { footprints, bounds, bucket_size, ..Self::default() };
+        if index.footprints.is_empty() {
+            return index;
+        }
+
+        let mut extent = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+        for f in &index.footprints {
+            extent = [extent[0].min(f[0]), extent[1].min(f[1]), extent[2].max(f[2]), extent[3].max(f[3])];
+        }
+        index.origin = (extent[0], extent[1]);
+        index.cols = ((extent[2] - extent[0]) / bucket_size).floor() as usize + 1;
+        index.rows = ((extent[3] - extent[1]) / bucket_size).floor() as usize + 1;
+        index.buckets = vec![Vec::new(); index.cols * index.rows];
+
+        for (room_idx, f) in index.footprints.iter().enumerate() {
+            let (c0, r0) = index.cell(f[0], f[1]);
+            let (c1, r1) = index.cell(f[2], f[3]);
+            for row in r0..=r1 {
+                for col in c0..=c1 {
+                    index.buckets[row * index.cols + col].push(room_idx);
+                }
+            }
+        }
+        index
+    }
+
+    /// Whether rooms were added, removed, moved or reshaped since the last build
+    pub fn is_stale(&self, level: &Level) -> bool {
+        self.footprints.len() != level.rooms.len()
+            || level.rooms.iter().enumerate().any(|(i, room)| {
+                self.footprints[i] != room_footprint(room) || self.bounds[i] != room_world_bounds(room)
+            })
+    }
+
+    /// Rebuild if stale; returns true if it was rebuilt
+    pub fn refresh(&mut self, level: &Level) -> bool {
+        let stale = self.is_stale(level);
+        if stale {
+            *self = Self::build(level);
+        }
+        stale
+    }
+
+    /// Bucket containing a world X/Z position (clamped to the grid)
+    fn cell(&self, x: f32, z: f32) -> (usize, usize) {
+        let col = ((x - self.origin.0) / self.bucket_size).floor().max(0.0) as usize;
+        let row = ((z - self.origin.1) / self.bucket_size).floor().max(0.0) as usize;
+        (col.min(self.cols.saturating_sub(1)), row.min(self.rows.saturating_sub(1)))
+    }
+
+    /// Rooms whose sector grid covers a world X/Z position, in ascending order
+    pub fn rooms_at(&self, x: f32, z: f32) -> impl Iterator<Item = usize> + '_ {
+        let bucket: &[usize] = if self.buckets.is_empty() {
+            &[]
+        } else {
+            let (col, row) = self.cell(x, z);
+            &self.buckets[row * self.cols + col]
+        };
+        bucket.iter().copied().filter(move |&i| {
+            let f = self.footprints[i];
+            x >= f[0] && z >= f[1] && x < f[2] && z < f[3]
+        })
+    }
+
+    /// Same result as `Level::find_room_at`, without visiting every room
+    pub fn find_room_at(&self, level: &Level, point: Vec3) -> Option<usize> {
+        self.rooms_at(point.x, point.z)
+            .find(|&i| level.rooms.get(i).is_some_and(|r| r.contains_point(point)))
+    }
+
+    /// Rooms whose geometry bounds overlap a box, in ascending order
+    pub fn rooms_in_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        if self.buckets.is_empty() {
+            return Vec::new();
+        }
+        let (c0, r0) = self.cell(aabb.min.x, aabb.min.z);
+        let (c1, r1) = self.cell(aabb.max.x, aabb.max.z);
+        let mut rooms: Vec<usize> = (r0..=r1)
+            .flat_map(|row| (c0..=c1).map(move |col| row * self.cols + col))
+            .flat_map(|b| self.buckets[b].iter().copied())
+            .filter(|&i| self.bounds[i].is_some_and(|b| b.intersects(aabb)))
+            .collect();
+        rooms.sort_unstable();
+        rooms.dedup();
+        rooms
+    }
+
+    /// `raycast_max`, visiting only the rooms in buckets along the ray.
+    /// The index must be up to date with `level`.
+    pub fn raycast(&self, level: &Level, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<Hit> {
+        if dir.len() < 1e-6 || self.buckets.is_empty() {
+            return None;
+        }
+        let dir = dir.normalize();
+        let start = (origin.x - self.origin.0, origin.z - self.origin.1);
+        let mut tested = vec![false; self.footprints.len()];
+        let mut best: Option<Hit> = None;
+
+        walk_grid(start, dir, self.bucket_size, (self.cols, self.rows), max_distance, |col, row, cell_exit| {
+            for &room_idx in &self.buckets[row * self.cols + col] {
+                if std::mem::replace(&mut tested[room_idx], true) {
+                    continue;
+                }
+                let limit = best.map_or(max_distance, |h| h.distance);
+                let Some(bounds) = self.bounds[room_idx] else { continue };
+                if bounds.ray_intersect(origin, dir).is_none_or(|t| t > limit) {
+                    continue;
+                }
+                if let Some(hit) = raycast_room(&level.rooms[room_idx], room_idx, origin, dir, limit) {
+                    best = Some(hit);
+                }
+            }
+            // Rooms further along can't be closer than a hit inside this bucket
+            best.is_some_and(|h| h.distance <= cell_exit)
+        });
+        best
+    }
+}
+
+/// View volume of a camera, for culling whole rooms
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    /// Planes as (point, inward normal): near, left, right, top, bottom
+    planes: [(Vec3, Vec3); 5],
+}
+
+impl Frustum {
+    /// Frustum matching the rasterizer's projection for a framebuffer size
+    pub fn new(camera: &Camera, fb_width: usize, fb_height: usize) -> Self {
+        // Same projection constants as `Camera::world_to_screen`
+        const SCALE: f32 = 0.75;
+        let ud = 5.0;
+        let us = ud - 1.0;
+        let vs = (fb_width.min(fb_height) as f32 / 2.0) * SCALE;
+        // Half-width/height of the view per unit of depth, measured from the
+        // projection center which sits `ud` behind the camera
+        let kx = fb_width as f32 / (2.0 * us * vs);
+        let ky = fb_height as f32 / (2.0 * us * vs);
+        let apex = camera.position - camera.basis_z.scale(ud);
+        let (x, y, z) = (camera.basis_x, camera.basis_y, camera.basis_z);
+
+        Self {
+            planes: [
+                (camera.position, z),
+                (apex, z.scale(kx) + x),
+                (apex, z.scale(kx) - x),
+                (apex, z.scale(ky) + y),
+                (apex, z.scale(ky) - y),
+            ],
+        }
+    }
+
+    /// Whether any part of a box may be visible (conservative)
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|&(point, normal)| {
+            // Corner furthest along the normal
+            let corner = Vec3::new(
+                if normal.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if normal.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if normal.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            (corner - point).dot(normal) >= 0.0
+        })
+    }
+
+    /// Whether a room may be visible (rooms without geometry never are)
+    pub fn contains_room(&self, room: &Room) -> bool {
+        room_world_bounds(room).is_some_and(|b| self.intersects_aabb(&b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{raycast_max, LevelBuilder, RoomBuilder, SECTOR_SIZE};
+
+    /// A row of `n` 2x2 rooms along X, each with a floor and ceiling
+    fn room_row(n: usize) -> Level {
+        (0..n)
+            .fold(LevelBuilder::new(), |level, i| {
+                let x = i as f32 * 4.0 * SECTOR_SIZE;
+                level.room(RoomBuilder::new(2, 2).at(Vec3::new(x, 0.0, 0.0)).fill(0.0, 1024.0))
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_point_queries_match_linear_search() {
+        let level = room_row(20);
+        let index = SpatialIndex::build(&level);
+        for x in (0..80).map(|i| i as f32 * 1000.0 + 10.0) {
+            let point = Vec3::new(x, 512.0, 1000.0);
+            assert_eq!(index.find_room_at(&level, point), level.find_room_at(point));
+        }
+        assert_eq!(index.rooms_at(4.0 * SECTOR_SIZE + 1.0, 10.0).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(index.rooms_at(-100.0, 10.0).count(), 0);
+    }
+
+    #[test]
+    fn test_buckets_follow_level_sector_size() {
+        let mut level = room_row(20);
+        level.set_sector_size(256.0);
+        level.apply_metrics();
+        let index = SpatialIndex::build(&level);
+        assert_eq!(index.bucket_size, 256.0 * BUCKET_SECTORS);
+        // 20 rooms, 4 sectors apart: 10 buckets of 8 sectors
+        assert_eq!((index.cols, index.rows), (10, 1));
+        for x in (0..80).map(|i| i as f32 * 250.0 + 10.0) {
+            let point = Vec3::new(x, 512.0, 250.0);
+            assert_eq!(index.find_room_at(&level, point), level.find_room_at(point));
+        }
+    }
+
+    #[test]
+    fn test_rooms_in_aabb() {
+        let level = room_row(20);
+        let index = SpatialIndex::build(&level);
+        let query = Aabb::new(Vec3::new(1500.0, 0.0, 0.0), Vec3::new(4200.0, 100.0, 100.0));
+        assert_eq!(index.rooms_in_aabb(&query), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_raycast_matches_linear_search() {
+        let level = room_row(20);
+        let index = SpatialIndex::build(&level);
+        let origin = Vec3::new(100.0, 512.0, 1000.0);
+        for dir in [Vec3::new(1.0, -0.02, 0.0), Vec3::new(1.0, 0.02, 0.1), Vec3::new(-1.0, 0.0, 0.0)] {
+            assert_eq!(index.raycast(&level, origin, dir, f32::MAX), raycast_max(&level, origin, dir, f32::MAX));
+        }
+        // Lands on the floor of the seventh room
+        let hit = index.raycast(&level, origin, Vec3::new(1.0, -0.02, 0.0), f32::MAX).unwrap();
+        assert_eq!(hit.room, 6);
+    }
+
+    #[test]
+    fn test_refresh_after_edit() {
+        let mut level = room_row(2);
+        let mut index = SpatialIndex::build(&level);
+        assert!(!index.refresh(&level));
+        level.rooms[1].position.x += SECTOR_SIZE;
+        assert!(index.refresh(&level));
+        assert_eq!(index.find_room_at(&level, Vec3::new(4.5 * SECTOR_SIZE, 10.0, 10.0)), None);
+    }
+
+    #[test]
+    fn test_frustum_culls_rooms_behind_and_beside() {
+        let level = room_row(3);
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(1024.0, 512.0, -3000.0);
+        camera.look_at(Vec3::new(1024.0, 512.0, 0.0));
+        let frustum = Frustum::new(&camera, 320, 240);
+        assert!(frustum.contains_room(&level.rooms[0]));
+        assert!(!frustum.contains_room(&level.rooms[2]));
+
+        camera.look_at(Vec3::new(1024.0, 512.0, -6000.0));
+        assert!(!Frustum::new(&camera, 320, 240).contains_room(&level.rooms[0]));
+    }
+}
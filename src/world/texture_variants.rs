@@ -0,0 +1,95 @@
+//! Texture variants - weighted random texture fills
+//!
+//! A set of related textures (e.g. floor_dirt_0..3) with weights, painted
+//! across a selection to break up repetition:
+//! - each face's pick depends only on its room, cell, face and the seed, so
+//!   a fill is reproducible and doesn't depend on selection order
+//! - the set and seed are saved with the level; reshuffling bumps the seed
+
+use serde::{Deserialize, Serialize};
+use super::{Direction, TextureRef};
+
+/// A texture and how often it's picked relative to the others
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeightedTexture {
+    pub texture: TextureRef,
+    pub weight: u32,
+}
+
+/// Textures to pick from and the seed of the last fill
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextureVariants {
+    pub textures: Vec<WeightedTexture>,
+    pub seed: u32,
+}
+
+/// Which face of a sector a pick is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantSlot {
+    Floor,
+    Ceiling,
+    Wall(Direction, usize),
+}
+
+impl TextureVariants {
+    /// Add a texture, or give it one more weight if it's already in the set
+    pub fn add(&mut self, texture: TextureRef) {
+        match self.textures.iter_mut().find(|t| t.texture == texture) {
+            Some(t) => t.weight += 1,
+            None => self.textures.push(WeightedTexture { texture, weight: 1 }),
+        }
+    }
+
+    /// Texture for a face, None when the set is empty
+    pub fn pick(&self, room: usize, x: usize, z: usize, slot: VariantSlot) -> Option<&TextureRef> {
+        let total: u32 = self.textures.iter().map(|t| t.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let slot = match slot {
+            VariantSlot::Floor => 0,
+            VariantSlot::Ceiling => 1,
+            VariantSlot::Wall(dir, i) => 2 + dir as u32 * 64 + i as u32,
+        };
+        let mut h = self.seed ^ (room as u32).wrapping_mul(0x27D4_EB2F);
+        for v in [x as u32, z as u32, slot] {
+            h = (h ^ v).wrapping_mul(0x9E37_79B1);
+            h ^= h >> 16;
+        }
+        let mut roll = h % total;
+        self.textures.iter().find(|t| {
+            if roll < t.weight {
+                return true;
+            }
+            roll -= t.weight;
+            false
+        }).map(|t| &t.texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_picks_follow_weights_and_seed() {
+        let mut variants = TextureVariants::default();
+        assert!(variants.pick(0, 0, 0, VariantSlot::Floor).is_none());
+        let (a, b) = (TextureRef::new("pack", "dirt_0"), TextureRef::new("pack", "dirt_1"));
+        variants.add(a.clone());
+        variants.add(b.clone());
+        variants.add(b.clone());
+        assert_eq!(variants.textures[1].weight, 2);
+
+        let picks = |v: &TextureVariants| -> Vec<TextureRef> {
+            (0..30).map(|x| v.pick(0, x, 0, VariantSlot::Floor).unwrap().clone()).collect()
+        };
+        let first = picks(&variants);
+        assert_eq!(first, picks(&variants));
+        let bs = first.iter().filter(|t| **t == b).count();
+        assert!(bs > first.len() / 2 && bs < first.len());
+
+        variants.seed += 1;
+        assert_ne!(first, picks(&variants));
+    }
+}
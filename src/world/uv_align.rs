@@ -0,0 +1,133 @@
+//! Texture alignment across neighbouring faces
+//!
+//! By default every face maps its texture once, so patterns restart at each
+//! sector and stretch with the wall height. Aligning gives a group of faces
+//! explicit UVs at one texture repeat per sector size, so tiled textures
+//! flow across them:
+//! - walls: U runs continuously along chains of edges (left to right, seen
+//!   from inside the room, around corners too); V follows world height from
+//!   the top of the highest selected wall
+//! - floors and ceilings: UVs follow the grid, measured from the selection's
+//!   north-west cell
+
+use std::collections::HashMap;
+use crate::rasterizer::Vec2;
+use super::{Direction, Room};
+
+/// Grid corners (left, right) of the wall on edge `dir` of cell (x, z), seen from inside
+fn edge_corners(x: usize, z: usize, dir: Direction) -> ((usize, usize), (usize, usize)) {
+    match dir {
+        Direction::North => ((x, z), (x + 1, z)),
+        Direction::East => ((x + 1, z), (x + 1, z + 1)),
+        Direction::South => ((x + 1, z + 1), (x, z + 1)),
+        Direction::West => ((x, z + 1), (x, z)),
+    }
+}
+
+impl Room {
+    /// Give walls (cell x, cell z, edge, index in the edge's stack) continuous
+    /// UVs. Returns how many walls were aligned.
+    pub fn align_wall_textures(&mut self, walls: &[(usize, usize, Direction, usize)]) -> usize {
+        let walls: Vec<_> = walls
+            .iter()
+            .copied()
+            .filter(|&(x, z, dir, i)| self.get_sector(x, z).is_some_and(|s| i < s.walls(dir).len()))
+            .collect();
+        let mut edges = Vec::new();
+        for &(x, z, dir, _) in &walls {
+            let edge = edge_corners(x, z, dir);
+            if !edges.contains(&edge) {
+                edges.push(edge);
+            }
+        }
+
+        // Walk each chain from an edge nothing leads into; whatever is left is a loop
+        let starts = edges.iter().filter(|e| !edges.iter().any(|o| o.1 == e.0));
+        let mut u_start = HashMap::new();
+        for &start in starts.chain(edges.iter()) {
+            let (mut edge, mut u) = (start, 0.0);
+            while !u_start.contains_key(&edge) {
+                u_start.insert(edge, u);
+                u += 1.0;
+                match edges.iter().find(|e| e.0 == edge.1 && !u_start.contains_key(*e)) {
+                    Some(&next) => edge = next,
+                    None => break,
+                }
+            }
+        }
+
+        let size = self.sector_size;
+        let top = walls
+            .iter()
+            .map(|&(x, z, dir, i)| {
+                let h = self.get_sector(x, z).unwrap().walls(dir)[i].heights;
+                h[2].max(h[3])
+            })
+            .fold(f32::MIN, f32::max);
+        for &(x, z, dir, i) in &walls {
+            let u = u_start[&edge_corners(x, z, dir)];
+            let wall = &mut self.get_sector_mut(x, z).unwrap().walls_mut(dir)[i];
+            let v = |h: f32| (top - h) / size;
+            // [bottom-left, bottom-right, top-right, top-left]
+            let h = wall.heights;
+            wall.uv = Some([
+                Vec2::new(u, v(h[0])),
+                Vec2::new(u + 1.0, v(h[1])),
+                Vec2::new(u + 1.0, v(h[2])),
+                Vec2::new(u, v(h[3])),
+            ]);
+        }
+        walls.len()
+    }
+
+    /// Give the floors (or ceilings) of `cells` UVs that follow the grid.
+    /// Returns how many faces were aligned.
+    pub fn align_horizontal_textures(&mut self, cells: &[(usize, usize)], ceiling: bool) -> usize {
+        let (Some(x0), Some(z0)) = (cells.iter().map(|c| c.0).min(), cells.iter().map(|c| c.1).min()) else {
+            return 0;
+        };
+        let mut count = 0;
+        for &(x, z) in cells {
+            let Some(sector) = self.get_sector_mut(x, z) else { continue };
+            let face = if ceiling { sector.ceiling.as_mut() } else { sector.floor.as_mut() };
+            let Some(face) = face else { continue };
+            let (u, v) = ((x - x0) as f32, (z - z0) as f32);
+            // [NW, NE, SE, SW]
+            face.uv = Some([
+                Vec2::new(u, v),
+                Vec2::new(u + 1.0, v),
+                Vec2::new(u + 1.0, v + 1.0),
+                Vec2::new(u, v + 1.0),
+            ]);
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::RoomBuilder;
+
+    #[test]
+    fn test_walls_flow_around_corners() {
+        // North walls along two cells, turning down the east side
+        let mut room = RoomBuilder::new(2, 2)
+            .floor(0, 0, 0.0)
+            .floor(1, 0, 0.0)
+            .wall(0, 0, Direction::North, 0.0, 1024.0)
+            .wall(1, 0, Direction::North, 0.0, 512.0)
+            .wall(1, 0, Direction::East, 0.0, 1024.0)
+            .build();
+        let walls = [(1, 0, Direction::East, 0), (0, 0, Direction::North, 0), (1, 0, Direction::North, 0)];
+        assert_eq!(room.align_wall_textures(&walls), 3);
+
+        let uv = |dir| room.get_sector(1, 0).unwrap().walls(dir)[0].uv.unwrap();
+        let first = room.get_sector(0, 0).unwrap().walls_north[0].uv.unwrap();
+        assert_eq!((first[0].x, first[1].x), (0.0, 1.0));
+        assert_eq!((uv(Direction::North)[0].x, uv(Direction::East)[0].x), (1.0, 2.0));
+        // The lower wall shows the bottom half of the tile, level with its neighbour
+        assert_eq!((uv(Direction::North)[0].y, uv(Direction::North)[3].y), (1.0, 0.5));
+    }
+}
@@ -0,0 +1,199 @@
+//! Room weather
+//!
+//! Cheap per-room particle effects drawn into the framebuffer after a room's
+//! geometry:
+//! - rain streaks, snowflakes and drifting dust, set in the Room panel
+//! - particles are a pure function of their index and the time, so there is
+//!   no state to simulate, save or replay
+//! - each particle is depth tested against the z-buffer (geometry hides it)
+//!   but doesn't write to it, so weather never hides other weather
+//! - the editor's 3D viewport draws every visible room's weather; play mode
+//!   draws the weather of the room the camera is in
+
+use std::f32::consts::TAU;
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::{BlendMode, Camera, Color, Framebuffer, Vec3, world_to_screen};
+use super::Room;
+
+/// Particles per sector at full density
+const PARTICLES_PER_SECTOR: f32 = 6.0;
+
+/// Most particles drawn for one room
+const MAX_PARTICLES: usize = 600;
+
+/// Particles closer to the camera than this are skipped (they'd cover the screen)
+const NEAR_DEPTH: f32 = 64.0;
+
+/// Kind of weather in a room
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherKind {
+    #[default]
+    None,
+    Rain,
+    Snow,
+    Dust,
+}
+
+impl WeatherKind {
+    pub const ALL: [WeatherKind; 4] = [WeatherKind::None, WeatherKind::Rain, WeatherKind::Snow, WeatherKind::Dust];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeatherKind::None => "None",
+            WeatherKind::Rain => "Rain",
+            WeatherKind::Snow => "Snow",
+            WeatherKind::Dust => "Dust",
+        }
+    }
+
+    /// Fall speed in units per second, particle color and blend mode
+    fn style(&self) -> (f32, Color, BlendMode) {
+        match self {
+            WeatherKind::None => (0.0, Color::BLACK, BlendMode::Opaque),
+            WeatherKind::Rain => (6000.0, Color::new(150, 165, 200), BlendMode::Average),
+            WeatherKind::Snow => (400.0, Color::new(235, 235, 245), BlendMode::Opaque),
+            WeatherKind::Dust => (60.0, Color::new(120, 100, 70), BlendMode::AddQuarter),
+        }
+    }
+}
+
+/// Weather settings of a room
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    /// 0.0 - 1.0, scales the particle count
+    pub density: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self { kind: WeatherKind::None, density: 0.5 }
+    }
+}
+
+impl Weather {
+    pub fn is_active(&self) -> bool {
+        self.kind != WeatherKind::None && self.density > 0.0
+    }
+}
+
+/// A pseudo-random value in 0..1 for particle `i`, channel `salt`
+fn hash01(i: u32, salt: u32) -> f32 {
+    let mut h = i.wrapping_mul(0x9E37_79B9) ^ salt.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h & 0xFFFF) as f32 / 65536.0
+}
+
+/// Draw a room's weather at `time` seconds
+pub fn draw_weather(fb: &mut Framebuffer, room: &Room, camera: &Camera, time: f32) {
+    let weather = room.weather;
+    if !weather.is_active() {
+        return;
+    }
+    let sectors = room.iter_sectors().count() as f32;
+    let count = ((sectors * PARTICLES_PER_SECTOR * weather.density.min(1.0)) as usize).min(MAX_PARTICLES);
+    let (speed, color, mode) = weather.kind.style();
+
+    // Fall from the top of the room (at least two sectors up) to its floor
+    let bounds = room.world_bounds();
+    let bottom = bounds.min.y;
+    let height = (bounds.max.y - bottom).max(room.sector_size * 2.0);
+    let (width, depth) = (room.width as f32 * room.sector_size, room.depth as f32 * room.sector_size);
+
+    for i in 0..count as u32 {
+        let phase = hash01(i, 3);
+        let fall = (phase + time * speed / height).fract();
+        let mut p = Vec3::new(
+            room.position.x + hash01(i, 1) * width,
+            bottom + height * (1.0 - fall),
+            room.position.z + hash01(i, 2) * depth,
+        );
+        match weather.kind {
+            WeatherKind::Rain => {
+                let top = p + Vec3::new(0.0, 160.0, 0.0);
+                draw_streak(fb, camera, p, top, color, mode);
+                continue;
+            }
+            WeatherKind::Snow => {
+                p.x += (time * 1.3 + phase * TAU).sin() * 48.0;
+                p.z += (time * 0.9 + phase * 4.0).cos() * 48.0;
+            }
+            // Dust drifts sideways more than it falls
+            _ => {
+                p.x += (time * 0.3 + phase * TAU).sin() * 256.0;
+                p.z += (time * 0.2 + phase * 3.0).cos() * 256.0;
+            }
+        }
+        draw_particle(fb, camera, p, color, mode);
+    }
+}
+
+/// Screen position and camera depth of a point, if it's in front of the camera
+fn project(fb: &Framebuffer, camera: &Camera, p: Vec3) -> Option<(i32, i32, f32)> {
+    let depth = (p - camera.position).dot(camera.basis_z);
+    if depth < NEAR_DEPTH {
+        return None;
+    }
+    let (x, y) = world_to_screen(p, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height)?;
+    Some((x as i32, y as i32, depth))
+}
+
+/// Whether a point at `depth` is in front of the geometry at pixel (x, y)
+fn visible(fb: &Framebuffer, x: i32, y: i32, depth: f32) -> bool {
+    x >= 0 && y >= 0 && (x as usize) < fb.width && (y as usize) < fb.height
+        && depth < fb.zbuffer[y as usize * fb.width + x as usize]
+}
+
+fn draw_particle(fb: &mut Framebuffer, camera: &Camera, p: Vec3, color: Color, mode: BlendMode) {
+    let Some((x, y, depth)) = project(fb, camera, p) else { return };
+    if visible(fb, x, y, depth) {
+        fb.set_pixel_blended(x as usize, y as usize, color, mode);
+    }
+}
+
+fn draw_streak(fb: &mut Framebuffer, camera: &Camera, bottom: Vec3, top: Vec3, color: Color, mode: BlendMode) {
+    let (Some((x0, y0, d0)), Some((x1, y1, d1))) = (project(fb, camera, bottom), project(fb, camera, top)) else { return };
+    // Depth tested at the streak's middle: streaks are only a few pixels long
+    if visible(fb, (x0 + x1) / 2, (y0 + y1) / 2, (d0 + d1) * 0.5) {
+        fb.draw_line_blended(x0, y0, x1, y1, color, mode);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::TextureRef;
+
+    #[test]
+    fn test_rain_draws_in_front_of_the_camera_only() {
+        let mut room = Room::new(0, Vec3::ZERO, 4, 4);
+        for x in 0..4 {
+            for z in 0..4 {
+                room.set_floor(x, z, 0.0, TextureRef::none());
+            }
+        }
+        room.recalculate_bounds();
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(2048.0, 1024.0, -4096.0);
+        camera.look_at(Vec3::new(2048.0, 1024.0, 2048.0));
+
+        let mut fb = Framebuffer::new(160, 120);
+        fb.clear(Color::BLACK);
+        draw_weather(&mut fb, &room, &camera, 1.0);
+        assert!(fb.pixels.chunks(4).all(|p| p[..3] == [0, 0, 0]), "no weather while it's off");
+
+        room.weather = Weather { kind: WeatherKind::Rain, density: 1.0 };
+        draw_weather(&mut fb, &room, &camera, 1.0);
+        assert!(fb.pixels.chunks(4).any(|p| p[2] > 0));
+
+        // Looking away: nothing to draw
+        let mut fb_away = Framebuffer::new(160, 120);
+        fb_away.clear(Color::BLACK);
+        camera.look_at(Vec3::new(2048.0, 1024.0, -8192.0));
+        draw_weather(&mut fb_away, &room, &camera, 1.0);
+        assert!(fb_away.pixels.chunks(4).all(|p| p[..3] == [0, 0, 0]));
+    }
+}